@@ -2,10 +2,18 @@ use super::{cursor, ordering, pager::Pager};
 use clap::ArgMatches;
 use error::Result;
 use fmt::{Formatter, Simple};
-use stellar_client::{endpoint::account, sync, sync::Client};
+use stellar_client::{endpoint::account, resources::AccountId, sync, sync::Client};
 
-pub fn data(client: &Client, matches: &ArgMatches) -> Result<()> {
+/// Parses the account id that every account command takes.
+pub fn id_from_arg(matches: &ArgMatches) -> Result<AccountId> {
     let id = matches.value_of("ID").expect("ID is required");
+    Ok(id
+        .parse()
+        .map_err(|_| format!("{} is not a valid account id", id))?)
+}
+
+pub fn data(client: &Client, matches: &ArgMatches) -> Result<()> {
+    let id = id_from_arg(matches)?;
     let key = matches.value_of("key").expect("Key is required");
     let endpoint = account::Data::new(id, key);
     let account = client.request(endpoint)?;
@@ -16,7 +24,7 @@ pub fn data(client: &Client, matches: &ArgMatches) -> Result<()> {
 }
 
 pub fn details(client: &Client, matches: &ArgMatches) -> Result<()> {
-    let id = matches.value_of("ID").expect("ID is required");
+    let id = id_from_arg(matches)?;
     let endpoint = account::Details::new(id);
     let account = client.request(endpoint)?;
 
@@ -28,7 +36,7 @@ pub fn details(client: &Client, matches: &ArgMatches) -> Result<()> {
 pub fn transactions(client: &Client, matches: &ArgMatches) -> Result<()> {
     let pager = Pager::from_arg(&matches);
 
-    let id = matches.value_of("ID").expect("ID is required");
+    let id = id_from_arg(matches)?;
     let endpoint = account::Transactions::new(id);
     let endpoint = pager.assign(endpoint);
     let endpoint = cursor::assign_from_arg(matches, endpoint);
@@ -49,7 +57,7 @@ pub fn transactions(client: &Client, matches: &ArgMatches) -> Result<()> {
 pub fn trades(client: &Client, matches: &ArgMatches) -> Result<()> {
     let pager = Pager::from_arg(&matches);
 
-    let id = matches.value_of("ID").expect("ID is required");
+    let id = id_from_arg(matches)?;
     let endpoint = account::Trades::new(id);
     let endpoint = pager.assign(endpoint);
     let endpoint = cursor::assign_from_arg(matches, endpoint);
@@ -70,7 +78,7 @@ pub fn trades(client: &Client, matches: &ArgMatches) -> Result<()> {
 pub fn effects(client: &Client, matches: &ArgMatches) -> Result<()> {
     let pager = Pager::from_arg(&matches);
 
-    let id = matches.value_of("ID").expect("ID is required");
+    let id = id_from_arg(matches)?;
     let endpoint = account::Effects::new(id);
     let endpoint = pager.assign(endpoint);
     let endpoint = cursor::assign_from_arg(matches, endpoint);
@@ -91,7 +99,7 @@ pub fn effects(client: &Client, matches: &ArgMatches) -> Result<()> {
 pub fn offers(client: &Client, matches: &ArgMatches) -> Result<()> {
     let pager = Pager::from_arg(&matches);
 
-    let id = matches.value_of("ID").expect("ID is required");
+    let id = id_from_arg(matches)?;
     let endpoint = account::Offers::new(id);
     let endpoint = pager.assign(endpoint);
     let endpoint = cursor::assign_from_arg(matches, endpoint);
@@ -112,7 +120,7 @@ pub fn offers(client: &Client, matches: &ArgMatches) -> Result<()> {
 pub fn operations(client: &Client, matches: &ArgMatches) -> Result<()> {
     let pager = Pager::from_arg(&matches);
 
-    let id = matches.value_of("ID").expect("ID is required");
+    let id = id_from_arg(matches)?;
     let endpoint = account::Operations::new(id);
     let endpoint = pager.assign(endpoint);
     let endpoint = cursor::assign_from_arg(matches, endpoint);
@@ -132,7 +140,7 @@ pub fn operations(client: &Client, matches: &ArgMatches) -> Result<()> {
 pub fn payments(client: &Client, matches: &ArgMatches) -> Result<()> {
     let pager = Pager::from_arg(&matches);
 
-    let id = matches.value_of("ID").expect("ID is required");
+    let id = id_from_arg(matches)?;
     let endpoint = account::Payments::new(id);
     let endpoint = pager.assign(endpoint);
     let endpoint = cursor::assign_from_arg(matches, endpoint);
@@ -4,7 +4,7 @@ use error::Result;
 use fmt::{Formatter, Simple};
 use stellar_client::{
     endpoint::payment,
-    resources::{Amount, AssetIdentifier},
+    resources::{AccountId, Amount, AssetIdentifier},
     sync,
     sync::Client,
 };
@@ -15,10 +15,14 @@ pub fn find_path(client: &Client, matches: &ArgMatches) -> Result<()> {
     let endpoint = {
         let destination_account = matches
             .value_of("to")
-            .expect("Destination account is a required field");
+            .expect("Destination account is a required field")
+            .parse::<AccountId>()
+            .map_err(|_| String::from("Destination account must be a valid account id"))?;
         let source_account = matches
             .value_of("from")
-            .expect("Source account is a required field");
+            .expect("Source account is a required field")
+            .parse::<AccountId>()
+            .map_err(|_| String::from("Source account must be a valid account id"))?;
         let destination_amount = matches
             .value_of("amount")
             .expect("Destination amount is a required field")
@@ -20,10 +20,9 @@ impl Render<Asset> for Simple {
 
 impl Render<AssetIdentifier> for Simple {
     fn render(&self, asset_id: &AssetIdentifier) -> Option<String> {
-        let asset_str = if asset_id.is_native() {
-            asset_id.code().to_string()
-        } else {
-            format!("{}-{}", asset_id.code(), asset_id.issuer())
+        let asset_str = match (asset_id.code(), asset_id.issuer()) {
+            (Some(code), Some(issuer)) => format!("{}-{}", code, issuer),
+            _ => "XLM".to_string(),
         };
         Some(asset_str)
     }
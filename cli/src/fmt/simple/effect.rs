@@ -24,10 +24,9 @@ impl Render<Effect> for Simple {
 }
 
 fn render_asset(id: &AssetIdentifier) -> String {
-    if id.is_native() {
-        id.code().to_string()
-    } else {
-        format!("{}-{}", id.code(), id.issuer())
+    match (id.code(), id.issuer()) {
+        (Some(code), Some(issuer)) => format!("{}-{}", code, issuer),
+        _ => "XLM".to_string(),
     }
 }
 
@@ -74,6 +74,8 @@ mod offer;
 mod operation;
 mod orderbook;
 mod payment_path;
+mod payout;
+mod submitted_transaction;
 mod trade;
 mod trade_aggregation;
 mod transaction;
@@ -19,6 +19,9 @@ impl Render<Operation> for Simple {
             Kind::AccountMerge(kind) => self.render(kind),
             Kind::Inflation => None,
             Kind::ManageData(kind) => self.render(kind),
+            Kind::InvokeHostFunction(kind) => self.render(kind),
+            Kind::ExtendFootprintTtl(kind) => self.render(kind),
+            Kind::RestoreFootprint => None,
         };
         if let Some(value) = kind_details {
             append!(buf, "{}", value);
@@ -40,10 +43,16 @@ impl Render<CreateAccount> for Simple {
 impl Render<Payment> for Simple {
     fn render(&self, op: &Payment) -> Option<String> {
         let mut buf = String::new();
-        append!(buf, "To Account:   {}", op.to());
-        append!(buf, "From Account: {}", op.from());
-        append!(buf, "Asset:        {}", self.render(op.asset()).unwrap());
-        append!(buf, "Amount:       {}", op.amount());
+        append!(buf, "To Account:    {}", op.to());
+        if let Some(id) = op.to_muxed_id() {
+            append!(buf, "To Muxed ID:   {}", id);
+        }
+        append!(buf, "From Account:  {}", op.from());
+        if let Some(id) = op.from_muxed_id() {
+            append!(buf, "From Muxed ID: {}", id);
+        }
+        append!(buf, "Asset:         {}", self.render(op.asset()).unwrap());
+        append!(buf, "Amount:        {}", op.amount());
         Some(buf)
     }
 }
@@ -52,7 +61,13 @@ impl Render<PathPayment> for Simple {
     fn render(&self, op: &PathPayment) -> Option<String> {
         let mut buf = String::new();
         append!(buf, "To Account:         {}", op.to());
+        if let Some(id) = op.to_muxed_id() {
+            append!(buf, "To Muxed ID:        {}", id);
+        }
         append!(buf, "From Account:       {}", op.from());
+        if let Some(id) = op.from_muxed_id() {
+            append!(buf, "From Muxed ID:      {}", id);
+        }
         append!(
             buf,
             "Source Asset:       {}",
@@ -167,3 +182,27 @@ impl Render<ManageData> for Simple {
         Some(buf)
     }
 }
+
+impl Render<InvokeHostFunction> for Simple {
+    fn render(&self, op: &InvokeHostFunction) -> Option<String> {
+        let mut buf = String::new();
+        append!(buf, "Function: {}", op.function());
+        if !op.address().is_empty() {
+            append!(buf, "Address:  {}", op.address());
+            append!(buf, "Salt:     {}", op.salt());
+        }
+        append!(buf, "Parameters:");
+        for param in op.parameters() {
+            indent!(buf, self, "{}: {}", param.value_type(), param.value());
+        }
+        Some(buf)
+    }
+}
+
+impl Render<ExtendFootprintTtl> for Simple {
+    fn render(&self, op: &ExtendFootprintTtl) -> Option<String> {
+        let mut buf = String::new();
+        append!(buf, "Extend To: {}", op.extend_to());
+        Some(buf)
+    }
+}
@@ -0,0 +1,29 @@
+use super::Simple;
+use fmt::Render;
+use stellar_client::helpers::payout::{PayoutOutcome, PayoutStatus};
+
+impl Render<PayoutOutcome> for Simple {
+    fn render(&self, outcome: &PayoutOutcome) -> Option<String> {
+        let payout = outcome.payout();
+        let mut buf = String::new();
+        append!(buf, "destination: {}", payout.destination());
+        append!(buf, "asset:       {}", self.render(payout.asset()).unwrap());
+        append!(buf, "amount:      {}", payout.amount());
+        match *outcome.status() {
+            PayoutStatus::Paid { ref hash, ledger } => {
+                append!(buf, "status:      paid");
+                append!(buf, "hash:        {}", hash);
+                append!(buf, "ledger:      {}", ledger);
+            }
+            PayoutStatus::Failed(ref reason) => {
+                append!(buf, "status:      failed");
+                append!(buf, "reason:      {}", reason);
+            }
+            PayoutStatus::Unconfirmed(ref hash) => {
+                append!(buf, "status:      unconfirmed");
+                append!(buf, "hash:        {}", hash);
+            }
+        }
+        Some(buf)
+    }
+}
@@ -0,0 +1,14 @@
+use super::Simple;
+use fmt::Render;
+use stellar_client::resources::SubmittedTransaction;
+
+impl Render<SubmittedTransaction> for Simple {
+    fn render(&self, txn: &SubmittedTransaction) -> Option<String> {
+        let mut buf = String::new();
+        append!(buf, "hash:     {}", txn.hash());
+        append!(buf, "ledger:   {}", txn.ledger());
+        append!(buf, "envelope: {}", txn.envelope_xdr());
+        append!(buf, "result:   {}", txn.result_xdr());
+        Some(buf)
+    }
+}
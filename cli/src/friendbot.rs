@@ -0,0 +1,16 @@
+use super::account;
+use clap::ArgMatches;
+use error::Result;
+use fmt::{Formatter, Simple};
+use stellar_client::sync::Client;
+
+/// Asks friendbot to create and fund the account, then shows the
+/// transaction that did it.
+pub fn fund(client: &Client, matches: &ArgMatches) -> Result<()> {
+    let id = account::id_from_arg(matches)?;
+    let transaction = client.friendbot(id)?;
+
+    Formatter::start_stdout(Simple::new()).render(&transaction);
+
+    Ok(())
+}
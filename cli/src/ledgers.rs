@@ -1,4 +1,4 @@
-use super::{cursor, ordering, pager::Pager};
+use super::{cursor, ordering, pager::Pager, stream};
 use clap::ArgMatches;
 use error::Result;
 use fmt::{Formatter, Simple};
@@ -14,8 +14,13 @@ pub fn all(client: &Client, matches: &ArgMatches) -> Result<()> {
     let endpoint = pager.assign(endpoint);
     let endpoint = cursor::assign_from_arg(matches, endpoint);
     let endpoint = ordering::assign_from_arg(matches, endpoint);
+    let endpoint = stream::assign_from_arg(matches, endpoint);
 
-    let iter = sync::Iter::new(&client, endpoint);
+    let iter: Box<dyn Iterator<Item = _>> = if stream::is_following(matches) {
+        Box::new(client.stream(endpoint)?)
+    } else {
+        Box::new(sync::Iter::new(&client, endpoint))
+    };
 
     let mut res = Ok(());
     let mut fmt = Formatter::start_stdout(Simple::new());
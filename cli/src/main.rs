@@ -7,7 +7,10 @@ extern crate stellar_client;
 use clap::{App, AppSettings, Arg, SubCommand};
 use error::CliError;
 use pager::Pager;
-use stellar_client::{error::Error, sync::Client};
+use stellar_client::{
+    error::Error,
+    sync::{Client, ClientConfig},
+};
 
 mod account;
 mod assets;
@@ -16,6 +19,7 @@ mod effects;
 mod error;
 mod find_path;
 mod fmt;
+mod friendbot;
 mod ledgers;
 mod operations;
 mod orderbook;
@@ -23,8 +27,11 @@ mod ordering;
 mod pager;
 mod payments;
 mod resolution;
+mod stream;
 mod trades;
 mod transactions;
+#[cfg(feature = "soroban")]
+mod xdr;
 
 fn build_app<'a, 'b>() -> App<'a, 'b> {
     macro_rules! listable {
@@ -33,7 +40,13 @@ fn build_app<'a, 'b>() -> App<'a, 'b> {
         };
     }
 
-    App::new("Stellar CLI")
+    macro_rules! streamable {
+        ($e:expr) => {
+            stream::add(listable!($e))
+        };
+    }
+
+    let app = App::new("Stellar CLI")
         .version("0.1")
         .about("Access the stellar horizon API via the command line.")
         .setting(AppSettings::SubcommandRequired)
@@ -57,6 +70,13 @@ fn build_app<'a, 'b>() -> App<'a, 'b> {
                 .conflicts_with_all(&["host", "test-net"])
                 .help("Connects to the public net."),
         )
+        .arg(
+            Arg::with_name("config")
+                .takes_value(true)
+                .long("config")
+                .conflicts_with_all(&["host", "test-net", "pub-net"])
+                .help("Reads the host and client settings from a json client config file."),
+        )
         .subcommand(
             SubCommand::with_name("account")
                 .about("Access information about accounts or related to them")
@@ -161,7 +181,7 @@ fn build_app<'a, 'b>() -> App<'a, 'b> {
                 .about("Access lists of effects")
                 .setting(AppSettings::SubcommandRequired)
                 .subcommand(
-                    listable!(
+                    streamable!(
                         SubCommand::with_name("all")
                             .about("Fetch all effects")
                     )
@@ -172,10 +192,53 @@ fn build_app<'a, 'b>() -> App<'a, 'b> {
                 .about("Access lists of payments")
                 .setting(AppSettings::SubcommandRequired)
                 .subcommand(
-                    listable!(
+                    streamable!(
                         SubCommand::with_name("all")
                             .about("Fetch all payments")
                     )
+                )
+                .subcommand(
+                    SubCommand::with_name("send")
+                        .about("Send a payment")
+                        .arg(
+                            Arg::with_name("DESTINATION")
+                                .required(true)
+                                .help("The account id to pay"),
+                        )
+                        .arg(
+                            Arg::with_name("AMOUNT")
+                                .required(true)
+                                .help("The amount to pay, such as 12.5"),
+                        )
+                        .arg(
+                            Arg::with_name("asset")
+                                .long("asset")
+                                .takes_value(true)
+                                .help("The asset to pay in, as CODE-ISSUER. Defaults to XLM"),
+                        )
+                        .arg(
+                            Arg::with_name("memo")
+                                .long("memo")
+                                .takes_value(true)
+                                .conflicts_with("memo-id")
+                                .help("A text memo to attach to the payment"),
+                        )
+                        .arg(
+                            Arg::with_name("memo-id")
+                                .long("memo-id")
+                                .takes_value(true)
+                                .conflicts_with("memo")
+                                .help("A numeric memo to attach to the payment"),
+                        )
+                        .arg(
+                            Arg::with_name("secret")
+                                .long("secret")
+                                .takes_value(true)
+                                .required(true)
+                                .env("STELLAR_SECRET_SEED")
+                                .hide_env_values(true)
+                                .help("The secret seed of the paying account"),
+                        ),
                 ),
         )
         .subcommand(
@@ -215,7 +278,7 @@ fn build_app<'a, 'b>() -> App<'a, 'b> {
                 .about("Access lists of operations")
                 .setting(AppSettings::SubcommandRequired)
                 .subcommand(
-                    listable!(
+                    streamable!(
                         SubCommand::with_name("all")
                             .about("Fetch all operations")
                     )
@@ -266,7 +329,7 @@ fn build_app<'a, 'b>() -> App<'a, 'b> {
                 .about("Access lists of transactions")
                 .setting(AppSettings::SubcommandRequired)
                 .subcommand(
-                    listable!(
+                    streamable!(
                         SubCommand::with_name("all")
                             .about("Fetch all transactions")
                     )
@@ -344,7 +407,7 @@ fn build_app<'a, 'b>() -> App<'a, 'b> {
                 .about("Access lists of ledgers")
                 .setting(AppSettings::SubcommandRequired)
                 .subcommand(
-                    listable!(
+                    streamable!(
                         SubCommand::with_name("all")
                             .about("Fetch all ledgers")
                     ),
@@ -474,12 +537,61 @@ fn build_app<'a, 'b>() -> App<'a, 'b> {
                         )
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("friendbot")
+                .about("Create and fund an account on the test net")
+                .arg(
+                    Arg::with_name("ID")
+                        .required(true)
+                        .help("The identifier of the account to fund"),
+                ),
+        );
+
+    #[cfg(feature = "soroban")]
+    let app = app.subcommand(
+        SubCommand::with_name("xdr")
+            .about("Decode base64 encoded soroban XDR")
+            .setting(AppSettings::SubcommandRequired)
+            .subcommand(
+                SubCommand::with_name("scval")
+                    .about("Decode a contract value")
+                    .arg(
+                        Arg::with_name("XDR")
+                            .required(true)
+                            .help("The base64 encoded SCVal"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("event")
+                    .about("Decode a diagnostic event from a simulation")
+                    .arg(
+                        Arg::with_name("XDR")
+                            .required(true)
+                            .help("The base64 encoded DiagnosticEvent"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("transaction-data")
+                    .about("Decode the footprint and resources of a soroban transaction")
+                    .arg(
+                        Arg::with_name("XDR")
+                            .required(true)
+                            .help("The base64 encoded SorobanTransactionData"),
+                    ),
+            ),
+    );
+
+    app
 }
 
 fn main() {
     let matches = build_app().get_matches();
 
-    let client = if let Some(host) = matches.value_of("host") {
+    let client = if let Some(path) = matches.value_of("config") {
+        ClientConfig::load(path)
+            .and_then(|config| Client::from_config(&config))
+            .expect("Failed to initialize client from the config")
+    } else if let Some(host) = matches.value_of("host") {
         Client::new(&host).expect("Failed to initialize client")
     } else if matches.is_present("pub-net") {
         Client::horizon().unwrap()
@@ -527,8 +639,10 @@ fn main() {
             _ => return print_help_and_exit(),
         },
         ("find-path", Some(sub_m)) => find_path::find_path(&client, sub_m),
+        ("friendbot", Some(sub_m)) => friendbot::fund(&client, sub_m),
         ("payments", Some(sub_m)) => match sub_m.subcommand() {
             ("all", Some(sub_m)) => payments::all(&client, sub_m),
+            ("send", Some(sub_m)) => payments::send(&client, sub_m),
             _ => return print_help_and_exit(),
         },
         ("trades", Some(sub_m)) => match sub_m.subcommand() {
@@ -544,6 +658,13 @@ fn main() {
             ("effects", Some(sub_m)) => transactions::effects(&client, sub_m),
             _ => return print_help_and_exit(),
         },
+        #[cfg(feature = "soroban")]
+        ("xdr", Some(sub_m)) => match sub_m.subcommand() {
+            ("scval", Some(sub_m)) => xdr::scval(sub_m),
+            ("event", Some(sub_m)) => xdr::event(sub_m),
+            ("transaction-data", Some(sub_m)) => xdr::transaction_data(sub_m),
+            _ => return print_help_and_exit(),
+        },
         _ => return print_help_and_exit(),
     };
 
@@ -41,7 +41,7 @@ impl Pager {
             Pager {
                 size: PageSize::Size(usize::from_str(&size).unwrap_or(10)),
             }
-        } else if arg.is_present("all") {
+        } else if arg.is_present("all") || arg.is_present("follow") {
             Pager {
                 size: PageSize::All,
             }
@@ -141,6 +141,13 @@ mod tests {
         assert_eq!(pager.horizon_page_limit(), 200);
     }
 
+    #[test]
+    fn it_shows_everything_when_following() {
+        let app = ::stream::add(test_app());
+        let pager = Pager::from_arg(&app.get_matches_from(vec!["test", "--follow"]));
+        assert_eq!(pager.size, PageSize::All);
+    }
+
     #[test]
     fn it_can_assign_a_limit() {
         struct Foo {
@@ -1,9 +1,12 @@
-use super::{cursor, ordering, pager::Pager};
+use super::{cursor, ordering, pager::Pager, stream};
 use clap::ArgMatches;
 use error::Result;
 use fmt::{Formatter, Simple};
 use stellar_client::{
     endpoint::payment,
+    helpers::payout::{Payout, PayoutBatch, PayoutStatus},
+    keys::Keypair,
+    resources::{AccountId, Amount, AssetIdentifier, Memo},
     sync::{self, Client},
 };
 
@@ -14,8 +17,13 @@ pub fn all(client: &Client, matches: &ArgMatches) -> Result<()> {
     let endpoint = pager.assign(endpoint);
     let endpoint = cursor::assign_from_arg(matches, endpoint);
     let endpoint = ordering::assign_from_arg(matches, endpoint);
+    let endpoint = stream::assign_from_arg(matches, endpoint);
 
-    let iter = sync::Iter::new(&client, endpoint);
+    let iter: Box<dyn Iterator<Item = _>> = if stream::is_following(matches) {
+        Box::new(client.stream(endpoint)?)
+    } else {
+        Box::new(sync::Iter::new(&client, endpoint))
+    };
 
     let mut res = Ok(());
     let mut fmt = Formatter::start_stdout(Simple::new());
@@ -25,3 +33,51 @@ pub fn all(client: &Client, matches: &ArgMatches) -> Result<()> {
     });
     res
 }
+
+/// Sends a payment from the account of the secret seed and shows where it
+/// ended up. The network is the one the client was set up for.
+pub fn send(client: &Client, matches: &ArgMatches) -> Result<()> {
+    let secret = matches
+        .value_of("secret")
+        .expect("Secret seed is a required field");
+    let keypair = Keypair::from_secret_seed(secret)
+        .map_err(|_| String::from("Secret seed must be a valid secret seed"))?;
+    let destination = matches
+        .value_of("DESTINATION")
+        .expect("Destination is a required field")
+        .parse::<AccountId>()
+        .map_err(|_| String::from("Destination must be a valid account id"))?;
+    let amount = matches
+        .value_of("AMOUNT")
+        .expect("Amount is a required field")
+        .parse::<Amount>()
+        .map_err(|_| String::from("Amount must be a number with at most 7 decimal places"))?;
+    let asset = match matches.value_of("asset") {
+        Some(asset) => asset
+            .parse::<AssetIdentifier>()
+            .map_err(|_| String::from("Asset must be properly formatted"))?,
+        None => AssetIdentifier::native(),
+    };
+    let memo = if let Some(text) = matches.value_of("memo") {
+        Memo::Text(text.to_string())
+    } else if let Some(id) = matches.value_of("memo-id") {
+        Memo::Id(id.parse()?)
+    } else {
+        Memo::None
+    };
+    let network = client.network().ok_or_else(|| {
+        String::from("The network of the host is unknown, set its passphrase in a config")
+    })?;
+
+    let batch = PayoutBatch::new(vec![Payout::new(destination, asset, amount).with_memo(memo)]);
+    let outcomes = batch.submit(client, network, &keypair)?;
+
+    let mut fmt = Formatter::start_stdout(Simple::new());
+    for outcome in &outcomes {
+        fmt.render(outcome);
+    }
+    match outcomes.first().map(|outcome| outcome.status()) {
+        Some(&PayoutStatus::Paid { .. }) => Ok(()),
+        _ => Err(String::from("The payment was not made").into()),
+    }
+}
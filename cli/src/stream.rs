@@ -0,0 +1,84 @@
+use clap::{App, Arg, ArgMatches};
+use stellar_client::endpoint::Cursor;
+
+static ARG_NAME: &'static str = "follow";
+
+/// Appends the follow arg to the app and returns a newly owned app.
+pub fn add<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+    app.arg(
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .short("f")
+            .conflicts_with("page-size")
+            .help("Keep waiting for new records. Starts from now unless a cursor is given."),
+    )
+}
+
+/// Returns whether to keep waiting for new records, which horizon then
+/// streams as they happen rather than being polled for.
+pub fn is_following(arg: &ArgMatches) -> bool {
+    arg.is_present(ARG_NAME)
+}
+
+/// When following, starts from the latest record unless a cursor has
+/// already been given.
+pub fn assign_from_arg<C>(arg: &ArgMatches, cursor: C) -> C
+where
+    C: Cursor,
+{
+    if arg.is_present(ARG_NAME) && cursor.cursor().is_none() {
+        cursor.with_cursor("now")
+    } else {
+        cursor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Foo {
+        cursor: Option<String>,
+    }
+
+    impl Cursor for Foo {
+        fn with_cursor(mut self, cursor: &str) -> Foo {
+            self.cursor = Some(cursor.to_owned());
+            self
+        }
+
+        fn cursor(&self) -> Option<&str> {
+            self.cursor.as_ref().map(|s| &**s)
+        }
+    }
+
+    fn get_matches(args: Vec<&str>) -> ArgMatches {
+        add(App::new("test")).get_matches_from(args)
+    }
+
+    #[test]
+    fn it_starts_from_now_when_following() {
+        let arg_matches = get_matches(vec!["test", "--follow"]);
+        let cursor = assign_from_arg(&arg_matches, Foo { cursor: None });
+        assert_eq!(cursor.cursor(), Some("now"));
+        assert!(is_following(&arg_matches));
+    }
+
+    #[test]
+    fn it_keeps_a_given_cursor() {
+        let arg_matches = get_matches(vec!["test", "--follow"]);
+        let cursor = Foo {
+            cursor: Some("123abc".to_string()),
+        };
+        let cursor = assign_from_arg(&arg_matches, cursor);
+        assert_eq!(cursor.cursor(), Some("123abc"));
+    }
+
+    #[test]
+    fn it_does_nothing_when_not_following() {
+        let arg_matches = get_matches(vec!["test"]);
+        let cursor = assign_from_arg(&arg_matches, Foo { cursor: None });
+        assert_eq!(cursor.cursor(), None);
+        assert!(!is_following(&arg_matches));
+    }
+}
@@ -0,0 +1,31 @@
+use clap::ArgMatches;
+use error::Result;
+use stellar_client::{
+    error::Error,
+    soroban::{DiagnosticEvent, ScVal, SorobanTransactionData},
+};
+
+/// Decodes a contract value and prints it.
+pub fn scval(matches: &ArgMatches) -> Result<()> {
+    let xdr = matches.value_of("XDR").expect("XDR is required");
+    let value = ScVal::from_xdr_base64(xdr).map_err(Error::from)?;
+    println!("{:#?}", value);
+    Ok(())
+}
+
+/// Decodes a diagnostic event, as found in simulation results, and prints it.
+pub fn event(matches: &ArgMatches) -> Result<()> {
+    let xdr = matches.value_of("XDR").expect("XDR is required");
+    let event = DiagnosticEvent::from_xdr_base64(xdr).map_err(Error::from)?;
+    println!("{:#?}", event);
+    Ok(())
+}
+
+/// Decodes the footprint and resources of a soroban transaction and
+/// prints them.
+pub fn transaction_data(matches: &ArgMatches) -> Result<()> {
+    let xdr = matches.value_of("XDR").expect("XDR is required");
+    let data = SorobanTransactionData::from_xdr_base64(xdr).map_err(Error::from)?;
+    println!("{:#?}", data);
+    Ok(())
+}
@@ -0,0 +1,297 @@
+//! Finds out which SEPs an anchor supports from its stellar.toml.
+//!
+//! An anchor announces the servers it runs in the stellar.toml at its home
+//! domain: a `TRANSFER_SERVER` for SEP-6 deposits and withdrawals, a
+//! `WEB_AUTH_ENDPOINT` for SEP-10 authentication, and so on. `Anchor` reads
+//! them all at once, so that a wallet can tell what it can do with an anchor
+//! before it starts, and hands out clients for the protocols this crate has
+//! clients for. For the others it gives the uri of the server.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use stellar_client::anchor::{Anchor, Sep};
+//!
+//! let anchor = Anchor::discover("example.com").unwrap();
+//! for sep in anchor.supported() {
+//!     println!("{} supports {}", anchor.home_domain(), sep);
+//! }
+//! if let Some(transfer_server) = anchor.endpoint(Sep::Interactive) {
+//!     println!("deposit at {}", transfer_server);
+//! }
+//! ```
+use error::Result;
+use reqwest;
+use sep10::{AuthSession, TokenCache};
+use sep8::{self, RegulatedAsset};
+use std::fmt;
+use std::io;
+use std::time::Duration;
+use stellar_toml::{self, StellarToml};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A SEP an anchor can announce support for in its stellar.toml.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Sep {
+    /// SEP-2, federation: resolving addresses like `bob*example.com`.
+    Federation,
+    /// SEP-6, programmatic deposits and withdrawals.
+    Transfer,
+    /// SEP-8, approval of transactions in regulated assets.
+    RegulatedAssets,
+    /// SEP-10, authenticating an account.
+    WebAuth,
+    /// SEP-12, customer information for KYC.
+    Kyc,
+    /// SEP-24, interactive deposits and withdrawals.
+    Interactive,
+    /// SEP-38, quotes for exchanging assets.
+    Quotes,
+}
+
+impl Sep {
+    /// Every SEP an `Anchor` looks for, in order.
+    pub const ALL: [Sep; 7] = [
+        Sep::Federation,
+        Sep::Transfer,
+        Sep::RegulatedAssets,
+        Sep::WebAuth,
+        Sep::Kyc,
+        Sep::Interactive,
+        Sep::Quotes,
+    ];
+
+    /// The number of the SEP, such as 24 for `Sep::Interactive`.
+    pub fn number(self) -> u32 {
+        match self {
+            Sep::Federation => 2,
+            Sep::Transfer => 6,
+            Sep::RegulatedAssets => 8,
+            Sep::WebAuth => 10,
+            Sep::Kyc => 12,
+            Sep::Interactive => 24,
+            Sep::Quotes => 38,
+        }
+    }
+}
+
+impl fmt::Display for Sep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SEP-{}", self.number())
+    }
+}
+
+/// What an anchor announces in its stellar.toml.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Anchor {
+    home_domain: String,
+    federation_server: Option<String>,
+    transfer_server: Option<String>,
+    web_auth_endpoint: Option<String>,
+    signing_key: Option<String>,
+    kyc_server: Option<String>,
+    transfer_server_sep24: Option<String>,
+    quote_server: Option<String>,
+    regulated_assets: Vec<RegulatedAsset>,
+}
+
+impl Anchor {
+    /// Fetches the stellar.toml of `home_domain` and reads what it
+    /// announces.
+    pub fn discover(home_domain: &str) -> Result<Anchor> {
+        let client = reqwest::ClientBuilder::new()
+            .timeout(DEFAULT_TIMEOUT)
+            .build()?;
+        let toml = stellar_toml::fetch(&client, home_domain)?;
+        Ok(Anchor::from_toml(home_domain, &toml))
+    }
+
+    /// Reads what the text of a stellar.toml published at `home_domain`
+    /// announces.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use stellar_client::anchor::{Anchor, Sep};
+    ///
+    /// let anchor = Anchor::from_toml("example.com", r#"
+    /// TRANSFER_SERVER_SEP0024 = "https://example.com/sep24"
+    /// WEB_AUTH_ENDPOINT = "https://example.com/auth"
+    /// SIGNING_KEY = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG"
+    /// "#);
+    /// assert_eq!(anchor.supported(), vec![Sep::WebAuth, Sep::Interactive]);
+    /// ```
+    pub fn from_toml(home_domain: &str, toml: &str) -> Anchor {
+        let parsed = StellarToml::parse(toml);
+        let root = parsed.tables("").next();
+        let string = |key: &str| {
+            root.and_then(|root| root.string(key))
+                .filter(|value| !value.is_empty())
+                .map(String::from)
+        };
+        Anchor {
+            home_domain: home_domain.to_string(),
+            federation_server: string("FEDERATION_SERVER"),
+            transfer_server: string("TRANSFER_SERVER"),
+            web_auth_endpoint: string("WEB_AUTH_ENDPOINT"),
+            signing_key: string("SIGNING_KEY"),
+            kyc_server: string("KYC_SERVER"),
+            transfer_server_sep24: string("TRANSFER_SERVER_SEP0024"),
+            quote_server: string("ANCHOR_QUOTE_SERVER"),
+            regulated_assets: RegulatedAsset::from_toml(toml),
+        }
+    }
+
+    /// The home domain the stellar.toml was published at.
+    pub fn home_domain(&self) -> &str {
+        &self.home_domain
+    }
+
+    /// The uri of the server for `sep`, if the anchor announces one. SEP-8
+    /// has a server for each regulated asset instead, so it has no uri
+    /// here.
+    pub fn endpoint(&self, sep: Sep) -> Option<&str> {
+        let endpoint = match sep {
+            Sep::Federation => &self.federation_server,
+            Sep::Transfer => &self.transfer_server,
+            Sep::RegulatedAssets => return None,
+            Sep::WebAuth => &self.web_auth_endpoint,
+            // SEP-12 falls back to the transfer server when there is no
+            // separate KYC server.
+            Sep::Kyc => {
+                return self
+                    .kyc_server
+                    .as_deref()
+                    .or(self.transfer_server.as_deref())
+            }
+            Sep::Interactive => &self.transfer_server_sep24,
+            Sep::Quotes => &self.quote_server,
+        };
+        endpoint.as_deref()
+    }
+
+    /// Whether the anchor supports `sep`. SEP-10 also needs the
+    /// `SIGNING_KEY` its challenges are signed with.
+    pub fn supports(&self, sep: Sep) -> bool {
+        match sep {
+            Sep::RegulatedAssets => !self.regulated_assets.is_empty(),
+            Sep::WebAuth => self.web_auth_endpoint.is_some() && self.signing_key.is_some(),
+            sep => self.endpoint(sep).is_some(),
+        }
+    }
+
+    /// Every SEP the anchor supports, in order.
+    pub fn supported(&self) -> Vec<Sep> {
+        Sep::ALL
+            .iter()
+            .cloned()
+            .filter(|sep| self.supports(*sep))
+            .collect()
+    }
+
+    /// The account that signs the anchor's SEP-10 challenges.
+    pub fn signing_key(&self) -> Option<&str> {
+        self.signing_key.as_deref()
+    }
+
+    /// The regulated assets the anchor lists.
+    pub fn regulated_assets(&self) -> &Vec<RegulatedAsset> {
+        &self.regulated_assets
+    }
+
+    /// A client for the approval servers of the anchor's regulated assets,
+    /// or `None` when it has none.
+    pub fn approval_client(&self) -> Option<Result<sep8::Client>> {
+        if self.supports(Sep::RegulatedAssets) {
+            Some(sep8::Client::new())
+        } else {
+            None
+        }
+    }
+
+    /// A session that keeps `account` authenticated with the anchor, or
+    /// `None` when it doesn't support SEP-10. `authenticate` runs the SEP-10
+    /// flow against `endpoint(Sep::WebAuth)` and returns the token.
+    pub fn auth_session<C, F>(
+        &self,
+        account: &str,
+        cache: C,
+        authenticate: F,
+    ) -> Option<AuthSession<C, F>>
+    where
+        C: TokenCache,
+        F: Fn() -> io::Result<String>,
+    {
+        if self.supports(Sep::WebAuth) {
+            Some(AuthSession::new(
+                &self.home_domain,
+                account,
+                cache,
+                authenticate,
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod anchor_tests {
+    use super::*;
+    use sep10::MemoryTokenCache;
+
+    const TOML: &str = r#"
+VERSION = "2.0.0"
+FEDERATION_SERVER = "https://example.com/federation"
+TRANSFER_SERVER = "https://example.com/sep6"
+WEB_AUTH_ENDPOINT = "https://example.com/auth"
+SIGNING_KEY = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG"
+ANCHOR_QUOTE_SERVER = ""
+
+[[CURRENCIES]]
+code = "USD"
+issuer = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG"
+regulated = true
+approval_server = "https://example.com/tx-approve"
+
+[DOCUMENTATION]
+ORG_NAME = "Example"
+"#;
+
+    #[test]
+    fn it_reports_the_seps_an_anchor_supports() {
+        let anchor = Anchor::from_toml("example.com", TOML);
+        assert_eq!(
+            anchor.supported(),
+            vec![
+                Sep::Federation,
+                Sep::Transfer,
+                Sep::RegulatedAssets,
+                Sep::WebAuth,
+                Sep::Kyc,
+            ]
+        );
+        assert_eq!(anchor.endpoint(Sep::Kyc), Some("https://example.com/sep6"));
+        assert_eq!(anchor.endpoint(Sep::Quotes), None);
+        assert_eq!(anchor.regulated_assets().len(), 1);
+        assert_eq!(Sep::Interactive.to_string(), "SEP-24");
+    }
+
+    #[test]
+    fn it_hands_out_clients_for_supported_seps() {
+        let anchor = Anchor::from_toml("example.com", TOML);
+        assert!(anchor.approval_client().is_some());
+        let session = anchor.auth_session("GABC", MemoryTokenCache::new(), || Ok(String::new()));
+        assert!(session.is_some());
+
+        let anchor = Anchor::from_toml(
+            "example.com",
+            r#"WEB_AUTH_ENDPOINT = "https://example.com/auth""#,
+        );
+        assert!(anchor.supported().is_empty());
+        assert!(anchor.approval_client().is_none());
+        let session = anchor.auth_session("GABC", MemoryTokenCache::new(), || Ok(String::new()));
+        assert!(session.is_none());
+    }
+}
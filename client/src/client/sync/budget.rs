@@ -0,0 +1,106 @@
+use error::Error;
+use std::io::{self, Read};
+use std::time::{Duration, Instant};
+
+/// Wraps a response body so that reading it stops once it grows past a
+/// size limit or runs past a time budget. The reader only sees an io error,
+/// so the caller asks for the typed error with `exceeded` afterwards.
+#[derive(Debug)]
+pub(super) struct Budgeted<R> {
+    inner: R,
+    read: u64,
+    max_size: Option<u64>,
+    budget: Option<Duration>,
+    started: Instant,
+    exceeded: Option<Exceeded>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Exceeded {
+    Size(u64),
+    Time(Duration),
+}
+
+impl<R> Budgeted<R>
+where
+    R: Read,
+{
+    /// Starts the clock on reading `inner`.
+    pub fn new(inner: R, max_size: Option<u64>, budget: Option<Duration>) -> Self {
+        Budgeted {
+            inner,
+            read: 0,
+            max_size,
+            budget,
+            started: Instant::now(),
+            exceeded: None,
+        }
+    }
+
+    /// The error for the limit that cut reading short, if one did.
+    pub fn exceeded(&self) -> Option<Error> {
+        self.exceeded.map(|exceeded| match exceeded {
+            Exceeded::Size(max) => Error::BodyTooLarge(max, None),
+            Exceeded::Time(budget) => Error::ParseTimeout(budget, None),
+        })
+    }
+
+    fn exceed(&mut self, exceeded: Exceeded) -> io::Error {
+        self.exceeded = Some(exceeded);
+        io::Error::other("Response budget exceeded")
+    }
+}
+
+impl<R> Read for Budgeted<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(budget) = self.budget {
+            if self.started.elapsed() > budget {
+                return Err(self.exceed(Exceeded::Time(budget)));
+            }
+        }
+        let read = self.inner.read(buf)?;
+        self.read += read as u64;
+        match self.max_size {
+            Some(max) if self.read > max => Err(self.exceed(Exceeded::Size(max))),
+            _ => Ok(read),
+        }
+    }
+}
+
+#[cfg(test)]
+mod budgeted_tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_within_the_budget() {
+        let mut body = Budgeted::new(&b"{}"[..], Some(2), Some(Duration::from_secs(60)));
+        let mut read = String::new();
+        body.read_to_string(&mut read).unwrap();
+        assert_eq!(read, "{}");
+        assert!(body.exceeded().is_none());
+    }
+
+    #[test]
+    fn it_stops_at_the_size_limit() {
+        let mut body = Budgeted::new(&b"{\"a\": 1}"[..], Some(4), None);
+        assert!(body.read_to_end(&mut Vec::new()).is_err());
+        match body.exceeded() {
+            Some(Error::BodyTooLarge(4, None)) => {}
+            other => panic!("expected the body to be too large, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn it_stops_once_the_time_budget_is_spent() {
+        let mut body = Budgeted::new(&b"{}"[..], None, Some(Duration::from_millis(0)));
+        ::std::thread::sleep(Duration::from_millis(5));
+        assert!(body.read_to_end(&mut Vec::new()).is_err());
+        match body.exceeded() {
+            Some(Error::ParseTimeout(budget, None)) => assert_eq!(budget, Duration::from_millis(0)),
+            other => panic!("expected the time budget to run out, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,484 @@
+use super::super::{Host, HORIZON_TEST_URI, HORIZON_URI};
+use super::{
+    Client, ClientConfig, Resolve, RetryPolicy, SignRequest, DEFAULT_FAILOVER_BACKOFF,
+    DEFAULT_MAX_INGEST_LAG, DEFAULT_TIMEOUT,
+};
+use error::Result;
+use http::{self, Uri};
+use network::Network;
+use reqwest;
+use resources::Strictness;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Configures the connection handling of a synchronous client.
+///
+/// A client keeps a pool of connections to horizon and reuses them between
+/// requests, which matters most when paging through many records. The
+/// builder allows the pool and its sockets to be tuned, and fallback servers
+/// to be configured for when the primary one is unavailable.
+///
+/// ## Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use stellar_client::sync::ClientBuilder;
+///
+/// let client = ClientBuilder::horizon_test()
+///     .with_max_idle_per_host(4)
+///     .with_connect_timeout(Duration::from_secs(5))
+///     .build()
+///     .unwrap();
+/// assert!(client.is_horizon_test());
+/// ```
+///
+/// A client for a local standalone network needs to be told which network
+/// it is talking to, so that friendbot can be found:
+///
+/// ```
+/// use stellar_client::{sync::ClientBuilder, Network};
+///
+/// let client = ClientBuilder::new("http://localhost:8000")
+///     .unwrap()
+///     .with_network(Network::standalone("Standalone Network ; February 2017"))
+///     .build()
+///     .unwrap();
+/// assert_eq!(
+///     client.network().unwrap().passphrase(),
+///     "Standalone Network ; February 2017"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    host: Host,
+    timeout: Duration,
+    connect_timeout: Option<Duration>,
+    max_idle_per_host: Option<usize>,
+    tcp_nodelay: bool,
+    http2_prior_knowledge: bool,
+    fallbacks: Vec<String>,
+    failover_backoff: Duration,
+    network: Option<Network>,
+    max_ingest_lag: u32,
+    strictness: Strictness,
+    max_body_size: Option<u64>,
+    parse_budget: Option<Duration>,
+    page_limit: Option<u32>,
+    prefetch: usize,
+    retries: Option<RetryPolicy>,
+    headers: Vec<(String, String)>,
+    proxy: Option<String>,
+    resolver: Option<Arc<dyn Resolve>>,
+    signer: Option<Arc<dyn SignRequest>>,
+    #[cfg(feature = "tracing")]
+    body_log_limit: Option<usize>,
+}
+
+impl ClientBuilder {
+    /// Starts building a client for the horizon server at `uri`.
+    pub fn new(uri: &str) -> Result<Self> {
+        // Ensure that the uri passed in can parse.
+        let _: Uri = uri.parse()?;
+        Ok(Self::for_host(Host::Other(uri.to_string())))
+    }
+
+    /// Starts building a client for the horizon test network.
+    pub fn horizon_test() -> Self {
+        Self::for_host(Host::HorizonTest)
+    }
+
+    /// Starts building a client for the horizon prod network.
+    pub fn horizon() -> Self {
+        Self::for_host(Host::HorizonProd)
+    }
+
+    /// Starts building a client with the settings of a configuration. The
+    /// stellar run servers are recognised by their uri, so the client knows
+    /// their network even when the configuration doesn't say.
+    pub fn from_config(config: &ClientConfig) -> Result<Self> {
+        let mut builder = if config.uri() == HORIZON_TEST_URI {
+            Self::horizon_test()
+        } else if config.uri() == HORIZON_URI {
+            Self::horizon()
+        } else {
+            Self::new(config.uri())?
+        };
+        for uri in config.fallbacks() {
+            builder = builder.with_fallback(uri);
+        }
+        if let Some(network) = config.network() {
+            builder = builder.with_network(network);
+        }
+        if let Some(timeout) = config.timeout() {
+            builder = builder.with_timeout(timeout);
+        }
+        if let Some(timeout) = config.connect_timeout() {
+            builder = builder.with_connect_timeout(timeout);
+        }
+        if let Some(policy) = config.retries() {
+            builder = builder.with_retries(policy);
+        }
+        for (name, value) in config.headers() {
+            builder = builder.with_header(name, value);
+        }
+        if let Some(proxy) = config.proxy() {
+            builder = builder.with_proxy(proxy);
+        }
+        Ok(builder)
+    }
+
+    pub(super) fn for_host(host: Host) -> Self {
+        let network = match host {
+            Host::HorizonTest => Some(Network::test()),
+            Host::HorizonProd => Some(Network::public()),
+            Host::Other(_) => None,
+        };
+        ClientBuilder {
+            network,
+            host,
+            timeout: DEFAULT_TIMEOUT,
+            connect_timeout: None,
+            max_idle_per_host: None,
+            tcp_nodelay: true,
+            http2_prior_knowledge: false,
+            fallbacks: Vec::new(),
+            failover_backoff: DEFAULT_FAILOVER_BACKOFF,
+            max_ingest_lag: DEFAULT_MAX_INGEST_LAG,
+            strictness: Strictness::default(),
+            max_body_size: None,
+            parse_budget: None,
+            page_limit: None,
+            prefetch: 0,
+            retries: None,
+            headers: Vec::new(),
+            proxy: None,
+            resolver: None,
+            signer: None,
+            #[cfg(feature = "tracing")]
+            body_log_limit: None,
+        }
+    }
+
+    /// Sets the total time allowed for a request, including reading the
+    /// response. Defaults to 60 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the time allowed for establishing a new connection. By default
+    /// only the overall request timeout applies.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sends requests through the proxy at `uri`. By default requests are
+    /// sent directly. An invalid uri is reported when the client is built.
+    pub fn with_proxy(mut self, uri: &str) -> Self {
+        self.proxy = Some(uri.to_string());
+        self
+    }
+
+    /// Resolves the names of the horizon servers with `resolver` rather than
+    /// the system's resolver. A `StaticResolver` pins names to fixed
+    /// addresses. Only servers reached over http can be pinned.
+    pub fn with_resolver<R: Resolve + 'static>(mut self, resolver: R) -> Self {
+        self.resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Signs every request with `signer` just before it is sent, for horizon
+    /// servers that authenticate each request. A `BearerToken` sends a token
+    /// in the `Authorization` header.
+    pub fn with_signer<S: SignRequest + 'static>(mut self, signer: S) -> Self {
+        self.signer = Some(Arc::new(signer));
+        self
+    }
+
+    /// Sets how many idle connections to horizon are kept open for reuse.
+    /// By default the pool is unbounded.
+    pub fn with_max_idle_per_host(mut self, max: usize) -> Self {
+        self.max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Sets whether sockets are opened with `TCP_NODELAY`. This is on by
+    /// default since horizon requests are small and latency sensitive.
+    pub fn with_tcp_nodelay(mut self, enabled: bool) -> Self {
+        self.tcp_nodelay = enabled;
+        self
+    }
+
+    /// Speak HTTP/2 to the server without negotiating it first. Only enable
+    /// this for a horizon deployment that is known to support HTTP/2.
+    pub fn with_http2_prior_knowledge(mut self, enabled: bool) -> Self {
+        self.http2_prior_knowledge = enabled;
+        self
+    }
+
+    /// Adds a horizon server to fall back to when the servers before it fail
+    /// to connect or respond with a server error. Fallbacks are tried in the
+    /// order they are added. Only GET requests fail over, so that a
+    /// transaction is never submitted twice.
+    pub fn with_fallback(mut self, uri: &str) -> Self {
+        self.fallbacks.push(uri.to_string());
+        self
+    }
+
+    /// Sets how long a server that failed is skipped before requests try it
+    /// again. Defaults to 30 seconds.
+    pub fn with_failover_backoff(mut self, backoff: Duration) -> Self {
+        self.failover_backoff = backoff;
+        self
+    }
+
+    /// Sets the network the horizon server belongs to. This is known for
+    /// the stellar run servers, but has to be given for any other.
+    pub fn with_network(mut self, network: Network) -> Self {
+        self.network = Some(network);
+        self
+    }
+
+    /// Sets how many ledgers horizon's history may be behind stellar core
+    /// before `Client::sync_status` reports it as lagging. Defaults to 10.
+    pub fn with_max_ingest_lag(mut self, ledgers: u32) -> Self {
+        self.max_ingest_lag = ledgers;
+        self
+    }
+
+    /// Sets how closely responses have to match the resources they are
+    /// parsed into. Defaults to `Strictness::Lenient`, which ignores fields
+    /// that the resources don't model.
+    pub fn with_strictness(mut self, strictness: Strictness) -> Self {
+        self.strictness = strictness;
+        self
+    }
+
+    /// Sets the largest response body, in bytes, that the client will read.
+    /// Larger responses fail with `Error::BodyTooLarge` rather than being
+    /// held in memory. By default there is no limit.
+    pub fn with_max_body_size(mut self, bytes: u64) -> Self {
+        self.max_body_size = Some(bytes);
+        self
+    }
+
+    /// Sets how long reading and parsing a response body may take once
+    /// horizon has started answering. Requests that take longer fail with
+    /// `Error::ParseTimeout`. By default only the request timeout applies.
+    pub fn with_parse_budget(mut self, budget: Duration) -> Self {
+        self.parse_budget = Some(budget);
+        self
+    }
+
+    /// Sets the `limit` that an `Iter` asks for when its endpoint doesn't
+    /// set one. Large pages suit backfills, while small ones get the first
+    /// records of an interactive query back sooner. By default horizon
+    /// decides, which is 10 records a page.
+    pub fn with_page_limit(mut self, limit: u32) -> Self {
+        self.page_limit = Some(limit);
+        self
+    }
+
+    /// Sets how many pages an iterator made with `Iter::prefetching` fetches
+    /// ahead on another thread while the records it already has are handled.
+    /// Defaults to 0, which fetches each page only once the one before it is
+    /// used up. Iterators that follow an endpoint never prefetch.
+    pub fn with_prefetch(mut self, pages: usize) -> Self {
+        self.prefetch = pages;
+        self
+    }
+
+    /// Retries requests that horizon answers with a 429 or 503 according to
+    /// the policy, waiting as long as the `Retry-After` header asks. By
+    /// default these responses are returned as errors straight away.
+    pub fn with_retries(mut self, policy: RetryPolicy) -> Self {
+        self.retries = Some(policy);
+        self
+    }
+
+    /// Adds a header that is sent with every request, such as one naming
+    /// the application to a horizon operator. An invalid name or value is
+    /// reported when the client is built.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Logs the uri, status and body of every response as a debug event,
+    /// for diagnosing responses that fail to parse. Bodies are cut down to
+    /// `max_len` bytes, and secret seeds and JWTs in the uri and body are
+    /// redacted. Responses are read in full before they are parsed while
+    /// this is on. Only available with the `tracing` feature enabled.
+    #[cfg(feature = "tracing")]
+    pub fn with_body_logging(mut self, max_len: usize) -> Self {
+        self.body_log_limit = Some(max_len);
+        self
+    }
+
+    /// Builds the client.
+    pub fn build(self) -> Result<Client> {
+        for uri in &self.fallbacks {
+            let _: Uri = uri.parse()?;
+        }
+
+        let mut inner = reqwest::ClientBuilder::new().timeout(self.timeout);
+        if let Some(timeout) = self.connect_timeout {
+            inner = inner.connect_timeout(timeout);
+        }
+        if let Some(max) = self.max_idle_per_host {
+            inner = inner.max_idle_per_host(max);
+        }
+        if self.tcp_nodelay {
+            inner = inner.tcp_nodelay();
+        }
+        if self.http2_prior_knowledge {
+            inner = inner.h2_prior_knowledge();
+        }
+        if let Some(ref uri) = self.proxy {
+            inner = inner.proxy(reqwest::Proxy::all(uri.as_str())?);
+        }
+        if !self.headers.is_empty() {
+            let mut headers = http::HeaderMap::new();
+            for (name, value) in &self.headers {
+                headers.append(
+                    http::header::HeaderName::from_bytes(name.as_bytes())
+                        .map_err(http::Error::from)?,
+                    http::header::HeaderValue::from_str(value).map_err(http::Error::from)?,
+                );
+            }
+            inner = inner.default_headers(headers);
+        }
+        Ok(Client {
+            host: self.host,
+            inner: inner.build()?,
+            failures: Arc::new(Mutex::new(vec![None; self.fallbacks.len() + 1])),
+            fallbacks: self.fallbacks,
+            failover_backoff: self.failover_backoff,
+            network: self.network,
+            max_ingest_lag: self.max_ingest_lag,
+            strictness: self.strictness,
+            max_body_size: self.max_body_size,
+            parse_budget: self.parse_budget,
+            page_limit: self.page_limit,
+            prefetch: self.prefetch,
+            retries: self.retries,
+            resolver: self.resolver,
+            signer: self.signer,
+            #[cfg(feature = "tracing")]
+            body_log_limit: self.body_log_limit,
+            horizon_version: Arc::new(Mutex::new(None)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod builder_tests {
+    use super::*;
+
+    #[test]
+    fn it_builds_a_client_for_each_host() {
+        assert!(ClientBuilder::horizon().build().unwrap().is_horizon());
+        assert!(ClientBuilder::horizon_test()
+            .build()
+            .unwrap()
+            .is_horizon_test());
+        let client = ClientBuilder::new("https://www.google.com")
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(client.uri(), "https://www.google.com");
+        assert_eq!(client.network(), None);
+    }
+
+    #[test]
+    fn it_knows_the_network_of_each_host() {
+        let client = ClientBuilder::horizon().build().unwrap();
+        assert_eq!(client.network(), Some(&Network::public()));
+        let client = ClientBuilder::new("http://localhost:8000")
+            .unwrap()
+            .with_network(Network::standalone("Standalone"))
+            .build()
+            .unwrap();
+        assert_eq!(client.network(), Some(&Network::standalone("Standalone")));
+    }
+
+    #[test]
+    fn it_applies_a_config() {
+        let config = ClientConfig::new("http://localhost:8000")
+            .with_fallback("http://localhost:8001")
+            .with_network(&Network::standalone("Standalone"))
+            .with_timeout(Duration::from_secs(10))
+            .with_connect_timeout(Duration::from_secs(2))
+            .with_retries(RetryPolicy::new(3))
+            .with_header("X-Client-Name", "payouts")
+            .with_proxy("http://proxy.internal:3128");
+        let builder = ClientBuilder::from_config(&config).unwrap();
+        assert_eq!(builder.fallbacks, vec!["http://localhost:8001".to_string()]);
+        assert_eq!(builder.network, Some(Network::standalone("Standalone")));
+        assert_eq!(builder.timeout, Duration::from_secs(10));
+        assert_eq!(builder.connect_timeout, Some(Duration::from_secs(2)));
+        assert_eq!(builder.retries, Some(RetryPolicy::new(3)));
+        assert_eq!(
+            builder.headers,
+            vec![("X-Client-Name".to_string(), "payouts".to_string())]
+        );
+        assert_eq!(
+            builder.proxy,
+            Some("http://proxy.internal:3128".to_string())
+        );
+        assert!(builder.build().is_ok());
+    }
+
+    #[test]
+    fn it_recognises_the_stellar_servers_in_a_config() {
+        let config = ClientConfig::new("https://horizon.stellar.org");
+        let client = ClientBuilder::from_config(&config)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert!(client.is_horizon());
+        assert_eq!(client.network(), Some(&Network::public()));
+    }
+
+    #[test]
+    fn it_errs_on_an_invalid_header() {
+        let builder = ClientBuilder::horizon_test().with_header("Bad Name", "value");
+        assert!(builder.build().is_err());
+    }
+
+    #[test]
+    fn it_errs_if_a_bad_uri_is_provided() {
+        assert!(ClientBuilder::new("htps:/www").is_err());
+    }
+
+    #[test]
+    fn it_applies_connection_settings() {
+        let builder = ClientBuilder::horizon_test()
+            .with_timeout(Duration::from_secs(10))
+            .with_connect_timeout(Duration::from_secs(2))
+            .with_max_idle_per_host(8)
+            .with_tcp_nodelay(false)
+            .with_http2_prior_knowledge(true)
+            .with_max_ingest_lag(20)
+            .with_strictness(Strictness::Lenient)
+            .with_max_body_size(1 << 20)
+            .with_parse_budget(Duration::from_secs(5))
+            .with_page_limit(200)
+            .with_prefetch(2)
+            .with_retries(RetryPolicy::new(3));
+        assert_eq!(builder.timeout, Duration::from_secs(10));
+        assert_eq!(builder.connect_timeout, Some(Duration::from_secs(2)));
+        assert_eq!(builder.max_idle_per_host, Some(8));
+        assert!(!builder.tcp_nodelay);
+        assert!(builder.http2_prior_knowledge);
+        assert_eq!(builder.max_ingest_lag, 20);
+        assert_eq!(builder.strictness, Strictness::Lenient);
+        assert_eq!(builder.max_body_size, Some(1 << 20));
+        assert_eq!(builder.parse_budget, Some(Duration::from_secs(5)));
+        assert_eq!(builder.page_limit, Some(200));
+        assert_eq!(builder.prefetch, 2);
+        assert_eq!(builder.retries, Some(RetryPolicy::new(3)));
+        assert!(builder.build().is_ok());
+    }
+}
@@ -0,0 +1,485 @@
+//! A serializable form of a client's settings, so that they can be kept in a
+//! file and shared between tools.
+use super::RetryPolicy;
+use error::{Error, Result};
+use network::Network;
+use serde_json;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::File;
+use std::io::{BufReader, Write};
+use std::path::Path;
+use std::time::Duration;
+
+/// The settings of a synchronous client that can be written to a file and
+/// read back, so that command line tools and services talking to the same
+/// horizon servers can share one configuration.
+///
+/// Configurations are stored as json. Only `uri` is required, and anything
+/// left out keeps the default of `ClientBuilder`. Unknown fields are refused
+/// so that a misspelled setting isn't silently ignored. Times are given in
+/// milliseconds.
+///
+/// ```json
+/// {
+///   "uri": "https://horizon.example.com",
+///   "fallbacks": ["https://horizon-backup.example.com"],
+///   "network_passphrase": "Public Global Stellar Network ; September 2015",
+///   "friendbot_uri": "https://friendbot.example.com",
+///   "timeout_ms": 30000,
+///   "connect_timeout_ms": 5000,
+///   "retries": {
+///     "max_retries": 3,
+///     "base_delay_ms": 500,
+///     "max_delay_ms": 30000,
+///     "max_wait_ms": 60000
+///   },
+///   "headers": { "X-Client-Name": "payouts" },
+///   "proxy": "http://proxy.internal:3128"
+/// }
+/// ```
+///
+/// ## Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use stellar_client::sync::{Client, ClientConfig, RetryPolicy};
+///
+/// let config: ClientConfig = r#"{
+///     "uri": "https://horizon-testnet.stellar.org",
+///     "timeout_ms": 10000,
+///     "retries": { "max_retries": 2 }
+/// }"#.parse().unwrap();
+/// assert_eq!(config.timeout(), Some(Duration::from_secs(10)));
+/// assert_eq!(config.retries(), Some(RetryPolicy::new(2)));
+///
+/// let client = Client::from_config(&config).unwrap();
+/// assert!(client.is_horizon_test());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientConfig {
+    uri: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    fallbacks: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    network_passphrase: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    friendbot_uri: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timeout_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    connect_timeout_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retries: Option<RetryConfig>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    proxy: Option<String>,
+}
+
+// A retry policy as it is written in a configuration. Delays that are left
+// out keep the defaults of `RetryPolicy::new`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RetryConfig {
+    max_retries: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base_delay_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_delay_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_wait_ms: Option<u64>,
+}
+
+impl ClientConfig {
+    /// A configuration for the horizon server at `uri` with every other
+    /// setting left at its default.
+    pub fn new(uri: &str) -> ClientConfig {
+        ClientConfig {
+            uri: uri.to_string(),
+            fallbacks: Vec::new(),
+            network_passphrase: None,
+            friendbot_uri: None,
+            timeout_ms: None,
+            connect_timeout_ms: None,
+            retries: None,
+            headers: BTreeMap::new(),
+            proxy: None,
+        }
+    }
+
+    /// Reads a configuration from environment variables, as is usual for
+    /// services deployed as twelve-factor apps.
+    ///
+    /// * `STELLAR_HORIZON_URL` is the uri of the horizon server, and is the
+    ///   only variable that is required.
+    /// * `STELLAR_NETWORK_PASSPHRASE` is the passphrase of the network the
+    ///   server belongs to.
+    /// * `STELLAR_HORIZON_TIMEOUT` and `STELLAR_HORIZON_CONNECT_TIMEOUT` are
+    ///   the request and connection timeouts, in whole seconds.
+    /// * `STELLAR_HORIZON_PROXY` is a proxy to send requests through. When it
+    ///   isn't set, the usual `HTTPS_PROXY` or `HTTP_PROXY` for the scheme of
+    ///   the horizon uri is used unless `NO_PROXY` lists its host.
+    ///
+    /// Empty variables count as unset. A variable that can't be read fails
+    /// with `Error::Config` naming it.
+    pub fn from_env() -> Result<ClientConfig> {
+        ClientConfig::from_vars(|name| env::var(name).ok())
+    }
+
+    fn from_vars<F>(var: F) -> Result<ClientConfig>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let var = |name: &str| var(name).filter(|value| !value.trim().is_empty());
+        let seconds = |name: &'static str| match var(name) {
+            Some(value) => value
+                .trim()
+                .parse()
+                .map(|secs| Some(Duration::from_secs(secs)))
+                .map_err(|_| Error::Config(format!("{} is not a number of seconds", name))),
+            None => Ok(None),
+        };
+
+        let uri = var("STELLAR_HORIZON_URL")
+            .ok_or_else(|| Error::Config("STELLAR_HORIZON_URL is not set".to_string()))?;
+        let mut config = ClientConfig::new(uri.trim());
+        if let Some(passphrase) = var("STELLAR_NETWORK_PASSPHRASE") {
+            config = config.with_network(&Network::new(&passphrase));
+        }
+        if let Some(timeout) = seconds("STELLAR_HORIZON_TIMEOUT")? {
+            config = config.with_timeout(timeout);
+        }
+        if let Some(timeout) = seconds("STELLAR_HORIZON_CONNECT_TIMEOUT")? {
+            config = config.with_connect_timeout(timeout);
+        }
+
+        let proxy = var("STELLAR_HORIZON_PROXY").or_else(|| {
+            let bypassed = var("NO_PROXY")
+                .or_else(|| var("no_proxy"))
+                .map_or(false, |no_proxy| bypasses_proxy(&no_proxy, &config.uri));
+            if bypassed {
+                None
+            } else if config.uri.starts_with("https:") {
+                var("HTTPS_PROXY").or_else(|| var("https_proxy"))
+            } else {
+                var("HTTP_PROXY").or_else(|| var("http_proxy"))
+            }
+        });
+        if let Some(proxy) = proxy {
+            config = config.with_proxy(proxy.trim());
+        }
+        Ok(config)
+    }
+
+    /// Reads a configuration from a json file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ClientConfig> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+
+    /// Writes the configuration to a json file, replacing the file if it
+    /// already exists.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut file = File::create(path)?;
+        serde_json::to_writer_pretty(&mut file, self)?;
+        file.write_all(b"\n")?;
+        Ok(())
+    }
+
+    /// Adds a horizon server to fall back to, as with
+    /// `ClientBuilder::with_fallback`.
+    pub fn with_fallback(mut self, uri: &str) -> Self {
+        self.fallbacks.push(uri.to_string());
+        self
+    }
+
+    /// Sets the network the horizon server belongs to, as with
+    /// `ClientBuilder::with_network`.
+    pub fn with_network(mut self, network: &Network) -> Self {
+        self.network_passphrase = Some(network.passphrase().to_string());
+        self.friendbot_uri = network.friendbot_uri().map(String::from);
+        self
+    }
+
+    /// Sets the total time allowed for a request, as with
+    /// `ClientBuilder::with_timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout_ms = Some(millis(timeout));
+        self
+    }
+
+    /// Sets the time allowed for establishing a new connection, as with
+    /// `ClientBuilder::with_connect_timeout`.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout_ms = Some(millis(timeout));
+        self
+    }
+
+    /// Sets how requests turned away by horizon are retried, as with
+    /// `ClientBuilder::with_retries`.
+    pub fn with_retries(mut self, policy: RetryPolicy) -> Self {
+        self.retries = Some(RetryConfig {
+            max_retries: policy.max_retries(),
+            base_delay_ms: Some(millis(policy.base_delay())),
+            max_delay_ms: Some(millis(policy.max_delay())),
+            max_wait_ms: Some(millis(policy.max_wait())),
+        });
+        self
+    }
+
+    /// Adds a header that is sent with every request, as with
+    /// `ClientBuilder::with_header`.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Sends requests through a proxy, as with `ClientBuilder::with_proxy`.
+    pub fn with_proxy(mut self, uri: &str) -> Self {
+        self.proxy = Some(uri.to_string());
+        self
+    }
+
+    /// The uri of the horizon server.
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// The horizon servers to fall back to, in order.
+    pub fn fallbacks(&self) -> &[String] {
+        &self.fallbacks
+    }
+
+    /// The network the horizon server belongs to, when it is configured.
+    pub fn network(&self) -> Option<Network> {
+        self.network_passphrase.as_ref().map(|passphrase| {
+            let network = Network::new(passphrase);
+            match self.friendbot_uri {
+                Some(ref uri) => network.with_friendbot(uri),
+                None => network,
+            }
+        })
+    }
+
+    /// The total time allowed for a request, when it is configured.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_ms.map(Duration::from_millis)
+    }
+
+    /// The time allowed for establishing a connection, when it is
+    /// configured.
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout_ms.map(Duration::from_millis)
+    }
+
+    /// How requests turned away by horizon are retried, when it is
+    /// configured.
+    pub fn retries(&self) -> Option<RetryPolicy> {
+        self.retries.map(|retries| {
+            let mut policy = RetryPolicy::new(retries.max_retries);
+            if let Some(delay) = retries.base_delay_ms {
+                policy = policy.with_base_delay(Duration::from_millis(delay));
+            }
+            if let Some(delay) = retries.max_delay_ms {
+                policy = policy.with_max_delay(Duration::from_millis(delay));
+            }
+            if let Some(wait) = retries.max_wait_ms {
+                policy = policy.with_max_wait(Duration::from_millis(wait));
+            }
+            policy
+        })
+    }
+
+    /// The headers sent with every request, by name.
+    pub fn headers(&self) -> &BTreeMap<String, String> {
+        &self.headers
+    }
+
+    /// The proxy requests are sent through, when one is configured.
+    pub fn proxy(&self) -> Option<&str> {
+        self.proxy.as_deref()
+    }
+}
+
+impl ::std::str::FromStr for ClientConfig {
+    type Err = serde_json::Error;
+
+    fn from_str(json: &str) -> ::std::result::Result<Self, Self::Err> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Whether a `NO_PROXY` list, of comma separated hosts and domains, covers
+/// the host of `uri`.
+fn bypasses_proxy(no_proxy: &str, uri: &str) -> bool {
+    let host = match uri.parse::<::http::Uri>() {
+        Ok(uri) => match uri.host() {
+            Some(host) => host.to_lowercase(),
+            None => return false,
+        },
+        Err(_) => return false,
+    };
+    no_proxy
+        .split(',')
+        .map(|entry| entry.trim().trim_start_matches('.').to_lowercase())
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| entry == "*" || host == entry || host.ends_with(&format!(".{}", entry)))
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1000 + u64::from(duration.subsec_millis())
+}
+
+#[cfg(test)]
+mod client_config_tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+
+    fn full() -> ClientConfig {
+        ClientConfig::new("https://horizon.example.com")
+            .with_fallback("https://horizon-backup.example.com")
+            .with_network(&Network::test())
+            .with_timeout(Duration::from_secs(30))
+            .with_connect_timeout(Duration::from_millis(2500))
+            .with_retries(RetryPolicy::new(3).with_max_wait(Duration::from_secs(5)))
+            .with_header("X-Client-Name", "payouts")
+            .with_proxy("http://proxy.internal:3128")
+    }
+
+    fn vars(vars: &[(&str, &str)]) -> Result<ClientConfig> {
+        let vars: BTreeMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        ClientConfig::from_vars(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn it_reads_a_minimal_config() {
+        let config: ClientConfig = r#"{ "uri": "http://localhost:8000" }"#.parse().unwrap();
+        assert_eq!(config, ClientConfig::new("http://localhost:8000"));
+        assert_eq!(config.network(), None);
+        assert_eq!(config.timeout(), None);
+        assert_eq!(config.retries(), None);
+        assert!(config.headers().is_empty());
+    }
+
+    #[test]
+    fn it_refuses_unknown_settings() {
+        let json = r#"{ "uri": "http://localhost:8000", "timeout": 10 }"#;
+        assert!(json.parse::<ClientConfig>().is_err());
+    }
+
+    #[test]
+    fn it_fills_in_retry_delays_that_are_left_out() {
+        let config: ClientConfig = r#"{
+            "uri": "http://localhost:8000",
+            "retries": { "max_retries": 4, "max_wait_ms": 1500 }
+        }"#
+        .parse()
+        .unwrap();
+        assert_eq!(
+            config.retries(),
+            Some(RetryPolicy::new(4).with_max_wait(Duration::from_millis(1500)))
+        );
+    }
+
+    #[test]
+    fn it_round_trips_through_json() {
+        let config = full();
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(json.parse::<ClientConfig>().unwrap(), config);
+        assert_eq!(config.network(), Some(Network::test()));
+        assert_eq!(config.connect_timeout(), Some(Duration::from_millis(2500)));
+        assert_eq!(
+            config.retries(),
+            Some(RetryPolicy::new(3).with_max_wait(Duration::from_secs(5)))
+        );
+        assert_eq!(config.headers()["X-Client-Name"], "payouts");
+    }
+
+    #[test]
+    fn it_saves_and_loads_a_file() {
+        let path = env::temp_dir().join("stellar_client_config.json");
+        full().save(&path).unwrap();
+        let loaded = ClientConfig::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), full());
+    }
+
+    #[test]
+    fn it_reads_the_environment() {
+        let config = vars(&[
+            ("STELLAR_HORIZON_URL", "https://horizon.example.com"),
+            (
+                "STELLAR_NETWORK_PASSPHRASE",
+                "Public Global Stellar Network ; September 2015",
+            ),
+            ("STELLAR_HORIZON_TIMEOUT", "30"),
+            ("STELLAR_HORIZON_CONNECT_TIMEOUT", " 5 "),
+            ("STELLAR_HORIZON_PROXY", "http://proxy.internal:3128"),
+        ])
+        .unwrap();
+        assert_eq!(config.uri(), "https://horizon.example.com");
+        assert_eq!(config.network(), Some(Network::public()));
+        assert_eq!(config.timeout(), Some(Duration::from_secs(30)));
+        assert_eq!(config.connect_timeout(), Some(Duration::from_secs(5)));
+        assert_eq!(config.proxy(), Some("http://proxy.internal:3128"));
+    }
+
+    #[test]
+    fn it_needs_the_horizon_url_in_the_environment() {
+        match vars(&[("STELLAR_HORIZON_URL", "")]).unwrap_err() {
+            Error::Config(ref reason) => assert_eq!(reason, "STELLAR_HORIZON_URL is not set"),
+            error => panic!("expected a config error, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn it_names_a_timeout_it_cannot_read() {
+        let err = vars(&[
+            ("STELLAR_HORIZON_URL", "http://localhost:8000"),
+            ("STELLAR_HORIZON_TIMEOUT", "30s"),
+        ])
+        .unwrap_err();
+        assert!(err.to_string().contains("STELLAR_HORIZON_TIMEOUT"));
+    }
+
+    #[test]
+    fn it_uses_the_usual_proxy_variables() {
+        let https = vars(&[
+            ("STELLAR_HORIZON_URL", "https://horizon.example.com"),
+            ("HTTPS_PROXY", "http://secure.proxy:3128"),
+            ("http_proxy", "http://plain.proxy:3128"),
+        ])
+        .unwrap();
+        assert_eq!(https.proxy(), Some("http://secure.proxy:3128"));
+
+        let http = vars(&[
+            ("STELLAR_HORIZON_URL", "http://horizon.example.com"),
+            ("HTTPS_PROXY", "http://secure.proxy:3128"),
+            ("http_proxy", "http://plain.proxy:3128"),
+        ])
+        .unwrap();
+        assert_eq!(http.proxy(), Some("http://plain.proxy:3128"));
+    }
+
+    #[test]
+    fn it_skips_the_proxy_for_hosts_in_no_proxy() {
+        let config = vars(&[
+            ("STELLAR_HORIZON_URL", "https://horizon.internal"),
+            ("HTTPS_PROXY", "http://secure.proxy:3128"),
+            ("NO_PROXY", "localhost, .internal"),
+        ])
+        .unwrap();
+        assert_eq!(config.proxy(), None);
+        assert!(bypasses_proxy("*", "https://horizon.stellar.org"));
+        assert!(!bypasses_proxy(
+            "stellar.org.evil",
+            "https://horizon.stellar.org"
+        ));
+    }
+}
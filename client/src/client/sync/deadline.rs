@@ -0,0 +1,259 @@
+use super::{wait, Requester, ResponseEnvelope, TRANSACTION_POLL_INTERVAL};
+use endpoint::IntoRequest;
+use error::{Error, Result};
+#[cfg(any(feature = "soroban", feature = "keys"))]
+use network::Network;
+use resources;
+use std::time::{Duration, Instant};
+
+/// A point in time by which a piece of work that takes several requests,
+/// such as paging through an endpoint or submitting a transaction and
+/// waiting for it, has to be done.
+///
+/// Each request the client makes has a timeout of its own, which bounds how
+/// long one request can take but not how long a helper making many of them
+/// can. Binding a deadline to a client gives a requester that refuses to
+/// start a request once the deadline has passed, failing with
+/// `Error::DeadlineExceeded` instead. Anything that takes a `Requester`,
+/// such as an `Iter` or the helpers, can be handed the bound client so that
+/// all the requests it makes share the one deadline.
+///
+/// A request that is already under way when the deadline passes is left to
+/// finish or run into the client's own timeout, so a deadline can be
+/// overrun by up to that timeout.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use stellar_client::endpoint::payment;
+/// use stellar_client::error::Error;
+/// use stellar_client::sync::{Client, Deadline, Iter};
+///
+/// let client = Client::horizon_test().unwrap();
+/// let bounded = Deadline::after(Duration::from_secs(30)).bind(&client);
+/// for payment in Iter::new(&bounded, payment::All::default()) {
+///     match payment {
+///         Ok(payment) => println!("{}", payment.paging_token()),
+///         Err(Error::DeadlineExceeded) => println!("out of time, carrying on later"),
+///         Err(err) => println!("failed: {}", err),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Deadline {
+    at: Instant,
+}
+
+impl Deadline {
+    /// A deadline `timeout` from now.
+    pub fn after(timeout: Duration) -> Deadline {
+        Deadline {
+            at: Instant::now() + timeout,
+        }
+    }
+
+    /// A deadline at `instant`.
+    pub fn at(instant: Instant) -> Deadline {
+        Deadline { at: instant }
+    }
+
+    /// The instant the deadline passes.
+    pub fn instant(&self) -> Instant {
+        self.at
+    }
+
+    /// The time left until the deadline, which is zero once it has passed.
+    pub fn remaining(&self) -> Duration {
+        self.at.saturating_duration_since(Instant::now())
+    }
+
+    /// Whether the deadline has passed.
+    pub fn has_passed(&self) -> bool {
+        Instant::now() >= self.at
+    }
+
+    /// Fails with `Error::DeadlineExceeded` once the deadline has passed.
+    pub fn check(&self) -> Result<()> {
+        if self.has_passed() {
+            Err(Error::DeadlineExceeded)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Binds the deadline to `client`. Binding a client that already has a
+    /// deadline keeps whichever of the two passes first.
+    pub fn bind<C>(self, client: C) -> WithDeadline<C>
+    where
+        C: Requester,
+    {
+        let deadline = match client.deadline() {
+            Some(earlier) if earlier < self => earlier,
+            _ => self,
+        };
+        WithDeadline { client, deadline }
+    }
+}
+
+/// A requester whose requests all have to start before a deadline. Made by
+/// `Deadline::bind`.
+#[derive(Debug, Clone)]
+pub struct WithDeadline<C> {
+    client: C,
+    deadline: Deadline,
+}
+
+impl<C> WithDeadline<C>
+where
+    C: Requester,
+{
+    /// The requester the deadline is bound to.
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    /// Submits a signed transaction like `Client::submit_transaction`,
+    /// with both the submission and any look up of the transaction after a
+    /// timed out submission held to the deadline. Only available with the
+    /// `keys` or `soroban` feature enabled.
+    #[cfg(any(feature = "soroban", feature = "keys"))]
+    pub fn submit_transaction(
+        &self,
+        envelope_xdr: &str,
+        network: &Network,
+    ) -> Result<resources::SubmittedTransaction> {
+        super::submit_transaction(self, envelope_xdr, network)
+    }
+
+    /// Waits for a submitted transaction to show up in horizon like
+    /// `Client::wait_for_transaction`, giving up with
+    /// `Error::TransactionTimeout` when the deadline passes.
+    pub fn wait_for_transaction(&self, hash: &str) -> Result<resources::Transaction> {
+        wait::wait_for_transaction(
+            self,
+            hash,
+            self.deadline.remaining(),
+            TRANSACTION_POLL_INTERVAL,
+        )
+    }
+}
+
+impl<C> Requester for WithDeadline<C>
+where
+    C: Requester,
+{
+    fn request<E>(&self, endpoint: E) -> Result<E::Response>
+    where
+        E: IntoRequest,
+    {
+        self.deadline.check()?;
+        self.client.request(endpoint)
+    }
+
+    fn request_page<E>(&self, endpoint: E) -> Result<E::Response>
+    where
+        E: IntoRequest,
+    {
+        self.deadline.check()?;
+        self.client.request_page(endpoint)
+    }
+
+    fn request_with_headers<E>(&self, endpoint: E) -> Result<ResponseEnvelope<E::Response>>
+    where
+        E: IntoRequest,
+    {
+        self.deadline.check()?;
+        self.client.request_with_headers(endpoint)
+    }
+
+    // Pages aren't fetched ahead, since a thread fetching them wouldn't stop
+    // at the deadline.
+
+    fn deadline(&self) -> Option<Deadline> {
+        Some(self.deadline)
+    }
+}
+
+#[cfg(test)]
+mod deadline_tests {
+    use super::*;
+    use endpoint::{asset, Limit};
+    use sync::Iter;
+    use test::MockClient;
+
+    fn page(next: &str) -> String {
+        format!(
+            r#"{{
+                "_links": {{ "next": {{ "href": "/assets?cursor={}&limit=1" }} }},
+                "_embedded": {{ "records": [{}] }}
+            }}"#,
+            next,
+            include_str!("../../../fixtures/asset.json")
+        )
+    }
+
+    #[test]
+    fn it_passes_requests_on_before_the_deadline() {
+        let client = MockClient::new().with_json("/assets*", &page("1"));
+        let bounded = Deadline::after(Duration::from_secs(60)).bind(&client);
+        assert!(bounded.request(asset::All::default()).is_ok());
+        assert!(bounded.deadline().unwrap().remaining() > Duration::from_secs(50));
+        assert_eq!(client.requests().len(), 1);
+    }
+
+    #[test]
+    fn it_refuses_requests_after_the_deadline() {
+        let client = MockClient::new().with_json("/assets*", &page("1"));
+        let bounded = Deadline::at(Instant::now()).bind(&client);
+        match bounded.request(asset::All::default()) {
+            Err(Error::DeadlineExceeded) => (),
+            other => panic!("expected the deadline to pass, got {:?}", other),
+        }
+        let mut iter = Iter::new(&bounded, asset::All::default().with_limit(1));
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+        assert!(client.requests().is_empty());
+    }
+
+    #[test]
+    fn it_keeps_the_earlier_of_two_deadlines() {
+        let client = MockClient::new();
+        let sooner = Deadline::after(Duration::from_secs(1));
+        let later = Deadline::after(Duration::from_secs(60));
+        assert_eq!(later.bind(sooner.bind(&client)).deadline(), Some(sooner));
+        assert_eq!(sooner.bind(later.bind(&client)).deadline(), Some(sooner));
+        assert_eq!(
+            Deadline::at(Instant::now()).remaining(),
+            Duration::from_secs(0)
+        );
+    }
+
+    #[test]
+    fn it_stops_waiting_for_a_transaction_at_the_deadline() {
+        let client = MockClient::new().with_response(
+            "/transactions/*",
+            404,
+            r#"{
+                "type": "https://stellar.org/horizon-errors/not_found",
+                "title": "Resource Missing",
+                "status": 404,
+                "detail": "The resource at the url requested was not found."
+            }"#,
+        );
+        let bounded = Deadline::after(Duration::from_millis(50)).bind(&client);
+        let started = Instant::now();
+        match bounded.wait_for_transaction("abc") {
+            Err(Error::TransactionTimeout(ref hash)) if hash == "abc" => (),
+            other => panic!("expected a timeout, got {:?}", other),
+        }
+        // The bound deadline cuts short a longer timeout too.
+        let bounded = Deadline::after(Duration::from_millis(50)).bind(&client);
+        let timeout = Duration::from_secs(60);
+        match wait::wait_for_transaction(&bounded, "abc", timeout, TRANSACTION_POLL_INTERVAL) {
+            Err(Error::TransactionTimeout(ref hash)) if hash == "abc" => (),
+            other => panic!("expected a timeout, got {:?}", other),
+        }
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
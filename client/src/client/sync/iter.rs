@@ -1,7 +1,10 @@
-use super::Client;
+use super::{Client, Requester};
 use endpoint::{IntoRequest, Records};
 use error::Result;
 use serde::de::DeserializeOwned;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
 use uri::TryFromUri;
 
 /// An iterator for records. Provides the ability to use the iterator
@@ -19,16 +22,36 @@ use uri::TryFromUri;
 /// let iter = Iter::new(&client, endpoint);
 /// assert_eq!(iter.take(10).count(), 10);
 /// ```
+///
+/// An iterator can also follow an endpoint, waiting for new records once it
+/// has caught up instead of ending. This is the polling equivalent of
+/// horizon's streaming, and works best with a cursor of `now`.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use stellar_client::{
+///     endpoint::{payment, Cursor},
+///     sync::{Client, Iter},
+/// };
+/// let client = Client::horizon_test().unwrap();
+/// let endpoint = payment::All::default().with_cursor("now");
+/// for payment in Iter::new(&client, endpoint).follow(Duration::from_secs(5)) {
+///     println!("{}", payment.unwrap().paging_token());
+/// }
+/// ```
 #[derive(Debug)]
-pub struct Iter<'a, T, E>
+pub struct Iter<'a, T, E, C = Client>
 where
     E: IntoRequest<Response = Records<T>> + TryFromUri + Clone,
     T: DeserializeOwned + Clone,
+    C: Requester + 'a,
 {
-    client: &'a Client,
+    client: &'a C,
     endpoint: E,
     records: Option<Records<T>>,
     state: State,
+    poll_interval: Option<Duration>,
+    pages: Option<mpsc::Receiver<Result<Records<T>>>>,
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -39,41 +62,98 @@ enum State {
     Error,
 }
 
-impl<'a, T, E> Iter<'a, T, E>
+impl<'a, T, E, C> Iter<'a, T, E, C>
+where
+    E: IntoRequest<Response = Records<T>> + TryFromUri + Clone + Send + 'static,
+    T: DeserializeOwned + Clone + Send + 'static,
+    C: Requester,
+{
+    /// Creates a new iterator for the client and endpoint that fetches pages
+    /// ahead on another thread, as many as the client was built to with
+    /// `ClientBuilder::with_prefetch`. With clients that don't prefetch it
+    /// is the same as `new`.
+    pub fn prefetching(client: &'a C, endpoint: E) -> Self {
+        let pages = client.prefetch_pages(endpoint.clone());
+        Iter {
+            pages,
+            ..Iter::new(client, endpoint)
+        }
+    }
+}
+
+impl<'a, T, E, C> Iter<'a, T, E, C>
 where
     E: IntoRequest<Response = Records<T>> + TryFromUri + Clone,
     T: DeserializeOwned + Clone,
+    C: Requester,
 {
     /// Creates a new iterator for the client and endpoint.
-    pub fn new(client: &'a Client, endpoint: E) -> Self {
+    pub fn new(client: &'a C, endpoint: E) -> Self {
         Iter {
             client,
             endpoint,
             records: None,
             state: State::Fetching,
+            poll_interval: None,
+            pages: None,
         }
     }
 
+    /// Keeps the iterator going once it runs out of records. Each time it
+    /// reaches an empty page it waits for `interval` and asks again, so the
+    /// iterator only ends on an error. An iterator that follows doesn't
+    /// fetch pages ahead.
+    pub fn follow(mut self, interval: Duration) -> Self {
+        self.poll_interval = Some(interval);
+        self.pages = None;
+        self
+    }
+
     fn fetch(&mut self) -> Result<()> {
-        // We already have records meaning we've made a request already
-        if let Some(ref records) = self.records {
-            // When we have a next link, use it for the next endpoint, otherwise
-            // return early and set state to the end of file.
-            if let Some(ref uri) = records.next() {
-                self.endpoint = E::try_from(uri)?;
-            } else {
-                self.state = State::EOF;
-                return Ok(());
+        let records = if let Some(ref pages) = self.pages {
+            match pages.recv() {
+                Ok(records) => records?,
+                // The pages ran out.
+                Err(_) => {
+                    self.state = State::EOF;
+                    return Ok(());
+                }
             }
-        }
+        } else {
+            // We already have records meaning we've made a request already
+            if let Some(ref records) = self.records {
+                // When we have a next link, use it for the next endpoint, otherwise
+                // return early and set state to the end of file.
+                if let Some(ref uri) = records.next() {
+                    #[cfg(feature = "tracing")]
+                    debug!(next = %uri, "following next page");
+                    self.endpoint = E::try_from(uri)?;
+                } else {
+                    self.state = State::EOF;
+                    return Ok(());
+                }
+            }
+            self.client.request_page(self.endpoint.clone())?
+        };
 
         // If there are records on this page, we switch to being
         // on the cache. If there aren't then we assume we are at
         // the end of the file.
-        let records = self.client.request(self.endpoint.clone())?;
+        #[cfg(feature = "tracing")]
+        debug!(records = records.records().len() as u64, "fetched page");
         if records.records().is_empty() {
             self.records = None;
-            self.state = State::EOF;
+            match self.poll_interval {
+                // Wait for new records, picking up from where the empty
+                // page says to.
+                Some(interval) => {
+                    thread::sleep(interval);
+                    if let Some(ref uri) = records.next() {
+                        self.endpoint = E::try_from(uri)?;
+                    }
+                }
+                None => self.state = State::EOF,
+            }
         } else {
             self.records = Some(records);
             self.state = State::OnCache(0);
@@ -94,10 +174,11 @@ where
     }
 }
 
-impl<'a, T, E> Iterator for Iter<'a, T, E>
+impl<'a, T, E, C> Iterator for Iter<'a, T, E, C>
 where
     E: IntoRequest<Response = Records<T>> + TryFromUri + Clone,
     T: DeserializeOwned + Clone,
+    C: Requester,
 {
     type Item = Result<T>;
 
@@ -123,11 +204,109 @@ where
     }
 }
 
+/// Fetches the pages of the endpoint on another thread, staying at most
+/// `depth` pages ahead of the receiver. The thread stops after the last page,
+/// after an error, or once the receiver is dropped.
+pub(super) fn prefetch<C, T, E>(
+    client: C,
+    mut endpoint: E,
+    depth: usize,
+) -> mpsc::Receiver<Result<Records<T>>>
+where
+    C: Requester + Send + 'static,
+    E: IntoRequest<Response = Records<T>> + TryFromUri + Clone + Send + 'static,
+    T: DeserializeOwned + Send + 'static,
+{
+    let (sender, receiver) = mpsc::sync_channel(depth);
+    thread::spawn(move || loop {
+        let page = client.request_page(endpoint.clone());
+        let next = match page {
+            Ok(ref records) if !records.records().is_empty() => {
+                records.next().map(|uri| E::try_from(uri))
+            }
+            _ => None,
+        };
+        if sender.send(page).is_err() {
+            return;
+        }
+        match next {
+            Some(Ok(next)) => endpoint = next,
+            Some(Err(err)) => {
+                let _ = sender.send(Err(err.into()));
+                return;
+            }
+            None => return,
+        }
+    });
+    receiver
+}
+
 #[cfg(test)]
 mod iterator_tests {
     use super::*;
     use endpoint::{account, asset, trade, Limit};
-    use resources::{AssetIdentifier, Transaction};
+    use resources::{Asset, AssetIdentifier, Transaction};
+    use sync::ClientBuilder;
+    use test::{TestResponse, TestServer};
+
+    // Serves each page in turn, one per connection.
+    fn serve(pages: Vec<String>) -> TestServer {
+        TestServer::new(pages.iter().map(|page| TestResponse::json(page)).collect())
+    }
+
+    // The iterator follows a next link by its path and query, on the
+    // client's own host, so the host in the links doesn't matter.
+    fn page(next: Option<&str>) -> String {
+        let links = match next {
+            Some(cursor) => format!(
+                r#""_links": {{ "next": {{ "href": "https://horizon.test/assets?cursor={}&limit=1&order=asc" }} }},"#,
+                cursor
+            ),
+            None => String::new(),
+        };
+        format!(
+            r#"{{ {} "_embedded": {{ "records": [{}] }} }}"#,
+            links,
+            include_str!("../../../fixtures/asset.json")
+        )
+    }
+
+    #[test]
+    fn it_asks_for_the_clients_page_limit() {
+        let server = serve(vec![page(None), page(None)]);
+        let client = ClientBuilder::new(server.uri())
+            .unwrap()
+            .with_page_limit(200)
+            .build()
+            .unwrap();
+        let assets: Vec<Result<Asset>> = Iter::new(&client, asset::All::default()).collect();
+        assert_eq!(assets.len(), 1);
+        assert!(server.next_request().path().contains("limit=200"));
+
+        // A limit on the endpoint wins over the client's.
+        let endpoint = asset::All::default().with_limit(3);
+        assert_eq!(Iter::new(&client, endpoint).count(), 1);
+        let request = server.next_request();
+        assert!(request.path().contains("limit=3"));
+        assert!(!request.path().contains("limit=200"));
+    }
+
+    #[test]
+    fn it_prefetches_pages_ahead() {
+        let server = serve(vec![page(Some("1")), page(Some("2")), page(None)]);
+        let client = ClientBuilder::new(server.uri())
+            .unwrap()
+            .with_prefetch(2)
+            .build()
+            .unwrap();
+        let mut iter = Iter::prefetching(&client, asset::All::default());
+        assert!(iter.next().unwrap().is_ok());
+
+        // Every page is requested while the first one is still being used.
+        let requested: Vec<_> = (0..3).map(|_| server.next_request()).collect();
+        assert!(requested[2].path().contains("cursor=2"));
+        assert_eq!(iter.count(), 2);
+    }
 
     #[test]
     fn it_can_iterate_through_records() {
@@ -152,7 +331,9 @@ mod iterator_tests {
     #[test]
     fn it_returns_one_with_error_if_request_fails() {
         let client = Client::horizon_test().unwrap();
-        let endpoint = account::Transactions::new("NOT AN ID");
+        let endpoint = account::Transactions::new(
+            "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF".into(),
+        );
         let iter = Iter::new(&client, endpoint);
         let all: Vec<Result<Transaction>> = iter.collect();
         assert_eq!(all.len(), 1);
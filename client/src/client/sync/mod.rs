@@ -14,28 +14,152 @@
 //! ```
 
 use super::{Host, HORIZON_TEST_URI, HORIZON_URI};
-use endpoint::IntoRequest;
+use endpoint::{
+    friendbot, health, operation, root, trade, Body, Capability, IntoRequest, Records, Streamable,
+};
 use error::{Error, Result};
 use http::{self, Uri};
+use network::Network;
 use reqwest;
+use resources::{self, HorizonVersion, Strictness};
+use serde::de::DeserializeOwned;
 use serde_json;
-use std::time::Duration;
+#[cfg(feature = "tracing")]
+use std::any::type_name;
+use std::io::{BufReader, Read};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+#[cfg(feature = "tracing")]
+use tracing::Level;
+use uri::TryFromUri;
 use StellarError;
 
+mod budget;
+mod builder;
+mod config;
+mod deadline;
 mod iter;
+#[cfg(feature = "tracing")]
+mod redact;
+mod resolve;
+mod response;
+mod retry;
+mod sign;
+mod status;
+mod stream;
+#[cfg(any(feature = "soroban", feature = "keys"))]
+mod submit;
+mod trades;
+mod wait;
 
+use self::budget::Budgeted;
+pub use self::builder::ClientBuilder;
+pub use self::config::ClientConfig;
+pub use self::deadline::{Deadline, WithDeadline};
 pub use self::iter::Iter;
+#[cfg(feature = "tracing")]
+use self::redact::{redact, truncate};
+pub use self::resolve::{Resolve, StaticResolver};
+pub use self::response::ResponseEnvelope;
+pub use self::retry::RetryPolicy;
+pub use self::sign::{BearerToken, SignRequest};
+pub use self::status::SyncStatus;
+pub use self::stream::{EventStream, StreamHandle};
+#[cfg(any(feature = "soroban", feature = "keys"))]
+pub(crate) use self::submit::submit_transaction;
+pub use self::trades::TradeStream;
 
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_MAX_INGEST_LAG: u32 = 10;
+const DEFAULT_FAILOVER_BACKOFF: Duration = Duration::from_secs(30);
+const TRANSACTION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Anything that can issue requests to a horizon api synchronously. The
+/// `Client` is the main implementation, but abstracting over this allows
+/// helpers and iterators to be driven by a mock in tests.
+pub trait Requester {
+    /// Issues a request for the endpoint and blocks until the response has
+    /// been parsed.
+    fn request<E>(&self, endpoint: E) -> Result<E::Response>
+    where
+        E: IntoRequest;
+
+    /// Issues a request for a page of records on behalf of an `Iter`, so
+    /// that the requester can apply its paging settings. Otherwise the same
+    /// as `request`.
+    fn request_page<E>(&self, endpoint: E) -> Result<E::Response>
+    where
+        E: IntoRequest,
+    {
+        self.request(endpoint)
+    }
+
+    /// Issues a request like `request`, and keeps the headers of the
+    /// response. Requesters that don't see the headers of their responses
+    /// return the response without any.
+    fn request_with_headers<E>(&self, endpoint: E) -> Result<ResponseEnvelope<E::Response>>
+    where
+        E: IntoRequest,
+    {
+        let resource = self.request(endpoint)?;
+        Ok(ResponseEnvelope::new(resource, http::HeaderMap::new(), ""))
+    }
+
+    /// Starts fetching the pages of the endpoint ahead of an iterator made
+    /// with `Iter::prefetching` on another thread, which sends them back in
+    /// order. Requesters that don't prefetch return `None`, and the iterator
+    /// fetches each page when it needs it.
+    fn prefetch_pages<T, E>(&self, _endpoint: E) -> Option<mpsc::Receiver<Result<Records<T>>>>
+    where
+        E: IntoRequest<Response = Records<T>> + TryFromUri + Clone + Send + 'static,
+        T: DeserializeOwned + Send + 'static,
+    {
+        None
+    }
+
+    /// The deadline the requests have to start by, for requesters made by
+    /// `Deadline::bind`. Helpers that wait between requests use it to stop
+    /// waiting in time.
+    fn deadline(&self) -> Option<Deadline> {
+        None
+    }
+}
 
 /// A client that can issue requests to a horizon api in a synchronous
 /// fashion, meaning that the functions will block until the response
 /// has been formed. The overall performance of this is slightly slower
 /// than using async but will generally be simpler to implement.
+///
+/// Connections are pooled and reused between requests. Use a `ClientBuilder`
+/// to tune the pool or to configure fallback horizon servers.
 #[derive(Debug, Clone)]
 pub struct Client {
     inner: reqwest::Client,
     host: Host,
+    fallbacks: Vec<String>,
+    network: Option<Network>,
+    max_ingest_lag: u32,
+    strictness: Strictness,
+    max_body_size: Option<u64>,
+    parse_budget: Option<Duration>,
+    page_limit: Option<u32>,
+    prefetch: usize,
+    retries: Option<RetryPolicy>,
+    resolver: Option<Arc<dyn Resolve>>,
+    signer: Option<Arc<dyn SignRequest>>,
+    #[cfg(feature = "tracing")]
+    body_log_limit: Option<usize>,
+    failover_backoff: Duration,
+    // For the host followed by each of the fallbacks, when the server last
+    // failed, if it has since the backoff started. Requests skip a server
+    // until its backoff is over. Shared between clones of the client.
+    failures: Arc<Mutex<Vec<Option<Instant>>>>,
+    // The version string from the root of the server, once it is known.
+    horizon_version: Arc<Mutex<Option<String>>>,
 }
 
 impl Client {
@@ -53,12 +177,41 @@ impl Client {
         Self::build(Host::Other(uri.to_string()))
     }
 
+    /// Constructs a client with the settings of a configuration, such as one
+    /// read from a file with `ClientConfig::load`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use stellar_client::sync::{Client, ClientConfig};
+    /// use stellar_client::Network;
+    ///
+    /// let config = ClientConfig::new("http://localhost:8000")
+    ///     .with_network(&Network::standalone("Standalone Network ; February 2017"));
+    /// let client = Client::from_config(&config).unwrap();
+    /// assert!(client.network().is_some());
+    /// ```
+    pub fn from_config(config: &ClientConfig) -> Result<Self> {
+        ClientBuilder::from_config(config)?.build()
+    }
+
+    /// Constructs a client from environment variables, as described by
+    /// `ClientConfig::from_env`. Only `STELLAR_HORIZON_URL` is required.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use stellar_client::sync::Client;
+    ///
+    /// // STELLAR_HORIZON_URL=https://horizon-testnet.stellar.org
+    /// let client = Client::from_env().unwrap();
+    /// ```
+    pub fn from_env() -> Result<Self> {
+        Self::from_config(&ClientConfig::from_env()?)
+    }
+
     fn build(host: Host) -> Result<Self> {
-        let inner = reqwest::ClientBuilder::new()
-            .timeout(Some(DEFAULT_TIMEOUT))
-            .build()
-            .expect("Http client failed to build");
-        Ok(Client { host, inner })
+        ClientBuilder::for_host(host).build()
     }
 
     /// Constructs a new stellar client connected to the horizon test network.
@@ -113,8 +266,101 @@ impl Client {
         self.host == Host::HorizonProd
     }
 
-    #[allow(dead_code)]
-    fn uri(&self) -> &str {
+    /// The network the client's horizon server belongs to, when it is
+    /// known.
+    pub fn network(&self) -> Option<&Network> {
+        self.network.as_ref()
+    }
+
+    /// How closely responses have to match the resources they are parsed
+    /// into.
+    pub fn strictness(&self) -> Strictness {
+        self.strictness
+    }
+
+    /// The release of horizon the server is running, read from the root of
+    /// the server the first time it is needed. `None` when the server
+    /// reports a version that can't be read, such as a build from source.
+    pub fn horizon_version(&self) -> Result<Option<HorizonVersion>> {
+        let mut cached = self
+            .horizon_version
+            .lock()
+            .expect("Horizon version lock was poisoned");
+        if cached.is_none() {
+            let root = self.request(root::Details)?;
+            *cached = Some(root.horizon_version().to_string());
+        }
+        Ok(cached
+            .as_ref()
+            .and_then(|version| HorizonVersion::parse(version)))
+    }
+
+    /// Checks that the server has the capability, failing with
+    /// `Error::ServerTooOld` when it is too old. A server whose version
+    /// can't be read is assumed to have it.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use stellar_client::endpoint::Capability;
+    /// use stellar_client::sync::Client;
+    ///
+    /// let client = Client::horizon_test().unwrap();
+    /// if client.require(Capability::Join).is_err() {
+    ///     println!("Fetching transactions one at a time instead");
+    /// }
+    /// ```
+    pub fn require(&self, capability: Capability) -> Result<()> {
+        match self.horizon_version()? {
+            Some(version) if !capability.is_supported_by(version) => {
+                Err(Error::ServerTooOld(capability, version))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Refuses endpoints that the server is too old to answer properly.
+    fn check_capability<E>(&self, endpoint: &E) -> Result<()>
+    where
+        E: IntoRequest,
+    {
+        match endpoint.required_capability() {
+            Some(capability) => self.require(capability),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn uri(&self) -> &str {
+        self.base(self.server_order()[0])
+    }
+
+    /// The indexes of the host followed by the fallbacks in the order a
+    /// request should try them: the servers that haven't failed within the
+    /// backoff first, then the others by how long ago they failed.
+    fn server_order(&self) -> Vec<usize> {
+        let failures = self.failures.lock().expect("Failover lock was poisoned");
+        let mut order: Vec<usize> = (0..=self.fallbacks.len()).collect();
+        order.sort_by_key(|&index| match failures.get(index).and_then(|failed| *failed) {
+            Some(failed) if failed.elapsed() < self.failover_backoff => Some(failed),
+            _ => None,
+        });
+        order
+    }
+
+    /// Records whether the server at `index` answered, starting or ending
+    /// its backoff.
+    fn record_health(&self, index: usize, healthy: bool) {
+        let mut failures = self.failures.lock().expect("Failover lock was poisoned");
+        if failures.len() <= index {
+            failures.resize(index + 1, None);
+        }
+        failures[index] = if healthy { None } else { Some(Instant::now()) };
+    }
+
+    fn base(&self, index: usize) -> &str {
+        if index > 0 {
+            return &self.fallbacks[index - 1];
+        }
         match self.host {
             Host::HorizonTest => HORIZON_TEST_URI,
             Host::HorizonProd => HORIZON_URI,
@@ -131,7 +377,7 @@ impl Client {
     /// use stellar_client::endpoint::account;
     /// let client = Client::horizon_test().unwrap();
     /// let endpoint =
-    ///     account::Details::new("GCEZWKCA5VLDNRLN3RPRJMRZOX3Z6G5CHCGSNFHEYVXM3XOJMDS674JZ");
+    ///     account::Details::new("GCEZWKCA5VLDNRLN3RPRJMRZOX3Z6G5CHCGSNFHEYVXM3XOJMDS674JZ".into());
     /// let account = client.request(endpoint).unwrap();
     /// assert_eq!(account.id(), "GCEZWKCA5VLDNRLN3RPRJMRZOX3Z6G5CHCGSNFHEYVXM3XOJMDS674JZ");
     /// ```
@@ -139,37 +385,840 @@ impl Client {
     where
         E: IntoRequest,
     {
-        let request = endpoint.into_request(&self.uri())?;
-        let request = Self::http_to_reqwest(&request);
-        let response = self.inner.execute(request)?;
-        if response.status().is_success() {
-            let resp: E::Response = serde_json::from_reader(response)?;
-            Ok(resp)
-        } else if response.status().is_client_error() {
-            let e: StellarError = serde_json::from_reader(response)?;
-            Err(Error::BadResponse(e))
+        self.request_with_id(endpoint).1
+    }
+
+    /// Issues a request like `request`, and also returns the id it was sent
+    /// with in the `X-Request-Id` header. Horizon logs the id, so it ties a
+    /// response or a failure to horizon's own record of the request. Errors
+    /// from horizon carry the id as well.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use stellar_client::sync::Client;
+    /// use stellar_client::endpoint::ledger;
+    ///
+    /// let client = Client::horizon_test().unwrap();
+    /// let (request_id, result) = client.request_with_id(ledger::Details::new(1));
+    /// if let Err(err) = result {
+    ///     println!("request {} failed: {}", request_id, err);
+    /// }
+    /// ```
+    pub fn request_with_id<E>(&self, endpoint: E) -> (String, Result<E::Response>)
+    where
+        E: IntoRequest,
+    {
+        let request_id = new_request_id();
+        let result = self.request_tagged(endpoint, &request_id, None);
+        (request_id, result)
+    }
+
+    // Sends the request tagged with `request_id`, adding `limit` to its
+    // query unless the endpoint set one itself.
+    fn request_tagged<E>(
+        &self,
+        endpoint: E,
+        request_id: &str,
+        limit: Option<u32>,
+    ) -> Result<E::Response>
+    where
+        E: IntoRequest,
+    {
+        self.check_capability(&endpoint)?;
+        let mut request = endpoint.into_request(self.uri())?;
+        if let Some(limit) = limit {
+            with_default_limit(&mut request, limit)?;
+        }
+        tag(&mut request, request_id);
+        #[cfg(feature = "tracing")]
+        let span = span!(
+            Level::DEBUG,
+            "horizon_request",
+            endpoint = type_name::<E>(),
+            method = %request.method(),
+            uri = %request.uri(),
+            request_id = request_id
+        );
+        #[cfg(feature = "tracing")]
+        let _enter = span.enter();
+
+        let response = self.send(&request)?;
+        self.parse(response)
+            .map_err(|err| err.with_request_id(request_id))
+    }
+
+    /// Issues a request like `request`, but keeps the json of the response
+    /// alongside the parsed resource. Only available with the `raw-json`
+    /// feature enabled.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use stellar_client::sync::Client;
+    /// use stellar_client::endpoint::ledger;
+    ///
+    /// let client = Client::horizon_test().unwrap();
+    /// let ledger = client.request_raw(ledger::Details::new(1)).unwrap();
+    /// assert_eq!(ledger.raw()["sequence"], ledger.sequence());
+    /// ```
+    #[cfg(feature = "raw-json")]
+    pub fn request_raw<E>(&self, endpoint: E) -> Result<resources::Raw<E::Response>>
+    where
+        E: IntoRequest,
+    {
+        self.check_capability(&endpoint)?;
+        let request_id = new_request_id();
+        let mut request = endpoint.into_request(self.uri())?;
+        tag(&mut request, &request_id);
+        let response = self.send(&request)?;
+        self.parse(response)
+            .map_err(|err| err.with_request_id(&request_id))
+    }
+
+    /// Issues a request like `request`, and keeps the headers of the response
+    /// alongside the parsed resource, such as the `Latest-Ledger` and `Date`
+    /// headers that say how fresh the data is.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use stellar_client::sync::Client;
+    /// use stellar_client::endpoint::orderbook;
+    /// use stellar_client::resources::AssetIdentifier;
+    ///
+    /// let client = Client::horizon_test().unwrap();
+    /// let endpoint =
+    ///     orderbook::Details::for_asset_pair(AssetIdentifier::native(), AssetIdentifier::native());
+    /// let orderbook = client.request_with_headers(endpoint).unwrap();
+    /// println!("{} bids as of ledger {:?}", orderbook.bids().len(), orderbook.latest_ledger());
+    /// ```
+    pub fn request_with_headers<E>(&self, endpoint: E) -> Result<ResponseEnvelope<E::Response>>
+    where
+        E: IntoRequest,
+    {
+        self.check_capability(&endpoint)?;
+        let request_id = new_request_id();
+        let mut request = endpoint.into_request(self.uri())?;
+        tag(&mut request, &request_id);
+        let response = self.send(&request)?;
+        let headers = response.headers().clone();
+        self.parse(response)
+            .map(|resource| ResponseEnvelope::new(resource, headers, &request_id))
+            .map_err(|err| err.with_request_id(&request_id))
+    }
+
+    /// Issues a request like `request`, but returns the json of a successful
+    /// response as it is instead of parsing it into the endpoint's resource.
+    /// This gets at a response that no longer matches the resource, such as
+    /// after horizon changes its schema, while the resource catches up.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use stellar_client::sync::Client;
+    /// use stellar_client::endpoint::ledger;
+    ///
+    /// let client = Client::horizon_test().unwrap();
+    /// let ledger = client.request_lenient(ledger::Details::new(1)).unwrap();
+    /// println!("{}", ledger["sequence"]);
+    /// ```
+    pub fn request_lenient<E>(&self, endpoint: E) -> Result<serde_json::Value>
+    where
+        E: IntoRequest,
+    {
+        self.check_capability(&endpoint)?;
+        let request_id = new_request_id();
+        let mut request = endpoint.into_request(self.uri())?;
+        tag(&mut request, &request_id);
+        let response = self.send(&request)?;
+        self.parse(response)
+            .map_err(|err| err.with_request_id(&request_id))
+    }
+
+    /// Streams the endpoint, returning an iterator over the events horizon
+    /// pushes for it. The connection is made once the iterator is first
+    /// advanced.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use stellar_client::endpoint::orderbook;
+    /// use stellar_client::resources::AssetIdentifier;
+    /// use stellar_client::sync::Client;
+    ///
+    /// let client = Client::horizon_test().unwrap();
+    /// let endpoint =
+    ///     orderbook::Details::for_asset_pair(AssetIdentifier::native(), AssetIdentifier::native());
+    /// let orderbook = client.stream(endpoint).unwrap().next().unwrap().unwrap();
+    /// ```
+    pub fn stream<E>(&self, endpoint: E) -> Result<EventStream<'_, E::Event>>
+    where
+        E: Streamable,
+    {
+        self.check_capability(&endpoint)?;
+        let request = endpoint.into_request(self.uri())?;
+        Ok(EventStream::new(self, request.uri().to_string()))
+    }
+
+    /// Streams the trades from the endpoint, filling in any that horizon
+    /// misses while the stream reconnects. The endpoint should be filtered
+    /// to an asset pair, which horizon needs in order to page through the
+    /// missed trades.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use stellar_client::endpoint::trade;
+    /// use stellar_client::resources::AssetIdentifier;
+    /// use stellar_client::sync::Client;
+    ///
+    /// let client = Client::horizon_test().unwrap();
+    /// let endpoint = trade::All::default()
+    ///     .with_asset_pair(AssetIdentifier::native(), AssetIdentifier::native());
+    /// let trade = client.stream_trades(endpoint).unwrap().next().unwrap().unwrap();
+    /// ```
+    pub fn stream_trades(&self, endpoint: trade::All) -> Result<TradeStream<'_>> {
+        let events = self.stream(endpoint.clone())?;
+        Ok(TradeStream::new(self, endpoint, events))
+    }
+
+    /// Checks that horizon is reachable and healthy, returning how long the
+    /// health check took. An unhealthy server results in an error.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use stellar_client::sync::Client;
+    /// let client = Client::horizon_test().unwrap();
+    /// let latency = client.ping().unwrap();
+    /// ```
+    pub fn ping(&self) -> Result<Duration> {
+        let started = Instant::now();
+        let health = self.request(health::Check)?;
+        if health.is_healthy() {
+            Ok(started.elapsed())
         } else {
-            Err(Error::ServerError)
+            Err(Error::ServerError(None))
+        }
+    }
+
+    /// Creates and funds an account with friendbot. Uses the friendbot of
+    /// the client's network when it has one, and otherwise the one served by
+    /// horizon.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use stellar_client::sync::Client;
+    /// let client = Client::horizon_test().unwrap();
+    /// let transaction = client
+    ///     .friendbot("GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM".into())
+    ///     .unwrap();
+    /// ```
+    pub fn friendbot(
+        &self,
+        account_id: resources::AccountId,
+    ) -> Result<resources::SubmittedTransaction> {
+        let endpoint = friendbot::Fund::new(account_id);
+        let endpoint = match self.network().and_then(Network::friendbot_uri) {
+            Some(uri) => endpoint.with_friendbot(uri),
+            None => endpoint,
+        };
+        self.request(endpoint)
+    }
+
+    /// Fetches the operation that caused an effect.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use stellar_client::sync::Client;
+    /// use stellar_client::endpoint::{effect, Limit};
+    ///
+    /// let client = Client::horizon_test().unwrap();
+    /// let effects = client.request(effect::All::default().with_limit(1)).unwrap();
+    /// let operation = client.parent_operation(&effects.records()[0]).unwrap();
+    /// assert_eq!(operation.id(), effects.records()[0].operation_id());
+    /// ```
+    pub fn parent_operation(&self, effect: &resources::Effect) -> Result<resources::Operation> {
+        self.request(operation::Details::new(effect.operation_id()))
+    }
+
+    /// Fetches what a link on a resource points at, as the endpoint `E`.
+    /// The link's path and query are read into the endpoint, and the request
+    /// goes to this client's server rather than the host in the href. A
+    /// templated link asks for the first page with horizon's defaults.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use stellar_client::sync::Client;
+    /// use stellar_client::endpoint::{account, operation};
+    ///
+    /// let client = Client::horizon_test().unwrap();
+    /// let account = client
+    ///     .request(account::Details::new(
+    ///         "GCEZWKCA5VLDNRLN3RPRJMRZOX3Z6G5CHCGSNFHEYVXM3XOJMDS674JZ".into(),
+    ///     ))
+    ///     .unwrap();
+    /// let link = account.links().get("operations").unwrap();
+    /// let operations = client.follow::<account::Operations>(link).unwrap();
+    /// ```
+    pub fn follow<E>(&self, link: &resources::Link) -> Result<E::Response>
+    where
+        E: IntoRequest + TryFromUri,
+    {
+        let uri = link.uri()?;
+        let endpoint = E::try_from(&uri)?;
+        self.request(endpoint)
+    }
+
+    /// Submits a signed transaction, given as its base64 encoded envelope,
+    /// and blocks until it is in a ledger.
+    ///
+    /// A submission that times out leaves it unknown whether the transaction
+    /// made it in, so before returning an error the client works out the
+    /// transaction's hash and asks horizon for it. A transaction that
+    /// succeeded in a ledger is returned, and one that failed there fails
+    /// with the same transaction_failed `Error::BadResponse`, result codes
+    /// and all, as a failed submission. One that horizon doesn't have fails
+    /// with `Error::TransactionTimeout`. It may still make it into a later
+    /// ledger, so wait for it or submit the same envelope again rather than
+    /// building a new transaction, since the same envelope can never be
+    /// applied twice.
+    ///
+    /// Working out the hash needs the network, which is known for the stellar
+    /// run servers and can be set with `ClientBuilder::with_network` for
+    /// others. Only available with the `keys` or `soroban` feature enabled.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use stellar_client::error::Error;
+    /// use stellar_client::sync::Client;
+    ///
+    /// let client = Client::horizon_test().unwrap();
+    /// # let envelope_xdr = "";
+    /// match client.submit_transaction(envelope_xdr) {
+    ///     Ok(submitted) => println!("included in ledger {}", submitted.ledger()),
+    ///     Err(Error::TransactionTimeout(hash)) => println!("{} is still pending", hash),
+    ///     Err(err) => println!("submission failed: {}", err),
+    /// }
+    /// ```
+    #[cfg(any(feature = "soroban", feature = "keys"))]
+    pub fn submit_transaction(
+        &self,
+        envelope_xdr: &str,
+    ) -> Result<resources::SubmittedTransaction> {
+        let network = self.network().ok_or(Error::UnknownNetwork)?;
+        submit::submit_transaction(self, envelope_xdr, network)
+    }
+
+    /// Waits for a submitted transaction to show up in horizon, polling for
+    /// it every second, and returns it. Gives up with a
+    /// `Error::TransactionTimeout` once `timeout` has passed.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use stellar_client::sync::Client;
+    /// use std::time::Duration;
+    ///
+    /// let client = Client::horizon_test().unwrap();
+    /// let transaction = client
+    ///     .wait_for_transaction(
+    ///         "5fef21d5ef75ecf18d65a160cfab17dca8dbf6dbc4e2fd66a510719ad8dddb09",
+    ///         Duration::from_secs(30),
+    ///     )
+    ///     .unwrap();
+    /// println!("included in ledger {}", transaction.ledger());
+    /// ```
+    pub fn wait_for_transaction(
+        &self,
+        hash: &str,
+        timeout: Duration,
+    ) -> Result<resources::Transaction> {
+        wait::wait_for_transaction(self, hash, timeout, TRANSACTION_POLL_INTERVAL)
+    }
+
+    /// Checks how far horizon's history is behind its stellar core node.
+    /// The status counts as lagging once the gap is more than the client's
+    /// maximum ingest lag, which defaults to 10 ledgers and can be set with
+    /// `ClientBuilder::with_max_ingest_lag`.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use stellar_client::sync::Client;
+    /// let client = Client::horizon_test().unwrap();
+    /// let status = client.sync_status().unwrap();
+    /// if status.is_lagging() {
+    ///     println!("horizon is {} ledgers behind", status.lag());
+    /// }
+    /// ```
+    pub fn sync_status(&self) -> Result<SyncStatus> {
+        SyncStatus::check(self, self.max_ingest_lag)
+    }
+
+    /// Issues a request and returns the raw json body of a successful
+    /// response without parsing it. This allows the body to be parsed into
+    /// types that borrow from it, such as `endpoint::RecordsRef`.
+    pub fn request_body<E>(&self, endpoint: E) -> Result<String>
+    where
+        E: IntoRequest,
+    {
+        self.check_capability(&endpoint)?;
+        let request_id = new_request_id();
+        let mut request = endpoint.into_request(self.uri())?;
+        tag(&mut request, &request_id);
+        let mut response = self.send(&request)?;
+        let retry_after = retry::retry_after(response.headers());
+        let mut body = String::new();
+        response
+            .read_to_string(&mut body)
+            .map_err(|err| Error::from(err).with_request_id(&request_id))?;
+        match response.status().as_u16() {
+            200..=299 => Ok(body),
+            status => Err(parse_error(status, body.as_bytes())
+                .with_request_id(&request_id)
+                .with_retry_after(retry_after)),
+        }
+    }
+
+    /// Sends the request and returns the status code and raw body of the
+    /// response without interpreting it.
+    pub(crate) fn execute(&self, request: &http::Request<Body>) -> Result<(u16, String)> {
+        let request_id = request_id_of(request).unwrap_or_else(new_request_id);
+        let mut response = self.send_tagged(request, &request_id)?;
+        let mut body = String::new();
+        response
+            .read_to_string(&mut body)
+            .map_err(|err| Error::from(err).with_request_id(&request_id))?;
+        Ok((response.status().as_u16(), body))
+    }
+
+    /// Parses a response as it is read, holding it to the client's size
+    /// limit and time budget. A body that declares itself too large is
+    /// turned down before any of it is read.
+    fn parse<T>(&self, response: reqwest::Response) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let status = response.status().as_u16();
+        if let (Some(max), Some(length)) = (self.max_body_size, response.content_length()) {
+            if length > max {
+                return Err(Error::BodyTooLarge(max, None));
+            }
+        }
+        let retry_after = retry::retry_after(response.headers());
+        #[cfg(feature = "tracing")]
+        {
+            if let Some(max_len) = self.body_log_limit {
+                return self
+                    .parse_logged(response, max_len)
+                    .map_err(|err| err.with_retry_after(retry_after));
+            }
+        }
+        let mut body = Budgeted::new(response, self.max_body_size, self.parse_budget);
+        let result = parse_response(status, BufReader::new(&mut body), self.strictness);
+        match body.exceeded() {
+            Some(err) => Err(err),
+            None => result.map_err(|err| err.with_retry_after(retry_after)),
+        }
+    }
+
+    /// Parses a response like `parse`, but reads the whole body first so
+    /// that it can be logged, with any credentials redacted, before parsing.
+    #[cfg(feature = "tracing")]
+    fn parse_logged<T>(&self, response: reqwest::Response, max_len: usize) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let status = response.status().as_u16();
+        let uri = redact(response.url().as_str());
+        let mut body = Budgeted::new(response, self.max_body_size, self.parse_budget);
+        let mut bytes = Vec::new();
+        if let Err(err) = body.read_to_end(&mut bytes) {
+            return Err(body.exceeded().unwrap_or_else(|| err.into()));
+        }
+        debug!(
+            uri = %uri,
+            status = status,
+            body = %truncate(&redact(&String::from_utf8_lossy(&bytes)), max_len),
+            "horizon response body"
+        );
+        parse_response(status, &bytes[..], self.strictness)
+    }
+
+    /// Sends the request without reading the body of the response, so that
+    /// large pages can be parsed as they arrive rather than buffered first.
+    ///
+    /// When the client has a retry policy, a 429 or 503 response is retried
+    /// after the wait its `Retry-After` header asks for, or after a backoff
+    /// when it doesn't say. The last response is returned once the policy
+    /// gives up.
+    fn send(&self, request: &http::Request<Body>) -> Result<reqwest::Response> {
+        // Every request goes out with an id, which stays the same when it is
+        // retried or moves on to a fallback server.
+        let request_id = request_id_of(request).unwrap_or_else(new_request_id);
+        self.send_tagged(request, &request_id)
+    }
+
+    /// Sends the request like `send`, with the given id, which any error it
+    /// fails with carries.
+    fn send_tagged(
+        &self,
+        request: &http::Request<Body>,
+        request_id: &str,
+    ) -> Result<reqwest::Response> {
+        let mut attempt = 0;
+        loop {
+            let response = self
+                .send_with_failover(request, request_id)
+                .map_err(|err| err.with_request_id(request_id))?;
+            let status = response.status().as_u16();
+            if status != 429 && status != 503 {
+                return Ok(response);
+            }
+            let retry_after = retry::retry_after(response.headers());
+            let delay = match self.retries {
+                Some(ref policy) => policy.delay(attempt, retry_after),
+                None => None,
+            };
+            match delay {
+                Some(delay) => {
+                    #[cfg(feature = "tracing")]
+                    warn!(
+                        status = status,
+                        delay_ms = delay.as_secs() * 1000 + u64::from(delay.subsec_millis()),
+                        "retrying the horizon request"
+                    );
+                    thread::sleep(delay);
+                    attempt += 1;
+                }
+                None => return Ok(response),
+            }
+        }
+    }
+
+    /// Sends the request once. When fallback servers are configured, a GET
+    /// that fails to connect or gets a 5xx response moves on to the next
+    /// server in order, and the server that failed is skipped by later
+    /// requests until the client's failover backoff is over. Other requests,
+    /// such as transaction submissions, could already have taken effect when
+    /// they fail, so they only go to the first server that isn't backing off.
+    fn send_with_failover(
+        &self,
+        request: &http::Request<Body>,
+        request_id: &str,
+    ) -> Result<reqwest::Response> {
+        let url = request.uri().to_string();
+        if self.fallbacks.is_empty() {
+            return self.send_to(request, &url, request_id);
+        }
+
+        // Find the server the request was built against so that its path can
+        // be moved over to the others.
+        let hosts = self.fallbacks.len() + 1;
+        let path = match (0..hosts)
+            .map(|index| self.base(index))
+            .filter(|base| url.starts_with(base))
+            .max_by_key(|base| base.len())
+        {
+            Some(base) => url[base.len()..].to_string(),
+            None => return self.send_to(request, &url, request_id),
+        };
+
+        let order = self.server_order();
+        if request.method() != http::Method::GET {
+            let url = format!("{}{}", self.base(order[0]), path);
+            return self.send_to(request, &url, request_id);
+        }
+        let mut attempt = 0;
+        loop {
+            let index = order[attempt];
+            let url = format!("{}{}", self.base(index), path);
+            let result = self.send_to(request, &url, request_id);
+            attempt += 1;
+
+            let healthy = match result {
+                Ok(ref response) => !response.status().is_server_error(),
+                Err(_) => false,
+            };
+            self.record_health(index, healthy);
+            if healthy || attempt == hosts {
+                return result;
+            }
+            #[cfg(feature = "tracing")]
+            warn!(
+                host = self.base(index),
+                "failing over to the next horizon server"
+            );
+        }
+    }
+
+    /// Sends the request to the server at `url`, signed when the client has
+    /// a signer. When the client has a resolver that pins the server's name,
+    /// the request goes to each of its addresses in turn until one responds.
+    fn send_to(
+        &self,
+        request: &http::Request<Body>,
+        url: &str,
+        request_id: &str,
+    ) -> Result<reqwest::Response> {
+        let signed;
+        let request = match self.signer {
+            Some(ref signer) => {
+                signed = sign::sign(&**signer, request, url)?;
+                &signed
+            }
+            None => request,
+        };
+        let pinned = match self.resolver {
+            Some(ref resolver) => resolve::pin(&**resolver, url)?,
+            None => Vec::new(),
+        };
+        let mut last = None;
+        for (pinned_url, host) in pinned {
+            match self.send_once(request, &pinned_url, Some(&host), request_id) {
+                Err(err) => last = Some(err),
+                result => return result,
+            }
+        }
+        match last {
+            Some(err) => Err(err),
+            None => self.send_once(request, url, None, request_id),
+        }
+    }
+
+    fn send_once(
+        &self,
+        request: &http::Request<Body>,
+        url: &str,
+        host: Option<&str>,
+        request_id: &str,
+    ) -> Result<reqwest::Response> {
+        #[cfg(feature = "tracing")]
+        let started = Instant::now();
+        let mut request = Self::http_to_reqwest(request, url)?;
+        request
+            .headers_mut()
+            .insert(REQUEST_ID_HEADER, request_id_value(request_id));
+        if let Some(host) = host {
+            request.headers_mut().insert(
+                http::header::HOST,
+                http::header::HeaderValue::from_str(host).map_err(http::Error::from)?,
+            );
+        }
+        let result = self.inner.execute(request).map_err(Error::from);
+
+        #[cfg(feature = "tracing")]
+        {
+            let elapsed = started.elapsed();
+            let latency_ms = elapsed.as_secs() * 1000 + u64::from(elapsed.subsec_millis());
+            match result {
+                Ok(ref response) => debug!(
+                    status = response.status().as_u16(),
+                    latency_ms = latency_ms,
+                    "horizon responded"
+                ),
+                Err(ref err) => {
+                    warn!(error = %err, latency_ms = latency_ms, "horizon request failed")
+                }
+            }
         }
+        result
     }
 
-    fn http_to_reqwest<T>(request: &http::Request<T>) -> reqwest::Request {
+    fn http_to_reqwest(request: &http::Request<Body>, url: &str) -> Result<reqwest::Request> {
         use http::method::Method;
         let method = match *request.method() {
-            Method::GET => reqwest::Method::Get,
+            Method::GET => reqwest::Method::GET,
+            Method::POST => reqwest::Method::POST,
             _ => unimplemented!(),
         };
-        // infalliable because it's already passed the more strenuous http crate
-        // url parsing.
-        let url: reqwest::Url = format!("{}", request.uri()).parse().unwrap();
-        reqwest::Request::new(method, url)
+        let url: reqwest::Url = url.parse()?;
+        let mut result = reqwest::Request::new(method, url);
+        *result.headers_mut() = request.headers().clone();
+        if let Body::Form(ref form) = *request.body() {
+            result.headers_mut().insert(
+                http::header::CONTENT_TYPE,
+                http::header::HeaderValue::from_static("application/x-www-form-urlencoded"),
+            );
+            *result.body_mut() = Some(form.clone().into());
+        }
+        Ok(result)
+    }
+}
+
+/// Makes an id for a request. Ids are unique within the process, and the
+/// time and process id make a clash with another process unlikely.
+fn new_request_id() -> String {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    format!(
+        "{:x}{:08x}-{:x}-{:x}",
+        now.as_secs(),
+        now.subsec_nanos(),
+        process::id(),
+        COUNT.fetch_add(1, Ordering::SeqCst)
+    )
+}
+
+/// Adds a `limit` to the query of the request when it doesn't have one.
+fn with_default_limit<T>(request: &mut http::Request<T>, limit: u32) -> Result<()> {
+    let uri = {
+        let query = request.uri().query();
+        if query.is_some_and(|query| query.split('&').any(|pair| pair.starts_with("limit="))) {
+            return Ok(());
+        }
+        let separator = if query.is_some() { '&' } else { '?' };
+        format!("{}{}limit={}", request.uri(), separator, limit)
+    };
+    *request.uri_mut() = uri.parse()?;
+    Ok(())
+}
+
+/// Sets the id that a request is sent with.
+/// The id a request was tagged with, if it was.
+fn request_id_of<T>(request: &http::Request<T>) -> Option<String> {
+    request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+}
+
+fn tag<T>(request: &mut http::Request<T>, request_id: &str) {
+    request
+        .headers_mut()
+        .insert(REQUEST_ID_HEADER, request_id_value(request_id));
+}
+
+// Ids are made by `new_request_id` or read back from a header, so they are
+// always valid header values.
+fn request_id_value(request_id: &str) -> http::header::HeaderValue {
+    http::header::HeaderValue::from_str(request_id).expect("Request ids are valid headers")
+}
+
+/// Interprets a raw horizon response. Successful responses are parsed into
+/// the expected resource, client errors into a `StellarError` and anything
+/// else is treated as a server error. The body is parsed as it is read.
+pub(crate) fn parse_response<T, R>(status: u16, body: R, strictness: Strictness) -> Result<T>
+where
+    T: DeserializeOwned,
+    R: Read,
+{
+    match status {
+        200..=299 => Ok(resources::from_json_reader_with(body, strictness)?),
+        _ => Err(parse_error(status, body)),
+    }
+}
+
+/// Interprets an unsuccessful horizon response.
+fn parse_error<R>(status: u16, body: R) -> Error
+where
+    R: Read,
+{
+    match status {
+        400..=499 => match serde_json::from_reader::<_, StellarError>(body) {
+            Ok(e) => Error::BadResponse(e),
+            Err(e) => e.into(),
+        },
+        503 => Error::ServiceUnavailable(None, None),
+        // Horizon answers with a timeout problem when a submitted transaction
+        // doesn't make it into a ledger in time.
+        504 => match serde_json::from_reader::<_, StellarError>(body) {
+            Ok(e) => Error::BadResponse(e),
+            Err(_) => Error::ServerError(None),
+        },
+        _ => Error::ServerError(None),
+    }
+}
+
+impl<'a, R> Requester for &'a R
+where
+    R: Requester + ?Sized,
+{
+    fn request<E>(&self, endpoint: E) -> Result<E::Response>
+    where
+        E: IntoRequest,
+    {
+        (**self).request(endpoint)
+    }
+
+    fn request_page<E>(&self, endpoint: E) -> Result<E::Response>
+    where
+        E: IntoRequest,
+    {
+        (**self).request_page(endpoint)
+    }
+
+    fn request_with_headers<E>(&self, endpoint: E) -> Result<ResponseEnvelope<E::Response>>
+    where
+        E: IntoRequest,
+    {
+        (**self).request_with_headers(endpoint)
+    }
+
+    fn prefetch_pages<T, E>(&self, endpoint: E) -> Option<mpsc::Receiver<Result<Records<T>>>>
+    where
+        E: IntoRequest<Response = Records<T>> + TryFromUri + Clone + Send + 'static,
+        T: DeserializeOwned + Send + 'static,
+    {
+        (**self).prefetch_pages(endpoint)
+    }
+
+    fn deadline(&self) -> Option<Deadline> {
+        (**self).deadline()
+    }
+}
+
+impl Requester for Client {
+    fn request<E>(&self, endpoint: E) -> Result<E::Response>
+    where
+        E: IntoRequest,
+    {
+        Client::request(self, endpoint)
+    }
+
+    fn request_page<E>(&self, endpoint: E) -> Result<E::Response>
+    where
+        E: IntoRequest,
+    {
+        self.request_tagged(endpoint, &new_request_id(), self.page_limit)
+    }
+
+    fn request_with_headers<E>(&self, endpoint: E) -> Result<ResponseEnvelope<E::Response>>
+    where
+        E: IntoRequest,
+    {
+        Client::request_with_headers(self, endpoint)
+    }
+
+    fn prefetch_pages<T, E>(&self, endpoint: E) -> Option<mpsc::Receiver<Result<Records<T>>>>
+    where
+        E: IntoRequest<Response = Records<T>> + TryFromUri + Clone + Send + 'static,
+        T: DeserializeOwned + Send + 'static,
+    {
+        match self.prefetch {
+            0 => None,
+            depth => Some(iter::prefetch(self.clone(), endpoint, depth)),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use endpoint;
     use stellar_error;
+    use test::{TestResponse, TestServer};
+
+    const ACCOUNT_ID: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
 
     #[test]
     fn it_constructs_a_test_client() {
@@ -205,7 +1254,8 @@ mod tests {
     fn it_can_make_a_request() {
         use endpoint::account::Details;
         let client = Client::horizon_test().unwrap();
-        let endpoint = Details::new("GCEZWKCA5VLDNRLN3RPRJMRZOX3Z6G5CHCGSNFHEYVXM3XOJMDS674JZ");
+        let endpoint =
+            Details::new("GCEZWKCA5VLDNRLN3RPRJMRZOX3Z6G5CHCGSNFHEYVXM3XOJMDS674JZ".into());
         let account = client.request(endpoint).unwrap();
         assert_eq!(
             account.id(),
@@ -217,10 +1267,457 @@ mod tests {
     fn it_can_make_a_failed_request() {
         use endpoint::account::Details;
         let client = Client::horizon_test().unwrap();
-        let endpoint = Details::new("LDNRLN3RPRJMRZOX3Z6G5CHCGSNFHEYVXM3XOJMDS674JZ");
+        // An account that has never been funded.
+        let endpoint =
+            Details::new("GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF".into());
         match client.request(endpoint).unwrap_err() {
             Error::BadResponse(error) => assert_eq!(error.kind(), stellar_error::Kind::NotFound),
             error => panic!("Client did not return a bad response {:?}", error),
         }
     }
+
+    // Serves a single canned json response on a local port and returns its uri.
+    fn serve_once(status: &str, body: &str) -> String {
+        serve_capturing(status, body).uri().to_string()
+    }
+
+    // Like `serve_once`, but keeps the server to look at the request.
+    fn serve_capturing(status: &str, body: &str) -> TestServer {
+        TestServer::new(vec![TestResponse::json(body).with_status(status)])
+    }
+
+    #[test]
+    fn it_tags_requests_and_errors_with_a_request_id() {
+        use endpoint::account::Details;
+
+        let server = serve_capturing(
+            "404 Not Found",
+            r#"{
+                "type": "https://stellar.org/horizon-errors/not_found",
+                "title": "Resource Missing",
+                "status": 404,
+                "detail": "The resource at the url requested was not found."
+            }"#,
+        );
+        let client = Client::new(server.uri()).unwrap();
+        let (request_id, result) = client.request_with_id(Details::new(
+            "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into(),
+        ));
+        match result {
+            Err(Error::BadResponse(err)) => assert_eq!(err.request_id(), Some(request_id.as_str())),
+            other => panic!("expected a bad response, got {:?}", other),
+        }
+        assert_eq!(
+            server.next_request().header("x-request-id"),
+            Some(request_id.as_str())
+        );
+    }
+
+    #[test]
+    fn it_tags_every_kind_of_failure_with_the_request_id() {
+        use endpoint::account::Details;
+        let client = Client::new(&TestServer::unreachable()).unwrap();
+        let (request_id, result) = client.request_with_id(Details::new(ACCOUNT_ID.into()));
+        match result {
+            Err(err @ Error::Reqwest(_, _)) => {
+                assert_eq!(err.request_id(), Some(request_id.as_str()))
+            }
+            other => panic!("expected a connection error, got {:?}", other),
+        }
+
+        let client = Client::new(&serve_once("500 Internal Server Error", "{}")).unwrap();
+        let (request_id, result) = client.request_with_id(Details::new(ACCOUNT_ID.into()));
+        match result {
+            Err(Error::ServerError(id)) => assert_eq!(id, Some(request_id)),
+            other => panic!("expected a server error, got {:?}", other),
+        }
+
+        let client = Client::new(&serve_once("200 OK", "{}")).unwrap();
+        let (request_id, result) = client.request_with_id(Details::new(ACCOUNT_ID.into()));
+        match result {
+            Err(Error::ParseResource(err)) => {
+                assert_eq!(err.request_id(), Some(request_id.as_str()))
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn it_makes_a_new_request_id_each_time() {
+        assert_ne!(new_request_id(), new_request_id());
+    }
+
+    #[test]
+    fn it_fails_over_when_a_server_is_unreachable() {
+        use endpoint::account::Details;
+        let fallback = serve_once("200 OK", include_str!("../../../fixtures/account.json"));
+        let client = ClientBuilder::new(&TestServer::unreachable())
+            .unwrap()
+            .with_fallback(&fallback)
+            .build()
+            .unwrap();
+        let account = client.request(Details::new(ACCOUNT_ID.into())).unwrap();
+        assert_eq!(account.id(), ACCOUNT_ID);
+        assert_eq!(client.uri(), fallback);
+    }
+
+    #[test]
+    fn it_fails_over_on_a_server_error() {
+        use endpoint::account::Details;
+        let primary = serve_once("503 Service Unavailable", "{}");
+        let fallback = serve_once("200 OK", include_str!("../../../fixtures/account.json"));
+        let client = ClientBuilder::new(&primary)
+            .unwrap()
+            .with_fallback(&fallback)
+            .build()
+            .unwrap();
+        let clone = client.clone();
+        assert!(client.request(Details::new(ACCOUNT_ID.into())).is_ok());
+        // Clones share which server is healthy.
+        assert_eq!(clone.uri(), fallback);
+    }
+
+    #[test]
+    fn it_tries_a_failed_server_again_once_its_backoff_is_over() {
+        use endpoint::account::Details;
+        let primary = serve_once("503 Service Unavailable", "{}");
+        let fallback = serve_once("200 OK", include_str!("../../../fixtures/account.json"));
+        let client = ClientBuilder::new(&primary)
+            .unwrap()
+            .with_fallback(&fallback)
+            .with_failover_backoff(Duration::from_secs(0))
+            .build()
+            .unwrap();
+        assert!(client.request(Details::new(ACCOUNT_ID.into())).is_ok());
+        assert_eq!(client.uri(), primary);
+    }
+
+    #[test]
+    fn it_does_not_fail_over_on_a_client_error() {
+        use endpoint::account::Details;
+        let primary = serve_once(
+            "410 Gone",
+            include_str!("../../../fixtures/before_history_error.json"),
+        );
+        let client = ClientBuilder::new(&primary)
+            .unwrap()
+            .with_fallback(&TestServer::unreachable())
+            .build()
+            .unwrap();
+        match client.request(Details::new(ACCOUNT_ID.into())).unwrap_err() {
+            Error::BadResponse(_) => (),
+            error => panic!("Client did not return a bad response {:?}", error),
+        }
+        assert_eq!(client.uri(), primary);
+    }
+
+    #[test]
+    fn it_pings_a_healthy_server() {
+        let client = Client::new(&serve_once(
+            "200 OK",
+            include_str!("../../../fixtures/health.json"),
+        ))
+        .unwrap();
+        assert!(client.ping().is_ok());
+    }
+
+    #[test]
+    fn it_follows_a_link_to_its_endpoint() {
+        use endpoint::account;
+        use resources::Account;
+
+        let account: Account =
+            serde_json::from_str(include_str!("../../../fixtures/account.json")).unwrap();
+        let server = serve_capturing(
+            "200 OK",
+            r#"{ "_links": {}, "_embedded": { "records": [] } }"#,
+        );
+        let client = Client::new(server.uri()).unwrap();
+        let link = account.links().get("transactions").unwrap();
+        let page = client.follow::<account::Transactions>(link).unwrap();
+        assert!(page.records().is_empty());
+        assert_eq!(
+            server.next_request().path(),
+            "/accounts/GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG/transactions"
+        );
+    }
+
+    #[test]
+    fn it_fails_to_follow_a_link_to_another_endpoint() {
+        use endpoint::account;
+
+        let client = Client::new(&TestServer::unreachable()).unwrap();
+        let link = resources::Link::new("/ledgers/1/transactions");
+        match client.follow::<account::Transactions>(&link).unwrap_err() {
+            Error::TryFromUri(_) => (),
+            error => panic!("expected a uri error, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn it_fails_to_ping_an_unhealthy_server() {
+        let body = r#"{ "database_connected": true, "core_up": true, "core_synced": false }"#;
+        let client = Client::new(&serve_once("200 OK", body)).unwrap();
+        match client.ping().unwrap_err() {
+            Error::ServerError(_) => (),
+            error => panic!("Client did not return a server error {:?}", error),
+        }
+    }
+
+    #[cfg(feature = "validate")]
+    #[test]
+    fn it_fails_on_unknown_fields_when_strict() {
+        let body = r#"{ "database_connected": true, "core_up": true, "core_synced": true, "ingest_up": true }"#;
+        let lenient = Client::new(&serve_once("200 OK", body)).unwrap();
+        assert!(lenient.ping().is_ok());
+
+        let strict = ClientBuilder::new(&serve_once("200 OK", body))
+            .unwrap()
+            .with_strictness(Strictness::Strict)
+            .build()
+            .unwrap();
+        match strict.ping().unwrap_err() {
+            Error::ParseResource(error) => assert_eq!(error.path(), "ingest_up"),
+            error => panic!("Client did not return a parse error {:?}", error),
+        }
+    }
+
+    #[test]
+    fn it_shows_the_body_that_failed_to_parse() {
+        let body = r#"{ "database_connected": "yes", "core_up": true, "core_synced": true }"#;
+        let client = Client::new(&serve_once("200 OK", body)).unwrap();
+        match client.ping().unwrap_err() {
+            Error::ParseResource(error) => {
+                assert_eq!(error.path(), "database_connected");
+                assert!(error.type_name().ends_with("::Health"));
+                assert_eq!(error.body(), Some(body));
+            }
+            error => panic!("Client did not return a parse error {:?}", error),
+        }
+    }
+
+    #[test]
+    fn it_requests_json_that_does_not_match_the_resource() {
+        let body = r#"{ "database_connected": "yes", "core_up": true, "core_synced": true }"#;
+        let client = Client::new(&serve_once("200 OK", body)).unwrap();
+        let health = client.request_lenient(health::Check).unwrap();
+        assert_eq!(health["database_connected"], "yes");
+    }
+
+    #[test]
+    fn it_turns_down_bodies_over_the_size_limit() {
+        let body = r#"{ "database_connected": true, "core_up": true, "core_synced": true }"#;
+        let client = ClientBuilder::new(&serve_once("200 OK", body))
+            .unwrap()
+            .with_max_body_size(16)
+            .build()
+            .unwrap();
+        match client.ping().unwrap_err() {
+            Error::BodyTooLarge(16, _) => (),
+            error => panic!("Client did not return a size error {:?}", error),
+        }
+
+        let client = ClientBuilder::new(&serve_once("200 OK", body))
+            .unwrap()
+            .with_max_body_size(body.len() as u64)
+            .build()
+            .unwrap();
+        assert!(client.ping().is_ok());
+    }
+
+    // The root endpoint, but relying on a capability that horizon only
+    // gained recently.
+    struct AsyncRoot;
+
+    impl IntoRequest for AsyncRoot {
+        type Response = resources::Root;
+
+        fn into_request(self, host: &str) -> Result<http::Request<endpoint::Body>> {
+            root::Details.into_request(host)
+        }
+
+        fn required_capability(&self) -> Option<Capability> {
+            Some(Capability::AsyncSubmission)
+        }
+    }
+
+    #[test]
+    fn it_refuses_requests_the_server_is_too_old_for() {
+        let client = Client::new(&serve_once(
+            "200 OK",
+            include_str!("../../../fixtures/root.json"),
+        ))
+        .unwrap();
+        assert_eq!(
+            client.horizon_version().unwrap(),
+            Some(HorizonVersion::new(2, 27, 0))
+        );
+        // The version is only fetched once, as the server only answers once.
+        assert!(client.require(Capability::Join).is_ok());
+        let err = client.request(AsyncRoot).unwrap_err();
+        match err {
+            Error::ServerTooOld(Capability::AsyncSubmission, version) => {
+                assert_eq!(version, HorizonVersion::new(2, 27, 0))
+            }
+            ref error => panic!("Client did not refuse the request {:?}", error),
+        }
+        assert_eq!(
+            err.to_string(),
+            "Horizon 2.27.0 is too old for async transaction submission, which needs 2.29.0 or later"
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn it_parses_responses_while_logging_bodies() {
+        let client = ClientBuilder::new(&serve_once(
+            "200 OK",
+            include_str!("../../../fixtures/health.json"),
+        ))
+        .unwrap()
+        .with_body_logging(16)
+        .build()
+        .unwrap();
+        assert!(client.ping().is_ok());
+    }
+
+    #[test]
+    fn it_returns_the_last_failure_when_every_server_fails() {
+        use endpoint::account::Details;
+        let client = ClientBuilder::new(&TestServer::unreachable())
+            .unwrap()
+            .with_fallback(&serve_once("500 Internal Server Error", "{}"))
+            .build()
+            .unwrap();
+        match client.request(Details::new(ACCOUNT_ID.into())).unwrap_err() {
+            Error::ServerError(_) => (),
+            error => panic!("Client did not return a server error {:?}", error),
+        }
+    }
+
+    #[cfg(any(feature = "soroban", feature = "keys"))]
+    #[test]
+    fn it_does_not_fail_over_a_submission() {
+        let fallback = serve_capturing(
+            "200 OK",
+            include_str!("../../../fixtures/submitted_transaction.json"),
+        );
+        let client = ClientBuilder::new(&serve_once("503 Service Unavailable", "{}"))
+            .unwrap()
+            .with_fallback(fallback.uri())
+            .with_network(Network::public())
+            .build()
+            .unwrap();
+        assert!(client.submit_transaction("AAAA").is_err());
+        assert!(fallback.received().is_empty());
+    }
+
+    #[cfg(any(feature = "soroban", feature = "keys"))]
+    #[test]
+    fn it_posts_a_transaction_to_submit() {
+        let envelope = "AAAAAH2Hmt1JWMfqAdUlDeyUtO9V8zPqJ0aLG8KrZyXv78QGAAAAZAAIgb4AAtRiAAAAAAAAAAEAAAAAAAAAAQAAAAAAAAABAAAAAJZgy/0KAk+3JQwG8hPGBNTZVGew2Joi1TwkVBdwPn9QAAAAAAAAAAA7mUNgAAAAAAAAAAHv78QGAAAAQITCXzWfgHgAjF3djx1VK9JK08UypfpftzFoyNXv7A0Agau/ur/3/+ZZtQb8xSsao8yVAsTiV4ttiT/HqfvvlAk=";
+        let server = serve_capturing(
+            "200 OK",
+            include_str!("../../../fixtures/submitted_transaction.json"),
+        );
+        let client = ClientBuilder::new(server.uri())
+            .unwrap()
+            .with_network(Network::public())
+            .build()
+            .unwrap();
+        let submitted = client.submit_transaction(envelope).unwrap();
+        assert_eq!(submitted.ledger(), 1_524_302);
+
+        let request = server.next_request();
+        assert_eq!(request.request_line(), "POST /transactions HTTP/1.1");
+        assert_eq!(
+            request.header("content-type"),
+            Some("application/x-www-form-urlencoded")
+        );
+        assert!(request.body().ends_with("tx=AAAAAH2Hmt1JWMfqAdUlDeyUtO9V8zPqJ0aLG8KrZyXv78QGAAAAZAAIgb4AAtRiAAAAAAAAAAEAAAAAAAAAAQAAAAAAAAABAAAAAJZgy%2F0KAk%2B3JQwG8hPGBNTZVGew2Joi1TwkVBdwPn9QAAAAAAAAAAA7mUNgAAAAAAAAAAHv78QGAAAAQITCXzWfgHgAjF3djx1VK9JK08UypfpftzFoyNXv7A0Agau%2Fur%2F3%2F%2BZZtQb8xSsao8yVAsTiV4ttiT%2FHqfvvlAk%3D"));
+    }
+
+    #[cfg(any(feature = "soroban", feature = "keys"))]
+    #[test]
+    fn it_needs_the_network_to_submit_a_transaction() {
+        let client = Client::new(&TestServer::unreachable()).unwrap();
+        match client.submit_transaction("AAAA").unwrap_err() {
+            Error::UnknownNetwork => (),
+            error => panic!("expected an unknown network, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn it_sends_the_configured_headers() {
+        use endpoint::account::Details;
+        let server = serve_capturing("200 OK", include_str!("../../../fixtures/account.json"));
+        let config = ClientConfig::new(server.uri()).with_header("X-Client-Name", "payouts");
+        let client = Client::from_config(&config).unwrap();
+        assert!(client.request(Details::new(ACCOUNT_ID.into())).is_ok());
+        assert_eq!(
+            server.next_request().header("x-client-name"),
+            Some("payouts")
+        );
+    }
+
+    #[test]
+    fn it_sends_requests_to_the_pinned_addresses_in_turn() {
+        use endpoint::account::Details;
+        let server = serve_capturing("200 OK", include_str!("../../../fixtures/account.json"));
+        let port = server.uri().rsplit(':').next().unwrap();
+        // Nothing listens on the first address, so the request moves on.
+        let resolver = StaticResolver::new().with_host(
+            "horizon.internal",
+            &["127.0.0.2".parse().unwrap(), "127.0.0.1".parse().unwrap()],
+        );
+        let client = ClientBuilder::new(&format!("http://horizon.internal:{}", port))
+            .unwrap()
+            .with_resolver(resolver)
+            .build()
+            .unwrap();
+        assert!(client.request(Details::new(ACCOUNT_ID.into())).is_ok());
+        assert_eq!(
+            server.next_request().header("host"),
+            Some(format!("horizon.internal:{}", port).as_str())
+        );
+    }
+
+    #[test]
+    fn it_signs_the_requests() {
+        use endpoint::account::Details;
+        let server = serve_capturing("200 OK", include_str!("../../../fixtures/account.json"));
+        let client = ClientBuilder::new(server.uri())
+            .unwrap()
+            .with_signer(BearerToken::new(|| Ok("secret".to_string())))
+            .build()
+            .unwrap();
+        assert!(client.request(Details::new(ACCOUNT_ID.into())).is_ok());
+        assert_eq!(
+            server.next_request().header("authorization"),
+            Some("Bearer secret")
+        );
+    }
+
+    #[test]
+    fn it_keeps_the_headers_of_the_response() {
+        use endpoint::account::Details;
+
+        let server = TestServer::new(vec![TestResponse::json(include_str!(
+            "../../../fixtures/account.json"
+        ))
+        .with_header("Latest-Ledger", "1524302")
+        .with_header("Date", "Wed, 21 Oct 2015 07:28:00 GMT")]);
+
+        let client = Client::new(server.uri()).unwrap();
+        let account = client
+            .request_with_headers(Details::new(ACCOUNT_ID.into()))
+            .unwrap();
+        assert_eq!(account.id(), ACCOUNT_ID);
+        assert_eq!(account.latest_ledger(), Some(1_524_302));
+        assert_eq!(
+            account.date().unwrap().to_rfc3339(),
+            "2015-10-21T07:28:00+00:00"
+        );
+        assert!(!account.request_id().is_empty());
+    }
 }
@@ -0,0 +1,167 @@
+//! Overrides how the names of horizon servers are resolved to addresses.
+use error::{Error, Result};
+use reqwest;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::net::IpAddr;
+
+/// Resolves the names of horizon servers in place of the system's resolver.
+/// This is for horizon servers that are only reachable through a service
+/// mesh, or that the system resolves to the wrong addresses because of
+/// split-horizon DNS. Set one with `ClientBuilder::with_resolver`.
+///
+/// Requests are sent to the resolved address with the `Host` header still
+/// naming the server, so virtual hosting keeps working. Only servers reached
+/// over http can be resolved this way. Sending to an https server would
+/// check its certificate against the address rather than the name, so a
+/// request to one whose name the resolver pins fails with `Error::Config`.
+pub trait Resolve: fmt::Debug + Send + Sync {
+    /// The addresses of `host`, in the order they are tried. A request moves
+    /// on to the next address when it fails without a response. An empty
+    /// list leaves the name to the system's resolver.
+    fn resolve(&self, host: &str) -> io::Result<Vec<IpAddr>>;
+}
+
+/// Pins the names of horizon servers to fixed addresses. Names that aren't
+/// pinned are left to the system's resolver.
+///
+/// ## Examples
+///
+/// ```
+/// use stellar_client::sync::{ClientBuilder, StaticResolver};
+///
+/// let resolver = StaticResolver::new().with_host(
+///     "horizon.internal",
+///     &["10.0.0.5".parse().unwrap(), "10.0.0.6".parse().unwrap()],
+/// );
+/// let client = ClientBuilder::new("http://horizon.internal:8000")
+///     .unwrap()
+///     .with_resolver(resolver)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StaticResolver {
+    hosts: HashMap<String, Vec<IpAddr>>,
+}
+
+impl StaticResolver {
+    /// A resolver that doesn't pin any names yet.
+    pub fn new() -> StaticResolver {
+        StaticResolver::default()
+    }
+
+    /// Pins `host` to `addresses`, replacing any addresses it was pinned to
+    /// before. Names are matched without regard to case.
+    pub fn with_host(mut self, host: &str, addresses: &[IpAddr]) -> Self {
+        self.hosts
+            .insert(host.to_ascii_lowercase(), addresses.to_vec());
+        self
+    }
+}
+
+impl Resolve for StaticResolver {
+    fn resolve(&self, host: &str) -> io::Result<Vec<IpAddr>> {
+        Ok(self
+            .hosts
+            .get(&host.to_ascii_lowercase())
+            .cloned()
+            .unwrap_or_default())
+    }
+}
+
+/// The urls to send a request for `url` to, each with the `Host` header it
+/// is sent with. The list is empty when the url names an address rather
+/// than a host, or when the resolver leaves the host to the system. A url
+/// over https whose host the resolver pins is refused.
+pub(crate) fn pin(resolver: &dyn Resolve, url: &str) -> Result<Vec<(String, String)>> {
+    let mut url: reqwest::Url = url.parse()?;
+    let host = match url.domain() {
+        Some(host) => host.to_string(),
+        None => return Ok(Vec::new()),
+    };
+    let authority = match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.clone(),
+    };
+
+    let addresses = resolver.resolve(&host)?;
+    if !addresses.is_empty() && url.scheme() != "http" {
+        return Err(Error::Config(format!(
+            "{} is pinned to an address, which only works over http",
+            host
+        )));
+    }
+
+    let mut pinned = Vec::new();
+    for address in addresses {
+        url.set_ip_host(address)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "cannot pin the url"))?;
+        pinned.push((url.to_string(), authority.clone()));
+    }
+    Ok(pinned)
+}
+
+#[cfg(test)]
+mod resolve_tests {
+    use super::*;
+
+    fn resolver() -> StaticResolver {
+        StaticResolver::new().with_host(
+            "Horizon.Internal",
+            &["10.0.0.5".parse().unwrap(), "::1".parse().unwrap()],
+        )
+    }
+
+    #[test]
+    fn it_resolves_pinned_hosts_regardless_of_case() {
+        let resolver = resolver();
+        assert_eq!(resolver.resolve("horizon.internal").unwrap().len(), 2);
+        assert_eq!(resolver.resolve("HORIZON.INTERNAL").unwrap().len(), 2);
+        assert!(resolver.resolve("horizon.example.com").unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_pins_a_url_to_each_address() {
+        let pinned = pin(&resolver(), "http://horizon.internal:8000/ledgers?limit=2").unwrap();
+        assert_eq!(
+            pinned,
+            vec![
+                (
+                    "http://10.0.0.5:8000/ledgers?limit=2".to_string(),
+                    "horizon.internal:8000".to_string()
+                ),
+                (
+                    "http://[::1]:8000/ledgers?limit=2".to_string(),
+                    "horizon.internal:8000".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_leaves_the_port_out_of_the_host_when_it_is_the_default() {
+        let pinned = pin(&resolver(), "http://horizon.internal/ledgers").unwrap();
+        assert_eq!(pinned[0].0, "http://10.0.0.5/ledgers");
+        assert_eq!(pinned[0].1, "horizon.internal");
+    }
+
+    #[test]
+    fn it_refuses_to_pin_a_server_over_https() {
+        match pin(&resolver(), "https://horizon.internal/ledgers").unwrap_err() {
+            Error::Config(ref reason) => assert!(reason.contains("horizon.internal")),
+            err => panic!("expected a configuration error, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn it_leaves_addresses_and_unpinned_hosts_alone() {
+        assert!(pin(&resolver(), "http://127.0.0.1:8000/")
+            .unwrap()
+            .is_empty());
+        assert!(pin(&resolver(), "https://horizon.stellar.org/")
+            .unwrap()
+            .is_empty());
+    }
+}
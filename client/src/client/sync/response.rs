@@ -0,0 +1,133 @@
+//! Keeps the headers horizon sent alongside a parsed response.
+use chrono::{DateTime, Utc};
+use http::HeaderMap;
+use std::ops::Deref;
+
+const LATEST_LEDGER_HEADER: &str = "Latest-Ledger";
+
+/// A parsed response along with the headers horizon sent it with.
+///
+/// Horizon says in the `Latest-Ledger` header which ledger it had ingested
+/// when it answered, and the `Date` header says when that was. Together they
+/// tell how fresh the data is, which matters when acting on prices or
+/// balances. The parsed resource is available through `Deref`. Use
+/// `sync::Client::request_with_headers` to fetch one.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use stellar_client::sync::Client;
+/// use stellar_client::endpoint::ledger;
+///
+/// let client = Client::horizon_test().unwrap();
+/// let response = client.request_with_headers(ledger::Details::new(1)).unwrap();
+/// println!(
+///     "ledger {} as of ledger {:?} at {:?}",
+///     response.sequence(),
+///     response.latest_ledger(),
+///     response.date()
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResponseEnvelope<T> {
+    resource: T,
+    headers: HeaderMap,
+    request_id: String,
+}
+
+impl<T> ResponseEnvelope<T> {
+    pub(crate) fn new(resource: T, headers: HeaderMap, request_id: &str) -> Self {
+        ResponseEnvelope {
+            resource,
+            headers,
+            request_id: request_id.to_string(),
+        }
+    }
+
+    /// The parsed resource.
+    pub fn resource(&self) -> &T {
+        &self.resource
+    }
+
+    /// Takes the parsed resource, dropping the headers.
+    pub fn into_resource(self) -> T {
+        self.resource
+    }
+
+    /// Every header of the response.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// The value of the header named `name`, when it was sent and is text.
+    /// Names are matched without regard to case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+
+    /// The id the request was sent with in the `X-Request-Id` header.
+    pub fn request_id(&self) -> &str {
+        &self.request_id
+    }
+
+    /// The latest ledger horizon had ingested when it answered, from the
+    /// `Latest-Ledger` header.
+    pub fn latest_ledger(&self) -> Option<u32> {
+        self.header(LATEST_LEDGER_HEADER)
+            .and_then(|value| value.trim().parse().ok())
+    }
+
+    /// When horizon answered, from the `Date` header.
+    pub fn date(&self) -> Option<DateTime<Utc>> {
+        self.header("Date")
+            .and_then(|value| DateTime::parse_from_rfc2822(value.trim()).ok())
+            .map(|date| date.with_timezone(&Utc))
+    }
+}
+
+impl<T> Deref for ResponseEnvelope<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.resource
+    }
+}
+
+#[cfg(test)]
+mod response_envelope_tests {
+    use super::*;
+    use http::header::HeaderValue;
+
+    fn envelope(headers: &[(&'static str, &'static str)]) -> ResponseEnvelope<u32> {
+        let mut map = HeaderMap::new();
+        for &(name, value) in headers {
+            map.insert(name, HeaderValue::from_static(value));
+        }
+        ResponseEnvelope::new(7, map, "request-1")
+    }
+
+    #[test]
+    fn it_reads_the_latest_ledger_and_date() {
+        let envelope = envelope(&[
+            ("latest-ledger", "1524302"),
+            ("date", "Wed, 21 Oct 2015 07:28:00 GMT"),
+        ]);
+        assert_eq!(*envelope, 7);
+        assert_eq!(envelope.latest_ledger(), Some(1_524_302));
+        assert_eq!(
+            envelope.date().unwrap().to_rfc3339(),
+            "2015-10-21T07:28:00+00:00"
+        );
+        assert_eq!(envelope.header("Latest-Ledger"), Some("1524302"));
+        assert_eq!(envelope.request_id(), "request-1");
+    }
+
+    #[test]
+    fn it_leaves_out_headers_that_are_missing_or_malformed() {
+        let envelope = envelope(&[("latest-ledger", "soon"), ("date", "yesterday")]);
+        assert_eq!(envelope.latest_ledger(), None);
+        assert_eq!(envelope.date(), None);
+        assert_eq!(envelope.header("x-missing"), None);
+        assert_eq!(envelope.into_resource(), 7);
+    }
+}
@@ -0,0 +1,311 @@
+//! Retrying requests that horizon turns away because it is rate limiting the
+//! client or is temporarily unavailable.
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::time::Duration;
+
+/// How a client retries requests that horizon answers with a 429 or a 503.
+///
+/// When the response has a `Retry-After` header the client waits exactly as
+/// long as it asks before trying again. A `Retry-After` longer than the
+/// policy's maximum wait is not waited out, and the error is returned with
+/// the wait on it instead. Responses without the header are retried after an
+/// exponential backoff, which starts at the base delay and doubles with each
+/// attempt up to the maximum delay.
+///
+/// ## Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use stellar_client::sync::{ClientBuilder, RetryPolicy};
+///
+/// let policy = RetryPolicy::new(3)
+///     .with_base_delay(Duration::from_millis(250))
+///     .with_max_wait(Duration::from_secs(10));
+/// assert_eq!(policy.delay(0, Some(Duration::from_secs(2))), Some(Duration::from_secs(2)));
+/// assert_eq!(policy.delay(1, None), Some(Duration::from_millis(500)));
+/// assert_eq!(policy.delay(3, None), None);
+///
+/// let client = ClientBuilder::horizon_test()
+///     .with_retries(policy)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    max_wait: Duration,
+}
+
+impl RetryPolicy {
+    /// Retries a request up to `max_retries` times. The backoff starts at
+    /// half a second and is capped at 30 seconds, and a `Retry-After` of up
+    /// to a minute is waited out.
+    pub fn new(max_retries: u32) -> Self {
+        RetryPolicy {
+            max_retries,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            max_wait: Duration::from_secs(60),
+        }
+    }
+
+    /// Sets the delay before the first retry of a response that doesn't say
+    /// when to try again.
+    pub fn with_base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Sets the longest the backoff grows to.
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Sets the longest `Retry-After` that the client will wait out.
+    pub fn with_max_wait(mut self, wait: Duration) -> Self {
+        self.max_wait = wait;
+        self
+    }
+
+    /// The most times a request is retried.
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// The delay before the first retry of a response that doesn't say when
+    /// to try again.
+    pub fn base_delay(&self) -> Duration {
+        self.base_delay
+    }
+
+    /// The longest the backoff grows to.
+    pub fn max_delay(&self) -> Duration {
+        self.max_delay
+    }
+
+    /// The longest `Retry-After` that the client will wait out.
+    pub fn max_wait(&self) -> Duration {
+        self.max_wait
+    }
+
+    /// How long to wait before retry number `attempt`, counting from 0,
+    /// given the `Retry-After` of the response if it had one. Returns `None`
+    /// when the request should not be retried.
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if attempt >= self.max_retries {
+            return None;
+        }
+        match retry_after {
+            Some(wait) if wait > self.max_wait => None,
+            Some(wait) => Some(wait),
+            None => Some(self.backoff(attempt)),
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        1u32.checked_shl(attempt)
+            .and_then(|factor| self.base_delay.checked_mul(factor))
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay))
+    }
+}
+
+/// Reads the `Retry-After` header of a response, which is either a number
+/// of seconds or the date to try again after.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value.trim(), Utc::now())
+}
+
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    // A date that has already passed means the request can be retried now.
+    Some(
+        at.with_timezone(&Utc)
+            .signed_duration_since(now)
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod retry_tests {
+    use super::super::{Client, ClientBuilder};
+    use super::*;
+    use endpoint::account::Details;
+    use error::Error;
+    use test::{TestResponse, TestServer};
+
+    const ACCOUNT_ID: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+
+    // Turns a request away with the given status, saying how long to wait
+    // when `retry_after` is given.
+    fn refused(status: &str, retry_after: Option<&str>) -> TestResponse {
+        let response = TestResponse::json(include_str!("../../../fixtures/rate_limit_error.json"))
+            .with_status(status);
+        match retry_after {
+            Some(wait) => response.with_header("Retry-After", wait),
+            None => response,
+        }
+    }
+
+    fn account() -> TestResponse {
+        TestResponse::json(include_str!("../../../fixtures/account.json"))
+    }
+
+    fn client(uri: &str, policy: RetryPolicy) -> Client {
+        ClientBuilder::new(uri)
+            .unwrap()
+            .with_retries(policy)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn it_waits_exactly_as_long_as_retry_after_asks() {
+        let policy = RetryPolicy::new(2).with_base_delay(Duration::from_secs(30));
+        assert_eq!(
+            policy.delay(0, Some(Duration::from_secs(7))),
+            Some(Duration::from_secs(7))
+        );
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(0))),
+            Some(Duration::from_secs(0))
+        );
+        assert_eq!(policy.delay(2, Some(Duration::from_secs(1))), None);
+    }
+
+    #[test]
+    fn it_gives_up_on_a_retry_after_longer_than_the_max_wait() {
+        let policy = RetryPolicy::new(5).with_max_wait(Duration::from_secs(10));
+        assert_eq!(policy.delay(0, Some(Duration::from_secs(11))), None);
+        assert_eq!(
+            policy.delay(0, Some(Duration::from_secs(10))),
+            Some(Duration::from_secs(10))
+        );
+    }
+
+    #[test]
+    fn it_backs_off_exponentially_without_retry_after() {
+        let policy = RetryPolicy::new(40)
+            .with_base_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_secs(1));
+        assert_eq!(policy.delay(0, None), Some(Duration::from_millis(100)));
+        assert_eq!(policy.delay(1, None), Some(Duration::from_millis(200)));
+        assert_eq!(policy.delay(3, None), Some(Duration::from_millis(800)));
+        assert_eq!(policy.delay(4, None), Some(Duration::from_secs(1)));
+        assert_eq!(policy.delay(39, None), Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn it_parses_retry_after_as_seconds_or_a_date() {
+        let now = DateTime::parse_from_rfc2822("Wed, 21 Oct 2015 07:28:00 GMT")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+            Some(Duration::from_secs(0))
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+        assert_eq!(parse_retry_after("-5", now), None);
+    }
+
+    #[test]
+    fn it_puts_retry_after_on_a_rate_limit_error() {
+        let server = TestServer::new(vec![refused("429 Too Many Requests", Some("42"))]);
+        let client = Client::new(server.uri()).unwrap();
+        let err = client.request(Details::new(ACCOUNT_ID.into())).unwrap_err();
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(42)));
+        match err {
+            Error::BadResponse(ref inner) => {
+                assert!(inner.is_rate_limit_exceeded());
+                assert_eq!(inner.retry_after(), Some(Duration::from_secs(42)));
+            }
+            ref error => panic!("expected a rate limit error, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn it_puts_retry_after_on_an_unavailable_error() {
+        let server = TestServer::new(vec![refused("503 Service Unavailable", Some("5"))]);
+        let client = Client::new(server.uri()).unwrap();
+        match client.request(Details::new(ACCOUNT_ID.into())).unwrap_err() {
+            Error::ServiceUnavailable(Some(wait), _) => assert_eq!(wait, Duration::from_secs(5)),
+            error => panic!("expected an unavailable error, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn it_retries_after_the_wait_horizon_asks_for() {
+        let server = TestServer::new(vec![
+            refused("429 Too Many Requests", Some("1")),
+            refused("503 Service Unavailable", Some("0")),
+            account(),
+        ]);
+        // The backoff would wait far longer, so the requests only come in
+        // this quickly if Retry-After is honored.
+        let policy = RetryPolicy::new(2).with_base_delay(Duration::from_secs(30));
+        let account = client(server.uri(), policy)
+            .request(Details::new(ACCOUNT_ID.into()))
+            .unwrap();
+        assert_eq!(account.id(), ACCOUNT_ID);
+
+        let first = server.next_request().received_at();
+        let second = server.next_request().received_at();
+        let third = server.next_request().received_at();
+        let waited = second.duration_since(first);
+        assert!(waited >= Duration::from_secs(1) && waited < Duration::from_secs(5));
+        assert!(third.duration_since(second) < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn it_backs_off_when_horizon_does_not_say_how_long() {
+        let server = TestServer::new(vec![refused("503 Service Unavailable", None), account()]);
+        let policy = RetryPolicy::new(1).with_base_delay(Duration::from_millis(200));
+        let account = client(server.uri(), policy)
+            .request(Details::new(ACCOUNT_ID.into()))
+            .unwrap();
+        assert_eq!(account.id(), ACCOUNT_ID);
+
+        let first = server.next_request().received_at();
+        let second = server.next_request().received_at();
+        assert!(second.duration_since(first) >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn it_returns_the_error_once_the_retries_run_out() {
+        let server = TestServer::new(vec![
+            refused("429 Too Many Requests", Some("0")),
+            refused("429 Too Many Requests", Some("0")),
+        ]);
+        let err = client(server.uri(), RetryPolicy::new(1))
+            .request(Details::new(ACCOUNT_ID.into()))
+            .unwrap_err();
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(0)));
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn it_does_not_wait_out_a_retry_after_beyond_the_max_wait() {
+        let server = TestServer::new(vec![refused("429 Too Many Requests", Some("3600"))]);
+        let err = client(server.uri(), RetryPolicy::new(3))
+            .request(Details::new(ACCOUNT_ID.into()))
+            .unwrap_err();
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(3600)));
+        assert_eq!(server.requests().len(), 1);
+    }
+}
@@ -0,0 +1,184 @@
+//! Authenticates the requests sent to horizon servers that require it.
+use endpoint::Body;
+use error::Result;
+use http;
+use std::fmt;
+use std::io;
+
+/// Signs the requests a client sends, for hosted horizon servers that
+/// authenticate every request. A signer can set headers from anything in the
+/// request, such as an HMAC of its method, path and date, or a token that it
+/// refreshes as it expires. Set one with `ClientBuilder::with_signer`.
+///
+/// Requests are signed each time they are sent, so a request that is retried
+/// or moves on to a fallback server is signed again with the uri of the
+/// server it goes to. A request the signer fails on is not sent.
+///
+/// ## Examples
+///
+/// ```
+/// extern crate http;
+/// extern crate stellar_client;
+///
+/// use stellar_client::endpoint::Body;
+/// use stellar_client::sync::{ClientBuilder, SignRequest};
+/// use std::io;
+///
+/// #[derive(Debug)]
+/// struct ApiKey(String);
+///
+/// impl SignRequest for ApiKey {
+///     fn sign(&self, request: &mut http::Request<Body>) -> io::Result<()> {
+///         let value = http::header::HeaderValue::from_str(&self.0)
+///             .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+///         request.headers_mut().insert("X-Api-Key", value);
+///         Ok(())
+///     }
+/// }
+///
+/// # fn main() {
+/// let client = ClientBuilder::new("https://horizon.example.com")
+///     .unwrap()
+///     .with_signer(ApiKey("key".to_string()))
+///     .build()
+///     .unwrap();
+/// # }
+/// ```
+pub trait SignRequest: fmt::Debug + Send + Sync {
+    /// Signs `request`, usually by adding headers to it, just before it is
+    /// sent.
+    fn sign(&self, request: &mut http::Request<Body>) -> io::Result<()>;
+}
+
+/// Sends a bearer token in the `Authorization` header of every request.
+/// The token comes from a callback that is called for each request, so the
+/// callback can refresh the token when it expires.
+///
+/// ## Examples
+///
+/// ```
+/// use stellar_client::sync::{BearerToken, ClientBuilder};
+///
+/// let client = ClientBuilder::new("https://horizon.example.com")
+///     .unwrap()
+///     .with_signer(BearerToken::new(|| Ok("token".to_string())))
+///     .build()
+///     .unwrap();
+/// ```
+pub struct BearerToken<F> {
+    token: F,
+}
+
+impl<F> BearerToken<F>
+where
+    F: Fn() -> io::Result<String> + Send + Sync,
+{
+    /// Sends the token that `token` returns with each request.
+    pub fn new(token: F) -> BearerToken<F> {
+        BearerToken { token }
+    }
+}
+
+// The token is left out so that it doesn't end up in logs.
+impl<F> fmt::Debug for BearerToken<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BearerToken").finish()
+    }
+}
+
+impl<F> SignRequest for BearerToken<F>
+where
+    F: Fn() -> io::Result<String> + Send + Sync,
+{
+    fn sign(&self, request: &mut http::Request<Body>) -> io::Result<()> {
+        let value = http::header::HeaderValue::from_str(&format!("Bearer {}", (self.token)()?))
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        request
+            .headers_mut()
+            .insert(http::header::AUTHORIZATION, value);
+        Ok(())
+    }
+}
+
+/// A copy of `request` addressed to `url` and signed by `signer`.
+pub(crate) fn sign(
+    signer: &dyn SignRequest,
+    request: &http::Request<Body>,
+    url: &str,
+) -> Result<http::Request<Body>> {
+    let mut signed = http::Request::builder()
+        .method(request.method().clone())
+        .uri(url)
+        .body(request.body().clone())?;
+    *signed.headers_mut() = request.headers().clone();
+    signer.sign(&mut signed)?;
+    Ok(signed)
+}
+
+#[cfg(test)]
+mod sign_tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct PathSigner;
+
+    impl SignRequest for PathSigner {
+        fn sign(&self, request: &mut http::Request<Body>) -> io::Result<()> {
+            let value = format!("{} {}", request.method(), request.uri().path());
+            request
+                .headers_mut()
+                .insert("X-Signature", value.parse().unwrap());
+            Ok(())
+        }
+    }
+
+    fn request() -> http::Request<Body> {
+        http::Request::post("http://horizon.example.com/transactions")
+            .header("X-Client-Name", "payouts")
+            .body(Body::Form("tx=AAAA".to_string()))
+            .unwrap()
+    }
+
+    #[test]
+    fn it_signs_a_copy_addressed_to_the_url() {
+        let signed = sign(
+            &PathSigner,
+            &request(),
+            "http://horizon-backup.example.com/transactions",
+        )
+        .unwrap();
+        assert_eq!(signed.method(), http::Method::POST);
+        assert_eq!(signed.uri().host(), Some("horizon-backup.example.com"));
+        assert_eq!(signed.headers()["X-Client-Name"], "payouts");
+        assert_eq!(signed.headers()["X-Signature"], "POST /transactions");
+        match *signed.body() {
+            Body::Form(ref form) => assert_eq!(form, "tx=AAAA"),
+            Body::None => panic!("expected the form to be kept"),
+        }
+    }
+
+    #[test]
+    fn it_sends_a_fresh_bearer_token() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let refreshes = AtomicUsize::new(0);
+        let signer = BearerToken::new(move || {
+            Ok(format!(
+                "token-{}",
+                refreshes.fetch_add(1, Ordering::SeqCst)
+            ))
+        });
+        let url = "http://horizon.example.com/transactions";
+        let first = sign(&signer, &request(), url).unwrap();
+        let second = sign(&signer, &request(), url).unwrap();
+        assert_eq!(first.headers()["Authorization"], "Bearer token-0");
+        assert_eq!(second.headers()["Authorization"], "Bearer token-1");
+    }
+
+    #[test]
+    fn it_fails_when_the_token_cannot_be_had() {
+        let signer = BearerToken::new(|| Err(io::Error::new(io::ErrorKind::Other, "expired")));
+        let result = sign(&signer, &request(), "http://horizon.example.com/");
+        assert!(result.is_err());
+        assert_eq!(format!("{:?}", signer), "BearerToken");
+    }
+}
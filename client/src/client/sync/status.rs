@@ -0,0 +1,88 @@
+use super::Requester;
+use endpoint::root;
+use error::Result;
+
+/// How far horizon's history is behind the stellar core node it ingests
+/// from, as reported by the root of the server.
+///
+/// Horizon only answers for the ledgers in its history, so while it is
+/// lagging, recently submitted transactions and the balances they changed
+/// will not show up yet. A lag of a ledger or two is normal while a ledger
+/// is being ingested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncStatus {
+    history_latest_ledger: u32,
+    core_latest_ledger: u32,
+    max_lag: u32,
+}
+
+impl SyncStatus {
+    /// Fetches the root of the server behind `client` and compares its
+    /// latest ledgers. Horizon is considered to be lagging once its history
+    /// is more than `max_lag` ledgers behind core.
+    pub fn check<C>(client: &C, max_lag: u32) -> Result<SyncStatus>
+    where
+        C: Requester + ?Sized,
+    {
+        let root = client.request(root::Details)?;
+        Ok(SyncStatus {
+            history_latest_ledger: root.history_latest_ledger(),
+            core_latest_ledger: root.core_latest_ledger(),
+            max_lag,
+        })
+    }
+
+    /// The latest ledger in horizon's history.
+    pub fn history_latest_ledger(&self) -> u32 {
+        self.history_latest_ledger
+    }
+
+    /// The latest ledger closed by stellar core.
+    pub fn core_latest_ledger(&self) -> u32 {
+        self.core_latest_ledger
+    }
+
+    /// How many ledgers horizon's history is behind core.
+    pub fn lag(&self) -> u32 {
+        self.core_latest_ledger
+            .saturating_sub(self.history_latest_ledger)
+    }
+
+    /// The most ledgers horizon may be behind before it counts as lagging.
+    pub fn max_lag(&self) -> u32 {
+        self.max_lag
+    }
+
+    /// Whether horizon is further behind core than is allowed.
+    pub fn is_lagging(&self) -> bool {
+        self.lag() > self.max_lag
+    }
+}
+
+#[cfg(test)]
+mod sync_status_tests {
+    use super::*;
+    use test::MockClient;
+
+    fn client() -> MockClient {
+        MockClient::new().with_json("/", include_str!("../../../fixtures/root.json"))
+    }
+
+    #[test]
+    fn it_compares_history_with_core() {
+        let client = client();
+        let status = SyncStatus::check(&client, 10).unwrap();
+        assert_eq!(status.history_latest_ledger(), 1_524_298);
+        assert_eq!(status.core_latest_ledger(), 1_524_302);
+        assert_eq!(status.lag(), 4);
+        assert!(!status.is_lagging());
+        client.assert_requested("/");
+    }
+
+    #[test]
+    fn it_is_lagging_beyond_the_threshold() {
+        let status = SyncStatus::check(&client(), 3).unwrap();
+        assert_eq!(status.max_lag(), 3);
+        assert!(status.is_lagging());
+    }
+}
@@ -0,0 +1,481 @@
+use super::{new_request_id, parse_error, retry, Client, REQUEST_ID_HEADER};
+use endpoint::Body;
+use error::Result;
+use helpers::cursor::CursorStore;
+use http::{header, Request};
+use reqwest;
+use resources;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::io::{self, BufRead, BufReader};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+// How long to wait before reconnecting when horizon hasn't said otherwise.
+const DEFAULT_RETRY: Duration = Duration::from_secs(1);
+
+/// An iterator over the events horizon streams for an endpoint, each parsed
+/// into the endpoint's event resource. Create one with `Client::stream`.
+///
+/// Horizon closes streams from time to time. When that happens the stream
+/// reconnects, passing along the id of the last event it saw so that horizon
+/// carries on from there, and the iterator keeps going. An error connecting
+/// ends the iteration after it has been returned.
+///
+/// # Examples
+///
+/// ```no_run
+/// use stellar_client::endpoint::orderbook;
+/// use stellar_client::resources::AssetIdentifier;
+/// use stellar_client::sync::Client;
+///
+/// let client = Client::horizon_test().unwrap();
+/// let endpoint = orderbook::Details::for_asset_pair(
+///     AssetIdentifier::native(),
+///     AssetIdentifier::alphanum4(
+///         "USD",
+///         "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG",
+///     ).unwrap(),
+/// );
+/// for orderbook in client.stream(endpoint).unwrap() {
+///     println!("{} bids", orderbook.unwrap().bids().len());
+/// }
+/// ```
+pub struct EventStream<'a, T> {
+    client: &'a Client,
+    url: String,
+    last_event_id: Option<String>,
+    retry: Duration,
+    reader: Option<BufReader<reqwest::Response>>,
+    connections: usize,
+    failed: bool,
+    shutdown: Arc<AtomicBool>,
+    cursor_store: Option<(Box<dyn CursorStore + 'a>, String)>,
+    event: PhantomData<T>,
+}
+
+/// Stops an `EventStream` from another thread, for instance when a service
+/// is asked to terminate during a deploy. Get one with `EventStream::handle`.
+///
+/// The stream notices the shutdown the next time it is advanced or horizon
+/// sends it something, which horizon does at least every few seconds to keep
+/// the connection alive. It then closes the connection, saves its cursor if
+/// it has a cursor store, and ends.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::thread;
+/// use stellar_client::endpoint::{account, Cursor};
+/// use stellar_client::helpers::cursor::{CursorStore, FileCursorStore};
+/// use stellar_client::sync::Client;
+///
+/// let client = Client::horizon_test().unwrap();
+/// let store = FileCursorStore::open("cursors.json").unwrap();
+/// let endpoint = account::Operations::new(
+///     "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into(),
+/// );
+/// let endpoint = store.resume("operations", endpoint).unwrap();
+/// let stream = client
+///     .stream(endpoint)
+///     .unwrap()
+///     .with_cursor_store(&store, "operations");
+///
+/// let handle = stream.handle();
+/// thread::spawn(move || {
+///     // Wait for a signal to terminate, then
+///     handle.shutdown();
+/// });
+/// for operation in stream {
+///     println!("{}", operation.unwrap().id());
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StreamHandle {
+    shutdown: Arc<AtomicBool>,
+}
+
+impl StreamHandle {
+    /// Asks the stream to close its connection and end.
+    pub fn shutdown(&self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether the stream has been asked to shut down.
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown.load(Ordering::SeqCst)
+    }
+}
+
+impl<'a, T> EventStream<'a, T>
+where
+    T: DeserializeOwned,
+{
+    pub(super) fn new(client: &'a Client, url: String) -> Self {
+        EventStream {
+            client,
+            url,
+            last_event_id: None,
+            retry: DEFAULT_RETRY,
+            reader: None,
+            connections: 0,
+            failed: false,
+            shutdown: Arc::new(AtomicBool::new(false)),
+            cursor_store: None,
+            event: PhantomData,
+        }
+    }
+
+    /// Saves the id of the last event to the store under `key` when the
+    /// stream is shut down through its handle. Resume the endpoint from the
+    /// same key to pick up from there on the next run.
+    pub fn with_cursor_store<S>(mut self, store: S, key: &str) -> Self
+    where
+        S: CursorStore + 'a,
+    {
+        self.cursor_store = Some((Box::new(store), key.to_string()));
+        self
+    }
+
+    /// A handle that shuts the stream down from another thread.
+    pub fn handle(&self) -> StreamHandle {
+        StreamHandle {
+            shutdown: Arc::clone(&self.shutdown),
+        }
+    }
+
+    /// The id of the last event horizon sent, which is where the stream
+    /// picks up from when it reconnects.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref()
+    }
+
+    /// How many times the stream has connected, counting the first time.
+    pub(super) fn connections(&self) -> usize {
+        self.connections
+    }
+
+    /// Closes the connection and saves the cursor. The stream ends after
+    /// this, returning the error first if saving failed.
+    fn close(&mut self) -> Option<Result<T>> {
+        self.reader = None;
+        self.failed = true;
+        let saved = match (&self.cursor_store, &self.last_event_id) {
+            (Some((store, key)), Some(id)) => store.save(key, id),
+            _ => Ok(()),
+        };
+        saved.err().map(Err)
+    }
+
+    fn connect(&self) -> Result<BufReader<reqwest::Response>> {
+        let request_id = new_request_id();
+        let mut request = Request::get(self.url.as_str());
+        request.header(header::ACCEPT, "text/event-stream");
+        request.header(REQUEST_ID_HEADER, request_id.as_str());
+        if let Some(ref id) = self.last_event_id {
+            request.header("Last-Event-ID", id.as_str());
+        }
+        let response = self.client.send(&request.body(Body::None)?)?;
+        match response.status().as_u16() {
+            200..=299 => Ok(BufReader::new(response)),
+            status => {
+                let retry_after = retry::retry_after(response.headers());
+                Err(parse_error(status, response)
+                    .with_request_id(&request_id)
+                    .with_retry_after(retry_after))
+            }
+        }
+    }
+}
+
+impl<'a, T> Iterator for EventStream<'a, T>
+where
+    T: DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        loop {
+            if self.shutdown.load(Ordering::SeqCst) {
+                return self.close();
+            }
+            if self.reader.is_none() {
+                match self.connect() {
+                    Ok(reader) => {
+                        self.reader = Some(reader);
+                        self.connections += 1;
+                    }
+                    Err(err) => {
+                        self.failed = true;
+                        return Some(Err(err));
+                    }
+                }
+            }
+
+            let message = match read_message(self.reader.as_mut().unwrap()) {
+                Ok(Some(message)) => message,
+                // Horizon closed the stream or it went quiet for longer than
+                // the client's timeout, so pick it up again.
+                Ok(None) | Err(_) => {
+                    #[cfg(feature = "tracing")]
+                    debug!(url = %self.url, "reconnecting to event stream");
+                    self.reader = None;
+                    thread::sleep(self.retry);
+                    continue;
+                }
+            };
+            if let Some(retry) = message.retry {
+                self.retry = Duration::from_millis(retry);
+            }
+            if message.id.is_some() {
+                self.last_event_id = message.id;
+            }
+            // Horizon opens every stream with a greeting and reports its
+            // own errors as named events. Only unnamed events are records.
+            if !message.event.is_empty() && message.event != "message" || message.data.is_empty() {
+                continue;
+            }
+            return Some(
+                resources::from_json_reader_with(message.data.as_bytes(), self.client.strictness())
+                    .map_err(Into::into),
+            );
+        }
+    }
+}
+
+impl<'a, T> fmt::Debug for EventStream<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EventStream")
+            .field("url", &self.url)
+            .field("last_event_id", &self.last_event_id)
+            .field("retry", &self.retry)
+            .field("connections", &self.connections)
+            .field("failed", &self.failed)
+            .field("shutdown", &self.shutdown)
+            .finish()
+    }
+}
+
+/// A single server sent event.
+#[derive(Debug, Default, PartialEq)]
+struct Message {
+    id: Option<String>,
+    event: String,
+    data: String,
+    retry: Option<u64>,
+}
+
+/// Reads the next event from the stream, or `None` once it has closed. A
+/// partial event at the end of the stream is dropped.
+fn read_message<R>(reader: &mut R) -> io::Result<Option<Message>>
+where
+    R: BufRead,
+{
+    let mut message = Message::default();
+    let mut seen = false;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end_matches(&['\n', '\r'][..]);
+        if line.is_empty() {
+            if seen {
+                return Ok(Some(message));
+            }
+            continue;
+        }
+        // Lines starting with a colon are comments, which keep the
+        // connection alive.
+        if line.starts_with(':') {
+            continue;
+        }
+
+        let (field, value) = match line.find(':') {
+            Some(index) => {
+                let value = &line[index + 1..];
+                (&line[..index], value.strip_prefix(' ').unwrap_or(value))
+            }
+            None => (line, ""),
+        };
+        seen = true;
+        match field {
+            "id" => message.id = Some(value.to_string()),
+            "event" => message.event = value.to_string(),
+            "data" => {
+                if !message.data.is_empty() {
+                    message.data.push('\n');
+                }
+                message.data.push_str(value);
+            }
+            "retry" => message.retry = value.parse().ok(),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod read_message_tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_each_event() {
+        let mut body = "retry: 1000\nevent: open\ndata: \"hello\"\n\n\
+                        id: 1\ndata: {}\n\n"
+            .as_bytes();
+        assert_eq!(
+            read_message(&mut body).unwrap(),
+            Some(Message {
+                id: None,
+                event: "open".to_string(),
+                data: "\"hello\"".to_string(),
+                retry: Some(1000),
+            })
+        );
+        assert_eq!(
+            read_message(&mut body).unwrap(),
+            Some(Message {
+                id: Some("1".to_string()),
+                event: String::new(),
+                data: "{}".to_string(),
+                retry: None,
+            })
+        );
+        assert_eq!(read_message(&mut body).unwrap(), None);
+    }
+
+    #[test]
+    fn it_joins_data_lines_and_skips_comments() {
+        let mut body = ": keep alive\r\n\r\ndata:{\r\ndata: \"a\": 1}\r\n\r\n".as_bytes();
+        let message = read_message(&mut body).unwrap().unwrap();
+        assert_eq!(message.data, "{\n\"a\": 1}");
+    }
+
+    #[test]
+    fn it_drops_a_partial_event() {
+        let mut body = "id: 1\ndata: {}\n".as_bytes();
+        assert_eq!(read_message(&mut body).unwrap(), None);
+    }
+}
+
+#[cfg(test)]
+mod event_stream_tests {
+    use super::*;
+    use endpoint::account;
+    use helpers::cursor::MemoryCursorStore;
+    use resources::Orderbook;
+    use test::{TestResponse, TestServer};
+
+    const ACCOUNT_ID: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+
+    // Serves each body as a stream in turn, one per connection.
+    fn serve(bodies: Vec<String>) -> TestServer {
+        TestServer::new(
+            bodies
+                .iter()
+                .map(|body| TestResponse::events(body))
+                .collect(),
+        )
+    }
+
+    fn event(id: u32) -> String {
+        format!(
+            "id: {}\ndata: {}\n\n",
+            id,
+            include_str!("../../../fixtures/orderbook.json").replace('\n', "")
+        )
+    }
+
+    #[test]
+    fn it_reconnects_from_the_last_event() {
+        let first = format!(
+            "retry: 10\nevent: open\ndata: \"hello\"\n\n{}{}",
+            event(1),
+            event(2)
+        );
+        let server = serve(vec![first, event(3)]);
+        let client = Client::new(server.uri()).unwrap();
+        let mut stream: EventStream<Orderbook> =
+            EventStream::new(&client, format!("{}/order_book", server.uri()));
+
+        for id in &["1", "2", "3"] {
+            let orderbook = stream.next().unwrap().unwrap();
+            assert_eq!(orderbook.counter().code(), Some("FOO"));
+            assert_eq!(stream.last_event_id(), Some(*id));
+        }
+
+        let first = server.next_request();
+        assert_eq!(first.request_line(), "GET /order_book HTTP/1.1");
+        assert_eq!(first.header("accept"), Some("text/event-stream"));
+        assert_eq!(first.header("last-event-id"), None);
+        assert_eq!(server.next_request().header("last-event-id"), Some("2"));
+    }
+
+    fn data(json: &str) -> String {
+        format!("data: {}\n\n", json.replace('\n', ""))
+    }
+
+    #[test]
+    fn it_streams_the_effects_of_an_account() {
+        let server = serve(vec![data(include_str!(
+            "../../../fixtures/effects/account_created.json"
+        ))]);
+        let client = Client::new(server.uri()).unwrap();
+        let endpoint = account::Effects::new(ACCOUNT_ID.into());
+        let effect = client.stream(endpoint).unwrap().next().unwrap().unwrap();
+        assert_eq!(effect.id(), "0000010157597659139-0000000002");
+        assert_eq!(
+            server.next_request().path(),
+            format!("/accounts/{}/effects", ACCOUNT_ID)
+        );
+    }
+
+    #[test]
+    fn it_streams_the_operations_of_an_account() {
+        let server = serve(vec![data(include_str!(
+            "../../../fixtures/operations/payment.json"
+        ))]);
+        let client = Client::new(server.uri()).unwrap();
+        let endpoint = account::Operations::new(ACCOUNT_ID.into());
+        let operation = client.stream(endpoint).unwrap().next().unwrap().unwrap();
+        assert_eq!(operation.id(), 58402965295104);
+        assert_eq!(
+            server.next_request().path(),
+            format!("/accounts/{}/operations", ACCOUNT_ID)
+        );
+    }
+
+    #[test]
+    fn it_saves_the_cursor_when_shut_down() {
+        let store = MemoryCursorStore::new();
+        let server = serve(vec![format!("{}{}", event(1), event(2))]);
+        let client = Client::new(server.uri()).unwrap();
+        let mut stream: EventStream<Orderbook> =
+            EventStream::new(&client, format!("{}/order_book", server.uri()))
+                .with_cursor_store(&store, "orderbook");
+        let handle = stream.handle();
+
+        assert!(stream.next().unwrap().is_ok());
+        handle.shutdown();
+        assert!(handle.is_shutdown());
+        assert!(stream.next().is_none());
+        assert!(stream.next().is_none());
+        assert_eq!(store.load("orderbook").unwrap(), Some("1".to_string()));
+    }
+
+    #[test]
+    fn it_ends_after_failing_to_connect() {
+        let uri = TestServer::unreachable();
+        let client = Client::new(&uri).unwrap();
+        let mut stream: EventStream<Orderbook> = EventStream::new(&client, uri);
+        assert!(stream.next().unwrap().is_err());
+        assert!(stream.next().is_none());
+    }
+}
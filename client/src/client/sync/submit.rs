@@ -0,0 +1,168 @@
+use super::Requester;
+use endpoint::transaction;
+use error::{Error, Result};
+use network::Network;
+use resources::{SubmittedTransaction, TransactionEnvelope};
+use std::io;
+use stellar_error::StellarError;
+
+/// Submits the envelope to horizon. When the submission times out, the
+/// transaction is looked up by its hash, worked out locally, before an error
+/// is returned. A transaction that succeeded in a ledger is returned. One
+/// that failed in a ledger fails with the same transaction_failed
+/// `Error::BadResponse` as a failed submission, and one that horizon doesn't
+/// have fails with `Error::TransactionTimeout`.
+pub(crate) fn submit_transaction<C>(
+    client: &C,
+    envelope_xdr: &str,
+    network: &Network,
+) -> Result<SubmittedTransaction>
+where
+    C: Requester + ?Sized,
+{
+    let hash = TransactionEnvelope::from_base64(envelope_xdr)?.horizon_hash(network);
+    match client.request(transaction::Submit::new(envelope_xdr)) {
+        Err(ref err) if is_timeout(err) => {}
+        result => return result,
+    }
+
+    #[cfg(feature = "tracing")]
+    warn!(hash = %hash, "transaction submission timed out, looking it up");
+    // Whatever stops the transaction from being found, it may still make it
+    // into a later ledger, so the caller is told it timed out.
+    match client.request(transaction::Details::new(&hash)) {
+        Ok(transaction) => match transaction.failure_reasons()? {
+            Some(reasons) => Err(Error::BadResponse(StellarError::transaction_failed(
+                reasons,
+            ))),
+            None => Ok(transaction.into()),
+        },
+        Err(_) => Err(Error::TransactionTimeout(hash)),
+    }
+}
+
+/// Whether the error leaves it unknown if the transaction made it in.
+fn is_timeout(err: &Error) -> bool {
+    match *err {
+        Error::BadResponse(ref inner) => inner.is_timeout(),
+        Error::Reqwest(ref inner, _) => inner.is_timeout(),
+        Error::Io(ref inner, _) => inner.kind() == io::ErrorKind::TimedOut,
+        Error::ParseTimeout(_, _) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod submit_transaction_tests {
+    use super::*;
+    use test::MockClient;
+
+    const ENVELOPE: &str = "AAAAAH2Hmt1JWMfqAdUlDeyUtO9V8zPqJ0aLG8KrZyXv78QGAAAAZAAIgb4AAtRiAAAAAAAAAAEAAAAAAAAAAQAAAAAAAAABAAAAAJZgy/0KAk+3JQwG8hPGBNTZVGew2Joi1TwkVBdwPn9QAAAAAAAAAAA7mUNgAAAAAAAAAAHv78QGAAAAQITCXzWfgHgAjF3djx1VK9JK08UypfpftzFoyNXv7A0Agau/ur/3/+ZZtQb8xSsao8yVAsTiV4ttiT/HqfvvlAk=";
+    const HASH: &str = "648da0d47aa3b3b20afd4499a68f89b6d10ead8b1f38858e99b1d94b6fef6e69";
+
+    fn timeout() -> &'static str {
+        r#"{
+            "type": "https://stellar.org/horizon-errors/timeout",
+            "title": "Timeout",
+            "status": 504,
+            "detail": "Your request timed out before completing."
+        }"#
+    }
+
+    fn not_found() -> &'static str {
+        r#"{
+            "type": "https://stellar.org/horizon-errors/not_found",
+            "title": "Resource Missing",
+            "status": 404,
+            "detail": "The resource at the url requested was not found."
+        }"#
+    }
+
+    #[test]
+    fn it_returns_the_submitted_transaction() {
+        let client = MockClient::new().with_json(
+            "/transactions",
+            include_str!("../../../fixtures/submitted_transaction.json"),
+        );
+        let submitted = submit_transaction(&client, ENVELOPE, &Network::public()).unwrap();
+        assert_eq!(submitted.ledger(), 1_524_302);
+        assert_eq!(client.requests(), vec!["/transactions"]);
+    }
+
+    #[test]
+    fn it_finds_a_transaction_whose_submission_timed_out() {
+        let client = MockClient::new()
+            .with_response("/transactions", 504, timeout())
+            .with_json(
+                "/transactions/*",
+                include_str!("../../../fixtures/transactions/transaction_memo_none.json"),
+            );
+        assert!(submit_transaction(&client, ENVELOPE, &Network::public()).is_ok());
+        assert_eq!(
+            client.requests(),
+            vec![
+                "/transactions".to_string(),
+                format!("/transactions/{}", HASH)
+            ]
+        );
+    }
+
+    #[test]
+    fn it_fails_when_the_transaction_found_had_failed() {
+        let client = MockClient::new()
+            .with_response("/transactions", 504, timeout())
+            .with_json(
+                "/transactions/*",
+                include_str!("../../../fixtures/transactions/transaction_failed.json"),
+            );
+        match submit_transaction(&client, ENVELOPE, &Network::public()).unwrap_err() {
+            Error::BadResponse(ref error) => {
+                assert!(error.is_transaction_failed());
+                let reasons = error.result_codes().unwrap();
+                assert_eq!(reasons.transaction(), "tx_failed");
+                assert_eq!(reasons.operations(), &vec!["op_underfunded".to_string()]);
+            }
+            error => panic!("expected a failed transaction, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn it_times_out_when_the_transaction_is_not_found() {
+        let client = MockClient::new()
+            .with_response("/transactions", 504, timeout())
+            .with_response("/transactions/*", 404, not_found());
+        match submit_transaction(&client, ENVELOPE, &Network::public()).unwrap_err() {
+            Error::TransactionTimeout(hash) => assert_eq!(hash, HASH),
+            error => panic!("expected a timeout, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn it_does_not_look_up_a_transaction_that_failed() {
+        let client = MockClient::new().with_response(
+            "/transactions",
+            400,
+            r#"{
+                "type": "https://stellar.org/horizon-errors/transaction_failed",
+                "title": "Transaction Failed",
+                "status": 400,
+                "detail": "The transaction failed when submitted to the stellar network."
+            }"#,
+        );
+        match submit_transaction(&client, ENVELOPE, &Network::public()).unwrap_err() {
+            Error::BadResponse(ref error) => assert!(error.is_transaction_failed()),
+            error => panic!("expected a failed transaction, got {:?}", error),
+        }
+        assert_eq!(client.hits("/transactions/*"), 0);
+    }
+
+    #[test]
+    fn it_refuses_an_envelope_it_cannot_hash() {
+        let client = MockClient::new();
+        match submit_transaction(&client, "not xdr", &Network::public()).unwrap_err() {
+            Error::Xdr(_) => (),
+            error => panic!("expected an xdr error, got {:?}", error),
+        }
+        assert!(client.requests().is_empty());
+    }
+}
@@ -0,0 +1,242 @@
+use super::{Client, EventStream, Iter};
+use endpoint::{trade, Cursor, Direction, Limit, Order};
+use error::Result;
+use resources::Trade;
+use std::collections::VecDeque;
+
+/// A stream of trades that doesn't miss any when horizon drops the
+/// connection. Create one with `Client::stream_trades`.
+///
+/// Each time the stream reconnects, the first trade that comes through is
+/// checked against the last one handed out. Any trades in between are
+/// fetched from the trades endpoint and handed out first, so every trade is
+/// seen once and in order. Trades the stream repeats are skipped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use stellar_client::endpoint::{trade, Cursor};
+/// use stellar_client::resources::AssetIdentifier;
+/// use stellar_client::sync::Client;
+///
+/// let client = Client::horizon_test().unwrap();
+/// let usd = AssetIdentifier::alphanum4(
+///     "USD",
+///     "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG",
+/// ).unwrap();
+/// let endpoint = trade::All::default()
+///     .with_asset_pair(AssetIdentifier::native(), usd)
+///     .with_cursor("now");
+/// for trade in client.stream_trades(endpoint).unwrap() {
+///     println!("{}", trade.unwrap().paging_token());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct TradeStream<'a> {
+    client: &'a Client,
+    endpoint: trade::All,
+    events: EventStream<'a, Trade>,
+    connections: usize,
+    pending: VecDeque<Trade>,
+    last_token: Option<String>,
+    backfilled: usize,
+    failed: bool,
+}
+
+// Horizon pages through at most 200 records at a time.
+const BACKFILL_LIMIT: u32 = 200;
+
+impl<'a> TradeStream<'a> {
+    pub(super) fn new(
+        client: &'a Client,
+        endpoint: trade::All,
+        events: EventStream<'a, Trade>,
+    ) -> Self {
+        TradeStream {
+            client,
+            endpoint,
+            events,
+            connections: 0,
+            pending: VecDeque::new(),
+            last_token: None,
+            backfilled: 0,
+            failed: false,
+        }
+    }
+
+    /// The paging token of the last trade handed out.
+    pub fn last_paging_token(&self) -> Option<&str> {
+        self.last_token.as_deref()
+    }
+
+    /// How many trades the stream missed while reconnecting and were fetched
+    /// from the trades endpoint instead.
+    pub fn backfilled(&self) -> usize {
+        self.backfilled
+    }
+
+    fn is_new(&self, trade: &Trade) -> bool {
+        match self.last_token {
+            Some(ref last) => token_order(trade.paging_token()) > token_order(last),
+            None => true,
+        }
+    }
+
+    /// Queues the trades between the last one handed out and `next`.
+    fn backfill(&mut self, next: &Trade) -> Result<()> {
+        let last = match self.last_token {
+            Some(ref last) => last.clone(),
+            None => return Ok(()),
+        };
+        let endpoint = self
+            .endpoint
+            .clone()
+            .with_cursor(&last)
+            .with_order(Direction::Asc)
+            .with_limit(BACKFILL_LIMIT);
+        let until = token_order(next.paging_token());
+        for trade in Iter::new(self.client, endpoint) {
+            let trade = trade?;
+            if token_order(trade.paging_token()) >= until {
+                break;
+            }
+            self.backfilled += 1;
+            self.pending.push_back(trade);
+        }
+        #[cfg(feature = "tracing")]
+        {
+            if !self.pending.is_empty() {
+                warn!(
+                    after = last.as_str(),
+                    missed = self.pending.len() as u64,
+                    "backfilled trades missed while reconnecting"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<Trade> {
+        let trade = self.pending.pop_front()?;
+        self.last_token = Some(trade.paging_token().to_string());
+        Some(trade)
+    }
+}
+
+impl<'a> Iterator for TradeStream<'a> {
+    type Item = Result<Trade>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(trade) = self.pop() {
+            return Some(Ok(trade));
+        }
+        if self.failed {
+            return None;
+        }
+        loop {
+            let trade = match self.events.next()? {
+                Ok(trade) => trade,
+                Err(err) => return Some(Err(err)),
+            };
+            if !self.is_new(&trade) {
+                continue;
+            }
+            if self.events.connections() != self.connections {
+                self.connections = self.events.connections();
+                // Handing out the trade without the ones before it would
+                // leave a gap, so stop here instead.
+                if let Err(err) = self.backfill(&trade) {
+                    self.failed = true;
+                    return Some(Err(err));
+                }
+            }
+            self.pending.push_back(trade);
+            return self.pop().map(Ok);
+        }
+    }
+}
+
+/// Trade paging tokens are the id of the operation that made the trade
+/// followed by the trade's index within it, such as `68836918321750017-0`.
+fn token_order(token: &str) -> (u64, u64) {
+    let mut parts = token.splitn(2, '-');
+    let operation = parts.next().and_then(|part| part.parse().ok());
+    let index = parts.next().and_then(|part| part.parse().ok());
+    (operation.unwrap_or(0), index.unwrap_or(0))
+}
+
+#[cfg(test)]
+mod trade_stream_tests {
+    use super::*;
+    use resources::AssetIdentifier;
+    use test::{TestResponse, TestServer};
+
+    fn trade(token: &str) -> String {
+        include_str!("../../../fixtures/trade.json")
+            .replace("68836918321750017-0", token)
+            .replace('\n', "")
+    }
+
+    fn events(tokens: &[&str]) -> TestResponse {
+        let mut body = "retry: 10\n\n".to_string();
+        for token in tokens {
+            body.push_str(&format!("id: {}\ndata: {}\n\n", token, trade(token)));
+        }
+        TestResponse::events(&body)
+    }
+
+    fn page(tokens: &[&str]) -> TestResponse {
+        let records: Vec<_> = tokens.iter().map(|token| trade(token)).collect();
+        TestResponse::json(&format!(
+            r#"{{ "_embedded": {{ "records": [{}] }} }}"#,
+            records.join(",")
+        ))
+    }
+
+    fn endpoint() -> trade::All {
+        trade::All::default().with_asset_pair(AssetIdentifier::native(), AssetIdentifier::native())
+    }
+
+    #[test]
+    fn it_backfills_trades_missed_while_reconnecting() {
+        let server = TestServer::new(vec![
+            events(&["10-0", "11-0"]),
+            events(&["11-0", "13-1"]),
+            page(&["12-0", "13-0", "13-1"]),
+        ]);
+        let client = Client::new(server.uri()).unwrap();
+        let stream = client.stream_trades(endpoint()).unwrap();
+        let tokens: Vec<_> = stream
+            .take(5)
+            .map(|trade| trade.unwrap().paging_token().to_string())
+            .collect();
+        assert_eq!(tokens, vec!["10-0", "11-0", "12-0", "13-0", "13-1"]);
+
+        let backfill = server.requests().remove(2);
+        assert!(backfill.path().contains("order=asc"));
+        assert!(backfill.path().contains("cursor=11-0"));
+    }
+
+    #[test]
+    fn it_counts_backfilled_trades() {
+        let server = TestServer::new(vec![
+            events(&["10-0"]),
+            events(&["12-0"]),
+            page(&["11-0", "12-0"]),
+        ]);
+        let client = Client::new(server.uri()).unwrap();
+        let mut stream = client.stream_trades(endpoint()).unwrap();
+        for _ in 0..3 {
+            stream.next().unwrap().unwrap();
+        }
+        assert_eq!(stream.backfilled(), 1);
+        assert_eq!(stream.last_paging_token(), Some("12-0"));
+    }
+
+    #[test]
+    fn it_orders_paging_tokens() {
+        assert!(token_order("9-1") < token_order("10-0"));
+        assert!(token_order("10-0") < token_order("10-1"));
+        assert_eq!(token_order("68836918321750017-0"), (68836918321750017, 0));
+    }
+}
@@ -0,0 +1,95 @@
+use super::Requester;
+use endpoint::transaction;
+use error::{Error, Result};
+use resources::Transaction;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Polls horizon for the transaction with `hash` every `interval` until it
+/// is found or `timeout` has passed. Any error other than the transaction
+/// not being found yet is returned straight away. A deadline bound to the
+/// client cuts the wait short like `timeout` does.
+pub(crate) fn wait_for_transaction<C>(
+    client: &C,
+    hash: &str,
+    timeout: Duration,
+    interval: Duration,
+) -> Result<Transaction>
+where
+    C: Requester + ?Sized,
+{
+    let mut deadline = Instant::now() + timeout;
+    if let Some(bound) = client.deadline() {
+        deadline = ::std::cmp::min(deadline, bound.instant());
+    }
+    loop {
+        match client.request(transaction::Details::new(hash)) {
+            Ok(transaction) => return Ok(transaction),
+            Err(Error::BadResponse(ref error)) if error.is_not_found() => {}
+            Err(Error::DeadlineExceeded) => {
+                return Err(Error::TransactionTimeout(hash.to_string()))
+            }
+            Err(error) => return Err(error),
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(Error::TransactionTimeout(hash.to_string()));
+        }
+        thread::sleep(::std::cmp::min(interval, deadline - now));
+    }
+}
+
+#[cfg(test)]
+mod wait_for_transaction_tests {
+    use super::*;
+    use test::MockClient;
+
+    const HASH: &str = "5fef21d5ef75ecf18d65a160cfab17dca8dbf6dbc4e2fd66a510719ad8dddb09";
+
+    fn not_found() -> &'static str {
+        r#"{
+            "type": "https://stellar.org/horizon-errors/not_found",
+            "title": "Resource Missing",
+            "status": 404,
+            "detail": "The resource at the url requested was not found."
+        }"#
+    }
+
+    #[test]
+    fn it_returns_a_transaction_that_is_already_in_the_ledger() {
+        let client = MockClient::new().with_json(
+            "/transactions/*",
+            include_str!("../../../fixtures/transactions/transaction_memo_none.json"),
+        );
+        let wait = Duration::from_millis(10);
+        assert!(wait_for_transaction(&client, HASH, wait, wait).is_ok());
+        assert_eq!(client.hits("/transactions/*"), 1);
+    }
+
+    #[test]
+    fn it_polls_until_the_timeout() {
+        let client = MockClient::new().with_response("/transactions/*", 404, not_found());
+        let started = Instant::now();
+        let error = wait_for_transaction(
+            &client,
+            HASH,
+            Duration::from_millis(50),
+            Duration::from_millis(20),
+        )
+        .unwrap_err();
+        match error {
+            Error::TransactionTimeout(hash) => assert_eq!(hash, HASH),
+            error => panic!("Expected a timeout {:?}", error),
+        }
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert!(client.hits(&format!("/transactions/{}", HASH)) >= 3);
+    }
+
+    #[test]
+    fn it_stops_on_other_errors() {
+        let client = MockClient::new().with_response("/transactions/*", 500, "");
+        let wait = Duration::from_secs(10);
+        assert!(wait_for_transaction(&client, HASH, wait, wait).is_err());
+        assert_eq!(client.hits("/transactions/*"), 1);
+    }
+}
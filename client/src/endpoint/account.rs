@@ -1,11 +1,17 @@
 //! Contains endpoints for accessing accounts and related information.
-use super::{Body, Cursor, Direction, IntoRequest, Limit, Order, Records};
+use super::{Body, Cursor, Direction, IntoRequest, Limit, Order, Projected, Records, Streamable};
 use error::Result;
 use http::{Request, Uri};
-use resources::{Account, Datum, Effect, Offer, Operation, Trade, Transaction};
+use resources::{
+    Account, AccountId, AssetIdentifier, Datum, Effect, Offer, Operation, Trade, Transaction,
+    TransactionHeader,
+};
 use std::str::FromStr;
 use uri::{self, TryFromUri, UriWrap};
 
+#[cfg(test)]
+const ACCOUNT_ID: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+
 /// Represents the account details on the stellar horizon server.
 /// The endpoint will return information relating to a specific account.
 ///
@@ -25,14 +31,14 @@ use uri::{self, TryFromUri, UriWrap};
 /// let account_id     = txn.source_account();
 ///
 /// // Now we issue a request for that account's transactions
-/// let endpoint  = account::Details::new(account_id);
+/// let endpoint  = account::Details::new(account_id.clone());
 /// let details   = client.request(endpoint).unwrap();
 ///
 /// assert_eq!(details.id(), account_id);
 /// ```
 #[derive(Debug)]
 pub struct Details {
-    account_id: String,
+    account_id: AccountId,
 }
 
 impl Details {
@@ -42,12 +48,10 @@ impl Details {
     /// ```
     /// use stellar_client::endpoint::account;
     ///
-    /// let details = account::Details::new("abc123");
+    /// let details = account::Details::new("GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into());
     /// ```
-    pub fn new(account_id: &str) -> Self {
-        Self {
-            account_id: account_id.to_string(),
-        }
+    pub fn new(account_id: AccountId) -> Self {
+        Self { account_id }
     }
 }
 
@@ -72,14 +76,14 @@ impl IntoRequest for Details {
 /// use stellar_client::endpoint::account;
 ///
 /// let client      = Client::horizon_test().unwrap();
-/// let endpoint    = account::Data::new("GATLAI2D7SSH6PE3HXTDPTRM4RE5VRK6HGA63K5EWP75PSANCZRFDNB5", "Food");
+/// let endpoint    = account::Data::new("GATLAI2D7SSH6PE3HXTDPTRM4RE5VRK6HGA63K5EWP75PSANCZRFDNB5".into(), "Food");
 /// let record      = client.request(endpoint).unwrap();
 /// #
 /// # assert_eq!(record.value(), "Pizza");
 /// ```
 #[derive(Debug)]
 pub struct Data {
-    account_id: String,
+    account_id: AccountId,
     key: String,
 }
 
@@ -90,11 +94,11 @@ impl Data {
     /// ```
     /// use stellar_client::endpoint::account;
     ///
-    /// let data = account::Data::new("abc123", "Food");
+    /// let data = account::Data::new("GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into(), "Food");
     /// ```
-    pub fn new(account_id: &str, key: &str) -> Self {
+    pub fn new(account_id: AccountId, key: &str) -> Self {
         Self {
-            account_id: account_id.to_string(),
+            account_id,
             key: key.to_string(),
         }
     }
@@ -119,22 +123,25 @@ mod account_tests {
 
     #[test]
     fn it_can_make_an_account_uri() {
-        let details = Details::new("abc123");
+        let details = Details::new(ACCOUNT_ID.into());
         let request = details
             .into_request("https://horizon-testnet.stellar.org")
             .unwrap();
         assert_eq!(request.uri().host().unwrap(), "horizon-testnet.stellar.org");
-        assert_eq!(request.uri().path(), "/accounts/abc123");
+        assert_eq!(request.uri().path(), format!("/accounts/{}", ACCOUNT_ID));
     }
 
     #[test]
     fn it_can_make_an_account_data_uri() {
-        let data = Data::new("abc123", "key");
+        let data = Data::new(ACCOUNT_ID.into(), "key");
         let request = data
             .into_request("https://horizon-testnet.stellar.org")
             .unwrap();
         assert_eq!(request.uri().host().unwrap(), "horizon-testnet.stellar.org");
-        assert_eq!(request.uri().path(), "/accounts/abc123/data/key");
+        assert_eq!(
+            request.uri().path(),
+            format!("/accounts/{}/data/key", ACCOUNT_ID)
+        );
     }
 }
 
@@ -157,14 +164,14 @@ mod account_tests {
 /// let account_id = trade.selling_account();
 ///
 /// // Now we issue a request for that account's trades
-/// let endpoint    = account::Trades::new(account_id);
+/// let endpoint    = account::Trades::new(account_id.clone());
 /// let acct_trades = client.request(endpoint).unwrap();
 ///
 /// assert!(acct_trades.records().len() > 0);
 /// ```
 #[derive(Debug, Clone)]
 pub struct Trades {
-    account_id: String,
+    account_id: AccountId,
     cursor: Option<String>,
     order: Option<Direction>,
     limit: Option<u32>,
@@ -181,11 +188,11 @@ impl Trades {
     /// ```
     /// use stellar_client::endpoint::account;
     ///
-    /// let trades = account::Trades::new("abc123");
+    /// let trades = account::Trades::new("GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into());
     /// ```
-    pub fn new(account_id: &str) -> Self {
+    pub fn new(account_id: AccountId) -> Self {
         Self {
-            account_id: account_id.to_string(),
+            account_id,
             cursor: None,
             order: None,
             limit: None,
@@ -210,7 +217,7 @@ impl IntoRequest for Trades {
             }
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
             }
 
             if let Some(limit) = self.limit {
@@ -230,7 +237,7 @@ impl TryFromUri for Trades {
             ["accounts", account_id, "trades"] => {
                 let params = wrap.params();
                 Ok(Self {
-                    account_id: account_id.to_string(),
+                    account_id: account_id.parse()?,
                     cursor: params.get_parse("cursor").ok(),
                     order: params.get_parse("order").ok(),
                     limit: params.get_parse("limit").ok(),
@@ -247,32 +254,35 @@ mod trades_tests {
 
     #[test]
     fn it_leaves_off_the_params_if_not_specified() {
-        let trades = Trades::new("abc123");
+        let trades = Trades::new(ACCOUNT_ID.into());
         let req = trades
             .into_request("https://horizon-testnet.stellar.org")
             .unwrap();
-        assert_eq!(req.uri().path(), "/accounts/abc123/trades");
+        assert_eq!(req.uri().path(), format!("/accounts/{}/trades", ACCOUNT_ID));
         assert_eq!(req.uri().query(), None);
     }
 
     #[test]
     fn it_can_make_a_trades_uri() {
-        let trades = Trades::new("abc123");
+        let trades = Trades::new(ACCOUNT_ID.into());
         let request = trades
             .into_request("https://horizon-testnet.stellar.org")
             .unwrap();
         assert_eq!(request.uri().host().unwrap(), "horizon-testnet.stellar.org");
-        assert_eq!(request.uri().path(), "/accounts/abc123/trades");
+        assert_eq!(
+            request.uri().path(),
+            format!("/accounts/{}/trades", ACCOUNT_ID)
+        );
     }
 
     #[test]
     fn it_puts_the_query_params_on_the_uri() {
-        let ep = Trades::new("abc123")
+        let ep = Trades::new(ACCOUNT_ID.into())
             .with_cursor("CURSOR")
             .with_order(Direction::Desc)
             .with_limit(123);
         let req = ep.into_request("https://www.google.com").unwrap();
-        assert_eq!(req.uri().path(), "/accounts/abc123/trades");
+        assert_eq!(req.uri().path(), format!("/accounts/{}/trades", ACCOUNT_ID));
         assert_eq!(
             req.uri().query(),
             Some("cursor=CURSOR&order=desc&limit=123")
@@ -281,15 +291,24 @@ mod trades_tests {
 
     #[test]
     fn it_parses_from_a_uri() {
-        let uri: Uri = "/accounts/abc123/trades?cursor=CURSOR&order=desc&limit=123"
-            .parse()
-            .unwrap();
+        let uri: Uri = format!(
+            "/accounts/{}/trades?cursor=CURSOR&order=desc&limit=123",
+            ACCOUNT_ID
+        )
+        .parse()
+        .unwrap();
         let ep = Trades::try_from(&uri).unwrap();
-        assert_eq!(ep.account_id, "abc123");
+        assert_eq!(ep.account_id, ACCOUNT_ID);
         assert_eq!(ep.limit, Some(123));
         assert_eq!(ep.cursor, Some("CURSOR".to_string()));
         assert_eq!(ep.order, Some(Direction::Desc));
     }
+
+    #[test]
+    fn it_errs_parsing_a_malformed_account_from_a_uri() {
+        let uri: Uri = "/accounts/abc123/trades".parse().unwrap();
+        assert!(Trades::try_from(&uri).is_err());
+    }
 }
 
 /// Represents the transaction for account endpoint on the stellar horizon server.
@@ -311,17 +330,18 @@ mod trades_tests {
 /// let account_id     = txn.source_account();
 ///
 /// // Now we issue a request for that account's transactions
-/// let endpoint  = account::Transactions::new(account_id);
+/// let endpoint  = account::Transactions::new(account_id.clone());
 /// let acct_txns = client.request(endpoint).unwrap();
 ///
 /// assert!(acct_txns.records().len() > 0);
 /// ```
 #[derive(Debug, Clone)]
 pub struct Transactions {
-    account_id: String,
+    account_id: AccountId,
     cursor: Option<String>,
     order: Option<Direction>,
     limit: Option<u32>,
+    include_failed: bool,
 }
 
 impl_cursor!(Transactions);
@@ -335,19 +355,34 @@ impl Transactions {
     /// ```
     /// use stellar_client::endpoint::account;
     ///
-    /// let txns = account::Transactions::new("abc123");
+    /// let txns = account::Transactions::new("GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into());
     /// ```
-    pub fn new(account_id: &str) -> Self {
+    pub fn new(account_id: AccountId) -> Self {
         Self {
-            account_id: account_id.to_string(),
+            account_id,
             cursor: None,
             order: None,
             limit: None,
+            include_failed: false,
         }
     }
 
     fn has_query(&self) -> bool {
-        self.order.is_some() || self.cursor.is_some() || self.limit.is_some()
+        self.order.is_some() || self.cursor.is_some() || self.limit.is_some() || self.include_failed
+    }
+
+    /// Also lists transactions that failed, which horizon leaves out by
+    /// default. Their `failure_reasons` say why they failed.
+    pub fn with_include_failed(mut self, include_failed: bool) -> Self {
+        self.include_failed = include_failed;
+        self
+    }
+
+    /// Parses the transactions into `TransactionHeader`s, which leave out
+    /// the xdr, for consumers that only need to know who sent what and
+    /// when.
+    pub fn without_xdr(self) -> Projected<Self, TransactionHeader> {
+        Projected::new(self)
     }
 }
 
@@ -364,7 +399,11 @@ impl IntoRequest for Transactions {
             }
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
+            }
+
+            if self.include_failed {
+                uri.push_str("include_failed=true&");
             }
 
             if let Some(limit) = self.limit {
@@ -384,10 +423,11 @@ impl TryFromUri for Transactions {
             ["accounts", account_id, "transactions"] => {
                 let params = wrap.params();
                 Ok(Self {
-                    account_id: account_id.to_string(),
+                    account_id: account_id.parse()?,
                     cursor: params.get_parse("cursor").ok(),
                     order: params.get_parse("order").ok(),
                     limit: params.get_parse("limit").ok(),
+                    include_failed: params.get("include_failed") == Some("true"),
                 })
             }
             _ => Err(uri::Error::invalid_path()),
@@ -401,32 +441,41 @@ mod transactions_tests {
 
     #[test]
     fn it_leaves_off_the_params_if_not_specified() {
-        let transactions = Transactions::new("abc123");
+        let transactions = Transactions::new(ACCOUNT_ID.into());
         let req = transactions
             .into_request("https://horizon-testnet.stellar.org")
             .unwrap();
-        assert_eq!(req.uri().path(), "/accounts/abc123/transactions");
+        assert_eq!(
+            req.uri().path(),
+            format!("/accounts/{}/transactions", ACCOUNT_ID)
+        );
         assert_eq!(req.uri().query(), None);
     }
 
     #[test]
     fn it_can_make_a_transactions_uri() {
-        let transactions = Transactions::new("abc123");
+        let transactions = Transactions::new(ACCOUNT_ID.into());
         let request = transactions
             .into_request("https://horizon-testnet.stellar.org")
             .unwrap();
         assert_eq!(request.uri().host().unwrap(), "horizon-testnet.stellar.org");
-        assert_eq!(request.uri().path(), "/accounts/abc123/transactions");
+        assert_eq!(
+            request.uri().path(),
+            format!("/accounts/{}/transactions", ACCOUNT_ID)
+        );
     }
 
     #[test]
     fn it_puts_the_query_params_on_the_uri() {
-        let ep = Transactions::new("abc123")
+        let ep = Transactions::new(ACCOUNT_ID.into())
             .with_cursor("CURSOR")
             .with_order(Direction::Desc)
             .with_limit(123);
         let req = ep.into_request("https://www.google.com").unwrap();
-        assert_eq!(req.uri().path(), "/accounts/abc123/transactions");
+        assert_eq!(
+            req.uri().path(),
+            format!("/accounts/{}/transactions", ACCOUNT_ID)
+        );
         assert_eq!(
             req.uri().query(),
             Some("cursor=CURSOR&order=desc&limit=123")
@@ -435,11 +484,14 @@ mod transactions_tests {
 
     #[test]
     fn it_parses_from_a_uri() {
-        let uri: Uri = "/accounts/abc123/transactions?cursor=CURSOR&order=desc&limit=123"
-            .parse()
-            .unwrap();
+        let uri: Uri = format!(
+            "/accounts/{}/transactions?cursor=CURSOR&order=desc&limit=123",
+            ACCOUNT_ID
+        )
+        .parse()
+        .unwrap();
         let ep = Transactions::try_from(&uri).unwrap();
-        assert_eq!(ep.account_id, "abc123");
+        assert_eq!(ep.account_id, ACCOUNT_ID);
         assert_eq!(ep.limit, Some(123));
         assert_eq!(ep.cursor, Some("CURSOR".to_string()));
         assert_eq!(ep.order, Some(Direction::Desc));
@@ -465,14 +517,14 @@ mod transactions_tests {
 /// let account_id     = txn.source_account();
 ///
 /// // Now we issue a request for that account's payments
-/// let endpoint  = account::Effects::new(account_id);
+/// let endpoint  = account::Effects::new(account_id.clone());
 /// let effects   = client.request(endpoint).unwrap();
 ///
 /// assert!(effects.records().len() > 0);
 /// ```
 #[derive(Debug, Clone)]
 pub struct Effects {
-    account_id: String,
+    account_id: AccountId,
     cursor: Option<String>,
     order: Option<Direction>,
     limit: Option<u32>,
@@ -489,11 +541,11 @@ impl Effects {
     /// ```
     /// use stellar_client::endpoint::account;
     ///
-    /// let effects = account::Effects::new("abc123");
+    /// let effects = account::Effects::new("GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into());
     /// ```
-    pub fn new(account_id: &str) -> Self {
+    pub fn new(account_id: AccountId) -> Self {
         Self {
-            account_id: account_id.to_string(),
+            account_id,
             cursor: None,
             order: None,
             limit: None,
@@ -518,7 +570,7 @@ impl IntoRequest for Effects {
             }
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
             }
 
             if let Some(limit) = self.limit {
@@ -532,13 +584,17 @@ impl IntoRequest for Effects {
     }
 }
 
+impl Streamable for Effects {
+    type Event = Effect;
+}
+
 impl TryFromUri for Effects {
     fn try_from_wrap(wrap: &UriWrap) -> ::std::result::Result<Self, uri::Error> {
         match wrap.path() {
             ["accounts", account_id, "effects"] => {
                 let params = wrap.params();
                 Ok(Self {
-                    account_id: account_id.to_string(),
+                    account_id: account_id.parse()?,
                     cursor: params.get_parse("cursor").ok(),
                     order: params.get_parse("order").ok(),
                     limit: params.get_parse("limit").ok(),
@@ -555,44 +611,56 @@ mod effects_tests {
 
     #[test]
     fn it_leaves_off_the_params_if_not_specified() {
-        let effects = Effects::new("abc123");
+        let effects = Effects::new(ACCOUNT_ID.into());
         let req = effects
             .into_request("https://horizon-testnet.stellar.org")
             .unwrap();
-        assert_eq!(req.uri().path(), "/accounts/abc123/effects");
+        assert_eq!(
+            req.uri().path(),
+            format!("/accounts/{}/effects", ACCOUNT_ID)
+        );
         assert_eq!(req.uri().query(), None);
     }
 
     #[test]
     fn it_can_make_a_actions_uri() {
-        let effects = Effects::new("abc123");
+        let effects = Effects::new(ACCOUNT_ID.into());
         let request = effects
             .into_request("https://horizon-testnet.stellar.org")
             .unwrap();
         assert_eq!(request.uri().host().unwrap(), "horizon-testnet.stellar.org");
-        assert_eq!(request.uri().path(), "/accounts/abc123/effects");
+        assert_eq!(
+            request.uri().path(),
+            format!("/accounts/{}/effects", ACCOUNT_ID)
+        );
     }
 
     #[test]
     fn it_puts_the_query_params_on_the_uri() {
-        let ep = Effects::new("abc123")
+        let ep = Effects::new(ACCOUNT_ID.into())
             .with_cursor("CURSOR")
             .with_order(Direction::Asc)
             .with_limit(123);
         let req = ep
             .into_request("https://horizon-testnet.stellar.org")
             .unwrap();
-        assert_eq!(req.uri().path(), "/accounts/abc123/effects");
+        assert_eq!(
+            req.uri().path(),
+            format!("/accounts/{}/effects", ACCOUNT_ID)
+        );
         assert_eq!(req.uri().query(), Some("cursor=CURSOR&order=asc&limit=123"));
     }
 
     #[test]
     fn it_parses_from_a_uri() {
-        let uri: Uri = "/accounts/abc123/effects?cursor=CURSOR&order=desc&limit=123"
-            .parse()
-            .unwrap();
+        let uri: Uri = format!(
+            "/accounts/{}/effects?cursor=CURSOR&order=desc&limit=123",
+            ACCOUNT_ID
+        )
+        .parse()
+        .unwrap();
         let ep = Effects::try_from(&uri).unwrap();
-        assert_eq!(ep.account_id, "abc123");
+        assert_eq!(ep.account_id, ACCOUNT_ID);
         assert_eq!(ep.limit, Some(123));
         assert_eq!(ep.cursor, Some("CURSOR".to_string()));
         assert_eq!(ep.order, Some(Direction::Desc));
@@ -620,14 +688,14 @@ mod effects_tests {
 /// let account_id = txn.source_account();
 ///
 /// // Now we issue a request for that accounts operations
-/// let endpoint = account::Operations::new(account_id);
+/// let endpoint = account::Operations::new(account_id.clone());
 /// let account_operations = client.request(endpoint).unwrap();
 ///
 /// assert!(account_operations.records().len() > 0);
 /// ```
 #[derive(Debug, Clone)]
 pub struct Operations {
-    account_id: String,
+    account_id: AccountId,
     cursor: Option<String>,
     order: Option<Direction>,
     limit: Option<u32>,
@@ -643,11 +711,11 @@ impl Operations {
     /// ```
     /// use stellar_client::endpoint::account;
     ///
-    /// let txns = account::Operations::new("abc123");
+    /// let txns = account::Operations::new("GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into());
     /// ```
-    pub fn new(account_id: &str) -> Operations {
+    pub fn new(account_id: AccountId) -> Operations {
         Operations {
-            account_id: account_id.to_string(),
+            account_id,
             cursor: None,
             order: None,
             limit: None,
@@ -669,7 +737,7 @@ impl IntoRequest for Operations {
             uri.push_str("?");
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
             }
 
             if let Some(cursor) = self.cursor {
@@ -687,13 +755,17 @@ impl IntoRequest for Operations {
     }
 }
 
+impl Streamable for Operations {
+    type Event = Operation;
+}
+
 impl TryFromUri for Operations {
     fn try_from_wrap(wrap: &UriWrap) -> ::std::result::Result<Self, uri::Error> {
         match wrap.path() {
             ["accounts", account_id, "operations"] => {
                 let params = wrap.params();
                 Ok(Self {
-                    account_id: account_id.to_string(),
+                    account_id: account_id.parse()?,
                     cursor: params.get_parse("cursor").ok(),
                     order: params.get_parse("order").ok(),
                     limit: params.get_parse("limit").ok(),
@@ -710,20 +782,26 @@ mod ledger_operations_tests {
 
     #[test]
     fn it_leaves_off_the_params_if_not_specified() {
-        let ep = Operations::new("abc123");
+        let ep = Operations::new(ACCOUNT_ID.into());
         let req = ep.into_request("https://www.google.com").unwrap();
-        assert_eq!(req.uri().path(), "/accounts/abc123/operations");
+        assert_eq!(
+            req.uri().path(),
+            format!("/accounts/{}/operations", ACCOUNT_ID)
+        );
         assert_eq!(req.uri().query(), None);
     }
 
     #[test]
     fn it_puts_the_query_params_on_the_uri() {
-        let ep = Operations::new("abc123")
+        let ep = Operations::new(ACCOUNT_ID.into())
             .with_cursor("CURSOR")
             .with_limit(123)
             .with_order(Direction::Desc);
         let req = ep.into_request("https://www.google.com").unwrap();
-        assert_eq!(req.uri().path(), "/accounts/abc123/operations");
+        assert_eq!(
+            req.uri().path(),
+            format!("/accounts/{}/operations", ACCOUNT_ID)
+        );
         assert_eq!(
             req.uri().query(),
             Some("order=desc&cursor=CURSOR&limit=123")
@@ -732,11 +810,14 @@ mod ledger_operations_tests {
 
     #[test]
     fn it_parses_from_a_uri() {
-        let uri: Uri = "/accounts/abc123/operations?cursor=CURSOR&order=desc&limit=123"
-            .parse()
-            .unwrap();
+        let uri: Uri = format!(
+            "/accounts/{}/operations?cursor=CURSOR&order=desc&limit=123",
+            ACCOUNT_ID
+        )
+        .parse()
+        .unwrap();
         let ep = Operations::try_from(&uri).unwrap();
-        assert_eq!(ep.account_id, "abc123");
+        assert_eq!(ep.account_id, ACCOUNT_ID);
         assert_eq!(ep.limit, Some(123));
         assert_eq!(ep.cursor, Some("CURSOR".to_string()));
         assert_eq!(ep.order, Some(Direction::Desc));
@@ -767,14 +848,14 @@ mod ledger_operations_tests {
 /// };
 ///
 /// // Now we issue a request for that account's payments
-/// let endpoint      = account::Payments::new(account_id);
+/// let endpoint      = account::Payments::new(account_id.clone());
 /// let acct_payments = client.request(endpoint).unwrap();
 ///
 /// assert!(acct_payments.records().len() > 0);
 /// ```
 #[derive(Debug, Clone)]
 pub struct Payments {
-    account_id: String,
+    account_id: AccountId,
     cursor: Option<String>,
     order: Option<Direction>,
     limit: Option<u32>,
@@ -791,11 +872,11 @@ impl Payments {
     /// ```
     /// use stellar_client::endpoint::account;
     ///
-    /// let payments = account::Payments::new("abc123");
+    /// let payments = account::Payments::new("GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into());
     /// ```
-    pub fn new(account_id: &str) -> Self {
+    pub fn new(account_id: AccountId) -> Self {
         Self {
-            account_id: account_id.to_string(),
+            account_id,
             cursor: None,
             order: None,
             limit: None,
@@ -820,7 +901,7 @@ impl IntoRequest for Payments {
             }
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
             }
 
             if let Some(limit) = self.limit {
@@ -834,13 +915,17 @@ impl IntoRequest for Payments {
     }
 }
 
+impl Streamable for Payments {
+    type Event = Operation;
+}
+
 impl TryFromUri for Payments {
     fn try_from_wrap(wrap: &UriWrap) -> ::std::result::Result<Self, uri::Error> {
         match wrap.path() {
             ["accounts", account_id, "payments"] => {
                 let params = wrap.params();
                 Ok(Self {
-                    account_id: account_id.to_string(),
+                    account_id: account_id.parse()?,
                     cursor: params.get_parse("cursor").ok(),
                     order: params.get_parse("order").ok(),
                     limit: params.get_parse("limit").ok(),
@@ -857,23 +942,29 @@ mod payments_tests {
 
     #[test]
     fn it_can_make_a_payments_uri() {
-        let payments = Payments::new("abc123");
+        let payments = Payments::new(ACCOUNT_ID.into());
         let request = payments
             .into_request("https://horizon-testnet.stellar.org")
             .unwrap();
         assert_eq!(request.uri().host().unwrap(), "horizon-testnet.stellar.org");
-        assert_eq!(request.uri().path(), "/accounts/abc123/payments");
+        assert_eq!(
+            request.uri().path(),
+            format!("/accounts/{}/payments", ACCOUNT_ID)
+        );
         assert_eq!(request.uri().query(), None);
     }
 
     #[test]
     fn it_puts_the_query_params_on_the_uri() {
-        let ep = Payments::new("abc123")
+        let ep = Payments::new(ACCOUNT_ID.into())
             .with_cursor("CURSOR")
             .with_order(Direction::Desc)
             .with_limit(123);
         let req = ep.into_request("https://www.google.com").unwrap();
-        assert_eq!(req.uri().path(), "/accounts/abc123/payments");
+        assert_eq!(
+            req.uri().path(),
+            format!("/accounts/{}/payments", ACCOUNT_ID)
+        );
         assert_eq!(
             req.uri().query(),
             Some("cursor=CURSOR&order=desc&limit=123")
@@ -882,11 +973,14 @@ mod payments_tests {
 
     #[test]
     fn it_parses_from_a_uri() {
-        let uri: Uri = "/accounts/abc123/payments?cursor=CURSOR&order=desc&limit=123"
-            .parse()
-            .unwrap();
+        let uri: Uri = format!(
+            "/accounts/{}/payments?cursor=CURSOR&order=desc&limit=123",
+            ACCOUNT_ID
+        )
+        .parse()
+        .unwrap();
         let ep = Payments::try_from(&uri).unwrap();
-        assert_eq!(ep.account_id, "abc123");
+        assert_eq!(ep.account_id, ACCOUNT_ID);
         assert_eq!(ep.limit, Some(123));
         assert_eq!(ep.cursor, Some("CURSOR".to_string()));
         assert_eq!(ep.order, Some(Direction::Desc));
@@ -912,14 +1006,14 @@ mod payments_tests {
 /// let account_id  = trade.base_account();
 ///
 /// // Now we issue a request for that account's offers
-/// let endpoint = account::Offers::new(account_id);
+/// let endpoint = account::Offers::new(account_id.clone());
 /// let offers   = client.request(endpoint).unwrap();
 ///
 /// assert!(offers.records().len() > 0);
 /// ```
 #[derive(Debug, Clone)]
 pub struct Offers {
-    account_id: String,
+    account_id: AccountId,
     cursor: Option<String>,
     order: Option<Direction>,
     limit: Option<u32>,
@@ -936,11 +1030,11 @@ impl Offers {
     /// ```
     /// use stellar_client::endpoint::account;
     ///
-    /// let offers = account::Offers::new("abc123");
+    /// let offers = account::Offers::new("GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into());
     /// ```
-    pub fn new(account_id: &str) -> Self {
+    pub fn new(account_id: AccountId) -> Self {
         Self {
-            account_id: account_id.to_string(),
+            account_id,
             cursor: None,
             order: None,
             limit: None,
@@ -965,7 +1059,7 @@ impl IntoRequest for Offers {
             }
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
             }
 
             if let Some(limit) = self.limit {
@@ -985,7 +1079,7 @@ impl TryFromUri for Offers {
             ["accounts", account_id, "offers"] => {
                 let params = wrap.params();
                 Ok(Self {
-                    account_id: account_id.to_string(),
+                    account_id: account_id.parse()?,
                     cursor: params.get_parse("cursor").ok(),
                     order: params.get_parse("order").ok(),
                     limit: params.get_parse("limit").ok(),
@@ -1002,23 +1096,26 @@ mod offers_tests {
 
     #[test]
     fn it_can_make_an_offers_uri() {
-        let payments = Offers::new("abc123");
+        let payments = Offers::new(ACCOUNT_ID.into());
         let request = payments
             .into_request("https://horizon-testnet.stellar.org")
             .unwrap();
         assert_eq!(request.uri().host().unwrap(), "horizon-testnet.stellar.org");
-        assert_eq!(request.uri().path(), "/accounts/abc123/offers");
+        assert_eq!(
+            request.uri().path(),
+            format!("/accounts/{}/offers", ACCOUNT_ID)
+        );
         assert_eq!(request.uri().query(), None);
     }
 
     #[test]
     fn it_puts_the_query_params_on_the_uri() {
-        let ep = Offers::new("abc123")
+        let ep = Offers::new(ACCOUNT_ID.into())
             .with_cursor("CURSOR")
             .with_order(Direction::Desc)
             .with_limit(123);
         let req = ep.into_request("https://www.google.com").unwrap();
-        assert_eq!(req.uri().path(), "/accounts/abc123/offers");
+        assert_eq!(req.uri().path(), format!("/accounts/{}/offers", ACCOUNT_ID));
         assert_eq!(
             req.uri().query(),
             Some("cursor=CURSOR&order=desc&limit=123")
@@ -1027,11 +1124,191 @@ mod offers_tests {
 
     #[test]
     fn it_parses_from_a_uri() {
-        let uri: Uri = "/accounts/abc123/offers?cursor=CURSOR&order=desc&limit=123"
-            .parse()
-            .unwrap();
+        let uri: Uri = format!(
+            "/accounts/{}/offers?cursor=CURSOR&order=desc&limit=123",
+            ACCOUNT_ID
+        )
+        .parse()
+        .unwrap();
         let ep = Offers::try_from(&uri).unwrap();
-        assert_eq!(ep.account_id, "abc123");
+        assert_eq!(ep.account_id, ACCOUNT_ID);
+        assert_eq!(ep.limit, Some(123));
+        assert_eq!(ep.cursor, Some("CURSOR".to_string()));
+        assert_eq!(ep.order, Some(Direction::Desc));
+    }
+}
+
+/// Represents the all accounts endpoint on the stellar horizon server. The
+/// endpoint lists the accounts that hold a trustline to an asset, that have a
+/// signer, or whose reserves are paid for by a sponsor. Horizon needs exactly
+/// one of these filters.
+///
+/// <https://developers.stellar.org/api/horizon/resources/list-all-accounts>
+///
+/// ## Example
+/// ```no_run
+/// use stellar_client::sync::Client;
+/// use stellar_client::endpoint::account;
+/// use stellar_client::resources::AssetIdentifier;
+///
+/// let client = Client::horizon_test().unwrap();
+/// let usd = AssetIdentifier::alphanum4(
+///     "USD",
+///     "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG",
+/// ).unwrap();
+/// let endpoint = account::All::default().with_asset(usd);
+/// let holders  = client.request(endpoint).unwrap();
+///
+/// assert!(holders.records().len() > 0);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct All {
+    asset: Option<AssetIdentifier>,
+    signer: Option<AccountId>,
+    sponsor: Option<AccountId>,
+    cursor: Option<String>,
+    order: Option<Direction>,
+    limit: Option<u32>,
+}
+
+impl_cursor!(All);
+impl_limit!(All);
+impl_order!(All);
+
+impl All {
+    /// Lists the accounts with a trustline to `asset`.
+    pub fn with_asset(mut self, asset: AssetIdentifier) -> Self {
+        self.asset = Some(asset);
+        self
+    }
+
+    /// Lists the accounts that `signer` can sign for, including its own.
+    pub fn with_signer(mut self, signer: AccountId) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Lists the accounts whose reserves `sponsor` pays for.
+    pub fn with_sponsor(mut self, sponsor: AccountId) -> Self {
+        self.sponsor = Some(sponsor);
+        self
+    }
+}
+
+impl IntoRequest for All {
+    type Response = Records<Account>;
+
+    fn into_request(self, host: &str) -> Result<Request<Body>> {
+        let mut uri = format!("{}/accounts?", host);
+
+        if let Some(asset) = self.asset {
+            match (asset.code(), asset.issuer()) {
+                (Some(code), Some(issuer)) => uri.push_str(&format!("asset={}:{}&", code, issuer)),
+                _ => uri.push_str("asset=native&"),
+            }
+        }
+
+        if let Some(signer) = self.signer {
+            uri.push_str(&format!("signer={}&", signer));
+        }
+
+        if let Some(sponsor) = self.sponsor {
+            uri.push_str(&format!("sponsor={}&", sponsor));
+        }
+
+        if let Some(cursor) = self.cursor {
+            uri.push_str(&format!("cursor={}&", cursor));
+        }
+
+        if let Some(order) = self.order {
+            uri.push_str(&format!("order={}&", order));
+        }
+
+        if let Some(limit) = self.limit {
+            uri.push_str(&format!("limit={}", limit));
+        }
+
+        let uri = Uri::from_str(uri.trim_end_matches(&['&', '?'][..]))?;
+        let request = Request::get(uri).body(Body::None)?;
+        Ok(request)
+    }
+}
+
+impl TryFromUri for All {
+    fn try_from_wrap(wrap: &UriWrap) -> ::std::result::Result<Self, uri::Error> {
+        match wrap.path() {
+            ["accounts"] => {
+                let params = wrap.params();
+                Ok(Self {
+                    asset: params
+                        .get("asset")
+                        .and_then(|asset| asset.replacen(':', "-", 1).parse().ok()),
+                    signer: params.get_parse("signer").ok(),
+                    sponsor: params.get_parse("sponsor").ok(),
+                    cursor: params.get_parse("cursor").ok(),
+                    order: params.get_parse("order").ok(),
+                    limit: params.get_parse("limit").ok(),
+                })
+            }
+            _ => Err(uri::Error::invalid_path()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod all_accounts_tests {
+    use super::*;
+
+    fn usd() -> AssetIdentifier {
+        AssetIdentifier::alphanum4("USD", ACCOUNT_ID).unwrap()
+    }
+
+    #[test]
+    fn it_filters_by_asset() {
+        let req = All::default()
+            .with_asset(usd())
+            .into_request("https://www.google.com")
+            .unwrap();
+        assert_eq!(req.uri().path(), "/accounts");
+        assert_eq!(
+            req.uri().query(),
+            Some(format!("asset=USD:{}", ACCOUNT_ID).as_str())
+        );
+    }
+
+    #[test]
+    fn it_puts_the_query_params_on_the_uri() {
+        let req = All::default()
+            .with_signer(ACCOUNT_ID.into())
+            .with_sponsor(ACCOUNT_ID.into())
+            .with_cursor("CURSOR")
+            .with_order(Direction::Desc)
+            .with_limit(123)
+            .into_request("https://www.google.com")
+            .unwrap();
+        assert_eq!(
+            req.uri().query(),
+            Some(
+                format!(
+                    "signer={0}&sponsor={0}&cursor=CURSOR&order=desc&limit=123",
+                    ACCOUNT_ID
+                )
+                .as_str()
+            )
+        );
+    }
+
+    #[test]
+    fn it_parses_from_a_uri() {
+        let uri: Uri = format!(
+            "/accounts?asset=USD:{}&cursor=CURSOR&order=desc&limit=123",
+            ACCOUNT_ID
+        )
+        .parse()
+        .unwrap();
+        let ep = All::try_from(&uri).unwrap();
+        assert_eq!(ep.asset, Some(usd()));
+        assert_eq!(ep.signer, None);
         assert_eq!(ep.limit, Some(123));
         assert_eq!(ep.cursor, Some("CURSOR".to_string()));
         assert_eq!(ep.order, Some(Direction::Desc));
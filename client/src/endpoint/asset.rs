@@ -106,7 +106,7 @@ impl IntoRequest for All {
             }
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
             }
 
             if let Some(cursor) = self.cursor {
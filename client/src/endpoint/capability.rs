@@ -0,0 +1,52 @@
+use resources::HorizonVersion;
+use std::fmt;
+
+/// An optional feature of horizon that only newer servers have. Older
+/// servers quietly ignore query parameters they don't know, so endpoints
+/// that rely on one declare it through `IntoRequest::required_capability`
+/// and the client checks the server's version before sending them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// The `join` query parameter, which embeds related records such as the
+    /// transaction of each operation.
+    Join,
+    /// Submitting transactions without waiting for them to be applied.
+    AsyncSubmission,
+}
+
+impl Capability {
+    /// The first release of horizon with the capability.
+    pub fn min_version(&self) -> HorizonVersion {
+        match *self {
+            Capability::Join => HorizonVersion::new(0, 24, 0),
+            Capability::AsyncSubmission => HorizonVersion::new(2, 29, 0),
+        }
+    }
+
+    /// Whether a server running `version` has the capability.
+    pub fn is_supported_by(&self, version: HorizonVersion) -> bool {
+        version >= self.min_version()
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Capability::Join => "the join parameter",
+            Capability::AsyncSubmission => "async transaction submission",
+        })
+    }
+}
+
+#[cfg(test)]
+mod capability_tests {
+    use super::*;
+
+    #[test]
+    fn it_knows_which_versions_have_a_capability() {
+        assert!(Capability::Join.is_supported_by(HorizonVersion::new(2, 27, 0)));
+        assert!(Capability::Join.is_supported_by(HorizonVersion::new(0, 24, 0)));
+        assert!(!Capability::Join.is_supported_by(HorizonVersion::new(0, 23, 1)));
+        assert!(!Capability::AsyncSubmission.is_supported_by(HorizonVersion::new(2, 27, 0)));
+    }
+}
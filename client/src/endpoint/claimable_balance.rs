@@ -0,0 +1,501 @@
+//! Contains endpoints for claimable balances and for the transactions and
+//! operations that touched one.
+use super::{Body, Cursor, Direction, IntoRequest, Limit, Order, Records, Streamable};
+use error::Result;
+use http::{Request, Uri};
+use resources::{
+    canonical_asset, AccountId, AssetIdentifier, ClaimableBalance, Operation, Transaction,
+};
+use std::str::FromStr;
+use uri::{self, TryFromUri, UriWrap};
+
+#[cfg(test)]
+const BALANCE_ID: &str = "00000000da0d57da7d4850e7fc10d2a9d0ebc731f7afb40574c03395b17d49149b91f5be";
+
+/// Represents the all claimable balances endpoint on the stellar horizon
+/// server. The endpoint returns the balances that haven't been claimed yet,
+/// optionally only those with a claimant, sponsor or asset. Listing by
+/// claimant includes balances the claimant can't claim yet, or can't any
+/// longer, so check `ClaimableBalance::is_claimable_by` before claiming.
+///
+/// <https://developers.stellar.org/api/horizon/resources/list-all-claimable-balances>
+///
+/// ## Example
+/// ```no_run
+/// use stellar_client::sync::Client;
+/// use stellar_client::endpoint::claimable_balance;
+///
+/// let client = Client::horizon_test().unwrap();
+/// let endpoint = claimable_balance::All::default()
+///     .with_claimant("GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM".into());
+/// let balances = client.request(endpoint).unwrap();
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct All {
+    claimant: Option<AccountId>,
+    sponsor: Option<AccountId>,
+    asset: Option<AssetIdentifier>,
+    cursor: Option<String>,
+    order: Option<Direction>,
+    limit: Option<u32>,
+}
+
+impl_cursor!(All);
+impl_limit!(All);
+impl_order!(All);
+
+impl All {
+    /// Only returns the balances the account is a claimant of.
+    pub fn with_claimant(mut self, claimant: AccountId) -> Self {
+        self.claimant = Some(claimant);
+        self
+    }
+
+    /// Only returns the balances whose reserve the account pays.
+    pub fn with_sponsor(mut self, sponsor: AccountId) -> Self {
+        self.sponsor = Some(sponsor);
+        self
+    }
+
+    /// Only returns the balances of the asset.
+    pub fn with_asset(mut self, asset: AssetIdentifier) -> Self {
+        self.asset = Some(asset);
+        self
+    }
+
+    fn params(&self) -> Vec<String> {
+        let mut params = Vec::new();
+        if let Some(ref claimant) = self.claimant {
+            params.push(format!("claimant={}", claimant));
+        }
+        if let Some(ref sponsor) = self.sponsor {
+            params.push(format!("sponsor={}", sponsor));
+        }
+        if let Some(ref asset) = self.asset {
+            params.push(format!("asset={}", canonical_asset::format(asset)));
+        }
+        if let Some(ref cursor) = self.cursor {
+            params.push(format!("cursor={}", cursor));
+        }
+        if let Some(order) = self.order {
+            params.push(format!("order={}", order));
+        }
+        if let Some(limit) = self.limit {
+            params.push(format!("limit={}", limit));
+        }
+        params
+    }
+}
+
+impl IntoRequest for All {
+    type Response = Records<ClaimableBalance>;
+
+    fn into_request(self, host: &str) -> Result<Request<Body>> {
+        let mut uri = format!("{}/claimable_balances", host);
+        let params = self.params();
+        if !params.is_empty() {
+            uri.push('?');
+            uri.push_str(&params.join("&"));
+        }
+
+        let uri = Uri::from_str(&uri)?;
+        let request = Request::get(uri).body(Body::None)?;
+        Ok(request)
+    }
+}
+
+impl TryFromUri for All {
+    fn try_from_wrap(wrap: &UriWrap) -> ::std::result::Result<All, uri::Error> {
+        let params = wrap.params();
+        let asset = match params.get("asset") {
+            Some(asset) => Some(canonical_asset::parse(asset)?),
+            None => None,
+        };
+        Ok(All {
+            claimant: params.get_parse("claimant").ok(),
+            sponsor: params.get_parse("sponsor").ok(),
+            asset,
+            cursor: params.get_parse("cursor").ok(),
+            order: params.get_parse("order").ok(),
+            limit: params.get_parse("limit").ok(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod all_claimable_balances_tests {
+    use super::*;
+
+    const ISSUER: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+
+    #[test]
+    fn it_leaves_off_the_params_if_not_specified() {
+        let ep = All::default();
+        let req = ep.into_request("https://www.google.com").unwrap();
+        assert_eq!(req.uri().path(), "/claimable_balances");
+        assert_eq!(req.uri().query(), None);
+    }
+
+    #[test]
+    fn it_puts_the_query_params_on_the_uri() {
+        let ep = All::default()
+            .with_claimant(ISSUER.into())
+            .with_asset(AssetIdentifier::alphanum4("USD", ISSUER).unwrap())
+            .with_cursor("CURSOR")
+            .with_limit(123)
+            .with_order(Direction::Desc);
+        let req = ep.into_request("https://www.google.com").unwrap();
+        assert_eq!(req.uri().path(), "/claimable_balances");
+        assert_eq!(
+            req.uri().query(),
+            Some(
+                format!(
+                    "claimant={0}&asset=USD:{0}&cursor=CURSOR&order=desc&limit=123",
+                    ISSUER
+                )
+                .as_str()
+            )
+        );
+    }
+
+    #[test]
+    fn it_parses_query_params_from_uri() {
+        let uri: Uri = format!(
+            "/claimable_balances?sponsor={0}&asset=USD:{0}&order=desc&cursor=CURSOR&limit=123",
+            ISSUER
+        )
+        .parse()
+        .unwrap();
+        let all = All::try_from(&uri).unwrap();
+        assert_eq!(all.sponsor, Some(ISSUER.into()));
+        assert_eq!(all.claimant, None);
+        assert_eq!(
+            all.asset,
+            Some(AssetIdentifier::alphanum4("USD", ISSUER).unwrap())
+        );
+        assert_eq!(all.order, Some(Direction::Desc));
+        assert_eq!(all.cursor, Some("CURSOR".to_string()));
+        assert_eq!(all.limit, Some(123));
+    }
+}
+
+/// Represents the transactions for claimable balance endpoint on the stellar
+/// horizon server. The endpoint will return all the transactions that
+/// created, claimed or clawed back a single claimable balance.
+///
+/// <https://developers.stellar.org/api/horizon/resources/claimablebalances/transactions>
+///
+/// ## Example
+/// ```no_run
+/// use stellar_client::sync::Client;
+/// use stellar_client::endpoint::claimable_balance;
+///
+/// let client = Client::horizon_test().unwrap();
+/// let endpoint = claimable_balance::Transactions::new(
+///     "00000000da0d57da7d4850e7fc10d2a9d0ebc731f7afb40574c03395b17d49149b91f5be".to_string(),
+/// );
+/// let balance_txns = client.request(endpoint).unwrap();
+///
+/// assert!(balance_txns.records().len() > 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Transactions {
+    balance_id: String,
+    cursor: Option<String>,
+    order: Option<Direction>,
+    limit: Option<u32>,
+    include_failed: bool,
+}
+
+impl_cursor!(Transactions);
+impl_limit!(Transactions);
+impl_order!(Transactions);
+
+impl Transactions {
+    /// Creates a new claimable_balance::Transactions endpoint struct from the
+    /// hex encoded id of the balance.
+    ///
+    /// ```
+    /// use stellar_client::endpoint::claimable_balance;
+    ///
+    /// let txns = claimable_balance::Transactions::new(
+    ///     "00000000da0d57da7d4850e7fc10d2a9d0ebc731f7afb40574c03395b17d49149b91f5be".to_string(),
+    /// );
+    /// ```
+    pub fn new(balance_id: String) -> Transactions {
+        Transactions {
+            balance_id,
+            cursor: None,
+            order: None,
+            limit: None,
+            include_failed: false,
+        }
+    }
+
+    fn has_query(&self) -> bool {
+        self.order.is_some() || self.cursor.is_some() || self.limit.is_some() || self.include_failed
+    }
+
+    /// Also lists transactions that failed, which horizon leaves out by
+    /// default. Their `failure_reasons` say why they failed.
+    pub fn with_include_failed(mut self, include_failed: bool) -> Self {
+        self.include_failed = include_failed;
+        self
+    }
+}
+
+impl IntoRequest for Transactions {
+    type Response = Records<Transaction>;
+
+    fn into_request(self, host: &str) -> Result<Request<Body>> {
+        let mut uri = format!(
+            "{}/claimable_balances/{}/transactions",
+            host, self.balance_id
+        );
+
+        if self.has_query() {
+            uri.push_str("?");
+
+            if let Some(order) = self.order {
+                uri.push_str(&format!("order={}&", order));
+            }
+
+            if self.include_failed {
+                uri.push_str("include_failed=true&");
+            }
+
+            if let Some(cursor) = self.cursor {
+                uri.push_str(&format!("cursor={}&", cursor));
+            }
+
+            if let Some(limit) = self.limit {
+                uri.push_str(&format!("limit={}", limit));
+            }
+        }
+
+        let uri = Uri::from_str(&uri)?;
+        let request = Request::get(uri).body(Body::None)?;
+        Ok(request)
+    }
+}
+
+impl TryFromUri for Transactions {
+    fn try_from_wrap(wrap: &UriWrap) -> ::std::result::Result<Self, uri::Error> {
+        match wrap.path() {
+            ["claimable_balances", balance_id, "transactions"] => {
+                let params = wrap.params();
+                Ok(Self {
+                    balance_id: balance_id.to_string(),
+                    cursor: params.get_parse("cursor").ok(),
+                    order: params.get_parse("order").ok(),
+                    limit: params.get_parse("limit").ok(),
+                    include_failed: params.get("include_failed") == Some("true"),
+                })
+            }
+            _ => Err(uri::Error::invalid_path()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod claimable_balance_transactions_tests {
+    use super::*;
+
+    #[test]
+    fn it_leaves_off_the_params_if_not_specified() {
+        let ep = Transactions::new(BALANCE_ID.to_string());
+        let req = ep.into_request("https://www.google.com").unwrap();
+        assert_eq!(
+            req.uri().path(),
+            format!("/claimable_balances/{}/transactions", BALANCE_ID)
+        );
+        assert_eq!(req.uri().query(), None);
+    }
+
+    #[test]
+    fn it_puts_the_query_params_on_the_uri() {
+        let ep = Transactions::new(BALANCE_ID.to_string())
+            .with_cursor("CURSOR")
+            .with_limit(123)
+            .with_order(Direction::Desc)
+            .with_include_failed(true);
+        let req = ep.into_request("https://www.google.com").unwrap();
+        assert_eq!(
+            req.uri().path(),
+            format!("/claimable_balances/{}/transactions", BALANCE_ID)
+        );
+        assert_eq!(
+            req.uri().query(),
+            Some("order=desc&include_failed=true&cursor=CURSOR&limit=123")
+        );
+    }
+
+    #[test]
+    fn it_parses_from_a_uri() {
+        let uri: Uri = format!(
+            "/claimable_balances/{}/transactions?cursor=CURSOR&order=desc&limit=123&include_failed=true",
+            BALANCE_ID
+        )
+        .parse()
+        .unwrap();
+        let ep = Transactions::try_from(&uri).unwrap();
+        assert_eq!(ep.balance_id, BALANCE_ID);
+        assert_eq!(ep.limit, Some(123));
+        assert_eq!(ep.cursor, Some("CURSOR".to_string()));
+        assert_eq!(ep.order, Some(Direction::Desc));
+        assert!(ep.include_failed);
+    }
+}
+
+/// Represents the operations for claimable balance endpoint on the stellar
+/// horizon server. The endpoint will return all the operations that created,
+/// claimed or clawed back a single claimable balance.
+///
+/// <https://developers.stellar.org/api/horizon/resources/claimablebalances/operations>
+///
+/// ## Example
+/// ```no_run
+/// use stellar_client::sync::Client;
+/// use stellar_client::endpoint::claimable_balance;
+///
+/// let client = Client::horizon_test().unwrap();
+/// let endpoint = claimable_balance::Operations::new(
+///     "00000000da0d57da7d4850e7fc10d2a9d0ebc731f7afb40574c03395b17d49149b91f5be".to_string(),
+/// );
+/// let balance_operations = client.request(endpoint).unwrap();
+///
+/// assert!(balance_operations.records().len() > 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Operations {
+    balance_id: String,
+    cursor: Option<String>,
+    order: Option<Direction>,
+    limit: Option<u32>,
+}
+
+impl_cursor!(Operations);
+impl_limit!(Operations);
+impl_order!(Operations);
+
+impl Operations {
+    /// Creates a new claimable_balance::Operations endpoint struct from the
+    /// hex encoded id of the balance.
+    ///
+    /// ```
+    /// use stellar_client::endpoint::claimable_balance;
+    ///
+    /// let ops = claimable_balance::Operations::new(
+    ///     "00000000da0d57da7d4850e7fc10d2a9d0ebc731f7afb40574c03395b17d49149b91f5be".to_string(),
+    /// );
+    /// ```
+    pub fn new(balance_id: String) -> Operations {
+        Operations {
+            balance_id,
+            cursor: None,
+            order: None,
+            limit: None,
+        }
+    }
+
+    fn has_query(&self) -> bool {
+        self.order.is_some() || self.cursor.is_some() || self.limit.is_some()
+    }
+}
+
+impl IntoRequest for Operations {
+    type Response = Records<Operation>;
+
+    fn into_request(self, host: &str) -> Result<Request<Body>> {
+        let mut uri = format!("{}/claimable_balances/{}/operations", host, self.balance_id);
+
+        if self.has_query() {
+            uri.push_str("?");
+
+            if let Some(order) = self.order {
+                uri.push_str(&format!("order={}&", order));
+            }
+
+            if let Some(cursor) = self.cursor {
+                uri.push_str(&format!("cursor={}&", cursor));
+            }
+
+            if let Some(limit) = self.limit {
+                uri.push_str(&format!("limit={}", limit));
+            }
+        }
+
+        let uri = Uri::from_str(&uri)?;
+        let request = Request::get(uri).body(Body::None)?;
+        Ok(request)
+    }
+}
+
+impl Streamable for Operations {
+    type Event = Operation;
+}
+
+impl TryFromUri for Operations {
+    fn try_from_wrap(wrap: &UriWrap) -> ::std::result::Result<Self, uri::Error> {
+        match wrap.path() {
+            ["claimable_balances", balance_id, "operations"] => {
+                let params = wrap.params();
+                Ok(Self {
+                    balance_id: balance_id.to_string(),
+                    cursor: params.get_parse("cursor").ok(),
+                    order: params.get_parse("order").ok(),
+                    limit: params.get_parse("limit").ok(),
+                })
+            }
+            _ => Err(uri::Error::invalid_path()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod claimable_balance_operations_tests {
+    use super::*;
+
+    #[test]
+    fn it_leaves_off_the_params_if_not_specified() {
+        let ep = Operations::new(BALANCE_ID.to_string());
+        let req = ep.into_request("https://www.google.com").unwrap();
+        assert_eq!(
+            req.uri().path(),
+            format!("/claimable_balances/{}/operations", BALANCE_ID)
+        );
+        assert_eq!(req.uri().query(), None);
+    }
+
+    #[test]
+    fn it_puts_the_query_params_on_the_uri() {
+        let ep = Operations::new(BALANCE_ID.to_string())
+            .with_cursor("CURSOR")
+            .with_limit(123)
+            .with_order(Direction::Desc);
+        let req = ep.into_request("https://www.google.com").unwrap();
+        assert_eq!(
+            req.uri().path(),
+            format!("/claimable_balances/{}/operations", BALANCE_ID)
+        );
+        assert_eq!(
+            req.uri().query(),
+            Some("order=desc&cursor=CURSOR&limit=123")
+        );
+    }
+
+    #[test]
+    fn it_parses_from_a_uri() {
+        let uri: Uri = format!(
+            "/claimable_balances/{}/operations?cursor=CURSOR&order=desc&limit=123",
+            BALANCE_ID
+        )
+        .parse()
+        .unwrap();
+        let ep = Operations::try_from(&uri).unwrap();
+        assert_eq!(ep.balance_id, BALANCE_ID);
+        assert_eq!(ep.limit, Some(123));
+        assert_eq!(ep.cursor, Some("CURSOR".to_string()));
+        assert_eq!(ep.order, Some(Direction::Desc));
+    }
+}
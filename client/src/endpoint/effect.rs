@@ -1,5 +1,5 @@
 //! Contains the endpoint for all effects.
-use super::{Body, Cursor, Direction, IntoRequest, Limit, Order, Records};
+use super::{Body, Cursor, Direction, IntoRequest, Limit, Order, Records, Streamable};
 use error::Result;
 use http::{Request, Uri};
 use resources::Effect;
@@ -54,7 +54,7 @@ impl IntoRequest for All {
             uri.push_str("?");
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
             }
 
             if let Some(cursor) = self.cursor {
@@ -72,6 +72,10 @@ impl IntoRequest for All {
     }
 }
 
+impl Streamable for All {
+    type Event = Effect;
+}
+
 impl TryFromUri for All {
     fn try_from_wrap(wrap: &UriWrap) -> ::std::result::Result<Self, uri::Error> {
         let params = wrap.params();
@@ -0,0 +1,99 @@
+//! Contains the endpoint for funding test accounts with friendbot.
+use super::{Body, IntoRequest};
+use error::Result;
+use http::{Request, Uri};
+use resources::{AccountId, SubmittedTransaction};
+use std::str::FromStr;
+
+/// Asks friendbot to create and fund an account with test lumens. Friendbot
+/// only exists on test networks, where horizon serves it at `/friendbot`.
+/// Funding an account that already exists is answered with a bad request.
+///
+/// A friendbot running elsewhere, such as the one of a local standalone
+/// network, can be used with `with_friendbot`. `sync::Client::friendbot`
+/// does this for the client's network.
+///
+/// <https://developers.stellar.org/docs/fundamentals-and-concepts/testnet-and-pubnet#friendbot>
+///
+/// ## Example
+/// ```no_run
+/// use stellar_client::sync::Client;
+/// use stellar_client::endpoint::friendbot;
+///
+/// let client      = Client::horizon_test().unwrap();
+/// let endpoint    = friendbot::Fund::new("GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM".into());
+/// let transaction = client.request(endpoint).unwrap();
+/// println!("funded in ledger {}", transaction.ledger());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Fund {
+    account_id: AccountId,
+    friendbot_uri: Option<String>,
+}
+
+impl Fund {
+    /// Funds the account with the given id.
+    pub fn new(account_id: AccountId) -> Fund {
+        Fund {
+            account_id,
+            friendbot_uri: None,
+        }
+    }
+
+    /// Asks the friendbot at `uri` rather than the one horizon serves.
+    pub fn with_friendbot(mut self, uri: &str) -> Fund {
+        self.friendbot_uri = Some(uri.to_string());
+        self
+    }
+
+    /// The account that will be funded.
+    pub fn account_id(&self) -> &AccountId {
+        &self.account_id
+    }
+}
+
+impl IntoRequest for Fund {
+    type Response = SubmittedTransaction;
+
+    fn into_request(self, host: &str) -> Result<Request<Body>> {
+        let uri = match self.friendbot_uri {
+            Some(ref friendbot) => format!("{}?addr={}", friendbot, self.account_id),
+            None => format!("{}/friendbot?addr={}", host, self.account_id),
+        };
+        let uri = Uri::from_str(&uri)?;
+        let request = Request::get(uri).body(Body::None)?;
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod friendbot_fund_tests {
+    use super::*;
+
+    const ACCOUNT_ID: &str = "GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM";
+
+    #[test]
+    fn it_can_make_a_fund_uri() {
+        let request = Fund::new(ACCOUNT_ID.into())
+            .into_request("https://horizon-testnet.stellar.org")
+            .unwrap();
+        assert_eq!(request.uri().host().unwrap(), "horizon-testnet.stellar.org");
+        assert_eq!(request.uri().path(), "/friendbot");
+        assert_eq!(
+            request.uri().query(),
+            Some(format!("addr={}", ACCOUNT_ID).as_str())
+        );
+    }
+
+    #[test]
+    fn it_can_use_another_friendbot() {
+        let request = Fund::new(ACCOUNT_ID.into())
+            .with_friendbot("http://localhost:8000/friendbot")
+            .into_request("https://horizon-testnet.stellar.org")
+            .unwrap();
+        assert_eq!(
+            request.uri().to_string(),
+            format!("http://localhost:8000/friendbot?addr={}", ACCOUNT_ID)
+        );
+    }
+}
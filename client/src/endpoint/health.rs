@@ -0,0 +1,50 @@
+//! Contains the endpoint for checking the health of a horizon server.
+use super::{Body, IntoRequest};
+use error::Result;
+use http::{Request, Uri};
+use resources::Health;
+use std::str::FromStr;
+
+/// Represents the health check endpoint of a horizon server. Horizon answers
+/// with a 503 when it is unhealthy, which the client reports as a server error.
+///
+/// <https://developers.stellar.org/api/aggregations/health/>
+///
+/// ## Example
+/// ```no_run
+/// use stellar_client::sync::Client;
+/// use stellar_client::endpoint::health;
+///
+/// let client      = Client::horizon_test().unwrap();
+/// let endpoint    = health::Check;
+/// let health      = client.request(endpoint).unwrap();
+/// #
+/// # assert!(health.is_healthy());
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Check;
+
+impl IntoRequest for Check {
+    type Response = Health;
+
+    fn into_request(self, host: &str) -> Result<Request<Body>> {
+        let uri = Uri::from_str(&format!("{}/health", host))?;
+        let request = Request::get(uri).body(Body::None)?;
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod health_check_tests {
+    use super::*;
+
+    #[test]
+    fn it_can_make_a_health_check_uri() {
+        let request = Check
+            .into_request("https://horizon-testnet.stellar.org")
+            .unwrap();
+        assert_eq!(request.uri().host().unwrap(), "horizon-testnet.stellar.org");
+        assert_eq!(request.uri().path(), "/health");
+        assert_eq!(request.uri().query(), None);
+    }
+}
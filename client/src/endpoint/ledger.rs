@@ -1,8 +1,8 @@
 //! Contains the endpoint for all ledgers.
-use super::{Body, Cursor, Direction, IntoRequest, Limit, Order, Records};
+use super::{Body, Cursor, Direction, IntoRequest, Limit, Order, Projected, Records, Streamable};
 use error::Result;
 use http::{Request, Uri};
-use resources::{Effect, Ledger, Operation, Transaction};
+use resources::{Effect, Ledger, Operation, Transaction, TransactionHeader};
 use std::str::FromStr;
 use uri::{self, TryFromUri, UriWrap};
 
@@ -49,7 +49,7 @@ impl IntoRequest for All {
             uri.push_str("?");
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
             }
 
             if let Some(cursor) = self.cursor {
@@ -67,6 +67,10 @@ impl IntoRequest for All {
     }
 }
 
+impl Streamable for All {
+    type Event = Ledger;
+}
+
 impl TryFromUri for All {
     fn try_from_wrap(wrap: &UriWrap) -> ::std::result::Result<All, uri::Error> {
         let params = wrap.params();
@@ -248,7 +252,7 @@ impl IntoRequest for Payments {
             uri.push_str("?");
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
             }
 
             if let Some(cursor) = self.cursor {
@@ -351,6 +355,7 @@ pub struct Transactions {
     cursor: Option<String>,
     order: Option<Direction>,
     limit: Option<u32>,
+    include_failed: bool,
 }
 
 impl_cursor!(Transactions);
@@ -371,11 +376,26 @@ impl Transactions {
             cursor: None,
             order: None,
             limit: None,
+            include_failed: false,
         }
     }
 
     fn has_query(&self) -> bool {
-        self.order.is_some() || self.cursor.is_some() || self.limit.is_some()
+        self.order.is_some() || self.cursor.is_some() || self.limit.is_some() || self.include_failed
+    }
+
+    /// Also lists transactions that failed, which horizon leaves out by
+    /// default. Their `failure_reasons` say why they failed.
+    pub fn with_include_failed(mut self, include_failed: bool) -> Self {
+        self.include_failed = include_failed;
+        self
+    }
+
+    /// Parses the transactions into `TransactionHeader`s, which leave out
+    /// the xdr, for consumers that only need to know who sent what and
+    /// when.
+    pub fn without_xdr(self) -> Projected<Self, TransactionHeader> {
+        Projected::new(self)
     }
 }
 
@@ -389,7 +409,11 @@ impl IntoRequest for Transactions {
             uri.push_str("?");
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
+            }
+
+            if self.include_failed {
+                uri.push_str("include_failed=true&");
             }
 
             if let Some(cursor) = self.cursor {
@@ -417,6 +441,7 @@ impl TryFromUri for Transactions {
                     cursor: params.get_parse("cursor").ok(),
                     order: params.get_parse("order").ok(),
                     limit: params.get_parse("limit").ok(),
+                    include_failed: params.get("include_failed") == Some("true"),
                 })
             }
             _ => Err(uri::Error::invalid_path()),
@@ -532,7 +557,7 @@ impl IntoRequest for Effects {
             uri.push_str("?");
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
             }
 
             if let Some(cursor) = self.cursor {
@@ -675,7 +700,7 @@ impl IntoRequest for Operations {
             uri.push_str("?");
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
             }
 
             if let Some(cursor) = self.cursor {
@@ -0,0 +1,329 @@
+//! Contains endpoints for the transactions and operations that touched a
+//! liquidity pool.
+use super::{Body, Cursor, Direction, IntoRequest, Limit, Order, Records, Streamable};
+use error::Result;
+use http::{Request, Uri};
+use resources::{Operation, Transaction};
+use std::str::FromStr;
+use uri::{self, TryFromUri, UriWrap};
+
+#[cfg(test)]
+const POOL_ID: &str = "67260c4c1807b262ff851b0a3fe141194936bb0215b2f77447f1df11998eabb9";
+
+/// Represents the transactions for liquidity pool endpoint on the stellar
+/// horizon server. The endpoint will return all the transactions that
+/// deposited into, withdrew from or traded against a single liquidity pool.
+///
+/// <https://developers.stellar.org/api/horizon/resources/liquiditypools/transactions>
+///
+/// ## Example
+/// ```no_run
+/// use stellar_client::sync::Client;
+/// use stellar_client::endpoint::liquidity_pool;
+///
+/// let client = Client::horizon_test().unwrap();
+/// let endpoint = liquidity_pool::Transactions::new(
+///     "67260c4c1807b262ff851b0a3fe141194936bb0215b2f77447f1df11998eabb9".to_string(),
+/// );
+/// let pool_txns = client.request(endpoint).unwrap();
+///
+/// assert!(pool_txns.records().len() > 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Transactions {
+    pool_id: String,
+    cursor: Option<String>,
+    order: Option<Direction>,
+    limit: Option<u32>,
+    include_failed: bool,
+}
+
+impl_cursor!(Transactions);
+impl_limit!(Transactions);
+impl_order!(Transactions);
+
+impl Transactions {
+    /// Creates a new liquidity_pool::Transactions endpoint struct from the
+    /// hex encoded id of the pool.
+    ///
+    /// ```
+    /// use stellar_client::endpoint::liquidity_pool;
+    ///
+    /// let txns = liquidity_pool::Transactions::new(
+    ///     "67260c4c1807b262ff851b0a3fe141194936bb0215b2f77447f1df11998eabb9".to_string(),
+    /// );
+    /// ```
+    pub fn new(pool_id: String) -> Transactions {
+        Transactions {
+            pool_id,
+            cursor: None,
+            order: None,
+            limit: None,
+            include_failed: false,
+        }
+    }
+
+    fn has_query(&self) -> bool {
+        self.order.is_some() || self.cursor.is_some() || self.limit.is_some() || self.include_failed
+    }
+
+    /// Also lists transactions that failed, which horizon leaves out by
+    /// default. Their `failure_reasons` say why they failed.
+    pub fn with_include_failed(mut self, include_failed: bool) -> Self {
+        self.include_failed = include_failed;
+        self
+    }
+}
+
+impl IntoRequest for Transactions {
+    type Response = Records<Transaction>;
+
+    fn into_request(self, host: &str) -> Result<Request<Body>> {
+        let mut uri = format!("{}/liquidity_pools/{}/transactions", host, self.pool_id);
+
+        if self.has_query() {
+            uri.push_str("?");
+
+            if let Some(order) = self.order {
+                uri.push_str(&format!("order={}&", order));
+            }
+
+            if self.include_failed {
+                uri.push_str("include_failed=true&");
+            }
+
+            if let Some(cursor) = self.cursor {
+                uri.push_str(&format!("cursor={}&", cursor));
+            }
+
+            if let Some(limit) = self.limit {
+                uri.push_str(&format!("limit={}", limit));
+            }
+        }
+
+        let uri = Uri::from_str(&uri)?;
+        let request = Request::get(uri).body(Body::None)?;
+        Ok(request)
+    }
+}
+
+impl TryFromUri for Transactions {
+    fn try_from_wrap(wrap: &UriWrap) -> ::std::result::Result<Self, uri::Error> {
+        match wrap.path() {
+            ["liquidity_pools", pool_id, "transactions"] => {
+                let params = wrap.params();
+                Ok(Self {
+                    pool_id: pool_id.to_string(),
+                    cursor: params.get_parse("cursor").ok(),
+                    order: params.get_parse("order").ok(),
+                    limit: params.get_parse("limit").ok(),
+                    include_failed: params.get("include_failed") == Some("true"),
+                })
+            }
+            _ => Err(uri::Error::invalid_path()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod liquidity_pool_transactions_tests {
+    use super::*;
+
+    #[test]
+    fn it_leaves_off_the_params_if_not_specified() {
+        let ep = Transactions::new(POOL_ID.to_string());
+        let req = ep.into_request("https://www.google.com").unwrap();
+        assert_eq!(
+            req.uri().path(),
+            format!("/liquidity_pools/{}/transactions", POOL_ID)
+        );
+        assert_eq!(req.uri().query(), None);
+    }
+
+    #[test]
+    fn it_puts_the_query_params_on_the_uri() {
+        let ep = Transactions::new(POOL_ID.to_string())
+            .with_cursor("CURSOR")
+            .with_limit(123)
+            .with_order(Direction::Desc)
+            .with_include_failed(true);
+        let req = ep.into_request("https://www.google.com").unwrap();
+        assert_eq!(
+            req.uri().path(),
+            format!("/liquidity_pools/{}/transactions", POOL_ID)
+        );
+        assert_eq!(
+            req.uri().query(),
+            Some("order=desc&include_failed=true&cursor=CURSOR&limit=123")
+        );
+    }
+
+    #[test]
+    fn it_parses_from_a_uri() {
+        let uri: Uri = format!(
+            "/liquidity_pools/{}/transactions?cursor=CURSOR&order=desc&limit=123&include_failed=true",
+            POOL_ID
+        )
+        .parse()
+        .unwrap();
+        let ep = Transactions::try_from(&uri).unwrap();
+        assert_eq!(ep.pool_id, POOL_ID);
+        assert_eq!(ep.limit, Some(123));
+        assert_eq!(ep.cursor, Some("CURSOR".to_string()));
+        assert_eq!(ep.order, Some(Direction::Desc));
+        assert!(ep.include_failed);
+    }
+}
+
+/// Represents the operations for liquidity pool endpoint on the stellar
+/// horizon server. The endpoint will return all the operations that deposited
+/// into, withdrew from or traded against a single liquidity pool.
+///
+/// <https://developers.stellar.org/api/horizon/resources/liquiditypools/operations>
+///
+/// ## Example
+/// ```no_run
+/// use stellar_client::sync::Client;
+/// use stellar_client::endpoint::liquidity_pool;
+///
+/// let client = Client::horizon_test().unwrap();
+/// let endpoint = liquidity_pool::Operations::new(
+///     "67260c4c1807b262ff851b0a3fe141194936bb0215b2f77447f1df11998eabb9".to_string(),
+/// );
+/// let pool_operations = client.request(endpoint).unwrap();
+///
+/// assert!(pool_operations.records().len() > 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Operations {
+    pool_id: String,
+    cursor: Option<String>,
+    order: Option<Direction>,
+    limit: Option<u32>,
+}
+
+impl_cursor!(Operations);
+impl_limit!(Operations);
+impl_order!(Operations);
+
+impl Operations {
+    /// Creates a new liquidity_pool::Operations endpoint struct from the
+    /// hex encoded id of the pool.
+    ///
+    /// ```
+    /// use stellar_client::endpoint::liquidity_pool;
+    ///
+    /// let ops = liquidity_pool::Operations::new(
+    ///     "67260c4c1807b262ff851b0a3fe141194936bb0215b2f77447f1df11998eabb9".to_string(),
+    /// );
+    /// ```
+    pub fn new(pool_id: String) -> Operations {
+        Operations {
+            pool_id,
+            cursor: None,
+            order: None,
+            limit: None,
+        }
+    }
+
+    fn has_query(&self) -> bool {
+        self.order.is_some() || self.cursor.is_some() || self.limit.is_some()
+    }
+}
+
+impl IntoRequest for Operations {
+    type Response = Records<Operation>;
+
+    fn into_request(self, host: &str) -> Result<Request<Body>> {
+        let mut uri = format!("{}/liquidity_pools/{}/operations", host, self.pool_id);
+
+        if self.has_query() {
+            uri.push_str("?");
+
+            if let Some(order) = self.order {
+                uri.push_str(&format!("order={}&", order));
+            }
+
+            if let Some(cursor) = self.cursor {
+                uri.push_str(&format!("cursor={}&", cursor));
+            }
+
+            if let Some(limit) = self.limit {
+                uri.push_str(&format!("limit={}", limit));
+            }
+        }
+
+        let uri = Uri::from_str(&uri)?;
+        let request = Request::get(uri).body(Body::None)?;
+        Ok(request)
+    }
+}
+
+impl Streamable for Operations {
+    type Event = Operation;
+}
+
+impl TryFromUri for Operations {
+    fn try_from_wrap(wrap: &UriWrap) -> ::std::result::Result<Self, uri::Error> {
+        match wrap.path() {
+            ["liquidity_pools", pool_id, "operations"] => {
+                let params = wrap.params();
+                Ok(Self {
+                    pool_id: pool_id.to_string(),
+                    cursor: params.get_parse("cursor").ok(),
+                    order: params.get_parse("order").ok(),
+                    limit: params.get_parse("limit").ok(),
+                })
+            }
+            _ => Err(uri::Error::invalid_path()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod liquidity_pool_operations_tests {
+    use super::*;
+
+    #[test]
+    fn it_leaves_off_the_params_if_not_specified() {
+        let ep = Operations::new(POOL_ID.to_string());
+        let req = ep.into_request("https://www.google.com").unwrap();
+        assert_eq!(
+            req.uri().path(),
+            format!("/liquidity_pools/{}/operations", POOL_ID)
+        );
+        assert_eq!(req.uri().query(), None);
+    }
+
+    #[test]
+    fn it_puts_the_query_params_on_the_uri() {
+        let ep = Operations::new(POOL_ID.to_string())
+            .with_cursor("CURSOR")
+            .with_limit(123)
+            .with_order(Direction::Desc);
+        let req = ep.into_request("https://www.google.com").unwrap();
+        assert_eq!(
+            req.uri().path(),
+            format!("/liquidity_pools/{}/operations", POOL_ID)
+        );
+        assert_eq!(
+            req.uri().query(),
+            Some("order=desc&cursor=CURSOR&limit=123")
+        );
+    }
+
+    #[test]
+    fn it_parses_from_a_uri() {
+        let uri: Uri = format!(
+            "/liquidity_pools/{}/operations?cursor=CURSOR&order=desc&limit=123",
+            POOL_ID
+        )
+        .parse()
+        .unwrap();
+        let ep = Operations::try_from(&uri).unwrap();
+        assert_eq!(ep.pool_id, POOL_ID);
+        assert_eq!(ep.limit, Some(123));
+        assert_eq!(ep.cursor, Some("CURSOR".to_string()));
+        assert_eq!(ep.order, Some(Direction::Desc));
+    }
+}
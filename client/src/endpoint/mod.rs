@@ -28,28 +28,41 @@ mod limit;
 #[macro_use]
 mod order;
 
+mod capability;
+mod projection;
 mod records;
 
 pub mod account;
 pub mod asset;
+pub mod claimable_balance;
 pub mod effect;
+pub mod friendbot;
+pub mod health;
 pub mod ledger;
+pub mod liquidity_pool;
+pub mod offer;
 pub mod operation;
 pub mod orderbook;
 pub mod payment;
+pub mod root;
 pub mod trade;
 pub mod transaction;
 
+pub use self::capability::Capability;
 pub use self::cursor::Cursor;
 pub use self::limit::Limit;
 pub use self::order::{Direction, Order, ParseDirectionError};
-pub use self::records::Records;
+pub use self::projection::Projected;
+pub use self::records::{Records, RecordsRef};
 
 /// Represents the body of a request to an IntoRequest.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Body {
     /// Declares that the endpoint does not have a body.
     None,
+    /// A url encoded form, which is how horizon takes the transactions that
+    /// are submitted to it.
+    Form(String),
 }
 
 /// Declares the definition of a stellar endpoint and the return type.
@@ -60,4 +73,19 @@ pub trait IntoRequest {
 
     /// Converts the implementing struct into an http request.
     fn into_request(self, host: &str) -> Result<http::Request<Body>>;
+
+    /// A feature the server needs for the request to mean what it says.
+    /// The sync client refuses to send the request to a server that is too
+    /// old to have it.
+    fn required_capability(&self) -> Option<Capability> {
+        None
+    }
+}
+
+/// An endpoint that horizon can also stream as server sent events, pushing a
+/// new event each time the resource changes. Pass one to
+/// `sync::Client::stream`.
+pub trait Streamable: IntoRequest {
+    /// The resource carried by each event.
+    type Event: DeserializeOwned;
 }
@@ -0,0 +1,198 @@
+//! Contains the endpoint for all offers.
+use super::{Body, Cursor, Direction, IntoRequest, Limit, Order, Records};
+use error::Result;
+use http::{Request, Uri};
+use resources::{AccountId, AssetIdentifier, Offer};
+use std::str::FromStr;
+use uri::{self, TryFromUri, UriWrap};
+
+/// Represents the all offers end point for the stellar horizon server. The
+/// endpoint returns the live offers on the order books, optionally only
+/// those selling or buying an asset or made by a single seller.
+///
+/// <https://developers.stellar.org/api/resources/offers/list/>
+///
+/// ## Example
+/// ```no_run
+/// use stellar_client::sync::Client;
+/// use stellar_client::endpoint::{offer, Limit};
+/// use stellar_client::resources::AssetIdentifier;
+///
+/// let client   = Client::horizon_test().unwrap();
+/// let endpoint = offer::All::default()
+///     .with_selling(AssetIdentifier::native())
+///     .with_limit(5);
+/// let records  = client.request(endpoint).unwrap();
+/// #
+/// # assert!(records.records().iter().all(|offer| offer.selling().is_native()));
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct All {
+    seller: Option<AccountId>,
+    selling: Option<AssetIdentifier>,
+    buying: Option<AssetIdentifier>,
+    cursor: Option<String>,
+    order: Option<Direction>,
+    limit: Option<u32>,
+}
+
+impl_cursor!(All);
+impl_limit!(All);
+impl_order!(All);
+
+impl All {
+    /// Only returns the offers made by the seller.
+    pub fn with_seller(mut self, seller: AccountId) -> Self {
+        self.seller = Some(seller);
+        self
+    }
+
+    /// Only returns the offers that sell the asset.
+    pub fn with_selling(mut self, asset: AssetIdentifier) -> Self {
+        self.selling = Some(asset);
+        self
+    }
+
+    /// Only returns the offers that buy the asset.
+    pub fn with_buying(mut self, asset: AssetIdentifier) -> Self {
+        self.buying = Some(asset);
+        self
+    }
+
+    fn params(&self) -> Vec<String> {
+        let mut params = Vec::new();
+        if let Some(ref seller) = self.seller {
+            params.push(format!("seller={}", seller));
+        }
+        if let Some(ref selling) = self.selling {
+            push_asset(&mut params, "selling", selling);
+        }
+        if let Some(ref buying) = self.buying {
+            push_asset(&mut params, "buying", buying);
+        }
+        if let Some(ref cursor) = self.cursor {
+            params.push(format!("cursor={}", cursor));
+        }
+        if let Some(order) = self.order {
+            params.push(format!("order={}", order));
+        }
+        if let Some(limit) = self.limit {
+            params.push(format!("limit={}", limit));
+        }
+        params
+    }
+}
+
+fn push_asset(params: &mut Vec<String>, side: &str, asset: &AssetIdentifier) {
+    params.push(format!("{}_asset_type={}", side, asset.asset_type()));
+    if let (Some(code), Some(issuer)) = (asset.code(), asset.issuer()) {
+        params.push(format!("{}_asset_code={}", side, code));
+        params.push(format!("{}_asset_issuer={}", side, issuer));
+    }
+}
+
+fn parse_asset(
+    wrap: &UriWrap,
+    side: &str,
+) -> ::std::result::Result<Option<AssetIdentifier>, uri::Error> {
+    let params = wrap.params();
+    match params.get_ok(&format!("{}_asset_type", side)) {
+        Ok(asset_type) => Ok(Some(AssetIdentifier::new(
+            asset_type,
+            params.get_parse(&format!("{}_asset_code", side)).ok(),
+            params.get_parse(&format!("{}_asset_issuer", side)).ok(),
+        )?)),
+        Err(_) => Ok(None),
+    }
+}
+
+impl IntoRequest for All {
+    type Response = Records<Offer>;
+
+    fn into_request(self, host: &str) -> Result<Request<Body>> {
+        let mut uri = format!("{}/offers", host);
+        let params = self.params();
+        if !params.is_empty() {
+            uri.push('?');
+            uri.push_str(&params.join("&"));
+        }
+
+        let uri = Uri::from_str(&uri)?;
+        let request = Request::get(uri).body(Body::None)?;
+        Ok(request)
+    }
+}
+
+impl TryFromUri for All {
+    fn try_from_wrap(wrap: &UriWrap) -> ::std::result::Result<All, uri::Error> {
+        let params = wrap.params();
+        Ok(All {
+            seller: params.get_parse("seller").ok(),
+            selling: parse_asset(wrap, "selling")?,
+            buying: parse_asset(wrap, "buying")?,
+            cursor: params.get_parse("cursor").ok(),
+            order: params.get_parse("order").ok(),
+            limit: params.get_parse("limit").ok(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod all_offers_tests {
+    use super::*;
+
+    const ISSUER: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+
+    #[test]
+    fn it_leaves_off_the_params_if_not_specified() {
+        let ep = All::default();
+        let req = ep.into_request("https://www.google.com").unwrap();
+        assert_eq!(req.uri().path(), "/offers");
+        assert_eq!(req.uri().query(), None);
+    }
+
+    #[test]
+    fn it_puts_the_query_params_on_the_uri() {
+        let ep = All::default()
+            .with_selling(AssetIdentifier::native())
+            .with_buying(AssetIdentifier::alphanum4("USD", ISSUER).unwrap())
+            .with_cursor("CURSOR")
+            .with_limit(123)
+            .with_order(Direction::Desc);
+        let req = ep.into_request("https://www.google.com").unwrap();
+        assert_eq!(req.uri().path(), "/offers");
+        assert_eq!(
+            req.uri().query(),
+            Some(
+                format!(
+                    "selling_asset_type=native&buying_asset_type=credit_alphanum4&\
+                     buying_asset_code=USD&buying_asset_issuer={}&\
+                     cursor=CURSOR&order=desc&limit=123",
+                    ISSUER
+                )
+                .as_str()
+            )
+        );
+    }
+
+    #[test]
+    fn it_parses_query_params_from_uri() {
+        let uri: Uri = format!(
+            "/offers?seller={0}&selling_asset_type=credit_alphanum4&\
+             selling_asset_code=USD&selling_asset_issuer={0}&order=desc&cursor=CURSOR&limit=123",
+            ISSUER
+        )
+        .parse()
+        .unwrap();
+        let all = All::try_from(&uri).unwrap();
+        assert_eq!(all.seller, Some(ISSUER.into()));
+        assert_eq!(
+            all.selling,
+            Some(AssetIdentifier::alphanum4("USD", ISSUER).unwrap())
+        );
+        assert_eq!(all.buying, None);
+        assert_eq!(all.order, Some(Direction::Desc));
+        assert_eq!(all.cursor, Some("CURSOR".to_string()));
+        assert_eq!(all.limit, Some(123));
+    }
+}
@@ -1,5 +1,5 @@
 //! Contains the endpoint for all operations.
-use super::{Body, Cursor, Direction, IntoRequest, Limit, Order, Records};
+use super::{Body, Cursor, Direction, IntoRequest, Limit, Order, Records, Streamable};
 use error::Result;
 use http::{Request, Uri};
 use resources::{Effect, Operation};
@@ -53,7 +53,7 @@ impl IntoRequest for All {
             uri.push_str("?");
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
             }
 
             if let Some(cursor) = self.cursor {
@@ -71,6 +71,10 @@ impl IntoRequest for All {
     }
 }
 
+impl Streamable for All {
+    type Event = Operation;
+}
+
 impl TryFromUri for All {
     fn try_from_wrap(wrap: &UriWrap) -> ::std::result::Result<Self, uri::Error> {
         let params = wrap.params();
@@ -260,7 +264,7 @@ impl IntoRequest for Effects {
             uri.push_str("?");
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
             }
 
             if let Some(cursor) = self.cursor {
@@ -1,4 +1,5 @@
-use std::{error::Error, fmt, str::FromStr, string::ToString};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{error::Error, fmt, str::FromStr};
 /// Declares that this endpoint has an order field and can have it set.
 ///
 /// ## Example
@@ -36,7 +37,10 @@ macro_rules! impl_order {
     };
 }
 
-/// The order to return results in.
+/// The order to return results in. Displays and serializes as `asc` or
+/// `desc`, the way horizon writes it, and parses from either in any case,
+/// since the links some horizon deployments return are written in upper
+/// case.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Direction {
     /// Order the results ascending
@@ -45,15 +49,34 @@ pub enum Direction {
     Desc,
 }
 
-impl ToString for Direction {
-    fn to_string(&self) -> String {
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            Direction::Asc => "asc".to_string(),
-            Direction::Desc => "desc".to_string(),
+            Direction::Asc => f.write_str("asc"),
+            Direction::Desc => f.write_str("desc"),
         }
     }
 }
 
+impl Serialize for Direction {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        s.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Direction {
+    fn deserialize<D>(d: D) -> Result<Direction, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(d)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
 /// When a bad token or string is provided to parsing into a direction
 /// you get an error.
 #[derive(Debug)]
@@ -84,7 +107,7 @@ impl FromStr for Direction {
     type Err = ParseDirectionError;
 
     fn from_str(s: &str) -> Result<Direction, Self::Err> {
-        match s.to_lowercase().as_ref() {
+        match s.trim().to_lowercase().as_ref() {
             "asc" => Ok(Direction::Asc),
             "desc" => Ok(Direction::Desc),
             _ => Err(ParseDirectionError {
@@ -122,6 +145,41 @@ mod tests {
         assert_eq!("aSc".parse::<Direction>().unwrap(), Direction::Asc);
         assert_eq!("desc".parse::<Direction>().unwrap(), Direction::Desc);
         assert_eq!("DESC".parse::<Direction>().unwrap(), Direction::Desc);
+        assert_eq!(" Desc ".parse::<Direction>().unwrap(), Direction::Desc);
         assert!("no".parse::<Direction>().is_err());
     }
+
+    #[test]
+    fn it_can_be_displayed() {
+        assert_eq!(format!("{}", Direction::Asc), "asc");
+        assert_eq!(format!("order={}", Direction::Desc), "order=desc");
+    }
+
+    #[test]
+    fn it_serializes_the_way_it_displays() {
+        use serde_json;
+
+        assert_eq!(
+            serde_json::to_string(&Direction::Desc).unwrap(),
+            r#""desc""#
+        );
+        let direction: Direction = serde_json::from_str(r#""ASC""#).unwrap();
+        assert_eq!(direction, Direction::Asc);
+        assert!(serde_json::from_str::<Direction>(r#""up""#).is_err());
+    }
+
+    #[test]
+    fn it_round_trips_through_a_next_link_in_any_case() {
+        use endpoint::{transaction, IntoRequest};
+        use uri::TryFromUri;
+
+        let uri = "/transactions?order=DESC&cursor=CURSOR&limit=10"
+            .parse()
+            .unwrap();
+        let all = transaction::All::try_from(&uri).unwrap();
+        assert_eq!(all.order(), Some(Direction::Desc));
+
+        let request = all.into_request("https://horizon.example.com").unwrap();
+        assert!(request.uri().query().unwrap().contains("order=desc"));
+    }
 }
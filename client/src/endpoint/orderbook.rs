@@ -1,5 +1,5 @@
 //! Contains the endpoint for fetching the orderbook for a given asset pair
-use super::{Body, IntoRequest, Limit};
+use super::{Body, IntoRequest, Limit, Streamable};
 use error::Result;
 use http::{Request, Uri};
 use resources::{AssetIdentifier, Orderbook};
@@ -105,6 +105,10 @@ impl IntoRequest for Details {
     }
 }
 
+impl Streamable for Details {
+    type Event = Orderbook;
+}
+
 #[cfg(test)]
 mod details_tests {
     use super::*;
@@ -112,13 +116,17 @@ mod details_tests {
     #[test]
     fn it_can_make_an_account_uri() {
         let xlm = AssetIdentifier::native();
-        let foxcoin = AssetIdentifier::alphanum4("USD", "FantasticMrFox");
+        let foxcoin = AssetIdentifier::alphanum4(
+            "USD",
+            "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG",
+        )
+        .unwrap();
         let details = Details::for_asset_pair(xlm, foxcoin);
         let request = details
             .into_request("https://horizon-testnet.stellar.org")
             .unwrap();
         assert_eq!(request.uri().host().unwrap(), "horizon-testnet.stellar.org");
         assert_eq!(request.uri().path(), "/order_book");
-        assert_eq!(request.uri().query().unwrap(), "selling_asset_type=native&buying_asset_type=credit_alphanum4&buying_asset_code=USD&buying_asset_issuer=FantasticMrFox");
+        assert_eq!(request.uri().query().unwrap(), "selling_asset_type=native&buying_asset_type=credit_alphanum4&buying_asset_code=USD&buying_asset_issuer=GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG");
     }
 }
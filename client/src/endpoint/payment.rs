@@ -1,8 +1,8 @@
 //! Contains the endpoint for all payment operations.
-use super::{Body, Cursor, Direction, IntoRequest, Limit, Order, Records};
+use super::{Body, Cursor, Direction, IntoRequest, Limit, Order, Records, Streamable};
 use error::Result;
 use http::{Request, Uri};
-use resources::{Amount, AssetIdentifier, Operation, PaymentPath};
+use resources::{AccountId, Amount, AssetIdentifier, Operation, PaymentPath};
 use std::str::FromStr;
 use uri::{self, TryFromUri, UriWrap};
 
@@ -53,7 +53,7 @@ impl IntoRequest for All {
             uri.push_str("?");
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
             }
 
             if let Some(cursor) = self.cursor {
@@ -71,6 +71,10 @@ impl IntoRequest for All {
     }
 }
 
+impl Streamable for All {
+    type Event = Operation;
+}
+
 impl TryFromUri for All {
     fn try_from_wrap(wrap: &UriWrap) -> ::std::result::Result<All, uri::Error> {
         let params = wrap.params();
@@ -147,12 +151,12 @@ mod all_payments_tests {
 ///         }
 ///     })
 ///     .take(2)
-///     .collect::<Vec<&str>>();
+///     .collect::<Vec<_>>();
 /// # assert_eq!(account_ids.len(), 2);
 ///
 /// let endpoint = payment::FindPath::new(
-///     &account_ids[0], // source_account
-///     &account_ids[1], // destination_account
+///     account_ids[0].clone(), // source_account
+///     account_ids[1].clone(), // destination_account
 ///     AssetIdentifier::Native,
 ///     Amount::new(1)
 /// );
@@ -164,8 +168,8 @@ mod all_payments_tests {
 /// ```
 #[derive(Debug, Clone)]
 pub struct FindPath {
-    source_account: String,
-    destination_account: String,
+    source_account: AccountId,
+    destination_account: AccountId,
     destination_asset: AssetIdentifier,
     destination_amount: Amount,
 }
@@ -180,25 +184,24 @@ impl FindPath {
     /// use stellar_client::resources::{Amount, AssetIdentifier};
     ///
     /// let paths = payment::FindPath::new(
-    ///     "source_account",
-    ///     "destination_account",
-    ///     AssetIdentifier::new(
-    ///         "credit_alphanum4",
-    ///         Some("code".to_string()),
-    ///         Some("issuer".to_string())
+    ///     "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into(),
+    ///     "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4".into(),
+    ///     AssetIdentifier::alphanum4(
+    ///         "USD",
+    ///         "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4",
     ///     ).unwrap(),
     ///     Amount::new(8675309)
     /// );
     /// ```
     pub fn new(
-        source_account: &str,
-        destination_account: &str,
+        source_account: AccountId,
+        destination_account: AccountId,
         destination_asset: AssetIdentifier,
         destination_amount: Amount,
     ) -> Self {
         Self {
-            source_account: source_account.to_string(),
-            destination_account: destination_account.to_string(),
+            source_account,
+            destination_account,
             destination_asset,
             destination_amount,
         }
@@ -225,7 +228,7 @@ impl IntoRequest for FindPath {
             ));
             uri.push_str(&format!(
                 "&destination_asset_issuer={}",
-                self.destination_asset.issuer().to_string()
+                self.destination_asset.issuer().unwrap()
             ));
         }
 
@@ -251,11 +254,14 @@ impl TryFromUri for FindPath {
 mod find_path_tests {
     use super::*;
 
+    const SOURCE: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+    const DESTINATION: &str = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4";
+
     #[test]
     fn it_can_make_a_paths_uri_for_native_assets() {
         let paths = FindPath::new(
-            "account_a",
-            "account_b",
+            SOURCE.into(),
+            DESTINATION.into(),
             AssetIdentifier::new("native", None, None).unwrap(),
             Amount::new(1000),
         );
@@ -267,8 +273,12 @@ mod find_path_tests {
         assert_eq!(
             request.uri().query(),
             Some(
-                "source_account=account_a&destination_account=account_b&\
-                 destination_amount=0.0001000&destination_asset_type=native"
+                format!(
+                    "source_account={}&destination_account={}&\
+                     destination_amount=0.0001000&destination_asset_type=native",
+                    SOURCE, DESTINATION
+                )
+                .as_str()
             )
         );
     }
@@ -276,13 +286,14 @@ mod find_path_tests {
     #[test]
     fn it_can_make_a_paths_uri_for_non_native_assets() {
         let paths = FindPath::new(
-            "account_a",
-            "account_b",
+            SOURCE.into(),
+            DESTINATION.into(),
             AssetIdentifier::new(
                 "credit_alphanum4",
                 Some("codx".to_string()),
-                Some("me".to_string()),
-            ).unwrap(),
+                Some(DESTINATION.to_string()),
+            )
+            .unwrap(),
             Amount::new(1000),
         );
         let request = paths
@@ -293,9 +304,13 @@ mod find_path_tests {
         assert_eq!(
             request.uri().query(),
             Some(
-                "source_account=account_a&destination_account=account_b&\
-                 destination_amount=0.0001000&destination_asset_type=credit_alphanum4&\
-                 destination_asset_code=codx&destination_asset_issuer=me"
+                format!(
+                    "source_account={}&destination_account={}&\
+                     destination_amount=0.0001000&destination_asset_type=credit_alphanum4&\
+                     destination_asset_code=codx&destination_asset_issuer={}",
+                    SOURCE, DESTINATION, DESTINATION
+                )
+                .as_str()
             )
         );
     }
@@ -0,0 +1,179 @@
+use super::{Body, Capability, Cursor, Direction, IntoRequest, Limit, Order, Records};
+use error::Result;
+use http::Request;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::marker::PhantomData;
+use uri::{self, TryFromUri, UriWrap};
+
+/// A list endpoint whose records are parsed into `T` rather than the
+/// endpoint's own resource.
+///
+/// `T` is usually a cut down version of the resource that passes over the
+/// fields a consumer doesn't need, such as `TransactionHeader` for
+/// transactions, so that they are never kept in memory. The request sent to
+/// horizon doesn't change, and neither do the links used to page through the
+/// results, so a projected endpoint can be handed to `sync::Client::iter`
+/// like any other.
+///
+/// ## Example
+///
+/// ```
+/// use stellar_client::endpoint::{transaction, Limit, Projected};
+/// use stellar_client::resources::TransactionHeader;
+///
+/// let endpoint: Projected<_, TransactionHeader> =
+///     Projected::new(transaction::All::default().with_limit(200));
+/// assert_eq!(endpoint.limit(), Some(200));
+/// ```
+pub struct Projected<E, T> {
+    endpoint: E,
+    record: PhantomData<fn() -> T>,
+}
+
+impl<E, T> Projected<E, T> {
+    /// Parses the records of `endpoint` into `T`.
+    pub fn new(endpoint: E) -> Projected<E, T> {
+        Projected {
+            endpoint,
+            record: PhantomData,
+        }
+    }
+
+    /// The endpoint the request is made to.
+    pub fn endpoint(&self) -> &E {
+        &self.endpoint
+    }
+}
+
+impl<E, T, R> IntoRequest for Projected<E, T>
+where
+    E: IntoRequest<Response = Records<R>>,
+    R: DeserializeOwned,
+    T: DeserializeOwned,
+{
+    type Response = Records<T>;
+
+    fn into_request(self, host: &str) -> Result<Request<Body>> {
+        self.endpoint.into_request(host)
+    }
+
+    fn required_capability(&self) -> Option<Capability> {
+        self.endpoint.required_capability()
+    }
+}
+
+impl<E, T> TryFromUri for Projected<E, T>
+where
+    E: TryFromUri,
+{
+    fn try_from_wrap(wrap: &UriWrap) -> ::std::result::Result<Self, uri::Error> {
+        E::try_from_wrap(wrap).map(Projected::new)
+    }
+}
+
+impl<E, T> Cursor for Projected<E, T>
+where
+    E: Cursor,
+{
+    fn with_cursor(self, cursor: &str) -> Self {
+        Projected::new(self.endpoint.with_cursor(cursor))
+    }
+
+    fn cursor(&self) -> Option<&str> {
+        self.endpoint.cursor()
+    }
+}
+
+impl<E, T> Limit for Projected<E, T>
+where
+    E: Limit,
+{
+    fn with_limit(self, limit: u32) -> Self {
+        Projected::new(self.endpoint.with_limit(limit))
+    }
+
+    fn limit(&self) -> Option<u32> {
+        self.endpoint.limit()
+    }
+}
+
+impl<E, T> Order for Projected<E, T>
+where
+    E: Order,
+{
+    fn with_order(self, order: Direction) -> Self {
+        Projected::new(self.endpoint.with_order(order))
+    }
+
+    fn order(&self) -> Option<Direction> {
+        self.endpoint.order()
+    }
+}
+
+// Derived impls would require `T` to be `Clone` and `Debug` as well, which
+// the records don't have to be.
+impl<E, T> Clone for Projected<E, T>
+where
+    E: Clone,
+{
+    fn clone(&self) -> Self {
+        Projected::new(self.endpoint.clone())
+    }
+}
+
+impl<E, T> fmt::Debug for Projected<E, T>
+where
+    E: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Projected")
+            .field("endpoint", &self.endpoint)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod projected_tests {
+    use super::*;
+    use endpoint::transaction;
+    use http::Uri;
+    use resources::{Memo, TransactionHeader};
+    use sync::Requester;
+    use test::MockClient;
+
+    type Headers = Projected<transaction::All, TransactionHeader>;
+
+    #[test]
+    fn it_makes_the_same_request_as_the_endpoint() {
+        let endpoint: Headers = Projected::new(transaction::All::default())
+            .with_cursor("CURSOR")
+            .with_order(Direction::Desc)
+            .with_limit(5);
+        let req = endpoint.into_request("https://www.google.com").unwrap();
+        assert_eq!(req.uri().path(), "/transactions");
+        assert_eq!(req.uri().query(), Some("cursor=CURSOR&order=desc&limit=5"));
+
+        let uri: Uri = "/transactions?cursor=CURSOR&limit=5".parse().unwrap();
+        let endpoint = Headers::try_from(&uri).unwrap();
+        assert_eq!(endpoint.cursor(), Some("CURSOR"));
+        assert_eq!(endpoint.limit(), Some(5));
+    }
+
+    #[test]
+    fn it_parses_the_records_into_the_projection() {
+        let record = include_str!("../../fixtures/transactions/transaction_memo_text.json");
+        let page = format!(
+            r#"{{ "_links": {{}}, "_embedded": {{ "records": [{}] }} }}"#,
+            record
+        );
+        let client = MockClient::new().with_json("/transactions", &page);
+        let endpoint: Headers = Projected::new(transaction::All::default());
+        let records = client.request(endpoint).unwrap();
+        assert_eq!(records.records().len(), 1);
+        assert_eq!(
+            records.records()[0].memo(),
+            &Memo::Text("hello".to_string())
+        );
+    }
+}
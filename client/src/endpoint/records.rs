@@ -1,6 +1,11 @@
+use error::Result;
 use http;
+use resources::PagingToken;
 use serde::de::{Deserialize, DeserializeOwned, Deserializer};
+use serde::{Serialize, Serializer};
+use serde_json;
 use std;
+use std::marker::PhantomData;
 
 /// A struct that represents a set of records returned from the horizon api.
 ///
@@ -13,6 +18,7 @@ where
     T: DeserializeOwned,
 {
     records: Vec<T>,
+    self_link: Option<http::Uri>,
     next: Option<http::Uri>,
     prev: Option<http::Uri>,
 }
@@ -21,11 +27,86 @@ impl<T> Records<T>
 where
     T: DeserializeOwned,
 {
+    /// Creates a page of records without any links, such as for a test
+    /// server to serialize in horizon's format.
+    ///
+    /// ```
+    /// extern crate serde_json;
+    /// extern crate stellar_client;
+    ///
+    /// use stellar_client::endpoint::Records;
+    /// use stellar_client::resources::Health;
+    ///
+    /// # fn main() {
+    /// let health: Health = serde_json::from_str(
+    ///     r#"{ "database_connected": true, "core_up": true, "core_synced": true }"#,
+    /// ).unwrap();
+    /// let page = Records::new(vec![health])
+    ///     .with_next("/health?cursor=1".parse().unwrap());
+    /// let json = serde_json::to_value(&page).unwrap();
+    /// assert_eq!(json["_links"]["next"]["href"], "/health?cursor=1");
+    /// assert_eq!(json["_embedded"]["records"][0]["core_up"], true);
+    /// # }
+    /// ```
+    pub fn new(records: Vec<T>) -> Records<T> {
+        Records {
+            records,
+            self_link: None,
+            next: None,
+            prev: None,
+        }
+    }
+
+    /// Sets the uri the page was fetched from.
+    pub fn with_self_link(mut self, uri: http::Uri) -> Records<T> {
+        self.self_link = Some(uri);
+        self
+    }
+
+    /// Sets the uri to the next page.
+    pub fn with_next(mut self, uri: http::Uri) -> Records<T> {
+        self.next = Some(uri);
+        self
+    }
+
+    /// Sets the uri to the previous page.
+    pub fn with_prev(mut self, uri: http::Uri) -> Records<T> {
+        self.prev = Some(uri);
+        self
+    }
+
     /// Returns a slice of the embedded records.
     pub fn records(&self) -> &Vec<T> {
         &self.records
     }
 
+    /// Returns an iterator over the embedded records.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.records.iter()
+    }
+
+    /// Returns the uri that this page was fetched from.
+    pub fn self_link(&self) -> Option<&http::Uri> {
+        self.self_link.as_ref()
+    }
+
+    /// Returns the page size that was asked for, as horizon reports it in
+    /// the page's own link.
+    pub fn limit(&self) -> Option<u32> {
+        self.self_link.as_ref().and_then(limit)
+    }
+
+    /// Returns true when this looks like the last page of the results,
+    /// meaning there were fewer records than the limit asked for. Horizon
+    /// always links to a next page, so this saves a request for an empty one.
+    /// Without a known limit, only an empty page counts as the last.
+    pub fn is_last_page(&self) -> bool {
+        match self.limit() {
+            Some(limit) => (self.records.len() as u64) < u64::from(limit),
+            None => self.records.is_empty(),
+        }
+    }
+
     /// Returns the uri to the next page.
     pub fn next(&self) -> Option<&http::Uri> {
         self.next.as_ref()
@@ -37,6 +118,47 @@ where
     }
 }
 
+impl<T> Records<T>
+where
+    T: DeserializeOwned + PagingToken,
+{
+    /// Merges pages that may overlap, such as the pages of a backfill and
+    /// those fetched after resuming a stream, into one page of distinct
+    /// records in ledger order.
+    ///
+    /// Records with the same paging token are the same record, and only the
+    /// first of them is kept. The pages can come from requests in either
+    /// order. No single request returns the merged page, so it has no links.
+    ///
+    /// ```
+    /// use stellar_client::endpoint::Records;
+    /// use stellar_client::resources::{Operation, PagingToken, Resource};
+    ///
+    /// let payment = include_str!("../../fixtures/operations/payment.json");
+    /// let operation = |token: &str| {
+    ///     let json = payment.replace("58402965295104", token);
+    ///     Operation::from_json_str(&json).unwrap()
+    /// };
+    /// let backfill = Records::new(vec![operation("9"), operation("10")]);
+    /// let resumed = Records::new(vec![operation("11"), operation("10")]);
+    ///
+    /// let merged = Records::merge(vec![resumed, backfill]);
+    /// let tokens: Vec<&str> = merged.iter().map(PagingToken::paging_token).collect();
+    /// assert_eq!(tokens, vec!["9", "10", "11"]);
+    /// ```
+    pub fn merge<I>(pages: I) -> Records<T>
+    where
+        I: IntoIterator<Item = Records<T>>,
+    {
+        let mut records: Vec<T> = pages.into_iter().flat_map(Records::into_iter).collect();
+        // The sort is stable, so the first of each set of duplicates stays
+        // in front of the others.
+        records.sort_by(PagingToken::cmp_paging_token);
+        records.dedup_by(|a, b| a.paging_token() == b.paging_token());
+        Records::new(records)
+    }
+}
+
 impl<'de, T> Deserialize<'de> for Records<T>
 where
     T: DeserializeOwned,
@@ -49,12 +171,14 @@ where
         if let Some(links) = embedded.links {
             Ok(Records {
                 records: embedded.embedded.records,
+                self_link: links.self_link.and_then(|v| v.uri()),
                 next: links.next.and_then(|v| v.uri()),
                 prev: links.prev.and_then(|v| v.uri()),
             })
         } else {
             Ok(Records {
                 records: embedded.embedded.records,
+                self_link: None,
                 next: None,
                 prev: None,
             })
@@ -62,36 +186,198 @@ where
     }
 }
 
+/// Writes the page the way horizon does, with the records under
+/// `_embedded.records` and the links to this and the neighbouring pages
+/// under `_links`.
+impl<T> Serialize for Records<T>
+where
+    T: DeserializeOwned + Serialize,
+{
+    fn serialize<S>(&self, s: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let page = Embedded {
+            links: Some(Links {
+                self_link: self.self_link.as_ref().map(Href::from),
+                next: self.next.as_ref().map(Href::from),
+                prev: self.prev.as_ref().map(Href::from),
+            }),
+            embedded: RecordsIntermediate {
+                records: self.records.iter().collect::<Vec<&T>>(),
+            },
+        };
+        page.serialize(s)
+    }
+}
+
+impl<T> IntoIterator for Records<T>
+where
+    T: DeserializeOwned,
+{
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.records.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Records<T>
+where
+    T: DeserializeOwned,
+{
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.records.iter()
+    }
+}
+
+/// Reads the `limit` query param out of a page link.
+fn limit(uri: &http::Uri) -> Option<u32> {
+    uri.query()?
+        .split('&')
+        .filter_map(|param| {
+            let mut pair = param.splitn(2, '=');
+            match (pair.next(), pair.next()) {
+                (Some("limit"), Some(value)) => value.parse().ok(),
+                _ => None,
+            }
+        })
+        .next()
+}
+
+/// A set of records that may borrow from the buffer holding the response.
+///
+/// The resources in this crate own their data, so parsing a page of them
+/// allocates a string for every field of every record. Ingestion pipelines
+/// that only need a few fields can instead define a record type that borrows
+/// those fields and parse the raw body from `sync::Client::request_body`
+/// into this struct, which avoids the allocations.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate stellar_client;
+///
+/// use stellar_client::endpoint::RecordsRef;
+///
+/// #[derive(Deserialize)]
+/// struct Hash<'a> {
+///     hash: &'a str,
+/// }
+///
+/// # fn main() {
+/// let body = r#"{ "_embedded": { "records": [{ "hash": "abc", "ledger": 1 }] } }"#;
+/// let page: RecordsRef<Hash> = RecordsRef::from_json_str(body).unwrap();
+/// assert_eq!(page.records()[0].hash, "abc");
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RecordsRef<'a, T>
+where
+    T: Deserialize<'a>,
+{
+    records: Vec<T>,
+    next: Option<http::Uri>,
+    prev: Option<http::Uri>,
+    buffer: PhantomData<&'a str>,
+}
+
+impl<'a, T> RecordsRef<'a, T>
+where
+    T: Deserialize<'a>,
+{
+    /// Parses a page of records that borrow from `json`.
+    pub fn from_json_str(json: &'a str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Returns a slice of the embedded records.
+    pub fn records(&self) -> &Vec<T> {
+        &self.records
+    }
+
+    /// Returns the uri to the next page.
+    pub fn next(&self) -> Option<&http::Uri> {
+        self.next.as_ref()
+    }
+
+    /// Returns the uri to the previous page.
+    pub fn prev(&self) -> Option<&http::Uri> {
+        self.prev.as_ref()
+    }
+}
+
+impl<'a, T> Deserialize<'a> for RecordsRef<'a, T>
+where
+    T: Deserialize<'a>,
+{
+    fn deserialize<D>(d: D) -> std::result::Result<RecordsRef<'a, T>, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        let embedded: Embedded<RecordsIntermediate<T>> = Embedded::deserialize(d)?;
+        let (next, prev) = match embedded.links {
+            Some(links) => (
+                links.next.and_then(|v| v.uri()),
+                links.prev.and_then(|v| v.uri()),
+            ),
+            None => (None, None),
+        };
+        Ok(RecordsRef {
+            records: embedded.embedded.records,
+            next,
+            prev,
+            buffer: PhantomData,
+        })
+    }
+}
+
 /// The HAL response format will embed resources within it. When it does
 /// this provides a wrapper to the `_embedded` key.
 ///
 /// https://www.stellar.org/developers/horizon/reference/responses.html
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 struct Embedded<T> {
-    #[serde(rename = "_embedded")]
-    embedded: T,
     #[serde(rename = "_links")]
     links: Option<Links>,
+    #[serde(rename = "_embedded")]
+    embedded: T,
 }
 
 /// If the embedded resource is a set of records, this can provide that data back in
 /// a generic way.
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 struct RecordsIntermediate<T> {
     records: Vec<T>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 struct Links {
+    #[serde(rename = "self")]
+    self_link: Option<Href>,
     next: Option<Href>,
     prev: Option<Href>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 struct Href {
     href: String,
 }
 
+impl<'a> From<&'a http::Uri> for Href {
+    fn from(uri: &'a http::Uri) -> Href {
+        Href {
+            href: uri.to_string(),
+        }
+    }
+}
+
 impl Href {
     fn uri(&self) -> Option<http::Uri> {
         self.href.parse().ok()
@@ -103,11 +389,28 @@ mod records_test {
     use super::*;
     use serde_json;
 
-    #[derive(Deserialize)]
+    #[derive(Deserialize, Serialize)]
     struct Foo {
         foo: String,
     }
 
+    impl PagingToken for Foo {
+        fn paging_token(&self) -> &str {
+            &self.foo
+        }
+    }
+
+    fn page(tokens: &[&str]) -> Records<Foo> {
+        Records::new(
+            tokens
+                .iter()
+                .map(|token| Foo {
+                    foo: token.to_string(),
+                })
+                .collect(),
+        )
+    }
+
     #[test]
     fn it_parses_out_a_embedded_records_string() {
         let json = r#"
@@ -136,6 +439,59 @@ mod records_test {
         assert_eq!(records.records().first().unwrap().foo, "bar");
         assert_eq!(records.next(), Some(&next));
         assert_eq!(records.prev(), Some(&prev));
+        assert_eq!(records.self_link(), None);
+        assert_eq!(records.limit(), None);
+        assert!(!records.is_last_page());
+    }
+
+    #[test]
+    fn it_reads_the_limit_from_the_self_link() {
+        let page = |count: usize| {
+            let records = vec![r#"{ "foo": "bar" }"#; count].join(",");
+            let json = format!(
+                r#"{{
+                    "_links": {{
+                        "self": {{ "href": "/assets?cursor=&limit=2&order=asc" }}
+                    }},
+                    "_embedded": {{ "records": [{}] }}
+                }}"#,
+                records
+            );
+            serde_json::from_str::<Records<Foo>>(&json).unwrap()
+        };
+
+        let full = page(2);
+        assert_eq!(
+            full.self_link().map(|uri| uri.to_string()),
+            Some("/assets?cursor=&limit=2&order=asc".to_string())
+        );
+        assert_eq!(full.limit(), Some(2));
+        assert!(!full.is_last_page());
+        assert!(page(1).is_last_page());
+        assert!(page(0).is_last_page());
+    }
+
+    #[test]
+    fn it_iterates_over_the_records() {
+        let json = r#"
+        {
+            "_embedded": {
+                "records": [
+                    { "foo": "bar" },
+                    { "foo": "baz" }
+                ]
+            }
+        }"#;
+        let records: Records<Foo> = serde_json::from_str(&json).unwrap();
+        let borrowed: Vec<&str> = records.iter().map(|foo| &*foo.foo).collect();
+        assert_eq!(borrowed, vec!["bar", "baz"]);
+        assert_eq!((&records).into_iter().count(), 2);
+
+        let mut owned = Vec::new();
+        for foo in records {
+            owned.push(foo.foo);
+        }
+        assert_eq!(owned, vec!["bar", "baz"]);
     }
 
     #[test]
@@ -177,4 +533,85 @@ mod records_test {
         assert_eq!(records.next(), None);
         assert_eq!(records.prev(), None);
     }
+
+    #[test]
+    fn it_writes_records_in_horizons_format() {
+        let next: http::Uri = "/assets?order=asc&limit=10&cursor=NEXT_CURSOR"
+            .parse()
+            .unwrap();
+        let records = Records::new(vec![Foo {
+            foo: "bar".to_string(),
+        }]).with_next(next.clone());
+        let json = serde_json::to_value(&records).unwrap();
+        let expected: serde_json::Value = serde_json::from_str(
+            r#"{
+                "_links": {
+                    "self": null,
+                    "next": { "href": "/assets?order=asc&limit=10&cursor=NEXT_CURSOR" },
+                    "prev": null
+                },
+                "_embedded": {
+                    "records": [{ "foo": "bar" }]
+                }
+            }"#,
+        ).unwrap();
+        assert_eq!(json, expected);
+
+        let parsed: Records<Foo> = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.records()[0].foo, "bar");
+        assert_eq!(parsed.next(), Some(&next));
+        assert_eq!(parsed.self_link(), None);
+    }
+
+    #[derive(Deserialize)]
+    struct FooRef<'a> {
+        foo: &'a str,
+    }
+
+    #[test]
+    fn it_parses_records_that_borrow_from_the_body() {
+        let json = r#"
+        {
+            "_links": {
+                "next": {
+                    "href": "/assets?order=asc&limit=10&cursor=NEXT_CURSOR"
+                }
+            },
+            "_embedded": {
+                "records": [
+                    { "foo": "bar" },
+                    { "foo": "baz" }
+                ]
+            }
+        }"#;
+        let records: RecordsRef<FooRef> = RecordsRef::from_json_str(json).unwrap();
+        assert_eq!(records.records().len(), 2);
+        assert_eq!(records.records()[1].foo, "baz");
+        assert_eq!(
+            records.next().map(|uri| uri.to_string()),
+            Some("/assets?order=asc&limit=10&cursor=NEXT_CURSOR".to_string())
+        );
+        assert_eq!(records.prev(), None);
+    }
+
+    #[test]
+    fn it_merges_overlapping_pages_in_ledger_order() {
+        let merged = Records::merge(vec![
+            page(&["12884905985-2", "12884905985-1", "12884905984"]),
+            page(&["12884905985-1", "12884905985-10"]),
+            page(&[]),
+        ]);
+        let tokens: Vec<&str> = merged.iter().map(|foo| foo.foo.as_str()).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                "12884905984",
+                "12884905985-1",
+                "12884905985-2",
+                "12884905985-10",
+            ]
+        );
+        assert!(Records::merge(vec![page(&[])]).records().is_empty());
+        assert_eq!(Records::merge(vec![page(&["GA"])]).next(), None);
+    }
 }
@@ -0,0 +1,49 @@
+//! Contains the endpoint for the root of a horizon server.
+use super::{Body, IntoRequest};
+use error::Result;
+use http::{Request, Uri};
+use resources::Root;
+use std::str::FromStr;
+
+/// Represents the root of a horizon server, which reports its versions and
+/// the latest ledgers known to horizon and to stellar core.
+///
+/// <https://developers.stellar.org/api/introduction/response-format/>
+///
+/// ## Example
+/// ```no_run
+/// use stellar_client::sync::Client;
+/// use stellar_client::endpoint::root;
+///
+/// let client      = Client::horizon_test().unwrap();
+/// let endpoint    = root::Details;
+/// let root        = client.request(endpoint).unwrap();
+/// #
+/// # assert!(root.core_latest_ledger() > 0);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Details;
+
+impl IntoRequest for Details {
+    type Response = Root;
+
+    fn into_request(self, host: &str) -> Result<Request<Body>> {
+        let uri = Uri::from_str(&format!("{}/", host))?;
+        let request = Request::get(uri).body(Body::None)?;
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod root_details_tests {
+    use super::*;
+
+    #[test]
+    fn it_can_make_a_root_uri() {
+        let request = Details
+            .into_request("https://horizon-testnet.stellar.org")
+            .unwrap();
+        assert_eq!(request.uri().host().unwrap(), "horizon-testnet.stellar.org");
+        assert_eq!(request.uri().path(), "/");
+    }
+}
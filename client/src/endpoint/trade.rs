@@ -1,5 +1,5 @@
 //! Contains the endpoint for all trades.
-use super::{Body, Cursor, Direction, IntoRequest, Limit, Order, Records};
+use super::{Body, Cursor, Direction, IntoRequest, Limit, Order, Records, Streamable};
 use error::Result;
 use http::{Request, Uri};
 use resources::{AssetIdentifier, Trade, TradeAggregation};
@@ -25,8 +25,8 @@ impl AssetPair {
             param.push_str(&format!(
                 "base_asset_type={}&base_asset_code={}&base_asset_issuer={}&",
                 self.base.asset_type(),
-                self.base.code(),
-                self.base.issuer(),
+                self.base.code().unwrap(),
+                self.base.issuer().unwrap(),
             ));
         }
 
@@ -36,8 +36,8 @@ impl AssetPair {
             param.push_str(&format!(
                 "counter_asset_type={}&counter_asset_code={}&counter_asset_issuer={}",
                 self.counter.asset_type(),
-                self.counter.code(),
-                self.counter.issuer()
+                self.counter.code().unwrap(),
+                self.counter.issuer().unwrap()
             ));
         }
         param
@@ -92,14 +92,22 @@ mod asset_pair_tests {
     fn it_can_parse_a_query_string_for_other_assets() {
         let uri: Uri = "/path?base_asset_type=credit_alphanum4&\
                         base_asset_code=BASE&\
-                        base_asset_issuer=BASE_ISSUER&\
+                        base_asset_issuer=GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG&\
                         counter_asset_type=credit_alphanum12&\
                         counter_asset_code=COUNTERASSET&\
-                        counter_asset_issuer=COUNTER_ISSUER"
+                        counter_asset_issuer=GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4"
             .parse()
             .unwrap();
-        let base = AssetIdentifier::alphanum4("BASE", "BASE_ISSUER");
-        let counter = AssetIdentifier::alphanum12("COUNTERASSET", "COUNTER_ISSUER");
+        let base = AssetIdentifier::alphanum4(
+            "BASE",
+            "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG",
+        )
+        .unwrap();
+        let counter = AssetIdentifier::alphanum12(
+            "COUNTERASSET",
+            "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4",
+        )
+        .unwrap();
         let pair = AssetPair { base, counter };
         let parsed_pair = AssetPair::try_from(&uri).unwrap();
         assert_eq!(pair, parsed_pair);
@@ -107,17 +115,25 @@ mod asset_pair_tests {
 
     #[test]
     fn it_can_make_a_query_string_for_other_assets() {
-        let base = AssetIdentifier::alphanum4("BASE", "BASE_ISSUER");
-        let counter = AssetIdentifier::alphanum12("COUNTERASSET", "COUNTER_ISSUER");
+        let base = AssetIdentifier::alphanum4(
+            "BASE",
+            "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG",
+        )
+        .unwrap();
+        let counter = AssetIdentifier::alphanum12(
+            "COUNTERASSET",
+            "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4",
+        )
+        .unwrap();
         let pair = AssetPair { base, counter };
         assert_eq!(
             pair.to_param(),
             "base_asset_type=credit_alphanum4&\
              base_asset_code=BASE&\
-             base_asset_issuer=BASE_ISSUER&\
+             base_asset_issuer=GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG&\
              counter_asset_type=credit_alphanum12&\
              counter_asset_code=COUNTERASSET&\
-             counter_asset_issuer=COUNTER_ISSUER"
+             counter_asset_issuer=GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4"
         )
     }
 }
@@ -228,7 +244,7 @@ impl IntoRequest for All {
             }
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("&order={}", order.to_string()));
+                uri.push_str(&format!("&order={}", order));
             }
 
             if let Some(cursor) = self.cursor {
@@ -246,6 +262,10 @@ impl IntoRequest for All {
     }
 }
 
+impl Streamable for All {
+    type Event = Trade;
+}
+
 impl TryFromUri for All {
     fn try_from_wrap(wrap: &UriWrap) -> ::std::result::Result<All, uri::Error> {
         let params = wrap.params();
@@ -294,12 +314,15 @@ mod all_trades_tests {
                 AssetIdentifier::alphanum4(
                     "MOBI",
                     "GA6HCMBLTZS5VYYBCATRBRZ3BZJMAFUDKYYF6AH6MVCMGWMRDNSWJPIH",
-                ),
+                )
+                .unwrap(),
                 AssetIdentifier::alphanum4(
                     "MOBI",
                     "GA6HCMBLTZS5VYYBCATRBRZ3BZJMAFUDKYYF6AH6MVCMGWMRDNSWJPIH",
-                ),
-            ).with_offer_id(123)
+                )
+                .unwrap(),
+            )
+            .with_offer_id(123)
             .with_cursor("CURSOR")
             .with_limit(123)
             .with_order(Direction::Desc);
@@ -505,7 +528,7 @@ impl IntoRequest for Aggregations {
         uri.push_str(&format!("end_time={}", self.end_time));
 
         if let Some(order) = self.order {
-            uri.push_str(&format!("&order={}", order.to_string()));
+            uri.push_str(&format!("&order={}", order));
         }
 
         if let Some(limit) = self.limit {
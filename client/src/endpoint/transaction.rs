@@ -1,8 +1,8 @@
 //! Contains endpoints for transactions and related information.
-use super::{Body, Cursor, Direction, IntoRequest, Limit, Order, Records};
+use super::{Body, Cursor, Direction, IntoRequest, Limit, Order, Projected, Records, Streamable};
 use error::Result;
 use http::{Request, Uri};
-use resources::{Effect, Operation, Transaction};
+use resources::{Effect, Operation, SubmittedTransaction, Transaction, TransactionHeader};
 use std::str::FromStr;
 use uri::{self, TryFromUri, UriWrap};
 
@@ -31,6 +31,7 @@ pub struct All {
     cursor: Option<String>,
     order: Option<Direction>,
     limit: Option<u32>,
+    include_failed: bool,
 }
 
 impl_cursor!(All);
@@ -39,7 +40,21 @@ impl_order!(All);
 
 impl All {
     fn has_query(&self) -> bool {
-        self.order.is_some() || self.cursor.is_some() || self.limit.is_some()
+        self.order.is_some() || self.cursor.is_some() || self.limit.is_some() || self.include_failed
+    }
+
+    /// Also lists transactions that failed, which horizon leaves out by
+    /// default. Their `failure_reasons` say why they failed.
+    pub fn with_include_failed(mut self, include_failed: bool) -> Self {
+        self.include_failed = include_failed;
+        self
+    }
+
+    /// Parses the transactions into `TransactionHeader`s, which leave out
+    /// the xdr, for consumers that only need to know who sent what and
+    /// when.
+    pub fn without_xdr(self) -> Projected<Self, TransactionHeader> {
+        Projected::new(self)
     }
 }
 
@@ -56,7 +71,11 @@ impl IntoRequest for All {
             }
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
+            }
+
+            if self.include_failed {
+                uri.push_str("include_failed=true&");
             }
 
             if let Some(limit) = self.limit {
@@ -70,6 +89,10 @@ impl IntoRequest for All {
     }
 }
 
+impl Streamable for All {
+    type Event = Transaction;
+}
+
 impl TryFromUri for All {
     fn try_from_wrap(wrap: &UriWrap) -> ::std::result::Result<All, uri::Error> {
         let params = wrap.params();
@@ -77,6 +100,7 @@ impl TryFromUri for All {
             cursor: params.get_parse("cursor").ok(),
             order: params.get_parse("order").ok(),
             limit: params.get_parse("limit").ok(),
+            include_failed: params.get("include_failed") == Some("true"),
         })
     }
 }
@@ -107,6 +131,24 @@ mod all_transactions_test {
         );
     }
 
+    #[test]
+    fn it_can_include_failed_transactions() {
+        let ep = All::default().with_include_failed(true).with_limit(5);
+        let req = ep.into_request("https://www.google.com").unwrap();
+        assert_eq!(req.uri().query(), Some("include_failed=true&limit=5"));
+
+        let uri: Uri = "/transactions?include_failed=true&limit=5".parse().unwrap();
+        assert!(All::try_from(&uri).unwrap().include_failed);
+    }
+
+    #[test]
+    fn it_can_leave_out_the_xdr() {
+        let ep = All::default().with_include_failed(true).without_xdr();
+        assert!(ep.endpoint().include_failed);
+        let req = ep.into_request("https://www.google.com").unwrap();
+        assert_eq!(req.uri().query(), Some("include_failed=true&"));
+    }
+
     #[test]
     fn it_parses_query_params_from_uri() {
         let uri: Uri = "/transactions?order=desc&cursor=CURSOR&limit=123"
@@ -179,6 +221,73 @@ mod transaction_details_tests {
     }
 }
 
+/// Represents submitting a signed transaction to the stellar horizon server.
+/// Horizon hands the transaction to stellar core and answers once it is in a
+/// ledger, or with a timeout error if it isn't in time.
+///
+/// Prefer `Client::submit_transaction`, which looks the transaction up by its
+/// hash when the submission times out rather than leaving it unknown whether
+/// it made it in.
+///
+/// <https://developers.stellar.org/api/resources/transactions/post/>
+#[derive(Debug, Clone)]
+pub struct Submit {
+    envelope_xdr: String,
+}
+
+impl Submit {
+    /// Returns a new endpoint that submits the base64 encoded transaction
+    /// envelope.
+    pub fn new(envelope_xdr: &str) -> Self {
+        Self {
+            envelope_xdr: envelope_xdr.to_string(),
+        }
+    }
+
+    /// The base64 encoded transaction envelope that will be submitted.
+    pub fn envelope_xdr(&self) -> &str {
+        &self.envelope_xdr
+    }
+}
+
+impl IntoRequest for Submit {
+    type Response = SubmittedTransaction;
+
+    fn into_request(self, host: &str) -> Result<Request<Body>> {
+        let uri = Uri::from_str(&format!("{}/transactions", host))?;
+        let form = format!("tx={}", form_encode(&self.envelope_xdr));
+        let request = Request::post(uri).body(Body::Form(form))?;
+        Ok(request)
+    }
+}
+
+// Base64 only has three characters that have to be escaped in a form.
+fn form_encode(base64: &str) -> String {
+    base64
+        .replace('+', "%2B")
+        .replace('/', "%2F")
+        .replace('=', "%3D")
+}
+
+#[cfg(test)]
+mod transaction_submit_tests {
+    use super::*;
+
+    #[test]
+    fn it_posts_the_envelope_as_a_form() {
+        let submit = Submit::new("AAAA+b/c==");
+        let request = submit
+            .into_request("https://horizon-testnet.stellar.org")
+            .unwrap();
+        assert_eq!(request.method(), &::http::Method::POST);
+        assert_eq!(request.uri().path(), "/transactions");
+        match *request.body() {
+            Body::Form(ref form) => assert_eq!(form, "tx=AAAA%2Bb%2Fc%3D%3D"),
+            ref other => panic!("expected a form, got {:?}", other),
+        }
+    }
+}
+
 /// ```
 /// use stellar_client::sync::Client;
 /// use stellar_client::endpoint::{transaction, effect, Limit};
@@ -239,7 +348,7 @@ impl IntoRequest for Effects {
             }
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
             }
 
             if let Some(limit) = self.limit {
@@ -378,7 +487,7 @@ impl IntoRequest for Payments {
             }
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
             }
 
             if let Some(limit) = self.limit {
@@ -513,7 +622,7 @@ impl IntoRequest for Operations {
             }
 
             if let Some(order) = self.order {
-                uri.push_str(&format!("order={}&", order.to_string()));
+                uri.push_str(&format!("order={}&", order));
             }
 
             if let Some(limit) = self.limit {
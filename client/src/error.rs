@@ -1,15 +1,28 @@
 //! Error and result module
 use super::StellarError;
+use endpoint::Capability;
 use http;
 use hyper;
 use hyper::error::UriError;
+#[cfg(feature = "keys")]
+use keys::KeyError;
 use reqwest;
+use resources::{Amount, HorizonVersion, ParseResourceError};
 use serde_json;
+#[cfg(feature = "soroban")]
+use soroban::{RpcError, Violation};
 use std::error::Error as StdError;
 use std::fmt;
+use std::io;
+use std::time::Duration;
 use uri;
+use xdr::XdrError;
 
 /// A set of errors for use in the client
+///
+/// The errors a request to horizon can fail with carry the `X-Request-Id`
+/// the sync client sent it with, which horizon logs alongside its own record
+/// of the request. `Error::request_id` returns it whichever the error is.
 #[derive(Debug)]
 pub enum Error {
     /// An invalid uri was used to construct the client.
@@ -18,22 +31,101 @@ pub enum Error {
     BadSSL,
     /// Placeholder for errors that come back from the client.
     BadResponse(StellarError),
-    /// Server error detected
-    ServerError,
+    /// Server error detected. The inner value is the id of the request,
+    /// when the error came from one.
+    ServerError(Option<String>),
+    /// Horizon responded with a 503 because it is temporarily unavailable.
+    /// The first value is how long it asked the client to wait before trying
+    /// again, when the response said, and the second the id of the request.
+    ServiceUnavailable(Option<Duration>, Option<String>),
     /// The response was from the http library and resulted in an error.
     /// this type does not map down well and currently is just wrapped
     /// generically. See the inner description for details.
     ///
     /// <https://github.com/hyperium/http/issues/188>
     Http(http::Error),
-    /// An error occurred while parsing the json
+    /// An error occurred while parsing the json. The second value is the id
+    /// of the request, when the json came from one.
     ///
     /// <https://docs.serde.rs/serde_json/error/struct.Error.html>
-    JsonParseError(serde_json::error::Error),
-    /// Catch-all for reqwest error handling
-    Reqwest(reqwest::Error),
+    JsonParseError(serde_json::error::Error, Option<String>),
+    /// A response could not be parsed into the expected resource. The inner
+    /// error knows which field was at fault.
+    ParseResource(ParseResourceError),
+    /// Catch-all for reqwest error handling, such as a request that failed to
+    /// connect or timed out. The second value is the id of the request.
+    Reqwest(reqwest::Error, Option<String>),
     /// Errors that occur when converting from uri into something else.
     TryFromUri(uri::Error),
+    /// An error occurred while reading or writing a file or response body.
+    /// The second value is the id of the request, when it was a response
+    /// body.
+    Io(io::Error, Option<String>),
+    /// A transaction did not show up in horizon before the deadline passed.
+    /// The inner string is the hash of the transaction. It may still make it
+    /// into a later ledger.
+    TransactionTimeout(String),
+    /// A response body was larger than the client allows. The first value is
+    /// the limit in bytes, and the second the id of the request.
+    BodyTooLarge(u64, Option<String>),
+    /// Reading and parsing a response took longer than the client allows.
+    /// The first value is the budget that was exceeded, and the second the
+    /// id of the request.
+    ParseTimeout(Duration, Option<String>),
+    /// The horizon server is too old to have a capability that the request
+    /// relies on. The version is the one the server reported.
+    ServerTooOld(Capability, HorizonVersion),
+    /// The client doesn't know which network its horizon server is on, which
+    /// it needs to work out the hash of a transaction. It can be set with
+    /// `ClientBuilder::with_network`.
+    UnknownNetwork,
+    /// The ledger kept advancing while a snapshot was taken across several
+    /// requests. The inner value is the number of attempts made.
+    LedgerAdvanced(u32),
+    /// Horizon found no path to make a path payment through.
+    NoPath,
+    /// A path payment failed because the prices along its path moved past
+    /// what its slippage bound allowed, such as with `op_over_source_max`.
+    /// The inner value is the most it was allowed to send. Quoting it again
+    /// may find a path that works.
+    SlippageExceeded(Amount),
+    /// A codec could not decode a memo or data value. The inner string says
+    /// why.
+    Codec(String),
+    /// A requester made by `Deadline::bind` was asked to start a request
+    /// after its deadline had passed.
+    DeadlineExceeded,
+    /// A client could not be configured. The inner string says which
+    /// setting was at fault.
+    Config(String),
+    /// A transaction could not be built because its source account's
+    /// sequence number, the inner value, has no number after it.
+    SequenceExhausted(u64),
+    /// XDR, such as a transaction envelope, a history archive file or a
+    /// value returned by the soroban rpc server, could not be decoded or
+    /// encoded.
+    Xdr(XdrError),
+    /// A history archive does not have the file. The inner string is its
+    /// path in the archive.
+    #[cfg(feature = "history")]
+    NotInArchive(String),
+    /// A history archive file could not be decompressed.
+    #[cfg(feature = "history")]
+    Decompress(io::Error),
+    /// The soroban rpc server answered a call with an error.
+    #[cfg(feature = "soroban")]
+    Rpc(RpcError),
+    /// Simulating a soroban transaction failed. The inner string is the
+    /// reason the rpc server gave.
+    #[cfg(feature = "soroban")]
+    Simulation(String),
+    /// A soroban transaction could not be built because it breaks a rule
+    /// the network applies.
+    #[cfg(feature = "soroban")]
+    InvalidTransaction(Violation),
+    /// A key could not be loaded, stored or used to sign.
+    #[cfg(feature = "keys")]
+    Key(KeyError),
     #[doc(hidden)]
     __Nonexhaustive,
 }
@@ -41,17 +133,106 @@ pub enum Error {
 /// A result including client specific errors.
 pub type Result<T> = ::std::result::Result<T, Error>;
 
+impl Error {
+    /// Records the id of the request that failed on the errors a request
+    /// can fail with.
+    pub(crate) fn with_request_id(self, request_id: &str) -> Self {
+        let id = Some(request_id.to_string());
+        match self {
+            Error::BadResponse(inner) => Error::BadResponse(inner.with_request_id(request_id)),
+            Error::ParseResource(inner) => Error::ParseResource(inner.with_request_id(request_id)),
+            Error::ServerError(_) => Error::ServerError(id),
+            Error::ServiceUnavailable(wait, _) => Error::ServiceUnavailable(wait, id),
+            Error::JsonParseError(inner, _) => Error::JsonParseError(inner, id),
+            Error::Reqwest(inner, _) => Error::Reqwest(inner, id),
+            Error::Io(inner, _) => Error::Io(inner, id),
+            Error::BodyTooLarge(max, _) => Error::BodyTooLarge(max, id),
+            Error::ParseTimeout(budget, _) => Error::ParseTimeout(budget, id),
+            other => other,
+        }
+    }
+
+    /// The `X-Request-Id` the failed request was sent with, when the error
+    /// came from a request to horizon.
+    pub fn request_id(&self) -> Option<&str> {
+        match *self {
+            Error::BadResponse(ref inner) => inner.request_id(),
+            Error::ParseResource(ref inner) => inner.request_id(),
+            Error::ServerError(ref id)
+            | Error::ServiceUnavailable(_, ref id)
+            | Error::JsonParseError(_, ref id)
+            | Error::Reqwest(_, ref id)
+            | Error::Io(_, ref id)
+            | Error::BodyTooLarge(_, ref id)
+            | Error::ParseTimeout(_, ref id) => id.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Records how long horizon asked the client to wait before trying
+    /// again on errors that come from horizon's response.
+    pub(crate) fn with_retry_after(self, retry_after: Option<Duration>) -> Self {
+        match (self, retry_after) {
+            (Error::BadResponse(inner), Some(wait)) => {
+                Error::BadResponse(inner.with_retry_after(wait))
+            }
+            (Error::ServiceUnavailable(_, id), Some(wait)) => {
+                Error::ServiceUnavailable(Some(wait), id)
+            }
+            (other, _) => other,
+        }
+    }
+
+    /// How long horizon asked the client to wait before trying the request
+    /// again, from the `Retry-After` header of a 429 or 503 response.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match *self {
+            Error::BadResponse(ref inner) => inner.retry_after(),
+            Error::ServiceUnavailable(retry_after, _) => retry_after,
+            _ => None,
+        }
+    }
+}
+
 impl StdError for Error {
     fn description(&self) -> &str {
         match *self {
             Error::BadUri => "An invalid uri was specified when constructing the client",
             Error::BadSSL => "Unable to resolve tls",
             Error::Http(ref inner) => inner.description(),
-            Error::Reqwest(ref inner) => inner.description(),
-            Error::JsonParseError(ref inner) => inner.description(),
+            Error::Reqwest(ref inner, _) => inner.description(),
+            Error::JsonParseError(ref inner, _) => inner.description(),
+            Error::ParseResource(ref inner) => inner.description(),
             Error::BadResponse(ref inner) => inner.description(),
             Error::TryFromUri(ref inner) => inner.description(),
-            Error::ServerError => "An unknown error on the server has occurred",
+            Error::Io(ref inner, _) => inner.description(),
+            Error::TransactionTimeout(_) => "Timed out waiting for the transaction",
+            Error::BodyTooLarge(_, _) => "The response body was larger than the client allows",
+            Error::ParseTimeout(_, _) => "Parsing the response took longer than the client allows",
+            Error::ServerTooOld(_, _) => "The horizon server is too old for the request",
+            Error::UnknownNetwork => "The network of the horizon server is not known",
+            Error::LedgerAdvanced(_) => "The ledger kept advancing while taking a snapshot",
+            Error::NoPath => "No path was found for the payment",
+            Error::SlippageExceeded(_) => "The path payment would have slipped past its bound",
+            Error::Codec(_) => "A memo or data value could not be decoded",
+            Error::DeadlineExceeded => "The deadline passed before the work was done",
+            Error::Config(_) => "The client could not be configured",
+            Error::SequenceExhausted(_) => "The account has used up its sequence numbers",
+            Error::Xdr(ref inner) => inner.description(),
+            #[cfg(feature = "history")]
+            Error::NotInArchive(_) => "The history archive does not have the file",
+            #[cfg(feature = "history")]
+            Error::Decompress(ref inner) => inner.description(),
+            #[cfg(feature = "soroban")]
+            Error::Rpc(ref inner) => inner.message(),
+            #[cfg(feature = "soroban")]
+            Error::Simulation(ref inner) => inner,
+            #[cfg(feature = "soroban")]
+            Error::InvalidTransaction(_) => "The transaction breaks a rule of the network",
+            #[cfg(feature = "keys")]
+            Error::Key(ref inner) => inner.description(),
+            Error::ServerError(_) => "An unknown error on the server has occurred",
+            Error::ServiceUnavailable(_, _) => "Horizon is temporarily unavailable",
             Error::__Nonexhaustive => unreachable!(),
         }
     }
@@ -59,7 +240,27 @@ impl StdError for Error {
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(self.description())
+        match *self {
+            Error::ServerTooOld(capability, version) => write!(
+                f,
+                "Horizon {} is too old for {}, which needs {} or later",
+                version,
+                capability,
+                capability.min_version()
+            ),
+            Error::ParseResource(ref inner) => inner.fmt(f),
+            Error::Config(ref reason) => write!(f, "{}: {}", self.description(), reason),
+            #[cfg(feature = "soroban")]
+            Error::InvalidTransaction(ref violation) => {
+                write!(f, "{}: {}", self.description(), violation)
+            }
+            Error::ServiceUnavailable(Some(wait), _) => write!(
+                f,
+                "Horizon is temporarily unavailable, retry after {} seconds",
+                wait.as_secs()
+            ),
+            _ => f.write_str(self.description()),
+        }
     }
 }
 
@@ -95,13 +296,19 @@ impl From<reqwest::UrlError> for Error {
 
 impl From<reqwest::Error> for Error {
     fn from(inner: reqwest::Error) -> Self {
-        Error::Reqwest(inner)
+        Error::Reqwest(inner, None)
     }
 }
 
 impl From<serde_json::error::Error> for Error {
     fn from(inner: serde_json::error::Error) -> Self {
-        Error::JsonParseError(inner)
+        Error::JsonParseError(inner, None)
+    }
+}
+
+impl From<ParseResourceError> for Error {
+    fn from(inner: ParseResourceError) -> Self {
+        Error::ParseResource(inner)
     }
 }
 
@@ -111,6 +318,32 @@ impl From<uri::Error> for Error {
     }
 }
 
+impl From<io::Error> for Error {
+    fn from(inner: io::Error) -> Self {
+        Error::Io(inner, None)
+    }
+}
+
+impl From<XdrError> for Error {
+    fn from(inner: XdrError) -> Self {
+        Error::Xdr(inner)
+    }
+}
+
+#[cfg(feature = "soroban")]
+impl From<RpcError> for Error {
+    fn from(inner: RpcError) -> Self {
+        Error::Rpc(inner)
+    }
+}
+
+#[cfg(feature = "keys")]
+impl From<KeyError> for Error {
+    fn from(inner: KeyError) -> Self {
+        Error::Key(inner)
+    }
+}
+
 #[cfg(test)]
 mod error_coversion_tests {
     use super::*;
@@ -0,0 +1,434 @@
+//! Scans the decentralized exchange for arbitrage: cycles of trades that
+//! start and end in the same asset and return more of it than they spent.
+//!
+//! The scanner walks every cycle through a set of assets, such as
+//! XLM → USD → EUR → XLM, selling a fixed amount into the bids of each order
+//! book in turn. Selling consumes as many price levels as the amount needs,
+//! so an opportunity reflects what the books could actually fill rather
+//! than their best prices alone. Cycles that return more than a threshold
+//! are reported as `Opportunity` values, either from a single `scan` or
+//! continuously through a channel with `run`.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::sync::mpsc;
+//! use std::thread;
+//! use stellar_client::helpers::arbitrage::ArbitrageScanner;
+//! use stellar_client::resources::{Amount, AssetIdentifier};
+//! use stellar_client::sync::Client;
+//!
+//! let issuer = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+//! let scanner = ArbitrageScanner::new(
+//!     vec![
+//!         AssetIdentifier::native(),
+//!         AssetIdentifier::alphanum4("USD", issuer).unwrap(),
+//!         AssetIdentifier::alphanum4("EUR", issuer).unwrap(),
+//!     ],
+//!     Amount::new(1_000_000_000),
+//! ).with_threshold(25);
+//!
+//! let (sender, opportunities) = mpsc::channel();
+//! thread::spawn(move || {
+//!     let client = Client::horizon_test().unwrap();
+//!     scanner.run(&client, sender)
+//! });
+//! for opportunity in opportunities {
+//!     println!("{}", opportunity);
+//! }
+//! ```
+use endpoint::{orderbook, Limit};
+use error::Result;
+use resources::{Amount, AssetIdentifier, OfferSummary, Orderbook};
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+use sync::Requester;
+
+/// The number of price levels fetched from each order book.
+const DEPTH: u32 = 200;
+
+/// Looks for profitable cycles of trades through a set of assets.
+#[derive(Debug, Clone)]
+pub struct ArbitrageScanner {
+    assets: Vec<AssetIdentifier>,
+    amount: Amount,
+    threshold: u32,
+    max_legs: usize,
+    interval: Duration,
+}
+
+impl ArbitrageScanner {
+    /// A scanner for cycles that start and end in the first of the assets,
+    /// trading `amount` of it through the others. By default any profit at
+    /// all is reported, cycles have up to three legs and `run` scans every
+    /// five seconds.
+    pub fn new(assets: Vec<AssetIdentifier>, amount: Amount) -> ArbitrageScanner {
+        ArbitrageScanner {
+            assets,
+            amount,
+            threshold: 0,
+            max_legs: 3,
+            interval: Duration::from_secs(5),
+        }
+    }
+
+    /// Only reports cycles whose profit is more than the given number of
+    /// basis points of the amount traded, to leave room for fees and for
+    /// the books moving before the trades land.
+    pub fn with_threshold(mut self, basis_points: u32) -> ArbitrageScanner {
+        self.threshold = basis_points;
+        self
+    }
+
+    /// The most trades a cycle may take. Every ordering of the assets is
+    /// scanned, so longer cycles cost many more order book requests.
+    pub fn with_max_legs(mut self, max_legs: usize) -> ArbitrageScanner {
+        self.max_legs = max_legs;
+        self
+    }
+
+    /// How long `run` waits between scans.
+    pub fn with_interval(mut self, interval: Duration) -> ArbitrageScanner {
+        self.interval = interval;
+        self
+    }
+
+    /// Fetches the order books the cycles trade through, once each, and
+    /// returns the cycles that clear the threshold, most profitable first.
+    pub fn scan<C>(&self, client: &C) -> Result<Vec<Opportunity>>
+    where
+        C: Requester,
+    {
+        let mut books: HashMap<(&AssetIdentifier, &AssetIdentifier), Orderbook> = HashMap::new();
+        let mut opportunities = Vec::new();
+        for cycle in self.cycles() {
+            let mut amounts = vec![self.amount];
+            for pair in cycle.windows(2) {
+                let book = match books.entry((pair[0], pair[1])) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        let endpoint =
+                            orderbook::Details::for_asset_pair(pair[0].clone(), pair[1].clone())
+                                .with_limit(DEPTH);
+                        entry.insert(client.request(endpoint)?)
+                    }
+                };
+                let sold = *amounts.last().unwrap();
+                match sell(sold, book.bids()) {
+                    Some(bought) => amounts.push(bought),
+                    None => break,
+                }
+            }
+            if amounts.len() < cycle.len() {
+                continue;
+            }
+            let opportunity = Opportunity {
+                cycle: cycle.into_iter().cloned().collect(),
+                amounts,
+            };
+            if opportunity.profit().to_stroops() > 0
+                && opportunity.profit_basis_points() > i64::from(self.threshold)
+            {
+                opportunities.push(opportunity);
+            }
+        }
+        opportunities.sort_by_key(|opportunity| Reverse(opportunity.profit()));
+        Ok(opportunities)
+    }
+
+    /// Scans again and again, sending each opportunity it finds. Returns
+    /// with the first error, or once the receiving end has hung up.
+    pub fn run<C>(&self, client: &C, sender: Sender<Opportunity>) -> Result<()>
+    where
+        C: Requester,
+    {
+        loop {
+            for opportunity in self.scan(client)? {
+                if sender.send(opportunity).is_err() {
+                    return Ok(());
+                }
+            }
+            thread::sleep(self.interval);
+        }
+    }
+
+    /// Every cycle from the first asset through distinct others and back.
+    fn cycles(&self) -> Vec<Vec<&AssetIdentifier>> {
+        let mut cycles = Vec::new();
+        if let Some((start, others)) = self.assets.split_first() {
+            let mut path = vec![start];
+            extend(&mut path, others, self.max_legs, &mut cycles);
+        }
+        cycles
+    }
+}
+
+fn extend<'a>(
+    path: &mut Vec<&'a AssetIdentifier>,
+    others: &'a [AssetIdentifier],
+    max_legs: usize,
+    cycles: &mut Vec<Vec<&'a AssetIdentifier>>,
+) {
+    if path.len() > 1 {
+        let mut cycle = path.clone();
+        cycle.push(path[0]);
+        cycles.push(cycle);
+    }
+    if path.len() >= max_legs {
+        return;
+    }
+    for asset in others {
+        if !path.contains(&asset) {
+            path.push(asset);
+            extend(path, others, max_legs, cycles);
+            path.pop();
+        }
+    }
+}
+
+/// Sells an amount of an order book's base asset into its bids, best price
+/// first, and returns how much of the counter asset it buys. Horizon gives
+/// the amount of each bid in the counter asset. Returns `None` if the bids
+/// can't absorb the whole amount.
+fn sell(amount: Amount, bids: &[OfferSummary]) -> Option<Amount> {
+    let mut remaining = i128::from(amount.to_stroops());
+    let mut bought = 0i128;
+    for bid in bids {
+        if remaining == 0 {
+            break;
+        }
+        let price = bid.price_ratio();
+        let (n, d) = (
+            i128::from(price.numerator()),
+            i128::from(price.denominator()),
+        );
+        if n == 0 {
+            continue;
+        }
+        let capacity = i128::from(bid.amount().to_stroops()) * d / n;
+        let sold = remaining.min(capacity);
+        bought += sold * n / d;
+        remaining -= sold;
+    }
+    if remaining > 0 {
+        None
+    } else {
+        Some(Amount::new(bought as i64))
+    }
+}
+
+/// A cycle of trades that returns more than it spends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Opportunity {
+    cycle: Vec<AssetIdentifier>,
+    amounts: Vec<Amount>,
+}
+
+impl Opportunity {
+    /// The assets traded through, starting and ending with the same one.
+    pub fn cycle(&self) -> &Vec<AssetIdentifier> {
+        &self.cycle
+    }
+
+    /// The amount held of each asset of the cycle in turn, from the amount
+    /// spent to the amount returned.
+    pub fn amounts(&self) -> &Vec<Amount> {
+        &self.amounts
+    }
+
+    /// The amount of the first asset spent.
+    pub fn input(&self) -> Amount {
+        self.amounts[0]
+    }
+
+    /// The amount of the first asset the cycle returns.
+    pub fn output(&self) -> Amount {
+        self.amounts[self.amounts.len() - 1]
+    }
+
+    /// What the cycle earns, in the first asset.
+    pub fn profit(&self) -> Amount {
+        &self.output() - &self.input()
+    }
+
+    /// The profit as a share of the amount spent, in basis points.
+    pub fn profit_basis_points(&self) -> i64 {
+        let input = i128::from(self.input().to_stroops());
+        if input == 0 {
+            return 0;
+        }
+        (i128::from(self.profit().to_stroops()) * 10_000 / input) as i64
+    }
+}
+
+impl fmt::Display for Opportunity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<String> = self
+            .cycle
+            .iter()
+            .map(|asset| asset.code().unwrap_or("XLM").to_string())
+            .collect();
+        write!(
+            f,
+            "{}: {} returns {} ({} bps)",
+            names.join(" -> "),
+            self.input(),
+            self.output(),
+            self.profit_basis_points()
+        )
+    }
+}
+
+#[cfg(test)]
+mod arbitrage_scanner_tests {
+    use super::*;
+    use resources::PriceRatio;
+    use std::sync::mpsc;
+    use test::MockClient;
+
+    const ISSUER: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+
+    fn asset(code: &str) -> AssetIdentifier {
+        AssetIdentifier::alphanum4(code, ISSUER).unwrap()
+    }
+
+    fn asset_json(code: &str) -> String {
+        match code {
+            "XLM" => r#"{"asset_type":"native"}"#.to_string(),
+            _ => format!(
+                r#"{{"asset_type":"credit_alphanum4","asset_code":"{}","asset_issuer":"{}"}}"#,
+                code, ISSUER
+            ),
+        }
+    }
+
+    // A book with a single bid of `amount` of the counter asset at n/d.
+    fn book(base: &str, counter: &str, n: u64, d: u64, amount: &str) -> String {
+        format!(
+            r#"{{"bids":[{{"price_r":{{"n":{},"d":{}}},"price":"1.0000000","amount":"{}"}}],"asks":[],"base":{},"counter":{}}}"#,
+            n,
+            d,
+            amount,
+            asset_json(base),
+            asset_json(counter)
+        )
+    }
+
+    // XLM -> FOO -> BAR -> XLM turns 100 XLM into 200 FOO, 200 BAR and then
+    // 110 XLM. Every other book is empty.
+    fn client() -> MockClient {
+        let empty = r#"{"bids":[],"asks":[],"base":{"asset_type":"native"},"counter":{"asset_type":"native"}}"#;
+        MockClient::new()
+            .with_json(
+                "/order_book?selling_asset_type=native&buying_asset_type=credit_alphanum4&buying_asset_code=FOO*",
+                &book("XLM", "FOO", 2, 1, "1000.0000000"),
+            )
+            .with_json(
+                "/order_book?selling_asset_type=credit_alphanum4&selling_asset_code=FOO*buying_asset_code=BAR*",
+                &book("FOO", "BAR", 1, 1, "1000.0000000"),
+            )
+            .with_json(
+                "/order_book?selling_asset_type=credit_alphanum4&selling_asset_code=BAR*buying_asset_type=native*",
+                &book("BAR", "XLM", 11, 20, "1000.0000000"),
+            )
+            .with_json("/order_book?*", empty)
+    }
+
+    fn scanner() -> ArbitrageScanner {
+        ArbitrageScanner::new(
+            vec![AssetIdentifier::native(), asset("FOO"), asset("BAR")],
+            Amount::new(1_000_000_000),
+        )
+    }
+
+    #[test]
+    fn it_walks_every_cycle_through_the_first_asset() {
+        let scanner = scanner();
+        let cycles = scanner.cycles();
+        assert_eq!(cycles.len(), 4);
+        assert!(cycles.iter().all(|cycle| cycle[0].is_native()));
+        assert!(cycles
+            .iter()
+            .all(|cycle| cycle[cycle.len() - 1].is_native()));
+        assert_eq!(scanner.with_max_legs(2).cycles().len(), 2);
+    }
+
+    #[test]
+    fn it_finds_profitable_cycles() {
+        let client = client();
+        let opportunities = scanner().scan(&client).unwrap();
+        assert_eq!(opportunities.len(), 1);
+        let opportunity = &opportunities[0];
+        assert_eq!(
+            opportunity.cycle(),
+            &vec![
+                AssetIdentifier::native(),
+                asset("FOO"),
+                asset("BAR"),
+                AssetIdentifier::native()
+            ]
+        );
+        assert_eq!(opportunity.output(), Amount::new(1_100_000_000));
+        assert_eq!(opportunity.profit(), Amount::new(100_000_000));
+        assert_eq!(opportunity.profit_basis_points(), 1000);
+        assert_eq!(
+            opportunity.to_string(),
+            "XLM -> FOO -> BAR -> XLM: 100.0000000 returns 110.0000000 (1000 bps)"
+        );
+        // Each book is fetched once, and cycles stop at the first empty one.
+        assert_eq!(client.requests().len(), 5);
+    }
+
+    #[test]
+    fn it_skips_cycles_below_the_threshold_or_beyond_the_depth() {
+        let client = client();
+        assert!(scanner()
+            .with_threshold(1000)
+            .scan(&client)
+            .unwrap()
+            .is_empty());
+        let too_much = ArbitrageScanner::new(
+            vec![AssetIdentifier::native(), asset("FOO"), asset("BAR")],
+            Amount::new(10_000_000_000),
+        );
+        assert!(too_much.scan(&client).unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_consumes_price_levels_in_turn() {
+        let bids = vec![
+            OfferSummary::new(
+                Amount::new(100_000_000),
+                PriceRatio::new(2, 1),
+                Amount::new(20_000_000),
+            ),
+            OfferSummary::new(
+                Amount::new(100_000_000),
+                PriceRatio::new(1, 1),
+                Amount::new(10_000_000),
+            ),
+        ];
+        // 5 at 2 fills the first level, and the other 5 go at 1.
+        assert_eq!(
+            sell(Amount::new(100_000_000), &bids),
+            Some(Amount::new(150_000_000))
+        );
+        assert_eq!(sell(Amount::new(200_000_000), &bids), None);
+    }
+
+    #[test]
+    fn it_sends_opportunities_until_the_receiver_hangs_up() {
+        let (sender, receiver) = mpsc::channel();
+        let scanner = scanner().with_interval(Duration::from_millis(0));
+        let handle = thread::spawn(move || scanner.run(&client(), sender));
+        let opportunity = receiver.recv().unwrap();
+        assert_eq!(opportunity.profit(), Amount::new(100_000_000));
+        drop(receiver);
+        assert!(handle.join().unwrap().is_ok());
+    }
+}
@@ -0,0 +1,377 @@
+//! Reconstructs balance changes for an account by folding over its effects.
+//!
+//! Exchanges and other custodians need to verify that the balances they have
+//! on record line up with the ledger. The types in this module make it possible
+//! to take a snapshot of an account, replay the effects (and fees) that touched
+//! it, and compare the result against the account as horizon reports it now.
+//!
+//! # Examples
+//!
+//! ```
+//! use stellar_client::helpers::balance::{BalanceDelta, BalanceSnapshot};
+//! use stellar_client::resources::AssetIdentifier;
+//!
+//! let account_id = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into();
+//! let mut before = BalanceSnapshot::default();
+//! before.insert(AssetIdentifier::native(), 1_000);
+//!
+//! let delta = BalanceDelta::new(account_id);
+//! let after = before.apply(&delta);
+//! assert!(before.discrepancies(&after).is_empty());
+//! ```
+use resources::effect::{account, trade, EffectKind};
+use resources::{Account, AccountId, AssetIdentifier, Effect, Transaction};
+use std::collections::HashMap;
+
+/// The balances of an account at a point in time, keyed by asset. Values are
+/// stored in stroops so that they can be freely added to and subtracted from.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BalanceSnapshot {
+    balances: HashMap<AssetIdentifier, i64>,
+}
+
+impl BalanceSnapshot {
+    /// Captures the current balances reported for an account.
+    pub fn from_account(account: &Account) -> BalanceSnapshot {
+        let balances = account
+            .balances()
+            .iter()
+            .map(|balance| {
+                (
+                    balance.asset_identifier().clone(),
+                    balance.balance().to_stroops(),
+                )
+            })
+            .collect();
+        BalanceSnapshot { balances }
+    }
+
+    /// Sets the balance, in stroops, held for an asset.
+    pub fn insert(&mut self, asset: AssetIdentifier, stroops: i64) {
+        self.balances.insert(asset, stroops);
+    }
+
+    /// The balance, in stroops, held for an asset. Assets that are not part of
+    /// the snapshot have a balance of zero.
+    pub fn get(&self, asset: &AssetIdentifier) -> i64 {
+        self.balances.get(asset).cloned().unwrap_or(0)
+    }
+
+    /// All of the balances in this snapshot, in stroops.
+    pub fn balances(&self) -> &HashMap<AssetIdentifier, i64> {
+        &self.balances
+    }
+
+    /// Returns a new snapshot with the changes in the delta applied.
+    pub fn apply(&self, delta: &BalanceDelta) -> BalanceSnapshot {
+        let mut balances = self.balances.clone();
+        for (asset, change) in delta.changes() {
+            *balances.entry(asset.clone()).or_insert(0) += *change;
+        }
+        BalanceSnapshot { balances }
+    }
+
+    /// Compares this snapshot, treated as the expected balances, against the
+    /// actual balances and lists every asset where the two disagree.
+    pub fn discrepancies(&self, actual: &BalanceSnapshot) -> Vec<Discrepancy> {
+        let mut assets: Vec<&AssetIdentifier> = self.balances.keys().collect();
+        assets.extend(
+            actual
+                .balances
+                .keys()
+                .filter(|asset| !self.balances.contains_key(asset)),
+        );
+        assets
+            .into_iter()
+            .filter_map(|asset| {
+                let expected = self.get(asset);
+                let actual = actual.get(asset);
+                if expected == actual {
+                    None
+                } else {
+                    Some(Discrepancy {
+                        asset: asset.clone(),
+                        expected,
+                        actual,
+                    })
+                }
+            })
+            .collect()
+    }
+}
+
+/// The net change in balances for a single account, built up by applying the
+/// effects and transactions that affected it.
+#[derive(Debug, Clone)]
+pub struct BalanceDelta {
+    account_id: AccountId,
+    changes: HashMap<AssetIdentifier, i64>,
+}
+
+impl BalanceDelta {
+    /// Creates an empty delta for the given account.
+    pub fn new(account_id: AccountId) -> BalanceDelta {
+        BalanceDelta {
+            account_id,
+            changes: HashMap::new(),
+        }
+    }
+
+    /// The account this delta is tracking.
+    pub fn account_id(&self) -> &AccountId {
+        &self.account_id
+    }
+
+    /// The net change, in stroops, for each asset touched so far.
+    pub fn changes(&self) -> &HashMap<AssetIdentifier, i64> {
+        &self.changes
+    }
+
+    /// The net change, in stroops, for a single asset.
+    pub fn change(&self, asset: &AssetIdentifier) -> i64 {
+        self.changes.get(asset).cloned().unwrap_or(0)
+    }
+
+    /// Applies an effect to the delta. Account creation, credits, debits and
+    /// trades move balances. Effects for other accounts and effects that do
+    /// not move funds are ignored.
+    pub fn apply_effect(&mut self, effect: &Effect) {
+        match *effect.kind() {
+            EffectKind::Account(account::Kind::Created(ref created))
+                if *created.account() == self.account_id =>
+            {
+                self.add(
+                    &AssetIdentifier::native(),
+                    created.starting_balance().to_stroops(),
+                );
+            }
+            EffectKind::Account(account::Kind::Credited(ref credited))
+                if *credited.account() == self.account_id =>
+            {
+                self.add(credited.asset(), credited.amount().to_stroops());
+            }
+            EffectKind::Account(account::Kind::Debited(ref debited))
+                if *debited.account() == self.account_id =>
+            {
+                self.add(debited.asset(), -debited.amount().to_stroops());
+            }
+            EffectKind::Trade(trade::Kind::Trade(ref trade))
+                if *trade.account() == self.account_id =>
+            {
+                self.add(trade.sold_asset(), -trade.sold_amount().to_stroops());
+                self.add(trade.bought_asset(), trade.bought_amount().to_stroops());
+            }
+            _ => {}
+        }
+    }
+
+    /// Applies the fee of a transaction to the delta. Fees are not represented
+    /// as effects, so they need to be folded in from the transactions submitted
+    /// by the account. Transactions submitted by other accounts are ignored.
+    pub fn apply_fee(&mut self, transaction: &Transaction) {
+        if *transaction.source_account() == self.account_id {
+            self.add(&AssetIdentifier::native(), -transaction.fee_paid());
+        }
+    }
+
+    /// Applies every effect yielded by the iterator.
+    pub fn apply_effects<'a, I>(&mut self, effects: I)
+    where
+        I: IntoIterator<Item = &'a Effect>,
+    {
+        for effect in effects {
+            self.apply_effect(effect);
+        }
+    }
+
+    /// Applies the fee of every transaction yielded by the iterator.
+    pub fn apply_fees<'a, I>(&mut self, transactions: I)
+    where
+        I: IntoIterator<Item = &'a Transaction>,
+    {
+        for transaction in transactions {
+            self.apply_fee(transaction);
+        }
+    }
+
+    fn add(&mut self, asset: &AssetIdentifier, stroops: i64) {
+        *self.changes.entry(asset.clone()).or_insert(0) += stroops;
+    }
+}
+
+/// A difference between the balance that was expected for an asset and the
+/// balance that was actually found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Discrepancy {
+    asset: AssetIdentifier,
+    expected: i64,
+    actual: i64,
+}
+
+impl Discrepancy {
+    /// The asset whose balance does not match.
+    pub fn asset(&self) -> &AssetIdentifier {
+        &self.asset
+    }
+
+    /// The balance, in stroops, that was expected.
+    pub fn expected(&self) -> i64 {
+        self.expected
+    }
+
+    /// The balance, in stroops, that was actually found.
+    pub fn actual(&self) -> i64 {
+        self.actual
+    }
+
+    /// How far the actual balance is from the expected one, in stroops. A
+    /// positive value means the account holds more than expected.
+    pub fn difference(&self) -> i64 {
+        self.actual - self.expected
+    }
+}
+
+/// Replays the delta on top of a starting snapshot and compares the result
+/// against the account as it currently exists, returning any assets whose
+/// balances disagree.
+pub fn reconcile(
+    starting: &BalanceSnapshot,
+    delta: &BalanceDelta,
+    current: &Account,
+) -> Vec<Discrepancy> {
+    starting
+        .apply(delta)
+        .discrepancies(&BalanceSnapshot::from_account(current))
+}
+
+#[cfg(test)]
+mod balance_tests {
+    use super::*;
+    use serde_json;
+
+    const ACCOUNT_ID: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+    const ISSUER: &str = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4";
+
+    fn account() -> Account {
+        serde_json::from_str(include_str!("../../fixtures/account.json")).unwrap()
+    }
+
+    fn transaction() -> Transaction {
+        serde_json::from_str(include_str!(
+            "../../fixtures/transactions/transaction_memo_none.json"
+        )).unwrap()
+    }
+
+    fn effect(account: &str, kind: &str, amount: &str) -> Effect {
+        serde_json::from_str(&format!(
+            r#"{{
+                "id": "0000013563506724865-0000000001",
+                "paging_token": "13563506724865-1",
+                "created_at": "2018-08-20T20:55:58Z",
+                "account": "{}",
+                "type": "{}",
+                "asset_type": "native",
+                "amount": "{}",
+                "starting_balance": "{}"
+            }}"#,
+            account, kind, amount, amount
+        )).unwrap()
+    }
+
+    fn trade(account: &str) -> Effect {
+        serde_json::from_str(&format!(
+            r#"{{
+                "id": "0000033788507721730-0000000002",
+                "paging_token": "33788507721730-2",
+                "created_at": "2018-08-20T20:55:58Z",
+                "account": "{}",
+                "type": "trade",
+                "seller": "{}",
+                "offer_id": 1,
+                "sold_amount": "10.0",
+                "sold_asset_type": "native",
+                "bought_amount": "2.5",
+                "bought_asset_type": "credit_alphanum4",
+                "bought_asset_code": "FOO",
+                "bought_asset_issuer": "{}"
+            }}"#,
+            account, ISSUER, ISSUER
+        )).unwrap()
+    }
+
+    fn foo() -> AssetIdentifier {
+        AssetIdentifier::alphanum4("FOO", ISSUER).unwrap()
+    }
+
+    #[test]
+    fn it_snapshots_account_balances() {
+        let snapshot = BalanceSnapshot::from_account(&account());
+        assert_eq!(snapshot.get(&AssetIdentifier::native()), 98_999_999_800);
+        assert_eq!(snapshot.get(&foo()), 10_000_000_000);
+        assert_eq!(
+            snapshot.get(&AssetIdentifier::alphanum4("BAR", ISSUER).unwrap()),
+            0
+        );
+    }
+
+    #[test]
+    fn it_folds_credits_and_debits() {
+        let mut delta = BalanceDelta::new(ACCOUNT_ID.into());
+        delta.apply_effect(&effect(ACCOUNT_ID, "account_created", "100.0"));
+        delta.apply_effect(&effect(ACCOUNT_ID, "account_credited", "5.0"));
+        delta.apply_effect(&effect(ACCOUNT_ID, "account_debited", "1.5"));
+        assert_eq!(delta.change(&AssetIdentifier::native()), 1_035_000_000);
+    }
+
+    #[test]
+    fn it_folds_trades_into_both_assets() {
+        let mut delta = BalanceDelta::new(ACCOUNT_ID.into());
+        delta.apply_effect(&trade(ACCOUNT_ID));
+        assert_eq!(delta.change(&AssetIdentifier::native()), -100_000_000);
+        assert_eq!(delta.change(&foo()), 25_000_000);
+    }
+
+    #[test]
+    fn it_ignores_effects_for_other_accounts() {
+        let mut delta = BalanceDelta::new(ACCOUNT_ID.into());
+        delta.apply_effects(&[effect(ISSUER, "account_credited", "5.0"), trade(ISSUER)]);
+        assert!(delta.changes().is_empty());
+    }
+
+    #[test]
+    fn it_subtracts_fees_for_the_source_account() {
+        let tx = transaction();
+        let mut delta = BalanceDelta::new(tx.source_account().clone());
+        delta.apply_fee(&tx);
+        assert_eq!(delta.change(&AssetIdentifier::native()), -100);
+
+        let mut other = BalanceDelta::new(ACCOUNT_ID.into());
+        other.apply_fees(&[tx]);
+        assert!(other.changes().is_empty());
+    }
+
+    #[test]
+    fn it_reconciles_matching_balances() {
+        let mut starting = BalanceSnapshot::default();
+        starting.insert(AssetIdentifier::native(), 99_099_999_800);
+        starting.insert(foo(), 9_975_000_000);
+
+        let mut delta = BalanceDelta::new(ACCOUNT_ID.into());
+        delta.apply_effect(&trade(ACCOUNT_ID));
+        assert!(reconcile(&starting, &delta, &account()).is_empty());
+    }
+
+    #[test]
+    fn it_flags_discrepancies() {
+        let mut starting = BalanceSnapshot::default();
+        starting.insert(AssetIdentifier::native(), 98_999_999_800);
+
+        let delta = BalanceDelta::new(ACCOUNT_ID.into());
+        let discrepancies = reconcile(&starting, &delta, &account());
+        assert_eq!(discrepancies.len(), 1);
+        assert_eq!(discrepancies[0].asset(), &foo());
+        assert_eq!(discrepancies[0].expected(), 0);
+        assert_eq!(discrepancies[0].actual(), 10_000_000_000);
+        assert_eq!(discrepancies[0].difference(), 10_000_000_000);
+    }
+}
@@ -0,0 +1,284 @@
+//! Fetches a series of candles for an asset pair across any span of time.
+//!
+//! Horizon's trade aggregations endpoint answers with at most 200 buckets at
+//! a time and leaves out the buckets in which nothing traded. `Candles`
+//! splits a long span into windows of 200 buckets, fetches each in turn and
+//! stitches them back together. Buckets are lined up on multiples of the
+//! resolution, and the buckets nothing traded in are filled with empty
+//! candles, so the series has one candle per bucket and can be handed
+//! straight to analysis.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use stellar_client::endpoint::trade::SegmentResolution;
+//! use stellar_client::helpers::candles::Candles;
+//! use stellar_client::resources::AssetIdentifier;
+//! use stellar_client::sync::Client;
+//!
+//! let client = Client::horizon_test().unwrap();
+//! let usd = AssetIdentifier::alphanum4(
+//!     "USD",
+//!     "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG",
+//! ).unwrap();
+//! let candles = Candles::new(AssetIdentifier::native(), usd, SegmentResolution::OneHour);
+//!
+//! // A month of hourly candles takes four requests.
+//! let end = 1_517_529_600_000;
+//! let series = candles.fetch(&client, end - 30 * 86_400_000, end).unwrap();
+//! for candle in series {
+//!     println!(
+//!         "{} open {} high {} low {} close {} volume {}",
+//!         candle.started_at(),
+//!         candle.open(),
+//!         candle.high(),
+//!         candle.low(),
+//!         candle.close(),
+//!         candle.base_volume(),
+//!     );
+//! }
+//! ```
+use endpoint::trade::{self, SegmentResolution};
+use endpoint::{Direction, Limit, Order};
+use error::Result;
+use resources::{Amount, AssetIdentifier, TradeAggregation};
+use std::collections::BTreeMap;
+use sync::Requester;
+
+/// The number of buckets asked for in each request, which is the most
+/// horizon returns.
+const PAGE_SIZE: u32 = 200;
+
+/// Fetches evenly spaced candles for an asset pair.
+#[derive(Debug, Clone)]
+pub struct Candles {
+    base: AssetIdentifier,
+    counter: AssetIdentifier,
+    resolution: SegmentResolution,
+}
+
+impl Candles {
+    /// Candles of the price of `base` in terms of `counter`, one for each
+    /// bucket of `resolution`.
+    pub fn new(
+        base: AssetIdentifier,
+        counter: AssetIdentifier,
+        resolution: SegmentResolution,
+    ) -> Candles {
+        Candles {
+            base,
+            counter,
+            resolution,
+        }
+    }
+
+    /// Fetches the candles between `start_time` and `end_time`, given in
+    /// milliseconds since epoch, oldest first.
+    ///
+    /// The start is moved back and the end forward to the nearest multiple of
+    /// the resolution, so that the first and last buckets are whole. A bucket
+    /// in which nothing traded gets a candle with no trades and no volume
+    /// whose prices are the close of the bucket before it. Buckets before the
+    /// first trade in the span have no price to carry, so the series starts
+    /// at the first bucket with trades and is empty if there were none.
+    pub fn fetch<C>(
+        &self,
+        client: &C,
+        start_time: u64,
+        end_time: u64,
+    ) -> Result<Vec<TradeAggregation>>
+    where
+        C: Requester + ?Sized,
+    {
+        let step = u64::from(self.resolution);
+        let start = start_time - start_time % step;
+        let end = match end_time % step {
+            0 => end_time,
+            rest => end_time + (step - rest),
+        };
+
+        let mut buckets = BTreeMap::new();
+        let mut from = start;
+        while from < end {
+            let to = end.min(from + step * u64::from(PAGE_SIZE));
+            let endpoint = trade::Aggregations::new(&self.base, &self.counter)
+                .with_resolution(self.resolution)
+                .with_start_time(from)
+                .with_end_time(to)
+                .with_order(Direction::Asc)
+                .with_limit(PAGE_SIZE);
+            for aggregation in client.request(endpoint)? {
+                let bucket = timestamp(&aggregation) / step * step;
+                if start <= bucket && bucket < end {
+                    buckets.insert(bucket, aggregation);
+                }
+            }
+            from = to;
+        }
+        Ok(fill(&buckets, end, step))
+    }
+}
+
+/// Lines the aggregations up on their buckets and puts an empty candle in
+/// each bucket that has none.
+fn fill(buckets: &BTreeMap<u64, TradeAggregation>, end: u64, step: u64) -> Vec<TradeAggregation> {
+    let first = match buckets.keys().next() {
+        Some(&first) => first,
+        None => return Vec::new(),
+    };
+    let mut candles = Vec::with_capacity(((end - first) / step) as usize);
+    let mut close = Amount::new(0);
+    let mut bucket = first;
+    while bucket < end {
+        let candle = match buckets.get(&bucket) {
+            Some(aggregation) => TradeAggregation::new(
+                bucket,
+                aggregation.count(),
+                aggregation.base_volume(),
+                aggregation.counter_volume(),
+                aggregation.average(),
+                aggregation.high(),
+                aggregation.low(),
+                aggregation.open(),
+                aggregation.close(),
+            ),
+            None => TradeAggregation::new(
+                bucket,
+                0,
+                Amount::new(0),
+                Amount::new(0),
+                close,
+                close,
+                close,
+                close,
+                close,
+            ),
+        };
+        close = candle.close();
+        candles.push(candle);
+        bucket += step;
+    }
+    candles
+}
+
+/// The start of an aggregation's bucket in milliseconds since epoch.
+fn timestamp(aggregation: &TradeAggregation) -> u64 {
+    aggregation.started_at().timestamp_millis() as u64
+}
+
+#[cfg(test)]
+mod candles_tests {
+    use super::*;
+    use test::MockClient;
+
+    const HOUR: u64 = 3_600_000;
+
+    fn candles() -> Candles {
+        Candles::new(
+            AssetIdentifier::native(),
+            AssetIdentifier::alphanum4(
+                "USD",
+                "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG",
+            )
+            .unwrap(),
+            SegmentResolution::OneHour,
+        )
+    }
+
+    // An aggregation of a single trade at `price`.
+    fn aggregation(timestamp: u64, price: &str) -> String {
+        format!(
+            r#"{{
+                "timestamp": {0},
+                "trade_count": 1,
+                "base_volume": "10.0000000",
+                "counter_volume": "2.0000000",
+                "avg": "{1}",
+                "high": "{1}",
+                "low": "{1}",
+                "open": "{1}",
+                "close": "{1}"
+            }}"#,
+            timestamp, price
+        )
+    }
+
+    fn page(aggregations: &[String]) -> String {
+        format!(
+            r#"{{ "_embedded": {{ "records": [{}] }} }}"#,
+            aggregations.join(",")
+        )
+    }
+
+    fn times(series: &[TradeAggregation]) -> Vec<u64> {
+        series.iter().map(timestamp).collect()
+    }
+
+    #[test]
+    fn it_stitches_windows_of_buckets_together() {
+        let client = MockClient::new()
+            .with_json(
+                "/trade_aggregations?*start_time=0&*",
+                &page(&[aggregation(0, "0.2000000")]),
+            )
+            .with_json(
+                &format!("/trade_aggregations?*start_time={}&*", 200 * HOUR),
+                &page(&[aggregation(250 * HOUR, "0.3000000")]),
+            );
+        let series = candles().fetch(&client, 0, 300 * HOUR).unwrap();
+        assert_eq!(
+            client.requests()[1],
+            format!(
+                "/trade_aggregations?base_asset_type=native&\
+                 counter_asset_type=credit_alphanum4&counter_asset_code=USD&\
+                 counter_asset_issuer=GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG&\
+                 resolution={}&start_time={}&end_time={}&order=asc&limit=200",
+                HOUR,
+                200 * HOUR,
+                300 * HOUR
+            )
+        );
+        assert_eq!(client.requests().len(), 2);
+        assert_eq!(series.len(), 300);
+        assert_eq!(series[250].close(), Amount::new(3_000_000));
+        assert_eq!(series[250].count(), 1);
+    }
+
+    #[test]
+    fn it_fills_gaps_with_the_previous_close() {
+        let client = MockClient::new().with_json(
+            "/trade_aggregations*",
+            &page(&[
+                aggregation(HOUR, "0.2000000"),
+                aggregation(4 * HOUR, "0.3000000"),
+            ]),
+        );
+        let series = candles().fetch(&client, 0, 6 * HOUR).unwrap();
+        assert_eq!(
+            times(&series),
+            vec![HOUR, 2 * HOUR, 3 * HOUR, 4 * HOUR, 5 * HOUR]
+        );
+        assert_eq!(series[1].count(), 0);
+        assert_eq!(series[1].base_volume(), Amount::new(0));
+        assert_eq!(series[2].open(), Amount::new(2_000_000));
+        assert_eq!(series[2].close(), Amount::new(2_000_000));
+        assert_eq!(series[4].low(), Amount::new(3_000_000));
+    }
+
+    #[test]
+    fn it_lines_buckets_up_on_the_resolution() {
+        let client = MockClient::new().with_json(
+            "/trade_aggregations*",
+            &page(&[aggregation(HOUR + 1_000, "0.2000000")]),
+        );
+        let series = candles().fetch(&client, HOUR / 2, 2 * HOUR + 1).unwrap();
+        assert_eq!(times(&series), vec![HOUR, 2 * HOUR]);
+        client.assert_requested(&format!("*start_time=0&end_time={}&*", 3 * HOUR));
+    }
+
+    #[test]
+    fn it_is_empty_without_trades() {
+        let client = MockClient::new().with_json("/trade_aggregations*", &page(&[]));
+        assert!(candles().fetch(&client, 0, 10 * HOUR).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,341 @@
+//! Decodes the structured data that applications keep in memos and data
+//! entries.
+//!
+//! Memos and `manage_data` values are free form, and many applications put
+//! something structured in them: an order id as json, or a payload packed
+//! with a binary format and base64 encoded to fit. A `Codec` turns the text
+//! horizon gives for one into a value of the application's own type. `Json`
+//! and `Base64` cover the common cases and can be combined, and anything else
+//! is a matter of implementing the trait.
+//!
+//! `DecodePayloads` runs a codec over the records coming out of a
+//! `PaymentWatcher`, an `Iter` or any other iterator of results, and an
+//! export `Page` decodes its records with `Page::into_decoded`. A payload
+//! that doesn't decode is handed on with its error rather than ending the
+//! pipeline, since anyone can send a payment with whatever memo they like.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! #[macro_use]
+//! extern crate serde_derive;
+//! extern crate stellar_client;
+//!
+//! use stellar_client::endpoint::{account, Cursor};
+//! use stellar_client::helpers::codec::{Base64, DecodePayloads, Json};
+//! use stellar_client::helpers::payments::PaymentWatcher;
+//! use stellar_client::sync::Client;
+//!
+//! #[derive(Debug, Deserialize)]
+//! struct Order {
+//!     id: u32,
+//! }
+//!
+//! # fn main() {
+//! let client = Client::horizon_test().unwrap();
+//! let account_id = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+//! let payments = client
+//!     .stream(account::Payments::new(account_id.into()).with_cursor("now"))
+//!     .unwrap();
+//! let watcher = PaymentWatcher::new(&client, account_id.into(), payments);
+//! for payment in DecodePayloads::new(watcher, Base64::new(Json::<Order>::new())) {
+//!     let payment = payment.unwrap();
+//!     match payment.payload() {
+//!         Some(Ok(order)) => println!("{} paid for order {}", payment.record().from(), order.id),
+//!         Some(Err(err)) => println!("unreadable memo: {}", err),
+//!         None => println!("no memo"),
+//!     }
+//! }
+//! # }
+//! ```
+use base64;
+use error::{Error, Result};
+use helpers::export::Page;
+use helpers::payments::IncomingPayment;
+use resources::{Memo, Operation, OperationKind, Transaction, TransactionHeader};
+use serde::de::DeserializeOwned;
+use serde_json;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Turns the text of a memo or data value into a value of another type.
+pub trait Codec {
+    /// What the codec decodes to.
+    type Output;
+
+    /// Decodes `raw`. Codecs that need to fail with a reason of their own
+    /// can use `Error::Codec`.
+    fn decode(&self, raw: &[u8]) -> Result<Self::Output>;
+}
+
+impl<'a, C> Codec for &'a C
+where
+    C: Codec + ?Sized,
+{
+    type Output = C::Output;
+
+    fn decode(&self, raw: &[u8]) -> Result<C::Output> {
+        (**self).decode(raw)
+    }
+}
+
+/// Decodes json into `T`.
+pub struct Json<T> {
+    output: PhantomData<fn() -> T>,
+}
+
+impl<T> Json<T> {
+    /// A codec for json that deserializes to `T`.
+    pub fn new() -> Json<T> {
+        Json {
+            output: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for Json<T> {
+    fn default() -> Json<T> {
+        Json::new()
+    }
+}
+
+impl<T> fmt::Debug for Json<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Json").finish()
+    }
+}
+
+impl<T> Codec for Json<T>
+where
+    T: DeserializeOwned,
+{
+    type Output = T;
+
+    fn decode(&self, raw: &[u8]) -> Result<T> {
+        Ok(serde_json::from_slice(raw)?)
+    }
+}
+
+/// Decodes base64 and hands the bytes on to another codec.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Base64<C> {
+    inner: C,
+}
+
+impl<C> Base64<C> {
+    /// A codec for base64 wrapped around whatever `inner` decodes.
+    pub fn new(inner: C) -> Base64<C> {
+        Base64 { inner }
+    }
+}
+
+impl<C> Codec for Base64<C>
+where
+    C: Codec,
+{
+    type Output = C::Output;
+
+    fn decode(&self, raw: &[u8]) -> Result<C::Output> {
+        let bytes = base64::decode(raw).map_err(|err| Error::Codec(err.to_string()))?;
+        self.inner.decode(&bytes)
+    }
+}
+
+/// A record that can carry an application's payload.
+pub trait Payload {
+    /// The payload as horizon gives it, or `None` when the record has none.
+    fn payload(&self) -> Option<&str>;
+}
+
+/// The text of a text memo. Other memos carry no payload.
+fn memo_payload(memo: &Memo) -> Option<&str> {
+    match *memo {
+        Memo::Text(ref text) => Some(text),
+        _ => None,
+    }
+}
+
+impl Payload for Transaction {
+    fn payload(&self) -> Option<&str> {
+        memo_payload(self.memo())
+    }
+}
+
+impl Payload for TransactionHeader {
+    fn payload(&self) -> Option<&str> {
+        memo_payload(self.memo())
+    }
+}
+
+impl Payload for IncomingPayment {
+    fn payload(&self) -> Option<&str> {
+        memo_payload(self.memo())
+    }
+}
+
+/// The value a `manage_data` operation sets, which horizon gives base64
+/// encoded. Operations of other kinds, and those that delete an entry, carry
+/// no payload.
+impl Payload for Operation {
+    fn payload(&self) -> Option<&str> {
+        match *self.kind() {
+            OperationKind::ManageData(ref data) if !data.value().is_empty() => Some(data.value()),
+            _ => None,
+        }
+    }
+}
+
+/// A record together with its decoded payload.
+#[derive(Debug)]
+pub struct Decoded<T, V> {
+    record: T,
+    payload: Option<Result<V>>,
+}
+
+impl<T, V> Decoded<T, V> {
+    /// Decodes the payload of `record` with `codec`.
+    pub fn new<C>(record: T, codec: &C) -> Decoded<T, V>
+    where
+        T: Payload,
+        C: Codec<Output = V> + ?Sized,
+    {
+        let payload = record
+            .payload()
+            .map(|payload| codec.decode(payload.as_bytes()));
+        Decoded { record, payload }
+    }
+
+    /// The record the payload came from.
+    pub fn record(&self) -> &T {
+        &self.record
+    }
+
+    /// The decoded payload, the error decoding it, or `None` when the record
+    /// has no payload.
+    pub fn payload(&self) -> Option<&Result<V>> {
+        self.payload.as_ref()
+    }
+
+    /// Splits the record from its decoded payload.
+    pub fn into_parts(self) -> (T, Option<Result<V>>) {
+        (self.record, self.payload)
+    }
+}
+
+/// Decodes the payloads of the records an iterator yields. Errors from the
+/// iterator are passed on untouched.
+#[derive(Debug)]
+pub struct DecodePayloads<I, C> {
+    records: I,
+    codec: C,
+}
+
+impl<I, C> DecodePayloads<I, C> {
+    /// Decodes the payloads of `records` with `codec`.
+    pub fn new(records: I, codec: C) -> DecodePayloads<I, C> {
+        DecodePayloads { records, codec }
+    }
+}
+
+impl<I, C, T> Iterator for DecodePayloads<I, C>
+where
+    I: Iterator<Item = Result<T>>,
+    C: Codec,
+    T: Payload,
+{
+    type Item = Result<Decoded<T, C::Output>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(
+            self.records
+                .next()?
+                .map(|record| Decoded::new(record, &self.codec)),
+        )
+    }
+}
+
+impl<T> Page<T>
+where
+    T: Payload,
+{
+    /// Takes the records off the page with their payloads decoded by
+    /// `codec`.
+    pub fn into_decoded<C>(self, codec: &C) -> Vec<Decoded<T, C::Output>>
+    where
+        C: Codec + ?Sized,
+    {
+        self.into_records()
+            .into_iter()
+            .map(|record| Decoded::new(record, codec))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod codec_tests {
+    use super::*;
+    use resources::{Resource, TransactionBuilder};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Order {
+        id: u32,
+    }
+
+    fn transaction(memo: Memo) -> Transaction {
+        TransactionBuilder::new(
+            "abc",
+            "GAKLBGHNHFQ3BMUYG5KU4BEWO6EYQHZHAXEWC33W34PH2RBHZDSQBD75".into(),
+        )
+        .with_memo(memo)
+        .build()
+    }
+
+    #[test]
+    fn it_decodes_json_inside_base64() {
+        let codec = Base64::new(Json::<Order>::new());
+        assert_eq!(codec.decode(b"eyJpZCI6N30=").unwrap(), Order { id: 7 });
+        match codec.decode(b"not base64!") {
+            Err(Error::Codec(_)) => (),
+            other => panic!("expected a codec error, got {:?}", other),
+        }
+        assert!(Json::<Order>::new().decode(b"{}").is_err());
+    }
+
+    #[test]
+    fn it_decodes_the_payloads_of_records() {
+        let records = vec![
+            Ok(transaction(Memo::Text(r#"{"id":7}"#.to_string()))),
+            Ok(transaction(Memo::Id(7))),
+            Ok(transaction(Memo::Text("spam".to_string()))),
+            Err(Error::ServerError(None)),
+        ];
+        let decoded: Vec<_> =
+            DecodePayloads::new(records.into_iter(), Json::<Order>::new()).collect();
+        match decoded[0].as_ref().unwrap().payload() {
+            Some(Ok(order)) => assert_eq!(*order, Order { id: 7 }),
+            other => panic!("expected an order, got {:?}", other),
+        }
+        assert!(decoded[1].as_ref().unwrap().payload().is_none());
+        assert!(decoded[2].as_ref().unwrap().payload().unwrap().is_err());
+        assert!(decoded[3].is_err());
+    }
+
+    #[test]
+    fn it_reads_the_value_of_a_data_entry() {
+        let operation =
+            Operation::from_json_str(include_str!("../../fixtures/operations/manage_data.json"))
+                .unwrap();
+        let value = match *operation.kind() {
+            OperationKind::ManageData(ref data) => data.value().to_string(),
+            _ => panic!("expected a manage_data operation"),
+        };
+        assert_eq!(operation.payload(), Some(value.as_str()));
+        let decoded = Decoded::new(operation, &Base64::new(Json::<Order>::new()));
+        assert!(decoded.payload().is_some());
+
+        let payment =
+            Operation::from_json_str(include_str!("../../fixtures/operations/payment.json"))
+                .unwrap();
+        assert_eq!(payment.payload(), None);
+    }
+}
@@ -0,0 +1,182 @@
+//! Persists the paging token of the last processed record so that a consumer
+//! can pick up where it left off after a restart.
+//!
+//! Saving the cursor only after a record has been fully handled gives at
+//! least once processing: a crash between handling and saving means the
+//! record is seen again, but it is never skipped.
+//!
+//! # Examples
+//!
+//! ```
+//! use stellar_client::endpoint::{payment, Cursor};
+//! use stellar_client::helpers::cursor::{CursorStore, MemoryCursorStore};
+//!
+//! let store = MemoryCursorStore::new();
+//! store.save("payments", "12884905984").unwrap();
+//!
+//! let endpoint = store.resume("payments", payment::All::default()).unwrap();
+//! assert_eq!(endpoint.cursor(), Some("12884905984"));
+//! ```
+use endpoint::Cursor;
+use error::Result;
+use serde_json;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Somewhere to keep the last processed cursor of one or more record
+/// streams, each identified by a key.
+pub trait CursorStore {
+    /// Returns the cursor saved under `key`, if any.
+    fn load(&self, key: &str) -> Result<Option<String>>;
+
+    /// Saves `cursor` under `key`, replacing any earlier cursor.
+    fn save(&self, key: &str, cursor: &str) -> Result<()>;
+
+    /// Applies the cursor saved under `key` to the endpoint. The endpoint is
+    /// returned untouched when nothing has been saved yet.
+    fn resume<E>(&self, key: &str, endpoint: E) -> Result<E>
+    where
+        E: Cursor,
+        Self: Sized,
+    {
+        Ok(match self.load(key)? {
+            Some(cursor) => endpoint.with_cursor(&cursor),
+            None => endpoint,
+        })
+    }
+}
+
+impl<'a, S> CursorStore for &'a S
+where
+    S: CursorStore + ?Sized,
+{
+    fn load(&self, key: &str) -> Result<Option<String>> {
+        (**self).load(key)
+    }
+
+    fn save(&self, key: &str, cursor: &str) -> Result<()> {
+        (**self).save(key, cursor)
+    }
+}
+
+/// Keeps cursors in memory. Useful in tests and for processes that don't
+/// need to survive a restart.
+#[derive(Debug, Default)]
+pub struct MemoryCursorStore {
+    cursors: Mutex<HashMap<String, String>>,
+}
+
+impl MemoryCursorStore {
+    /// Creates an empty store.
+    pub fn new() -> MemoryCursorStore {
+        MemoryCursorStore::default()
+    }
+}
+
+impl CursorStore for MemoryCursorStore {
+    fn load(&self, key: &str) -> Result<Option<String>> {
+        let cursors = self.cursors.lock().expect("Cursor store lock was poisoned");
+        Ok(cursors.get(key).cloned())
+    }
+
+    fn save(&self, key: &str, cursor: &str) -> Result<()> {
+        let mut cursors = self.cursors.lock().expect("Cursor store lock was poisoned");
+        cursors.insert(key.to_string(), cursor.to_string());
+        Ok(())
+    }
+}
+
+/// Keeps cursors in a json file on disk.
+///
+/// Every save rewrites the whole file. The new contents are written to a
+/// temporary file next to it which is then renamed into place, so a crash
+/// part way through a save leaves the previous cursors intact.
+#[derive(Debug)]
+pub struct FileCursorStore {
+    path: PathBuf,
+    cursors: Mutex<HashMap<String, String>>,
+}
+
+impl FileCursorStore {
+    /// Opens the store at `path`, reading any cursors already saved there.
+    /// The file is created on the first save if it doesn't exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<FileCursorStore> {
+        let path = path.as_ref().to_path_buf();
+        let cursors = if path.exists() {
+            serde_json::from_reader(File::open(&path)?)?
+        } else {
+            HashMap::new()
+        };
+        Ok(FileCursorStore {
+            path,
+            cursors: Mutex::new(cursors),
+        })
+    }
+
+    /// The file the cursors are saved to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl CursorStore for FileCursorStore {
+    fn load(&self, key: &str) -> Result<Option<String>> {
+        let cursors = self.cursors.lock().expect("Cursor store lock was poisoned");
+        Ok(cursors.get(key).cloned())
+    }
+
+    fn save(&self, key: &str, cursor: &str) -> Result<()> {
+        let mut cursors = self.cursors.lock().expect("Cursor store lock was poisoned");
+        cursors.insert(key.to_string(), cursor.to_string());
+
+        let temp = self.path.with_extension("tmp");
+        serde_json::to_writer(File::create(&temp)?, &*cursors)?;
+        fs::rename(&temp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod cursor_store_tests {
+    use super::*;
+    use endpoint::payment;
+    use std::env;
+
+    #[test]
+    fn it_keeps_cursors_in_memory() {
+        let store = MemoryCursorStore::new();
+        assert_eq!(store.load("payments").unwrap(), None);
+        store.save("payments", "1").unwrap();
+        store.save("payments", "2").unwrap();
+        store.save("trades", "3").unwrap();
+        assert_eq!(store.load("payments").unwrap(), Some("2".to_string()));
+        assert_eq!(store.load("trades").unwrap(), Some("3".to_string()));
+    }
+
+    #[test]
+    fn it_leaves_the_endpoint_alone_without_a_cursor() {
+        let store = MemoryCursorStore::new();
+        let endpoint = store.resume("payments", payment::All::default()).unwrap();
+        assert_eq!(endpoint.cursor(), None);
+    }
+
+    #[test]
+    fn it_keeps_cursors_across_reopening_the_file() {
+        let path = env::temp_dir().join("stellar_client_cursor_store.json");
+        let _ = fs::remove_file(&path);
+
+        let store = FileCursorStore::open(&path).unwrap();
+        assert_eq!(store.load("payments").unwrap(), None);
+        store.save("payments", "12884905984").unwrap();
+        assert_eq!(store.path(), path.as_path());
+
+        let reopened = FileCursorStore::open(&path).unwrap();
+        assert_eq!(
+            reopened.load("payments").unwrap(),
+            Some("12884905984".to_string())
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,376 @@
+//! Listens for deposits to an exchange account and confirms them.
+//!
+//! Exchanges usually take deposits into a single account and tell their
+//! users apart by the memo each user is asked to send. A `DepositListener`
+//! watches the payments arriving at the account, matches their memos against
+//! the memos it was told to expect, and holds each matched deposit until a
+//! number of ledgers have closed on top of the one it arrived in. Payments
+//! with a memo nobody expects are reported too, so that they can be refunded.
+//!
+//! A confirmed deposit is checked against the balance changes of its
+//! transaction, which catch every way the account's balance moved, so the
+//! amount to credit doesn't rest on the payment operation alone.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use stellar_client::endpoint::{account, Cursor};
+//! use stellar_client::helpers::deposits::{DepositEvent, DepositListener};
+//! use stellar_client::resources::Memo;
+//! use stellar_client::sync::Client;
+//!
+//! let client = Client::horizon_test().unwrap();
+//! let account_id = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+//! let payments = client
+//!     .stream(account::Payments::new(account_id.into()).with_cursor("now"))
+//!     .unwrap();
+//! let mut listener = DepositListener::new(&client, account_id.into(), payments)
+//!     .with_confirmations(2);
+//! listener.expect(Memo::Id(1001));
+//! for event in listener {
+//!     match event.unwrap() {
+//!         DepositEvent::Received(deposit) => println!("pending {}", deposit.amount()),
+//!         DepositEvent::Confirmed(deposit) => println!("credit {}", deposit.amount()),
+//!         DepositEvent::Unmatched(payment) => println!("refund {}", payment.from()),
+//!     }
+//! }
+//! ```
+use endpoint::{root, transaction};
+use error::Result;
+use helpers::payments::{memo_text, IncomingPayment, PaymentFilter, PaymentWatcher};
+use resources::{AccountId, Amount, Memo, Operation};
+use std::collections::{HashSet, VecDeque};
+use sync::Requester;
+
+/// A payment that carried an expected memo.
+#[derive(Debug, Clone)]
+pub struct Deposit {
+    payment: IncomingPayment,
+    balance_change: Option<i64>,
+}
+
+impl Deposit {
+    /// The payment that made the deposit.
+    pub fn payment(&self) -> &IncomingPayment {
+        &self.payment
+    }
+
+    /// The memo the deposit was matched by.
+    pub fn memo(&self) -> &Memo {
+        self.payment.memo()
+    }
+
+    /// The amount that arrived.
+    pub fn amount(&self) -> Amount {
+        self.payment.amount()
+    }
+
+    /// The net change, in stroops, that the deposit's transaction made to
+    /// the account's balance of the deposited asset, leaving out fees. It is
+    /// only known once the deposit is confirmed, and only when the meta of
+    /// the transaction could be read.
+    ///
+    /// A transaction that pays the account more than once in the same asset
+    /// has one balance change covering all of those payments, so it can be
+    /// more than `amount`. It is less than `amount` when the transaction
+    /// also took funds from the account.
+    pub fn balance_change(&self) -> Option<i64> {
+        self.balance_change
+    }
+}
+
+/// What a `DepositListener` saw happen.
+#[derive(Debug, Clone)]
+pub enum DepositEvent {
+    /// A payment with an expected memo arrived and is waiting for its
+    /// confirmations.
+    Received(Deposit),
+    /// Enough ledgers have closed on top of a deposit's ledger for it to be
+    /// credited.
+    Confirmed(Deposit),
+    /// A payment arrived with a memo that wasn't expected, or with none.
+    Unmatched(IncomingPayment),
+}
+
+/// Turns the payments of an account into `DepositEvent`s.
+///
+/// The latest ledger is tracked from the ledgers payments arrive in and, when
+/// deposits are waiting for their confirmations, from horizon's root, which
+/// is checked each time the listener is advanced. A stream that is quiet
+/// doesn't advance the listener, so call `confirm_pending` on a timer to
+/// confirm deposits without waiting for the next payment.
+#[derive(Debug)]
+pub struct DepositListener<C, I> {
+    client: C,
+    account_id: AccountId,
+    payments: PaymentWatcher<C, I>,
+    expected: HashSet<String>,
+    confirmations: u32,
+    latest_ledger: u32,
+    pending: Vec<Deposit>,
+    ready: VecDeque<DepositEvent>,
+}
+
+impl<C, I> DepositListener<C, I>
+where
+    C: Requester + Clone,
+    I: Iterator<Item = Result<Operation>>,
+{
+    /// Listens for deposits among `payments`, usually a stream of
+    /// `account::Payments`, to `account_id`. Deposits are confirmed as soon
+    /// as they arrive until `with_confirmations` says otherwise.
+    pub fn new(client: C, account_id: AccountId, payments: I) -> Self {
+        DepositListener {
+            payments: PaymentWatcher::new(client.clone(), account_id.clone(), payments),
+            client,
+            account_id,
+            expected: HashSet::new(),
+            confirmations: 0,
+            latest_ledger: 0,
+            pending: Vec::new(),
+            ready: VecDeque::new(),
+        }
+    }
+
+    /// Waits until `confirmations` ledgers have closed on top of a deposit's
+    /// ledger before confirming it.
+    pub fn with_confirmations(mut self, confirmations: u32) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    /// Leaves out the payments that `filter` rejects, so that dust and spam
+    /// are neither deposits nor unmatched.
+    pub fn with_filter(mut self, filter: PaymentFilter) -> Self {
+        self.payments = self.payments.with_filter(filter);
+        self
+    }
+
+    /// Treats payments with `memo` as deposits. Memos are matched by their
+    /// text, so a text memo holding a user's number matches an id memo with
+    /// the same number.
+    pub fn expect(&mut self, memo: Memo) {
+        self.expected.insert(memo_text(&memo));
+    }
+
+    /// Stops treating payments with `memo` as deposits. Deposits already
+    /// received with it are still confirmed.
+    pub fn forget(&mut self, memo: &Memo) {
+        self.expected.remove(&memo_text(memo));
+    }
+
+    /// The number of deposits waiting for their confirmations.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Checks horizon for the latest ledger and confirms the deposits that
+    /// have waited long enough.
+    pub fn confirm_pending(&mut self) -> Result<Vec<Deposit>> {
+        if self.pending.is_empty() {
+            return Ok(Vec::new());
+        }
+        let latest = self.client.request(root::Details)?.history_latest_ledger();
+        self.latest_ledger = self.latest_ledger.max(latest);
+        self.confirm()
+    }
+
+    // Confirms the pending deposits that the latest ledger already known
+    // covers.
+    fn confirm(&mut self) -> Result<Vec<Deposit>> {
+        let covered = self.latest_ledger.saturating_sub(self.confirmations);
+        let (ready, waiting): (Vec<_>, Vec<_>) = self
+            .pending
+            .drain(..)
+            .partition(|deposit| deposit.payment.ledger() <= covered);
+        self.pending = waiting;
+        ready
+            .into_iter()
+            .map(|deposit| self.check_balance(deposit))
+            .collect()
+    }
+
+    // Fills in the balance change of a deposit from its transaction.
+    fn check_balance(&self, mut deposit: Deposit) -> Result<Deposit> {
+        let transaction = self.client.request(transaction::Details::new(
+            deposit.payment.transaction_hash(),
+        ))?;
+        deposit.balance_change = transaction.balance_changes().ok().map(|changes| {
+            changes
+                .iter()
+                .filter(|change| {
+                    !change.is_fee()
+                        && *change.account_id() == self.account_id
+                        && change.asset() == deposit.payment.asset()
+                })
+                .map(|change| change.delta())
+                .sum()
+        });
+        Ok(deposit)
+    }
+
+    fn receive(&mut self, payment: IncomingPayment) -> Result<()> {
+        self.latest_ledger = self.latest_ledger.max(payment.ledger());
+        if !self.expected.contains(&memo_text(payment.memo())) {
+            self.ready.push_back(DepositEvent::Unmatched(payment));
+            return Ok(());
+        }
+        let deposit = Deposit {
+            payment,
+            balance_change: None,
+        };
+        self.ready
+            .push_back(DepositEvent::Received(deposit.clone()));
+        self.pending.push(deposit);
+        let confirmed = self.confirm()?;
+        self.ready
+            .extend(confirmed.into_iter().map(DepositEvent::Confirmed));
+        Ok(())
+    }
+}
+
+impl<C, I> Iterator for DepositListener<C, I>
+where
+    C: Requester + Clone,
+    I: Iterator<Item = Result<Operation>>,
+{
+    type Item = Result<DepositEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.ready.pop_front() {
+                return Some(Ok(event));
+            }
+            match self.confirm_pending() {
+                Ok(ref confirmed) if !confirmed.is_empty() => {
+                    self.ready
+                        .extend(confirmed.iter().cloned().map(DepositEvent::Confirmed));
+                    continue;
+                }
+                Ok(_) => (),
+                Err(err) => return Some(Err(err)),
+            }
+            let result = match self.payments.next()? {
+                Ok(payment) => self.receive(payment),
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod deposit_listener_tests {
+    use super::*;
+    use serde_json;
+    use test::MockClient;
+
+    const ACCOUNT_ID: &str = "GCEZWKCA5VLDNRLN3RPRJMRZOX3Z6G5CHCGSNFHEYVXM3XOJMDS674JZ";
+    // The ledger of the transaction fixtures.
+    const LEDGER: u32 = 16_751_283;
+
+    fn payment() -> Operation {
+        serde_json::from_str(include_str!("../../fixtures/operations/payment.json")).unwrap()
+    }
+
+    fn root(history_latest_ledger: u32) -> String {
+        include_str!("../../fixtures/root.json").replace(
+            r#""history_latest_ledger": 1524298"#,
+            &format!(r#""history_latest_ledger": {}"#, history_latest_ledger),
+        )
+    }
+
+    fn client(history_latest_ledger: u32) -> MockClient {
+        MockClient::new()
+            .with_json(
+                "/transactions/*",
+                include_str!("../../fixtures/transactions/transaction_memo_text.json"),
+            )
+            .with_json("/", &root(history_latest_ledger))
+    }
+
+    fn kinds(events: &[DepositEvent]) -> Vec<&'static str> {
+        events
+            .iter()
+            .map(|event| match *event {
+                DepositEvent::Received(_) => "received",
+                DepositEvent::Confirmed(_) => "confirmed",
+                DepositEvent::Unmatched(_) => "unmatched",
+            })
+            .collect()
+    }
+
+    fn listen(client: &MockClient, confirmations: u32, memo: Memo) -> Vec<DepositEvent> {
+        let mut listener =
+            DepositListener::new(client, ACCOUNT_ID.into(), vec![Ok(payment())].into_iter())
+                .with_confirmations(confirmations);
+        listener.expect(memo);
+        listener.map(Result::unwrap).collect()
+    }
+
+    #[test]
+    fn it_confirms_deposits_right_away_by_default() {
+        let client = client(LEDGER);
+        let events = listen(&client, 0, Memo::Text("hello".to_string()));
+        assert_eq!(kinds(&events), vec!["received", "confirmed"]);
+        match events[1] {
+            DepositEvent::Confirmed(ref deposit) => {
+                assert_eq!(deposit.memo(), &Memo::Text("hello".to_string()));
+                assert_eq!(deposit.amount(), Amount::new(2_000_000_000));
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(client.hits("/"), 0);
+    }
+
+    #[test]
+    fn it_waits_for_confirmations() {
+        let client = client(LEDGER + 1);
+        let events = listen(&client, 2, Memo::Text("hello".to_string()));
+        assert_eq!(kinds(&events), vec!["received"]);
+        assert_eq!(client.hits("/"), 1);
+
+        let client = self::client(LEDGER + 2);
+        let events = listen(&client, 2, Memo::Text("hello".to_string()));
+        assert_eq!(kinds(&events), vec!["received", "confirmed"]);
+    }
+
+    #[test]
+    fn it_reports_payments_with_unexpected_memos() {
+        let client = client(LEDGER);
+        let events = listen(&client, 0, Memo::Id(1001));
+        assert_eq!(kinds(&events), vec!["unmatched"]);
+    }
+
+    #[test]
+    fn it_confirms_pending_deposits_when_asked() {
+        let client = client(LEDGER + 3);
+        let mut listener =
+            DepositListener::new(&client, ACCOUNT_ID.into(), vec![Ok(payment())].into_iter())
+                .with_confirmations(3);
+        assert!(listener.confirm_pending().unwrap().is_empty());
+        assert_eq!(client.hits("/"), 0);
+
+        listener.expect(Memo::Text("hello".to_string()));
+        assert_eq!(
+            kinds(&[listener.next().unwrap().unwrap()]),
+            vec!["received"]
+        );
+        assert_eq!(listener.pending(), 1);
+        let confirmed = listener.confirm_pending().unwrap();
+        assert_eq!(confirmed.len(), 1);
+        assert_eq!(confirmed[0].balance_change(), Some(0));
+        assert_eq!(listener.pending(), 0);
+    }
+
+    #[test]
+    fn it_matches_memos_by_their_text() {
+        let client = client(LEDGER);
+        let mut listener = DepositListener::new(&client, ACCOUNT_ID.into(), Vec::new().into_iter());
+        listener.expect(Memo::Id(42));
+        listener.forget(&Memo::Text("42".to_string()));
+        assert!(listener.expected.is_empty());
+    }
+}
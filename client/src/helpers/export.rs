@@ -0,0 +1,342 @@
+//! Exports the records of an endpoint a page at a time, in a way that can be
+//! picked up again after the process stops.
+//!
+//! Exporting the history of a busy account or of the whole network takes
+//! hours, and starting over after a crash or a deploy is not an option. An
+//! `Export` pages through an endpoint and hands out each page together with a
+//! `Checkpoint`: the request for the page after it, the paging token it
+//! continues from and the ledger that token falls in. Saving the checkpoint
+//! once the page has been written out, and starting the next run with
+//! `Export::resume`, carries on from the first page that wasn't written.
+//!
+//! Like the cursors in `helpers::cursor`, this gives at least once delivery: a
+//! crash after a page is written but before its checkpoint is saved writes
+//! that page again on the next run, but no page is ever skipped.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use stellar_client::endpoint::{payment, Direction, Limit, Order};
+//! use stellar_client::helpers::export::{Checkpoint, Export};
+//! use stellar_client::sync::Client;
+//!
+//! let client = Client::horizon_test().unwrap();
+//! let path = "payments.checkpoint";
+//! let export = match Checkpoint::load(path).unwrap() {
+//!     Some(checkpoint) => Export::resume(&client, &checkpoint).unwrap(),
+//!     None => Export::new(
+//!         &client,
+//!         payment::All::default()
+//!             .with_order(Direction::Asc)
+//!             .with_limit(200),
+//!     ).unwrap(),
+//! };
+//!
+//! for page in export {
+//!     let page = page.unwrap();
+//!     for payment in page.records() {
+//!         println!("{}", payment.paging_token());
+//!     }
+//!     page.checkpoint().save(path).unwrap();
+//!     println!("exported up to ledger {:?}", page.checkpoint().ledger());
+//! }
+//! ```
+use endpoint::{Cursor, IntoRequest, Records};
+use error::Result;
+use serde::de::DeserializeOwned;
+use serde_json;
+use std::fs::{self, File};
+use std::path::Path;
+use sync::Requester;
+use uri::TryFromUri;
+
+/// Only the path and query of an endpoint's request are kept, so any host
+/// will do when building it.
+const HOST: &str = "https://horizon.stellar.org";
+
+/// Where an export got to: enough to build the request for the next page in
+/// a later process.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    endpoint: String,
+    paging_token: Option<String>,
+    ledger: Option<u32>,
+}
+
+impl Checkpoint {
+    fn new<E>(endpoint: &E) -> Result<Checkpoint>
+    where
+        E: IntoRequest + Cursor + Clone,
+    {
+        let request = endpoint.clone().into_request(HOST)?;
+        let endpoint_uri = match request.uri().path_and_query() {
+            Some(path_and_query) => path_and_query.as_str().to_string(),
+            None => request.uri().path().to_string(),
+        };
+        let paging_token = endpoint.cursor().map(str::to_string);
+        Ok(Checkpoint {
+            endpoint: endpoint_uri,
+            ledger: paging_token.as_deref().and_then(ledger),
+            paging_token,
+        })
+    }
+
+    /// The path and query of the request for the next page, without a host,
+    /// so that an export can carry on against another horizon server.
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// The paging token the next page starts after, or `None` when nothing
+    /// has been exported yet and the endpoint had no cursor.
+    pub fn paging_token(&self) -> Option<&str> {
+        self.paging_token.as_deref()
+    }
+
+    /// The ledger the paging token falls in. Only the history endpoints,
+    /// such as transactions, operations, payments, effects and trades, have
+    /// paging tokens that say which ledger they're in, so this is `None` for
+    /// the others.
+    pub fn ledger(&self) -> Option<u32> {
+        self.ledger
+    }
+
+    /// Reads the checkpoint saved at `path`, or `None` when there isn't one.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Option<Checkpoint>> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_reader(File::open(path)?)?))
+    }
+
+    /// Saves the checkpoint at `path` as json. It is written to a temporary
+    /// file next to it and renamed into place, so a crash part way through
+    /// leaves the previous checkpoint intact.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let temp = path.with_extension("tmp");
+        serde_json::to_writer(File::create(&temp)?, self)?;
+        fs::rename(&temp, path)?;
+        Ok(())
+    }
+}
+
+/// The ledger of a paging token from one of the history endpoints. These are
+/// the id of an operation, optionally followed by a dash and an index, and
+/// the ledger sits in the top 32 bits of the id.
+fn ledger(paging_token: &str) -> Option<u32> {
+    let id = paging_token.split('-').next()?.parse::<u64>().ok()?;
+    match (id >> 32) as u32 {
+        0 => None,
+        ledger => Some(ledger),
+    }
+}
+
+/// A page of exported records and the checkpoint to save once they have been
+/// written out.
+#[derive(Debug)]
+pub struct Page<T> {
+    records: Vec<T>,
+    checkpoint: Checkpoint,
+}
+
+impl<T> Page<T> {
+    /// The records on the page, in the order horizon returned them.
+    pub fn records(&self) -> &[T] {
+        &self.records
+    }
+
+    /// Where to carry on from after this page.
+    pub fn checkpoint(&self) -> &Checkpoint {
+        &self.checkpoint
+    }
+
+    /// Takes the records off the page.
+    pub fn into_records(self) -> Vec<T> {
+        self.records
+    }
+}
+
+/// Pages through an endpoint, handing out each page with the checkpoint
+/// after it. The export ends at the last page horizon has.
+#[derive(Debug)]
+pub struct Export<'a, E, C>
+where
+    C: Requester + 'a,
+{
+    client: &'a C,
+    endpoint: Option<E>,
+    checkpoint: Checkpoint,
+}
+
+impl<'a, T, E, C> Export<'a, E, C>
+where
+    E: IntoRequest<Response = Records<T>> + TryFromUri + Cursor + Clone,
+    T: DeserializeOwned,
+    C: Requester,
+{
+    /// Starts an export of `endpoint` from its cursor, or from the start of
+    /// its records without one.
+    pub fn new(client: &'a C, endpoint: E) -> Result<Self> {
+        let checkpoint = Checkpoint::new(&endpoint)?;
+        Ok(Export {
+            client,
+            endpoint: Some(endpoint),
+            checkpoint,
+        })
+    }
+
+    /// Carries on an export from where `checkpoint` says it got to. The
+    /// checkpoint has to be of an endpoint of type `E`.
+    pub fn resume(client: &'a C, checkpoint: &Checkpoint) -> Result<Self> {
+        let uri = checkpoint.endpoint.parse()?;
+        Export::new(client, E::try_from(&uri)?)
+    }
+
+    /// The checkpoint after the last page handed out, or the one the export
+    /// started from before any were.
+    pub fn checkpoint(&self) -> &Checkpoint {
+        &self.checkpoint
+    }
+
+    fn fetch(&mut self, endpoint: E) -> Result<Option<Page<T>>> {
+        let records = self.client.request_page(endpoint)?;
+        if records.records().is_empty() {
+            return Ok(None);
+        }
+        // Horizon always links to the next page. Without a link there is
+        // nowhere to carry on from, so the export ends here like an `Iter`
+        // does.
+        if let Some(uri) = records.next() {
+            let next = E::try_from(uri)?;
+            self.checkpoint = Checkpoint::new(&next)?;
+            if !records.is_last_page() {
+                self.endpoint = Some(next);
+            }
+        }
+        Ok(Some(Page {
+            checkpoint: self.checkpoint.clone(),
+            records: records.into_iter().collect(),
+        }))
+    }
+}
+
+impl<'a, T, E, C> Iterator for Export<'a, E, C>
+where
+    E: IntoRequest<Response = Records<T>> + TryFromUri + Cursor + Clone,
+    T: DeserializeOwned,
+    C: Requester,
+{
+    type Item = Result<Page<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let endpoint = self.endpoint.take()?;
+        self.fetch(endpoint).transpose()
+    }
+}
+
+#[cfg(test)]
+mod export_tests {
+    use super::*;
+    use endpoint::{payment, Limit};
+    use resources::Operation;
+    use std::env;
+    use test::MockClient;
+
+    // A page of payments with the given paging tokens, linking to the page
+    // after the last of them.
+    fn page(tokens: &[&str]) -> String {
+        let payment = include_str!("../../fixtures/operations/payment.json");
+        let id = r#""paging_token": "58402965295104""#;
+        let records: Vec<String> = tokens
+            .iter()
+            .map(|token| payment.replace(id, &format!(r#""paging_token": "{}""#, token)))
+            .collect();
+        format!(
+            r#"{{
+                "_links": {{
+                    "self": {{ "href": "https://horizon.stellar.org/payments?cursor=&limit=2&order=asc" }},
+                    "next": {{ "href": "https://horizon.stellar.org/payments?cursor={}&limit=2&order=asc" }}
+                }},
+                "_embedded": {{ "records": [{}] }}
+            }}"#,
+            tokens.last().cloned().unwrap_or(""),
+            records.join(",")
+        )
+    }
+
+    fn client() -> MockClient {
+        MockClient::new()
+            .with_json("/payments?*cursor=12884905985*", &page(&["17179873281"]))
+            .with_json("/payments?*", &page(&["12884905984", "12884905985"]))
+    }
+
+    #[test]
+    fn it_hands_out_a_checkpoint_with_each_page() {
+        let client = client();
+        let endpoint = payment::All::default().with_limit(2);
+        let pages: Vec<Page<Operation>> = Export::new(&client, endpoint)
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].records().len(), 2);
+
+        let checkpoint = pages[0].checkpoint();
+        assert_eq!(
+            checkpoint.endpoint(),
+            "/payments?order=asc&cursor=12884905985&limit=2"
+        );
+        assert_eq!(checkpoint.paging_token(), Some("12884905985"));
+        assert_eq!(checkpoint.ledger(), Some(3));
+        assert_eq!(pages[1].checkpoint().ledger(), Some(4));
+
+        // The second page has fewer records than the limit, so the export
+        // stops without asking for a third.
+        assert_eq!(client.requests().len(), 2);
+    }
+
+    #[test]
+    fn it_resumes_from_a_checkpoint() {
+        let checkpoint = Checkpoint {
+            endpoint: "/payments?order=asc&cursor=12884905985&limit=2".to_string(),
+            paging_token: Some("12884905985".to_string()),
+            ledger: Some(3),
+        };
+        let client = client();
+        let mut export = Export::<payment::All, _>::resume(&client, &checkpoint).unwrap();
+        assert_eq!(*export.checkpoint(), checkpoint);
+
+        let page: Page<Operation> = export.next().unwrap().unwrap();
+        assert_eq!(page.records().len(), 1);
+        assert_eq!(page.checkpoint().paging_token(), Some("17179873281"));
+        assert!(export.next().is_none());
+        assert_eq!(
+            client.requests(),
+            vec!["/payments?order=asc&cursor=12884905985&limit=2"]
+        );
+    }
+
+    #[test]
+    fn it_reads_the_ledger_out_of_paging_tokens() {
+        assert_eq!(ledger("12884905984"), Some(3));
+        assert_eq!(ledger("12884905985-2"), Some(3));
+        assert_eq!(ledger("now"), None);
+        assert_eq!(ledger("42"), None);
+    }
+
+    #[test]
+    fn it_keeps_checkpoints_across_restarts() {
+        let path = env::temp_dir().join("stellar_client_export_checkpoint.json");
+        let _ = fs::remove_file(&path);
+        assert_eq!(Checkpoint::load(&path).unwrap(), None);
+
+        let checkpoint = Checkpoint::new(&payment::All::default().with_limit(2)).unwrap();
+        assert_eq!(checkpoint.endpoint(), "/payments?limit=2");
+        assert_eq!(checkpoint.paging_token(), None);
+        checkpoint.save(&path).unwrap();
+        assert_eq!(Checkpoint::load(&path).unwrap(), Some(checkpoint));
+        fs::remove_file(&path).unwrap();
+    }
+}
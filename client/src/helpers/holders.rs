@@ -0,0 +1,271 @@
+//! Counts the holders of an asset and describes how its supply is spread
+//! among them.
+//!
+//! Issuers reporting on a token want to know how many accounts hold it and
+//! whether a few of them hold most of it. `Census::take` pages through every
+//! account with a trustline to the asset and keeps each one's balance, from
+//! which the counts, the largest holders and percentiles of the balances can
+//! be read.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use stellar_client::helpers::holders::Census;
+//! use stellar_client::resources::AssetIdentifier;
+//! use stellar_client::sync::Client;
+//!
+//! let client = Client::horizon_test().unwrap();
+//! let usd = AssetIdentifier::alphanum4(
+//!     "USD",
+//!     "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG",
+//! ).unwrap();
+//! let census = Census::take(&client, usd).unwrap();
+//! println!(
+//!     "{} trustlines, {} funded, {} in circulation",
+//!     census.holders(),
+//!     census.funded(),
+//!     census.total(),
+//! );
+//! println!("median balance {:?}", census.percentile(50.0));
+//! for holder in census.top(10) {
+//!     println!("{} holds {}", holder.account_id(), holder.balance());
+//! }
+//! println!("the top 10 hold {} basis points", census.top_share(10));
+//! ```
+use endpoint::{account, Limit};
+use error::Result;
+use resources::{Account, AccountId, Amount, AssetIdentifier};
+use sync::{Iter, Requester};
+
+/// The number of accounts asked for in each page.
+const PAGE_SIZE: u32 = 200;
+
+/// An account with a trustline to the asset, and its balance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Holder {
+    account_id: AccountId,
+    balance: Amount,
+}
+
+impl Holder {
+    /// The account holding the asset.
+    pub fn account_id(&self) -> &AccountId {
+        &self.account_id
+    }
+
+    /// How much of the asset the account holds.
+    pub fn balance(&self) -> Amount {
+        self.balance
+    }
+}
+
+/// The holders of an asset at the time they were counted.
+#[derive(Debug, Clone)]
+pub struct Census {
+    asset: AssetIdentifier,
+    // Largest balance first.
+    holders: Vec<Holder>,
+}
+
+impl Census {
+    /// Pages through the accounts with a trustline to `asset` and counts
+    /// them. Horizon can't list the holders of lumens, so the asset has to
+    /// be one that is issued.
+    pub fn take<C>(client: &C, asset: AssetIdentifier) -> Result<Census>
+    where
+        C: Requester,
+    {
+        let endpoint = account::All::default()
+            .with_asset(asset.clone())
+            .with_limit(PAGE_SIZE);
+        let accounts = Iter::new(client, endpoint).collect::<Result<Vec<Account>>>()?;
+        Ok(Census::from_accounts(asset, &accounts))
+    }
+
+    /// Counts the holders of `asset` among `accounts`. Accounts without a
+    /// trustline to the asset are left out.
+    pub fn from_accounts(asset: AssetIdentifier, accounts: &[Account]) -> Census {
+        let mut holders: Vec<Holder> = accounts
+            .iter()
+            .filter_map(|account| {
+                account.balance(&asset).map(|balance| Holder {
+                    account_id: account.id().clone(),
+                    balance: balance.balance(),
+                })
+            })
+            .collect();
+        holders.sort_by(|a, b| {
+            b.balance
+                .cmp(&a.balance)
+                .then_with(|| a.account_id.cmp(&b.account_id))
+        });
+        Census { asset, holders }
+    }
+
+    /// The asset that was counted.
+    pub fn asset(&self) -> &AssetIdentifier {
+        &self.asset
+    }
+
+    /// The number of accounts with a trustline to the asset, whether or not
+    /// they hold any of it.
+    pub fn holders(&self) -> usize {
+        self.holders.len()
+    }
+
+    /// The number of accounts that hold some of the asset.
+    pub fn funded(&self) -> usize {
+        self.holders
+            .iter()
+            .take_while(|holder| holder.balance > Amount::new(0))
+            .count()
+    }
+
+    /// The sum of every holder's balance, which is the amount of the asset
+    /// outside of the issuer's account.
+    pub fn total(&self) -> Amount {
+        Amount::new(self.stroops(self.holders.len()))
+    }
+
+    /// The average balance across all the accounts with a trustline, or
+    /// `None` when there are none.
+    pub fn mean(&self) -> Option<Amount> {
+        match self.holders.len() as i64 {
+            0 => None,
+            count => Some(Amount::new(self.stroops(self.holders.len()) / count)),
+        }
+    }
+
+    /// The balance below which `percent` of the holders fall, by the
+    /// nearest rank, so that `percentile(50.0)` is the median and
+    /// `percentile(100.0)` the largest balance. `None` when there are no
+    /// holders.
+    pub fn percentile(&self, percent: f64) -> Option<Amount> {
+        if self.holders.is_empty() {
+            return None;
+        }
+        let count = self.holders.len();
+        let rank = (percent.clamp(0.0, 100.0) / 100.0 * count as f64).ceil() as usize;
+        // The holders are largest first, so count the rank from the end.
+        Some(self.holders[count - rank.max(1)].balance)
+    }
+
+    /// The `n` holders with the largest balances, largest first.
+    pub fn top(&self, n: usize) -> &[Holder] {
+        &self.holders[..n.min(self.holders.len())]
+    }
+
+    /// The share of the total held by the `n` largest holders, in basis
+    /// points. Zero when nobody holds any of the asset.
+    pub fn top_share(&self, n: usize) -> u32 {
+        match self.stroops(self.holders.len()) {
+            0 => 0,
+            total => (self.stroops(n) as i128 * 10_000 / total as i128) as u32,
+        }
+    }
+
+    /// Every holder, largest balance first.
+    pub fn iter(&self) -> impl Iterator<Item = &Holder> {
+        self.holders.iter()
+    }
+
+    // The sum of the balances of the `n` largest holders, in stroops.
+    fn stroops(&self, n: usize) -> i64 {
+        self.top(n)
+            .iter()
+            .map(|holder| holder.balance.to_stroops())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod census_tests {
+    use super::*;
+    use resources::{AccountBuilder, Balance};
+    use test::MockClient;
+
+    const ISSUER: &str = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4";
+    const ACCOUNTS: [&str; 4] = [
+        "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG",
+        "GAKLBGHNHFQ3BMUYG5KU4BEWO6EYQHZHAXEWC33W34PH2RBHZDSQBD75",
+        "GCEZWKCA5VLDNRLN3RPRJMRZOX3Z6G5CHCGSNFHEYVXM3XOJMDS674JZ",
+        "GDSBCQO34HWPGUGQSP3QBFEXVTSR2PW46UIGTHVWGWJGQKH3AFNHXHXN",
+    ];
+
+    fn foo() -> AssetIdentifier {
+        AssetIdentifier::alphanum4("FOO", ISSUER).unwrap()
+    }
+
+    fn account(account_id: &str, balance: i64) -> Account {
+        AccountBuilder::new(account_id.into())
+            .with_balance(Balance::new(Amount::new(balance), None, foo()))
+            .build()
+    }
+
+    fn census() -> Census {
+        Census::from_accounts(
+            foo(),
+            &[
+                account(ACCOUNTS[0], 100),
+                account(ACCOUNTS[1], 0),
+                account(ACCOUNTS[2], 700),
+                account(ACCOUNTS[3], 200),
+                AccountBuilder::new(ISSUER.into()).build(),
+            ],
+        )
+    }
+
+    #[test]
+    fn it_counts_the_holders() {
+        let census = census();
+        assert_eq!(census.holders(), 4);
+        assert_eq!(census.funded(), 3);
+        assert_eq!(census.total(), Amount::new(1_000));
+        assert_eq!(census.mean(), Some(Amount::new(250)));
+    }
+
+    #[test]
+    fn it_ranks_the_largest_holders() {
+        let census = census();
+        let top: Vec<_> = census.top(2).iter().map(Holder::balance).collect();
+        assert_eq!(top, vec![Amount::new(700), Amount::new(200)]);
+        assert_eq!(*census.top(2)[0].account_id(), ACCOUNTS[2]);
+        assert_eq!(census.top(10).len(), 4);
+        assert_eq!(census.top_share(1), 7_000);
+        assert_eq!(census.top_share(2), 9_000);
+    }
+
+    #[test]
+    fn it_finds_percentiles_by_nearest_rank() {
+        let census = census();
+        assert_eq!(census.percentile(0.0), Some(Amount::new(0)));
+        assert_eq!(census.percentile(25.0), Some(Amount::new(0)));
+        assert_eq!(census.percentile(50.0), Some(Amount::new(100)));
+        assert_eq!(census.percentile(75.0), Some(Amount::new(200)));
+        assert_eq!(census.percentile(100.0), Some(Amount::new(700)));
+    }
+
+    #[test]
+    fn it_has_no_statistics_without_holders() {
+        let census = Census::from_accounts(foo(), &[]);
+        assert_eq!(census.holders(), 0);
+        assert_eq!(census.mean(), None);
+        assert_eq!(census.percentile(50.0), None);
+        assert_eq!(census.top_share(1), 0);
+    }
+
+    #[test]
+    fn it_pages_through_the_accounts_holding_the_asset() {
+        let client = MockClient::new().with_json(
+            "/accounts?*",
+            &format!(
+                r#"{{ "_embedded": {{ "records": [{}] }} }}"#,
+                include_str!("../../fixtures/account.json")
+            ),
+        );
+        let census = Census::take(&client, foo()).unwrap();
+        assert_eq!(census.holders(), 1);
+        assert_eq!(census.total(), Amount::new(10_000_000_000));
+        client.assert_requested(&format!("/accounts?asset=FOO:{}&limit=200", ISSUER));
+    }
+}
@@ -0,0 +1,639 @@
+//! Plans the transactions that issue a new asset with the usual pair of
+//! accounts: an issuing account that creates the asset and a distribution
+//! account that holds the supply and hands it out.
+//!
+//! Issuing takes a few steps that have to happen in order and are easy to
+//! get wrong. The issuer's flags must be set before anyone trusts the asset,
+//! the distributor has to trust the asset before it can be paid, and an
+//! issuer that is locked can never change its flags or mint again. The plan
+//! lays the steps out as transactions, each with the account that has to
+//! sign it, so that they can be reviewed before anything is submitted.
+//! With the `keys` feature enabled, `IssuancePlan::submit` then builds, signs
+//! and submits them in order.
+//!
+//! # Examples
+//!
+//! ```
+//! use stellar_client::helpers::issuance::AssetIssuer;
+//! use stellar_client::resources::{Amount, AssetIdentifier, Flags};
+//!
+//! let asset = AssetIdentifier::alphanum4(
+//!     "FOO",
+//!     "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG",
+//! ).unwrap();
+//! let plan = AssetIssuer::new(
+//!     asset,
+//!     "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4".into(),
+//!     Amount::new(10_000_000_000),
+//! ).with_flags(Flags::new(false, true))
+//!     .with_locked_issuer()
+//!     .plan()
+//!     .unwrap();
+//!
+//! // The issuer sets its flags, the distributor trusts the asset, and the
+//! // issuer mints the supply and locks itself.
+//! assert_eq!(plan.transactions().len(), 3);
+//! println!("{}", plan);
+//! ```
+#[cfg(feature = "keys")]
+use super::transaction;
+#[cfg(feature = "keys")]
+use error;
+#[cfg(feature = "keys")]
+use keys::Signer;
+#[cfg(feature = "keys")]
+use network::Network;
+#[cfg(feature = "keys")]
+use resources::envelope::{MuxedAccount, Operation, OperationBody};
+#[cfg(feature = "keys")]
+use resources::SubmittedTransaction;
+use resources::{AccountId, Amount, AssetIdentifier, Flags};
+use std::error::Error as StdError;
+use std::fmt;
+#[cfg(feature = "keys")]
+use sync::Requester;
+
+#[cfg(feature = "keys")]
+const AUTH_REQUIRED_FLAG: u32 = 1;
+#[cfg(feature = "keys")]
+const AUTH_REVOCABLE_FLAG: u32 = 2;
+
+/// Describes an asset to issue and plans the transactions that issue it.
+#[derive(Debug, Clone)]
+pub struct AssetIssuer {
+    asset: AssetIdentifier,
+    distributor: AccountId,
+    supply: Amount,
+    flags: Flags,
+    home_domain: Option<String>,
+    limit: Option<Amount>,
+    lock: bool,
+}
+
+impl AssetIssuer {
+    /// Starts describing the issue of `supply` units of `asset` to the
+    /// `distributor`. The issuer is the asset's issuer. No flags are set and
+    /// the issuer is left unlocked.
+    pub fn new(asset: AssetIdentifier, distributor: AccountId, supply: Amount) -> AssetIssuer {
+        AssetIssuer {
+            asset,
+            distributor,
+            supply,
+            flags: Flags::new(false, false),
+            home_domain: None,
+            limit: None,
+            lock: false,
+        }
+    }
+
+    /// Sets the flags on the issuer that control who may hold the asset and
+    /// whether it can be frozen.
+    pub fn with_flags(mut self, flags: Flags) -> AssetIssuer {
+        self.flags = flags;
+        self
+    }
+
+    /// Sets the issuer's home domain, where its stellar.toml describes the
+    /// asset.
+    pub fn with_home_domain(mut self, home_domain: &str) -> AssetIssuer {
+        self.home_domain = Some(home_domain.to_string());
+        self
+    }
+
+    /// Caps the distributor's trust line at `limit`. By default it is as
+    /// large as a trust line can be.
+    pub fn with_trust_limit(mut self, limit: Amount) -> AssetIssuer {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Locks the issuer once the supply is minted by dropping the weight of
+    /// its master key to zero, so that no more of the asset can ever be
+    /// made.
+    pub fn with_locked_issuer(mut self) -> AssetIssuer {
+        self.lock = true;
+        self
+    }
+
+    /// Lays out the transactions that issue the asset, in the order they
+    /// need to be submitted. Setting the issuer's options is left out when
+    /// there are none to set.
+    pub fn plan(&self) -> Result<IssuancePlan, IssuanceError> {
+        let issuer = match self.asset.issuer() {
+            Some(issuer) => issuer.clone(),
+            None => return Err(IssuanceError::NativeAsset),
+        };
+        if issuer == self.distributor {
+            return Err(IssuanceError::SameAccount);
+        }
+        if self.supply <= Amount::new(0) {
+            return Err(IssuanceError::NoSupply);
+        }
+        // A locked issuer could never authorize anyone else to hold it.
+        if self.lock && self.flags.is_auth_required() {
+            return Err(IssuanceError::LockedWithAuthRequired);
+        }
+
+        let mut transactions = Vec::new();
+        let flags = self.flags.is_auth_required() || self.flags.is_auth_revocable();
+        if flags || self.home_domain.is_some() {
+            transactions.push(PlannedTransaction {
+                source: issuer.clone(),
+                steps: vec![IssuanceStep::ConfigureIssuer {
+                    flags: self.flags,
+                    home_domain: self.home_domain.clone(),
+                }],
+            });
+        }
+        transactions.push(PlannedTransaction {
+            source: self.distributor.clone(),
+            steps: vec![IssuanceStep::Trust {
+                asset: self.asset.clone(),
+                limit: self.limit,
+            }],
+        });
+
+        let mut steps = Vec::new();
+        if self.flags.is_auth_required() {
+            steps.push(IssuanceStep::Authorize {
+                trustor: self.distributor.clone(),
+                asset: self.asset.clone(),
+            });
+        }
+        steps.push(IssuanceStep::Mint {
+            destination: self.distributor.clone(),
+            asset: self.asset.clone(),
+            amount: self.supply,
+        });
+        if self.lock {
+            steps.push(IssuanceStep::LockIssuer);
+        }
+        transactions.push(PlannedTransaction {
+            source: issuer,
+            steps,
+        });
+
+        Ok(IssuancePlan {
+            asset: self.asset.clone(),
+            transactions,
+        })
+    }
+}
+
+/// The transactions that issue an asset, in the order they need to be
+/// submitted. Displays as a numbered list of the transactions for review.
+#[derive(Debug, Clone)]
+pub struct IssuancePlan {
+    asset: AssetIdentifier,
+    transactions: Vec<PlannedTransaction>,
+}
+
+impl IssuancePlan {
+    /// The asset being issued.
+    pub fn asset(&self) -> &AssetIdentifier {
+        &self.asset
+    }
+
+    /// The transactions to submit, in order. Each one has to land before
+    /// the next is submitted.
+    pub fn transactions(&self) -> &Vec<PlannedTransaction> {
+        &self.transactions
+    }
+
+    /// Builds each transaction of the plan, has `issuer` or `distributor`
+    /// sign it depending on which account it is from, and submits it for
+    /// `network`, waiting for each one to land before the next. A failure
+    /// stops the plan part way, with the transactions before it applied.
+    ///
+    /// Only available with the `keys` feature enabled.
+    #[cfg(feature = "keys")]
+    pub fn submit<C, I, D>(
+        &self,
+        client: &C,
+        network: &Network,
+        issuer: &I,
+        distributor: &D,
+    ) -> error::Result<Vec<SubmittedTransaction>>
+    where
+        C: Requester + ?Sized,
+        I: Signer,
+        D: Signer,
+    {
+        let mut submitted = Vec::new();
+        for planned in &self.transactions {
+            let signer: &dyn Signer = if planned.source == distributor.account_id() {
+                distributor
+            } else {
+                issuer
+            };
+            submitted.push(transaction::submit_operations(
+                client,
+                network,
+                &planned.source,
+                planned.operations(),
+                &[signer],
+            )?);
+        }
+        Ok(submitted)
+    }
+}
+
+impl fmt::Display for IssuancePlan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, transaction) in self.transactions.iter().enumerate() {
+            writeln!(f, "{}. {}", index + 1, transaction)?;
+        }
+        Ok(())
+    }
+}
+
+/// A transaction in an issuance plan. Its source account pays the fee and
+/// has to sign it.
+#[derive(Debug, Clone)]
+pub struct PlannedTransaction {
+    source: AccountId,
+    steps: Vec<IssuanceStep>,
+}
+
+impl PlannedTransaction {
+    /// The account the transaction is from.
+    pub fn source(&self) -> &AccountId {
+        &self.source
+    }
+
+    /// The steps of the transaction, one operation each.
+    pub fn steps(&self) -> &Vec<IssuanceStep> {
+        &self.steps
+    }
+
+    #[cfg(feature = "keys")]
+    fn operations(&self) -> Vec<Operation> {
+        self.steps
+            .iter()
+            .map(|step| Operation::new(step.operation()))
+            .collect()
+    }
+}
+
+impl fmt::Display for PlannedTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "from {}: ", self.source)?;
+        for (index, step) in self.steps.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", then ")?;
+            }
+            write!(f, "{}", step)?;
+        }
+        Ok(())
+    }
+}
+
+/// A single operation in an issuance plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IssuanceStep {
+    /// A set options operation that sets the issuer's flags and home
+    /// domain.
+    ConfigureIssuer {
+        /// The flags to set.
+        flags: Flags,
+        /// The home domain to set, if any.
+        home_domain: Option<String>,
+    },
+    /// A change trust operation from the distributor.
+    Trust {
+        /// The asset to trust.
+        asset: AssetIdentifier,
+        /// The limit of the trust line, or `None` for the largest there is.
+        limit: Option<Amount>,
+    },
+    /// An allow trust operation that authorizes the distributor to hold the
+    /// asset, needed when the issuer requires authorization.
+    Authorize {
+        /// The account being authorized.
+        trustor: AccountId,
+        /// The asset it may hold.
+        asset: AssetIdentifier,
+    },
+    /// A payment from the issuer, which creates the asset.
+    Mint {
+        /// The account that receives the supply.
+        destination: AccountId,
+        /// The asset being minted.
+        asset: AssetIdentifier,
+        /// How much is minted.
+        amount: Amount,
+    },
+    /// A set options operation that sets the weight of the issuer's master
+    /// key to zero.
+    LockIssuer,
+}
+
+impl fmt::Display for IssuanceStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IssuanceStep::ConfigureIssuer {
+                ref flags,
+                ref home_domain,
+            } => {
+                let mut set = Vec::new();
+                if flags.is_auth_required() {
+                    set.push("auth required".to_string());
+                }
+                if flags.is_auth_revocable() {
+                    set.push("auth revocable".to_string());
+                }
+                if let Some(ref domain) = *home_domain {
+                    set.push(format!("home domain {}", domain));
+                }
+                write!(f, "set {}", set.join(" and "))
+            }
+            IssuanceStep::Trust { ref asset, limit } => {
+                write!(f, "trust {}", describe(asset))?;
+                match limit {
+                    Some(limit) => write!(f, " up to {}", limit),
+                    None => f.write_str(" with no limit"),
+                }
+            }
+            IssuanceStep::Authorize {
+                ref trustor,
+                ref asset,
+            } => write!(f, "authorize {} to hold {}", trustor, describe(asset)),
+            IssuanceStep::Mint {
+                ref destination,
+                ref asset,
+                amount,
+            } => write!(f, "pay {} {} to {}", amount, describe(asset), destination),
+            IssuanceStep::LockIssuer => f.write_str("lock the issuer"),
+        }
+    }
+}
+
+#[cfg(feature = "keys")]
+impl IssuanceStep {
+    // The operation that carries out the step.
+    fn operation(&self) -> OperationBody {
+        match *self {
+            IssuanceStep::ConfigureIssuer {
+                ref flags,
+                ref home_domain,
+            } => {
+                let mut set_flags = 0;
+                if flags.is_auth_required() {
+                    set_flags |= AUTH_REQUIRED_FLAG;
+                }
+                if flags.is_auth_revocable() {
+                    set_flags |= AUTH_REVOCABLE_FLAG;
+                }
+                OperationBody::SetOptions {
+                    inflation_dest: None,
+                    clear_flags: None,
+                    set_flags: Some(set_flags).filter(|flags| *flags != 0),
+                    master_weight: None,
+                    low_threshold: None,
+                    med_threshold: None,
+                    high_threshold: None,
+                    home_domain: home_domain.clone(),
+                    signer: None,
+                }
+            }
+            IssuanceStep::Trust { ref asset, limit } => OperationBody::ChangeTrust {
+                line: Some(asset.clone()),
+                limit: limit.unwrap_or_else(|| Amount::new(i64::MAX)),
+            },
+            IssuanceStep::Authorize {
+                ref trustor,
+                ref asset,
+            } => OperationBody::AllowTrust {
+                trustor: trustor.clone(),
+                asset_code: asset.code().unwrap_or_default().to_string(),
+                authorize: 1,
+            },
+            IssuanceStep::Mint {
+                ref destination,
+                ref asset,
+                amount,
+            } => OperationBody::Payment {
+                destination: MuxedAccount::new(destination, None),
+                asset: asset.clone(),
+                amount,
+            },
+            IssuanceStep::LockIssuer => OperationBody::SetOptions {
+                inflation_dest: None,
+                clear_flags: None,
+                set_flags: None,
+                master_weight: Some(0),
+                low_threshold: None,
+                med_threshold: None,
+                high_threshold: None,
+                home_domain: None,
+                signer: None,
+            },
+        }
+    }
+}
+
+fn describe(asset: &AssetIdentifier) -> String {
+    match (asset.code(), asset.issuer()) {
+        (Some(code), Some(issuer)) => format!("{}-{}", code, issuer),
+        _ => "XLM".to_string(),
+    }
+}
+
+/// Why an asset can't be issued as described.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssuanceError {
+    /// Lumens can't be issued.
+    NativeAsset,
+    /// The distributor is the issuer, which can't hold its own asset.
+    SameAccount,
+    /// The supply is zero or less.
+    NoSupply,
+    /// The issuer would be locked while requiring authorization, so nobody
+    /// else could ever be authorized to hold the asset.
+    LockedWithAuthRequired,
+}
+
+impl fmt::Display for IssuanceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+impl StdError for IssuanceError {
+    fn description(&self) -> &str {
+        match *self {
+            IssuanceError::NativeAsset => "Lumens can't be issued",
+            IssuanceError::SameAccount => "The distributor can't be the issuer",
+            IssuanceError::NoSupply => "The supply must be more than zero",
+            IssuanceError::LockedWithAuthRequired => {
+                "An issuer that requires authorization can't be locked"
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod asset_issuer_tests {
+    use super::*;
+
+    const ISSUER: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+    const DISTRIBUTOR: &str = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4";
+
+    fn foo() -> AssetIdentifier {
+        AssetIdentifier::alphanum4("FOO", ISSUER).unwrap()
+    }
+
+    fn issuer() -> AssetIssuer {
+        AssetIssuer::new(foo(), DISTRIBUTOR.into(), Amount::new(10_000_000_000))
+    }
+
+    #[test]
+    fn it_trusts_then_mints_without_options() {
+        let plan = issuer().plan().unwrap();
+        let transactions = plan.transactions();
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0].source(), DISTRIBUTOR);
+        assert_eq!(
+            transactions[0].steps(),
+            &vec![IssuanceStep::Trust {
+                asset: foo(),
+                limit: None,
+            }]
+        );
+        assert_eq!(transactions[1].source(), ISSUER);
+        assert_eq!(
+            transactions[1].steps(),
+            &vec![IssuanceStep::Mint {
+                destination: DISTRIBUTOR.into(),
+                asset: foo(),
+                amount: Amount::new(10_000_000_000),
+            }]
+        );
+    }
+
+    #[test]
+    fn it_configures_authorizes_and_locks_in_order() {
+        let plan = issuer()
+            .with_flags(Flags::new(true, true))
+            .with_home_domain("example.com")
+            .with_trust_limit(Amount::new(20_000_000_000))
+            .plan()
+            .unwrap();
+        assert_eq!(
+            plan.to_string(),
+            format!(
+                "1. from {issuer}: set auth required and auth revocable and home domain example.com\n\
+                 2. from {distributor}: trust FOO-{issuer} up to 2000.0000000\n\
+                 3. from {issuer}: authorize {distributor} to hold FOO-{issuer}, then pay 1000.0000000 FOO-{issuer} to {distributor}\n",
+                issuer = ISSUER,
+                distributor = DISTRIBUTOR
+            )
+        );
+
+        let plan = issuer()
+            .with_flags(Flags::new(false, true))
+            .with_locked_issuer()
+            .plan()
+            .unwrap();
+        let last = &plan.transactions()[2];
+        assert_eq!(last.steps().len(), 2);
+        assert_eq!(last.steps()[1], IssuanceStep::LockIssuer);
+    }
+
+    #[test]
+    fn it_refuses_plans_that_cant_work() {
+        let native = AssetIssuer::new(
+            AssetIdentifier::native(),
+            DISTRIBUTOR.into(),
+            Amount::new(1),
+        );
+        assert_eq!(native.plan().unwrap_err(), IssuanceError::NativeAsset);
+        let same = AssetIssuer::new(foo(), ISSUER.into(), Amount::new(1));
+        assert_eq!(same.plan().unwrap_err(), IssuanceError::SameAccount);
+        let empty = AssetIssuer::new(foo(), DISTRIBUTOR.into(), Amount::new(0));
+        assert_eq!(empty.plan().unwrap_err(), IssuanceError::NoSupply);
+        let locked = issuer()
+            .with_flags(Flags::new(true, false))
+            .with_locked_issuer();
+        assert_eq!(
+            locked.plan().unwrap_err(),
+            IssuanceError::LockedWithAuthRequired
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "keys")]
+    fn it_submits_each_transaction_signed_by_its_source() {
+        use keys::Keypair;
+        use test::MockClient;
+
+        let client = MockClient::new()
+            .with_json("/accounts/*", include_str!("../../fixtures/account.json"))
+            .with_json(
+                "/transactions",
+                include_str!("../../fixtures/submitted_transaction.json"),
+            );
+        let plan = issuer()
+            .with_flags(Flags::new(true, true))
+            .with_home_domain("example.com")
+            .plan()
+            .unwrap();
+        let issuer = Keypair::random().unwrap();
+        let distributor = Keypair::random().unwrap();
+        let submitted = plan
+            .submit(&client, &Network::test(), &issuer, &distributor)
+            .unwrap();
+        assert_eq!(submitted.len(), 3);
+        assert_eq!(client.hits("/accounts/*"), 3);
+        assert_eq!(client.hits("/transactions"), 3);
+    }
+
+    #[test]
+    #[cfg(feature = "keys")]
+    fn it_turns_each_step_into_its_operation() {
+        let plan = issuer()
+            .with_flags(Flags::new(false, true))
+            .with_home_domain("example.com")
+            .with_locked_issuer()
+            .plan()
+            .unwrap();
+        let operations: Vec<_> = plan
+            .transactions()
+            .iter()
+            .flat_map(|transaction| transaction.operations())
+            .map(|operation| operation.body().clone())
+            .collect();
+        match operations[0] {
+            OperationBody::SetOptions {
+                set_flags,
+                ref home_domain,
+                ..
+            } => {
+                assert_eq!(set_flags, Some(AUTH_REVOCABLE_FLAG));
+                assert_eq!(home_domain.as_ref().unwrap(), "example.com");
+            }
+            ref other => panic!("expected set options, got {:?}", other),
+        }
+        assert_eq!(
+            operations[1],
+            OperationBody::ChangeTrust {
+                line: Some(foo()),
+                limit: Amount::new(i64::MAX),
+            }
+        );
+        assert_eq!(
+            operations[2],
+            OperationBody::Payment {
+                destination: MuxedAccount::new(&DISTRIBUTOR.into(), None),
+                asset: foo(),
+                amount: Amount::new(10_000_000_000),
+            }
+        );
+        match operations[3] {
+            OperationBody::SetOptions { master_weight, .. } => {
+                assert_eq!(master_weight, Some(0))
+            }
+            ref other => panic!("expected set options, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,422 @@
+//! Turns effects into double-entry journal entries for accounting systems.
+//!
+//! Every operation that moves funds becomes one journal entry. The holdings
+//! of a stellar account are treated as asset accounts in the books, so funds
+//! arriving at an account debit it and funds leaving an account credit it.
+//! Horizon reports both sides of every movement as effects, so the entry of
+//! an operation balances for each asset: a payment debits the receiver and
+//! credits the sender, and a trade moves both assets between the two
+//! parties. Fees are not effects, so `fee_entry` books the fee of a
+//! transaction against the network's fee pool.
+//!
+//! The effects of an operation always arrive together and in order, whether
+//! they are paged or streamed. A `Journal` can be fed effects as they stream
+//! in and hands back the entry of each operation once the effects of the next
+//! one begin.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use stellar_client::endpoint::account;
+//! use stellar_client::helpers::journal::Journal;
+//! use stellar_client::sync::Client;
+//!
+//! let client = Client::horizon_test().unwrap();
+//! let account_id = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into();
+//! let mut journal = Journal::new();
+//! for effect in client.stream(account::Effects::new(account_id)).unwrap() {
+//!     if let Some(entry) = journal.push(&effect.unwrap()) {
+//!         for line in entry.lines() {
+//!             println!("{:?} {:?} {} {:?}", line.side(), line.book(), line.amount(), line.asset());
+//!         }
+//!     }
+//! }
+//! ```
+use chrono::{DateTime, Utc};
+use resources::effect::{account, trade, EffectKind};
+use resources::{AccountId, Amount, AssetIdentifier, Effect, Transaction};
+use std::collections::HashMap;
+
+/// The bits of an operation id that hold the operation's index within its
+/// transaction. Clearing them gives the id of the transaction.
+const OPERATION_INDEX_MASK: i64 = 0xFFF;
+
+/// Which side of an entry a line is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    /// Funds arriving at the book.
+    Debit,
+    /// Funds leaving the book.
+    Credit,
+}
+
+/// The book a journal line is posted to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Book {
+    /// The holdings of a stellar account.
+    Account(AccountId),
+    /// The pool that the network collects transaction fees into.
+    FeePool,
+}
+
+/// A single debit or credit of an amount of an asset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalLine {
+    side: Side,
+    book: Book,
+    asset: AssetIdentifier,
+    amount: Amount,
+}
+
+impl JournalLine {
+    /// Creates a line posting `amount` of `asset` to one side of `book`.
+    pub fn new(side: Side, book: Book, asset: AssetIdentifier, amount: Amount) -> JournalLine {
+        JournalLine {
+            side,
+            book,
+            asset,
+            amount,
+        }
+    }
+
+    /// Whether the line is a debit or a credit.
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    /// The book the line is posted to.
+    pub fn book(&self) -> &Book {
+        &self.book
+    }
+
+    /// The asset that moved.
+    pub fn asset(&self) -> &AssetIdentifier {
+        &self.asset
+    }
+
+    /// The amount that moved.
+    pub fn amount(&self) -> Amount {
+        self.amount
+    }
+}
+
+/// The lines of a single operation, or of the fee of a single transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalEntry {
+    transaction_id: i64,
+    operation_id: Option<i64>,
+    transaction_hash: Option<String>,
+    created_at: DateTime<Utc>,
+    lines: Vec<JournalLine>,
+}
+
+impl JournalEntry {
+    /// The id of the transaction the entry belongs to. It is the paging
+    /// token horizon lists the transaction by.
+    pub fn transaction_id(&self) -> i64 {
+        self.transaction_id
+    }
+
+    /// The id of the operation the entry was made from. Fee entries belong
+    /// to the whole transaction and have none.
+    pub fn operation_id(&self) -> Option<i64> {
+        self.operation_id
+    }
+
+    /// The hash of the transaction, when it was known. Entries made from
+    /// effects only know the transaction by its id.
+    pub fn transaction_hash(&self) -> Option<&str> {
+        self.transaction_hash.as_ref().map(String::as_str)
+    }
+
+    /// The time the ledger containing the transaction closed.
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    /// The debits and credits of the entry, in the order of the effects
+    /// they were made from.
+    pub fn lines(&self) -> &[JournalLine] {
+        &self.lines
+    }
+
+    /// Whether the debits equal the credits for every asset.
+    pub fn is_balanced(&self) -> bool {
+        let mut totals: HashMap<&AssetIdentifier, i64> = HashMap::new();
+        for line in &self.lines {
+            let stroops = match line.side {
+                Side::Debit => line.amount.to_stroops(),
+                Side::Credit => -line.amount.to_stroops(),
+            };
+            *totals.entry(&line.asset).or_insert(0) += stroops;
+        }
+        totals.values().all(|total| *total == 0)
+    }
+}
+
+/// The journal lines for a single effect. Account creation, credits, debits
+/// and trades move funds, and every other effect has no lines.
+pub fn lines(effect: &Effect) -> Vec<JournalLine> {
+    let line = |side, account: &AccountId, asset: &AssetIdentifier, amount| {
+        JournalLine::new(side, Book::Account(account.clone()), asset.clone(), amount)
+    };
+    match *effect.kind() {
+        EffectKind::Account(account::Kind::Created(ref created)) => vec![line(
+            Side::Debit,
+            created.account(),
+            &AssetIdentifier::native(),
+            created.starting_balance(),
+        )],
+        EffectKind::Account(account::Kind::Credited(ref credited)) => vec![line(
+            Side::Debit,
+            credited.account(),
+            credited.asset(),
+            credited.amount(),
+        )],
+        EffectKind::Account(account::Kind::Debited(ref debited)) => vec![line(
+            Side::Credit,
+            debited.account(),
+            debited.asset(),
+            debited.amount(),
+        )],
+        EffectKind::Trade(trade::Kind::Trade(ref trade)) => vec![
+            line(
+                Side::Credit,
+                trade.account(),
+                trade.sold_asset(),
+                trade.sold_amount(),
+            ),
+            line(
+                Side::Debit,
+                trade.account(),
+                trade.bought_asset(),
+                trade.bought_amount(),
+            ),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+/// The entry for the fee of a transaction, crediting the account that paid
+/// it and debiting the fee pool.
+pub fn fee_entry(transaction: &Transaction) -> JournalEntry {
+    let fee = transaction.fee_as_amount();
+    JournalEntry {
+        transaction_id: transaction.paging_token().parse().unwrap_or_default(),
+        operation_id: None,
+        transaction_hash: Some(transaction.hash().clone()),
+        created_at: transaction.created_at(),
+        lines: vec![
+            JournalLine::new(
+                Side::Credit,
+                Book::Account(transaction.source_account().clone()),
+                AssetIdentifier::native(),
+                fee,
+            ),
+            JournalLine::new(Side::Debit, Book::FeePool, AssetIdentifier::native(), fee),
+        ],
+    }
+}
+
+/// The entries for every operation that moved funds among the effects, in
+/// order.
+pub fn entries<'a, I>(effects: I) -> Vec<JournalEntry>
+where
+    I: IntoIterator<Item = &'a Effect>,
+{
+    let mut journal = Journal::new();
+    let mut entries: Vec<_> = effects
+        .into_iter()
+        .filter_map(|effect| journal.push(effect))
+        .collect();
+    entries.extend(journal.finish());
+    entries
+}
+
+/// Gathers effects into an entry per operation as they arrive.
+#[derive(Debug, Default, Clone)]
+pub struct Journal {
+    pending: Option<JournalEntry>,
+}
+
+impl Journal {
+    /// A journal that hasn't seen any effects.
+    pub fn new() -> Journal {
+        Journal::default()
+    }
+
+    /// Adds the effect to the entry of its operation. When the effect is the
+    /// first of a new operation, the entry of the previous operation is
+    /// complete and is returned, unless it moved no funds.
+    pub fn push(&mut self, effect: &Effect) -> Option<JournalEntry> {
+        let operation_id = effect.operation_id();
+        let finished = match self.pending {
+            Some(ref entry) if entry.operation_id == Some(operation_id) => None,
+            _ => self.pending.take(),
+        };
+        let entry = self.pending.get_or_insert_with(|| JournalEntry {
+            transaction_id: operation_id & !OPERATION_INDEX_MASK,
+            operation_id: Some(operation_id),
+            transaction_hash: None,
+            created_at: effect.created_at(),
+            lines: Vec::new(),
+        });
+        entry.lines.extend(lines(effect));
+        finished.filter(|entry| !entry.lines.is_empty())
+    }
+
+    /// The entry of the last operation, once no more effects are coming,
+    /// unless it moved no funds.
+    pub fn finish(self) -> Option<JournalEntry> {
+        self.pending.filter(|entry| !entry.lines.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod journal_tests {
+    use super::*;
+    use serde_json;
+
+    const SENDER: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+    const RECEIVER: &str = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4";
+
+    fn effect(operation_id: i64, account: &str, kind: &str, amount: &str) -> Effect {
+        serde_json::from_str(&format!(
+            r#"{{
+                "id": "{0:019}-0000000001",
+                "paging_token": "{0}-1",
+                "created_at": "2018-08-20T20:55:58Z",
+                "account": "{1}",
+                "type": "{2}",
+                "asset_type": "native",
+                "amount": "{3}",
+                "starting_balance": "{3}"
+            }}"#,
+            operation_id, account, kind, amount
+        ))
+        .unwrap()
+    }
+
+    // A trade of ten lumens for two and a half FOO, from the side of
+    // `account`, which sold lumens when `sold_lumens` is set.
+    fn trade(account: &str, seller: &str, sold_lumens: bool) -> Effect {
+        let lumens = r#""amount": "10.0", "asset_type": "native""#;
+        let foo = format!(
+            r#""amount": "2.5", "asset_type": "credit_alphanum4", "asset_code": "FOO", "asset_issuer": "{}""#,
+            RECEIVER
+        );
+        let (sold, bought) = if sold_lumens {
+            (lumens.to_string(), foo)
+        } else {
+            (foo, lumens.to_string())
+        };
+        serde_json::from_str(&format!(
+            r#"{{
+                "id": "0000033788507721730-0000000002",
+                "paging_token": "33788507721730-2",
+                "created_at": "2018-08-20T20:55:58Z",
+                "account": "{}",
+                "type": "trade",
+                "seller": "{}",
+                "offer_id": 1,
+                {},
+                {}
+            }}"#,
+            account,
+            seller,
+            sold.replace("\"a", "\"sold_a"),
+            bought.replace("\"a", "\"bought_a")
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn it_books_a_payment_against_both_accounts() {
+        let entries = entries(&[
+            effect(13_563_506_724_865, RECEIVER, "account_credited", "5.0"),
+            effect(13_563_506_724_865, SENDER, "account_debited", "5.0"),
+        ]);
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert!(entry.is_balanced());
+        assert_eq!(entry.operation_id(), Some(13_563_506_724_865));
+        assert_eq!(entry.transaction_id(), 13_563_506_724_864);
+        assert_eq!(entry.transaction_hash(), None);
+        assert_eq!(
+            entry.lines()[0],
+            JournalLine::new(
+                Side::Debit,
+                Book::Account(RECEIVER.into()),
+                AssetIdentifier::native(),
+                Amount::new(50_000_000),
+            )
+        );
+        assert_eq!(entry.lines()[1].side(), Side::Credit);
+        assert_eq!(entry.lines()[1].book(), &Book::Account(SENDER.into()));
+    }
+
+    #[test]
+    fn it_books_both_sides_of_a_trade() {
+        let entries = entries(&[
+            trade(SENDER, RECEIVER, true),
+            trade(RECEIVER, SENDER, false),
+        ]);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].lines().len(), 4);
+        assert!(entries[0].is_balanced());
+        assert_eq!(
+            entries[0].lines()[0],
+            JournalLine::new(
+                Side::Credit,
+                Book::Account(SENDER.into()),
+                AssetIdentifier::native(),
+                Amount::new(100_000_000),
+            )
+        );
+    }
+
+    #[test]
+    fn it_notices_an_entry_that_does_not_balance() {
+        let entries = entries(&[effect(4097, RECEIVER, "account_credited", "5.0")]);
+        assert!(!entries[0].is_balanced());
+    }
+
+    #[test]
+    fn it_hands_back_each_entry_once_the_next_operation_begins() {
+        let mut journal = Journal::new();
+        assert_eq!(
+            journal.push(&effect(4097, RECEIVER, "account_created", "1.0")),
+            None
+        );
+        assert_eq!(
+            journal.push(&effect(4097, SENDER, "account_debited", "1.0")),
+            None
+        );
+        let created = journal
+            .push(&effect(4098, SENDER, "account_removed", "0"))
+            .unwrap();
+        assert!(created.is_balanced());
+        assert_eq!(created.operation_id(), Some(4097));
+        // Removing the account moved no funds, so there is nothing left to
+        // book.
+        assert_eq!(journal.finish(), None);
+    }
+
+    #[test]
+    fn it_books_the_fee_against_the_fee_pool() {
+        let transaction: Transaction = serde_json::from_str(include_str!(
+            "../../fixtures/transactions/transaction_memo_none.json"
+        ))
+        .unwrap();
+        let entry = fee_entry(&transaction);
+        assert!(entry.is_balanced());
+        assert_eq!(entry.operation_id(), None);
+        assert_eq!(entry.transaction_hash(), Some(transaction.hash().as_str()));
+        assert_eq!(
+            entry.transaction_id().to_string(),
+            *transaction.paging_token()
+        );
+        assert_eq!(entry.lines()[1].book(), &Book::FeePool);
+        assert_eq!(entry.lines()[1].amount(), transaction.fee_as_amount());
+    }
+}
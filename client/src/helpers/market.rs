@@ -0,0 +1,316 @@
+//! Fetches both sides of the market for an asset pair from the offers
+//! endpoint.
+//!
+//! Horizon lists offers by what they sell and buy, so the market for a pair
+//! takes two queries: the offers selling the base asset for the counter
+//! asset, which are the asks, and the offers selling the counter asset for
+//! the base asset, which are the bids. `Market` runs both, pages through
+//! them, and puts the two sides in terms of the base asset so they can be
+//! read together, like an order book that keeps the individual offers.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use stellar_client::helpers::market::Market;
+//! use stellar_client::resources::AssetIdentifier;
+//! use stellar_client::sync::Client;
+//!
+//! let client = Client::horizon_test().unwrap();
+//! let usd = AssetIdentifier::alphanum4(
+//!     "USD",
+//!     "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG",
+//! ).unwrap();
+//! let market = Market::fetch(&client, AssetIdentifier::native(), usd).unwrap();
+//! for offer in market.iter() {
+//!     println!("{:?} {} at {}", offer.side(), offer.base_amount(), offer.price());
+//! }
+//! ```
+use endpoint::{offer, Limit};
+use error::Result;
+use resources::{Amount, AssetIdentifier, Offer, PriceRatio};
+use std::cmp::Ordering;
+use sync::{Iter, Requester};
+
+/// The number of offers asked for in each page.
+const PAGE_SIZE: u32 = 200;
+
+/// Which side of the market an offer is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// The offer buys the base asset with the counter asset.
+    Bid,
+    /// The offer sells the base asset for the counter asset.
+    Ask,
+}
+
+/// An offer along with its price and amount in terms of the base asset.
+#[derive(Debug, Clone)]
+pub struct MarketOffer {
+    side: Side,
+    offer: Offer,
+}
+
+impl MarketOffer {
+    fn new(side: Side, offer: Offer) -> MarketOffer {
+        MarketOffer { side, offer }
+    }
+
+    /// Whether the offer is a bid or an ask.
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    /// The offer as horizon returned it.
+    pub fn offer(&self) -> &Offer {
+        &self.offer
+    }
+
+    /// The price of the offer as units of the counter asset per unit of the
+    /// base asset, whichever side it is on. Horizon prices a bid in units of
+    /// the base asset per unit of the counter asset it sells, so a bid's
+    /// price is the inverse of the offer's own.
+    pub fn price(&self) -> PriceRatio {
+        let (n, d) = self.offer.price_ratio();
+        match self.side {
+            Side::Ask => PriceRatio::new(n, d),
+            Side::Bid => PriceRatio::new(d, n),
+        }
+    }
+
+    /// The amount of the base asset the offer would trade. An ask sells this
+    /// much; a bid buys this much with the counter asset it sells.
+    pub fn base_amount(&self) -> Amount {
+        match self.side {
+            Side::Ask => self.offer.amount(),
+            Side::Bid => {
+                let (n, d) = self.offer.price_ratio();
+                let amount = i128::from(self.offer.amount().to_stroops()) * i128::from(n)
+                    / i128::from(d.max(1));
+                Amount::new(amount as i64)
+            }
+        }
+    }
+}
+
+/// The live offers on both sides of an asset pair.
+#[derive(Debug, Clone)]
+pub struct Market {
+    base: AssetIdentifier,
+    counter: AssetIdentifier,
+    bids: Vec<MarketOffer>,
+    asks: Vec<MarketOffer>,
+}
+
+impl Market {
+    /// Fetches every offer selling the base asset for the counter asset and
+    /// every offer selling the counter asset for the base asset, following
+    /// the pages of both queries to the end.
+    pub fn fetch<C>(client: &C, base: AssetIdentifier, counter: AssetIdentifier) -> Result<Market>
+    where
+        C: Requester,
+    {
+        let asks = fetch_side(client, &base, &counter)?;
+        let bids = fetch_side(client, &counter, &base)?;
+        Ok(Market::from_offers(base, counter, bids, asks))
+    }
+
+    /// Builds the market from offers that have already been fetched. `bids`
+    /// sell the counter asset for the base asset and `asks` sell the base
+    /// asset for the counter asset.
+    pub fn from_offers(
+        base: AssetIdentifier,
+        counter: AssetIdentifier,
+        bids: Vec<Offer>,
+        asks: Vec<Offer>,
+    ) -> Market {
+        let mut bids: Vec<MarketOffer> = bids
+            .into_iter()
+            .map(|offer| MarketOffer::new(Side::Bid, offer))
+            .collect();
+        let mut asks: Vec<MarketOffer> = asks
+            .into_iter()
+            .map(|offer| MarketOffer::new(Side::Ask, offer))
+            .collect();
+        bids.sort_by(|a, b| compare(b.price(), a.price()));
+        asks.sort_by(|a, b| compare(a.price(), b.price()));
+        Market {
+            base,
+            counter,
+            bids,
+            asks,
+        }
+    }
+
+    /// The asset that amounts are given in.
+    pub fn base(&self) -> &AssetIdentifier {
+        &self.base
+    }
+
+    /// The asset that prices are given in.
+    pub fn counter(&self) -> &AssetIdentifier {
+        &self.counter
+    }
+
+    /// The offers buying the base asset, highest price first.
+    pub fn bids(&self) -> &[MarketOffer] {
+        &self.bids
+    }
+
+    /// The offers selling the base asset, lowest price first.
+    pub fn asks(&self) -> &[MarketOffer] {
+        &self.asks
+    }
+
+    /// The highest bid, if anyone is buying.
+    pub fn best_bid(&self) -> Option<&MarketOffer> {
+        self.bids.first()
+    }
+
+    /// The lowest ask, if anyone is selling.
+    pub fn best_ask(&self) -> Option<&MarketOffer> {
+        self.asks.first()
+    }
+
+    /// Iterates over every offer in the market in order of price, from the
+    /// lowest bid up through the highest bid and on from the lowest ask to
+    /// the highest.
+    pub fn iter(&self) -> impl Iterator<Item = &MarketOffer> {
+        self.bids.iter().rev().chain(self.asks.iter())
+    }
+
+    /// Returns true when there are no offers on either side.
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty()
+    }
+}
+
+fn fetch_side<C>(
+    client: &C,
+    selling: &AssetIdentifier,
+    buying: &AssetIdentifier,
+) -> Result<Vec<Offer>>
+where
+    C: Requester,
+{
+    let endpoint = offer::All::default()
+        .with_selling(selling.clone())
+        .with_buying(buying.clone())
+        .with_limit(PAGE_SIZE);
+    Iter::new(client, endpoint).collect()
+}
+
+fn compare(a: PriceRatio, b: PriceRatio) -> Ordering {
+    let left = u128::from(a.numerator()) * u128::from(b.denominator());
+    let right = u128::from(b.numerator()) * u128::from(a.denominator());
+    left.cmp(&right)
+}
+
+#[cfg(test)]
+mod market_tests {
+    use super::*;
+    use test::MockClient;
+
+    const ISSUER: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+    const SELLER: &str = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4";
+
+    fn bar() -> AssetIdentifier {
+        AssetIdentifier::alphanum4("BAR", ISSUER).unwrap()
+    }
+
+    fn foo() -> AssetIdentifier {
+        AssetIdentifier::alphanum4("FOO", ISSUER).unwrap()
+    }
+
+    fn offer(id: i64, selling: AssetIdentifier, buying: AssetIdentifier, n: u64, d: u64) -> Offer {
+        Offer::new(
+            id,
+            id.to_string(),
+            SELLER.into(),
+            selling,
+            buying,
+            Amount::new(150_000_000),
+            PriceRatio::new(n, d),
+            Amount::new(0),
+        )
+    }
+
+    fn page(offer: &str) -> String {
+        format!(r#"{{ "_embedded": {{ "records": [{}] }} }}"#, offer)
+    }
+
+    #[test]
+    fn it_fetches_both_sides_of_the_market() {
+        // The fixture sells BAR for FOO at 387/50. The bid sells FOO for BAR
+        // at 2/15, which is 7.5 FOO per BAR.
+        let ask = include_str!("../../fixtures/offer.json");
+        let bid = ask
+            .replace("BAR", "TMP")
+            .replace("FOO", "BAR")
+            .replace("TMP", "FOO")
+            .replace(r#""n": 387"#, r#""n": 2"#)
+            .replace(r#""d": 50"#, r#""d": 15"#);
+        let client = MockClient::new()
+            .with_json(
+                "/offers?selling_asset_type=credit_alphanum4&selling_asset_code=BAR*",
+                &page(ask),
+            )
+            .with_json(
+                "/offers?selling_asset_type=credit_alphanum4&selling_asset_code=FOO*",
+                &page(&bid),
+            );
+
+        let market = Market::fetch(&client, bar(), foo()).unwrap();
+        assert_eq!(client.requests().len(), 2);
+        assert!(client.requests()[0].contains("buying_asset_code=FOO"));
+        assert!(client.requests()[0].contains("limit=200"));
+
+        let ask = market.best_ask().unwrap();
+        assert_eq!(ask.side(), Side::Ask);
+        assert_eq!(ask.price(), PriceRatio::new(387, 50));
+        assert_eq!(ask.base_amount(), Amount::new(236_692_509));
+
+        let bid = market.best_bid().unwrap();
+        assert_eq!(bid.side(), Side::Bid);
+        assert_eq!(bid.price(), PriceRatio::new(15, 2));
+        assert_eq!(bid.base_amount(), Amount::new(31_559_001));
+    }
+
+    #[test]
+    fn it_orders_each_side_by_price() {
+        let market = Market::from_offers(
+            bar(),
+            foo(),
+            vec![
+                offer(1, foo(), bar(), 1, 7),
+                offer(2, foo(), bar(), 1, 5),
+                offer(3, foo(), bar(), 1, 6),
+            ],
+            vec![offer(4, bar(), foo(), 9, 1), offer(5, bar(), foo(), 8, 1)],
+        );
+        let ids: Vec<i64> = market.bids().iter().map(|bid| bid.offer().id()).collect();
+        assert_eq!(ids, vec![1, 3, 2]);
+        let ids: Vec<i64> = market.asks().iter().map(|ask| ask.offer().id()).collect();
+        assert_eq!(ids, vec![5, 4]);
+
+        let prices: Vec<PriceRatio> = market.iter().map(MarketOffer::price).collect();
+        assert_eq!(
+            prices,
+            vec![
+                PriceRatio::new(5, 1),
+                PriceRatio::new(6, 1),
+                PriceRatio::new(7, 1),
+                PriceRatio::new(8, 1),
+                PriceRatio::new(9, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_is_empty_without_offers() {
+        let client = MockClient::new().with_json("/offers*", &page(""));
+        let market = Market::fetch(&client, bar(), foo()).unwrap();
+        assert!(market.is_empty());
+        assert!(market.best_bid().is_none());
+        assert!(market.best_ask().is_none());
+    }
+}
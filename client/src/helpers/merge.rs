@@ -0,0 +1,367 @@
+//! Checks that an account can be merged before the merge is attempted.
+//!
+//! An account merge fails with `op_has_sub_entries` while the account still
+//! has offers, trustlines, data entries or extra signers, and with
+//! `op_is_sponsor` while it pays reserves for other accounts. Finding out
+//! which of those is in the way from a failed transaction is tedious, so
+//! `MergeCheck` looks at the account up front and lists what has to be
+//! cleaned up first. With the `keys` feature enabled, `MergeCheck::submit`
+//! then merges an account that is clear.
+//!
+//! # Examples
+//!
+//! ```
+//! use stellar_client::helpers::merge::{Blocker, MergeCheck};
+//! use stellar_client::resources::{AccountBuilder, Amount, AssetIdentifier, Balance};
+//!
+//! let foo = AssetIdentifier::alphanum4(
+//!     "FOO",
+//!     "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4",
+//! ).unwrap();
+//! let account = AccountBuilder::new("GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into())
+//!     .with_subentry_count(1)
+//!     .with_balance(Balance::new(Amount::new(0), Some(Amount::new(100)), foo.clone()))
+//!     .build();
+//!
+//! let check = MergeCheck::from_account(&account, &[]);
+//! assert_eq!(check.blockers(), &[Blocker::Trustline(foo, Amount::new(0))]);
+//! ```
+#[cfg(feature = "keys")]
+use super::transaction;
+use endpoint::account;
+#[cfg(feature = "keys")]
+use error::Error;
+use error::Result;
+#[cfg(feature = "keys")]
+use keys::Signer;
+#[cfg(feature = "keys")]
+use network::Network;
+#[cfg(feature = "keys")]
+use resources::envelope::{MuxedAccount, Operation, OperationBody};
+use resources::operation::AccountMerge;
+#[cfg(feature = "keys")]
+use resources::SubmittedTransaction;
+use resources::{Account, AccountId, Amount, AssetIdentifier, Offer};
+#[cfg(feature = "keys")]
+use std::error::Error as StdError;
+use std::fmt;
+use sync::{Iter, Requester};
+
+/// Something that stops an account from being merged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Blocker {
+    /// The account has open offers, listed by id. Each has to be deleted.
+    OpenOffers(Vec<i64>),
+    /// The account trusts an asset. The balance has to be sent back to the
+    /// issuer or elsewhere and the trustline removed.
+    Trustline(AssetIdentifier, Amount),
+    /// The account has a data entry by this name, which has to be deleted.
+    DataEntry(String),
+    /// The account pays for this many reserves of other accounts. The
+    /// sponsorships have to be revoked or transferred.
+    Sponsoring(u32),
+    /// The account has this many subentries that horizon doesn't list
+    /// individually, most likely extra signers, which have to be removed.
+    OtherSubentries(u64),
+}
+
+impl fmt::Display for Blocker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Blocker::OpenOffers(ref ids) => write!(f, "{} open offer(s)", ids.len()),
+            Blocker::Trustline(ref asset, balance) => write!(
+                f,
+                "trustline to {}-{} holding {}",
+                asset.code().unwrap_or_default(),
+                asset.issuer().map(AccountId::as_str).unwrap_or_default(),
+                balance
+            ),
+            Blocker::DataEntry(ref name) => write!(f, "data entry `{}`", name),
+            Blocker::Sponsoring(count) => write!(f, "sponsoring {} reserve(s)", count),
+            Blocker::OtherSubentries(count) => write!(f, "{} other subentry(s)", count),
+        }
+    }
+}
+
+/// The result of checking whether an account can be merged.
+#[derive(Debug, Clone)]
+pub struct MergeCheck {
+    account_id: AccountId,
+    blockers: Vec<Blocker>,
+}
+
+impl MergeCheck {
+    /// Fetches the account and its offers and checks them.
+    pub fn fetch<C>(client: &C, account_id: AccountId) -> Result<MergeCheck>
+    where
+        C: Requester,
+    {
+        let account = client.request(account::Details::new(account_id.clone()))?;
+        let offers =
+            Iter::new(client, account::Offers::new(account_id)).collect::<Result<Vec<Offer>>>()?;
+        Ok(MergeCheck::from_account(&account, &offers))
+    }
+
+    /// Checks an account that has already been fetched, along with its
+    /// offers.
+    pub fn from_account(account: &Account, offers: &[Offer]) -> MergeCheck {
+        let mut blockers = Vec::new();
+        if !offers.is_empty() {
+            blockers.push(Blocker::OpenOffers(
+                offers.iter().map(|offer| offer.id()).collect(),
+            ));
+        }
+
+        let trustlines: Vec<_> = account
+            .balances()
+            .iter()
+            .filter(|balance| !balance.asset_identifier().is_native())
+            .collect();
+        for balance in &trustlines {
+            blockers.push(Blocker::Trustline(
+                balance.asset_identifier().clone(),
+                balance.balance(),
+            ));
+        }
+
+        let mut names: Vec<&String> = account.data().keys().collect();
+        names.sort();
+        for name in names {
+            blockers.push(Blocker::DataEntry(name.clone()));
+        }
+
+        if account.num_sponsoring() > 0 {
+            blockers.push(Blocker::Sponsoring(account.num_sponsoring()));
+        }
+
+        let listed = (offers.len() + trustlines.len() + account.data().len()) as u64;
+        if account.subentry_count() > listed {
+            blockers.push(Blocker::OtherSubentries(account.subentry_count() - listed));
+        }
+
+        MergeCheck {
+            account_id: account.account_id().clone(),
+            blockers,
+        }
+    }
+
+    /// The account that was checked.
+    pub fn account_id(&self) -> &AccountId {
+        &self.account_id
+    }
+
+    /// Everything that stops the account from being merged, offers first,
+    /// then trustlines, data entries, sponsorships and anything else.
+    pub fn blockers(&self) -> &[Blocker] {
+        &self.blockers
+    }
+
+    /// Returns true when nothing stops the account from being merged.
+    pub fn is_safe(&self) -> bool {
+        self.blockers.is_empty()
+    }
+
+    /// The operation that merges the account into `into`, or the blockers
+    /// when there are any.
+    pub fn merge_into(&self, into: AccountId) -> ::std::result::Result<AccountMerge, Vec<Blocker>> {
+        if self.is_safe() {
+            Ok(AccountMerge::new(self.account_id.clone(), into))
+        } else {
+            Err(self.blockers.clone())
+        }
+    }
+
+    /// Merges the account into `into` with a transaction that `signer`
+    /// signs for `network`. Fails with `MergeError::Blocked` without
+    /// submitting anything when the check found blockers. The account may
+    /// have changed since it was checked, in which case the network rejects
+    /// the merge and `MergeError::Submission` holds why.
+    ///
+    /// Only available with the `keys` feature enabled.
+    #[cfg(feature = "keys")]
+    pub fn submit<C, S>(
+        &self,
+        client: &C,
+        network: &Network,
+        signer: &S,
+        into: &AccountId,
+    ) -> ::std::result::Result<SubmittedTransaction, MergeError>
+    where
+        C: Requester + ?Sized,
+        S: Signer,
+    {
+        if !self.is_safe() {
+            return Err(MergeError::Blocked(self.blockers.clone()));
+        }
+        let merge = OperationBody::AccountMerge {
+            destination: MuxedAccount::new(into, None),
+        };
+        transaction::submit_operations(
+            client,
+            network,
+            &self.account_id,
+            vec![Operation::new(merge)],
+            &[signer],
+        )
+        .map_err(MergeError::Submission)
+    }
+}
+
+/// Why an account was not merged.
+///
+/// Only available with the `keys` feature enabled.
+#[cfg(feature = "keys")]
+#[derive(Debug)]
+pub enum MergeError {
+    /// The check found these blockers, so nothing was submitted.
+    Blocked(Vec<Blocker>),
+    /// The merge could not be built, signed or submitted, or the network
+    /// rejected it.
+    Submission(Error),
+}
+
+#[cfg(feature = "keys")]
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MergeError::Blocked(ref blockers) => {
+                let blockers: Vec<String> = blockers.iter().map(Blocker::to_string).collect();
+                write!(f, "{}: {}", self.description(), blockers.join(", "))
+            }
+            MergeError::Submission(ref err) => write!(f, "{}: {}", self.description(), err),
+        }
+    }
+}
+
+#[cfg(feature = "keys")]
+impl StdError for MergeError {
+    fn description(&self) -> &str {
+        match *self {
+            MergeError::Blocked(_) => "The account can't be merged yet",
+            MergeError::Submission(_) => "The merge failed",
+        }
+    }
+}
+
+#[cfg(test)]
+mod merge_check_tests {
+    use super::*;
+    use resources::{AccountBuilder, Balance};
+    use serde_json;
+    use test::MockClient;
+
+    const ACCOUNT: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+    const DESTINATION: &str = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4";
+
+    fn foo() -> AssetIdentifier {
+        AssetIdentifier::alphanum4("FOO", DESTINATION).unwrap()
+    }
+
+    #[test]
+    fn it_lists_everything_in_the_way() {
+        // The fixture trusts FOO, has a data entry and sponsors one reserve.
+        let client = MockClient::new()
+            .with_json(
+                "/accounts/*/offers*",
+                &format!(
+                    r#"{{ "_embedded": {{ "records": [{}] }} }}"#,
+                    include_str!("../../fixtures/offer.json")
+                ),
+            )
+            .with_json("/accounts/*", include_str!("../../fixtures/account.json"));
+        let check = MergeCheck::fetch(&client, ACCOUNT.into()).unwrap();
+        assert_eq!(
+            check.blockers(),
+            &[
+                Blocker::OpenOffers(vec![121]),
+                Blocker::Trustline(foo(), Amount::new(10_000_000_000)),
+                Blocker::DataEntry("welcome".to_string()),
+                Blocker::Sponsoring(1),
+            ]
+        );
+        assert!(!check.is_safe());
+        assert_eq!(check.merge_into(DESTINATION.into()).unwrap_err().len(), 4);
+    }
+
+    #[test]
+    fn it_counts_subentries_it_cannot_see() {
+        let account = AccountBuilder::new(ACCOUNT.into())
+            .with_subentry_count(2)
+            .with_balance(Balance::new(Amount::new(0), Some(Amount::new(100)), foo()))
+            .build();
+        let check = MergeCheck::from_account(&account, &[]);
+        assert_eq!(
+            check.blockers(),
+            &[
+                Blocker::Trustline(foo(), Amount::new(0)),
+                Blocker::OtherSubentries(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_merges_a_clean_account() {
+        let account = AccountBuilder::new(ACCOUNT.into())
+            .with_balance(Balance::new(
+                Amount::new(100),
+                None,
+                AssetIdentifier::native(),
+            ))
+            .build();
+        let check = MergeCheck::from_account(&account, &[]);
+        assert!(check.is_safe());
+        let merge = check.merge_into(DESTINATION.into()).unwrap();
+        assert_eq!(merge.account(), ACCOUNT);
+        assert_eq!(AccountMerge::into(&merge), DESTINATION);
+    }
+
+    #[test]
+    fn it_describes_blockers() {
+        let offer: Offer = serde_json::from_str(include_str!("../../fixtures/offer.json")).unwrap();
+        let check = MergeCheck::from_account(
+            &AccountBuilder::new(ACCOUNT.into())
+                .with_subentry_count(1)
+                .build(),
+            &[offer],
+        );
+        assert_eq!(check.blockers()[0].to_string(), "1 open offer(s)");
+        assert_eq!(
+            Blocker::Trustline(foo(), Amount::new(10_000_000)).to_string(),
+            format!("trustline to FOO-{} holding 1.0000000", DESTINATION)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "keys")]
+    fn it_submits_the_merge_of_a_clean_account() {
+        use keys::Keypair;
+
+        let client = MockClient::new()
+            .with_json("/accounts/*", include_str!("../../fixtures/account.json"))
+            .with_json(
+                "/transactions",
+                include_str!("../../fixtures/submitted_transaction.json"),
+            );
+        let keypair = Keypair::random().unwrap();
+        let blocked = MergeCheck::from_account(
+            &AccountBuilder::new(ACCOUNT.into())
+                .with_subentry_count(1)
+                .build(),
+            &[],
+        );
+        match blocked.submit(&client, &Network::test(), &keypair, &DESTINATION.into()) {
+            Err(MergeError::Blocked(blockers)) => {
+                assert_eq!(blockers, vec![Blocker::OtherSubentries(1)])
+            }
+            other => panic!("expected blockers, got {:?}", other),
+        }
+        assert!(client.requests().is_empty());
+
+        let check = MergeCheck::from_account(&AccountBuilder::new(ACCOUNT.into()).build(), &[]);
+        let submitted = check
+            .submit(&client, &Network::test(), &keypair, &DESTINATION.into())
+            .unwrap();
+        assert_eq!(submitted.ledger(), 1_524_302);
+        assert_eq!(client.hits("/transactions"), 1);
+    }
+}
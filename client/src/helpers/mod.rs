@@ -0,0 +1,38 @@
+//! Higher level helpers built on top of the endpoints and resources.
+//!
+//! The endpoints map one to one with horizon's api. Most applications end up
+//! combining several of them to answer a single question, such as "does this
+//! account hold what I think it holds?". The helpers in this module capture
+//! those common workflows so that they don't need to be rewritten by each
+//! consumer of the library.
+
+pub mod arbitrage;
+pub mod balance;
+pub mod candles;
+pub mod codec;
+pub mod cursor;
+pub mod deposits;
+pub mod export;
+pub mod holders;
+pub mod issuance;
+pub mod journal;
+pub mod market;
+pub mod merge;
+pub mod offers;
+pub mod orderbook;
+#[cfg(feature = "keys")]
+pub mod path_payment;
+pub mod payments;
+pub mod payout;
+pub mod portfolio;
+pub mod rotation;
+pub mod snapshot;
+pub mod sponsorship;
+pub mod sweep;
+pub mod trustlines;
+
+#[cfg(feature = "keys")]
+mod transaction;
+
+/// The most operations the network allows in one transaction.
+const MAX_OPERATIONS: usize = 100;
@@ -0,0 +1,415 @@
+//! Tracks the live offers of an account and plans the operations needed to
+//! move them to a new set of quotes.
+//!
+//! Market makers repeatedly replace their quotes as the market moves. Rather
+//! than cancelling every offer and placing them all again, the manager reuses
+//! existing offers where it can so that the fewest operations are produced.
+//! The plan is expressed as manage offer operations: an offer id of zero
+//! creates a new offer and an amount of zero deletes an existing one.
+//!
+//! With the `keys` feature enabled, `OfferManager::submit` batches the plan
+//! into transactions, has them signed and submits them. Offers are placed at
+//! the exact price ratio of their quote.
+//!
+//! Every quote is a sell offer, placed with a manage sell offer operation.
+//! Horizon lists all offers by what they sell, including those placed with
+//! manage buy offer, so that is the only way a plan can match them. A bid is
+//! a quote selling the counter asset: to bid for 10 FOO at 2 XLM each, sell
+//! 20 XLM for FOO at a price of 1/2.
+//!
+//! # Examples
+//!
+//! ```
+//! use stellar_client::helpers::offers::{OfferManager, Quote};
+//! use stellar_client::resources::{AssetIdentifier, PriceRatio, Amount};
+//!
+//! let manager = OfferManager::new("GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into());
+//! let quote = Quote::new(
+//!     AssetIdentifier::native(),
+//!     AssetIdentifier::alphanum4(
+//!         "FOO",
+//!         "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4",
+//!     ).unwrap(),
+//!     Amount::new(10_000_000),
+//!     PriceRatio::new(1, 2),
+//! );
+//!
+//! // With no live offers every quote becomes a new offer.
+//! let plan = manager.plan(&[quote]);
+//! assert_eq!(plan.len(), 1);
+//! assert_eq!(plan[0].offer_id(), 0);
+//! ```
+#[cfg(feature = "keys")]
+use super::transaction;
+use endpoint::account;
+use error::Result;
+#[cfg(feature = "keys")]
+use keys::Signer;
+#[cfg(feature = "keys")]
+use network::Network;
+#[cfg(feature = "keys")]
+use resources::envelope::{Operation, OperationBody};
+use resources::operation::ManageOffer;
+#[cfg(feature = "keys")]
+use resources::SubmittedTransaction;
+use resources::{AccountId, Amount, AssetIdentifier, Offer, PriceRatio};
+use std::convert::TryFrom;
+use sync::{Iter, Requester};
+
+/// An offer that should be live on the orderbook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quote {
+    selling: AssetIdentifier,
+    buying: AssetIdentifier,
+    amount: Amount,
+    price_ratio: PriceRatio,
+}
+
+impl Quote {
+    /// Creates a quote to sell `amount` of `selling` in exchange for `buying`
+    /// at the given price, expressed as units of `buying` per unit of `selling`.
+    pub fn new(
+        selling: AssetIdentifier,
+        buying: AssetIdentifier,
+        amount: Amount,
+        price_ratio: PriceRatio,
+    ) -> Quote {
+        Quote {
+            selling,
+            buying,
+            amount,
+            price_ratio,
+        }
+    }
+
+    /// The asset being sold.
+    pub fn selling(&self) -> &AssetIdentifier {
+        &self.selling
+    }
+
+    /// The asset being bought.
+    pub fn buying(&self) -> &AssetIdentifier {
+        &self.buying
+    }
+
+    /// The amount of the selling asset on offer.
+    pub fn amount(&self) -> Amount {
+        self.amount
+    }
+
+    /// The price of the quote as a ratio of buying to selling.
+    pub fn price_ratio(&self) -> PriceRatio {
+        self.price_ratio
+    }
+
+    fn same_pair(&self, offer: &Offer) -> bool {
+        self.selling == *offer.selling() && self.buying == *offer.buying()
+    }
+
+    fn matches(&self, offer: &Offer) -> bool {
+        let (n, d) = offer.price_ratio();
+        self.same_pair(offer)
+            && self.amount == offer.amount()
+            && u128::from(self.price_ratio.numerator()) * u128::from(d)
+                == u128::from(n) * u128::from(self.price_ratio.denominator())
+    }
+
+    fn into_operation(self, offer_id: i64) -> ManageOffer {
+        let price = price_of(self.price_ratio);
+        ManageOffer::new(
+            offer_id,
+            self.selling,
+            self.buying,
+            self.amount,
+            self.price_ratio,
+            price,
+        )
+    }
+}
+
+/// Keeps track of the live offers for a single account.
+#[derive(Debug, Clone)]
+pub struct OfferManager {
+    account_id: AccountId,
+    offers: Vec<Offer>,
+}
+
+impl OfferManager {
+    /// Creates a manager for an account that is not yet aware of any offers.
+    pub fn new(account_id: AccountId) -> OfferManager {
+        OfferManager {
+            account_id,
+            offers: Vec::new(),
+        }
+    }
+
+    /// Creates a manager that is seeded with already known offers.
+    pub fn with_offers(account_id: AccountId, offers: Vec<Offer>) -> OfferManager {
+        OfferManager { account_id, offers }
+    }
+
+    /// The account whose offers are being managed.
+    pub fn account_id(&self) -> &AccountId {
+        &self.account_id
+    }
+
+    /// The offers currently known to be live.
+    pub fn offers(&self) -> &Vec<Offer> {
+        &self.offers
+    }
+
+    /// Replaces the known offers with every offer horizon reports for the account.
+    pub fn refresh<C>(&mut self, client: &C) -> Result<()>
+    where
+        C: Requester,
+    {
+        let endpoint = account::Offers::new(self.account_id.clone());
+        self.offers = Iter::new(client, endpoint).collect::<Result<Vec<Offer>>>()?;
+        Ok(())
+    }
+
+    /// Computes the operations needed to go from the live offers to the
+    /// target quotes. Offers that already match a quote are left alone, other
+    /// offers on the same pair are updated in place, and anything left over is
+    /// created or deleted. Deletions come first so that their reserves are
+    /// freed before any new offers are created.
+    pub fn plan(&self, targets: &[Quote]) -> Vec<ManageOffer> {
+        let mut unused: Vec<&Offer> = self.offers.iter().collect();
+        let mut unmatched = Vec::new();
+
+        for quote in targets {
+            match unused.iter().position(|offer| quote.matches(offer)) {
+                Some(index) => {
+                    unused.remove(index);
+                }
+                None => unmatched.push(quote),
+            }
+        }
+
+        let mut updates = Vec::new();
+        let mut creates = Vec::new();
+        for quote in unmatched {
+            match unused.iter().position(|offer| quote.same_pair(offer)) {
+                Some(index) => {
+                    let offer = unused.remove(index);
+                    updates.push(quote.clone().into_operation(offer.id()));
+                }
+                None => creates.push(quote.clone().into_operation(0)),
+            }
+        }
+
+        let mut plan: Vec<ManageOffer> = unused
+            .into_iter()
+            .map(|offer| {
+                let (n, d) = offer.price_ratio();
+                ManageOffer::new(
+                    offer.id(),
+                    offer.selling().clone(),
+                    offer.buying().clone(),
+                    Amount::new(0),
+                    PriceRatio::new(n, d),
+                    offer.price(),
+                )
+            })
+            .collect();
+        plan.extend(updates);
+        plan.extend(creates);
+        plan
+    }
+
+    /// Plans the operations needed to reach `targets` and submits them from
+    /// the account, signed by `signer` for `network`. Once they are applied
+    /// the live offers are refreshed, which picks up the ids of the offers
+    /// that were created.
+    ///
+    /// The network takes at most 100 operations in a transaction, so a
+    /// longer plan is split over several transactions that are submitted in
+    /// order. If one fails, the ones before it stay applied and the known
+    /// offers are left as they were. Nothing is submitted when the live
+    /// offers already match the targets.
+    ///
+    /// Only available with the `keys` feature enabled.
+    #[cfg(feature = "keys")]
+    pub fn submit<C, S>(
+        &mut self,
+        client: &C,
+        network: &Network,
+        signer: &S,
+        targets: &[Quote],
+    ) -> Result<Vec<SubmittedTransaction>>
+    where
+        C: Requester,
+        S: Signer,
+    {
+        let operations: Vec<Operation> = self
+            .plan(targets)
+            .into_iter()
+            .map(|offer| {
+                Operation::new(OperationBody::ManageSellOffer {
+                    selling: offer.selling().clone(),
+                    buying: offer.buying().clone(),
+                    amount: offer.amount(),
+                    price: *offer.price_ratio(),
+                    offer_id: offer.offer_id(),
+                })
+            })
+            .collect();
+        let mut submitted = Vec::new();
+        for batch in operations.chunks(super::MAX_OPERATIONS) {
+            submitted.push(transaction::submit_operations(
+                client,
+                network,
+                &self.account_id,
+                batch.to_vec(),
+                &[signer],
+            )?);
+        }
+        if !submitted.is_empty() {
+            self.refresh(client)?;
+        }
+        Ok(submitted)
+    }
+}
+
+/// The price horizon shows next to a price ratio, with seven decimal places
+/// and rounded to the nearest. Only the ratio goes into a transaction, so
+/// the rounding never changes the price an offer is placed at.
+fn price_of(ratio: PriceRatio) -> Amount {
+    let denominator = u128::from(ratio.denominator().max(1));
+    let price = (u128::from(ratio.numerator()) * 20_000_000 + denominator) / (2 * denominator);
+    Amount::new(i64::try_from(price).unwrap_or(i64::MAX))
+}
+
+#[cfg(test)]
+mod offer_manager_tests {
+    use super::*;
+    use serde_json;
+    use test::MockClient;
+
+    const SELLER: &str = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4";
+    const ISSUER: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+
+    // The fixture offer sells 23.6692509 BAR for FOO at 387/50.
+    fn offer() -> Offer {
+        serde_json::from_str(include_str!("../../fixtures/offer.json")).unwrap()
+    }
+
+    fn bar() -> AssetIdentifier {
+        AssetIdentifier::alphanum4("BAR", ISSUER).unwrap()
+    }
+
+    fn foo() -> AssetIdentifier {
+        AssetIdentifier::alphanum4("FOO", ISSUER).unwrap()
+    }
+
+    fn manager() -> OfferManager {
+        OfferManager::with_offers(SELLER.into(), vec![offer()])
+    }
+
+    #[test]
+    fn it_keeps_offers_that_already_match() {
+        let quote = Quote::new(
+            bar(),
+            foo(),
+            Amount::new(236_692_509),
+            PriceRatio::new(774, 100),
+        );
+        assert!(manager().plan(&[quote]).is_empty());
+    }
+
+    #[test]
+    fn it_updates_offers_on_the_same_pair() {
+        let quote = Quote::new(bar(), foo(), Amount::new(10_000_000), PriceRatio::new(8, 1));
+        let plan = manager().plan(&[quote]);
+        assert_eq!(plan.len(), 1);
+        assert_eq!(plan[0].offer_id(), 121);
+        assert_eq!(plan[0].amount(), Amount::new(10_000_000));
+        assert_eq!(plan[0].price(), Amount::new(80_000_000));
+    }
+
+    #[test]
+    fn it_deletes_offers_that_are_no_longer_wanted() {
+        let quote = Quote::new(foo(), bar(), Amount::new(10_000_000), PriceRatio::new(1, 8));
+        let plan = manager().plan(&[quote]);
+        assert_eq!(plan.len(), 2);
+        assert_eq!(plan[0].offer_id(), 121);
+        assert_eq!(plan[0].amount(), Amount::new(0));
+        assert_eq!(plan[1].offer_id(), 0);
+        assert_eq!(plan[1].selling(), &foo());
+        assert_eq!(plan[1].price(), Amount::new(1_250_000));
+    }
+
+    #[test]
+    fn it_rounds_the_price_it_shows_to_the_nearest() {
+        assert_eq!(price_of(PriceRatio::new(1, 3)), Amount::new(3_333_333));
+        assert_eq!(price_of(PriceRatio::new(2, 3)), Amount::new(6_666_667));
+        assert_eq!(
+            price_of(PriceRatio::new(u64::MAX, 1)),
+            Amount::new(i64::MAX)
+        );
+    }
+
+    #[test]
+    fn it_deletes_everything_for_an_empty_target() {
+        let plan = manager().plan(&[]);
+        assert_eq!(plan.len(), 1);
+        assert_eq!(plan[0].amount(), Amount::new(0));
+    }
+
+    #[test]
+    fn it_refreshes_offers_from_horizon() {
+        let client = MockClient::new().with_json(
+            "/accounts/*/offers*",
+            &format!(
+                r#"{{ "_embedded": {{ "records": [{}] }} }}"#,
+                include_str!("../../fixtures/offer.json")
+            ),
+        );
+        let mut manager = OfferManager::new(SELLER.into());
+        manager.refresh(&client).unwrap();
+        assert_eq!(manager.offers().len(), 1);
+        assert_eq!(manager.offers()[0].id(), 121);
+    }
+
+    #[test]
+    #[cfg(feature = "keys")]
+    fn it_submits_the_plan_in_one_transaction() {
+        use keys::Keypair;
+        use network::Network;
+
+        let client = MockClient::new()
+            .with_json(
+                "/accounts/*/offers*",
+                &format!(
+                    r#"{{ "_embedded": {{ "records": [{}] }} }}"#,
+                    include_str!("../../fixtures/offer.json")
+                ),
+            )
+            .with_json("/accounts/*", include_str!("../../fixtures/account.json"))
+            .with_json(
+                "/transactions",
+                include_str!("../../fixtures/submitted_transaction.json"),
+            );
+        let keypair = Keypair::random().unwrap();
+        let quotes = [
+            Quote::new(foo(), bar(), Amount::new(10_000_000), PriceRatio::new(1, 3)),
+            Quote::new(bar(), foo(), Amount::new(10_000_000), PriceRatio::new(8, 1)),
+        ];
+        let mut manager = OfferManager::new(SELLER.into());
+        let submitted = manager
+            .submit(&client, &Network::test(), &keypair, &quotes)
+            .unwrap();
+        assert_eq!(submitted.len(), 1);
+        assert_eq!(client.hits("/transactions"), 1);
+        assert_eq!(manager.offers().len(), 1);
+
+        // Once the offers match, there is nothing left to submit.
+        let client = MockClient::new();
+        let mut manager = OfferManager::new(SELLER.into());
+        assert!(manager
+            .submit(&client, &Network::test(), &keypair, &[])
+            .unwrap()
+            .is_empty());
+        assert!(client.requests().is_empty());
+    }
+}
@@ -0,0 +1,284 @@
+//! Keeps a live copy of an order book in memory from horizon's order book
+//! stream, so that trading systems can watch it change without polling.
+//!
+//! Horizon streams a full snapshot of the book whenever it changes. The
+//! mirror compares each snapshot with the last one and reports the price
+//! levels that were added, resized or removed.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use stellar_client::endpoint::orderbook;
+//! use stellar_client::helpers::orderbook::OrderBookMirror;
+//! use stellar_client::resources::AssetIdentifier;
+//! use stellar_client::sync::Client;
+//!
+//! let client = Client::horizon_test().unwrap();
+//! let usd = AssetIdentifier::alphanum4(
+//!     "USD",
+//!     "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG",
+//! ).unwrap();
+//! let endpoint = orderbook::Details::for_asset_pair(AssetIdentifier::native(), usd);
+//!
+//! let mut mirror = OrderBookMirror::new();
+//! mirror
+//!     .follow(client.stream(endpoint).unwrap(), |mirror, changes| {
+//!         println!("{} levels changed", changes.len());
+//!         if let Some(bid) = mirror.best_bid() {
+//!             println!("best bid is now {}", bid.price());
+//!         }
+//!     })
+//!     .unwrap();
+//! ```
+use error::Result;
+use resources::{Amount, OfferSummary, Orderbook, PriceRatio};
+
+/// Which side of the book a price level is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// Offers to buy the base asset.
+    Bid,
+    /// Offers to sell the base asset.
+    Ask,
+}
+
+/// A price level that differs between two snapshots of an order book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderBookChange {
+    side: Side,
+    price: PriceRatio,
+    before: Option<Amount>,
+    after: Option<Amount>,
+}
+
+impl OrderBookChange {
+    /// The side of the book the level is on.
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    /// The price of the level.
+    pub fn price(&self) -> PriceRatio {
+        self.price
+    }
+
+    /// The amount offered at the level before the change, if it existed.
+    pub fn before(&self) -> Option<Amount> {
+        self.before
+    }
+
+    /// The amount offered at the level after the change, unless it was
+    /// removed.
+    pub fn after(&self) -> Option<Amount> {
+        self.after
+    }
+
+    /// Whether the level is new to the book.
+    pub fn is_added(&self) -> bool {
+        self.before.is_none()
+    }
+
+    /// Whether the level has left the book.
+    pub fn is_removed(&self) -> bool {
+        self.after.is_none()
+    }
+}
+
+/// An in-memory order book kept up to date by applying each snapshot horizon
+/// sends.
+#[derive(Debug, Default)]
+pub struct OrderBookMirror {
+    book: Option<Orderbook>,
+}
+
+impl OrderBookMirror {
+    /// Creates a mirror that has yet to see a snapshot.
+    pub fn new() -> OrderBookMirror {
+        OrderBookMirror::default()
+    }
+
+    /// The latest snapshot of the book, if one has been applied.
+    pub fn orderbook(&self) -> Option<&Orderbook> {
+        self.book.as_ref()
+    }
+
+    /// The highest bid in the book.
+    pub fn best_bid(&self) -> Option<&OfferSummary> {
+        self.book.as_ref().and_then(|book| book.bids().first())
+    }
+
+    /// The lowest ask in the book.
+    pub fn best_ask(&self) -> Option<&OfferSummary> {
+        self.book.as_ref().and_then(|book| book.asks().first())
+    }
+
+    /// Replaces the book with the snapshot and returns the levels that
+    /// changed, bids first. Every level of the first snapshot counts as
+    /// added.
+    pub fn apply(&mut self, snapshot: Orderbook) -> Vec<OrderBookChange> {
+        let mut changes = Vec::new();
+        {
+            let (bids, asks): (&[OfferSummary], &[OfferSummary]) = match self.book {
+                Some(ref book) => (book.bids(), book.asks()),
+                None => (&[], &[]),
+            };
+            diff(Side::Bid, bids, snapshot.bids(), &mut changes);
+            diff(Side::Ask, asks, snapshot.asks(), &mut changes);
+        }
+        self.book = Some(snapshot);
+        changes
+    }
+
+    /// Applies every snapshot from the iterator, usually the stream of the
+    /// order book endpoint, calling `on_change` with the mirror and the
+    /// changes whenever a snapshot changes the book. Returns once the
+    /// snapshots run out, or with the first error.
+    pub fn follow<I, F>(&mut self, snapshots: I, mut on_change: F) -> Result<()>
+    where
+        I: IntoIterator<Item = Result<Orderbook>>,
+        F: FnMut(&OrderBookMirror, &[OrderBookChange]),
+    {
+        for snapshot in snapshots {
+            let changes = self.apply(snapshot?);
+            if !changes.is_empty() {
+                on_change(self, &changes);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn diff(
+    side: Side,
+    before: &[OfferSummary],
+    after: &[OfferSummary],
+    changes: &mut Vec<OrderBookChange>,
+) {
+    let amount_at = |levels: &[OfferSummary], price: PriceRatio| {
+        levels
+            .iter()
+            .find(|level| level.price_ratio() == price)
+            .map(OfferSummary::amount)
+    };
+    for level in after {
+        let previous = amount_at(before, level.price_ratio());
+        if previous != Some(level.amount()) {
+            changes.push(OrderBookChange {
+                side,
+                price: level.price_ratio(),
+                before: previous,
+                after: Some(level.amount()),
+            });
+        }
+    }
+    for level in before {
+        if amount_at(after, level.price_ratio()).is_none() {
+            changes.push(OrderBookChange {
+                side,
+                price: level.price_ratio(),
+                before: Some(level.amount()),
+                after: None,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod order_book_mirror_tests {
+    use super::*;
+    use error::Error;
+    use resources::AssetIdentifier;
+
+    fn level(n: u64, amount: i64) -> OfferSummary {
+        OfferSummary::new(
+            Amount::new(amount),
+            PriceRatio::new(n, 1),
+            Amount::new(n as i64 * 10_000_000),
+        )
+    }
+
+    fn book(bids: Vec<OfferSummary>, asks: Vec<OfferSummary>) -> Orderbook {
+        Orderbook::new(
+            bids,
+            asks,
+            AssetIdentifier::native(),
+            AssetIdentifier::alphanum4(
+                "USD",
+                "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG",
+            )
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn it_adds_every_level_of_the_first_snapshot() {
+        let mut mirror = OrderBookMirror::new();
+        let changes = mirror.apply(book(vec![level(2, 10)], vec![level(3, 5)]));
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().all(OrderBookChange::is_added));
+        assert_eq!(changes[0].side(), Side::Bid);
+        assert_eq!(changes[1].side(), Side::Ask);
+        assert_eq!(mirror.best_bid().unwrap().amount(), Amount::new(10));
+        assert_eq!(mirror.best_ask().unwrap().amount(), Amount::new(5));
+    }
+
+    #[test]
+    fn it_reports_what_changed_between_snapshots() {
+        let mut mirror = OrderBookMirror::new();
+        mirror.apply(book(vec![level(2, 10), level(1, 4)], vec![level(3, 5)]));
+        let changes = mirror.apply(book(vec![level(2, 10), level(1, 6)], vec![level(4, 1)]));
+        assert_eq!(
+            changes,
+            vec![
+                OrderBookChange {
+                    side: Side::Bid,
+                    price: PriceRatio::new(1, 1),
+                    before: Some(Amount::new(4)),
+                    after: Some(Amount::new(6)),
+                },
+                OrderBookChange {
+                    side: Side::Ask,
+                    price: PriceRatio::new(4, 1),
+                    before: None,
+                    after: Some(Amount::new(1)),
+                },
+                OrderBookChange {
+                    side: Side::Ask,
+                    price: PriceRatio::new(3, 1),
+                    before: Some(Amount::new(5)),
+                    after: None,
+                },
+            ]
+        );
+        assert!(changes[2].is_removed());
+        assert_eq!(mirror.best_ask().unwrap().amount(), Amount::new(1));
+    }
+
+    #[test]
+    fn it_only_notifies_when_the_book_changes() {
+        let snapshots = vec![
+            Ok(book(vec![level(2, 10)], vec![])),
+            Ok(book(vec![level(2, 10)], vec![])),
+            Ok(book(vec![], vec![])),
+        ];
+        let mut mirror = OrderBookMirror::new();
+        let mut notified = Vec::new();
+        mirror
+            .follow(snapshots, |mirror, changes| {
+                notified.push((mirror.best_bid().is_some(), changes.len()));
+            })
+            .unwrap();
+        assert_eq!(notified, vec![(true, 1), (false, 1)]);
+    }
+
+    #[test]
+    fn it_stops_following_on_an_error() {
+        let snapshots = vec![
+            Err(Error::ServerError(None)),
+            Ok(book(vec![level(2, 10)], vec![])),
+        ];
+        let mut mirror = OrderBookMirror::new();
+        assert!(mirror.follow(snapshots, |_, _| {}).is_err());
+        assert!(mirror.orderbook().is_none());
+    }
+}
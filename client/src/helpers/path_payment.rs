@@ -0,0 +1,548 @@
+//! Sends path payments that refuse to pay more than a quoted price allows.
+//!
+//! A path payment converts one asset into another through the order books
+//! as it is applied, so what it costs depends on the books at that moment
+//! rather than when it was planned. `execute_path_payment` asks horizon's
+//! paths endpoint what a payment would cost now, allows the price to move by
+//! a number of basis points on top of that, and builds, signs and submits a
+//! strict receive path payment that sends no more than the bound.
+//!
+//! Failures come back as a `PathPaymentError`, which says how far the
+//! payment got: whether it could be built and signed, whether horizon could
+//! be reached, and, for a transaction the network rejected, which operation
+//! failed it. When the books move further than the bound before the
+//! transaction is applied, the payment fails with
+//! `PathPaymentError::SlippageExceeded` rather than the result codes, so that
+//! retry logic can quote the payment again.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use stellar_client::helpers::path_payment::{
+//!     execute_path_payment, PathPayment, PathPaymentError,
+//! };
+//! use stellar_client::keys::Keypair;
+//! use stellar_client::resources::{Amount, AssetIdentifier};
+//! use stellar_client::sync::Client;
+//! use stellar_client::Network;
+//!
+//! let client = Client::horizon_test().unwrap();
+//! let keypair = Keypair::from_secret_seed(
+//!     "SAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6NKI",
+//! ).unwrap();
+//! let issuer = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+//! let payment = PathPayment::new(
+//!     keypair.account_id().into(),
+//!     "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4".into(),
+//!     AssetIdentifier::alphanum4("USD", issuer).unwrap(),
+//!     AssetIdentifier::alphanum4("EUR", issuer).unwrap(),
+//!     Amount::new(100_000_000),
+//! ).with_max_slippage(50);
+//!
+//! for _ in 0..3 {
+//!     match execute_path_payment(&client, &Network::test(), &keypair, &payment) {
+//!         Ok(submitted) => println!("paid in ledger {}", submitted.ledger()),
+//!         Err(PathPaymentError::SlippageExceeded(send_max)) => {
+//!             println!("the price moved past {}, quoting again", send_max);
+//!             continue;
+//!         }
+//!         Err(PathPaymentError::Unconfirmed(hash)) => {
+//!             println!("{} may still go through, not paying twice", hash)
+//!         }
+//!         Err(err) => println!("payment failed: {}", err),
+//!     }
+//!     break;
+//! }
+//! ```
+use super::transaction;
+use endpoint::payment;
+use error::{Error, Result};
+use keys::Signer;
+use network::Network;
+use resources::envelope::{EnvelopeBuilder, MuxedAccount, Operation, OperationBody};
+use resources::{
+    AccountId, Amount, AssetIdentifier, FailureReasons, PaymentPath, SubmittedTransaction,
+    TransactionEnvelope,
+};
+use std::error::Error as StdError;
+use std::fmt;
+use sync::{self, Requester};
+use xdr;
+
+/// The operation result codes of a path payment whose price moved past its
+/// bound.
+const SLIPPAGE_CODES: &[&str] = &["op_over_source_max", "op_under_dest_min"];
+
+/// A payment of an exact amount of one asset, paid for in another.
+#[derive(Debug, Clone)]
+pub struct PathPayment {
+    source: AccountId,
+    destination: AccountId,
+    send_asset: AssetIdentifier,
+    dest_asset: AssetIdentifier,
+    dest_amount: Amount,
+    max_slippage: u32,
+    fee: u32,
+}
+
+impl PathPayment {
+    /// A payment of `dest_amount` of `dest_asset` to `destination`, paid
+    /// for by `source` in `send_asset`. By default the price may not move at
+    /// all from the quote and the transaction pays a fee of 100 stroops.
+    pub fn new(
+        source: AccountId,
+        destination: AccountId,
+        send_asset: AssetIdentifier,
+        dest_asset: AssetIdentifier,
+        dest_amount: Amount,
+    ) -> PathPayment {
+        PathPayment {
+            source,
+            destination,
+            send_asset,
+            dest_asset,
+            dest_amount,
+            max_slippage: 0,
+            fee: 100,
+        }
+    }
+
+    /// Allows the payment to send up to the given number of basis points
+    /// more than quoted.
+    pub fn with_max_slippage(mut self, basis_points: u32) -> PathPayment {
+        self.max_slippage = basis_points;
+        self
+    }
+
+    /// The fee, in stroops, the transaction pays.
+    pub fn with_fee(mut self, fee: u32) -> PathPayment {
+        self.fee = fee;
+        self
+    }
+
+    /// Asks horizon for the paths the payment can take and picks the one
+    /// that sends the least of the send asset. Fails with `Error::NoPath`
+    /// when there is none.
+    pub fn quote<C>(&self, client: &C) -> Result<Quote>
+    where
+        C: Requester + ?Sized,
+    {
+        let paths = client.request(payment::FindPath::new(
+            self.source.clone(),
+            self.destination.clone(),
+            self.dest_asset.clone(),
+            self.dest_amount,
+        ))?;
+        let path = paths
+            .into_iter()
+            .filter(|path| *path.source_asset() == self.send_asset)
+            .min_by_key(|path| *path.source_amount())
+            .ok_or(Error::NoPath)?;
+        let send_max = with_slippage(*path.source_amount(), self.max_slippage);
+        Ok(Quote { path, send_max })
+    }
+
+    // The transaction that makes the payment along the quoted path, using
+    // up `sequence`.
+    fn envelope(&self, quote: &Quote, sequence: i64) -> xdr::Result<TransactionEnvelope> {
+        let payment = OperationBody::PathPaymentStrictReceive {
+            send_asset: self.send_asset.clone(),
+            send_max: quote.send_max,
+            destination: MuxedAccount::new(&self.destination, None),
+            dest_asset: self.dest_asset.clone(),
+            dest_amount: self.dest_amount,
+            path: quote.path.path().to_vec(),
+        };
+        EnvelopeBuilder::new(MuxedAccount::new(&self.source, None), sequence)
+            .with_fee(self.fee)
+            .with_operation(Operation::new(payment))
+            .build()
+    }
+}
+
+/// The path a payment will take and the most it is allowed to send.
+#[derive(Debug, Clone)]
+pub struct Quote {
+    path: PaymentPath,
+    send_max: Amount,
+}
+
+impl Quote {
+    /// The path horizon found, with the amount it would send right now.
+    pub fn path(&self) -> &PaymentPath {
+        &self.path
+    }
+
+    /// The most the payment may send: the quoted amount plus the slippage
+    /// allowed on top of it.
+    pub fn send_max(&self) -> Amount {
+        self.send_max
+    }
+}
+
+/// Quotes `payment`, then builds the transaction that makes it, has `signer`
+/// sign it for `network` and submits it.
+///
+/// The source account is loaded for its sequence number. A payment whose
+/// price moved past its bound fails with `PathPaymentError::SlippageExceeded`,
+/// and horizon finding no path for it with `PathPaymentError::NoPath`.
+pub fn execute_path_payment<C, S>(
+    client: &C,
+    network: &Network,
+    signer: &S,
+    payment: &PathPayment,
+) -> ::std::result::Result<SubmittedTransaction, PathPaymentError>
+where
+    C: Requester + ?Sized,
+    S: Signer + ?Sized,
+{
+    let quote = payment.quote(client).map_err(|err| match err {
+        Error::NoPath => PathPaymentError::NoPath,
+        err => PathPaymentError::Network(err),
+    })?;
+    let sequence =
+        transaction::next_sequence(client, &payment.source).map_err(|err| match err {
+            Error::SequenceExhausted(_) => PathPaymentError::Invalid(err.to_string()),
+            err => PathPaymentError::Network(err),
+        })?;
+    let mut envelope = payment
+        .envelope(&quote, sequence)
+        .map_err(|err| PathPaymentError::Invalid(err.to_string()))?;
+    envelope
+        .sign(network, signer)
+        .map_err(PathPaymentError::Signing)?;
+
+    sync::submit_transaction(client, &envelope.to_base64(), network).map_err(|err| match err {
+        Error::BadResponse(err) => match err.result_codes().cloned() {
+            Some(ref reasons) if slipped(reasons) => {
+                PathPaymentError::SlippageExceeded(quote.send_max)
+            }
+            Some(reasons) => PathPaymentError::Rejected {
+                operation: reasons.failed_operation(),
+                reasons,
+            },
+            None => PathPaymentError::Network(Error::BadResponse(err)),
+        },
+        Error::TransactionTimeout(hash) => PathPaymentError::Unconfirmed(hash),
+        err => PathPaymentError::Network(err),
+    })
+}
+
+/// Why a path payment failed, told apart by how far the payment got so that
+/// callers can tell one that is safe to try again from one that needs
+/// looking into first.
+#[derive(Debug)]
+pub enum PathPaymentError {
+    /// The transaction could not be built, such as when the payment names
+    /// an account id that isn't valid. Trying again won't help.
+    Invalid(String),
+    /// Horizon found no path for the payment.
+    NoPath,
+    /// The signer failed to sign the transaction, so nothing was submitted.
+    Signing(Error),
+    /// A request to horizon failed while quoting, loading the source account
+    /// or submitting, without the transaction being applied.
+    Network(Error),
+    /// The submission timed out and horizon hasn't seen the transaction
+    /// since. The inner string is its hash. It may still make it into a
+    /// later ledger, so it has to be looked up before paying again.
+    Unconfirmed(String),
+    /// The network rejected the payment because the price moved past its
+    /// bound. The inner value is the most it was allowed to send. Quoting it
+    /// again may find a path that works.
+    SlippageExceeded(Amount),
+    /// The network rejected the transaction for any other reason.
+    Rejected {
+        /// The index of the operation that failed the transaction, or `None`
+        /// when it failed before its operations ran, such as with
+        /// `tx_bad_seq`.
+        operation: Option<usize>,
+        /// The result codes horizon gave.
+        reasons: FailureReasons,
+    },
+}
+
+impl fmt::Display for PathPaymentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PathPaymentError::Invalid(ref reason) => {
+                write!(f, "{}: {}", self.description(), reason)
+            }
+            PathPaymentError::Signing(ref err) | PathPaymentError::Network(ref err) => {
+                write!(f, "{}: {}", self.description(), err)
+            }
+            PathPaymentError::Unconfirmed(ref hash) => {
+                write!(f, "{}: {}", self.description(), hash)
+            }
+            PathPaymentError::SlippageExceeded(send_max) => {
+                write!(f, "{}: {}", self.description(), send_max)
+            }
+            PathPaymentError::Rejected {
+                operation: Some(index),
+                ref reasons,
+            } => write!(
+                f,
+                "{}: operation {} failed with {}",
+                self.description(),
+                index,
+                reasons.operations()[index]
+            ),
+            PathPaymentError::Rejected { ref reasons, .. } => {
+                write!(f, "{}: {}", self.description(), reasons.transaction())
+            }
+            PathPaymentError::NoPath => f.write_str(self.description()),
+        }
+    }
+}
+
+impl StdError for PathPaymentError {
+    fn description(&self) -> &str {
+        match *self {
+            PathPaymentError::Invalid(_) => "The payment could not be built",
+            PathPaymentError::NoPath => "No path was found for the payment",
+            PathPaymentError::Signing(_) => "The payment could not be signed",
+            PathPaymentError::Network(_) => "A request to horizon failed",
+            PathPaymentError::Unconfirmed(_) => "The payment was submitted but not confirmed",
+            PathPaymentError::SlippageExceeded(_) => {
+                "The path payment would have slipped past its bound"
+            }
+            PathPaymentError::Rejected { .. } => "The network rejected the payment",
+        }
+    }
+}
+
+fn slipped(codes: &FailureReasons) -> bool {
+    codes
+        .operations()
+        .iter()
+        .any(|code| SLIPPAGE_CODES.contains(&code.as_str()))
+}
+
+/// `amount` raised by `basis_points`, rounded down so that the bound is
+/// never looser than asked for.
+fn with_slippage(amount: Amount, basis_points: u32) -> Amount {
+    let stroops = amount.to_stroops();
+    let factor = 10_000 + i64::from(basis_points);
+    (stroops / 10_000)
+        .checked_mul(factor)
+        .and_then(|whole| whole.checked_add(stroops % 10_000 * factor / 10_000))
+        .map_or(Amount::new(i64::MAX), Amount::new)
+}
+
+#[cfg(test)]
+mod path_payment_tests {
+    use super::*;
+    use keys::Keypair;
+    use test::MockClient;
+
+    const SOURCE: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+    const DESTINATION: &str = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4";
+    const ISSUER: &str = "GDSBCQO34HWPGUGQSP3QBFEXVTSR2PW46UIGTHVWGWJGQKH3AFNHXHXN";
+
+    fn usd() -> AssetIdentifier {
+        AssetIdentifier::alphanum4("USD", ISSUER).unwrap()
+    }
+
+    fn eur() -> AssetIdentifier {
+        AssetIdentifier::alphanum4("EUR", ISSUER).unwrap()
+    }
+
+    fn payment() -> PathPayment {
+        PathPayment::new(
+            SOURCE.into(),
+            DESTINATION.into(),
+            usd(),
+            eur(),
+            Amount::new(200_000_000),
+        )
+    }
+
+    // The fixture path, which sends 20 USD, and one that sends `amount` of
+    // `code` instead.
+    fn paths(code: &str, amount: &str) -> String {
+        let fixture = include_str!("../../fixtures/payment_path.json");
+        let other = fixture
+            .replace(
+                r#""source_amount": "20.0000000""#,
+                &format!(r#""source_amount": "{}""#, amount),
+            )
+            .replace(
+                r#""source_asset_code": "USD""#,
+                &format!(r#""source_asset_code": "{}""#, code),
+            );
+        format!(
+            r#"{{ "_embedded": {{ "records": [{}, {}] }} }}"#,
+            fixture, other
+        )
+    }
+
+    fn client() -> MockClient {
+        MockClient::new()
+            .with_json("/paths*", &paths("USD", "19.0000000"))
+            .with_json("/accounts/*", include_str!("../../fixtures/account.json"))
+    }
+
+    #[test]
+    fn it_quotes_the_cheapest_path_from_the_send_asset() {
+        let quote = payment().with_max_slippage(100).quote(&client()).unwrap();
+        assert_eq!(*quote.path().source_amount(), Amount::new(190_000_000));
+        assert_eq!(quote.send_max(), Amount::new(191_900_000));
+
+        let client = MockClient::new().with_json("/paths*", &paths("GBP", "1.0000000"));
+        let quote = payment().quote(&client).unwrap();
+        assert_eq!(quote.send_max(), Amount::new(200_000_000));
+    }
+
+    #[test]
+    fn it_fails_when_there_is_no_path() {
+        let client =
+            MockClient::new().with_json("/paths*", r#"{ "_embedded": { "records": [] } }"#);
+        match payment().quote(&client).unwrap_err() {
+            Error::NoPath => (),
+            error => panic!("expected no path, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn it_rounds_the_slippage_bound_down() {
+        assert_eq!(with_slippage(Amount::new(19_999), 50), Amount::new(20_098));
+        assert_eq!(
+            with_slippage(Amount::new(i64::MAX), 1),
+            Amount::new(i64::MAX)
+        );
+    }
+
+    #[test]
+    fn it_builds_a_strict_receive_path_payment() {
+        let payment = payment().with_fee(300).with_max_slippage(100);
+        let quote = payment.quote(&client()).unwrap();
+        let envelope = payment.envelope(&quote, 42).unwrap();
+        let envelope = TransactionEnvelope::from_base64(&envelope.to_base64()).unwrap();
+        assert_eq!(envelope.source_account().account_id(), *SOURCE);
+        assert_eq!(envelope.fee(), 300);
+        assert_eq!(envelope.sequence(), 42);
+        match *envelope.operations()[0].body() {
+            OperationBody::PathPaymentStrictReceive {
+                ref send_asset,
+                send_max,
+                ref destination,
+                ref dest_asset,
+                dest_amount,
+                ref path,
+            } => {
+                assert_eq!(*send_asset, usd());
+                assert_eq!(send_max, Amount::new(191_900_000));
+                assert_eq!(destination.account_id(), *DESTINATION);
+                assert_eq!(*dest_asset, eur());
+                assert_eq!(dest_amount, Amount::new(200_000_000));
+                assert_eq!(
+                    *path,
+                    vec![AssetIdentifier::alphanum4("1", ISSUER).unwrap()]
+                );
+            }
+            ref body => panic!("expected a path payment, got {:?}", body),
+        }
+    }
+
+    #[test]
+    fn it_signs_and_submits_the_payment() {
+        let client = client().with_json(
+            "/transactions",
+            include_str!("../../fixtures/submitted_transaction.json"),
+        );
+        let keypair = Keypair::random().unwrap();
+        let submitted =
+            execute_path_payment(&client, &Network::test(), &keypair, &payment()).unwrap();
+        assert_eq!(submitted.ledger(), 1_524_302);
+        assert_eq!(client.hits("/accounts/*"), 1);
+        assert_eq!(client.hits("/transactions"), 1);
+    }
+
+    #[test]
+    fn it_reports_a_price_that_moved_past_the_bound() {
+        let client = client().with_response(
+            "/transactions",
+            400,
+            r#"{
+                "type": "https://stellar.org/horizon-errors/transaction_failed",
+                "title": "Transaction Failed",
+                "status": 400,
+                "detail": "The transaction failed when submitted to the stellar network.",
+                "extras": {
+                    "result_codes": {
+                        "transaction": "tx_failed",
+                        "operations": ["op_over_source_max"]
+                    }
+                }
+            }"#,
+        );
+        let keypair = Keypair::random().unwrap();
+        let payment = payment().with_max_slippage(100);
+        match execute_path_payment(&client, &Network::test(), &keypair, &payment).unwrap_err() {
+            PathPaymentError::SlippageExceeded(send_max) => {
+                assert_eq!(send_max, Amount::new(191_900_000))
+            }
+            error => panic!("expected slippage, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn it_reports_which_operation_was_rejected() {
+        let client = client().with_response(
+            "/transactions",
+            400,
+            r#"{
+                "type": "https://stellar.org/horizon-errors/transaction_failed",
+                "title": "Transaction Failed",
+                "status": 400,
+                "detail": "The transaction failed when submitted to the stellar network.",
+                "extras": {
+                    "result_codes": {
+                        "transaction": "tx_failed",
+                        "operations": ["op_underfunded"]
+                    }
+                }
+            }"#,
+        );
+        let keypair = Keypair::random().unwrap();
+        let error = execute_path_payment(&client, &Network::test(), &keypair, &payment());
+        match error.unwrap_err() {
+            PathPaymentError::Rejected {
+                operation,
+                ref reasons,
+            } => {
+                assert_eq!(operation, Some(0));
+                assert_eq!(reasons.transaction(), "tx_failed");
+            }
+            error => panic!("expected a rejection, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn it_tells_failed_requests_apart_from_rejections() {
+        let client = MockClient::new()
+            .with_json("/paths*", &paths("USD", "19.0000000"))
+            .with_response(
+                "/accounts/*",
+                404,
+                r#"{
+                    "type": "https://stellar.org/horizon-errors/not_found",
+                    "title": "Resource Missing",
+                    "status": 404,
+                    "detail": "The resource at the url requested was not found."
+                }"#,
+            );
+        let keypair = Keypair::random().unwrap();
+        match execute_path_payment(&client, &Network::test(), &keypair, &payment()).unwrap_err() {
+            PathPaymentError::Network(_) => (),
+            error => panic!("expected a failed request, got {:?}", error),
+        }
+
+        let client =
+            MockClient::new().with_json("/paths*", r#"{ "_embedded": { "records": [] } }"#);
+        match execute_path_payment(&client, &Network::test(), &keypair, &payment()).unwrap_err() {
+            PathPaymentError::NoPath => (),
+            error => panic!("expected no path, got {:?}", error),
+        }
+    }
+}
@@ -0,0 +1,679 @@
+//! Watches the payments arriving at an account, leaving out dust and spam.
+//!
+//! Any account can send a payment to any other, and exchanges see a steady
+//! trickle of tiny payments, payments in assets they don't list, and memos
+//! that advertise scams. A `PaymentFilter` says which payments are worth
+//! acting on, by a minimum amount for each asset, by allowing or denying
+//! assets, and by matching the memo against patterns. A `PaymentWatcher`
+//! applies it to the payments of an account as they arrive, so that every
+//! consumer doesn't have to.
+//!
+//! Memos are on transactions rather than payments, so the watcher looks up
+//! the transaction of each payment that makes it past the asset and amount
+//! checks.
+//!
+//! Listing payments rather than watching them for one account is narrowed
+//! down with `FilteredPayments`, which keeps the payments of an asset, to an
+//! account or above an amount as they come out of an `Iter`.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use stellar_client::endpoint::{account, Cursor};
+//! use stellar_client::helpers::payments::{PaymentFilter, PaymentWatcher};
+//! use stellar_client::resources::{Amount, AssetIdentifier};
+//! use stellar_client::sync::Client;
+//!
+//! let client = Client::horizon_test().unwrap();
+//! let account_id = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+//! let filter = PaymentFilter::new()
+//!     .with_min_amount(AssetIdentifier::native(), Amount::new(10_000_000))
+//!     .with_blocked_memo("*http*");
+//! let payments = client
+//!     .stream(account::Payments::new(account_id.into()).with_cursor("now"))
+//!     .unwrap();
+//! for payment in PaymentWatcher::new(&client, account_id.into(), payments).with_filter(filter) {
+//!     let payment = payment.unwrap();
+//!     println!("{} {:?} from {}", payment.amount(), payment.asset(), payment.from());
+//! }
+//! ```
+use chrono::{DateTime, Utc};
+use endpoint::transaction;
+use error::Result;
+use resources::{AccountId, Amount, AssetIdentifier, Memo, Operation, OperationKind};
+use std::collections::{HashMap, HashSet};
+use sync::Requester;
+
+/// Which payments a `PaymentWatcher` passes on. A new filter lets every
+/// payment through.
+#[derive(Debug, Default, Clone)]
+pub struct PaymentFilter {
+    min_amounts: HashMap<AssetIdentifier, Amount>,
+    allowed: Option<HashSet<AssetIdentifier>>,
+    denied: HashSet<AssetIdentifier>,
+    memo_patterns: Vec<String>,
+    blocked_memo_patterns: Vec<String>,
+}
+
+impl PaymentFilter {
+    /// A filter that lets every payment through.
+    pub fn new() -> PaymentFilter {
+        PaymentFilter::default()
+    }
+
+    /// Leaves out payments of `asset` smaller than `amount`.
+    pub fn with_min_amount(mut self, asset: AssetIdentifier, amount: Amount) -> Self {
+        self.min_amounts.insert(asset, amount);
+        self
+    }
+
+    /// Lets payments of `asset` through. Once any asset is allowed, payments
+    /// of assets that aren't are left out.
+    pub fn with_allowed_asset(mut self, asset: AssetIdentifier) -> Self {
+        self.allowed.get_or_insert_with(HashSet::new).insert(asset);
+        self
+    }
+
+    /// Leaves out payments of `asset`, even when it is allowed.
+    pub fn with_denied_asset(mut self, asset: AssetIdentifier) -> Self {
+        self.denied.insert(asset);
+        self
+    }
+
+    /// Lets through only payments whose memo matches `pattern`, or one of
+    /// the other patterns given this way. A `*` in the pattern matches any
+    /// run of characters. Id memos are matched as decimal numbers, hash and
+    /// return memos as they are encoded by horizon, and a missing memo as an
+    /// empty string.
+    pub fn with_memo(mut self, pattern: &str) -> Self {
+        self.memo_patterns.push(pattern.to_string());
+        self
+    }
+
+    /// Leaves out payments whose memo matches `pattern`, matched as with
+    /// `with_memo`.
+    pub fn with_blocked_memo(mut self, pattern: &str) -> Self {
+        self.blocked_memo_patterns.push(pattern.to_string());
+        self
+    }
+
+    /// Whether the filter lets the payment through.
+    pub fn allows(&self, payment: &IncomingPayment) -> bool {
+        self.allows_amount(&payment.asset, payment.amount) && self.allows_memo(&payment.memo)
+    }
+
+    fn allows_amount(&self, asset: &AssetIdentifier, amount: Amount) -> bool {
+        if self.denied.contains(asset) {
+            return false;
+        }
+        if let Some(ref allowed) = self.allowed {
+            if !allowed.contains(asset) {
+                return false;
+            }
+        }
+        match self.min_amounts.get(asset) {
+            Some(min) => amount.to_stroops() >= min.to_stroops(),
+            None => true,
+        }
+    }
+
+    fn allows_memo(&self, memo: &Memo) -> bool {
+        let text = memo_text(memo);
+        let matches_any = |patterns: &[String]| patterns.iter().any(|pattern| glob(pattern, &text));
+        (self.memo_patterns.is_empty() || matches_any(&self.memo_patterns))
+            && !matches_any(&self.blocked_memo_patterns)
+    }
+}
+
+/// A payment that arrived at the watched account.
+#[derive(Debug, Clone)]
+pub struct IncomingPayment {
+    operation_id: i64,
+    paging_token: String,
+    transaction_hash: String,
+    ledger: u32,
+    created_at: DateTime<Utc>,
+    from: AccountId,
+    asset: AssetIdentifier,
+    amount: Amount,
+    to_muxed_id: Option<u64>,
+    memo: Memo,
+}
+
+impl IncomingPayment {
+    /// The id of the operation that made the payment.
+    pub fn operation_id(&self) -> i64 {
+        self.operation_id
+    }
+
+    /// A paging token suitable for resuming the watch after this payment.
+    pub fn paging_token(&self) -> &str {
+        &self.paging_token
+    }
+
+    /// The hash of the transaction that made the payment.
+    pub fn transaction_hash(&self) -> &str {
+        &self.transaction_hash
+    }
+
+    /// The ledger the payment was made in.
+    pub fn ledger(&self) -> u32 {
+        self.ledger
+    }
+
+    /// The time the ledger containing the payment closed.
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    /// The account that sent the payment.
+    pub fn from(&self) -> &AccountId {
+        &self.from
+    }
+
+    /// The asset that arrived.
+    pub fn asset(&self) -> &AssetIdentifier {
+        &self.asset
+    }
+
+    /// The amount that arrived.
+    pub fn amount(&self) -> Amount {
+        self.amount
+    }
+
+    /// The id of the muxed account the payment was sent to, when it was
+    /// sent to one.
+    pub fn to_muxed_id(&self) -> Option<u64> {
+        self.to_muxed_id
+    }
+
+    /// The memo of the transaction that made the payment.
+    pub fn memo(&self) -> &Memo {
+        &self.memo
+    }
+}
+
+/// Turns the payments of an account into the `IncomingPayment`s that a
+/// filter lets through. Payments the account sent, and those from failed
+/// transactions, are left out. Account creations funding the account count
+/// as payments of lumens.
+#[derive(Debug)]
+pub struct PaymentWatcher<C, I> {
+    client: C,
+    account_id: AccountId,
+    payments: I,
+    filter: PaymentFilter,
+}
+
+impl<C, I> PaymentWatcher<C, I>
+where
+    C: Requester,
+    I: Iterator<Item = Result<Operation>>,
+{
+    /// Watches `payments`, usually a stream of `account::Payments`, for
+    /// payments to `account_id`. `client` looks up the transactions of the
+    /// payments.
+    pub fn new(client: C, account_id: AccountId, payments: I) -> Self {
+        PaymentWatcher {
+            client,
+            account_id,
+            payments,
+            filter: PaymentFilter::new(),
+        }
+    }
+
+    /// Passes on only the payments that `filter` lets through.
+    pub fn with_filter(mut self, filter: PaymentFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    // The sender, asset, amount and muxed id of an operation that paid the
+    // watched account.
+    fn incoming(
+        &self,
+        operation: &Operation,
+    ) -> Option<(AccountId, AssetIdentifier, Amount, Option<u64>)> {
+        match *operation.kind() {
+            OperationKind::Payment(ref payment) if *payment.to() == self.account_id => Some((
+                payment.from().clone(),
+                payment.asset().clone(),
+                payment.amount(),
+                payment.to_muxed_id(),
+            )),
+            OperationKind::PathPayment(ref payment) if *payment.to() == self.account_id => Some((
+                payment.from().clone(),
+                payment.destination_asset().clone(),
+                payment.destination_amount(),
+                payment.to_muxed_id(),
+            )),
+            OperationKind::CreateAccount(ref create) if *create.account() == self.account_id => {
+                Some((
+                    create.funder().clone(),
+                    AssetIdentifier::native(),
+                    create.starting_balance(),
+                    None,
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<C, I> Iterator for PaymentWatcher<C, I>
+where
+    C: Requester,
+    I: Iterator<Item = Result<Operation>>,
+{
+    type Item = Result<IncomingPayment>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let operation = match self.payments.next()? {
+                Ok(operation) => operation,
+                Err(err) => return Some(Err(err)),
+            };
+            if !operation.transaction_successful() {
+                continue;
+            }
+            let (from, asset, amount, to_muxed_id) = match self.incoming(&operation) {
+                Some(incoming) => incoming,
+                None => continue,
+            };
+            // The cheap checks go first so that dust doesn't cost a request.
+            if !self.filter.allows_amount(&asset, amount) {
+                continue;
+            }
+            let transaction = match self
+                .client
+                .request(transaction::Details::new(operation.transaction_hash()))
+            {
+                Ok(transaction) => transaction,
+                Err(err) => return Some(Err(err)),
+            };
+            if !self.filter.allows_memo(transaction.memo()) {
+                continue;
+            }
+            return Some(Ok(IncomingPayment {
+                operation_id: operation.id(),
+                paging_token: operation.paging_token().to_string(),
+                transaction_hash: operation.transaction_hash().to_string(),
+                ledger: transaction.ledger(),
+                created_at: operation.created_at(),
+                from,
+                asset,
+                amount,
+                to_muxed_id,
+                memo: transaction.memo().clone(),
+            }));
+        }
+    }
+}
+
+/// Narrows down the payments coming out of an iterator, usually an `Iter`
+/// over one of the payments endpoints, by what horizon can't filter them by.
+///
+/// Every payment is let through until a condition is added, and each
+/// condition leaves out more. Payments, path payments and account creations
+/// are judged by the asset and amount that arrived at the receiving account.
+/// Once any condition has been added, account merges are left out, since
+/// horizon doesn't say how much they moved. Errors are passed on untouched.
+///
+/// # Examples
+///
+/// ```no_run
+/// use stellar_client::endpoint::payment;
+/// use stellar_client::helpers::payments::FilteredPayments;
+/// use stellar_client::resources::{Amount, AssetIdentifier};
+/// use stellar_client::sync::{Client, Iter};
+///
+/// let client = Client::horizon_test().unwrap();
+/// let usd = AssetIdentifier::alphanum4(
+///     "USD",
+///     "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG",
+/// ).unwrap();
+/// let payments = FilteredPayments::new(Iter::new(&client, payment::All::default()))
+///     .only_asset(usd)
+///     .only_incoming("GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4".into())
+///     .min_amount(Amount::new(1_000_000_000));
+/// for payment in payments {
+///     println!("{}", payment.unwrap().paging_token());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FilteredPayments<I> {
+    payments: I,
+    assets: Option<HashSet<AssetIdentifier>>,
+    receiver: Option<AccountId>,
+    min_amount: Option<Amount>,
+}
+
+impl<I> FilteredPayments<I>
+where
+    I: Iterator<Item = Result<Operation>>,
+{
+    /// Lets every payment out of `payments` through.
+    pub fn new(payments: I) -> Self {
+        FilteredPayments {
+            payments,
+            assets: None,
+            receiver: None,
+            min_amount: None,
+        }
+    }
+
+    /// Lets through only payments that deliver `asset`, or one of the other
+    /// assets given this way.
+    pub fn only_asset(mut self, asset: AssetIdentifier) -> Self {
+        self.assets.get_or_insert_with(HashSet::new).insert(asset);
+        self
+    }
+
+    /// Lets through only payments to `account_id`.
+    pub fn only_incoming(mut self, account_id: AccountId) -> Self {
+        self.receiver = Some(account_id);
+        self
+    }
+
+    /// Leaves out payments that deliver less than `amount`, whatever their
+    /// asset.
+    pub fn min_amount(mut self, amount: Amount) -> Self {
+        self.min_amount = Some(amount);
+        self
+    }
+
+    fn allows(&self, operation: &Operation) -> bool {
+        if self.assets.is_none() && self.receiver.is_none() && self.min_amount.is_none() {
+            return true;
+        }
+        let (to, asset, amount) = match received(operation) {
+            Some(received) => received,
+            None => return false,
+        };
+        self.assets
+            .as_ref()
+            .is_none_or(|assets| assets.contains(&asset))
+            && self.receiver.as_ref().is_none_or(|receiver| receiver == to)
+            && self.min_amount.is_none_or(|min| amount >= min)
+    }
+}
+
+impl<I> Iterator for FilteredPayments<I>
+where
+    I: Iterator<Item = Result<Operation>>,
+{
+    type Item = Result<Operation>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.payments.next()? {
+                Ok(ref operation) if !self.allows(operation) => continue,
+                result => return Some(result),
+            }
+        }
+    }
+}
+
+/// The receiver, asset and amount that an operation delivered, if it is
+/// one that pays.
+fn received(operation: &Operation) -> Option<(&AccountId, AssetIdentifier, Amount)> {
+    match *operation.kind() {
+        OperationKind::Payment(ref payment) => {
+            Some((payment.to(), payment.asset().clone(), payment.amount()))
+        }
+        OperationKind::PathPayment(ref payment) => Some((
+            payment.to(),
+            payment.destination_asset().clone(),
+            payment.destination_amount(),
+        )),
+        OperationKind::CreateAccount(ref create) => Some((
+            create.account(),
+            AssetIdentifier::native(),
+            create.starting_balance(),
+        )),
+        _ => None,
+    }
+}
+
+/// The memo as the text that memo patterns are matched against.
+pub(crate) fn memo_text(memo: &Memo) -> String {
+    match *memo {
+        Memo::Text(ref text) | Memo::Hash(ref text) | Memo::Return(ref text) => text.clone(),
+        Memo::Id(id) => id.to_string(),
+        Memo::None => String::new(),
+    }
+}
+
+/// Matches text against a pattern where `*` stands in for any run of
+/// characters.
+fn glob(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    if !text.starts_with(first) {
+        return false;
+    }
+    let mut rest = &text[first.len()..];
+    let parts: Vec<&str> = parts.collect();
+    match parts.split_last() {
+        None => rest.is_empty(),
+        Some((last, middle)) => {
+            for part in middle {
+                match rest.find(part) {
+                    Some(index) => rest = &rest[index + part.len()..],
+                    None => return false,
+                }
+            }
+            rest.len() >= last.len() && rest.ends_with(last)
+        }
+    }
+}
+
+#[cfg(test)]
+mod payment_watcher_tests {
+    use super::*;
+    use serde_json;
+    use test::MockClient;
+
+    const ACCOUNT_ID: &str = "GCEZWKCA5VLDNRLN3RPRJMRZOX3Z6G5CHCGSNFHEYVXM3XOJMDS674JZ";
+    const SENDER: &str = "GAKLBGHNHFQ3BMUYG5KU4BEWO6EYQHZHAXEWC33W34PH2RBHZDSQBD75";
+    const ISSUER: &str = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4";
+
+    fn payment(amount: &str) -> Operation {
+        let json = include_str!("../../fixtures/operations/payment.json").replace(
+            r#""amount": "200.0""#,
+            &format!(r#""amount": "{}""#, amount),
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    fn client(memo_fixture: &str) -> MockClient {
+        MockClient::new().with_json("/transactions/*", memo_fixture)
+    }
+
+    fn text_memo() -> &'static str {
+        include_str!("../../fixtures/transactions/transaction_memo_text.json")
+    }
+
+    fn watch(client: &MockClient, payments: Vec<Operation>, filter: PaymentFilter) -> Vec<Amount> {
+        PaymentWatcher::new(client, ACCOUNT_ID.into(), payments.into_iter().map(Ok))
+            .with_filter(filter)
+            .map(|payment| payment.unwrap().amount())
+            .collect()
+    }
+
+    #[test]
+    fn it_passes_on_payments_to_the_account() {
+        let client = client(text_memo());
+        let payment = PaymentWatcher::new(
+            &client,
+            ACCOUNT_ID.into(),
+            vec![Ok(payment("200.0"))].into_iter(),
+        )
+        .next()
+        .unwrap()
+        .unwrap();
+        assert_eq!(payment.amount(), Amount::new(2_000_000_000));
+        assert_eq!(payment.asset(), &AssetIdentifier::native());
+        assert_eq!(payment.memo(), &Memo::Text("hello".to_string()));
+        assert_eq!(payment.operation_id(), 58_402_965_295_104);
+        assert_eq!(payment.paging_token(), "58402965295104");
+    }
+
+    #[test]
+    fn it_leaves_out_payments_the_account_sent() {
+        let client = client(text_memo());
+        let sender = PaymentWatcher::new(
+            &client,
+            SENDER.into(),
+            vec![Ok(payment("200.0"))].into_iter(),
+        );
+        assert_eq!(sender.count(), 0);
+        assert!(client.requests().is_empty());
+    }
+
+    #[test]
+    fn it_leaves_out_dust_without_looking_it_up() {
+        let client = client(text_memo());
+        let filter = PaymentFilter::new()
+            .with_min_amount(AssetIdentifier::native(), Amount::new(10_000_000));
+        let amounts = watch(&client, vec![payment("0.0000001"), payment("1.0")], filter);
+        assert_eq!(amounts, vec![Amount::new(10_000_000)]);
+        assert_eq!(client.hits("/transactions/*"), 1);
+    }
+
+    #[test]
+    fn it_allows_and_denies_assets() {
+        let client = client(text_memo());
+        let foo = AssetIdentifier::alphanum4("FOO", ISSUER).unwrap();
+        let allowed = PaymentFilter::new().with_allowed_asset(foo.clone());
+        assert!(watch(&client, vec![payment("1.0")], allowed).is_empty());
+
+        let denied = PaymentFilter::new()
+            .with_allowed_asset(AssetIdentifier::native())
+            .with_denied_asset(AssetIdentifier::native());
+        assert!(watch(&client, vec![payment("1.0")], denied).is_empty());
+
+        let native = PaymentFilter::new().with_allowed_asset(AssetIdentifier::native());
+        assert_eq!(watch(&client, vec![payment("1.0")], native).len(), 1);
+    }
+
+    #[test]
+    fn it_matches_memos_against_patterns() {
+        let client = client(text_memo());
+        let required = PaymentFilter::new().with_memo("hel*");
+        assert_eq!(watch(&client, vec![payment("1.0")], required).len(), 1);
+
+        let blocked = PaymentFilter::new().with_blocked_memo("*ll*");
+        assert!(watch(&client, vec![payment("1.0")], blocked).is_empty());
+
+        let id_client = self::client(include_str!(
+            "../../fixtures/transactions/transaction_memo_id.json"
+        ));
+        let digits = PaymentFilter::new().with_memo("19292920");
+        assert_eq!(watch(&id_client, vec![payment("1.0")], digits).len(), 1);
+    }
+
+    #[test]
+    fn it_globs() {
+        assert!(glob("*", ""));
+        assert!(glob("a*c", "abc"));
+        assert!(!glob("a*c", "abd"));
+        assert!(glob("*http*", "visit http://spam"));
+        assert!(!glob("ab*ba", "aba"));
+        assert!(!glob("", "a"));
+    }
+}
+
+#[cfg(test)]
+mod filtered_payments_tests {
+    use super::*;
+    use error::Error;
+    use resources::Resource;
+
+    const RECEIVER: &str = "GCEZWKCA5VLDNRLN3RPRJMRZOX3Z6G5CHCGSNFHEYVXM3XOJMDS674JZ";
+
+    fn operations() -> Vec<Operation> {
+        vec![
+            include_str!("../../fixtures/operations/payment.json"),
+            include_str!("../../fixtures/operations/path_payment_strict_receive.json"),
+            include_str!("../../fixtures/operations/create_account.json"),
+            include_str!("../../fixtures/operations/account_merge.json"),
+        ]
+        .into_iter()
+        .map(|json| Operation::from_json_str(json).unwrap())
+        .collect()
+    }
+
+    fn filter<F>(build: F) -> Vec<String>
+    where
+        F: FnOnce(
+            FilteredPayments<::std::vec::IntoIter<Result<Operation>>>,
+        ) -> FilteredPayments<::std::vec::IntoIter<Result<Operation>>>,
+    {
+        let payments: Vec<Result<Operation>> = operations().into_iter().map(Ok).collect();
+        build(FilteredPayments::new(payments.into_iter()))
+            .map(|operation| operation.unwrap().paging_token().to_string())
+            .collect()
+    }
+
+    fn tokens(indices: &[usize]) -> Vec<String> {
+        let operations = operations();
+        indices
+            .iter()
+            .map(|&index| operations[index].paging_token().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn it_lets_everything_through_without_conditions() {
+        assert_eq!(filter(|payments| payments), tokens(&[0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn it_filters_by_the_asset_delivered() {
+        let eur = AssetIdentifier::alphanum4(
+            "EUR",
+            "GCQPYGH4K57XBDENKKX55KDTWOTK5WDWRQOH2LHEDX3EKVIQRLMESGBG",
+        )
+        .unwrap();
+        assert_eq!(
+            filter(|payments| payments.only_asset(eur.clone())),
+            tokens(&[1])
+        );
+        assert_eq!(
+            filter(|payments| payments
+                .only_asset(eur)
+                .only_asset(AssetIdentifier::native())),
+            tokens(&[0, 1, 2])
+        );
+    }
+
+    #[test]
+    fn it_filters_by_the_receiver_and_amount() {
+        assert_eq!(
+            filter(|payments| payments.only_incoming(RECEIVER.into())),
+            tokens(&[0, 2])
+        );
+        assert_eq!(
+            filter(|payments| payments.min_amount(Amount::new(1_000_000_000))),
+            tokens(&[0, 2])
+        );
+        assert_eq!(
+            filter(|payments| payments
+                .only_incoming(RECEIVER.into())
+                .min_amount(Amount::new(10_000_000_000))),
+            tokens(&[2])
+        );
+    }
+
+    #[test]
+    fn it_passes_errors_on() {
+        let payments = vec![Err(Error::ServerError(None)), Ok(operations().remove(3))];
+        let mut filtered = FilteredPayments::new(payments.into_iter()).min_amount(Amount::new(1));
+        match filtered.next() {
+            Some(Err(Error::ServerError(None))) => (),
+            other => panic!("expected the error, got {:?}", other),
+        }
+        assert!(filtered.next().is_none());
+    }
+}
@@ -0,0 +1,583 @@
+//! Pays many recipients at once, such as for an airdrop or a payroll run.
+//!
+//! A `PayoutBatch` takes a list of payouts, each a destination, an asset, an
+//! amount and an optional memo, and packs them into as few transactions as
+//! it can. A transaction holds at most a hundred operations and a single
+//! memo, so payouts with different memos never share one. With the `keys`
+//! feature enabled, `PayoutBatch::submit` then sends the transactions one
+//! after another from the signer's account and reports where each payout
+//! ended up.
+//!
+//! The sequence number is loaded once and counted up locally as the
+//! transactions go in. After a transaction fails it is loaded again, since a
+//! failed transaction may or may not have used its number. A submission
+//! that times out, or that horizon turns away because it is busy, is sent
+//! again as the same envelope, which the network never applies twice. One
+//! rejected with `tx_bad_seq` is built again with a fresh sequence number.
+//! Both are retried as the batch's `RetryPolicy` allows.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use stellar_client::helpers::payout::{Payout, PayoutBatch, PayoutStatus};
+//! use stellar_client::keys::Keypair;
+//! use stellar_client::resources::{Amount, AssetIdentifier, Memo};
+//! use stellar_client::sync::Client;
+//! use stellar_client::Network;
+//!
+//! let client = Client::horizon_test().unwrap();
+//! let keypair = Keypair::from_secret_seed(
+//!     "SAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6NKI",
+//! ).unwrap();
+//! let batch = PayoutBatch::new(vec![
+//!     Payout::new(
+//!         "GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM".into(),
+//!         AssetIdentifier::native(),
+//!         Amount::new(10_000_000),
+//!     ),
+//!     Payout::new(
+//!         "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4".into(),
+//!         AssetIdentifier::native(),
+//!         Amount::new(20_000_000),
+//!     )
+//!     .with_memo(Memo::Id(42)),
+//! ]);
+//!
+//! for outcome in batch.submit(&client, &Network::test(), &keypair).unwrap() {
+//!     match *outcome.status() {
+//!         PayoutStatus::Paid { ref hash, .. } => println!("paid in {}", hash),
+//!         ref status => println!("{} not paid: {:?}", outcome.payout().destination(), status),
+//!     }
+//! }
+//! ```
+#[cfg(feature = "keys")]
+use super::transaction;
+#[cfg(feature = "keys")]
+use error::{Error, Result};
+#[cfg(feature = "keys")]
+use keys::Signer;
+#[cfg(feature = "keys")]
+use network::Network;
+#[cfg(feature = "keys")]
+use resources::envelope::{EnvelopeBuilder, MuxedAccount, Operation, OperationBody};
+use resources::{AccountId, Amount, AssetIdentifier, Memo};
+#[cfg(feature = "keys")]
+use resources::{FailureReasons, TransactionEnvelope};
+#[cfg(feature = "keys")]
+use std::thread;
+use sync::RetryPolicy;
+#[cfg(feature = "keys")]
+use sync::{self, Requester};
+
+/// A payment of an amount of an asset to one recipient.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payout {
+    destination: AccountId,
+    asset: AssetIdentifier,
+    amount: Amount,
+    memo: Memo,
+}
+
+impl Payout {
+    /// Pays `amount` of `asset` to `destination`, without a memo.
+    pub fn new(destination: AccountId, asset: AssetIdentifier, amount: Amount) -> Payout {
+        Payout {
+            destination,
+            asset,
+            amount,
+            memo: Memo::None,
+        }
+    }
+
+    /// Sends the payout in a transaction with the memo, which exchanges and
+    /// other shared accounts use to tell who it is for.
+    pub fn with_memo(mut self, memo: Memo) -> Payout {
+        self.memo = memo;
+        self
+    }
+
+    /// The account paid.
+    pub fn destination(&self) -> &AccountId {
+        &self.destination
+    }
+
+    /// The asset paid.
+    pub fn asset(&self) -> &AssetIdentifier {
+        &self.asset
+    }
+
+    /// How much is paid.
+    pub fn amount(&self) -> Amount {
+        self.amount
+    }
+
+    /// The memo of the transaction the payout is sent in.
+    pub fn memo(&self) -> &Memo {
+        &self.memo
+    }
+}
+
+/// Where a payout ended up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PayoutStatus {
+    /// The payout was made.
+    Paid {
+        /// The hash of the transaction that paid it.
+        hash: String,
+        /// The ledger the transaction was included in.
+        ledger: u32,
+    },
+    /// The transaction holding the payout could not be built or sent, or
+    /// failed, for the given reason. Nothing in it was paid. For a
+    /// transaction the network rejected, the reason is the result code of
+    /// the payout's own operation, or the transaction's when the operation
+    /// itself was fine.
+    Failed(String),
+    /// The transaction holding the payout, whose hash this is, was sent but
+    /// still hadn't shown up in horizon once the retries ran out. It may
+    /// still make it into a ledger, so look the hash up before paying again.
+    Unconfirmed(String),
+}
+
+/// A payout along with where it ended up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayoutOutcome {
+    payout: Payout,
+    status: PayoutStatus,
+}
+
+impl PayoutOutcome {
+    /// The payout.
+    pub fn payout(&self) -> &Payout {
+        &self.payout
+    }
+
+    /// Where it ended up.
+    pub fn status(&self) -> &PayoutStatus {
+        &self.status
+    }
+}
+
+/// A list of payouts to send together.
+#[derive(Debug, Clone)]
+pub struct PayoutBatch {
+    payouts: Vec<Payout>,
+    retries: RetryPolicy,
+}
+
+impl PayoutBatch {
+    /// Sends the payouts, retrying each transaction up to three times.
+    pub fn new(payouts: Vec<Payout>) -> PayoutBatch {
+        PayoutBatch {
+            payouts,
+            retries: RetryPolicy::new(3),
+        }
+    }
+
+    /// Sets how often, and after how long, a transaction is tried again.
+    pub fn with_retries(mut self, retries: RetryPolicy) -> PayoutBatch {
+        self.retries = retries;
+        self
+    }
+
+    /// The payouts, in the order they were given.
+    pub fn payouts(&self) -> &[Payout] {
+        &self.payouts
+    }
+
+    /// The payouts each transaction will hold, in the order the
+    /// transactions are sent. Each transaction starts at the first payout
+    /// that isn't in an earlier one, and takes the payouts after it with the
+    /// same memo until it is full.
+    pub fn transactions(&self) -> Vec<Vec<&Payout>> {
+        self.plan()
+            .into_iter()
+            .map(|indexes| indexes.into_iter().map(|i| &self.payouts[i]).collect())
+            .collect()
+    }
+
+    // The indexes of the payouts in each transaction.
+    fn plan(&self) -> Vec<Vec<usize>> {
+        let mut planned = vec![false; self.payouts.len()];
+        let mut transactions = Vec::new();
+        for first in 0..self.payouts.len() {
+            if planned[first] {
+                continue;
+            }
+            let memo = &self.payouts[first].memo;
+            let indexes: Vec<usize> = (first..self.payouts.len())
+                .filter(|&i| !planned[i] && self.payouts[i].memo == *memo)
+                .take(super::MAX_OPERATIONS)
+                .collect();
+            for &i in &indexes {
+                planned[i] = true;
+            }
+            transactions.push(indexes);
+        }
+        transactions
+    }
+}
+
+#[cfg(feature = "keys")]
+impl PayoutBatch {
+    /// Sends every payout from the account `signer` signs for, and reports
+    /// where each one ended up, in the order they were given. A payout that
+    /// wasn't made is reported rather than returned as an error, so the
+    /// error is only for a batch that couldn't be started, such as when the
+    /// account can't be loaded.
+    pub fn submit<C, S>(
+        &self,
+        client: &C,
+        network: &Network,
+        signer: &S,
+    ) -> Result<Vec<PayoutOutcome>>
+    where
+        C: Requester,
+        S: Signer,
+    {
+        let source = AccountId::from(signer.account_id());
+        let mut sequence = Some(transaction::next_sequence(client, &source)?);
+        let mut statuses = vec![None; self.payouts.len()];
+        for indexes in self.plan() {
+            let payouts: Vec<_> = indexes.iter().map(|&i| &self.payouts[i]).collect();
+            let sent = self.send(client, network, signer, &source, &mut sequence, &payouts);
+            for (i, status) in indexes.into_iter().zip(sent) {
+                statuses[i] = Some(status);
+            }
+        }
+        Ok(self
+            .payouts
+            .iter()
+            .cloned()
+            .zip(statuses)
+            .map(|(payout, status)| PayoutOutcome {
+                payout,
+                status: status.expect("every payout is planned into a transaction"),
+            })
+            .collect())
+    }
+
+    // Sends one transaction, coming back with the status of each of its
+    // payouts. `sequence` is the number the next transaction uses, or `None`
+    // when it has to be loaded again.
+    fn send<C, S>(
+        &self,
+        client: &C,
+        network: &Network,
+        signer: &S,
+        source: &AccountId,
+        sequence: &mut Option<i64>,
+        payouts: &[&Payout],
+    ) -> Vec<PayoutStatus>
+    where
+        C: Requester,
+        S: Signer,
+    {
+        let failed = |reason: String| vec![PayoutStatus::Failed(reason); payouts.len()];
+        let mut attempt = 0;
+        loop {
+            let number = match sequence.take() {
+                Some(number) => number,
+                None => match transaction::next_sequence(client, source) {
+                    Ok(number) => number,
+                    Err(err) => return failed(err.to_string()),
+                },
+            };
+            let mut envelope = match envelope(source, number, payouts) {
+                Ok(envelope) => envelope,
+                Err(err) => {
+                    // Nothing was sent, so the number is still free.
+                    *sequence = Some(number);
+                    return failed(err.to_string());
+                }
+            };
+            if let Err(err) = envelope.sign(network, signer) {
+                *sequence = Some(number);
+                return failed(err.to_string());
+            }
+
+            match self.submit_envelope(client, network, &envelope, &mut attempt) {
+                Ok(submitted) => {
+                    *sequence = number.checked_add(1);
+                    return vec![
+                        PayoutStatus::Paid {
+                            hash: submitted.hash().to_string(),
+                            ledger: submitted.ledger(),
+                        };
+                        payouts.len()
+                    ];
+                }
+                Err(Error::BadResponse(ref err)) if is_bad_sequence(err.result_codes()) => {
+                    match self.retries.delay(attempt, None) {
+                        Some(delay) => {
+                            attempt += 1;
+                            thread::sleep(delay);
+                        }
+                        None => return failed(err.to_string()),
+                    }
+                }
+                Err(Error::BadResponse(ref err)) if err.result_codes().is_some() => {
+                    let reasons = err.result_codes().expect("checked by the guard");
+                    return (0..payouts.len())
+                        .map(|i| match reasons.operations().get(i) {
+                            Some(code) if code != "op_success" => {
+                                PayoutStatus::Failed(code.clone())
+                            }
+                            _ => PayoutStatus::Failed(reasons.transaction().to_string()),
+                        })
+                        .collect();
+                }
+                Err(Error::TransactionTimeout(hash)) => {
+                    return vec![PayoutStatus::Unconfirmed(hash); payouts.len()];
+                }
+                Err(err) => return failed(err.to_string()),
+            }
+        }
+    }
+
+    // Submits the envelope, sending it again while horizon times out or is
+    // too busy to take it.
+    fn submit_envelope<C>(
+        &self,
+        client: &C,
+        network: &Network,
+        envelope: &TransactionEnvelope,
+        attempt: &mut u32,
+    ) -> Result<::resources::SubmittedTransaction>
+    where
+        C: Requester,
+    {
+        let envelope_xdr = envelope.to_base64();
+        loop {
+            let result = sync::submit_transaction(client, &envelope_xdr, network);
+            let delay = match result {
+                Err(ref err) if is_transient(err) => {
+                    self.retries.delay(*attempt, err.retry_after())
+                }
+                _ => None,
+            };
+            match delay {
+                Some(delay) => {
+                    *attempt += 1;
+                    thread::sleep(delay);
+                }
+                None => return result,
+            }
+        }
+    }
+}
+
+#[cfg(feature = "keys")]
+fn envelope(
+    source: &AccountId,
+    sequence: i64,
+    payouts: &[&Payout],
+) -> ::std::result::Result<TransactionEnvelope, ::xdr::XdrError> {
+    let memo = payouts
+        .first()
+        .map_or(Memo::None, |payout| payout.memo.clone());
+    payouts
+        .iter()
+        .map(|payout| {
+            Operation::new(OperationBody::Payment {
+                destination: MuxedAccount::new(&payout.destination, None),
+                asset: payout.asset.clone(),
+                amount: payout.amount,
+            })
+        })
+        .fold(
+            EnvelopeBuilder::new(MuxedAccount::new(source, None), sequence).with_memo(memo),
+            EnvelopeBuilder::with_operation,
+        )
+        .build()
+}
+
+/// Whether sending the same envelope again may get it in.
+#[cfg(feature = "keys")]
+fn is_transient(err: &Error) -> bool {
+    match *err {
+        Error::TransactionTimeout(_) | Error::ServiceUnavailable(_, _) => true,
+        Error::BadResponse(ref inner) => inner.is_rate_limit_exceeded(),
+        _ => false,
+    }
+}
+
+#[cfg(feature = "keys")]
+fn is_bad_sequence(reasons: Option<&FailureReasons>) -> bool {
+    reasons.map(FailureReasons::transaction) == Some("tx_bad_seq")
+}
+
+#[cfg(test)]
+mod payout_tests {
+    use super::*;
+
+    const ALICE: &str = "GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM";
+    const BOB: &str = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4";
+
+    fn payout(destination: &str) -> Payout {
+        Payout::new(
+            destination.into(),
+            AssetIdentifier::native(),
+            Amount::new(10_000_000),
+        )
+    }
+
+    #[test]
+    fn it_packs_payouts_with_the_same_memo_together() {
+        let mut payouts = vec![payout(BOB).with_memo(Memo::Id(7))];
+        payouts.extend((0..150).map(|_| payout(ALICE)));
+        payouts.push(payout(BOB).with_memo(Memo::Id(7)));
+        let batch = PayoutBatch::new(payouts);
+        let sizes: Vec<_> = batch.transactions().iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![2, 100, 50]);
+        assert!(batch.transactions()[0]
+            .iter()
+            .all(|payout| payout.memo() == &Memo::Id(7)));
+    }
+
+    #[cfg(feature = "keys")]
+    mod submit {
+        use super::*;
+        use keys::Keypair;
+        use std::time::Duration;
+        use test::MockClient;
+
+        fn failed(transaction: &str, operations: &str) -> String {
+            format!(
+                r#"{{
+                    "type": "https://stellar.org/horizon-errors/transaction_failed",
+                    "title": "Transaction Failed",
+                    "status": 400,
+                    "detail": "The transaction failed when submitted to the stellar network.",
+                    "extras": {{
+                        "result_codes": {{
+                            "transaction": "{}",
+                            "operations": [{}]
+                        }}
+                    }}
+                }}"#,
+                transaction, operations
+            )
+        }
+
+        fn no_delay(retries: u32) -> RetryPolicy {
+            RetryPolicy::new(retries).with_base_delay(Duration::from_millis(0))
+        }
+
+        #[test]
+        fn it_pays_every_recipient_loading_the_sequence_once() {
+            let client = MockClient::new()
+                .with_json("/accounts/*", include_str!("../../fixtures/account.json"))
+                .with_json(
+                    "/transactions",
+                    include_str!("../../fixtures/submitted_transaction.json"),
+                );
+            let keypair = Keypair::random().unwrap();
+            let batch = PayoutBatch::new((0..101).map(|_| payout(ALICE)).collect());
+            let outcomes = batch.submit(&client, &Network::test(), &keypair).unwrap();
+            assert_eq!(outcomes.len(), 101);
+            assert!(outcomes.iter().all(|outcome| match *outcome.status() {
+                PayoutStatus::Paid { ledger, .. } => ledger == 1_524_302,
+                _ => false,
+            }));
+            assert_eq!(client.hits("/transactions"), 2);
+            assert_eq!(client.hits("/accounts/*"), 1);
+        }
+
+        #[test]
+        fn it_reports_the_operation_that_failed_each_payout() {
+            let client = MockClient::new()
+                .with_json("/accounts/*", include_str!("../../fixtures/account.json"))
+                .with_response(
+                    "/transactions",
+                    400,
+                    &failed("tx_failed", r#""op_success", "op_no_trust""#),
+                );
+            let keypair = Keypair::random().unwrap();
+            let batch = PayoutBatch::new(vec![
+                payout(ALICE),
+                payout(BOB),
+                payout(ALICE).with_memo(Memo::Id(1)),
+            ]);
+            let outcomes = batch.submit(&client, &Network::test(), &keypair).unwrap();
+            let statuses: Vec<_> = outcomes.iter().map(PayoutOutcome::status).collect();
+            assert_eq!(
+                statuses,
+                vec![
+                    &PayoutStatus::Failed("tx_failed".to_string()),
+                    &PayoutStatus::Failed("op_no_trust".to_string()),
+                    &PayoutStatus::Failed("tx_failed".to_string()),
+                ]
+            );
+            assert_eq!(outcomes[1].payout().destination(), &BOB);
+            // The sequence number is loaded again after each failure.
+            assert_eq!(client.hits("/accounts/*"), 2);
+        }
+
+        #[test]
+        fn it_reports_a_timed_out_payout_that_failed_in_its_ledger() {
+            let client = MockClient::new()
+                .with_json("/accounts/*", include_str!("../../fixtures/account.json"))
+                .with_response(
+                    "/transactions",
+                    504,
+                    r#"{
+                        "type": "https://stellar.org/horizon-errors/timeout",
+                        "title": "Timeout",
+                        "status": 504,
+                        "detail": "Your request timed out before completing."
+                    }"#,
+                )
+                .with_json(
+                    "/transactions/*",
+                    include_str!("../../fixtures/transactions/transaction_failed.json"),
+                );
+            let keypair = Keypair::random().unwrap();
+            let batch = PayoutBatch::new(vec![payout(ALICE)]);
+            let outcomes = batch.submit(&client, &Network::test(), &keypair).unwrap();
+            assert_eq!(
+                outcomes[0].status(),
+                &PayoutStatus::Failed("op_underfunded".to_string())
+            );
+            assert_eq!(client.hits("/transactions"), 1);
+        }
+
+        #[test]
+        fn it_rebuilds_a_transaction_with_a_bad_sequence() {
+            let client = MockClient::new()
+                .with_json("/accounts/*", include_str!("../../fixtures/account.json"))
+                .with_response("/transactions", 400, &failed("tx_bad_seq", ""));
+            let keypair = Keypair::random().unwrap();
+            let batch = PayoutBatch::new(vec![payout(ALICE)]).with_retries(no_delay(2));
+            let outcomes = batch.submit(&client, &Network::test(), &keypair).unwrap();
+            assert_eq!(client.hits("/transactions"), 3);
+            assert_eq!(client.hits("/accounts/*"), 3);
+            assert!(match *outcomes[0].status() {
+                PayoutStatus::Failed(_) => true,
+                _ => false,
+            });
+        }
+
+        #[test]
+        fn it_sends_the_same_envelope_again_while_horizon_is_busy() {
+            let client = MockClient::new()
+                .with_json("/accounts/*", include_str!("../../fixtures/account.json"))
+                .with_response(
+                    "/transactions",
+                    503,
+                    r#"{
+                        "type": "https://stellar.org/horizon-errors/service_unavailable",
+                        "title": "Service Unavailable",
+                        "status": 503
+                    }"#,
+                );
+            let keypair = Keypair::random().unwrap();
+            let batch = PayoutBatch::new(vec![payout(ALICE)]).with_retries(no_delay(2));
+            batch.submit(&client, &Network::test(), &keypair).unwrap();
+            // The envelope is sent again as it was, so the sequence number
+            // is never loaded again.
+            assert_eq!(client.hits("/transactions"), 3);
+            assert_eq!(client.hits("/accounts/*"), 1);
+        }
+    }
+}
@@ -0,0 +1,322 @@
+//! Aggregates balances across several accounts and values them in a single
+//! reference asset.
+//!
+//! A portfolio is built from account resources. Valuation is driven by a
+//! pricing function so that callers can plug in their own price source; the
+//! order book and trade aggregation endpoints are provided as ready made
+//! sources through the synchronous client.
+//!
+//! # Examples
+//!
+//! ```
+//! use stellar_client::helpers::portfolio::Portfolio;
+//! use stellar_client::resources::{Amount, AssetIdentifier};
+//!
+//! let portfolio = Portfolio::default();
+//! let valuation = portfolio.value_with(&AssetIdentifier::native(), |_| Some(Amount::new(1)));
+//! assert_eq!(valuation.total(), Amount::new(0));
+//! ```
+use chrono::Utc;
+use endpoint::{account, orderbook, trade, Direction, Limit, Order};
+use error::Result;
+use resources::{Account, AccountId, Amount, AssetIdentifier};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use sync::Requester;
+
+/// The number of stroops in a single unit of an asset.
+const STROOPS_PER_UNIT: i128 = 10_000_000;
+
+/// The combined holdings of a set of accounts.
+#[derive(Debug, Default, Clone)]
+pub struct Portfolio {
+    accounts: Vec<AccountId>,
+    totals: HashMap<AssetIdentifier, Amount>,
+}
+
+impl Portfolio {
+    /// Builds a portfolio from the balances of each of the accounts.
+    pub fn from_accounts<'a, I>(accounts: I) -> Portfolio
+    where
+        I: IntoIterator<Item = &'a Account>,
+    {
+        let mut portfolio = Portfolio::default();
+        for account in accounts {
+            portfolio.add_account(account);
+        }
+        portfolio
+    }
+
+    /// Fetches each of the accounts from horizon and builds a portfolio from
+    /// their balances.
+    pub fn fetch<C>(client: &C, account_ids: &[AccountId]) -> Result<Portfolio>
+    where
+        C: Requester,
+    {
+        let mut portfolio = Portfolio::default();
+        for account_id in account_ids {
+            let account = client.request(account::Details::new(account_id.clone()))?;
+            portfolio.add_account(&account);
+        }
+        Ok(portfolio)
+    }
+
+    /// Adds the balances of an account to the portfolio.
+    pub fn add_account(&mut self, account: &Account) {
+        self.accounts.push(account.account_id().clone());
+        for balance in account.balances() {
+            let total = self
+                .totals
+                .entry(balance.asset_identifier().clone())
+                .or_insert_with(|| Amount::new(0));
+            *total = &*total + &balance.balance();
+        }
+    }
+
+    /// The ids of the accounts that make up this portfolio.
+    pub fn accounts(&self) -> &Vec<AccountId> {
+        &self.accounts
+    }
+
+    /// The total amount held of each asset across every account.
+    pub fn totals(&self) -> &HashMap<AssetIdentifier, Amount> {
+        &self.totals
+    }
+
+    /// The total amount held of a single asset across every account.
+    pub fn total(&self, asset: &AssetIdentifier) -> Amount {
+        self.totals
+            .get(asset)
+            .cloned()
+            .unwrap_or_else(|| Amount::new(0))
+    }
+
+    /// Values every holding in terms of the reference asset. The pricing
+    /// function is given each held asset and should return how many units of
+    /// the reference asset a single unit of it is worth, or `None` if no
+    /// price is known. Holdings of the reference asset itself are never
+    /// priced and are valued one to one. Holdings whose value is too large
+    /// for an `Amount` are listed as overflowed rather than valued.
+    pub fn value_with<F>(&self, reference: &AssetIdentifier, mut price: F) -> Valuation
+    where
+        F: FnMut(&AssetIdentifier) -> Option<Amount>,
+    {
+        let mut values = HashMap::new();
+        let mut unpriced = Vec::new();
+        let mut overflowed = Vec::new();
+        for (asset, amount) in &self.totals {
+            let value = if asset == reference {
+                Some(Some(*amount))
+            } else {
+                price(asset).map(|price| convert(*amount, price))
+            };
+            match value {
+                Some(Some(value)) => {
+                    values.insert(asset.clone(), value);
+                }
+                Some(None) => overflowed.push(asset.clone()),
+                None => unpriced.push(asset.clone()),
+            }
+        }
+        Valuation {
+            reference: reference.clone(),
+            values,
+            unpriced,
+            overflowed,
+        }
+    }
+
+    /// Values every holding using the best bid in the order book between the
+    /// held asset and the reference asset. Assets without any bids are left
+    /// unpriced.
+    pub fn value_with_orderbook<C>(
+        &self,
+        client: &C,
+        reference: &AssetIdentifier,
+    ) -> Result<Valuation>
+    where
+        C: Requester,
+    {
+        let mut prices = HashMap::new();
+        for asset in self.totals.keys().filter(|asset| *asset != reference) {
+            let endpoint =
+                orderbook::Details::for_asset_pair(asset.clone(), reference.clone()).with_limit(1);
+            let orderbook = client.request(endpoint)?;
+            if let Some(bid) = orderbook.bids().first() {
+                prices.insert(asset.clone(), bid.price());
+            }
+        }
+        Ok(self.value_with(reference, |asset| prices.get(asset).cloned()))
+    }
+
+    /// Values every holding using the closing price of the most recent trade
+    /// aggregation between the held asset and the reference asset at the
+    /// given resolution. Assets that have never traded against the reference
+    /// are left unpriced.
+    pub fn value_with_trade_aggregations<C>(
+        &self,
+        client: &C,
+        reference: &AssetIdentifier,
+        resolution: trade::SegmentResolution,
+    ) -> Result<Valuation>
+    where
+        C: Requester,
+    {
+        let now = Utc::now().timestamp() as u64 * 1000;
+        let mut prices = HashMap::new();
+        for asset in self.totals.keys().filter(|asset| *asset != reference) {
+            let endpoint = trade::Aggregations::new(asset, reference)
+                .with_start_time(0)
+                .with_end_time(now)
+                .with_resolution(resolution)
+                .with_order(Direction::Desc)
+                .with_limit(1);
+            let aggregations = client.request(endpoint)?;
+            if let Some(aggregation) = aggregations.records().first() {
+                prices.insert(asset.clone(), aggregation.close());
+            }
+        }
+        Ok(self.value_with(reference, |asset| prices.get(asset).cloned()))
+    }
+}
+
+/// A portfolio valued in terms of a single reference asset.
+#[derive(Debug, Clone)]
+pub struct Valuation {
+    reference: AssetIdentifier,
+    values: HashMap<AssetIdentifier, Amount>,
+    unpriced: Vec<AssetIdentifier>,
+    overflowed: Vec<AssetIdentifier>,
+}
+
+impl Valuation {
+    /// The asset that every holding has been valued in.
+    pub fn reference(&self) -> &AssetIdentifier {
+        &self.reference
+    }
+
+    /// The value of each priced holding, in units of the reference asset.
+    pub fn values(&self) -> &HashMap<AssetIdentifier, Amount> {
+        &self.values
+    }
+
+    /// The value of a single holding, if it could be priced.
+    pub fn value(&self, asset: &AssetIdentifier) -> Option<Amount> {
+        self.values.get(asset).cloned()
+    }
+
+    /// Held assets for which no price could be found. These are not included
+    /// in the total.
+    pub fn unpriced(&self) -> &Vec<AssetIdentifier> {
+        &self.unpriced
+    }
+
+    /// Held assets whose value in the reference asset is more than an
+    /// `Amount` can hold. These are not included in the total either.
+    pub fn overflowed(&self) -> &Vec<AssetIdentifier> {
+        &self.overflowed
+    }
+
+    /// The combined value of every priced holding.
+    pub fn total(&self) -> Amount {
+        self.values
+            .values()
+            .fold(Amount::new(0), |total, value| &total + value)
+    }
+}
+
+/// Multiplies an amount by a price, both of which carry seven decimal places,
+/// or returns `None` when the result doesn't fit in an amount.
+fn convert(amount: Amount, price: Amount) -> Option<Amount> {
+    let value = i128::from(amount.to_stroops()) * i128::from(price.to_stroops()) / STROOPS_PER_UNIT;
+    i64::try_from(value).ok().map(Amount::new)
+}
+
+#[cfg(test)]
+mod portfolio_tests {
+    use super::*;
+    use serde_json;
+    use test::MockClient;
+
+    const ISSUER: &str = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4";
+    const ACCOUNT_ID: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+
+    fn account() -> Account {
+        serde_json::from_str(include_str!("../../fixtures/account.json")).unwrap()
+    }
+
+    fn foo() -> AssetIdentifier {
+        AssetIdentifier::alphanum4("FOO", ISSUER).unwrap()
+    }
+
+    #[test]
+    fn it_aggregates_balances_across_accounts() {
+        let first = account();
+        let second = account();
+        let portfolio = Portfolio::from_accounts(vec![&first, &second]);
+        assert_eq!(portfolio.accounts().len(), 2);
+        assert_eq!(
+            portfolio.total(&AssetIdentifier::native()),
+            Amount::new(197_999_999_600)
+        );
+        assert_eq!(portfolio.total(&foo()), Amount::new(20_000_000_000));
+        assert_eq!(
+            portfolio.total(&AssetIdentifier::alphanum4("BAR", ISSUER).unwrap()),
+            Amount::new(0)
+        );
+    }
+
+    #[test]
+    fn it_values_holdings_against_a_reference() {
+        let portfolio = Portfolio::from_accounts(vec![&account()]);
+        // One FOO is worth half a lumen.
+        let valuation = portfolio.value_with(&AssetIdentifier::native(), |asset| {
+            assert_eq!(asset, &foo());
+            Some(Amount::new(5_000_000))
+        });
+        assert_eq!(valuation.value(&foo()), Some(Amount::new(5_000_000_000)));
+        assert_eq!(
+            valuation.value(&AssetIdentifier::native()),
+            Some(Amount::new(98_999_999_800))
+        );
+        assert_eq!(valuation.total(), Amount::new(103_999_999_800));
+        assert!(valuation.unpriced().is_empty());
+    }
+
+    #[test]
+    fn it_leaves_assets_without_prices_out_of_the_total() {
+        let portfolio = Portfolio::from_accounts(vec![&account()]);
+        let valuation = portfolio.value_with(&foo(), |_| None);
+        assert_eq!(valuation.reference(), &foo());
+        assert_eq!(valuation.unpriced(), &vec![AssetIdentifier::native()]);
+        assert_eq!(valuation.total(), Amount::new(10_000_000_000));
+    }
+
+    #[test]
+    fn it_reports_values_too_large_for_an_amount() {
+        let portfolio = Portfolio::from_accounts(vec![&account()]);
+        let valuation =
+            portfolio.value_with(&AssetIdentifier::native(), |_| Some(Amount::new(i64::MAX)));
+        assert_eq!(valuation.overflowed(), &vec![foo()]);
+        assert_eq!(valuation.value(&foo()), None);
+        assert!(valuation.unpriced().is_empty());
+        assert_eq!(valuation.total(), Amount::new(98_999_999_800));
+    }
+
+    #[test]
+    fn it_values_holdings_with_the_orderbook() {
+        let client = MockClient::new()
+            .with_json("/accounts/*", include_str!("../../fixtures/account.json"))
+            .with_json(
+                "/order_book?*",
+                include_str!("../../fixtures/orderbook.json"),
+            );
+        let portfolio = Portfolio::fetch(&client, &[ACCOUNT_ID.into()]).unwrap();
+        let valuation = portfolio
+            .value_with_orderbook(&client, &AssetIdentifier::native())
+            .unwrap();
+        // The best bid in the fixture orderbook is 7.7200005
+        assert_eq!(valuation.value(&foo()), Some(Amount::new(77_200_005_000)));
+        client.assert_requested("/order_book?selling_asset_type=credit_alphanum4*");
+    }
+}
@@ -0,0 +1,602 @@
+//! Plans the rotation of an account's keys from its master key to other
+//! signers.
+//!
+//! Rotating keys is done with set options operations that add the new
+//! signers, raise the thresholds and lower or remove the master key, all in
+//! one transaction so that the account is never left half rotated. Getting
+//! the weights wrong can lock an account for good, so `KeyRotation` works
+//! out the signers and thresholds the account will end up with from the
+//! live `Account` and refuses any rotation that would leave the account
+//! unable to sign for itself. The plan displays as a report of the changes
+//! for review before anything is submitted.
+//!
+//! `RotationPlan::transaction` builds the unsigned transaction for signers
+//! that live elsewhere. With the `keys` feature enabled,
+//! `RotationPlan::submit` signs it with the account's current keys and
+//! submits it.
+//!
+//! # Examples
+//!
+//! ```
+//! use stellar_client::helpers::rotation::KeyRotation;
+//! use stellar_client::resources::{AccountBuilder, Thresholds};
+//!
+//! let account = AccountBuilder::new("GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into())
+//!     .build();
+//! let plan = KeyRotation::new()
+//!     .with_signer("GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4".into(), 10)
+//!     .with_thresholds(Thresholds::new(10, 10, 10))
+//!     .without_master_key()
+//!     .plan(&account)
+//!     .unwrap();
+//!
+//! assert_eq!(plan.operations().len(), 1);
+//! assert_eq!(plan.after().total_weight(), 10);
+//! println!("{}", plan);
+//! ```
+#[cfg(feature = "keys")]
+use super::transaction;
+#[cfg(feature = "keys")]
+use error;
+#[cfg(feature = "keys")]
+use keys;
+#[cfg(feature = "keys")]
+use network::Network;
+use resources::envelope::{EnvelopeBuilder, MuxedAccount, Operation, OperationBody};
+use resources::operation::SetOptions;
+#[cfg(feature = "keys")]
+use resources::SubmittedTransaction;
+use resources::{Account, AccountId, Flags, Signer, Thresholds, TransactionEnvelope, XdrError};
+use std::error::Error as StdError;
+use std::fmt;
+#[cfg(feature = "keys")]
+use sync::Requester;
+
+/// The most signers an account can have besides its master key.
+const MAX_SIGNERS: usize = 20;
+
+/// Describes how an account's keys should change and plans the operations
+/// that change them.
+#[derive(Debug, Clone, Default)]
+pub struct KeyRotation {
+    signers: Vec<(AccountId, u8)>,
+    thresholds: Option<Thresholds>,
+    master_weight: Option<u8>,
+}
+
+impl KeyRotation {
+    /// Starts describing a rotation that changes nothing. At least one signer
+    /// has to be added before it can be planned.
+    pub fn new() -> KeyRotation {
+        KeyRotation::default()
+    }
+
+    /// Adds a signer with the given weight, or changes the weight of a
+    /// signer the account already has.
+    pub fn with_signer(mut self, key: AccountId, weight: u8) -> KeyRotation {
+        self.signers.push((key, weight));
+        self
+    }
+
+    /// Sets new thresholds. The account's current thresholds are kept
+    /// otherwise.
+    pub fn with_thresholds(mut self, thresholds: Thresholds) -> KeyRotation {
+        self.thresholds = Some(thresholds);
+        self
+    }
+
+    /// Sets the weight of the master key. The master key's current weight is
+    /// kept otherwise.
+    pub fn with_master_weight(mut self, weight: u8) -> KeyRotation {
+        self.master_weight = Some(weight);
+        self
+    }
+
+    /// Sets the weight of the master key to zero, so that it can no longer
+    /// sign for the account.
+    pub fn without_master_key(self) -> KeyRotation {
+        self.with_master_weight(0)
+    }
+
+    /// Works out the signers and thresholds `account` would end up with and
+    /// lays out the operations that get it there.
+    pub fn plan(&self, account: &Account) -> Result<RotationPlan, RotationError> {
+        if self.signers.is_empty() {
+            return Err(RotationError::NoSigners);
+        }
+        let master = account.account_id();
+        for &(ref key, weight) in &self.signers {
+            if key == master {
+                return Err(RotationError::MasterKeyAsSigner);
+            }
+            if weight == 0 {
+                return Err(RotationError::ZeroWeight(key.clone()));
+            }
+        }
+
+        let before = SignerConfig::new(account.thresholds(), account.signers().clone());
+        let thresholds = self.thresholds.unwrap_or_else(|| account.thresholds());
+        let master_weight = self
+            .master_weight
+            .unwrap_or_else(|| before.weight(master.as_str()));
+
+        let mut signers: Vec<Signer> = account
+            .signers()
+            .iter()
+            .filter(|signer| signer.key() != master.as_str())
+            .filter(|signer| !self.signers.iter().any(|(key, _)| key == signer.key()))
+            .cloned()
+            .collect();
+        for &(ref key, weight) in &self.signers {
+            signers.retain(|signer| signer.key() != key.as_str());
+            signers.push(Signer::new(key.to_string(), weight));
+        }
+        if signers.len() > MAX_SIGNERS {
+            return Err(RotationError::TooManySigners(signers.len()));
+        }
+        if master_weight > 0 {
+            signers.insert(0, Signer::new(master.to_string(), master_weight));
+        }
+
+        let after = SignerConfig::new(thresholds, signers);
+        let needed = u32::from(thresholds.high()).max(1);
+        if after.total_weight() < needed {
+            return Err(RotationError::LockedOut {
+                available: after.total_weight(),
+                needed,
+            });
+        }
+
+        let operations = self
+            .signers
+            .iter()
+            .map(|&(ref key, weight)| {
+                SetOptions::new(
+                    key.to_string(),
+                    weight,
+                    master_weight,
+                    (
+                        u32::from(thresholds.low()),
+                        u32::from(thresholds.med()),
+                        u32::from(thresholds.high()),
+                    ),
+                    String::new(),
+                    None,
+                    None,
+                )
+            })
+            .collect();
+
+        Ok(RotationPlan {
+            account_id: master.clone(),
+            operations,
+            before,
+            after,
+        })
+    }
+}
+
+/// The operations that rotate an account's keys, along with the signers and
+/// thresholds the account has before and after. Displays as a report of the
+/// changes.
+#[derive(Debug, Clone)]
+pub struct RotationPlan {
+    account_id: AccountId,
+    operations: Vec<SetOptions>,
+    before: SignerConfig,
+    after: SignerConfig,
+}
+
+impl RotationPlan {
+    /// The account whose keys are rotated, which is the source of the
+    /// transaction.
+    pub fn account_id(&self) -> &AccountId {
+        &self.account_id
+    }
+
+    /// The set options operations of the transaction, one for each signer
+    /// added. Each sets the master key weight and thresholds as well.
+    pub fn operations(&self) -> &Vec<SetOptions> {
+        &self.operations
+    }
+
+    /// The signers and thresholds of the account as it was when planned.
+    pub fn before(&self) -> &SignerConfig {
+        &self.before
+    }
+
+    /// The signers and thresholds of the account once the transaction has
+    /// been applied.
+    pub fn after(&self) -> &SignerConfig {
+        &self.after
+    }
+
+    /// The signature weight the transaction needs, which is the high
+    /// threshold the account has before the rotation. Signatures from the
+    /// keys in `before` have to add up to at least this much.
+    pub fn required_weight(&self) -> u32 {
+        u32::from(self.before.thresholds().high()).max(1)
+    }
+
+    /// Returns true when `account` has the signers and thresholds the plan
+    /// ends with, such as after the transaction has been submitted.
+    pub fn is_applied(&self, account: &Account) -> bool {
+        SignerConfig::new(account.thresholds(), account.signers().clone()) == self.after
+    }
+
+    /// Builds the unsigned transaction, using up `sequence`, which has to be
+    /// the one after the account's current sequence number.
+    pub fn transaction(&self, sequence: i64) -> Result<TransactionEnvelope, XdrError> {
+        self.operations
+            .iter()
+            .map(|operation| Operation::new(set_options(operation)))
+            .fold(
+                EnvelopeBuilder::new(MuxedAccount::new(&self.account_id, None), sequence),
+                EnvelopeBuilder::with_operation,
+            )
+            .build()
+    }
+
+    /// Builds the transaction on the account's next sequence number, has
+    /// each of `signers` sign it for `network` and submits it. The weights
+    /// of the signers in `before` have to add up to `required_weight`, or
+    /// the network rejects the transaction.
+    ///
+    /// Only available with the `keys` feature enabled.
+    #[cfg(feature = "keys")]
+    pub fn submit<C>(
+        &self,
+        client: &C,
+        network: &Network,
+        signers: &[&dyn keys::Signer],
+    ) -> error::Result<SubmittedTransaction>
+    where
+        C: Requester + ?Sized,
+    {
+        let sequence = transaction::next_sequence(client, &self.account_id)?;
+        let envelope = self.transaction(sequence)?;
+        transaction::sign_and_submit(client, network, envelope, signers)
+    }
+}
+
+impl fmt::Display for RotationPlan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "rotate {}, signed with a weight of at least {}:",
+            self.account_id,
+            self.required_weight()
+        )?;
+        let mut keys: Vec<&str> = self.before.signers.iter().map(Signer::key).collect();
+        for signer in &self.after.signers {
+            if !keys.contains(&signer.key()) {
+                keys.push(signer.key());
+            }
+        }
+        for key in keys {
+            let before = self.before.weight(key);
+            let after = self.after.weight(key);
+            let master = if key == self.account_id.as_str() {
+                " (master)"
+            } else {
+                ""
+            };
+            if before == after {
+                writeln!(f, "  {}{}: {}", key, master, after)?;
+            } else {
+                writeln!(f, "  {}{}: {} -> {}", key, master, before, after)?;
+            }
+        }
+        let (before, after) = (self.before.thresholds(), self.after.thresholds());
+        writeln!(
+            f,
+            "  thresholds: {}/{}/{} -> {}/{}/{}",
+            before.low(),
+            before.med(),
+            before.high(),
+            after.low(),
+            after.med(),
+            after.high()
+        )
+    }
+}
+
+/// The signers of an account and the thresholds their signatures have to
+/// meet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignerConfig {
+    thresholds: Thresholds,
+    signers: Vec<Signer>,
+}
+
+impl SignerConfig {
+    fn new(thresholds: Thresholds, signers: Vec<Signer>) -> SignerConfig {
+        let mut signers: Vec<Signer> = signers
+            .into_iter()
+            .filter(|signer| signer.weight() > 0)
+            .collect();
+        signers.sort_by(|a, b| a.key().cmp(b.key()));
+        SignerConfig {
+            thresholds,
+            signers,
+        }
+    }
+
+    /// The thresholds of the account.
+    pub fn thresholds(&self) -> Thresholds {
+        self.thresholds
+    }
+
+    /// The keys that can sign for the account, ordered by key. Keys with a
+    /// weight of zero are left out.
+    pub fn signers(&self) -> &[Signer] {
+        &self.signers
+    }
+
+    /// The weight of a key's signatures, which is zero for keys that can't
+    /// sign.
+    pub fn weight(&self, key: &str) -> u8 {
+        self.signers
+            .iter()
+            .find(|signer| signer.key() == key)
+            .map(Signer::weight)
+            .unwrap_or(0)
+    }
+
+    /// The weight of every signer together.
+    pub fn total_weight(&self) -> u32 {
+        self.signers
+            .iter()
+            .map(|signer| u32::from(signer.weight()))
+            .sum()
+    }
+}
+
+/// The set options operation in the form it is encoded. The empty home
+/// domain of a planned operation leaves the home domain as it is.
+fn set_options(operation: &SetOptions) -> OperationBody {
+    let flags = |flags: Flags| {
+        u32::from(flags.is_auth_required()) | u32::from(flags.is_auth_revocable()) << 1
+    };
+    OperationBody::SetOptions {
+        inflation_dest: None,
+        clear_flags: operation.clear_flags().map(flags),
+        set_flags: operation.set_flags().map(flags),
+        master_weight: Some(u32::from(operation.master_key_weight())),
+        low_threshold: Some(operation.low_threshold()),
+        med_threshold: Some(operation.med_threshold()),
+        high_threshold: Some(operation.high_threshold()),
+        home_domain: Some(operation.home_domain().to_string()).filter(|domain| !domain.is_empty()),
+        signer: Some((
+            operation.signer_key().to_string(),
+            u32::from(operation.signer_weight()),
+        )),
+    }
+}
+
+/// Why a key rotation can't be planned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RotationError {
+    /// No signers were added.
+    NoSigners,
+    /// The account's own key was added as a signer. Its weight is set with
+    /// `with_master_weight` instead.
+    MasterKeyAsSigner,
+    /// A signer was added with a weight of zero, which would remove it.
+    ZeroWeight(AccountId),
+    /// The account would have this many signers besides its master key,
+    /// more than the network allows.
+    TooManySigners(usize),
+    /// The signers left could never meet the high threshold, so the account
+    /// could never change its signers again.
+    LockedOut {
+        /// The weight of every signer together.
+        available: u32,
+        /// The weight needed to meet the high threshold.
+        needed: u32,
+    },
+}
+
+impl fmt::Display for RotationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RotationError::LockedOut { available, needed } => write!(
+                f,
+                "{}: signers weigh {} of the {} needed",
+                self.description(),
+                available,
+                needed
+            ),
+            _ => f.write_str(self.description()),
+        }
+    }
+}
+
+impl StdError for RotationError {
+    fn description(&self) -> &str {
+        match *self {
+            RotationError::NoSigners => "A rotation has to add a signer",
+            RotationError::MasterKeyAsSigner => "The master key can't be added as a signer",
+            RotationError::ZeroWeight(_) => "A signer has to have a weight of at least one",
+            RotationError::TooManySigners(_) => "An account can have at most 20 signers",
+            RotationError::LockedOut { .. } => "The rotation would lock the account",
+        }
+    }
+}
+
+#[cfg(test)]
+mod key_rotation_tests {
+    use super::*;
+    use resources::AccountBuilder;
+    use serde_json;
+
+    const ACCOUNT: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+    const NEW_KEY: &str = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4";
+
+    fn account() -> Account {
+        serde_json::from_str(include_str!("../../fixtures/account.json")).unwrap()
+    }
+
+    #[test]
+    fn it_replaces_the_master_key() {
+        let plan = KeyRotation::new()
+            .with_signer(NEW_KEY.into(), 10)
+            .with_thresholds(Thresholds::new(5, 10, 10))
+            .without_master_key()
+            .plan(&account())
+            .unwrap();
+
+        assert_eq!(plan.operations().len(), 1);
+        let op = &plan.operations()[0];
+        assert_eq!(op.signer_key(), NEW_KEY);
+        assert_eq!(op.signer_weight(), 10);
+        assert_eq!(op.master_key_weight(), 0);
+        assert_eq!(op.low_threshold(), 5);
+        assert_eq!(op.high_threshold(), 10);
+
+        assert_eq!(plan.required_weight(), 1);
+        assert_eq!(plan.before().weight(ACCOUNT), 1);
+        assert_eq!(plan.after().weight(ACCOUNT), 0);
+        assert_eq!(
+            plan.after().signers(),
+            &[Signer::new(NEW_KEY.to_string(), 10)]
+        );
+        assert_eq!(
+            plan.to_string(),
+            format!(
+                "rotate {account}, signed with a weight of at least 1:\n  \
+                 {account} (master): 1 -> 0\n  \
+                 {new_key}: 0 -> 10\n  \
+                 thresholds: 0/0/0 -> 5/10/10\n",
+                account = ACCOUNT,
+                new_key = NEW_KEY
+            )
+        );
+    }
+
+    #[test]
+    fn it_keeps_what_is_not_changed() {
+        let account = AccountBuilder::new(ACCOUNT.into())
+            .with_thresholds(Thresholds::new(1, 2, 3))
+            .with_signer(Signer::new(ACCOUNT.to_string(), 3))
+            .build();
+        let plan = KeyRotation::new()
+            .with_signer(NEW_KEY.into(), 1)
+            .plan(&account)
+            .unwrap();
+        assert_eq!(plan.required_weight(), 3);
+        assert_eq!(plan.after().thresholds(), Thresholds::new(1, 2, 3));
+        assert_eq!(plan.after().weight(ACCOUNT), 3);
+        assert_eq!(plan.after().total_weight(), 4);
+        assert_eq!(plan.operations()[0].master_key_weight(), 3);
+    }
+
+    #[test]
+    fn it_refuses_to_lock_the_account() {
+        let rotation = KeyRotation::new()
+            .with_signer(NEW_KEY.into(), 5)
+            .with_thresholds(Thresholds::new(10, 10, 10))
+            .without_master_key();
+        let err = rotation.plan(&account()).unwrap_err();
+        assert_eq!(
+            err,
+            RotationError::LockedOut {
+                available: 5,
+                needed: 10,
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "The rotation would lock the account: signers weigh 5 of the 10 needed"
+        );
+    }
+
+    #[test]
+    fn it_refuses_bad_signers() {
+        assert_eq!(
+            KeyRotation::new().plan(&account()).unwrap_err(),
+            RotationError::NoSigners
+        );
+        assert_eq!(
+            KeyRotation::new()
+                .with_signer(ACCOUNT.into(), 1)
+                .plan(&account())
+                .unwrap_err(),
+            RotationError::MasterKeyAsSigner
+        );
+        assert_eq!(
+            KeyRotation::new()
+                .with_signer(NEW_KEY.into(), 0)
+                .plan(&account())
+                .unwrap_err(),
+            RotationError::ZeroWeight(NEW_KEY.into())
+        );
+    }
+
+    #[test]
+    fn it_checks_whether_the_rotation_was_applied() {
+        let plan = KeyRotation::new()
+            .with_signer(NEW_KEY.into(), 2)
+            .with_master_weight(1)
+            .with_thresholds(Thresholds::new(1, 2, 2))
+            .plan(&account())
+            .unwrap();
+        assert!(!plan.is_applied(&account()));
+
+        let rotated = AccountBuilder::new(ACCOUNT.into())
+            .with_signer(Signer::new(NEW_KEY.to_string(), 2))
+            .with_thresholds(Thresholds::new(1, 2, 2))
+            .build();
+        assert!(plan.is_applied(&rotated));
+    }
+
+    #[test]
+    fn it_builds_the_transaction_from_the_account() {
+        let plan = KeyRotation::new()
+            .with_signer(NEW_KEY.into(), 10)
+            .with_thresholds(Thresholds::new(5, 10, 10))
+            .without_master_key()
+            .plan(&account())
+            .unwrap();
+        let envelope = plan.transaction(3).unwrap();
+        let envelope = TransactionEnvelope::from_base64(&envelope.to_base64()).unwrap();
+        assert_eq!(envelope.source_account().account_id(), ACCOUNT);
+        assert_eq!(envelope.sequence(), 3);
+        assert_eq!(
+            envelope.operations()[0].body(),
+            &OperationBody::SetOptions {
+                inflation_dest: None,
+                clear_flags: None,
+                set_flags: None,
+                master_weight: Some(0),
+                low_threshold: Some(5),
+                med_threshold: Some(10),
+                high_threshold: Some(10),
+                home_domain: None,
+                signer: Some((NEW_KEY.to_string(), 10)),
+            }
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "keys")]
+    fn it_submits_the_rotation() {
+        use keys::Keypair;
+        use test::MockClient;
+
+        let client = MockClient::new()
+            .with_json("/accounts/*", include_str!("../../fixtures/account.json"))
+            .with_json(
+                "/transactions",
+                include_str!("../../fixtures/submitted_transaction.json"),
+            );
+        let keypair = Keypair::random().unwrap();
+        let plan = KeyRotation::new()
+            .with_signer(NEW_KEY.into(), 10)
+            .plan(&account())
+            .unwrap();
+        let submitted = plan.submit(&client, &Network::test(), &[&keypair]).unwrap();
+        assert_eq!(submitted.ledger(), 1_524_302);
+        assert_eq!(client.hits("/transactions"), 1);
+    }
+}
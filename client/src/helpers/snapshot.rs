@@ -0,0 +1,208 @@
+//! Takes a consistent view of an account across several requests.
+//!
+//! An account, its offers and its trustlines come from separate requests,
+//! and a ledger can close between any two of them. A trading decision made
+//! from an account as of one ledger and its offers as of the next can act on
+//! a state that never existed. Horizon says in the `Latest-Ledger` header of
+//! each response which ledger it had ingested, so a snapshot compares the
+//! header across its requests and starts over when the ledger moved on.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use stellar_client::helpers::snapshot::AccountSnapshot;
+//! use stellar_client::sync::Client;
+//!
+//! let client = Client::horizon_test().unwrap();
+//! let account_id = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into();
+//! let snapshot = AccountSnapshot::fetch(&client, &account_id, 3).unwrap();
+//! println!(
+//!     "{} offers and {} trustlines as of ledger {:?}",
+//!     snapshot.offers().len(),
+//!     snapshot.trustlines().len(),
+//!     snapshot.ledger()
+//! );
+//! ```
+use endpoint::{account, Limit};
+use error::{Error, Result};
+use resources::{Account, AccountId, Balance, Offer};
+use sync::Requester;
+use uri::TryFromUri;
+
+/// The number of offers asked for with each page.
+const OFFERS_PAGE_LIMIT: u32 = 200;
+
+/// An account and its offers as horizon reported them at a single ledger.
+#[derive(Debug)]
+pub struct AccountSnapshot {
+    ledger: Option<u32>,
+    account: Account,
+    offers: Vec<Offer>,
+}
+
+impl AccountSnapshot {
+    /// Fetches the account and every page of its offers, starting over when
+    /// the latest ledger advances before the last response. After
+    /// `max_attempts` tries that each saw the ledger advance, this fails with
+    /// `Error::LedgerAdvanced`.
+    ///
+    /// A horizon server that doesn't send `Latest-Ledger` can't be checked,
+    /// and its snapshots have no ledger.
+    pub fn fetch<C>(
+        client: &C,
+        account_id: &AccountId,
+        max_attempts: u32,
+    ) -> Result<AccountSnapshot>
+    where
+        C: Requester,
+    {
+        for _ in 0..max_attempts {
+            if let Some(snapshot) = Self::attempt(client, account_id)? {
+                return Ok(snapshot);
+            }
+        }
+        Err(Error::LedgerAdvanced(max_attempts))
+    }
+
+    // Takes the snapshot once, returning `None` when the ledger advanced
+    // between its requests.
+    fn attempt<C>(client: &C, account_id: &AccountId) -> Result<Option<AccountSnapshot>>
+    where
+        C: Requester,
+    {
+        let account = client.request_with_headers(account::Details::new(account_id.clone()))?;
+        let ledger = account.latest_ledger();
+
+        let mut offers = Vec::new();
+        let mut endpoint = account::Offers::new(account_id.clone()).with_limit(OFFERS_PAGE_LIMIT);
+        loop {
+            let page = client.request_with_headers(endpoint)?;
+            if page.latest_ledger() != ledger {
+                return Ok(None);
+            }
+            offers.extend(page.records().iter().cloned());
+            match page.next() {
+                Some(uri) if !page.is_last_page() => endpoint = account::Offers::try_from(uri)?,
+                _ => break,
+            }
+        }
+
+        Ok(Some(AccountSnapshot {
+            ledger,
+            account: account.into_resource(),
+            offers,
+        }))
+    }
+
+    /// The ledger horizon had ingested when every response of the snapshot
+    /// was made, when horizon said.
+    pub fn ledger(&self) -> Option<u32> {
+        self.ledger
+    }
+
+    /// The account as of the snapshot's ledger.
+    pub fn account(&self) -> &Account {
+        &self.account
+    }
+
+    /// The offers of the account as of the snapshot's ledger.
+    pub fn offers(&self) -> &[Offer] {
+        &self.offers
+    }
+
+    /// The balances of the account in assets other than lumens, which are
+    /// the account's trustlines.
+    pub fn trustlines(&self) -> Vec<&Balance> {
+        self.account
+            .balances()
+            .iter()
+            .filter(|balance| !balance.asset_identifier().is_native())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod account_snapshot_tests {
+    use super::*;
+    use test::MockClient;
+
+    const ACCOUNT_ID: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+
+    fn offers_json() -> String {
+        format!(
+            r#"{{
+                "_links": {{
+                    "self": {{ "href": "https://horizon.mock/accounts/{0}/offers?limit=200" }},
+                    "next": {{ "href": "https://horizon.mock/accounts/{0}/offers?cursor=1&limit=200" }}
+                }},
+                "_embedded": {{ "records": [{1}] }}
+            }}"#,
+            ACCOUNT_ID,
+            include_str!("../../fixtures/offer.json")
+        )
+    }
+
+    fn client() -> MockClient {
+        MockClient::new()
+            .with_json("/accounts/*/offers*", &offers_json())
+            .with_json("/accounts/*", include_str!("../../fixtures/account.json"))
+    }
+
+    #[test]
+    fn it_takes_a_snapshot_at_one_ledger() {
+        let client = client().with_latest_ledgers(&[100]);
+        let snapshot = AccountSnapshot::fetch(&client, &ACCOUNT_ID.into(), 3).unwrap();
+        assert_eq!(snapshot.ledger(), Some(100));
+        assert_eq!(snapshot.account().id(), ACCOUNT_ID);
+        assert_eq!(snapshot.offers().len(), 1);
+        assert!(snapshot
+            .trustlines()
+            .iter()
+            .all(|balance| !balance.asset_identifier().is_native()));
+        assert_eq!(client.hits("/accounts/*/offers*"), 1);
+    }
+
+    #[test]
+    fn it_starts_over_when_the_ledger_advances() {
+        let client = client().with_latest_ledgers(&[100, 101, 101]);
+        let snapshot = AccountSnapshot::fetch(&client, &ACCOUNT_ID.into(), 3).unwrap();
+        assert_eq!(snapshot.ledger(), Some(101));
+        assert_eq!(client.requests().len(), 4);
+    }
+
+    #[test]
+    fn it_gives_up_when_the_ledger_keeps_advancing() {
+        let client = client().with_latest_ledgers(&[100, 101, 102, 103]);
+        match AccountSnapshot::fetch(&client, &ACCOUNT_ID.into(), 2).unwrap_err() {
+            Error::LedgerAdvanced(attempts) => assert_eq!(attempts, 2),
+            error => panic!("expected the ledger to advance, got {:?}", error),
+        }
+        assert_eq!(client.requests().len(), 4);
+    }
+
+    #[test]
+    fn it_follows_every_page_of_offers() {
+        let full_page = format!(
+            r#"{{
+                "_links": {{
+                    "self": {{ "href": "https://horizon.mock/accounts/{0}/offers?limit=1" }},
+                    "next": {{ "href": "https://horizon.mock/accounts/{0}/offers?cursor=1&limit=1" }}
+                }},
+                "_embedded": {{ "records": [{1}] }}
+            }}"#,
+            ACCOUNT_ID,
+            include_str!("../../fixtures/offer.json")
+        );
+        let client = MockClient::new()
+            .with_json(
+                "/accounts/*/offers?cursor=1*",
+                r#"{ "_embedded": { "records": [] } }"#,
+            )
+            .with_json("/accounts/*/offers*", &full_page)
+            .with_json("/accounts/*", include_str!("../../fixtures/account.json"))
+            .with_latest_ledgers(&[100]);
+        let snapshot = AccountSnapshot::fetch(&client, &ACCOUNT_ID.into(), 1).unwrap();
+        assert_eq!(snapshot.offers().len(), 1);
+        client.assert_requested("/accounts/*/offers?cursor=1*");
+    }
+}
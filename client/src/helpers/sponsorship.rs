@@ -0,0 +1,524 @@
+//! Plans the transaction that creates an account whose reserves are paid
+//! for by another account.
+//!
+//! Onboarding a user without handing them lumens takes a sandwich of
+//! operations in a single transaction. The sponsor begins sponsoring the
+//! new account's future reserves, creates the account, and the new account
+//! opens any trustlines it needs and ends the sponsorship. Because the new
+//! account is the source of the operations after the account is created,
+//! the transaction has to be signed by both accounts.
+//!
+//! `SponsoredAccountPlan::transaction` builds the unsigned transaction for
+//! signers that live elsewhere. With the `keys` feature enabled,
+//! `SponsoredAccountPlan::submit` has both accounts sign it and submits it.
+//!
+//! # Examples
+//!
+//! ```
+//! use stellar_client::helpers::sponsorship::SponsoredAccount;
+//! use stellar_client::resources::AssetIdentifier;
+//!
+//! let usd = AssetIdentifier::alphanum4(
+//!     "USD",
+//!     "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG",
+//! ).unwrap();
+//! let plan = SponsoredAccount::new(
+//!     "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into(),
+//!     "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4".into(),
+//! ).with_trustline(usd, None)
+//!     .plan()
+//!     .unwrap();
+//!
+//! // Begin sponsoring, create the account, trust USD and end sponsoring.
+//! assert_eq!(plan.operations().len(), 4);
+//! assert_eq!(plan.signers().len(), 2);
+//! assert_eq!(plan.sponsored_reserves(), 3);
+//! println!("{}", plan);
+//! ```
+#[cfg(feature = "keys")]
+use super::transaction;
+#[cfg(feature = "keys")]
+use error;
+#[cfg(feature = "keys")]
+use keys::Signer;
+#[cfg(feature = "keys")]
+use network::Network;
+use resources::envelope::{EnvelopeBuilder, MuxedAccount, Operation, OperationBody};
+#[cfg(feature = "keys")]
+use resources::SubmittedTransaction;
+use resources::{AccountId, Amount, AssetIdentifier, TransactionEnvelope, XdrError};
+use std::error::Error as StdError;
+use std::fmt;
+#[cfg(feature = "keys")]
+use sync::Requester;
+
+/// Describes an account to create under a sponsor and plans the transaction
+/// that creates it.
+#[derive(Debug, Clone)]
+pub struct SponsoredAccount {
+    sponsor: AccountId,
+    account: AccountId,
+    starting_balance: Amount,
+    trustlines: Vec<(AssetIdentifier, Option<Amount>)>,
+}
+
+impl SponsoredAccount {
+    /// Starts describing the creation of `account` with its reserves paid by
+    /// `sponsor`. The account starts with a balance of zero and no
+    /// trustlines.
+    pub fn new(sponsor: AccountId, account: AccountId) -> SponsoredAccount {
+        SponsoredAccount {
+            sponsor,
+            account,
+            starting_balance: Amount::new(0),
+            trustlines: Vec::new(),
+        }
+    }
+
+    /// Sends the new account some lumens from the sponsor when it is
+    /// created, such as enough to pay for its first few transactions.
+    pub fn with_starting_balance(mut self, starting_balance: Amount) -> SponsoredAccount {
+        self.starting_balance = starting_balance;
+        self
+    }
+
+    /// Opens a trustline from the new account to `asset`, with its reserve
+    /// also paid by the sponsor. A limit of `None` is the largest there is.
+    pub fn with_trustline(
+        mut self,
+        asset: AssetIdentifier,
+        limit: Option<Amount>,
+    ) -> SponsoredAccount {
+        self.trustlines.push((asset, limit));
+        self
+    }
+
+    /// Lays out the operations of the transaction in the order they need to
+    /// run.
+    pub fn plan(&self) -> Result<SponsoredAccountPlan, SponsorshipError> {
+        if self.sponsor == self.account {
+            return Err(SponsorshipError::SameAccount);
+        }
+        for (index, (asset, _)) in self.trustlines.iter().enumerate() {
+            if asset.is_native() {
+                return Err(SponsorshipError::NativeTrustline);
+            }
+            if self.trustlines[..index]
+                .iter()
+                .any(|(seen, _)| seen == asset)
+            {
+                return Err(SponsorshipError::DuplicateTrustline(asset.clone()));
+            }
+        }
+
+        let mut operations = vec![
+            SponsoredOperation {
+                source: self.sponsor.clone(),
+                step: SponsorshipStep::BeginSponsoring {
+                    sponsored: self.account.clone(),
+                },
+            },
+            SponsoredOperation {
+                source: self.sponsor.clone(),
+                step: SponsorshipStep::CreateAccount {
+                    destination: self.account.clone(),
+                    starting_balance: self.starting_balance,
+                },
+            },
+        ];
+        for &(ref asset, limit) in &self.trustlines {
+            operations.push(SponsoredOperation {
+                source: self.account.clone(),
+                step: SponsorshipStep::Trust {
+                    asset: asset.clone(),
+                    limit,
+                },
+            });
+        }
+        operations.push(SponsoredOperation {
+            source: self.account.clone(),
+            step: SponsorshipStep::EndSponsoring,
+        });
+
+        Ok(SponsoredAccountPlan {
+            sponsor: self.sponsor.clone(),
+            account: self.account.clone(),
+            operations,
+        })
+    }
+}
+
+/// The transaction that creates a sponsored account. The sponsor is its
+/// source account and pays the fee, and both accounts have to sign it.
+/// Displays as a numbered list of the operations for review.
+#[derive(Debug, Clone)]
+pub struct SponsoredAccountPlan {
+    sponsor: AccountId,
+    account: AccountId,
+    operations: Vec<SponsoredOperation>,
+}
+
+impl SponsoredAccountPlan {
+    /// The account paying for the reserves, which is the source of the
+    /// transaction.
+    pub fn sponsor(&self) -> &AccountId {
+        &self.sponsor
+    }
+
+    /// The account being created.
+    pub fn account(&self) -> &AccountId {
+        &self.account
+    }
+
+    /// The operations of the transaction, in order.
+    pub fn operations(&self) -> &Vec<SponsoredOperation> {
+        &self.operations
+    }
+
+    /// The accounts that have to sign the transaction: the sponsor, then
+    /// the new account.
+    pub fn signers(&self) -> Vec<&AccountId> {
+        vec![&self.sponsor, &self.account]
+    }
+
+    /// The number of base reserves the sponsor takes on: two for the
+    /// account itself and one for each trustline.
+    pub fn sponsored_reserves(&self) -> u32 {
+        let mut reserves = 2;
+        for operation in &self.operations {
+            if let SponsorshipStep::Trust { .. } = operation.step {
+                reserves += 1;
+            }
+        }
+        reserves
+    }
+
+    /// Builds the unsigned transaction, using up `sequence`, which has to be
+    /// the one after the sponsor's current sequence number.
+    pub fn transaction(&self, sequence: i64) -> Result<TransactionEnvelope, XdrError> {
+        let sponsor = MuxedAccount::new(&self.sponsor, None);
+        self.operations
+            .iter()
+            .map(|operation| {
+                let planned = Operation::new(operation.step.operation());
+                if operation.source == self.sponsor {
+                    planned
+                } else {
+                    planned.with_source_account(MuxedAccount::new(&operation.source, None))
+                }
+            })
+            .fold(
+                EnvelopeBuilder::new(sponsor, sequence),
+                EnvelopeBuilder::with_operation,
+            )
+            .build()
+    }
+
+    /// Builds the transaction on the sponsor's next sequence number, has
+    /// `sponsor` and `account` sign it for `network` and submits it.
+    ///
+    /// Only available with the `keys` feature enabled.
+    #[cfg(feature = "keys")]
+    pub fn submit<C, S, A>(
+        &self,
+        client: &C,
+        network: &Network,
+        sponsor: &S,
+        account: &A,
+    ) -> error::Result<SubmittedTransaction>
+    where
+        C: Requester + ?Sized,
+        S: Signer,
+        A: Signer,
+    {
+        let sequence = transaction::next_sequence(client, &self.sponsor)?;
+        let envelope = self.transaction(sequence)?;
+        transaction::sign_and_submit(client, network, envelope, &[sponsor, account])
+    }
+}
+
+impl fmt::Display for SponsoredAccountPlan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "from {}, signed by {} and {}:",
+            self.sponsor, self.sponsor, self.account
+        )?;
+        for (index, operation) in self.operations.iter().enumerate() {
+            writeln!(f, "{}. {}", index + 1, operation)?;
+        }
+        Ok(())
+    }
+}
+
+/// An operation in the transaction, along with the account it acts for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SponsoredOperation {
+    source: AccountId,
+    step: SponsorshipStep,
+}
+
+impl SponsoredOperation {
+    /// The account the operation acts for, which is set as the operation's
+    /// source account.
+    pub fn source(&self) -> &AccountId {
+        &self.source
+    }
+
+    /// What the operation does.
+    pub fn step(&self) -> &SponsorshipStep {
+        &self.step
+    }
+}
+
+impl fmt::Display for SponsoredOperation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.source, self.step)
+    }
+}
+
+/// A single operation in a sponsored account plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SponsorshipStep {
+    /// A begin sponsoring future reserves operation from the sponsor.
+    BeginSponsoring {
+        /// The account whose reserves are sponsored.
+        sponsored: AccountId,
+    },
+    /// A create account operation from the sponsor.
+    CreateAccount {
+        /// The account being created.
+        destination: AccountId,
+        /// The lumens sent to the account, which may be zero.
+        starting_balance: Amount,
+    },
+    /// A change trust operation from the new account.
+    Trust {
+        /// The asset to trust.
+        asset: AssetIdentifier,
+        /// The limit of the trust line, or `None` for the largest there is.
+        limit: Option<Amount>,
+    },
+    /// An end sponsoring future reserves operation from the new account.
+    EndSponsoring,
+}
+
+impl fmt::Display for SponsorshipStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SponsorshipStep::BeginSponsoring { ref sponsored } => {
+                write!(f, "begin sponsoring {}", sponsored)
+            }
+            SponsorshipStep::CreateAccount {
+                ref destination,
+                starting_balance,
+            } => write!(f, "create {} with {} XLM", destination, starting_balance),
+            SponsorshipStep::Trust { ref asset, limit } => {
+                write!(
+                    f,
+                    "trust {}-{}",
+                    asset.code().unwrap_or_default(),
+                    asset.issuer().map(AccountId::as_str).unwrap_or_default()
+                )?;
+                match limit {
+                    Some(limit) => write!(f, " up to {}", limit),
+                    None => f.write_str(" with no limit"),
+                }
+            }
+            SponsorshipStep::EndSponsoring => f.write_str("end sponsoring"),
+        }
+    }
+}
+
+impl SponsorshipStep {
+    // The operation that carries out the step.
+    fn operation(&self) -> OperationBody {
+        match *self {
+            SponsorshipStep::BeginSponsoring { ref sponsored } => {
+                OperationBody::BeginSponsoringFutureReserves {
+                    sponsored_id: sponsored.clone(),
+                }
+            }
+            SponsorshipStep::CreateAccount {
+                ref destination,
+                starting_balance,
+            } => OperationBody::CreateAccount {
+                destination: destination.clone(),
+                starting_balance,
+            },
+            SponsorshipStep::Trust { ref asset, limit } => OperationBody::ChangeTrust {
+                line: Some(asset.clone()),
+                limit: limit.unwrap_or_else(|| Amount::new(i64::MAX)),
+            },
+            SponsorshipStep::EndSponsoring => OperationBody::EndSponsoringFutureReserves,
+        }
+    }
+}
+
+/// Why a sponsored account can't be created as described.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SponsorshipError {
+    /// The sponsor is the account being created.
+    SameAccount,
+    /// A trustline to lumens was asked for, which accounts don't need.
+    NativeTrustline,
+    /// The same asset was asked to be trusted twice.
+    DuplicateTrustline(AssetIdentifier),
+}
+
+impl fmt::Display for SponsorshipError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+impl StdError for SponsorshipError {
+    fn description(&self) -> &str {
+        match *self {
+            SponsorshipError::SameAccount => "An account can't sponsor itself",
+            SponsorshipError::NativeTrustline => "Lumens don't need a trustline",
+            SponsorshipError::DuplicateTrustline(_) => "An asset can only be trusted once",
+        }
+    }
+}
+
+#[cfg(test)]
+mod sponsored_account_tests {
+    use super::*;
+
+    const SPONSOR: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+    const ACCOUNT: &str = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4";
+
+    fn usd() -> AssetIdentifier {
+        AssetIdentifier::alphanum4("USD", SPONSOR).unwrap()
+    }
+
+    fn sponsored() -> SponsoredAccount {
+        SponsoredAccount::new(SPONSOR.into(), ACCOUNT.into())
+    }
+
+    #[test]
+    fn it_sandwiches_the_creation_in_a_sponsorship() {
+        let plan = sponsored().plan().unwrap();
+        let operations = plan.operations();
+        assert_eq!(operations.len(), 3);
+        assert_eq!(operations[0].source(), SPONSOR);
+        assert_eq!(
+            operations[0].step(),
+            &SponsorshipStep::BeginSponsoring {
+                sponsored: ACCOUNT.into(),
+            }
+        );
+        assert_eq!(operations[1].source(), SPONSOR);
+        assert_eq!(
+            operations[1].step(),
+            &SponsorshipStep::CreateAccount {
+                destination: ACCOUNT.into(),
+                starting_balance: Amount::new(0),
+            }
+        );
+        assert_eq!(operations[2].source(), ACCOUNT);
+        assert_eq!(operations[2].step(), &SponsorshipStep::EndSponsoring);
+        assert_eq!(plan.signers(), vec![SPONSOR, ACCOUNT]);
+        assert_eq!(plan.sponsored_reserves(), 2);
+    }
+
+    #[test]
+    fn it_opens_trustlines_from_the_new_account() {
+        let plan = sponsored()
+            .with_starting_balance(Amount::new(10_000_000))
+            .with_trustline(usd(), Some(Amount::new(1_000_000_000)))
+            .plan()
+            .unwrap();
+        assert_eq!(
+            plan.to_string(),
+            format!(
+                "from {sponsor}, signed by {sponsor} and {account}:\n\
+                 1. {sponsor}: begin sponsoring {account}\n\
+                 2. {sponsor}: create {account} with 1.0000000 XLM\n\
+                 3. {account}: trust USD-{sponsor} up to 100.0000000\n\
+                 4. {account}: end sponsoring\n",
+                sponsor = SPONSOR,
+                account = ACCOUNT
+            )
+        );
+        assert_eq!(plan.sponsored_reserves(), 3);
+    }
+
+    #[test]
+    fn it_refuses_plans_that_cant_work() {
+        let same = SponsoredAccount::new(SPONSOR.into(), SPONSOR.into());
+        assert_eq!(same.plan().unwrap_err(), SponsorshipError::SameAccount);
+        let native = sponsored().with_trustline(AssetIdentifier::native(), None);
+        assert_eq!(
+            native.plan().unwrap_err(),
+            SponsorshipError::NativeTrustline
+        );
+        let twice = sponsored()
+            .with_trustline(usd(), None)
+            .with_trustline(usd(), Some(Amount::new(1)));
+        assert_eq!(
+            twice.plan().unwrap_err(),
+            SponsorshipError::DuplicateTrustline(usd())
+        );
+    }
+
+    #[test]
+    fn it_builds_the_transaction_from_the_sponsor() {
+        let plan = sponsored().with_trustline(usd(), None).plan().unwrap();
+        let envelope = plan.transaction(8).unwrap();
+        let envelope = TransactionEnvelope::from_base64(&envelope.to_base64()).unwrap();
+        assert_eq!(envelope.source_account().account_id(), SPONSOR);
+        assert_eq!(envelope.sequence(), 8);
+        assert_eq!(envelope.fee(), 400);
+
+        let operations = envelope.operations();
+        assert!(operations[0].source_account().is_none());
+        assert_eq!(
+            operations[0].body(),
+            &OperationBody::BeginSponsoringFutureReserves {
+                sponsored_id: ACCOUNT.into(),
+            }
+        );
+        assert!(operations[1].source_account().is_none());
+        assert_eq!(
+            operations[2].source_account().map(MuxedAccount::account_id),
+            Some(ACCOUNT.into())
+        );
+        assert_eq!(
+            operations[2].body(),
+            &OperationBody::ChangeTrust {
+                line: Some(usd()),
+                limit: Amount::new(i64::MAX),
+            }
+        );
+        assert_eq!(
+            operations[3].body(),
+            &OperationBody::EndSponsoringFutureReserves
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "keys")]
+    fn it_submits_the_transaction_signed_by_both_accounts() {
+        use keys::Keypair;
+        use test::MockClient;
+
+        let client = MockClient::new()
+            .with_json("/accounts/*", include_str!("../../fixtures/account.json"))
+            .with_json(
+                "/transactions",
+                include_str!("../../fixtures/submitted_transaction.json"),
+            );
+        let sponsor = Keypair::random().unwrap();
+        let account = Keypair::random().unwrap();
+        let plan = SponsoredAccount::new(sponsor.account_id().into(), account.account_id().into())
+            .plan()
+            .unwrap();
+        let submitted = plan
+            .submit(&client, &Network::test(), &sponsor, &account)
+            .unwrap();
+        assert_eq!(submitted.ledger(), 1_524_302);
+        assert_eq!(client.hits("/transactions"), 1);
+    }
+}
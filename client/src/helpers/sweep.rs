@@ -0,0 +1,230 @@
+//! Finds the claimable balances an account can claim, and claims them.
+//!
+//! Horizon lists every balance an account is a claimant of, including those
+//! whose predicate doesn't let it claim yet or any longer. `claimable` keeps
+//! only the balances whose predicate holds at a given time, so that a claim
+//! isn't sent only to fail. With the `keys` feature enabled, `sweep` then
+//! claims them, up to a hundred to a transaction, and reports what happened
+//! to each balance.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # extern crate chrono;
+//! # extern crate stellar_client;
+//! use stellar_client::helpers::sweep;
+//! use stellar_client::sync::Client;
+//!
+//! # fn main() {
+//! let client = Client::horizon_test().unwrap();
+//! let now = chrono::Utc::now().timestamp();
+//! let balances = sweep::claimable(
+//!     &client,
+//!     &"GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM".into(),
+//!     now,
+//! ).unwrap();
+//! for balance in &balances {
+//!     println!("{} can be claimed", balance.amount());
+//! }
+//! # }
+//! ```
+#[cfg(feature = "keys")]
+use super::transaction;
+use endpoint::claimable_balance;
+#[cfg(feature = "keys")]
+use error::Error;
+use error::Result;
+#[cfg(feature = "keys")]
+use keys::Signer;
+#[cfg(feature = "keys")]
+use network::Network;
+#[cfg(feature = "keys")]
+use resources::envelope::{Operation, OperationBody};
+use resources::{AccountId, ClaimableBalance};
+use sync::{Iter, Requester};
+
+/// Lists the balances `claimant` can claim in a ledger that closes at
+/// `time`, in unix seconds.
+pub fn claimable<C>(client: &C, claimant: &AccountId, time: i64) -> Result<Vec<ClaimableBalance>>
+where
+    C: Requester,
+{
+    let endpoint = claimable_balance::All::default().with_claimant(claimant.clone());
+    Iter::new(client, endpoint)
+        .filter(|balance| match *balance {
+            Ok(ref balance) => balance.is_claimable_by(claimant, time),
+            Err(_) => true,
+        })
+        .collect()
+}
+
+/// Where a claim ended up.
+#[cfg(feature = "keys")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClaimStatus {
+    /// The balance was claimed.
+    Claimed {
+        /// The hash of the transaction that claimed it.
+        hash: String,
+        /// The ledger the transaction was included in.
+        ledger: u32,
+    },
+    /// The transaction holding the claim could not be sent, or failed, for
+    /// the given reason. Nothing in it was claimed.
+    Failed(String),
+    /// The transaction holding the claim, whose hash this is, was sent but
+    /// hadn't shown up in horizon by the time the submission gave up. It may
+    /// still make it into a ledger.
+    Unconfirmed(String),
+}
+
+/// A balance along with where claiming it ended up.
+#[cfg(feature = "keys")]
+#[derive(Debug, Clone)]
+pub struct ClaimOutcome {
+    balance: ClaimableBalance,
+    status: ClaimStatus,
+}
+
+#[cfg(feature = "keys")]
+impl ClaimOutcome {
+    /// The balance.
+    pub fn balance(&self) -> &ClaimableBalance {
+        &self.balance
+    }
+
+    /// Where claiming it ended up.
+    pub fn status(&self) -> &ClaimStatus {
+        &self.status
+    }
+}
+
+/// Claims every balance the account `claimant` signs for can claim in a
+/// ledger that closes at `time`, in unix seconds, and reports where each
+/// claim ended up.
+///
+/// The claims are sent up to a hundred to a transaction, and a transaction
+/// fails as a whole, so a balance that can't be claimed, such as one whose
+/// asset the account doesn't trust, fails the others in its transaction
+/// too. The error is only returned when the balances couldn't be listed.
+/// Only available with the `keys` feature enabled.
+#[cfg(feature = "keys")]
+pub fn sweep<C, S>(
+    client: &C,
+    network: &Network,
+    claimant: &S,
+    time: i64,
+) -> Result<Vec<ClaimOutcome>>
+where
+    C: Requester,
+    S: Signer,
+{
+    let account_id = AccountId::from(claimant.account_id());
+    let balances = claimable(client, &account_id, time)?;
+    let mut outcomes = Vec::with_capacity(balances.len());
+    for batch in balances.chunks(super::MAX_OPERATIONS) {
+        let operations = batch
+            .iter()
+            .map(|balance| {
+                Operation::new(OperationBody::ClaimClaimableBalance {
+                    balance_id: balance.id().to_string(),
+                })
+            })
+            .collect();
+        let status = match transaction::submit_operations(
+            client,
+            network,
+            &account_id,
+            operations,
+            &[claimant],
+        ) {
+            Ok(submitted) => ClaimStatus::Claimed {
+                hash: submitted.hash().to_string(),
+                ledger: submitted.ledger(),
+            },
+            Err(Error::TransactionTimeout(hash)) => ClaimStatus::Unconfirmed(hash),
+            Err(err) => ClaimStatus::Failed(err.to_string()),
+        };
+        outcomes.extend(batch.iter().map(|balance| ClaimOutcome {
+            balance: balance.clone(),
+            status: status.clone(),
+        }));
+    }
+    Ok(outcomes)
+}
+
+#[cfg(test)]
+mod sweep_tests {
+    use super::*;
+    use test::MockClient;
+
+    const CLAIMANT: &str = "GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM";
+    const LATE_CLAIMANT: &str = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4";
+
+    fn page(claimant: &str) -> String {
+        format!(
+            r#"{{ "_embedded": {{ "records": [{}] }} }}"#,
+            include_str!("../../fixtures/claimable_balance.json").replace(CLAIMANT, claimant)
+        )
+    }
+
+    #[test]
+    fn it_lists_the_balances_whose_predicate_holds() {
+        let client = MockClient::new().with_json("/claimable_balances*", &page(CLAIMANT));
+        assert_eq!(claimable(&client, &CLAIMANT.into(), 0).unwrap().len(), 1);
+        client.assert_requested(&format!("/claimable_balances?claimant={}", CLAIMANT));
+
+        let late = LATE_CLAIMANT.into();
+        assert!(claimable(&client, &late, 0).unwrap().is_empty());
+        assert_eq!(claimable(&client, &late, 1_700_100_000).unwrap().len(), 1);
+    }
+
+    #[cfg(feature = "keys")]
+    #[test]
+    fn it_claims_the_balances_and_reports_each() {
+        use keys::Keypair;
+
+        let keypair = Keypair::random().unwrap();
+        let client = MockClient::new()
+            .with_json("/claimable_balances*", &page(keypair.account_id()))
+            .with_json("/accounts/*", include_str!("../../fixtures/account.json"))
+            .with_json(
+                "/transactions",
+                include_str!("../../fixtures/submitted_transaction.json"),
+            );
+        let outcomes = sweep(&client, &Network::test(), &keypair, 0).unwrap();
+        assert_eq!(outcomes.len(), 1);
+        match *outcomes[0].status() {
+            ClaimStatus::Claimed { ledger, .. } => assert_eq!(ledger, 1_524_302),
+            ref other => panic!("expected the balance to be claimed, not {:?}", other),
+        }
+        assert_eq!(client.hits("/transactions"), 1);
+    }
+
+    #[cfg(feature = "keys")]
+    #[test]
+    fn it_reports_a_failed_claim_without_failing_the_sweep() {
+        use keys::Keypair;
+
+        let keypair = Keypair::random().unwrap();
+        let client = MockClient::new()
+            .with_json("/claimable_balances*", &page(keypair.account_id()))
+            .with_json("/accounts/*", include_str!("../../fixtures/account.json"))
+            .with_response(
+                "/transactions",
+                400,
+                r#"{
+                    "type": "https://stellar.org/horizon-errors/transaction_failed",
+                    "title": "Transaction Failed",
+                    "status": 400,
+                    "detail": "The transaction failed when submitted to the stellar network."
+                }"#,
+            );
+        let outcomes = sweep(&client, &Network::test(), &keypair, 0).unwrap();
+        assert_eq!(outcomes.len(), 1);
+        match *outcomes[0].status() {
+            ClaimStatus::Failed(_) => {}
+            ref other => panic!("expected the claim to fail, not {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! Builds, signs and submits the transactions that the other helpers plan.
+use endpoint::account;
+use error::{Error, Result};
+use keys::Signer;
+use network::Network;
+use resources::envelope::{EnvelopeBuilder, MuxedAccount, Operation};
+use resources::{AccountId, SubmittedTransaction, TransactionEnvelope};
+use std::convert::TryFrom;
+use sync::{self, Requester};
+
+/// Loads `source` for the sequence number its next transaction has to use.
+pub(crate) fn next_sequence<C>(client: &C, source: &AccountId) -> Result<i64>
+where
+    C: Requester + ?Sized,
+{
+    let current = client
+        .request(account::Details::new(source.clone()))?
+        .sequence();
+    i64::try_from(current)
+        .ok()
+        .and_then(|sequence| sequence.checked_add(1))
+        .ok_or(Error::SequenceExhausted(current))
+}
+
+/// Has each of `signers` sign `envelope` for `network`, then submits it.
+pub(crate) fn sign_and_submit<C>(
+    client: &C,
+    network: &Network,
+    mut envelope: TransactionEnvelope,
+    signers: &[&dyn Signer],
+) -> Result<SubmittedTransaction>
+where
+    C: Requester + ?Sized,
+{
+    for signer in signers {
+        envelope.sign(network, signer)?;
+    }
+    sync::submit_transaction(client, &envelope.to_base64(), network)
+}
+
+/// Submits `operations` from `source` in one transaction, signed by each of
+/// `signers`.
+pub(crate) fn submit_operations<C>(
+    client: &C,
+    network: &Network,
+    source: &AccountId,
+    operations: Vec<Operation>,
+    signers: &[&dyn Signer],
+) -> Result<SubmittedTransaction>
+where
+    C: Requester + ?Sized,
+{
+    let builder = EnvelopeBuilder::new(
+        MuxedAccount::new(source, None),
+        next_sequence(client, source)?,
+    );
+    let envelope = operations
+        .into_iter()
+        .fold(builder, EnvelopeBuilder::with_operation)
+        .build()?;
+    sign_and_submit(client, network, envelope, signers)
+}
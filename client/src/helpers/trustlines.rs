@@ -0,0 +1,498 @@
+//! Watches the trustlines of an asset from its issuer's side.
+//!
+//! Issuers of assets that require authorization have to approve every
+//! trustline before it can hold the asset. A `TrustlineMonitor` picks the
+//! trustlines of one asset out of a stream of effects, and reports each one
+//! that is created, authorized or deauthorized, either as an iterator or by
+//! calling a `TrustlineHandler`. Trustlines are created by their holders, so
+//! their effects aren't filed under the issuer, and the monitor needs a
+//! stream of every effect on the network rather than just the issuer's.
+//!
+//! With `with_approvals`, the monitor also builds an unsigned transaction for
+//! each new trustline that sets its authorized flag, ready for the issuer to
+//! review, sign and submit.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use stellar_client::endpoint::{effect, Cursor};
+//! use stellar_client::helpers::trustlines::{TrustlineEvent, TrustlineHandler, TrustlineMonitor};
+//! use stellar_client::resources::AssetIdentifier;
+//! use stellar_client::sync::Client;
+//!
+//! struct Log;
+//!
+//! impl TrustlineHandler for Log {
+//!     fn created(&mut self, event: &TrustlineEvent) {
+//!         println!("{} wants to hold the asset: {:?}", event.trustor(), event.approval());
+//!     }
+//! }
+//!
+//! let client = Client::horizon_test().unwrap();
+//! let asset = AssetIdentifier::alphanum4(
+//!     "USD",
+//!     "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG",
+//! ).unwrap();
+//! let effects = client.stream(effect::All::default().with_cursor("now")).unwrap();
+//! TrustlineMonitor::new(&client, asset, effects)
+//!     .with_approvals(100)
+//!     .run(&mut Log)
+//!     .unwrap();
+//! ```
+use base64;
+use chrono::{DateTime, Utc};
+use endpoint::account;
+use error::Result;
+use resources::effect::{trustline, EffectKind};
+use resources::{AccountId, Amount, AssetIdentifier, Effect};
+use strkey;
+use sync::Requester;
+
+const ENVELOPE_TYPE_TX: u32 = 2;
+const KEY_TYPE_ED25519: u32 = 0;
+const SET_TRUST_LINE_FLAGS: u32 = 21;
+const AUTHORIZED_FLAG: u32 = 1;
+
+/// What happened to a trustline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrustlineChange {
+    /// An account added a trustline to the asset.
+    Created,
+    /// The issuer authorized a trustline to hold the asset.
+    Authorized,
+    /// The issuer took the authorization of a trustline away.
+    Deauthorized,
+}
+
+/// A change to a trustline of the monitored asset.
+#[derive(Debug, Clone)]
+pub struct TrustlineEvent {
+    change: TrustlineChange,
+    trustor: AccountId,
+    limit: Option<Amount>,
+    paging_token: String,
+    created_at: DateTime<Utc>,
+    approval: Option<String>,
+}
+
+impl TrustlineEvent {
+    /// What happened to the trustline.
+    pub fn change(&self) -> TrustlineChange {
+        self.change
+    }
+
+    /// The account that holds the trustline.
+    pub fn trustor(&self) -> &AccountId {
+        &self.trustor
+    }
+
+    /// The limit the trustor set, for a new trustline.
+    pub fn limit(&self) -> Option<Amount> {
+        self.limit
+    }
+
+    /// A paging token suitable for resuming the watch after this event.
+    pub fn paging_token(&self) -> &str {
+        &self.paging_token
+    }
+
+    /// The time the ledger with the change closed.
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    /// The base64 encoded, unsigned transaction envelope that authorizes a
+    /// new trustline, when the monitor builds approvals.
+    pub fn approval(&self) -> Option<&str> {
+        self.approval.as_deref()
+    }
+}
+
+/// Callbacks for the changes a `TrustlineMonitor` sees. Every callback does
+/// nothing unless it is implemented.
+pub trait TrustlineHandler {
+    /// Called when an account adds a trustline to the asset.
+    fn created(&mut self, _event: &TrustlineEvent) {}
+
+    /// Called when the issuer authorizes a trustline.
+    fn authorized(&mut self, _event: &TrustlineEvent) {}
+
+    /// Called when the issuer takes the authorization of a trustline away.
+    fn deauthorized(&mut self, _event: &TrustlineEvent) {}
+}
+
+/// Turns a stream of effects into the `TrustlineEvent`s of one asset.
+#[derive(Debug)]
+pub struct TrustlineMonitor<C, I> {
+    client: C,
+    asset: AssetIdentifier,
+    effects: I,
+    approval_fee: Option<u32>,
+    next_sequence: Option<u64>,
+}
+
+impl<C, I> TrustlineMonitor<C, I>
+where
+    C: Requester,
+    I: Iterator<Item = Result<Effect>>,
+{
+    /// Watches `effects`, usually a stream of `effect::All`, for changes to
+    /// the trustlines of `asset`. The client loads the issuer's account when
+    /// approvals are built.
+    pub fn new(client: C, asset: AssetIdentifier, effects: I) -> Self {
+        TrustlineMonitor {
+            client,
+            asset,
+            effects,
+            approval_fee: None,
+            next_sequence: None,
+        }
+    }
+
+    /// Builds an approval for each new trustline, paying `fee` stroops.
+    ///
+    /// The first approval uses the sequence number after the issuer's
+    /// current one and each approval after it the next one along, so they
+    /// have to be submitted in the order they were built.
+    pub fn with_approvals(mut self, fee: u32) -> Self {
+        self.approval_fee = Some(fee);
+        self
+    }
+
+    /// Calls `handler` for each change until the effects run out.
+    pub fn run<H>(self, handler: &mut H) -> Result<()>
+    where
+        H: TrustlineHandler,
+    {
+        for event in self {
+            let event = event?;
+            match event.change {
+                TrustlineChange::Created => handler.created(&event),
+                TrustlineChange::Authorized => handler.authorized(&event),
+                TrustlineChange::Deauthorized => handler.deauthorized(&event),
+            }
+        }
+        Ok(())
+    }
+
+    // The change an effect made to a trustline of the asset, and the
+    // trustline's holder and limit.
+    fn change(&self, effect: &Effect) -> Option<(TrustlineChange, AccountId, Option<Amount>)> {
+        let issuer = self.asset.issuer()?;
+        // Horizon leaves the issuer out of the authorization effects. They
+        // are filed under the issuer, and the trustor takes the issuer's
+        // place in the asset.
+        let authorization = |account: &AccountId, asset: &AssetIdentifier| {
+            if account == issuer && asset.code() == self.asset.code() {
+                asset.issuer().cloned()
+            } else {
+                None
+            }
+        };
+        match *effect.kind() {
+            EffectKind::Trustline(trustline::Kind::Created(ref created))
+                if *created.asset() == self.asset =>
+            {
+                Some((
+                    TrustlineChange::Created,
+                    created.account().clone(),
+                    Some(created.limit()),
+                ))
+            }
+            EffectKind::Trustline(trustline::Kind::Authorized(ref authorized)) => {
+                authorization(authorized.account(), authorized.asset())
+                    .map(|trustor| (TrustlineChange::Authorized, trustor, None))
+            }
+            EffectKind::Trustline(trustline::Kind::Deauthorized(ref deauthorized)) => {
+                authorization(deauthorized.account(), deauthorized.asset())
+                    .map(|trustor| (TrustlineChange::Deauthorized, trustor, None))
+            }
+            _ => None,
+        }
+    }
+
+    fn approve(&mut self, trustor: &AccountId) -> Result<Option<String>> {
+        let (fee, issuer) = match (self.approval_fee, self.asset.issuer()) {
+            (Some(fee), Some(issuer)) => (fee, issuer.clone()),
+            _ => return Ok(None),
+        };
+        let sequence = match self.next_sequence {
+            Some(sequence) => sequence,
+            None => self
+                .client
+                .request(account::Details::new(issuer.clone()))?
+                .sequence(),
+        };
+        let approval = approval(&issuer, sequence, fee, trustor, &self.asset);
+        if approval.is_some() {
+            self.next_sequence = Some(sequence + 1);
+        }
+        Ok(approval)
+    }
+}
+
+impl<C, I> Iterator for TrustlineMonitor<C, I>
+where
+    C: Requester,
+    I: Iterator<Item = Result<Effect>>,
+{
+    type Item = Result<TrustlineEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let effect = match self.effects.next()? {
+                Ok(effect) => effect,
+                Err(err) => return Some(Err(err)),
+            };
+            let (change, trustor, limit) = match self.change(&effect) {
+                Some(change) => change,
+                None => continue,
+            };
+            let approval = match change {
+                TrustlineChange::Created => match self.approve(&trustor) {
+                    Ok(approval) => approval,
+                    Err(err) => return Some(Err(err)),
+                },
+                _ => None,
+            };
+            return Some(Ok(TrustlineEvent {
+                change,
+                trustor,
+                limit,
+                paging_token: effect.paging_token().clone(),
+                created_at: effect.created_at(),
+                approval,
+            }));
+        }
+    }
+}
+
+/// The base64 encoded, unsigned transaction envelope in which `issuer`
+/// authorizes the trustline of `trustor` to `asset`, with a single set
+/// trustline flags operation. `sequence` is the issuer's current sequence
+/// number; the transaction uses the one after it.
+///
+/// Returns `None` when the asset is lumens, which have no trustlines, or
+/// when an account id isn't valid.
+pub fn approval(
+    issuer: &AccountId,
+    sequence: u64,
+    fee: u32,
+    trustor: &AccountId,
+    asset: &AssetIdentifier,
+) -> Option<String> {
+    let (asset_type, code, width) = match *asset {
+        AssetIdentifier::Native => return None,
+        AssetIdentifier::CreditAlphanum4(ref id) => (1, id.code(), 4),
+        AssetIdentifier::CreditAlphanum12(ref id) => (2, id.code(), 12),
+    };
+    let mut xdr = Vec::new();
+    put_u32(&mut xdr, ENVELOPE_TYPE_TX);
+    put_account_id(&mut xdr, issuer)?;
+    put_u32(&mut xdr, fee);
+    put_u32(&mut xdr, ((sequence + 1) >> 32) as u32);
+    put_u32(&mut xdr, (sequence + 1) as u32);
+    // No preconditions, no memo, and one operation without a source of its
+    // own.
+    put_u32(&mut xdr, 0);
+    put_u32(&mut xdr, 0);
+    put_u32(&mut xdr, 1);
+    put_u32(&mut xdr, 0);
+    put_u32(&mut xdr, SET_TRUST_LINE_FLAGS);
+    put_account_id(&mut xdr, trustor)?;
+    put_u32(&mut xdr, asset_type);
+    xdr.extend_from_slice(code.as_bytes());
+    xdr.extend_from_slice(&[0; 12][..width - code.len()]);
+    put_account_id(&mut xdr, asset.issuer()?)?;
+    // Clear no flags and set the authorized one.
+    put_u32(&mut xdr, 0);
+    put_u32(&mut xdr, AUTHORIZED_FLAG);
+    // No transaction extension, and no signatures yet.
+    put_u32(&mut xdr, 0);
+    put_u32(&mut xdr, 0);
+    Some(base64::encode(&xdr))
+}
+
+fn put_u32(xdr: &mut Vec<u8>, value: u32) {
+    xdr.extend_from_slice(&[
+        (value >> 24) as u8,
+        (value >> 16) as u8,
+        (value >> 8) as u8,
+        value as u8,
+    ]);
+}
+
+/// Writes a `G...` account id as an ed25519 `PublicKey`, which a
+/// `MuxedAccount` without an id shares its encoding with.
+fn put_account_id(xdr: &mut Vec<u8>, account: &AccountId) -> Option<()> {
+    let key = strkey::decode(strkey::ACCOUNT, account.as_str())?;
+    put_u32(xdr, KEY_TYPE_ED25519);
+    xdr.extend_from_slice(&key);
+    Some(())
+}
+
+#[cfg(test)]
+mod trustline_monitor_tests {
+    use super::*;
+    use resources::envelope::OperationBody;
+    use resources::TransactionEnvelope;
+    use serde_json;
+    use test::MockClient;
+
+    const ISSUER: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+    const TRUSTOR: &str = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4";
+
+    fn asset() -> AssetIdentifier {
+        AssetIdentifier::alphanum4("USD", ISSUER).unwrap()
+    }
+
+    fn created(code: &str) -> Effect {
+        serde_json::from_str(&format!(
+            r#"{{
+                "id": "0000033788507721730-0000000001",
+                "paging_token": "33788507721730-1",
+                "created_at": "2018-08-20T20:55:58Z",
+                "account": "{}",
+                "type": "trustline_created",
+                "asset_type": "credit_alphanum4",
+                "asset_code": "{}",
+                "asset_issuer": "{}",
+                "limit": "1000.0"
+            }}"#,
+            TRUSTOR, code, ISSUER
+        ))
+        .unwrap()
+    }
+
+    fn authorization(kind: &str, account: &str) -> Effect {
+        serde_json::from_str(&format!(
+            r#"{{
+                "id": "0000033788507721731-0000000001",
+                "paging_token": "33788507721731-1",
+                "created_at": "2018-08-20T20:55:58Z",
+                "account": "{}",
+                "type": "{}",
+                "asset_type": "credit_alphanum4",
+                "asset_code": "USD",
+                "trustor": "{}"
+            }}"#,
+            account, kind, TRUSTOR
+        ))
+        .unwrap()
+    }
+
+    fn client() -> MockClient {
+        MockClient::new().with_json("/accounts/*", include_str!("../../fixtures/account.json"))
+    }
+
+    #[test]
+    fn it_picks_out_the_trustlines_of_the_asset() {
+        let client = client();
+        let effects = vec![
+            created("USD"),
+            created("EUR"),
+            authorization("trustline_authorized", ISSUER),
+            authorization("trustline_authorized", TRUSTOR),
+            authorization("trustline_deauthorized", ISSUER),
+        ];
+        let events: Vec<_> = TrustlineMonitor::new(&client, asset(), effects.into_iter().map(Ok))
+            .map(Result::unwrap)
+            .collect();
+        let changes: Vec<_> = events.iter().map(TrustlineEvent::change).collect();
+        assert_eq!(
+            changes,
+            vec![
+                TrustlineChange::Created,
+                TrustlineChange::Authorized,
+                TrustlineChange::Deauthorized,
+            ]
+        );
+        assert!(events.iter().all(|event| event.trustor() == TRUSTOR));
+        assert_eq!(events[0].limit(), Some(Amount::new(10_000_000_000)));
+        assert_eq!(events[0].approval(), None);
+        assert!(client.requests().is_empty());
+    }
+
+    #[test]
+    fn it_builds_approvals_in_sequence() {
+        let client = client();
+        let effects = vec![created("USD"), created("USD")];
+        let events: Vec<_> = TrustlineMonitor::new(&client, asset(), effects.into_iter().map(Ok))
+            .with_approvals(100)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(client.requests().len(), 1);
+
+        let sequences: Vec<_> = events
+            .iter()
+            .map(|event| {
+                TransactionEnvelope::from_base64(event.approval().unwrap())
+                    .unwrap()
+                    .sequence()
+            })
+            .collect();
+        assert_eq!(sequences[1], sequences[0] + 1);
+    }
+
+    #[test]
+    fn it_builds_an_approval_that_sets_the_authorized_flag() {
+        let xdr = approval(&ISSUER.into(), 41, 100, &TRUSTOR.into(), &asset()).unwrap();
+        let envelope = TransactionEnvelope::from_base64(&xdr).unwrap();
+        assert_eq!(envelope.source_account().to_string(), ISSUER);
+        assert_eq!(envelope.fee(), 100);
+        assert_eq!(envelope.sequence(), 42);
+        assert!(envelope.signatures().is_empty());
+        match *envelope.operations()[0].body() {
+            OperationBody::SetTrustLineFlags {
+                ref trustor,
+                ref asset,
+                clear_flags,
+                set_flags,
+            } => {
+                assert_eq!(trustor, TRUSTOR);
+                assert_eq!(*asset, self::asset());
+                assert_eq!(clear_flags, 0);
+                assert_eq!(set_flags, 1);
+            }
+            ref other => panic!("expected a set trustline flags operation, got {:?}", other),
+        }
+        assert_eq!(
+            approval(
+                &ISSUER.into(),
+                41,
+                100,
+                &TRUSTOR.into(),
+                &AssetIdentifier::native()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn it_calls_the_handler_for_each_change() {
+        #[derive(Default)]
+        struct Counts(usize, usize, usize);
+
+        impl TrustlineHandler for Counts {
+            fn created(&mut self, _event: &TrustlineEvent) {
+                self.0 += 1;
+            }
+
+            fn deauthorized(&mut self, _event: &TrustlineEvent) {
+                self.2 += 1;
+            }
+        }
+
+        let client = client();
+        let effects = vec![
+            created("USD"),
+            authorization("trustline_authorized", ISSUER),
+            authorization("trustline_deauthorized", ISSUER),
+        ];
+        let mut counts = Counts::default();
+        TrustlineMonitor::new(&client, asset(), effects.into_iter().map(Ok))
+            .run(&mut counts)
+            .unwrap();
+        assert_eq!((counts.0, counts.1, counts.2), (1, 0, 1));
+    }
+}
@@ -0,0 +1,576 @@
+//! A reader for the history archives that stellar-core publishes, which hold
+//! every ledger header, transaction and result since the network began.
+//!
+//! An archive is a tree of files served over http, often straight from an S3
+//! bucket. Every 64 ledgers core publishes a checkpoint: a JSON state file
+//! plus gzipped XDR files of the checkpoint's ledger headers, transactions
+//! and results. The files are named after the last ledger of the checkpoint,
+//! and `checkpoint_containing` finds that ledger for any other.
+//!
+//! Each header can be checked against its own hash and the hash of the
+//! ledger before it, and the transactions and results against the hashes in
+//! the header, so data from an archive or from horizon can be trusted as far
+//! as the ledger it is checked against.
+//!
+//! This module is only available with the `history` feature enabled.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use stellar_client::history::Archive;
+//!
+//! let archive = Archive::new("https://history.stellar.org/prd/core-testnet/core_testnet_001").unwrap();
+//! let state = archive.state().unwrap();
+//! for header in archive.ledger_headers(state.current_ledger()).unwrap() {
+//!     println!("ledger {} closed at {}", header.sequence(), header.close_time());
+//! }
+//! ```
+use error::{Error, Result};
+use flate2::read::GzDecoder;
+use http::Uri;
+use reqwest::{self, StatusCode};
+use serde_json;
+use std::io::Read;
+use std::time::Duration;
+
+mod state;
+mod xdr;
+
+pub use self::state::{BucketLevel, HistoryArchiveState};
+pub use self::xdr::{
+    LedgerHeader, TransactionHistoryEntry, TransactionResultEntry, TransactionResultPair,
+};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const DEFAULT_MAX_FILE_SIZE: u64 = 512 * 1024 * 1024;
+
+/// The number of ledgers in each checkpoint.
+pub const CHECKPOINT_FREQUENCY: u32 = 64;
+
+/// The checkpoint that `ledger` is published in, which is named after the
+/// checkpoint's last ledger.
+///
+/// ## Example
+///
+/// ```
+/// use stellar_client::history::checkpoint_containing;
+///
+/// assert_eq!(checkpoint_containing(1), 63);
+/// assert_eq!(checkpoint_containing(64), 127);
+/// ```
+pub fn checkpoint_containing(ledger: u32) -> u32 {
+    (ledger / CHECKPOINT_FREQUENCY) * CHECKPOINT_FREQUENCY + (CHECKPOINT_FREQUENCY - 1)
+}
+
+/// A synchronous reader for a history archive.
+#[derive(Debug)]
+pub struct Archive {
+    inner: reqwest::Client,
+    uri: String,
+    max_file_size: u64,
+}
+
+impl Archive {
+    /// Constructs a reader for the archive at `uri`. An `s3://bucket/path`
+    /// uri is read over https, so the bucket must be public.
+    pub fn new(uri: &str) -> Result<Self> {
+        let uri = match uri.trim_end_matches('/') {
+            uri if uri.starts_with("s3://") => format!("https://s3.amazonaws.com/{}", &uri[5..]),
+            uri => uri.to_string(),
+        };
+        // Ensure that the uri passed in can parse.
+        let _: Uri = uri.parse()?;
+        let inner = reqwest::ClientBuilder::new()
+            .timeout(DEFAULT_TIMEOUT)
+            .build()?;
+        Ok(Archive {
+            inner,
+            uri,
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+        })
+    }
+
+    /// Sets the largest file, in bytes once decompressed, that will be
+    /// read from the archive. Larger files fail with `Error::BodyTooLarge`
+    /// rather than being read into memory. Defaults to 512 MiB.
+    pub fn with_max_file_size(mut self, max: u64) -> Self {
+        self.max_file_size = max;
+        self
+    }
+
+    /// The uri that files are read from.
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Fetches the state of the archive, which names the latest checkpoint
+    /// it has published.
+    pub fn state(&self) -> Result<HistoryArchiveState> {
+        let response = self.get(".well-known/stellar-history.json")?;
+        Ok(serde_json::from_reader(response)?)
+    }
+
+    /// Fetches the state of the bucket list at the checkpoint containing
+    /// `ledger`.
+    pub fn checkpoint_state(&self, ledger: u32) -> Result<HistoryArchiveState> {
+        let response = self.get(&path("history", ledger, "json"))?;
+        Ok(serde_json::from_reader(response)?)
+    }
+
+    /// Fetches the headers of every ledger in the checkpoint containing
+    /// `ledger`.
+    pub fn ledger_headers(&self, ledger: u32) -> Result<Vec<LedgerHeader>> {
+        let bytes = self.xdr(&path("ledger", ledger, "xdr.gz"))?;
+        Ok(xdr::records(&bytes, LedgerHeader::from_record)?)
+    }
+
+    /// Fetches the transactions of the ledgers in the checkpoint containing
+    /// `ledger`. Ledgers without transactions are left out.
+    pub fn transactions(&self, ledger: u32) -> Result<Vec<TransactionHistoryEntry>> {
+        let bytes = self.xdr(&path("transactions", ledger, "xdr.gz"))?;
+        Ok(xdr::records(&bytes, TransactionHistoryEntry::from_record)?)
+    }
+
+    /// Fetches the transaction results of the ledgers in the checkpoint
+    /// containing `ledger`. Ledgers without transactions are left out.
+    pub fn results(&self, ledger: u32) -> Result<Vec<TransactionResultEntry>> {
+        let bytes = self.xdr(&path("results", ledger, "xdr.gz"))?;
+        Ok(xdr::records(&bytes, TransactionResultEntry::from_record)?)
+    }
+
+    fn get(&self, path: &str) -> Result<reqwest::Response> {
+        let response = self.inner.get(&format!("{}/{}", self.uri, path)).send()?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Err(Error::NotInArchive(path.to_string())),
+            status if !status.is_success() => Err(Error::ServerError(None)),
+            _ => Ok(response),
+        }
+    }
+
+    /// Fetches and decompresses an XDR file. Some servers have already
+    /// decompressed it by the time it arrives, so that is checked first.
+    fn xdr(&self, path: &str) -> Result<Vec<u8>> {
+        let mut compressed = Vec::new();
+        self.get(path)?
+            .take(self.max_file_size + 1)
+            .read_to_end(&mut compressed)?;
+        let bytes = if compressed.starts_with(GZIP_MAGIC) {
+            let mut bytes = Vec::new();
+            GzDecoder::new(&compressed[..])
+                .take(self.max_file_size + 1)
+                .read_to_end(&mut bytes)
+                .map_err(Error::Decompress)?;
+            bytes
+        } else {
+            compressed
+        };
+        if bytes.len() as u64 > self.max_file_size {
+            return Err(Error::BodyTooLarge(self.max_file_size, None));
+        }
+        Ok(bytes)
+    }
+}
+
+/// The path of a checkpoint file, which is spread across directories by the
+/// first three bytes of the checkpoint in hex.
+fn path(category: &str, ledger: u32, extension: &str) -> String {
+    let hex = format!("{:08x}", checkpoint_containing(ledger));
+    format!(
+        "{}/{}/{}/{}/{}-{}.{}",
+        category,
+        &hex[0..2],
+        &hex[2..4],
+        &hex[4..6],
+        category,
+        hex,
+        extension
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use openssl::sha::sha256;
+    use resources::LedgerBuilder;
+    use std::io::Write;
+    use test::{TestResponse, TestServer};
+    use xdr::XdrError;
+
+    // The single payment of a V0 envelope from the public network.
+    const ENVELOPE: &str = "AAAAAH2Hmt1JWMfqAdUlDeyUtO9V8zPqJ0aLG8KrZyXv78QGAAAAZAAIgb4AAtRiAAAAAAAAAAEAAAAAAAAAAQAAAAA\
+                            AAAABAAAAAJZgy/0KAk+3JQwG8hPGBNTZVGew2Joi1TwkVBdwPn9QAAAAAAAAAAA7mUNgAAAAAAAAAAHv78QGAAAAQI\
+                            TCXzWfgHgAjF3djx1VK9JK08UypfpftzFoyNXv7A0Agau/ur/3/+ZZtQb8xSsao8yVAsTiV4ttiT/HqfvvlAk=";
+
+    // Answers a single request on a local port with `file`. Returns the
+    // uri of the archive along with the server, to check which file was
+    // asked for.
+    fn serve(file: Vec<u8>) -> (String, TestServer) {
+        serve_response(TestResponse::new("200 OK").with_body("application/octet-stream", file))
+    }
+
+    fn serve_response(response: TestResponse) -> (String, TestServer) {
+        let server = TestServer::new(vec![response]);
+        (format!("{}/archive", server.uri()), server)
+    }
+
+    fn assert_fetched(server: &TestServer, name: &str) {
+        assert_eq!(server.next_request().path(), format!("/archive/{}", name));
+    }
+
+    // Frames each record as a single fragment and gzips the lot, the way
+    // core writes checkpoint files.
+    fn checkpoint_file(records: &[Vec<u8>]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&frames(records)).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn frames(records: &[Vec<u8>]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for record in records {
+            bytes.extend_from_slice(&be(record.len() as u32 | 0x8000_0000));
+            bytes.extend_from_slice(record);
+        }
+        bytes
+    }
+
+    fn be(value: u32) -> [u8; 4] {
+        [
+            (value >> 24) as u8,
+            (value >> 16) as u8,
+            (value >> 8) as u8,
+            value as u8,
+        ]
+    }
+
+    // A history entry for a header with made up hashes.
+    fn ledger_header(sequence: u32) -> Vec<u8> {
+        header_entry(&[1; 32], header_xdr(sequence, &[2; 32], &[3; 32], &[4; 32]))
+    }
+
+    fn header_entry(hash: &[u8], header: Vec<u8>) -> Vec<u8> {
+        let mut record = hash.to_vec();
+        record.extend_from_slice(&header);
+        record.extend_from_slice(&be(0));
+        record
+    }
+
+    fn header_xdr(sequence: u32, previous: &[u8], tx_set: &[u8], results: &[u8]) -> Vec<u8> {
+        let mut record = Vec::new();
+        // The version, previous hash and transaction set hash.
+        record.extend_from_slice(&be(11));
+        record.extend_from_slice(previous);
+        record.extend_from_slice(tx_set);
+        // Closed at 1533081600 with no upgrades and an unsigned value.
+        record.extend_from_slice(&be(0));
+        record.extend_from_slice(&be(1_533_081_600));
+        record.extend_from_slice(&be(0));
+        record.extend_from_slice(&be(0));
+        // The result and bucket list hashes.
+        record.extend_from_slice(results);
+        record.extend_from_slice(&[5; 32]);
+        record.extend_from_slice(&be(sequence));
+        // 100 billion lumens in stroops, then a fee pool of 1 lumen.
+        record.extend_from_slice(&be(0x0DE0_B6B3));
+        record.extend_from_slice(&be(0xA764_0000));
+        record.extend_from_slice(&be(0));
+        record.extend_from_slice(&be(10_000_000));
+        // Inflation sequence and id pool.
+        record.extend_from_slice(&be(7));
+        record.extend_from_slice(&be(0));
+        record.extend_from_slice(&be(42));
+        // Base fee, reserve and set size.
+        record.extend_from_slice(&be(100));
+        record.extend_from_slice(&be(5_000_000));
+        record.extend_from_slice(&be(1000));
+        record.extend_from_slice(&[0; 128]);
+        // The header extension.
+        record.extend_from_slice(&be(0));
+        record
+    }
+
+    #[test]
+    fn it_finds_the_checkpoint_of_a_ledger() {
+        assert_eq!(checkpoint_containing(0), 63);
+        assert_eq!(checkpoint_containing(63), 63);
+        assert_eq!(checkpoint_containing(64), 127);
+        assert_eq!(checkpoint_containing(17_535_807), 17_535_807);
+        assert_eq!(checkpoint_containing(17_535_808), 17_535_871);
+    }
+
+    #[test]
+    fn it_names_checkpoint_files() {
+        assert_eq!(
+            path("ledger", 100, "xdr.gz"),
+            "ledger/00/00/00/ledger-0000007f.xdr.gz"
+        );
+        assert_eq!(
+            path("history", 17_535_807, "json"),
+            "history/01/0b/93/history-010b933f.json"
+        );
+    }
+
+    #[test]
+    fn it_reads_s3_buckets_over_https() {
+        let archive =
+            Archive::new("s3://history.stellar.org/prd/core-live/core_live_001/").unwrap();
+        assert_eq!(
+            archive.uri(),
+            "https://s3.amazonaws.com/history.stellar.org/prd/core-live/core_live_001"
+        );
+    }
+
+    #[test]
+    fn it_reads_the_archive_state() {
+        let state = br#"{
+            "version": 2,
+            "server": "stellar-core 19.5.0",
+            "currentLedger": 127,
+            "networkPassphrase": "Test SDF Network ; September 2015",
+            "currentBuckets": [
+                {
+                    "curr": "5a9a3c5e4d1f1a3b3a5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b1c2d3e4f5a6b7c",
+                    "next": {"state": 0},
+                    "snap": "0000000000000000000000000000000000000000000000000000000000000000"
+                },
+                {
+                    "curr": "0000000000000000000000000000000000000000000000000000000000000000",
+                    "next": {"state": 1, "output": "5a9a3c5e4d1f1a3b3a5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b1c2d3e4f5a6b7c"},
+                    "snap": "1e2f3a4b5c6d7e8f9a0b1c2d3e4f5a6b7c5a9a3c5e4d1f1a3b3a5e6f7a8b9c0d"
+                }
+            ]
+        }"#;
+        let (uri, server) = serve(state.to_vec());
+        let state = Archive::new(&uri).unwrap().state().unwrap();
+        assert_eq!(state.current_ledger(), 127);
+        assert_eq!(
+            state.network_passphrase(),
+            Some("Test SDF Network ; September 2015")
+        );
+        assert_eq!(state.current_buckets().len(), 2);
+        assert_eq!(
+            state.buckets(),
+            vec![
+                "5a9a3c5e4d1f1a3b3a5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b1c2d3e4f5a6b7c",
+                "1e2f3a4b5c6d7e8f9a0b1c2d3e4f5a6b7c5a9a3c5e4d1f1a3b3a5e6f7a8b9c0d",
+            ]
+        );
+        assert_fetched(&server, ".well-known/stellar-history.json");
+    }
+
+    #[test]
+    fn it_reads_ledger_headers() {
+        let file = checkpoint_file(&[ledger_header(126), ledger_header(127)]);
+        let (uri, server) = serve(file);
+        let headers = Archive::new(&uri).unwrap().ledger_headers(100).unwrap();
+        assert_eq!(headers.len(), 2);
+        let header = &headers[1];
+        assert_eq!(header.sequence(), 127);
+        assert_eq!(header.hash(), &"01".repeat(32));
+        assert_eq!(header.previous_ledger_hash(), &"02".repeat(32));
+        assert_eq!(header.ledger_version(), 11);
+        assert_eq!(
+            header.close_time().to_rfc3339(),
+            "2018-08-01T00:00:00+00:00"
+        );
+        assert_eq!(header.total_coins().to_string(), "100000000000.0000000");
+        assert_eq!(header.fee_pool().to_string(), "1.0000000");
+        assert_eq!(header.id_pool(), 42);
+        assert_eq!(header.base_reserve(), 5_000_000);
+        assert_eq!(header.skip_list().len(), 4);
+        assert_fetched(&server, "ledger/00/00/00/ledger-0000007f.xdr.gz");
+    }
+
+    #[test]
+    fn it_reads_transactions() {
+        let envelope = ::base64::decode(ENVELOPE).unwrap();
+        let mut record = be(64).to_vec();
+        record.extend_from_slice(&[0; 32]);
+        record.extend_from_slice(&be(1));
+        record.extend_from_slice(&envelope);
+        record.extend_from_slice(&be(0));
+        let file = checkpoint_file(&[record]);
+        let (uri, server) = serve(file);
+        let entries = Archive::new(&uri).unwrap().transactions(127).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].ledger_sequence(), 64);
+        let transactions = entries[0].transactions().unwrap();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].fee(), 100);
+        assert_fetched(&server, "transactions/00/00/00/transactions-0000007f.xdr.gz");
+    }
+
+    #[test]
+    fn it_reads_results() {
+        // A successful payment followed by one that was underfunded.
+        let success = ::base64::decode("AAAAAAAAAGQAAAAAAAAAAQAAAAAAAAABAAAAAAAAAAA=").unwrap();
+        let failure = ::base64::decode("AAAAAAAAAGT/////AAAAAQAAAAAAAAAB/////gAAAAA=").unwrap();
+        let mut record = be(64).to_vec();
+        record.extend_from_slice(&be(2));
+        record.extend_from_slice(&[6; 32]);
+        record.extend_from_slice(&success);
+        record.extend_from_slice(&[7; 32]);
+        record.extend_from_slice(&failure);
+        record.extend_from_slice(&be(0));
+        let file = checkpoint_file(&[record]);
+        let (uri, server) = serve(file);
+        let entries = Archive::new(&uri).unwrap().results(64).unwrap();
+        let results = entries[0].results();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].hash(), &"06".repeat(32));
+        assert_eq!(
+            results[0].result_xdr(),
+            "AAAAAAAAAGQAAAAAAAAAAQAAAAAAAAABAAAAAAAAAAA="
+        );
+        assert!(results[0].failure_reasons().unwrap().is_none());
+        let reasons = results[1].failure_reasons().unwrap().unwrap();
+        assert_eq!(reasons.operations(), &vec!["op_underfunded".to_string()]);
+        assert_fetched(&server, "results/00/00/00/results-0000007f.xdr.gz");
+    }
+
+    #[test]
+    fn it_joins_records_split_into_fragments() {
+        let record = ledger_header(63);
+        let mut bytes = be(100).to_vec();
+        bytes.extend_from_slice(&record[..100]);
+        bytes.extend_from_slice(&be((record.len() - 100) as u32 | 0x8000_0000));
+        bytes.extend_from_slice(&record[100..]);
+        let headers = xdr::records(&bytes, LedgerHeader::from_record).unwrap();
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0].sequence(), 63);
+        assert_eq!(
+            xdr::records(&bytes[..104], LedgerHeader::from_record).unwrap_err(),
+            XdrError::UnexpectedEnd
+        );
+    }
+
+    #[test]
+    fn it_reports_files_that_fail_to_decompress() {
+        let mut file = checkpoint_file(&[ledger_header(127)]);
+        file.truncate(file.len() / 2);
+        let (uri, server) = serve(file);
+        match Archive::new(&uri).unwrap().ledger_headers(100) {
+            Err(Error::Decompress(_)) => {}
+            other => panic!("expected a decompression error, got {:?}", other),
+        }
+        assert_fetched(&server, "ledger/00/00/00/ledger-0000007f.xdr.gz");
+    }
+
+    #[test]
+    fn it_refuses_files_that_decompress_past_the_limit() {
+        let file = checkpoint_file(&[vec![0; 4096]]);
+        assert!(file.len() < 1024);
+        let (uri, server) = serve(file);
+        let archive = Archive::new(&uri).unwrap().with_max_file_size(1024);
+        match archive.ledger_headers(100) {
+            Err(Error::BodyTooLarge(1024, None)) => {}
+            other => panic!("expected the file to be too large, got {:?}", other),
+        }
+        assert_fetched(&server, "ledger/00/00/00/ledger-0000007f.xdr.gz");
+    }
+
+    #[test]
+    fn it_reports_files_missing_from_the_archive() {
+        let (uri, _server) = serve_response(TestResponse::new("404 Not Found"));
+        match Archive::new(&uri).unwrap().results(64) {
+            Err(Error::NotInArchive(path)) => {
+                assert_eq!(path, "results/00/00/00/results-0000007f.xdr.gz")
+            }
+            other => panic!("expected a missing file, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn it_verifies_a_chain_of_headers() {
+        let first = header_xdr(63, &[2; 32], &[3; 32], &[4; 32]);
+        let first_hash = sha256(&first);
+        let second = header_xdr(64, &first_hash, &[3; 32], &[4; 32]);
+        let records = vec![
+            header_entry(&first_hash, first.clone()),
+            header_entry(&sha256(&second), second),
+            ledger_header(65),
+        ];
+        let headers = xdr::records(&frames(&records), LedgerHeader::from_record).unwrap();
+        assert!(headers[0].verify());
+        assert!(headers[1].verify());
+        assert!(headers[1].follows(&headers[0]));
+        assert!(!headers[0].follows(&headers[1]));
+        // The made up hash of the last header isn't its hash.
+        assert!(!headers[2].verify());
+        assert!(!headers[2].follows(&headers[1]));
+
+        // Horizon's header xdr leaves out the hash, so it is computed.
+        let header = LedgerHeader::from_base64(&::base64::encode(&first)).unwrap();
+        assert_eq!(header.hash(), headers[0].hash());
+        assert!(header.verify());
+        let ledger = LedgerBuilder::new(63, headers[0].hash()).build();
+        assert!(header.verify_ledger(&ledger));
+        assert!(!headers[1].verify_ledger(&ledger));
+    }
+
+    #[test]
+    fn it_verifies_transactions_and_results_against_the_header() {
+        let envelope = ::base64::decode(ENVELOPE).unwrap();
+        // A legacy set hashes the previous ledger hash then each envelope.
+        let mut legacy = be(64).to_vec();
+        legacy.extend_from_slice(&[8; 32]);
+        legacy.extend_from_slice(&be(1));
+        legacy.extend_from_slice(&envelope);
+        legacy.extend_from_slice(&be(0));
+        let mut contents = vec![8; 32];
+        contents.extend_from_slice(&envelope);
+        let legacy_hash = sha256(&contents);
+
+        // A generalized set, with one phase of one component that has a
+        // discounted base fee, hashes as a whole.
+        let mut generalized = be(1).to_vec();
+        generalized.extend_from_slice(&[8; 32]);
+        for &value in &[1, 0, 1, 0, 1, 0, 100, 1] {
+            generalized.extend_from_slice(&be(value));
+        }
+        generalized.extend_from_slice(&envelope);
+        let generalized_hash = sha256(&generalized);
+        let mut current = be(65).to_vec();
+        current.extend_from_slice(&[0; 32]);
+        current.extend_from_slice(&be(0));
+        current.extend_from_slice(&be(1));
+        current.extend_from_slice(&generalized);
+
+        let entries = xdr::records(
+            &frames(&[legacy, current]),
+            TransactionHistoryEntry::from_record,
+        )
+        .unwrap();
+        assert_eq!(entries[1].transactions().unwrap().len(), 1);
+
+        let success = ::base64::decode("AAAAAAAAAGQAAAAAAAAAAQAAAAAAAAABAAAAAAAAAAA=").unwrap();
+        let mut result_set = be(1).to_vec();
+        result_set.extend_from_slice(&[6; 32]);
+        result_set.extend_from_slice(&success);
+        let mut results = be(64).to_vec();
+        results.extend_from_slice(&result_set);
+        results.extend_from_slice(&be(0));
+        let results =
+            xdr::records(&frames(&[results]), TransactionResultEntry::from_record).unwrap();
+
+        let headers = vec![
+            header_entry(
+                &[1; 32],
+                header_xdr(64, &[2; 32], &legacy_hash, &sha256(&result_set)),
+            ),
+            header_entry(
+                &[1; 32],
+                header_xdr(65, &[2; 32], &generalized_hash, &[4; 32]),
+            ),
+        ];
+        let headers = xdr::records(&frames(&headers), LedgerHeader::from_record).unwrap();
+        assert!(headers[0].verify_transactions(&entries[0]).unwrap());
+        assert!(headers[0].verify_results(&results[0]));
+        assert!(headers[1].verify_transactions(&entries[1]).unwrap());
+        assert!(!headers[0].verify_transactions(&entries[1]).unwrap());
+        assert!(!headers[1].verify_results(&results[0]));
+    }
+}
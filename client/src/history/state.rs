@@ -0,0 +1,98 @@
+/// The state of a history archive, or of the bucket list at one checkpoint.
+/// It is the JSON found at `.well-known/stellar-history.json` and beside each
+/// checkpoint.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryArchiveState {
+    version: u32,
+    server: String,
+    current_ledger: u32,
+    #[serde(default)]
+    network_passphrase: Option<String>,
+    current_buckets: Vec<BucketLevel>,
+}
+
+impl HistoryArchiveState {
+    /// The version of the state's format.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// The stellar-core build that published the state.
+    pub fn server(&self) -> &str {
+        &self.server
+    }
+
+    /// The last ledger of the checkpoint the state describes.
+    pub fn current_ledger(&self) -> u32 {
+        self.current_ledger
+    }
+
+    /// The passphrase of the network the archive is for. Only states of
+    /// version 2 and up have it.
+    pub fn network_passphrase(&self) -> Option<&str> {
+        self.network_passphrase.as_deref()
+    }
+
+    /// The levels of the bucket list, from the smallest and most recently
+    /// changed to the largest.
+    pub fn current_buckets(&self) -> &Vec<BucketLevel> {
+        &self.current_buckets
+    }
+
+    /// The hash of every bucket the state refers to, once each and without
+    /// the hash of the empty bucket.
+    pub fn buckets(&self) -> Vec<&str> {
+        let mut buckets: Vec<&str> = Vec::new();
+        for level in &self.current_buckets {
+            let hashes = vec![Some(level.curr()), Some(level.snap()), level.next_output()];
+            for hash in hashes.into_iter().flatten() {
+                if !is_empty_bucket(hash) && !buckets.contains(&hash) {
+                    buckets.push(hash);
+                }
+            }
+        }
+        buckets
+    }
+}
+
+/// One level of the bucket list.
+#[derive(Deserialize, Debug, Clone)]
+pub struct BucketLevel {
+    curr: String,
+    snap: String,
+    next: FutureBucket,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct FutureBucket {
+    state: u32,
+    #[serde(default)]
+    output: Option<String>,
+}
+
+impl BucketLevel {
+    /// The hash of the bucket the level is filling.
+    pub fn curr(&self) -> &str {
+        &self.curr
+    }
+
+    /// The hash of the bucket the level last filled, which is waiting to
+    /// be merged into the next level.
+    pub fn snap(&self) -> &str {
+        &self.snap
+    }
+
+    /// The hash of the bucket a finished merge into the level produced, if
+    /// there is one.
+    pub fn next_output(&self) -> Option<&str> {
+        match self.next.state {
+            0 => None,
+            _ => self.next.output.as_deref(),
+        }
+    }
+}
+
+fn is_empty_bucket(hash: &str) -> bool {
+    hash.bytes().all(|b| b == b'0')
+}
@@ -0,0 +1,471 @@
+//! The XDR records in a history archive's checkpoint files.
+use base64;
+use chrono::{DateTime, TimeZone, Utc};
+use openssl::sha::sha256;
+use resources::{skip_transaction_result, Amount, FailureReasons, Ledger, TransactionEnvelope};
+use std::mem;
+use xdr::{hex, Reader, Result, XdrError};
+
+const LAST_FRAGMENT: u32 = 0x8000_0000;
+
+/// Splits an uncompressed checkpoint file into its records and decodes each
+/// one. Records are framed as in RFC 5531: each fragment starts with its
+/// length, and the high bit of the length marks the last fragment of a
+/// record.
+pub(super) fn records<T, F>(bytes: &[u8], mut read: F) -> Result<Vec<T>>
+where
+    F: FnMut(Vec<u8>) -> Result<T>,
+{
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut position = 0;
+    while position < bytes.len() {
+        if bytes.len() - position < 4 {
+            return Err(XdrError::UnexpectedEnd);
+        }
+        let mark = bytes[position..position + 4]
+            .iter()
+            .fold(0, |mark, &b| (mark << 8) | u32::from(b));
+        position += 4;
+        let len = (mark & !LAST_FRAGMENT) as usize;
+        if bytes.len() - position < len {
+            return Err(XdrError::UnexpectedEnd);
+        }
+        record.extend_from_slice(&bytes[position..position + len]);
+        position += len;
+        if mark & LAST_FRAGMENT != 0 {
+            records.push(read(mem::take(&mut record))?);
+        }
+    }
+    if !record.is_empty() {
+        return Err(XdrError::UnexpectedEnd);
+    }
+    Ok(records)
+}
+
+/// The header of a closed ledger, from the `ledger` files of an archive.
+#[derive(Debug, Clone)]
+pub struct LedgerHeader {
+    hash: String,
+    ledger_version: u32,
+    previous_ledger_hash: String,
+    tx_set_hash: String,
+    close_time: DateTime<Utc>,
+    upgrades: Vec<String>,
+    tx_set_result_hash: String,
+    bucket_list_hash: String,
+    sequence: u32,
+    total_coins: Amount,
+    fee_pool: Amount,
+    inflation_sequence: u32,
+    id_pool: u64,
+    base_fee: u32,
+    base_reserve: u32,
+    max_tx_set_size: u32,
+    skip_list: Vec<String>,
+    flags: u32,
+    xdr: Vec<u8>,
+}
+
+impl LedgerHeader {
+    /// Decodes a `LedgerHeaderHistoryEntry`.
+    pub(super) fn from_record(record: Vec<u8>) -> Result<LedgerHeader> {
+        let r = &mut Reader::new(&record);
+        let hash = r.hash()?;
+        let header = LedgerHeader::read(r, hash)?;
+        r.extension_point()?;
+        Ok(header)
+    }
+
+    /// Decodes a base64 encoded `LedgerHeader`, such as the `header_xdr`
+    /// horizon gives for a ledger. Its hash is computed from the XDR.
+    pub fn from_base64(xdr: &str) -> Result<LedgerHeader> {
+        let r = &mut Reader::from_base64(xdr)?;
+        LedgerHeader::read(r, String::new()).map(|mut header| {
+            header.hash = header.computed_hash();
+            header
+        })
+    }
+
+    fn read(r: &mut Reader, hash: String) -> Result<LedgerHeader> {
+        let start = r.position();
+        let ledger_version = r.u32()?;
+        let previous_ledger_hash = r.hash()?;
+        let tx_set_hash = r.hash()?;
+        let close_time = Utc.timestamp(r.u64()? as i64, 0);
+        let upgrades = r.vec(|r| r.opaque().map(|upgrade| base64::encode(&upgrade)))?;
+        match r.i32()? {
+            0 => {}
+            // Signed by the validator that nominated the value.
+            1 => {
+                r.account_id()?;
+                r.opaque()?;
+            }
+            other => return Err(XdrError::UnknownDiscriminant("StellarValueExt", other)),
+        }
+        let tx_set_result_hash = r.hash()?;
+        let bucket_list_hash = r.hash()?;
+        let sequence = r.u32()?;
+        let total_coins = Amount::new(r.i64()?);
+        let fee_pool = Amount::new(r.i64()?);
+        let inflation_sequence = r.u32()?;
+        let id_pool = r.u64()?;
+        let base_fee = r.u32()?;
+        let base_reserve = r.u32()?;
+        let max_tx_set_size = r.u32()?;
+        let skip_list = (0..4).map(|_| r.hash()).collect::<Result<_>>()?;
+        let flags = match r.i32()? {
+            0 => 0,
+            1 => {
+                let flags = r.u32()?;
+                r.extension_point()?;
+                flags
+            }
+            other => return Err(XdrError::UnknownDiscriminant("LedgerHeaderExt", other)),
+        };
+        Ok(LedgerHeader {
+            hash,
+            ledger_version,
+            previous_ledger_hash,
+            tx_set_hash,
+            close_time,
+            upgrades,
+            tx_set_result_hash,
+            bucket_list_hash,
+            sequence,
+            total_coins,
+            fee_pool,
+            inflation_sequence,
+            id_pool,
+            base_fee,
+            base_reserve,
+            max_tx_set_size,
+            skip_list,
+            flags,
+            xdr: r.since(start).to_vec(),
+        })
+    }
+
+    /// The hex encoded SHA-256 hash of the header's XDR, which is what
+    /// `hash` should be.
+    pub fn computed_hash(&self) -> String {
+        hex(&sha256(&self.xdr))
+    }
+
+    /// Whether the header hashes to the hash the archive recorded for it.
+    pub fn verify(&self) -> bool {
+        self.computed_hash() == self.hash
+    }
+
+    /// Whether the header is the one after `previous` in the chain of
+    /// ledgers.
+    pub fn follows(&self, previous: &LedgerHeader) -> bool {
+        self.sequence == previous.sequence + 1 && self.previous_ledger_hash == previous.hash
+    }
+
+    /// Whether `ledger`, as horizon served it, is this ledger.
+    pub fn verify_ledger(&self, ledger: &Ledger) -> bool {
+        self.sequence == ledger.sequence() && &self.hash == ledger.hash()
+    }
+
+    /// Whether `entry` holds the transactions the ledger closed with.
+    pub fn verify_transactions(&self, entry: &TransactionHistoryEntry) -> Result<bool> {
+        Ok(self.sequence == entry.ledger_sequence && self.tx_set_hash == entry.tx_set_hash()?)
+    }
+
+    /// Whether `entry` holds the results of the ledger's transactions.
+    pub fn verify_results(&self, entry: &TransactionResultEntry) -> bool {
+        self.sequence == entry.ledger_sequence
+            && self.tx_set_result_hash == entry.tx_set_result_hash()
+    }
+
+    /// The hex encoded hash of the ledger.
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    /// The protocol version the ledger closed under.
+    pub fn ledger_version(&self) -> u32 {
+        self.ledger_version
+    }
+
+    /// The hex encoded hash of the ledger before this one.
+    pub fn previous_ledger_hash(&self) -> &str {
+        &self.previous_ledger_hash
+    }
+
+    /// The hex encoded hash of the ledger's transaction set.
+    pub fn tx_set_hash(&self) -> &str {
+        &self.tx_set_hash
+    }
+
+    /// When the ledger closed.
+    pub fn close_time(&self) -> DateTime<Utc> {
+        self.close_time
+    }
+
+    /// The base64 encoded `LedgerUpgrade`s applied in the ledger.
+    pub fn upgrades(&self) -> &Vec<String> {
+        &self.upgrades
+    }
+
+    /// The hex encoded hash of the results of the ledger's transactions.
+    pub fn tx_set_result_hash(&self) -> &str {
+        &self.tx_set_result_hash
+    }
+
+    /// The hex encoded hash of the bucket list after the ledger closed.
+    pub fn bucket_list_hash(&self) -> &str {
+        &self.bucket_list_hash
+    }
+
+    /// The sequence number of the ledger.
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    /// The number of lumens in existence.
+    pub fn total_coins(&self) -> Amount {
+        self.total_coins
+    }
+
+    /// The fees collected and not yet handed out by inflation.
+    pub fn fee_pool(&self) -> Amount {
+        self.fee_pool
+    }
+
+    /// The number of times inflation has run.
+    pub fn inflation_sequence(&self) -> u32 {
+        self.inflation_sequence
+    }
+
+    /// The last id handed out to an offer or other ledger entry.
+    pub fn id_pool(&self) -> u64 {
+        self.id_pool
+    }
+
+    /// The fee, in stroops, for each operation.
+    pub fn base_fee(&self) -> u32 {
+        self.base_fee
+    }
+
+    /// The reserve, in stroops, for an account and each of its subentries.
+    pub fn base_reserve(&self) -> u32 {
+        self.base_reserve
+    }
+
+    /// The most operations a ledger can hold.
+    pub fn max_tx_set_size(&self) -> u32 {
+        self.max_tx_set_size
+    }
+
+    /// The hex encoded hashes of earlier ledgers that core uses to catch
+    /// up quickly.
+    pub fn skip_list(&self) -> &Vec<String> {
+        &self.skip_list
+    }
+
+    /// The ledger's flags, such as those that disable liquidity pools.
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+}
+
+/// The transactions of a ledger, from the `transactions` files of an
+/// archive.
+#[derive(Debug, Clone)]
+pub struct TransactionHistoryEntry {
+    ledger_sequence: u32,
+    xdr: Vec<u8>,
+}
+
+impl TransactionHistoryEntry {
+    /// Decodes the ledger sequence of a `TransactionHistoryEntry`, leaving
+    /// the transactions to be decoded when they are asked for.
+    pub(super) fn from_record(mut record: Vec<u8>) -> Result<TransactionHistoryEntry> {
+        if record.len() < 4 {
+            return Err(XdrError::UnexpectedEnd);
+        }
+        let xdr = record.split_off(4);
+        Ok(TransactionHistoryEntry {
+            ledger_sequence: Reader::new(&record).u32()?,
+            xdr,
+        })
+    }
+
+    /// The sequence number of the ledger.
+    pub fn ledger_sequence(&self) -> u32 {
+        self.ledger_sequence
+    }
+
+    /// Decodes the transactions in the ledger. Ledgers from protocol 20 on
+    /// list them in a generalized transaction set, which is read too.
+    ///
+    /// This fails with `XdrError::Unsupported` if any transaction
+    /// invokes a contract, since the envelope decoder doesn't read soroban
+    /// operations.
+    pub fn transactions(&self) -> Result<Vec<TransactionEnvelope>> {
+        let mut r = Reader::new(&self.xdr);
+        // The legacy set, which later ledgers leave empty.
+        r.take(32)?;
+        let mut transactions = r.vec(TransactionEnvelope::read)?;
+        match r.i32()? {
+            0 => {}
+            1 => generalized_transaction_set(&mut r, &mut transactions)?,
+            other => {
+                return Err(XdrError::UnknownDiscriminant(
+                    "TransactionHistoryEntryExt",
+                    other,
+                ))
+            }
+        }
+        Ok(transactions)
+    }
+
+    /// The hex encoded hash of the ledger's transaction set, to compare
+    /// with the header's `tx_set_hash`.
+    ///
+    /// A generalized set is hashed as a whole. A legacy set is hashed as the
+    /// previous ledger's hash followed by each envelope in the order they
+    /// are stored, so its envelopes are decoded to find where each ends.
+    pub fn tx_set_hash(&self) -> Result<String> {
+        let mut r = Reader::new(&self.xdr);
+        r.take(32)?;
+        let count = r.u32()?;
+        if count > 0 {
+            let mut contents = self.xdr[..32].to_vec();
+            let start = r.position();
+            for _ in 0..count {
+                TransactionEnvelope::read(&mut r)?;
+            }
+            contents.extend_from_slice(r.since(start));
+            return Ok(hex(&sha256(&contents)));
+        }
+        match r.i32()? {
+            1 => Ok(hex(&sha256(&self.xdr[r.position()..]))),
+            _ => Ok(hex(&sha256(&self.xdr[..32]))),
+        }
+    }
+}
+
+fn generalized_transaction_set(
+    r: &mut Reader,
+    transactions: &mut Vec<TransactionEnvelope>,
+) -> Result<()> {
+    match r.i32()? {
+        1 => {}
+        other => {
+            return Err(XdrError::UnknownDiscriminant(
+                "GeneralizedTransactionSet",
+                other,
+            ))
+        }
+    }
+    r.take(32)?;
+    let phases = r.u32()?;
+    for _ in 0..phases {
+        match r.i32()? {
+            // Components, each with the base fee its transactions pay.
+            0 => {
+                let components = r.u32()?;
+                for _ in 0..components {
+                    match r.i32()? {
+                        0 => {
+                            r.optional(|r| r.i64())?;
+                            transactions.extend(r.vec(TransactionEnvelope::read)?);
+                        }
+                        other => {
+                            return Err(XdrError::UnknownDiscriminant("TxSetComponent", other))
+                        }
+                    }
+                }
+            }
+            // Stages of clusters of transactions that can run in parallel.
+            1 => {
+                r.optional(|r| r.i64())?;
+                for stage in r.vec(|r| r.vec(|r| r.vec(TransactionEnvelope::read)))? {
+                    for cluster in stage {
+                        transactions.extend(cluster);
+                    }
+                }
+            }
+            other => return Err(XdrError::UnknownDiscriminant("TransactionPhase", other)),
+        }
+    }
+    Ok(())
+}
+
+/// The results of a ledger's transactions, from the `results` files of an
+/// archive.
+#[derive(Debug, Clone)]
+pub struct TransactionResultEntry {
+    ledger_sequence: u32,
+    results: Vec<TransactionResultPair>,
+    xdr: Vec<u8>,
+}
+
+impl TransactionResultEntry {
+    /// Decodes a `TransactionHistoryResultEntry`.
+    pub(super) fn from_record(record: Vec<u8>) -> Result<TransactionResultEntry> {
+        let r = &mut Reader::new(&record);
+        let ledger_sequence = r.u32()?;
+        let start = r.position();
+        let results = r.vec(|r| {
+            let hash = r.hash()?;
+            let start = r.position();
+            skip_transaction_result(r)?;
+            Ok(TransactionResultPair {
+                hash,
+                result_xdr: base64::encode(r.since(start)),
+            })
+        })?;
+        let xdr = r.since(start).to_vec();
+        r.extension_point()?;
+        Ok(TransactionResultEntry {
+            ledger_sequence,
+            results,
+            xdr,
+        })
+    }
+
+    /// The sequence number of the ledger.
+    pub fn ledger_sequence(&self) -> u32 {
+        self.ledger_sequence
+    }
+
+    /// The result of each transaction, in the order they were applied.
+    pub fn results(&self) -> &Vec<TransactionResultPair> {
+        &self.results
+    }
+
+    /// The hex encoded hash of the results, to compare with the header's
+    /// `tx_set_result_hash`.
+    pub fn tx_set_result_hash(&self) -> String {
+        hex(&sha256(&self.xdr))
+    }
+}
+
+/// The result of one transaction in an archive.
+#[derive(Debug, Clone)]
+pub struct TransactionResultPair {
+    hash: String,
+    result_xdr: String,
+}
+
+impl TransactionResultPair {
+    /// The hex encoded hash of the transaction.
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    /// The base64 encoded `TransactionResult`, as horizon gives in a
+    /// transaction's `result_xdr`.
+    pub fn result_xdr(&self) -> &str {
+        &self.result_xdr
+    }
+
+    /// Why the transaction failed, or `None` if it succeeded.
+    pub fn failure_reasons(&self) -> Result<Option<FailureReasons>> {
+        FailureReasons::from_result_xdr(&self.result_xdr)
+    }
+}
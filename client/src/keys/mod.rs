@@ -0,0 +1,212 @@
+//! Signing keys and somewhere safe to keep them.
+//!
+//! A `Keypair` is an ed25519 key that signs on behalf of a stellar account.
+//! Services that sign transactions look their keys up by account id through
+//! the `KeyStore` trait instead of carrying `S...` seeds around in their
+//! configuration. `FileKeyStore` keeps the seeds encrypted on disk.
+//!
+//! Transactions are signed through the `Signer` trait, which `Keypair`
+//! implements. Keys held by a hardware wallet or a KMS can implement it too.
+//!
+//! This module is only available with the `keys` feature enabled.
+//!
+//! ## Example
+//!
+//! ```
+//! use stellar_client::keys::Keypair;
+//!
+//! let keypair = Keypair::from_secret_seed(
+//!     "SAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6NKI",
+//! ).unwrap();
+//! assert_eq!(
+//!     keypair.account_id(),
+//!     "GAB2CB576PHBBPQ5ODORRZ2LYCMWPZGWGCN2KDK7DXOIMZASKUY3QZ6Q"
+//! );
+//! ```
+use openssl::error::ErrorStack;
+use openssl::pkey::{Id, PKey, Private};
+use openssl::sign;
+use std::error::Error as StdError;
+use std::fmt;
+use strkey;
+
+mod secret;
+mod signer;
+mod store;
+
+pub use self::secret::SecretKey;
+pub use self::signer::{DecoratedSignature, SignatureFuture, Signer};
+pub use self::store::{FileKeyStore, KeyStore, MemoryKeyStore};
+
+/// An ed25519 keypair for signing on behalf of an account.
+///
+/// The `Debug` output only shows the account id, so a keypair can be logged
+/// without giving its seed away. The seed is kept in a `SecretKey`, which
+/// clears it when the keypair is dropped.
+#[derive(Clone)]
+pub struct Keypair {
+    secret: SecretKey,
+    public_key: [u8; 32],
+    account_id: String,
+    pkey: PKey<Private>,
+}
+
+impl Keypair {
+    /// Loads the keypair for an `S...` secret seed.
+    pub fn from_secret_seed(seed: &str) -> Result<Keypair, KeyError> {
+        Keypair::from_secret_key(SecretKey::from_secret_seed(seed)?)
+    }
+
+    /// Generates a new keypair from the operating system's random source.
+    pub fn random() -> Result<Keypair, KeyError> {
+        Keypair::from_secret_key(SecretKey::random()?)
+    }
+
+    /// The keypair for a secret key.
+    pub fn from_secret_key(secret: SecretKey) -> Result<Keypair, KeyError> {
+        let pkey = PKey::private_key_from_raw_bytes(secret.expose_secret(), Id::ED25519)?;
+        let mut public_key = [0; 32];
+        public_key.copy_from_slice(&pkey.raw_public_key()?);
+        Ok(Keypair {
+            secret,
+            public_key,
+            account_id: strkey::encode(strkey::ACCOUNT, &public_key),
+            pkey,
+        })
+    }
+
+    /// The `G...` id of the account the keypair signs for.
+    pub fn account_id(&self) -> &str {
+        &self.account_id
+    }
+
+    /// The raw ed25519 public key.
+    pub fn public_key(&self) -> &[u8; 32] {
+        &self.public_key
+    }
+
+    /// The last four bytes of the public key, which go with its signatures
+    /// to hint at which key made them.
+    pub fn signature_hint(&self) -> [u8; 4] {
+        let mut hint = [0; 4];
+        hint.copy_from_slice(&self.public_key[28..]);
+        hint
+    }
+
+    /// The secret key of the keypair.
+    pub fn secret_key(&self) -> &SecretKey {
+        &self.secret
+    }
+
+    /// Signs `data` with the keypair's private key.
+    pub fn sign(&self, data: &[u8]) -> Result<[u8; 64], KeyError> {
+        let mut signature = [0; 64];
+        sign::Signer::new_without_digest(&self.pkey)?.sign_oneshot(&mut signature, data)?;
+        Ok(signature)
+    }
+}
+
+impl fmt::Debug for Keypair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Keypair")
+            .field("account_id", &self.account_id)
+            .finish()
+    }
+}
+
+/// Errors from loading, using or storing keys.
+#[derive(Debug)]
+pub enum KeyError {
+    /// A secret seed that was not a valid `S...` strkey.
+    InvalidSeed,
+    /// A stored key could not be decrypted, which usually means the password
+    /// was wrong or the file has been tampered with.
+    Decryption,
+    /// openssl failed to carry out a cryptographic operation.
+    Crypto(ErrorStack),
+}
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            KeyError::Crypto(ref inner) => write!(f, "{}", inner),
+            _ => f.write_str(self.description()),
+        }
+    }
+}
+
+impl StdError for KeyError {
+    fn description(&self) -> &str {
+        match *self {
+            KeyError::InvalidSeed => "The secret seed was not a valid strkey",
+            KeyError::Decryption => "The stored key could not be decrypted",
+            KeyError::Crypto(_) => "A cryptographic operation failed",
+        }
+    }
+}
+
+impl From<ErrorStack> for KeyError {
+    fn from(inner: ErrorStack) -> Self {
+        KeyError::Crypto(inner)
+    }
+}
+
+#[cfg(test)]
+mod keypair_tests {
+    use super::*;
+
+    const SEED: &str = "SAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6NKI";
+    const ACCOUNT: &str = "GAB2CB576PHBBPQ5ODORRZ2LYCMWPZGWGCN2KDK7DXOIMZASKUY3QZ6Q";
+
+    #[test]
+    fn it_loads_a_secret_seed() {
+        let keypair = Keypair::from_secret_seed(SEED).unwrap();
+        assert_eq!(keypair.account_id(), ACCOUNT);
+        assert_eq!(keypair.secret_key().expose_secret_seed(), SEED);
+    }
+
+    #[test]
+    fn it_rejects_an_account_id_as_a_seed() {
+        match Keypair::from_secret_seed(ACCOUNT).unwrap_err() {
+            KeyError::InvalidSeed => {}
+            error => panic!("Expected an invalid seed error {:?}", error),
+        }
+    }
+
+    #[test]
+    fn it_signs_data() {
+        let keypair = Keypair::from_secret_seed(SEED).unwrap();
+        let signature: String = keypair
+            .sign(b"hello")
+            .unwrap()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        assert_eq!(
+            signature,
+            "e1a7fca94a835127885b99e2eba733d6ee5bf5dc463ed8385eb6f1dcaa1117c0\
+             f151750a10f46f5b3796a91203578f702c85c67c334b5689a516284d499f710f"
+        );
+    }
+
+    #[test]
+    fn it_generates_random_keypairs() {
+        let first = Keypair::random().unwrap();
+        let second = Keypair::random().unwrap();
+        assert_ne!(first.account_id(), second.account_id());
+        assert_eq!(
+            Keypair::from_secret_seed(&first.secret_key().expose_secret_seed())
+                .unwrap()
+                .account_id(),
+            first.account_id()
+        );
+    }
+
+    #[test]
+    fn it_keeps_the_seed_out_of_debug_output() {
+        let keypair = Keypair::from_secret_seed(SEED).unwrap();
+        let debug = format!("{:?}", keypair);
+        assert!(debug.contains(ACCOUNT));
+        assert!(!debug.contains(SEED));
+    }
+}
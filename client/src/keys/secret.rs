@@ -0,0 +1,118 @@
+use super::KeyError;
+use openssl::rand::rand_bytes;
+use std::fmt;
+use std::ptr;
+use std::sync::atomic::{compiler_fence, Ordering};
+use strkey;
+
+/// The secret half of a keypair, its 32 byte ed25519 seed.
+///
+/// The seed is overwritten with zeros when the key is dropped and is never
+/// printed, not even by `Debug`. Getting at it takes a call to one of the
+/// `expose_secret` methods, which keeps every place that handles the raw
+/// seed easy to find.
+///
+/// ## Example
+///
+/// ```
+/// use stellar_client::keys::SecretKey;
+///
+/// let secret = SecretKey::from_secret_seed(
+///     "SAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6NKI",
+/// ).unwrap();
+/// assert_eq!(format!("{:?}", secret), "SecretKey([REDACTED])");
+/// assert_eq!(secret.expose_secret()[31], 31);
+/// ```
+#[derive(Clone)]
+pub struct SecretKey {
+    seed: [u8; 32],
+}
+
+impl SecretKey {
+    /// Loads the key from an `S...` secret seed.
+    pub fn from_secret_seed(seed: &str) -> Result<SecretKey, KeyError> {
+        let seed = strkey::decode(strkey::SEED, seed).ok_or(KeyError::InvalidSeed)?;
+        Ok(SecretKey { seed })
+    }
+
+    /// Generates a new key from the operating system's random source.
+    pub fn random() -> Result<SecretKey, KeyError> {
+        let mut secret = SecretKey { seed: [0; 32] };
+        rand_bytes(&mut secret.seed)?;
+        Ok(secret)
+    }
+
+    /// Copies a raw seed out of a buffer, which the caller is then expected
+    /// to clear.
+    pub(super) fn from_slice(bytes: &[u8]) -> Option<SecretKey> {
+        if bytes.len() != 32 {
+            return None;
+        }
+        let mut secret = SecretKey { seed: [0; 32] };
+        secret.seed.copy_from_slice(bytes);
+        Some(secret)
+    }
+
+    /// The raw 32 byte seed.
+    pub fn expose_secret(&self) -> &[u8; 32] {
+        &self.seed
+    }
+
+    /// The seed as an `S...` strkey. Unlike the key itself, the returned
+    /// string is not cleared when it is dropped, so don't keep it around.
+    pub fn expose_secret_seed(&self) -> String {
+        strkey::encode(strkey::SEED, &self.seed)
+    }
+}
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        zeroize(&mut self.seed);
+    }
+}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SecretKey([REDACTED])")
+    }
+}
+
+/// Overwrites the bytes with zeros in a way the compiler won't optimize out
+/// for being a write to memory that is about to be freed.
+pub(super) fn zeroize(bytes: &mut [u8]) {
+    for byte in bytes.iter_mut() {
+        unsafe { ptr::write_volatile(byte, 0) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod secret_key_tests {
+    use super::*;
+
+    const SEED: &str = "SAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6NKI";
+
+    #[test]
+    fn it_exposes_the_seed_only_when_asked() {
+        let secret = SecretKey::from_secret_seed(SEED).unwrap();
+        assert_eq!(secret.expose_secret_seed(), SEED);
+        assert_eq!(secret.expose_secret()[..4], [0, 1, 2, 3]);
+        assert!(!format!("{:?}", secret).contains(SEED));
+    }
+
+    #[test]
+    fn it_zeroizes_bytes() {
+        let mut bytes = [7; 32];
+        zeroize(&mut bytes);
+        assert_eq!(bytes, [0; 32]);
+    }
+
+    #[test]
+    fn it_only_copies_whole_seeds() {
+        assert!(SecretKey::from_slice(&[1; 31]).is_none());
+        assert_eq!(
+            SecretKey::from_slice(&[1; 32]).unwrap().expose_secret(),
+            &[1; 32]
+        );
+    }
+}
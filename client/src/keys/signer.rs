@@ -0,0 +1,114 @@
+use super::Keypair;
+use error::Error;
+use futures::{future, Future};
+
+/// A future that resolves to a signature.
+pub type SignatureFuture = Box<dyn Future<Item = DecoratedSignature, Error = Error> + Send>;
+
+/// Something that can sign transactions on behalf of an account.
+///
+/// Signing is asynchronous so that keys which never leave a hardware wallet,
+/// an HSM or a cloud KMS can be plugged in as easily as a `Keypair`. All a
+/// signer is asked to do is sign the 32 byte hash of a transaction; building
+/// the transaction and hashing it for the right network is left to the
+/// caller.
+///
+/// # Examples
+///
+/// ```
+/// extern crate futures;
+/// extern crate stellar_client;
+///
+/// use futures::Future;
+/// use stellar_client::keys::{Keypair, Signer};
+///
+/// # fn main() {
+/// let keypair = Keypair::random().unwrap();
+/// let signature = keypair.sign_hash(&[0; 32]).wait().unwrap();
+/// assert_eq!(signature.hint(), keypair.signature_hint());
+/// # }
+/// ```
+pub trait Signer {
+    /// The `G...` id of the account the signer signs for.
+    fn account_id(&self) -> &str;
+
+    /// Signs a transaction hash.
+    fn sign_hash(&self, hash: &[u8; 32]) -> SignatureFuture;
+}
+
+impl<'a, S> Signer for &'a S
+where
+    S: Signer + ?Sized,
+{
+    fn account_id(&self) -> &str {
+        (**self).account_id()
+    }
+
+    fn sign_hash(&self, hash: &[u8; 32]) -> SignatureFuture {
+        (**self).sign_hash(hash)
+    }
+}
+
+impl Signer for Keypair {
+    fn account_id(&self) -> &str {
+        Keypair::account_id(self)
+    }
+
+    fn sign_hash(&self, hash: &[u8; 32]) -> SignatureFuture {
+        let signature = self
+            .sign(hash)
+            .map(|signature| DecoratedSignature::new(self.signature_hint(), signature.to_vec()))
+            .map_err(Error::from);
+        Box::new(future::result(signature))
+    }
+}
+
+/// A signature together with a hint of which key made it, the way they are
+/// attached to a transaction envelope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecoratedSignature {
+    hint: [u8; 4],
+    signature: Vec<u8>,
+}
+
+impl DecoratedSignature {
+    /// A signature with the hint of the key that made it. The hint is the
+    /// last four bytes of the signer's public key.
+    pub fn new(hint: [u8; 4], signature: Vec<u8>) -> DecoratedSignature {
+        DecoratedSignature { hint, signature }
+    }
+
+    /// The last four bytes of the signer's public key.
+    pub fn hint(&self) -> [u8; 4] {
+        self.hint
+    }
+
+    /// The signature itself.
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+}
+
+#[cfg(test)]
+mod signer_tests {
+    use super::*;
+
+    const SEED: &str = "SAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6NKI";
+
+    #[test]
+    fn it_signs_a_hash_with_a_keypair() {
+        let keypair = Keypair::from_secret_seed(SEED).unwrap();
+        let hash = [7; 32];
+        let signature = keypair.sign_hash(&hash).wait().unwrap();
+        assert_eq!(signature.hint(), [0x12, 0x55, 0x31, 0xb8]);
+        assert_eq!(signature.signature(), &keypair.sign(&hash).unwrap()[..]);
+    }
+
+    #[test]
+    fn it_signs_through_a_trait_object() {
+        let keypair = Keypair::from_secret_seed(SEED).unwrap();
+        let signer: &dyn Signer = &keypair;
+        assert_eq!(signer.account_id(), keypair.account_id());
+        assert!(signer.sign_hash(&[0; 32]).wait().is_ok());
+    }
+}
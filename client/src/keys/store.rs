@@ -0,0 +1,327 @@
+use super::secret::{zeroize, SecretKey};
+use super::{KeyError, Keypair};
+use base64;
+use error::Result;
+use openssl::pkcs5::scrypt;
+use openssl::rand::rand_bytes;
+use openssl::symm::{decrypt_aead, encrypt_aead, Cipher};
+use serde_json;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// scrypt cost parameters, as recommended for interactive logins. Deriving a
+// key takes in the order of 100ms and 32MiB of memory.
+const SCRYPT_N: u64 = 1 << 15;
+const SCRYPT_R: u64 = 8;
+const SCRYPT_P: u64 = 1;
+const SCRYPT_MAX_MEM: u64 = 64 * 1024 * 1024;
+
+/// Somewhere to find the signing keys for accounts.
+///
+/// # Examples
+///
+/// ```
+/// use stellar_client::keys::{KeyStore, Keypair, MemoryKeyStore};
+///
+/// let store = MemoryKeyStore::new();
+/// let keypair = Keypair::random().unwrap();
+/// store.save(&keypair).unwrap();
+///
+/// let found = store.load(keypair.account_id()).unwrap().unwrap();
+/// assert_eq!(found.account_id(), keypair.account_id());
+/// ```
+pub trait KeyStore {
+    /// Returns the keypair that signs for `account_id`, if the store has one.
+    fn load(&self, account_id: &str) -> Result<Option<Keypair>>;
+
+    /// Adds the keypair to the store under its account id, replacing any
+    /// keypair already there.
+    fn save(&self, keypair: &Keypair) -> Result<()>;
+}
+
+impl<'a, S> KeyStore for &'a S
+where
+    S: KeyStore + ?Sized,
+{
+    fn load(&self, account_id: &str) -> Result<Option<Keypair>> {
+        (**self).load(account_id)
+    }
+
+    fn save(&self, keypair: &Keypair) -> Result<()> {
+        (**self).save(keypair)
+    }
+}
+
+/// Keeps keypairs in memory. Useful in tests and for keys generated on the
+/// fly.
+#[derive(Debug, Default)]
+pub struct MemoryKeyStore {
+    keypairs: Mutex<HashMap<String, Keypair>>,
+}
+
+impl MemoryKeyStore {
+    /// Creates an empty store.
+    pub fn new() -> MemoryKeyStore {
+        MemoryKeyStore::default()
+    }
+}
+
+impl KeyStore for MemoryKeyStore {
+    fn load(&self, account_id: &str) -> Result<Option<Keypair>> {
+        let keypairs = self.keypairs.lock().expect("Key store lock was poisoned");
+        Ok(keypairs.get(account_id).cloned())
+    }
+
+    fn save(&self, keypair: &Keypair) -> Result<()> {
+        let mut keypairs = self.keypairs.lock().expect("Key store lock was poisoned");
+        keypairs.insert(keypair.account_id().to_string(), keypair.clone());
+        Ok(())
+    }
+}
+
+/// Keeps keypairs in a json file on disk, with every seed encrypted under a
+/// password.
+///
+/// Each seed is encrypted with AES-256-GCM using a key derived from the
+/// password and a random salt with scrypt. The account id is authenticated
+/// along with the seed, so a seed can't be moved to another account's entry
+/// without failing to decrypt. Seeds are only decrypted when they're loaded.
+///
+/// Saving rewrites the whole file through a temporary file next to it, the
+/// same way `FileCursorStore` does. On unix the file can only be read and
+/// written by its owner.
+///
+/// The password is cleared from memory when the store is dropped, as are the
+/// keys derived from it once they've been used.
+pub struct FileKeyStore {
+    path: PathBuf,
+    password: Vec<u8>,
+    keys: Mutex<HashMap<String, EncryptedKey>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct EncryptedKey {
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+    tag: String,
+}
+
+impl FileKeyStore {
+    /// Opens the store at `path`, which is created on the first save if it
+    /// doesn't exist. A wrong password is only noticed once a key is loaded.
+    pub fn open<P: AsRef<Path>>(path: P, password: &str) -> Result<FileKeyStore> {
+        let path = path.as_ref().to_path_buf();
+        let keys = if path.exists() {
+            serde_json::from_reader(File::open(&path)?)?
+        } else {
+            HashMap::new()
+        };
+        Ok(FileKeyStore {
+            path,
+            password: password.as_bytes().to_vec(),
+            keys: Mutex::new(keys),
+        })
+    }
+
+    /// The file the keys are saved to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The account ids the store holds keys for.
+    pub fn account_ids(&self) -> Vec<String> {
+        let keys = self.keys.lock().expect("Key store lock was poisoned");
+        keys.keys().cloned().collect()
+    }
+
+    fn derive_key(&self, salt: &[u8]) -> Result<[u8; 32]> {
+        let mut key = [0; 32];
+        scrypt(
+            &self.password,
+            salt,
+            SCRYPT_N,
+            SCRYPT_R,
+            SCRYPT_P,
+            SCRYPT_MAX_MEM,
+            &mut key,
+        )
+        .map_err(KeyError::from)?;
+        Ok(key)
+    }
+
+    fn encrypt(&self, keypair: &Keypair) -> Result<EncryptedKey> {
+        let mut salt = [0; 16];
+        let mut nonce = [0; 12];
+        rand_bytes(&mut salt).map_err(KeyError::from)?;
+        rand_bytes(&mut nonce).map_err(KeyError::from)?;
+        let mut key = self.derive_key(&salt)?;
+        let mut tag = [0; 16];
+        let ciphertext = encrypt_aead(
+            Cipher::aes_256_gcm(),
+            &key,
+            Some(&nonce),
+            keypair.account_id().as_bytes(),
+            keypair.secret_key().expose_secret(),
+            &mut tag,
+        );
+        zeroize(&mut key);
+        let ciphertext = ciphertext.map_err(KeyError::from)?;
+        Ok(EncryptedKey {
+            salt: base64::encode(&salt),
+            nonce: base64::encode(&nonce),
+            ciphertext: base64::encode(&ciphertext),
+            tag: base64::encode(&tag),
+        })
+    }
+
+    fn decrypt(&self, account_id: &str, encrypted: &EncryptedKey) -> Result<Keypair> {
+        let decode = |field: &str| base64::decode(field).map_err(|_| KeyError::Decryption);
+        let (nonce, ciphertext, tag) = (
+            decode(&encrypted.nonce)?,
+            decode(&encrypted.ciphertext)?,
+            decode(&encrypted.tag)?,
+        );
+        let mut key = self.derive_key(&decode(&encrypted.salt)?)?;
+        let seed = decrypt_aead(
+            Cipher::aes_256_gcm(),
+            &key,
+            Some(&nonce),
+            account_id.as_bytes(),
+            &ciphertext,
+            &tag,
+        );
+        zeroize(&mut key);
+        let mut seed = seed.map_err(|_| KeyError::Decryption)?;
+        let secret = SecretKey::from_slice(&seed);
+        zeroize(&mut seed);
+        Ok(Keypair::from_secret_key(
+            secret.ok_or(KeyError::Decryption)?,
+        )?)
+    }
+}
+
+impl Drop for FileKeyStore {
+    fn drop(&mut self) {
+        zeroize(&mut self.password);
+    }
+}
+
+impl fmt::Debug for FileKeyStore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FileKeyStore")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl KeyStore for FileKeyStore {
+    fn load(&self, account_id: &str) -> Result<Option<Keypair>> {
+        let encrypted = {
+            let keys = self.keys.lock().expect("Key store lock was poisoned");
+            keys.get(account_id).cloned()
+        };
+        match encrypted {
+            Some(encrypted) => self.decrypt(account_id, &encrypted).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn save(&self, keypair: &Keypair) -> Result<()> {
+        let encrypted = self.encrypt(keypair)?;
+        let mut keys = self.keys.lock().expect("Key store lock was poisoned");
+        keys.insert(keypair.account_id().to_string(), encrypted);
+
+        let temp = self.path.with_extension("tmp");
+        serde_json::to_writer(create_private(&temp)?, &*keys)?;
+        fs::rename(&temp, &self.path)?;
+        Ok(())
+    }
+}
+
+// Creates the file at `path` so that only its owner can read it, replacing
+// any file already there. The permissions only apply to a new file, so an
+// old one is removed first.
+fn create_private(path: &Path) -> io::Result<File> {
+    if let Err(err) = fs::remove_file(path) {
+        if err.kind() != io::ErrorKind::NotFound {
+            return Err(err);
+        }
+    }
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+#[cfg(test)]
+mod key_store_tests {
+    use super::*;
+    use error::Error;
+    use std::env;
+
+    const SEED: &str = "SAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6NKI";
+
+    #[test]
+    fn it_keeps_keypairs_in_memory() {
+        let store = MemoryKeyStore::new();
+        let keypair = Keypair::from_secret_seed(SEED).unwrap();
+        assert!(store.load(keypair.account_id()).unwrap().is_none());
+        store.save(&keypair).unwrap();
+        let loaded = store.load(keypair.account_id()).unwrap().unwrap();
+        assert_eq!(loaded.secret_key().expose_secret_seed(), SEED);
+    }
+
+    #[test]
+    fn it_keeps_encrypted_keys_across_reopening_the_file() {
+        let path = env::temp_dir().join("stellar_client_key_store.json");
+        let _ = fs::remove_file(&path);
+        let keypair = Keypair::from_secret_seed(SEED).unwrap();
+
+        let store = FileKeyStore::open(&path, "correct horse").unwrap();
+        store.save(&keypair).unwrap();
+        assert_eq!(store.path(), path.as_path());
+        assert_eq!(store.account_ids(), vec![keypair.account_id().to_string()]);
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.contains(keypair.account_id()));
+        assert!(!contents.contains(SEED));
+
+        let reopened = FileKeyStore::open(&path, "correct horse").unwrap();
+        let loaded = reopened.load(keypair.account_id()).unwrap().unwrap();
+        assert_eq!(loaded.secret_key().expose_secret_seed(), SEED);
+        assert!(reopened.load("GABC").unwrap().is_none());
+
+        let wrong = FileKeyStore::open(&path, "battery staple").unwrap();
+        assert!(!format!("{:?}", wrong).contains("battery"));
+        match wrong.load(keypair.account_id()).unwrap_err() {
+            Error::Key(KeyError::Decryption) => {}
+            error => panic!("Expected a decryption error {:?}", error),
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_keeps_the_file_private_to_its_owner() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = env::temp_dir().join("stellar_client_private_key_store.json");
+        let _ = fs::remove_file(&path);
+        fs::write(path.with_extension("tmp"), "").unwrap();
+        let keypair = Keypair::from_secret_seed(SEED).unwrap();
+        let store = FileKeyStore::open(&path, "correct horse").unwrap();
+        store.save(&keypair).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -38,23 +38,52 @@
 
 extern crate base64;
 extern crate chrono;
+#[cfg(feature = "history")]
+extern crate flate2;
 extern crate futures;
 extern crate http;
 extern crate hyper;
 extern crate hyper_tls;
+#[cfg(any(feature = "soroban", feature = "keys", feature = "history"))]
+extern crate openssl;
 extern crate reqwest;
+#[cfg(test)]
+extern crate rmp_serde;
 extern crate serde;
+#[cfg(test)]
+extern crate serde_cbor;
 #[macro_use]
 extern crate serde_derive;
+#[cfg(feature = "validate")]
+extern crate serde_ignored;
 extern crate serde_json;
+extern crate serde_path_to_error;
 extern crate tokio_core;
+#[cfg(feature = "tracing")]
+#[macro_use]
+extern crate tracing;
 
+pub mod anchor;
 pub mod client;
 pub mod endpoint;
 pub mod error;
+pub mod helpers;
+#[cfg(feature = "history")]
+pub mod history;
+#[cfg(feature = "keys")]
+pub mod keys;
+mod network;
 pub mod resources;
+pub mod sep10;
+pub mod sep8;
+#[cfg(feature = "soroban")]
+pub mod soroban;
 mod stellar_error;
+mod stellar_toml;
+mod strkey;
+pub mod test;
 mod uri;
+mod xdr;
 
 /// The stellar client is a data structure that wraps the logic and state of the
 /// stellar horizon api. Interaction generally relies on building resources from
@@ -67,4 +96,5 @@ mod uri;
 /// itself.
 pub use client::{async, sync};
 pub use error::{Error, Result};
+pub use network::Network;
 pub use stellar_error::StellarError;
@@ -0,0 +1,123 @@
+//! The stellar networks that transactions and contracts belong to.
+#[cfg(any(feature = "soroban", feature = "keys"))]
+use openssl::sha::sha256;
+
+const PUBLIC_PASSPHRASE: &str = "Public Global Stellar Network ; September 2015";
+const TEST_PASSPHRASE: &str = "Test SDF Network ; September 2015";
+const TEST_FRIENDBOT: &str = "https://friendbot.stellar.org";
+const STANDALONE_FRIENDBOT: &str = "http://localhost:8000/friendbot";
+
+/// A stellar network, identified by its passphrase. Transaction signatures
+/// and contract ids are tied to the network they were made for, so the same
+/// transaction is not valid on both the public and the test network.
+///
+/// Test networks also have a friendbot that hands out lumens to new
+/// accounts.
+///
+/// ## Example
+///
+/// ```
+/// use stellar_client::Network;
+///
+/// let network = Network::test();
+/// assert_eq!(network.passphrase(), "Test SDF Network ; September 2015");
+/// assert_eq!(network.friendbot_uri(), Some("https://friendbot.stellar.org"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Network {
+    passphrase: String,
+    friendbot_uri: Option<String>,
+}
+
+impl Network {
+    /// A network with the given passphrase.
+    pub fn new(passphrase: &str) -> Network {
+        Network {
+            passphrase: passphrase.to_string(),
+            friendbot_uri: None,
+        }
+    }
+
+    /// The public network, which horizon prod serves.
+    pub fn public() -> Network {
+        Network::new(PUBLIC_PASSPHRASE)
+    }
+
+    /// The test network, which horizon test serves.
+    pub fn test() -> Network {
+        Network::new(TEST_PASSPHRASE).with_friendbot(TEST_FRIENDBOT)
+    }
+
+    /// A private network run locally, such as the quickstart docker image
+    /// started with `--standalone`, whose passphrase is
+    /// `Standalone Network ; February 2017` unless it has been changed.
+    /// Friendbot is expected at `http://localhost:8000/friendbot`, where
+    /// quickstart serves it.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use stellar_client::Network;
+    ///
+    /// let network = Network::standalone("Standalone Network ; February 2017")
+    ///     .with_friendbot("http://quickstart:8000/friendbot");
+    /// assert_eq!(network.friendbot_uri(), Some("http://quickstart:8000/friendbot"));
+    /// ```
+    pub fn standalone(passphrase: &str) -> Network {
+        Network::new(passphrase).with_friendbot(STANDALONE_FRIENDBOT)
+    }
+
+    /// Sets where the network's friendbot can be reached.
+    pub fn with_friendbot(mut self, uri: &str) -> Network {
+        self.friendbot_uri = Some(uri.to_string());
+        self
+    }
+
+    /// The passphrase of the network.
+    pub fn passphrase(&self) -> &str {
+        &self.passphrase
+    }
+
+    /// The uri of the network's friendbot, if it has one.
+    pub fn friendbot_uri(&self) -> Option<&str> {
+        self.friendbot_uri.as_ref().map(|s| &**s)
+    }
+
+    /// The network id, the sha-256 hash of the passphrase.
+    #[cfg(any(feature = "soroban", feature = "keys"))]
+    pub fn id(&self) -> [u8; 32] {
+        sha256(self.passphrase.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod network_tests {
+    use super::*;
+
+    #[test]
+    fn it_knows_the_well_known_networks() {
+        assert_eq!(
+            Network::public().passphrase(),
+            "Public Global Stellar Network ; September 2015"
+        );
+        assert_eq!(Network::test().passphrase(), TEST_PASSPHRASE);
+        assert_eq!(Network::public().friendbot_uri(), None);
+    }
+
+    #[test]
+    fn it_points_a_standalone_network_at_a_local_friendbot() {
+        let network = Network::standalone("Standalone Network ; February 2017");
+        assert_eq!(network.passphrase(), "Standalone Network ; February 2017");
+        assert_eq!(
+            network.friendbot_uri(),
+            Some("http://localhost:8000/friendbot")
+        );
+    }
+
+    #[cfg(any(feature = "soroban", feature = "keys"))]
+    #[test]
+    fn it_hashes_the_passphrase_into_an_id() {
+        let id = Network::test().id();
+        assert_eq!(&id[..4], &[0xce, 0xe0, 0x30, 0x2d]);
+    }
+}
@@ -1,18 +1,35 @@
-use super::deserialize;
+use super::{deserialize, serialize};
 use resources::base64string::Base64String;
+use resources::{AccountId, Amount, AssetIdentifier, Links};
+use serde::ser::SerializeSeq;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
 
 /// In the Stellar network, users interact using accounts which can be controlled by a
 /// corresponding keypair that can authorize transactions.
 ///
 /// <https://www.stellar.org/developers/horizon/reference/resources/account.html>
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Account {
-    id: String,
-    account_id: String,
-    #[serde(deserialize_with = "deserialize::from_str")]
+    #[serde(rename = "_links", default)]
+    links: Links,
+    id: AccountId,
+    account_id: AccountId,
+    #[serde(
+        deserialize_with = "deserialize::from_str",
+        serialize_with = "serialize::to_string"
+    )]
     sequence: u64,
     subentry_count: u64,
+    #[serde(default)]
+    num_sponsoring: u32,
+    #[serde(default)]
+    num_sponsored: u32,
+    #[serde(default)]
+    thresholds: Thresholds,
+    #[serde(default)]
+    signers: Vec<Signer>,
+    balances: Balances,
     data: HashMap<String, Base64String>,
 }
 
@@ -21,23 +38,28 @@ impl Account {
     /// for url templates that require an account’s ID. Returns a slice that lives
     /// as long as the account does.
     pub fn id_ref(&self) -> &str {
-        &self.id
+        self.id.as_str()
+    }
+
+    /// The links to the resources related to this one.
+    pub fn links(&self) -> &Links {
+        &self.links
     }
 
     /// The account’s public key encoded into a base32 string representation.
     /// Returns a slice that lives as long as the account does.
     pub fn account_id_ref(&self) -> &str {
-        &self.account_id
+        self.account_id.as_str()
     }
 
     /// The canonical id of this account, suitable for use as the :id parameter
     /// for url templates that require an account’s ID.
-    pub fn id(&self) -> &String {
+    pub fn id(&self) -> &AccountId {
         &self.id
     }
 
     /// The account’s public key encoded into a base32 string representation.
-    pub fn account_id(&self) -> &String {
+    pub fn account_id(&self) -> &AccountId {
         &self.account_id
     }
 
@@ -53,8 +75,522 @@ impl Account {
         self.subentry_count
     }
 
+    /// The number of reserves this account pays for on behalf of other
+    /// accounts. Horizon servers older than protocol 15 report zero.
+    pub fn num_sponsoring(&self) -> u32 {
+        self.num_sponsoring
+    }
+
+    /// The number of this account's reserves that another account pays for.
+    /// Horizon servers older than protocol 15 report zero.
+    pub fn num_sponsored(&self) -> u32 {
+        self.num_sponsored
+    }
+
+    /// The weights of signatures needed to authorize the operations this
+    /// account is the source of.
+    pub fn thresholds(&self) -> Thresholds {
+        self.thresholds
+    }
+
+    /// The keys that can sign for this account, including the master key
+    /// unless its weight has been set to zero.
+    pub fn signers(&self) -> &Vec<Signer> {
+        &self.signers
+    }
+
+    /// Returns the signer for a particular key, if it can sign for the account.
+    pub fn signer(&self, key: &str) -> Option<&Signer> {
+        self.signers.iter().find(|signer| signer.key() == key)
+    }
+
+    /// The assets this account holds along with the amount of each.
+    pub fn balances(&self) -> &Vec<Balance> {
+        &self.balances.assets
+    }
+
+    /// Returns the balance held for a particular asset, if the account holds it.
+    pub fn balance(&self, asset: &AssetIdentifier) -> Option<&Balance> {
+        self.balances
+            .assets
+            .iter()
+            .find(|balance| balance.asset_identifier() == asset)
+    }
+
+    /// The shares this account holds in liquidity pools. Horizon lists them
+    /// among the balances, but they aren't an asset that can be sent.
+    pub fn pool_share_balances(&self) -> &[PoolShareBalance] {
+        &self.balances.pool_shares
+    }
+
     /// A key/value store of data attached to this account.
     pub fn data(&self) -> &HashMap<String, Base64String> {
         &self.data
     }
 }
+
+#[cfg(test)]
+mod account_tests {
+    use super::*;
+    use serde_json;
+
+    fn account_json() -> &'static str {
+        include_str!("../../fixtures/account.json")
+    }
+
+    #[test]
+    fn it_parses_an_account_from_json() {
+        let account: Account = serde_json::from_str(account_json()).unwrap();
+        assert_eq!(
+            account.account_id(),
+            "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG"
+        );
+        assert_eq!(account.sequence(), 30232549674450945);
+        assert_eq!(account.subentry_count(), 2);
+        assert_eq!(account.num_sponsoring(), 1);
+        assert_eq!(account.num_sponsored(), 0);
+        assert_eq!(account.balances().len(), 2);
+    }
+
+    #[test]
+    fn it_parses_signers_from_json() {
+        let account: Account = serde_json::from_str(account_json()).unwrap();
+        assert_eq!(account.thresholds(), Thresholds::new(0, 0, 0));
+        assert_eq!(account.signers().len(), 1);
+        let master = account.signer(account.account_id_ref()).unwrap();
+        assert_eq!(master.weight(), 1);
+        assert_eq!(master.signer_type(), "ed25519_public_key");
+    }
+
+    #[test]
+    fn it_parses_balances_from_json() {
+        let account: Account = serde_json::from_str(account_json()).unwrap();
+        let native = account.balance(&AssetIdentifier::native()).unwrap();
+        assert_eq!(native.balance(), Amount::new(98_999_999_800));
+        assert_eq!(native.limit(), None);
+
+        let foo = AssetIdentifier::alphanum4(
+            "FOO",
+            "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4",
+        )
+        .unwrap();
+        let credit = account.balance(&foo).unwrap();
+        assert_eq!(credit.balance(), Amount::new(10_000_000_000));
+        assert_eq!(credit.limit(), Some(Amount::new(9_223_372_036_854_775_807)));
+    }
+
+    #[test]
+    fn it_parses_pool_share_balances_apart_from_assets() {
+        let account: Account =
+            serde_json::from_str(include_str!("../../fixtures/account_with_pool_shares.json"))
+                .unwrap();
+        assert_eq!(account.balances().len(), 2);
+        assert!(account.balance(&AssetIdentifier::native()).is_some());
+
+        let shares = account.pool_share_balances();
+        assert_eq!(shares.len(), 1);
+        assert_eq!(
+            shares[0].liquidity_pool_id(),
+            "dd7b1ab831c273310ddbec6f97870aa83c2fbd78ce22aded37ecbf4f3380fac7"
+        );
+        assert_eq!(shares[0].balance(), Amount::new(5_000_000));
+        assert_eq!(shares[0].limit(), Amount::new(9_223_372_036_854_775_807));
+    }
+
+    #[test]
+    fn it_skips_balances_of_unknown_kinds() {
+        let json = account_json().replace(
+            r#""asset_type": "native""#,
+            r#""asset_type": "something_new""#,
+        );
+        let account: Account = serde_json::from_str(&json).unwrap();
+        assert_eq!(account.balances().len(), 1);
+        assert!(account.pool_share_balances().is_empty());
+    }
+}
+
+/// Builds an `Account` from its id. Like a newly created account, it starts
+/// with its master key as the only signer, at a weight of one, and with all
+/// thresholds at zero.
+///
+/// ```
+/// use stellar_client::resources::{AccountBuilder, Amount, AssetIdentifier, Balance};
+///
+/// let account = AccountBuilder::new("GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into())
+///     .with_sequence(12)
+///     .with_balance(Balance::new(Amount::new(100), None, AssetIdentifier::native()))
+///     .build();
+/// assert_eq!(account.sequence(), 12);
+/// assert_eq!(account.balances().len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AccountBuilder {
+    account_id: AccountId,
+    sequence: u64,
+    subentry_count: u64,
+    num_sponsoring: u32,
+    num_sponsored: u32,
+    thresholds: Thresholds,
+    signers: Vec<Signer>,
+    balances: Balances,
+    data: HashMap<String, Base64String>,
+}
+
+impl AccountBuilder {
+    /// Starts building an account with the given id. The account starts with a
+    /// sequence of zero and no balances, subentries or data.
+    pub fn new(account_id: AccountId) -> AccountBuilder {
+        AccountBuilder {
+            signers: vec![Signer::new(account_id.to_string(), 1)],
+            account_id,
+            sequence: 0,
+            subentry_count: 0,
+            num_sponsoring: 0,
+            num_sponsored: 0,
+            thresholds: Thresholds::default(),
+            balances: Balances::default(),
+            data: HashMap::new(),
+        }
+    }
+
+    /// Sets the sequence number of the account.
+    pub fn with_sequence(mut self, sequence: u64) -> AccountBuilder {
+        self.sequence = sequence;
+        self
+    }
+
+    /// Sets the number of subentries the account has.
+    pub fn with_subentry_count(mut self, subentry_count: u64) -> AccountBuilder {
+        self.subentry_count = subentry_count;
+        self
+    }
+
+    /// Sets the number of reserves the account pays for on behalf of others.
+    pub fn with_num_sponsoring(mut self, num_sponsoring: u32) -> AccountBuilder {
+        self.num_sponsoring = num_sponsoring;
+        self
+    }
+
+    /// Sets the number of the account's reserves that others pay for.
+    pub fn with_num_sponsored(mut self, num_sponsored: u32) -> AccountBuilder {
+        self.num_sponsored = num_sponsored;
+        self
+    }
+
+    /// Sets the thresholds of the account.
+    pub fn with_thresholds(mut self, thresholds: Thresholds) -> AccountBuilder {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Adds a signer to the account, or changes the weight of the signer
+    /// with the same key, such as the master key.
+    pub fn with_signer(mut self, signer: Signer) -> AccountBuilder {
+        self.signers
+            .retain(|existing| existing.key() != signer.key());
+        self.signers.push(signer);
+        self
+    }
+
+    /// Adds a balance to the account.
+    pub fn with_balance(mut self, balance: Balance) -> AccountBuilder {
+        self.balances.assets.push(balance);
+        self
+    }
+
+    /// Adds shares in a liquidity pool to the account.
+    pub fn with_pool_share_balance(mut self, balance: PoolShareBalance) -> AccountBuilder {
+        self.balances.pool_shares.push(balance);
+        self
+    }
+
+    /// Adds a data entry to the account.
+    pub fn with_data(mut self, key: &str, value: Base64String) -> AccountBuilder {
+        self.data.insert(key.to_string(), value);
+        self
+    }
+
+    /// Creates the account.
+    pub fn build(self) -> Account {
+        Account {
+            id: self.account_id.clone(),
+            account_id: self.account_id,
+            sequence: self.sequence,
+            subentry_count: self.subentry_count,
+            num_sponsoring: self.num_sponsoring,
+            num_sponsored: self.num_sponsored,
+            thresholds: self.thresholds,
+            signers: self.signers,
+            balances: self.balances,
+            data: self.data,
+            links: Links::default(),
+        }
+    }
+}
+
+/// The sums of signature weights an account needs to authorize operations
+/// of low, medium and high security.
+///
+/// <https://developers.stellar.org/docs/encyclopedia/signatures-multisig#thresholds>
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Thresholds {
+    low_threshold: u8,
+    med_threshold: u8,
+    high_threshold: u8,
+}
+
+impl Thresholds {
+    /// Creates new thresholds, from low to high.
+    pub fn new(low: u8, med: u8, high: u8) -> Thresholds {
+        Thresholds {
+            low_threshold: low,
+            med_threshold: med,
+            high_threshold: high,
+        }
+    }
+
+    /// The weight needed for low security operations such as allowing trust.
+    pub fn low(&self) -> u8 {
+        self.low_threshold
+    }
+
+    /// The weight needed for medium security operations such as payments.
+    pub fn med(&self) -> u8 {
+        self.med_threshold
+    }
+
+    /// The weight needed for high security operations such as changing the
+    /// signers or thresholds and merging the account.
+    pub fn high(&self) -> u8 {
+        self.high_threshold
+    }
+}
+
+/// A key that can sign for an account, along with the weight its signature
+/// carries.
+///
+/// <https://www.stellar.org/developers/horizon/reference/resources/account.html#signer-object>
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Signer {
+    key: String,
+    weight: u8,
+    #[serde(rename = "type")]
+    signer_type: String,
+}
+
+impl Signer {
+    /// Creates an ed25519 public key signer.
+    pub fn new(key: String, weight: u8) -> Signer {
+        Signer {
+            key,
+            weight,
+            signer_type: "ed25519_public_key".to_string(),
+        }
+    }
+
+    /// The key, such as an account id for an ed25519 public key.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The weight of signatures from the key. A weight of zero means the
+    /// key can't sign.
+    pub fn weight(&self) -> u8 {
+        self.weight
+    }
+
+    /// The kind of key, such as `ed25519_public_key`, `sha256_hash` or
+    /// `preauth_tx`.
+    pub fn signer_type(&self) -> &str {
+        &self.signer_type
+    }
+}
+
+/// The amount of a single asset held by an account. Non-native balances are backed
+/// by a trustline and carry the limit of that trustline.
+///
+/// <https://www.stellar.org/developers/horizon/reference/resources/account.html#balances>
+#[derive(Debug, Clone)]
+pub struct Balance {
+    balance: Amount,
+    limit: Option<Amount>,
+    asset_identifier: AssetIdentifier,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+struct IntermediateBalance {
+    balance: Amount,
+    limit: Option<Amount>,
+    asset_type: String,
+    asset_code: Option<String>,
+    asset_issuer: Option<String>,
+    #[serde(default)]
+    liquidity_pool_id: Option<String>,
+}
+
+const POOL_SHARES: &str = "liquidity_pool_shares";
+
+impl<'de> Deserialize<'de> for Balance {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let rep: IntermediateBalance = IntermediateBalance::deserialize(d)?;
+        let asset_identifier =
+            AssetIdentifier::new(&rep.asset_type, rep.asset_code, rep.asset_issuer)
+                .map_err(de::Error::custom)?;
+        Ok(Balance {
+            balance: rep.balance,
+            limit: rep.limit,
+            asset_identifier,
+        })
+    }
+}
+
+impl Serialize for Balance {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let rep = IntermediateBalance {
+            balance: self.balance,
+            limit: self.limit,
+            asset_type: self.asset_identifier.asset_type().to_string(),
+            asset_code: self.asset_identifier.asset_code(),
+            asset_issuer: self.asset_identifier.asset_issuer(),
+            liquidity_pool_id: None,
+        };
+        rep.serialize(s)
+    }
+}
+
+impl Balance {
+    /// Creates a new balance. Lumen balances have no limit.
+    pub fn new(
+        balance: Amount,
+        limit: Option<Amount>,
+        asset_identifier: AssetIdentifier,
+    ) -> Balance {
+        Balance {
+            balance,
+            limit,
+            asset_identifier,
+        }
+    }
+
+    /// The amount of the asset held by the account.
+    pub fn balance(&self) -> Amount {
+        self.balance
+    }
+
+    /// The trustline limit for this asset. Lumens are not held through a
+    /// trustline and so have no limit.
+    pub fn limit(&self) -> Option<Amount> {
+        self.limit
+    }
+
+    /// The identifier of the asset this balance is for.
+    pub fn asset_identifier(&self) -> &AssetIdentifier {
+        &self.asset_identifier
+    }
+}
+
+/// An account's shares in a liquidity pool, which are held through a trust
+/// line to the pool like an issued asset is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolShareBalance {
+    balance: Amount,
+    limit: Amount,
+    liquidity_pool_id: String,
+}
+
+impl PoolShareBalance {
+    /// Creates a new pool share balance.
+    pub fn new(balance: Amount, limit: Amount, liquidity_pool_id: String) -> PoolShareBalance {
+        PoolShareBalance {
+            balance,
+            limit,
+            liquidity_pool_id,
+        }
+    }
+
+    /// The number of shares held.
+    pub fn balance(&self) -> Amount {
+        self.balance
+    }
+
+    /// The most shares the trust line to the pool allows.
+    pub fn limit(&self) -> Amount {
+        self.limit
+    }
+
+    /// The hex encoded id of the pool.
+    pub fn liquidity_pool_id(&self) -> &str {
+        &self.liquidity_pool_id
+    }
+}
+
+/// The balances horizon lists for an account, split into the assets it holds
+/// and its shares in liquidity pools. Balances of a kind this library doesn't
+/// know are left out, so that horizon adding one doesn't stop accounts from
+/// being read.
+#[derive(Debug, Clone, Default)]
+struct Balances {
+    assets: Vec<Balance>,
+    pool_shares: Vec<PoolShareBalance>,
+}
+
+impl<'de> Deserialize<'de> for Balances {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut balances = Balances::default();
+        for rep in Vec::<IntermediateBalance>::deserialize(d)? {
+            match rep.asset_type.as_str() {
+                "native" | "credit_alphanum4" | "credit_alphanum12" => {
+                    let asset_identifier =
+                        AssetIdentifier::new(&rep.asset_type, rep.asset_code, rep.asset_issuer)
+                            .map_err(de::Error::custom)?;
+                    balances.assets.push(Balance {
+                        balance: rep.balance,
+                        limit: rep.limit,
+                        asset_identifier,
+                    });
+                }
+                POOL_SHARES => balances.pool_shares.push(PoolShareBalance {
+                    balance: rep.balance,
+                    limit: rep.limit.ok_or_else(|| de::Error::missing_field("limit"))?,
+                    liquidity_pool_id: rep
+                        .liquidity_pool_id
+                        .ok_or_else(|| de::Error::missing_field("liquidity_pool_id"))?,
+                }),
+                _ => {}
+            }
+        }
+        Ok(balances)
+    }
+}
+
+impl Serialize for Balances {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = s.serialize_seq(Some(self.assets.len() + self.pool_shares.len()))?;
+        for balance in &self.assets {
+            seq.serialize_element(balance)?;
+        }
+        for shares in &self.pool_shares {
+            seq.serialize_element(&IntermediateBalance {
+                balance: shares.balance,
+                limit: Some(shares.limit),
+                asset_type: POOL_SHARES.to_string(),
+                asset_code: None,
+                asset_issuer: None,
+                liquidity_pool_id: Some(shares.liquidity_pool_id.clone()),
+            })?;
+        }
+        seq.end()
+    }
+}
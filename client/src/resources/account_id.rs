@@ -0,0 +1,183 @@
+use serde::de;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use strkey;
+
+/// The public key of an account, as the `G...` strkey that horizon uses to
+/// identify it.
+///
+/// An `AccountId` can only hold a well formed strkey, so a mistyped address
+/// is caught when the id is made rather than when horizon rejects the
+/// request. Parse one with `str::parse` to handle bad input, or convert from
+/// a `&str` when the id is known to be good.
+///
+/// <https://www.stellar.org/developers/guides/concepts/accounts.html#account-id>
+///
+/// ## Example
+///
+/// ```
+/// use stellar_client::resources::AccountId;
+///
+/// let id: AccountId = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(id, "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG");
+/// assert!("GBAUUA74".parse::<AccountId>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AccountId(String);
+
+impl AccountId {
+    /// The strkey of the account.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The raw ed25519 public key of the account.
+    pub fn public_key(&self) -> [u8; 32] {
+        // Only valid strkeys make it into an account id.
+        strkey::decode(strkey::ACCOUNT, &self.0).unwrap()
+    }
+}
+
+/// The error returned when a string is not a valid `G...` account id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseAccountIdError(String);
+
+impl fmt::Display for ParseAccountIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} is not a valid account id", self.0)
+    }
+}
+
+impl ::std::error::Error for ParseAccountIdError {
+    fn description(&self) -> &str {
+        "invalid account id"
+    }
+}
+
+impl FromStr for AccountId {
+    type Err = ParseAccountIdError;
+
+    fn from_str(s: &str) -> Result<AccountId, ParseAccountIdError> {
+        match strkey::decode(strkey::ACCOUNT, s) {
+            Some(_) => Ok(AccountId(s.to_string())),
+            None => Err(ParseAccountIdError(s.to_string())),
+        }
+    }
+}
+
+/// Converts a known good strkey into an account id.
+///
+/// # Panics
+///
+/// Panics if the string is not a valid account id. Use `str::parse` for
+/// input that may be malformed.
+impl<'a> From<&'a str> for AccountId {
+    fn from(s: &'a str) -> AccountId {
+        match s.parse() {
+            Ok(id) => id,
+            Err(err) => panic!("{}", err),
+        }
+    }
+}
+
+impl<'a> From<&'a AccountId> for AccountId {
+    fn from(id: &'a AccountId) -> AccountId {
+        id.clone()
+    }
+}
+
+impl AsRef<str> for AccountId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for AccountId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl PartialEq<str> for AccountId {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl<'a> PartialEq<&'a str> for AccountId {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<String> for AccountId {
+    fn eq(&self, other: &String) -> bool {
+        self.0 == *other
+    }
+}
+
+impl<'de> Deserialize<'de> for AccountId {
+    fn deserialize<D>(d: D) -> Result<AccountId, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(d)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for AccountId {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        s.serialize_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod account_id_tests {
+    use super::*;
+    use serde_json;
+
+    const ID: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+
+    #[test]
+    fn it_parses_an_account_id() {
+        let id: AccountId = ID.parse().unwrap();
+        assert_eq!(id.as_str(), ID);
+        assert_eq!(id.to_string(), ID);
+        assert_eq!(AccountId::from(ID), id);
+    }
+
+    #[test]
+    fn it_rejects_malformed_account_ids() {
+        // Too short, a bad checksum, a secret seed and a contract id.
+        assert!("GBAUUA74".parse::<AccountId>().is_err());
+        assert!("GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAH"
+            .parse::<AccountId>()
+            .is_err());
+        assert!("SAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6NKI"
+            .parse::<AccountId>()
+            .is_err());
+        assert!("CDLZFC3SYJYDZT7K67VZ75HPJVIEUVNIXF47ZG2FB2RMQQVU2HHGCYSC"
+            .parse::<AccountId>()
+            .is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_panics_converting_a_malformed_account_id() {
+        let _ = AccountId::from("abc123");
+    }
+
+    #[test]
+    fn it_round_trips_through_json() {
+        let id: AccountId = serde_json::from_str(&format!("\"{}\"", ID)).unwrap();
+        assert_eq!(serde_json::to_string(&id).unwrap(), format!("\"{}\"", ID));
+        assert!(serde_json::from_str::<AccountId>("\"abc123\"").is_err());
+    }
+}
@@ -20,6 +20,128 @@ impl Amount {
         assert!(amount >= 0);
         Amount(amount)
     }
+
+    /// The number of stroops in this amount, where a stroop is one ten
+    /// millionth of a unit.
+    ///
+    /// ```
+    /// use stellar_client::resources::Amount;
+    ///
+    /// let amount: Amount = "1.5".parse().unwrap();
+    /// assert_eq!(amount.to_stroops(), 15_000_000);
+    /// ```
+    pub fn to_stroops(&self) -> i64 {
+        self.0
+    }
+
+    /// Makes an amount from a number of stroops, or `None` if the number is
+    /// negative.
+    ///
+    /// ```
+    /// use stellar_client::resources::Amount;
+    ///
+    /// assert_eq!(Amount::from_stroops(15_000_000).unwrap().to_string(), "1.5000000");
+    /// assert_eq!(Amount::from_stroops(-1), None);
+    /// ```
+    pub fn from_stroops(stroops: i64) -> Option<Amount> {
+        if stroops >= 0 {
+            Some(Amount(stroops))
+        } else {
+            None
+        }
+    }
+
+    /// Formats the amount for people to read. By default this is the same as
+    /// `Display`, with all seven decimals and no thousands separators, and
+    /// the decimal point is always a `.` whatever the locale.
+    ///
+    /// ```
+    /// use stellar_client::resources::Amount;
+    ///
+    /// let amount = Amount::new(12_345_678_900_000);
+    /// assert_eq!(amount.format().to_string(), "1234567.8900000");
+    /// assert_eq!(amount.format().trim_trailing_zeros().to_string(), "1234567.89");
+    /// assert_eq!(
+    ///     amount.format().with_thousands_separator(',').to_string(),
+    ///     "1,234,567.8900000"
+    /// );
+    /// ```
+    pub fn format(&self) -> AmountFormat {
+        AmountFormat {
+            amount: *self,
+            trim_trailing_zeros: false,
+            thousands_separator: None,
+        }
+    }
+}
+
+/// How to format an amount, made with `Amount::format`. Displays the
+/// formatted amount.
+#[derive(Debug, Clone, Copy)]
+pub struct AmountFormat {
+    amount: Amount,
+    trim_trailing_zeros: bool,
+    thousands_separator: Option<char>,
+}
+
+impl AmountFormat {
+    /// Leaves off the zeros at the end of the decimals, along with the
+    /// decimal point when the amount is a whole number.
+    pub fn trim_trailing_zeros(mut self) -> AmountFormat {
+        self.trim_trailing_zeros = true;
+        self
+    }
+
+    /// Shows all seven decimals, which is the default.
+    pub fn fixed(mut self) -> AmountFormat {
+        self.trim_trailing_zeros = false;
+        self
+    }
+
+    /// Puts `separator` between every group of three digits of the whole
+    /// units.
+    pub fn with_thousands_separator(mut self, separator: char) -> AmountFormat {
+        self.thousands_separator = Some(separator);
+        self
+    }
+
+    /// Leaves the whole units ungrouped, which is the default.
+    pub fn without_thousands_separator(mut self) -> AmountFormat {
+        self.thousands_separator = None;
+        self
+    }
+}
+
+impl fmt::Display for AmountFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let units = (self.amount.0 / 10_000_000).to_string();
+        let mut buf = String::with_capacity(32);
+        // The number of digits left before the next separator, starting
+        // with the shorter group at the front.
+        let mut group = (units.len() + 2) % 3 + 1;
+        for digit in units.chars() {
+            if group == 0 {
+                if let Some(separator) = self.thousands_separator {
+                    buf.push(separator);
+                }
+                group = 3;
+            }
+            buf.push(digit);
+            group -= 1;
+        }
+
+        let decimals = format!("{:07}", self.amount.0 % 10_000_000);
+        let decimals = if self.trim_trailing_zeros {
+            decimals.trim_end_matches('0')
+        } else {
+            &decimals
+        };
+        if !decimals.is_empty() {
+            buf.push('.');
+            buf.push_str(decimals);
+        }
+        f.write_str(&buf)
+    }
 }
 
 #[cfg(test)]
@@ -38,6 +160,63 @@ mod amount_tests {
     fn it_panics_with_negative_amounts() {
         Amount::new(-1);
     }
+    #[test]
+    fn it_converts_to_and_from_stroops() {
+        assert_eq!(Amount::from_stroops(12), Some(Amount(12)));
+        assert_eq!(Amount::from_stroops(0), Some(Amount(0)));
+        assert_eq!(Amount::from_stroops(-1), None);
+        assert_eq!(Amount(12).to_stroops(), 12);
+    }
+}
+
+#[cfg(test)]
+mod format_amount_tests {
+    use super::*;
+    #[test]
+    fn it_formats_like_display_by_default() {
+        for &stroops in &[0, 10, 123_456_789, 10_000_000, 12_345_678_900_000] {
+            let amount = Amount(stroops);
+            assert_eq!(amount.format().to_string(), amount.to_string());
+        }
+    }
+    #[test]
+    fn it_trims_trailing_zeros() {
+        let format = |stroops| Amount(stroops).format().trim_trailing_zeros().to_string();
+        assert_eq!(format(0), "0");
+        assert_eq!(format(10), "0.000001");
+        assert_eq!(format(10_000_000), "1");
+        assert_eq!(format(21_200_000), "2.12");
+        assert_eq!(
+            Amount(21_200_000)
+                .format()
+                .trim_trailing_zeros()
+                .fixed()
+                .to_string(),
+            "2.1200000"
+        );
+    }
+    #[test]
+    fn it_separates_thousands() {
+        let format = |stroops| {
+            Amount(stroops)
+                .format()
+                .with_thousands_separator(',')
+                .trim_trailing_zeros()
+                .to_string()
+        };
+        assert_eq!(format(1_230_000_000), "123");
+        assert_eq!(format(12_340_000_000), "1,234");
+        assert_eq!(format(1_234_567_000_000_000), "123,456,700");
+        assert_eq!(format(i64::max_value()), "922,337,203,685.4775807");
+        assert_eq!(
+            Amount(12_340_000_000)
+                .format()
+                .with_thousands_separator(' ')
+                .without_thousands_separator()
+                .to_string(),
+            "1234.0000000"
+        );
+    }
 }
 
 impl<'a> Add for &'a Amount {
@@ -1,4 +1,4 @@
-use resources::Amount;
+use resources::{AccountId, Amount, Links, ParseAccountIdError};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 use std::str::FromStr;
@@ -10,7 +10,7 @@ use std::str::FromStr;
 /// <https://www.stellar.org/developers/horizon/reference/resources/asset.html>
 
 /// An identifer is the type, code, and issuer.
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub enum AssetIdentifier {
     /// Stellar Lumens!
     Native,
@@ -21,19 +21,17 @@ pub enum AssetIdentifier {
 }
 
 /// Struct containing code and issuer
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
 pub struct AssetId {
     code: String,
-    issuer: String,
+    issuer: AccountId,
 }
 
 /// A convenience struct used for deserializing AssetIdentifier
 #[derive(Serialize, Deserialize, Debug)]
 pub struct IntermediateAssetIdentifier {
     asset_type: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
     asset_code: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     asset_issuer: Option<String>,
 }
 
@@ -61,8 +59,8 @@ impl Serialize for AssetIdentifier {
             },
             _ => IntermediateAssetIdentifier {
                 asset_type: self.asset_type().to_string(),
-                asset_code: Some(self.code().to_string()),
-                asset_issuer: Some(self.issuer().to_string()),
+                asset_code: self.asset_code(),
+                asset_issuer: self.asset_issuer(),
             },
         };
         rep.serialize(s)
@@ -80,42 +78,32 @@ impl AssetIdentifier {
         }
     }
 
-    /// The code of this asset.
-    /// Returns a slice that lives as long as the asset does.
-    pub fn code(&self) -> &str {
+    /// The code of this asset, or `None` for lumens.
+    pub fn code(&self) -> Option<&str> {
         match *self {
-            AssetIdentifier::Native => &"XLM",
-            AssetIdentifier::CreditAlphanum4(ref asset_id) => &asset_id.code,
-            AssetIdentifier::CreditAlphanum12(ref asset_id) => &asset_id.code,
+            AssetIdentifier::Native => None,
+            AssetIdentifier::CreditAlphanum4(ref asset_id) => Some(asset_id.code()),
+            AssetIdentifier::CreditAlphanum12(ref asset_id) => Some(asset_id.code()),
         }
     }
 
     /// The code of this asset as a result.
     pub fn asset_code(&self) -> Option<String> {
-        match *self {
-            AssetIdentifier::Native => None,
-            AssetIdentifier::CreditAlphanum4(ref asset_id) => Some(asset_id.code.clone()),
-            AssetIdentifier::CreditAlphanum12(ref asset_id) => Some(asset_id.code.clone()),
-        }
+        self.code().map(|code| code.to_string())
     }
 
-    /// The issuer of this asset.  This corresponds to the id of an account.
-    /// Returns a slice that lives as long as the asset does.
-    pub fn issuer(&self) -> &str {
+    /// The account that issued this asset, or `None` for lumens.
+    pub fn issuer(&self) -> Option<&AccountId> {
         match *self {
-            AssetIdentifier::Native => &"Stellar Foundation",
-            AssetIdentifier::CreditAlphanum4(ref asset_id) => &asset_id.issuer,
-            AssetIdentifier::CreditAlphanum12(ref asset_id) => &asset_id.issuer,
+            AssetIdentifier::Native => None,
+            AssetIdentifier::CreditAlphanum4(ref asset_id) => Some(asset_id.issuer()),
+            AssetIdentifier::CreditAlphanum12(ref asset_id) => Some(asset_id.issuer()),
         }
     }
 
     /// The issuer of this asset as a result
     pub fn asset_issuer(&self) -> Option<String> {
-        match *self {
-            AssetIdentifier::Native => None,
-            AssetIdentifier::CreditAlphanum4(ref asset_id) => Some(asset_id.issuer.clone()),
-            AssetIdentifier::CreditAlphanum12(ref asset_id) => Some(asset_id.issuer.clone()),
-        }
+        self.issuer().map(|issuer| issuer.to_string())
     }
 
     /// Returns true if this is the native lumen on the network
@@ -129,16 +117,20 @@ impl AssetIdentifier {
         code: Option<String>,
         issuer: Option<String>,
     ) -> Result<AssetIdentifier, String> {
+        let credit = |code: Option<String>, issuer: Option<String>| match (code, issuer) {
+            (Some(code), Some(issuer)) => Ok((code, issuer)),
+            _ => Err("Issued assets need a code and an issuer.".to_string()),
+        };
         match asset_type {
             "native" => Ok(AssetIdentifier::Native),
-            "credit_alphanum4" => Ok(AssetIdentifier::CreditAlphanum4(AssetId {
-                code: code.unwrap(),
-                issuer: issuer.unwrap(),
-            })),
-            "credit_alphanum12" => Ok(AssetIdentifier::CreditAlphanum12(AssetId {
-                code: code.unwrap(),
-                issuer: issuer.unwrap(),
-            })),
+            "credit_alphanum4" => {
+                let (code, issuer) = credit(code, issuer)?;
+                AssetIdentifier::alphanum4(&code, &issuer).map_err(|err| err.to_string())
+            }
+            "credit_alphanum12" => {
+                let (code, issuer) = credit(code, issuer)?;
+                AssetIdentifier::alphanum12(&code, &issuer).map_err(|err| err.to_string())
+            }
             _ => Err("Invalid Asset Type.".to_string()),
         }
     }
@@ -148,21 +140,67 @@ impl AssetIdentifier {
         AssetIdentifier::Native
     }
 
-    /// A type safe way of creating an alphanum4 asset AssetIdentifier
-    pub fn alphanum4(code: &str, issuer: &str) -> AssetIdentifier {
-        AssetIdentifier::CreditAlphanum4(AssetId {
-            code: code.to_string(),
-            issuer: issuer.to_string(),
-        })
+    /// Creates an alphanum4 asset. The code has to be 1 to 4 letters or
+    /// digits and the issuer a valid account id.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use stellar_client::resources::AssetIdentifier;
+    ///
+    /// let usd = AssetIdentifier::alphanum4(
+    ///     "USD",
+    ///     "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG",
+    /// ).unwrap();
+    /// assert_eq!(usd.code(), Some("USD"));
+    /// assert!(AssetIdentifier::alphanum4("USD", "GBAUUA74").is_err());
+    /// ```
+    pub fn alphanum4(
+        code: &str,
+        issuer: &str,
+    ) -> Result<AssetIdentifier, ParseAssetIdentifierError> {
+        AssetId::new(code, 1, 4, issuer).map(AssetIdentifier::CreditAlphanum4)
+    }
+
+    /// Creates an alphanum12 asset. The code has to be 5 to 12 letters or
+    /// digits, since shorter codes belong to alphanum4 assets, and the issuer
+    /// a valid account id.
+    pub fn alphanum12(
+        code: &str,
+        issuer: &str,
+    ) -> Result<AssetIdentifier, ParseAssetIdentifierError> {
+        AssetId::new(code, 5, 12, issuer).map(AssetIdentifier::CreditAlphanum12)
     }
+}
 
-    /// A type safe way of creating an alphanum12 asset AssetIdentifier
-    pub fn alphanum12(code: &str, issuer: &str) -> AssetIdentifier {
-        AssetIdentifier::CreditAlphanum12(AssetId {
+impl AssetId {
+    fn new(
+        code: &str,
+        min_len: usize,
+        max_len: usize,
+        issuer: &str,
+    ) -> Result<AssetId, ParseAssetIdentifierError> {
+        if code.len() < min_len
+            || code.len() > max_len
+            || !code.chars().all(|c| c.is_ascii_alphanumeric())
+        {
+            return Err(ParseAssetIdentifierError::InvalidCode(code.to_string()));
+        }
+        Ok(AssetId {
             code: code.to_string(),
-            issuer: issuer.to_string(),
+            issuer: issuer.parse()?,
         })
     }
+
+    /// The code of the asset.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// The account that issued the asset.
+    pub fn issuer(&self) -> &AccountId {
+        &self.issuer
+    }
 }
 
 #[cfg(test)]
@@ -178,13 +216,15 @@ mod asset_identifier_tests {
         include_str!("../../fixtures/native_asset.json")
     }
 
+    const ISSUER: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+
     #[test]
     fn it_parses_native_assets_from_json() {
         let native_asset: AssetIdentifier = serde_json::from_str(&native_asset_json()).unwrap();
         assert_eq!(native_asset.asset_type(), "native");
-        assert_eq!(native_asset.code(), "XLM");
+        assert_eq!(native_asset.code(), None);
         assert_eq!(native_asset.asset_code(), None);
-        assert_eq!(native_asset.issuer(), "Stellar Foundation");
+        assert_eq!(native_asset.issuer(), None);
         assert_eq!(native_asset.asset_issuer(), None);
         assert!(native_asset.is_native());
     }
@@ -193,14 +233,20 @@ mod asset_identifier_tests {
     fn it_parses_an_identifier() {
         let asset: AssetIdentifier = serde_json::from_str(&asset_json()).unwrap();
         assert_eq!(asset.asset_type(), "credit_alphanum4");
-        assert_eq!(asset.code(), "USD");
-        assert_eq!(
-            asset.issuer(),
-            "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG"
-        );
+        assert_eq!(asset.code(), Some("USD"));
+        assert_eq!(asset.issuer().unwrap(), ISSUER);
         assert!(!asset.is_native());
     }
 
+    #[test]
+    fn it_rejects_an_identifier_with_a_bad_issuer() {
+        let json =
+            r#"{"asset_type":"credit_alphanum4","asset_code":"USD","asset_issuer":"GBAUUA74"}"#;
+        assert!(serde_json::from_str::<AssetIdentifier>(json).is_err());
+        let json = r#"{"asset_type":"credit_alphanum4","asset_code":"USD"}"#;
+        assert!(serde_json::from_str::<AssetIdentifier>(json).is_err());
+    }
+
     #[test]
     fn it_serializes_non_native_assets() {
         let asset: AssetIdentifier = serde_json::from_str(&asset_json()).unwrap();
@@ -220,7 +266,9 @@ mod asset_identifier_tests {
         assert_eq!(
             serde_json::to_string(&native_asset).unwrap(),
             "{\
-             \"asset_type\":\"native\"\
+             \"asset_type\":\"native\",\
+             \"asset_code\":null,\
+             \"asset_issuer\":null\
              }"
         );
     }
@@ -229,43 +277,103 @@ mod asset_identifier_tests {
     fn it_creates_a_native_asset() {
         let asset: AssetIdentifier = AssetIdentifier::native();
         assert_eq!(asset.asset_type(), "native");
-        assert_eq!(asset.code(), "XLM");
+        assert_eq!(asset.code(), None);
         assert_eq!(asset.asset_code(), None);
-        assert_eq!(asset.issuer(), "Stellar Foundation");
+        assert_eq!(asset.issuer(), None);
         assert_eq!(asset.asset_issuer(), None);
         assert!(asset.is_native());
     }
 
     #[test]
     fn it_creates_an_alphanum4_asset() {
-        let asset: AssetIdentifier = AssetIdentifier::alphanum4("ABCD", "ISSUER");
+        let asset: AssetIdentifier = AssetIdentifier::alphanum4("ABCD", ISSUER).unwrap();
         assert_eq!(asset.asset_type(), "credit_alphanum4");
-        assert_eq!(asset.code(), "ABCD");
-        assert_eq!(asset.issuer(), "ISSUER");
+        assert_eq!(asset.code(), Some("ABCD"));
+        assert_eq!(asset.issuer().unwrap(), ISSUER);
         assert!(!asset.is_native());
     }
 
     #[test]
     fn it_creates_an_alphanum12_asset() {
-        let asset: AssetIdentifier = AssetIdentifier::alphanum12("ABCD", "ISSUER");
+        let asset: AssetIdentifier = AssetIdentifier::alphanum12("ABCDEF", ISSUER).unwrap();
         assert_eq!(asset.asset_type(), "credit_alphanum12");
-        assert_eq!(asset.code(), "ABCD");
-        assert_eq!(asset.issuer(), "ISSUER");
+        assert_eq!(asset.code(), Some("ABCDEF"));
+        assert_eq!(asset.issuer().unwrap(), ISSUER);
         assert!(!asset.is_native());
     }
+
+    #[test]
+    fn it_validates_asset_codes() {
+        let invalid = |code: &str| ParseAssetIdentifierError::InvalidCode(code.to_string());
+        assert_eq!(AssetIdentifier::alphanum4("", ISSUER), Err(invalid("")));
+        assert_eq!(
+            AssetIdentifier::alphanum4("ABCDE", ISSUER),
+            Err(invalid("ABCDE"))
+        );
+        assert_eq!(
+            AssetIdentifier::alphanum4("AB-C", ISSUER),
+            Err(invalid("AB-C"))
+        );
+        assert_eq!(
+            AssetIdentifier::alphanum12("ABCD", ISSUER),
+            Err(invalid("ABCD"))
+        );
+        assert_eq!(
+            AssetIdentifier::alphanum12("ABCDEFGHIJKLM", ISSUER),
+            Err(invalid("ABCDEFGHIJKLM"))
+        );
+    }
+
+    #[test]
+    fn it_validates_asset_issuers() {
+        match AssetIdentifier::alphanum4("USD", "ISSUER") {
+            Err(ParseAssetIdentifierError::InvalidIssuer(_)) => {}
+            result => panic!("Expected an invalid issuer, got {:?}", result),
+        }
+        assert!(AssetIdentifier::alphanum12("STARFOX", "GBAUUA74").is_err());
+    }
 }
 
 /// When a bad token or string is provided to parsing into an asset
 /// you get an error.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ParseAssetIdentifierError {
     /// Asset identifier not of the form <asset_code>-<asset_issuer>
     FormattedIncorrectly,
+    /// The asset code is empty, too long for its asset type, or has
+    /// characters other than letters and digits
+    InvalidCode(String),
+    /// The asset issuer is not a valid account id
+    InvalidIssuer(ParseAccountIdError),
+}
+
+impl From<ParseAccountIdError> for ParseAssetIdentifierError {
+    fn from(err: ParseAccountIdError) -> Self {
+        ParseAssetIdentifierError::InvalidIssuer(err)
+    }
 }
 
 impl fmt::Display for ParseAssetIdentifierError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self)
+        match *self {
+            ParseAssetIdentifierError::FormattedIncorrectly => {
+                write!(f, "assets are formatted as <asset_code>-<asset_issuer>")
+            }
+            ParseAssetIdentifierError::InvalidCode(ref code) => {
+                write!(f, "{} is not a valid asset code", code)
+            }
+            ParseAssetIdentifierError::InvalidIssuer(ref err) => err.fmt(f),
+        }
+    }
+}
+
+impl ::std::error::Error for ParseAssetIdentifierError {
+    fn description(&self) -> &str {
+        match *self {
+            ParseAssetIdentifierError::FormattedIncorrectly => "incorrectly formatted asset",
+            ParseAssetIdentifierError::InvalidCode(_) => "invalid asset code",
+            ParseAssetIdentifierError::InvalidIssuer(_) => "invalid asset issuer",
+        }
     }
 }
 
@@ -277,8 +385,8 @@ impl FromStr for AssetIdentifier {
 
         match &tokens[..] {
             ["XLM"] | ["xlm"] | ["lumen"] => Ok(AssetIdentifier::Native),
-            [code, issuer] if code.len() <= 4 => Ok(AssetIdentifier::alphanum4(code, issuer)),
-            [code, issuer] if code.len() <= 12 => Ok(AssetIdentifier::alphanum12(code, issuer)),
+            [code, issuer] if code.len() <= 4 => AssetIdentifier::alphanum4(code, issuer),
+            [code, issuer] => AssetIdentifier::alphanum12(code, issuer),
             _ => Err(ParseAssetIdentifierError::FormattedIncorrectly),
         }
     }
@@ -288,6 +396,8 @@ impl FromStr for AssetIdentifier {
 mod from_str_asset_identifier_tests {
     use super::*;
 
+    const ISSUER: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+
     #[test]
     fn it_knows_some_lumen_aliases() {
         assert_eq!(
@@ -315,12 +425,12 @@ mod from_str_asset_identifier_tests {
             AssetIdentifier::Native
         );
         assert_eq!(
-            AssetIdentifier::from_str("fox-123ABC").unwrap(),
-            AssetIdentifier::alphanum4("fox", "123ABC")
+            AssetIdentifier::from_str(&format!("fox-{}", ISSUER)).unwrap(),
+            AssetIdentifier::alphanum4("fox", ISSUER).unwrap()
         );
         assert_eq!(
-            AssetIdentifier::from_str("starfox-123ABC").unwrap(),
-            AssetIdentifier::alphanum12("starfox", "123ABC")
+            AssetIdentifier::from_str(&format!("starfox-{}", ISSUER)).unwrap(),
+            AssetIdentifier::alphanum12("starfox", ISSUER).unwrap()
         );
     }
 
@@ -328,12 +438,19 @@ mod from_str_asset_identifier_tests {
     fn it_returns_appropriate_errors() {
         assert!(AssetIdentifier::from_str("fox-123-abs").is_err());
         assert!(AssetIdentifier::from_str("foxisareallycoolanimal-123").is_err());
+        assert_eq!(
+            AssetIdentifier::from_str(&format!("foxisareallycoolanimal-{}", ISSUER)),
+            Err(ParseAssetIdentifierError::InvalidCode(
+                "foxisareallycoolanimal".to_string()
+            ))
+        );
+        assert!(AssetIdentifier::from_str("fox-123ABC").is_err());
     }
 }
 
 /// Permissions around who can own an asset and whether or
 /// not the asset issuer can freeze the asset.
-#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Flags {
     auth_required: bool,
     auth_revocable: bool,
@@ -371,18 +488,19 @@ impl Flags {
 /// <https://www.stellar.org/developers/horizon/reference/resources/asset.html>
 #[derive(Debug, Clone)]
 pub struct Asset {
+    links: Links,
     asset_identifier: AssetIdentifier,
     amount: Amount,
     num_accounts: u32,
     flags: Flags,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct IntermediateAsset {
+    #[serde(rename = "_links", default)]
+    links: Links,
     asset_type: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
     asset_code: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     asset_issuer: Option<String>,
     amount: Amount,
     num_accounts: u32,
@@ -399,6 +517,7 @@ impl<'de> Deserialize<'de> for Asset {
             AssetIdentifier::new(&rep.asset_type, rep.asset_code, rep.asset_issuer)
                 .map_err(de::Error::custom);
         Ok(Asset {
+            links: rep.links,
             asset_identifier: asset_identifier.unwrap(),
             amount: rep.amount,
             num_accounts: rep.num_accounts,
@@ -407,7 +526,46 @@ impl<'de> Deserialize<'de> for Asset {
     }
 }
 
+impl Serialize for Asset {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let rep = IntermediateAsset {
+            links: self.links.clone(),
+            asset_type: self.asset_identifier.asset_type().to_string(),
+            asset_code: self.asset_identifier.asset_code(),
+            asset_issuer: self.asset_identifier.asset_issuer(),
+            amount: self.amount,
+            num_accounts: self.num_accounts,
+            flags: self.flags,
+        };
+        rep.serialize(s)
+    }
+}
+
 impl Asset {
+    /// Creates a new asset.
+    pub fn new(
+        asset_identifier: AssetIdentifier,
+        amount: Amount,
+        num_accounts: u32,
+        flags: Flags,
+    ) -> Asset {
+        Asset {
+            links: Links::default(),
+            asset_identifier,
+            amount,
+            num_accounts,
+            flags,
+        }
+    }
+
+    /// The links to the resources related to this one.
+    pub fn links(&self) -> &Links {
+        &self.links
+    }
+
     /// The identifier of this asset.
     pub fn identifier(&self) -> &AssetIdentifier {
         &self.asset_identifier
@@ -422,13 +580,17 @@ impl Asset {
     /// The code of this asset.
     /// Returns a slice that lives as long as the asset does.
     pub fn code(&self) -> &str {
-        &self.asset_identifier.code()
+        // Horizon only lists issued assets, which always have a code.
+        self.asset_identifier.code().unwrap_or_default()
     }
 
     /// The issuer of this asset.  This corresponds to the id of an account.
     /// Returns a slice that lives as long as the asset does.
     pub fn issuer(&self) -> &str {
-        &self.asset_identifier.issuer()
+        self.asset_identifier
+            .issuer()
+            .map(AccountId::as_str)
+            .unwrap_or_default()
     }
 
     /// The number of units of credit issued for this asset.
@@ -497,7 +659,8 @@ mod asset_tests {
             &AssetIdentifier::alphanum4(
                 "USD",
                 "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG"
-            ),
+            )
+            .unwrap(),
         );
     }
 }
@@ -0,0 +1,316 @@
+use chrono::{DateTime, TimeZone, Utc};
+use resources::{AccountId, Amount, AssetIdentifier, Links};
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// A balance set aside for one or more claimants, any of which can take it
+/// into their account once its predicate allows.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ClaimableBalance {
+    #[serde(rename = "_links", default)]
+    links: Links,
+    id: String,
+    paging_token: String,
+    #[serde(with = "canonical_asset")]
+    asset: AssetIdentifier,
+    amount: Amount,
+    sponsor: Option<AccountId>,
+    last_modified_ledger: u32,
+    last_modified_time: Option<DateTime<Utc>>,
+    claimants: Vec<Claimant>,
+    #[serde(default)]
+    flags: BalanceFlags,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default)]
+struct BalanceFlags {
+    clawback_enabled: bool,
+}
+
+impl ClaimableBalance {
+    /// The links to the resources related to this one.
+    pub fn links(&self) -> &Links {
+        &self.links
+    }
+
+    /// The hex encoded id of the balance, which is what a claim names.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// A paging_token suitable for use as a cursor parameter.
+    pub fn paging_token(&self) -> &str {
+        &self.paging_token
+    }
+
+    /// The asset held.
+    pub fn asset(&self) -> &AssetIdentifier {
+        &self.asset
+    }
+
+    /// How much of the asset is held.
+    pub fn amount(&self) -> Amount {
+        self.amount
+    }
+
+    /// The account paying the reserve for the balance, when it is
+    /// sponsored.
+    pub fn sponsor(&self) -> Option<&AccountId> {
+        self.sponsor.as_ref()
+    }
+
+    /// The ledger the balance was created or last changed in.
+    pub fn last_modified_ledger(&self) -> u32 {
+        self.last_modified_ledger
+    }
+
+    /// When that ledger closed, if horizon said.
+    pub fn last_modified_time(&self) -> Option<DateTime<Utc>> {
+        self.last_modified_time
+    }
+
+    /// The accounts that can claim the balance, and when.
+    pub fn claimants(&self) -> &[Claimant] {
+        &self.claimants
+    }
+
+    /// Whether the issuer can claw the balance back.
+    pub fn is_clawback_enabled(&self) -> bool {
+        self.flags.clawback_enabled
+    }
+
+    /// Whether `account` is a claimant whose predicate holds in a ledger
+    /// that closes at `time`, in unix seconds.
+    pub fn is_claimable_by(&self, account: &AccountId, time: i64) -> bool {
+        self.claimants
+            .iter()
+            .any(|claimant| claimant.destination == *account && claimant.predicate.holds_at(time))
+    }
+}
+
+/// An account that can claim a claimable balance, and when.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Claimant {
+    destination: AccountId,
+    predicate: ClaimPredicate,
+}
+
+impl Claimant {
+    /// The account that can claim.
+    pub fn destination(&self) -> &AccountId {
+        &self.destination
+    }
+
+    /// The condition under which the account can claim.
+    pub fn predicate(&self) -> &ClaimPredicate {
+        &self.predicate
+    }
+}
+
+/// A condition on claiming a claimable balance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClaimPredicate {
+    /// The balance can always be claimed.
+    Unconditional,
+    /// Every predicate must hold.
+    And(Vec<ClaimPredicate>),
+    /// Any predicate must hold.
+    Or(Vec<ClaimPredicate>),
+    /// The predicate must not hold.
+    Not(Box<ClaimPredicate>),
+    /// The ledger must close before this time, in unix seconds.
+    BeforeAbsoluteTime(i64),
+    /// The ledger must close within this many seconds of the balance being
+    /// created.
+    BeforeRelativeTime(i64),
+}
+
+impl ClaimPredicate {
+    /// Whether the predicate holds in a ledger that closes at `time`, in
+    /// unix seconds.
+    ///
+    /// The network turns relative times into absolute ones when a balance
+    /// is created, so horizon only shows them on the operations that
+    /// created balances. Without the time the balance was created they
+    /// can't be decided, and are taken not to hold.
+    pub fn holds_at(&self, time: i64) -> bool {
+        match *self {
+            ClaimPredicate::Unconditional => true,
+            ClaimPredicate::And(ref predicates) => predicates.iter().all(|p| p.holds_at(time)),
+            ClaimPredicate::Or(ref predicates) => predicates.iter().any(|p| p.holds_at(time)),
+            ClaimPredicate::Not(ref predicate) => !predicate.holds_at(time),
+            ClaimPredicate::BeforeAbsoluteTime(before) => time < before,
+            ClaimPredicate::BeforeRelativeTime(_) => false,
+        }
+    }
+}
+
+// Horizon writes a predicate as an object with a single key naming its
+// kind. Absolute times come as a date along with the exact unix time, which
+// the date can't always hold.
+#[derive(Deserialize)]
+struct IntermediatePredicate {
+    #[serde(default)]
+    unconditional: bool,
+    and: Option<Vec<ClaimPredicate>>,
+    or: Option<Vec<ClaimPredicate>>,
+    not: Option<Box<ClaimPredicate>>,
+    abs_before: Option<DateTime<Utc>>,
+    abs_before_epoch: Option<String>,
+    rel_before: Option<String>,
+}
+
+impl<'de> Deserialize<'de> for ClaimPredicate {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let rep = IntermediatePredicate::deserialize(d)?;
+        let seconds = |value: String| value.parse::<i64>().map_err(de::Error::custom);
+        if rep.unconditional {
+            Ok(ClaimPredicate::Unconditional)
+        } else if let Some(predicates) = rep.and {
+            Ok(ClaimPredicate::And(predicates))
+        } else if let Some(predicates) = rep.or {
+            Ok(ClaimPredicate::Or(predicates))
+        } else if let Some(predicate) = rep.not {
+            Ok(ClaimPredicate::Not(predicate))
+        } else if let Some(epoch) = rep.abs_before_epoch {
+            seconds(epoch).map(ClaimPredicate::BeforeAbsoluteTime)
+        } else if let Some(before) = rep.abs_before {
+            Ok(ClaimPredicate::BeforeAbsoluteTime(before.timestamp()))
+        } else if let Some(before) = rep.rel_before {
+            seconds(before).map(ClaimPredicate::BeforeRelativeTime)
+        } else {
+            Err(de::Error::custom("unknown claim predicate"))
+        }
+    }
+}
+
+impl Serialize for ClaimPredicate {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // MessagePack needs to know how many entries there are up front.
+        let date = match *self {
+            ClaimPredicate::BeforeAbsoluteTime(before) => Utc.timestamp_opt(before, 0).single(),
+            _ => None,
+        };
+        let mut map = s.serialize_map(Some(if date.is_some() { 2 } else { 1 }))?;
+        match *self {
+            ClaimPredicate::Unconditional => map.serialize_entry("unconditional", &true)?,
+            ClaimPredicate::And(ref predicates) => map.serialize_entry("and", predicates)?,
+            ClaimPredicate::Or(ref predicates) => map.serialize_entry("or", predicates)?,
+            ClaimPredicate::Not(ref predicate) => map.serialize_entry("not", predicate)?,
+            ClaimPredicate::BeforeAbsoluteTime(before) => {
+                if let Some(date) = date {
+                    map.serialize_entry("abs_before", &date)?;
+                }
+                map.serialize_entry("abs_before_epoch", &before.to_string())?;
+            }
+            ClaimPredicate::BeforeRelativeTime(before) => {
+                map.serialize_entry("rel_before", &before.to_string())?
+            }
+        }
+        map.end()
+    }
+}
+
+/// Claimable balances give their asset as a single string, `native` or
+/// `CODE:ISSUER`, rather than the separate fields used elsewhere.
+pub(crate) mod canonical_asset {
+    use resources::{AssetIdentifier, ParseAssetIdentifierError};
+    use serde::de::{self, Deserialize, Deserializer};
+    use serde::Serializer;
+
+    pub fn parse(asset: &str) -> Result<AssetIdentifier, ParseAssetIdentifierError> {
+        match asset.split(':').collect::<Vec<_>>()[..] {
+            ["native"] => Ok(AssetIdentifier::native()),
+            [code, issuer] if code.len() <= 4 => AssetIdentifier::alphanum4(code, issuer),
+            [code, issuer] => AssetIdentifier::alphanum12(code, issuer),
+            _ => Err(ParseAssetIdentifierError::FormattedIncorrectly),
+        }
+    }
+
+    pub fn format(asset: &AssetIdentifier) -> String {
+        match (asset.code(), asset.issuer()) {
+            (Some(code), Some(issuer)) => format!("{}:{}", code, issuer),
+            _ => "native".to_string(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(d: D) -> Result<AssetIdentifier, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        parse(&String::deserialize(d)?).map_err(de::Error::custom)
+    }
+
+    pub fn serialize<S>(asset: &AssetIdentifier, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        s.serialize_str(&format(asset))
+    }
+}
+
+#[cfg(test)]
+mod claimable_balance_tests {
+    use super::*;
+    use serde_json;
+
+    const CLAIMANT: &str = "GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM";
+    const LATE_CLAIMANT: &str = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4";
+
+    fn balance() -> ClaimableBalance {
+        serde_json::from_str(include_str!("../../fixtures/claimable_balance.json")).unwrap()
+    }
+
+    #[test]
+    fn it_parses_a_claimable_balance_from_json() {
+        let balance = balance();
+        assert_eq!(
+            balance.id(),
+            "00000000da0d57da7d4850e7fc10d2a9d0ebc731f7afb40574c03395b17d49149b91f5be"
+        );
+        assert_eq!(balance.asset().code(), Some("USD"));
+        assert_eq!(balance.amount(), Amount::new(100_000_000));
+        assert_eq!(balance.sponsor(), Some(&LATE_CLAIMANT.into()));
+        assert_eq!(balance.last_modified_ledger(), 1_524_302);
+        assert!(!balance.is_clawback_enabled());
+        assert_eq!(balance.claimants().len(), 2);
+        assert_eq!(
+            balance.claimants()[1].predicate(),
+            &ClaimPredicate::And(vec![
+                ClaimPredicate::Not(Box::new(ClaimPredicate::BeforeAbsoluteTime(1_700_092_800))),
+                ClaimPredicate::BeforeAbsoluteTime(1_710_000_000),
+            ])
+        );
+    }
+
+    #[test]
+    fn it_evaluates_who_can_claim_and_when() {
+        let balance = balance();
+        let (claimant, late) = (CLAIMANT.into(), LATE_CLAIMANT.into());
+        assert!(balance.is_claimable_by(&claimant, 0));
+        assert!(!balance.is_claimable_by(&late, 1_700_092_799));
+        assert!(balance.is_claimable_by(&late, 1_700_092_800));
+        assert!(!balance.is_claimable_by(&late, 1_710_000_000));
+        assert!(!balance.is_claimable_by(
+            &"GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into(),
+            0
+        ));
+    }
+
+    #[test]
+    fn it_leaves_relative_times_undecided() {
+        let predicate: ClaimPredicate = serde_json::from_str(r#"{ "rel_before": "60" }"#).unwrap();
+        assert_eq!(predicate, ClaimPredicate::BeforeRelativeTime(60));
+        assert!(!predicate.holds_at(0));
+        assert_eq!(
+            serde_json::to_string(&predicate).unwrap(),
+            r#"{"rel_before":"60"}"#
+        );
+    }
+}
@@ -5,12 +5,17 @@ use resources::base64string::Base64String;
 /// for various reasons. Datum represents the value of a single key/value pair.
 ///
 /// <https://www.stellar.org/developers/horizon/reference/resources/data.html>
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct Datum {
     value: Base64String,
 }
 
 impl Datum {
+    /// Creates a new datum holding the value.
+    pub fn new(value: Base64String) -> Datum {
+        Datum { value }
+    }
+
     /// The value of a single key/value pair tied to a single account.
     pub fn value(&self) -> &str {
         &self.value.0
@@ -1,5 +1,6 @@
 use serde::de;
 use serde::{Deserialize, Deserializer};
+use std::fmt;
 use std::str::FromStr;
 
 /// Some fields in the json are represented as "strings" but are actually
@@ -16,6 +17,35 @@ where
     T::from_str(&s).map_err(|_| de::Error::custom("Failed to parse string field"))
 }
 
+/// Passes over a string field without keeping it. Declaring a heavy field
+/// with this rather than leaving it out of the struct matters when the struct
+/// flattens another one in, since serde buffers every field the struct
+/// doesn't declare for the flattened struct to look at. The string is only
+/// borrowed for as long as the visitor looks at it, so it isn't copied.
+pub(crate) fn skip<'de, D>(d: D) -> Result<(), D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct Skip;
+
+    impl<'de> de::Visitor<'de> for Skip {
+        type Value = ();
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a string")
+        }
+
+        fn visit_str<E>(self, _: &str) -> Result<(), E>
+        where
+            E: de::Error,
+        {
+            Ok(())
+        }
+    }
+
+    d.deserialize_str(Skip)
+}
+
 #[cfg(test)]
 mod from_str_tests {
     use serde_json::value::Value;
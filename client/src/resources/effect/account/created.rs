@@ -1,22 +1,22 @@
-use resources::Amount;
+use resources::{AccountId, Amount};
 /// This effect is the result of a create account operation and represents
 /// the fact that an account was created
 #[derive(Debug, Deserialize, Clone)]
 pub struct Created {
-    account: String,
+    account: AccountId,
     starting_balance: Amount,
 }
 
 impl Created {
     /// Creates a new Account
-    pub fn new(account: String, starting_balance: Amount) -> Created {
+    pub fn new(account: AccountId, starting_balance: Amount) -> Created {
         Created {
             account,
             starting_balance,
         }
     }
     /// The public address of a new account that was funded.
-    pub fn account(&self) -> &String {
+    pub fn account(&self) -> &AccountId {
         &self.account
     }
 
@@ -1,17 +1,17 @@
-use resources::{Amount, AssetIdentifier};
+use resources::{AccountId, Amount, AssetIdentifier};
 /// This effect can be the result of a create_account, payment, path_payment
 /// or merge_account operation.  It represents the fact that assets were
 /// added to an account
 #[derive(Debug, Deserialize, Clone)]
 pub struct Credited {
-    account: String,
+    account: AccountId,
     amount: Amount,
     asset: AssetIdentifier,
 }
 
 impl Credited {
     /// Creates a new Credited effect
-    pub fn new(account: String, amount: Amount, asset: AssetIdentifier) -> Credited {
+    pub fn new(account: AccountId, amount: Amount, asset: AssetIdentifier) -> Credited {
         Credited {
             account,
             amount,
@@ -19,7 +19,7 @@ impl Credited {
         }
     }
     /// The public address of the account that was removed
-    pub fn account(&self) -> &String {
+    pub fn account(&self) -> &AccountId {
         &self.account
     }
 
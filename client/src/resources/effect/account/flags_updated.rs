@@ -1,20 +1,21 @@
 use resources::asset::Flags;
+use resources::AccountId;
 /// This effect can be the result of a set options operation and represents
 /// the fact that an account's flags have been updated
 #[derive(Debug, Deserialize, Clone)]
 pub struct FlagsUpdated {
-    account: String,
+    account: AccountId,
     flags: Flags,
 }
 
 impl FlagsUpdated {
     /// Creates a new FlagsUpdated effect
-    pub fn new(account: String, flags: Flags) -> FlagsUpdated {
+    pub fn new(account: AccountId, flags: Flags) -> FlagsUpdated {
         FlagsUpdated { account, flags }
     }
 
     /// The public address of the account with updated flags
-    pub fn account(&self) -> &String {
+    pub fn account(&self) -> &AccountId {
         &self.account
     }
 
@@ -1,14 +1,15 @@
+use resources::AccountId;
 /// This effect can be the result of a set options operation and represents
 /// the fact that an account's home domain has changed
 #[derive(Debug, Deserialize, Clone)]
 pub struct HomeDomainUpdated {
-    account: String,
+    account: AccountId,
     home_domain: String,
 }
 
 impl HomeDomainUpdated {
     /// Creates a HomeDomainUpdated effect
-    pub fn new(account: String, home_domain: String) -> HomeDomainUpdated {
+    pub fn new(account: AccountId, home_domain: String) -> HomeDomainUpdated {
         HomeDomainUpdated {
             account,
             home_domain,
@@ -16,7 +17,7 @@ impl HomeDomainUpdated {
     }
 
     /// The public address of the account whose home_domain was updated
-    pub fn account(&self) -> &String {
+    pub fn account(&self) -> &AccountId {
         &self.account
     }
 
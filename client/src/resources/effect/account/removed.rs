@@ -1,17 +1,18 @@
+use resources::AccountId;
 /// This effect is the result of a create merge operation and represents
 /// the fact that an account was removed in the merge
 #[derive(Debug, Deserialize, Clone)]
 pub struct Removed {
-    account: String,
+    account: AccountId,
 }
 
 impl Removed {
     /// Creates a new account Removed effect
-    pub fn new(account: String) -> Removed {
+    pub fn new(account: AccountId) -> Removed {
         Removed { account }
     }
     /// The public address of the account that was removed
-    pub fn account(&self) -> &String {
+    pub fn account(&self) -> &AccountId {
         &self.account
     }
 }
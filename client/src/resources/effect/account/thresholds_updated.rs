@@ -1,8 +1,9 @@
+use resources::AccountId;
 /// This effect can be the result of a set options operation and represents
 /// the fact that an account's weight thresholds have changed.
 #[derive(Debug, Deserialize, Clone)]
 pub struct ThresholdsUpdated {
-    account: String,
+    account: AccountId,
     low: u32,
     med: u32,
     high: u32,
@@ -10,7 +11,7 @@ pub struct ThresholdsUpdated {
 
 impl ThresholdsUpdated {
     /// Creates a new ThresholdsUpdated effect
-    pub fn new(account: String, low: u32, med: u32, high: u32) -> ThresholdsUpdated {
+    pub fn new(account: AccountId, low: u32, med: u32, high: u32) -> ThresholdsUpdated {
         ThresholdsUpdated {
             account,
             low,
@@ -20,7 +21,7 @@ impl ThresholdsUpdated {
     }
 
     /// The public address of the account that had its thresholds updated.
-    pub fn account(&self) -> &String {
+    pub fn account(&self) -> &AccountId {
         &self.account
     }
 
@@ -1,4 +1,5 @@
 //! Contains effects related to the management of data.
+use resources::AccountId;
 
 /// The type of change that was performed
 #[derive(Debug, Deserialize, Clone)]
@@ -14,16 +15,16 @@ pub enum Kind {
 /// Contains details about the data that was changed
 #[derive(Debug, Deserialize, Clone)]
 pub struct Effect {
-    account: String,
+    account: AccountId,
 }
 
 impl Effect {
     /// Creates a new Account
-    pub fn new(account: String) -> Self {
+    pub fn new(account: AccountId) -> Self {
         Self { account }
     }
     /// The public address of a new account that was funded.
-    pub fn account(&self) -> &str {
+    pub fn account(&self) -> &AccountId {
         &self.account
     }
 }
@@ -1,5 +1,6 @@
-use resources::{asset::Flags, Amount, AssetIdentifier};
-use serde::{de, Deserialize, Deserializer};
+use chrono::prelude::*;
+use resources::{asset::Flags, AccountId, Amount, AssetIdentifier, Links};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 pub mod account;
 pub mod data;
@@ -15,8 +16,11 @@ mod test;
 /// history, as transactions and operations are.
 #[derive(Debug, Clone)]
 pub struct Effect {
+    links: Links,
     id: String,
     paging_token: String,
+    operation_id: i64,
+    created_at: DateTime<Utc>,
     kind: Kind,
 }
 
@@ -41,16 +45,84 @@ pub enum EffectKind {
 use self::EffectKind as Kind;
 
 impl Effect {
+    /// Creates a new effect of the given kind, caused by the operation with
+    /// id `operation_id`.
+    pub fn new(
+        id: String,
+        paging_token: String,
+        operation_id: i64,
+        created_at: DateTime<Utc>,
+        kind: Kind,
+    ) -> Effect {
+        Effect {
+            links: Links::default(),
+            id,
+            paging_token,
+            operation_id,
+            created_at,
+            kind,
+        }
+    }
+
     /// the unique identifier of an effect
     pub fn id(&self) -> &String {
         &self.id
     }
 
+    /// The links to the resources related to this one.
+    pub fn links(&self) -> &Links {
+        &self.links
+    }
+
     /// A paging token suitable for use as a cursor parameter.
     pub fn paging_token(&self) -> &String {
         &self.paging_token
     }
 
+    /// The id of the operation that caused this effect. Use it with
+    /// `endpoint::operation::Details` to fetch the operation.
+    pub fn operation_id(&self) -> i64 {
+        self.operation_id
+    }
+
+    /// The time the ledger containing the effect closed.
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    /// The account whose state the effect changed.
+    pub fn account(&self) -> &AccountId {
+        match self.kind {
+            Kind::Account(ref account_kind) => match *account_kind {
+                account::Kind::Created(ref effect) => effect.account(),
+                account::Kind::Removed(ref effect) => effect.account(),
+                account::Kind::Credited(ref effect) => effect.account(),
+                account::Kind::Debited(ref effect) => effect.account(),
+                account::Kind::ThresholdsUpdated(ref effect) => effect.account(),
+                account::Kind::HomeDomainUpdated(ref effect) => effect.account(),
+                account::Kind::FlagsUpdated(ref effect) => effect.account(),
+            },
+            Kind::Signer(ref signer_kind) => match *signer_kind {
+                signer::Kind::Created(ref effect) => effect.account(),
+                signer::Kind::Removed(ref effect) => effect.account(),
+                signer::Kind::Updated(ref effect) => effect.account(),
+            },
+            Kind::Trustline(ref trustline_kind) => match *trustline_kind {
+                trustline::Kind::Created(ref effect) => effect.account(),
+                trustline::Kind::Removed(ref effect) => effect.account(),
+                trustline::Kind::Updated(ref effect) => effect.account(),
+                trustline::Kind::Authorized(ref effect) => effect.account(),
+                trustline::Kind::Deauthorized(ref effect) => effect.account(),
+            },
+            Kind::Trade(trade::Kind::Trade(ref effect)) => effect.account(),
+            Kind::Data(ref kind) => match *kind {
+                data::Kind::Created(ref effect) => effect.account(),
+                data::Kind::Removed(ref effect) => effect.account(),
+                data::Kind::Updated(ref effect) => effect.account(),
+            },
+        }
+    }
+
     /// Specifies the type of effect, See “Types” section below for reference.
     pub fn type_i(&self) -> u32 {
         match self.kind {
@@ -277,13 +349,18 @@ impl Effect {
 
 /// Represents the actual structure of the json api. This allows us to parse
 /// directly from the captured json into our own types.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 struct Intermediate {
+    #[serde(rename = "_links", default)]
+    links: Links,
     id: String,
     paging_token: String,
+    created_at: DateTime<Utc>,
     #[serde(rename = "type")]
     kind: String,
-    account: Option<String>,
+    #[serde(default)]
+    type_i: Option<u32>,
+    account: Option<AccountId>,
     starting_balance: Option<Amount>,
     amount: Option<Amount>,
     asset_type: Option<String>,
@@ -300,7 +377,7 @@ struct Intermediate {
     limit: Option<Amount>,
     trustor: Option<String>,
     offer_id: Option<i64>,
-    seller: Option<String>,
+    seller: Option<AccountId>,
     bought_amount: Option<Amount>,
     bought_asset_type: Option<String>,
     bought_asset_code: Option<String>,
@@ -317,6 +394,15 @@ impl<'de> Deserialize<'de> for Effect {
         D: Deserializer<'de>,
     {
         let rep = Intermediate::deserialize(d)?;
+        // Effect ids are the id of their operation followed by the effect's
+        // position among that operation's effects.
+        let operation_id = rep
+            .id
+            .split('-')
+            .next()
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(|| de::Error::custom("Malformed effect id."))?;
+        let created_at = rep.created_at;
 
         let kind: Kind = match rep.kind.as_str() {
             "account_created" => match rep {
@@ -665,9 +751,163 @@ impl<'de> Deserialize<'de> for Effect {
         };
 
         Ok(Effect {
+            links: rep.links,
             id: rep.id,
             paging_token: rep.paging_token,
+            operation_id,
+            created_at,
             kind,
         })
     }
 }
+
+impl Serialize for Effect {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut rep = Intermediate {
+            links: self.links.clone(),
+            id: self.id.clone(),
+            paging_token: self.paging_token.clone(),
+            created_at: self.created_at,
+            kind: String::new(),
+            type_i: Some(self.type_i()),
+            account: None,
+            starting_balance: None,
+            amount: None,
+            asset_type: None,
+            asset_code: None,
+            asset_issuer: None,
+            low_threshold: None,
+            med_threshold: None,
+            high_threshold: None,
+            home_domain: None,
+            auth_required_flag: None,
+            auth_revokable_flag: None,
+            weight: None,
+            public_key: None,
+            limit: None,
+            trustor: None,
+            offer_id: None,
+            seller: None,
+            bought_amount: None,
+            bought_asset_type: None,
+            bought_asset_code: None,
+            bought_asset_issuer: None,
+            sold_amount: None,
+            sold_asset_type: None,
+            sold_asset_code: None,
+            sold_asset_issuer: None,
+        };
+        rep.account = Some(self.account().clone());
+        let kind = match self.kind {
+            Kind::Account(ref kind) => match *kind {
+                account::Kind::Created(ref effect) => {
+                    rep.starting_balance = Some(effect.starting_balance());
+                    "account_created"
+                }
+                account::Kind::Removed(_) => "account_removed",
+                account::Kind::Credited(ref effect) => {
+                    rep.set_asset(effect.asset());
+                    rep.amount = Some(effect.amount());
+                    "account_credited"
+                }
+                account::Kind::Debited(ref effect) => {
+                    rep.set_asset(effect.asset());
+                    rep.amount = Some(effect.amount());
+                    "account_debited"
+                }
+                account::Kind::ThresholdsUpdated(ref effect) => {
+                    rep.low_threshold = Some(effect.low());
+                    rep.med_threshold = Some(effect.med());
+                    rep.high_threshold = Some(effect.high());
+                    "account_thresholds_updated"
+                }
+                account::Kind::HomeDomainUpdated(ref effect) => {
+                    rep.home_domain = Some(effect.home_domain().clone());
+                    "account_home_domain_updated"
+                }
+                account::Kind::FlagsUpdated(ref effect) => {
+                    rep.auth_required_flag = Some(effect.flags().is_auth_required());
+                    rep.auth_revokable_flag = Some(effect.flags().is_auth_revocable());
+                    "account_flags_updated"
+                }
+            },
+            Kind::Signer(ref kind) => {
+                let (name, public_key, weight) = match *kind {
+                    signer::Kind::Created(ref effect) => {
+                        ("signer_created", effect.public_key(), effect.weight())
+                    }
+                    signer::Kind::Removed(ref effect) => {
+                        ("signer_removed", effect.public_key(), effect.weight())
+                    }
+                    signer::Kind::Updated(ref effect) => {
+                        ("signer_updated", effect.public_key(), effect.weight())
+                    }
+                };
+                rep.public_key = Some(public_key.clone());
+                rep.weight = Some(weight);
+                name
+            }
+            Kind::Trustline(ref kind) => match *kind {
+                trustline::Kind::Created(ref effect) => {
+                    rep.set_asset(effect.asset());
+                    rep.limit = Some(effect.limit());
+                    "trustline_created"
+                }
+                trustline::Kind::Removed(ref effect) => {
+                    rep.set_asset(effect.asset());
+                    rep.limit = Some(effect.limit());
+                    "trustline_removed"
+                }
+                trustline::Kind::Updated(ref effect) => {
+                    rep.set_asset(effect.asset());
+                    rep.limit = Some(effect.limit());
+                    "trustline_updated"
+                }
+                // Horizon names the issuer of an authorized asset `trustor`.
+                trustline::Kind::Authorized(ref effect) => {
+                    rep.asset_type = Some(effect.asset().asset_type().to_string());
+                    rep.asset_code = effect.asset().asset_code();
+                    rep.trustor = effect.asset().asset_issuer();
+                    "trustline_authorized"
+                }
+                trustline::Kind::Deauthorized(ref effect) => {
+                    rep.asset_type = Some(effect.asset().asset_type().to_string());
+                    rep.asset_code = effect.asset().asset_code();
+                    rep.trustor = effect.asset().asset_issuer();
+                    "trustline_deauthorized"
+                }
+            },
+            Kind::Trade(trade::Kind::Trade(ref effect)) => {
+                rep.offer_id = Some(effect.offer_id());
+                rep.seller = Some(effect.seller().clone());
+                rep.sold_amount = Some(effect.sold_amount());
+                rep.sold_asset_type = Some(effect.sold_asset().asset_type().to_string());
+                rep.sold_asset_code = effect.sold_asset().asset_code();
+                rep.sold_asset_issuer = effect.sold_asset().asset_issuer();
+                rep.bought_amount = Some(effect.bought_amount());
+                rep.bought_asset_type = Some(effect.bought_asset().asset_type().to_string());
+                rep.bought_asset_code = effect.bought_asset().asset_code();
+                rep.bought_asset_issuer = effect.bought_asset().asset_issuer();
+                "trade"
+            }
+            Kind::Data(ref kind) => match *kind {
+                data::Kind::Created(_) => "data_created",
+                data::Kind::Removed(_) => "data_removed",
+                data::Kind::Updated(_) => "data_updated",
+            },
+        };
+        rep.kind = kind.to_string();
+        rep.serialize(s)
+    }
+}
+
+impl Intermediate {
+    fn set_asset(&mut self, asset: &AssetIdentifier) {
+        self.asset_type = Some(asset.asset_type().to_string());
+        self.asset_code = asset.asset_code();
+        self.asset_issuer = asset.asset_issuer();
+    }
+}
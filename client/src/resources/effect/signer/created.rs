@@ -1,15 +1,16 @@
+use resources::AccountId;
 /// This effect can be the result of a set options operation and represents
 /// the fact that a new signer has been created for an account.
 #[derive(Debug, Deserialize, Clone)]
 pub struct Created {
-    account: String,
+    account: AccountId,
     public_key: String,
     weight: u8,
 }
 
 impl Created {
     /// Creates a new Signer
-    pub fn new(account: String, public_key: String, weight: u8) -> Created {
+    pub fn new(account: AccountId, public_key: String, weight: u8) -> Created {
         Created {
             account,
             public_key,
@@ -18,7 +19,7 @@ impl Created {
     }
 
     /// The public address of the account that received a new signer
-    pub fn account(&self) -> &String {
+    pub fn account(&self) -> &AccountId {
         &self.account
     }
 
@@ -1,15 +1,16 @@
+use resources::AccountId;
 /// This effect can be the result of a set options operation and represents
 /// the fact that a new signer has been removed from an account.
 #[derive(Debug, Deserialize, Clone)]
 pub struct Removed {
-    account: String,
+    account: AccountId,
     public_key: String,
     weight: u8,
 }
 
 impl Removed {
     /// Removes a Signer
-    pub fn new(account: String, public_key: String, weight: u8) -> Removed {
+    pub fn new(account: AccountId, public_key: String, weight: u8) -> Removed {
         Removed {
             account,
             public_key,
@@ -18,7 +19,7 @@ impl Removed {
     }
 
     /// The public address of the account that lost a new signer
-    pub fn account(&self) -> &String {
+    pub fn account(&self) -> &AccountId {
         &self.account
     }
 
@@ -1,15 +1,16 @@
+use resources::AccountId;
 /// This effect can be the result of a set options operation and represents
 /// the fact that a signer has been updated for an account.
 #[derive(Debug, Deserialize, Clone)]
 pub struct Updated {
-    account: String,
+    account: AccountId,
     public_key: String,
     weight: u8,
 }
 
 impl Updated {
     /// Updates a Signer
-    pub fn new(account: String, public_key: String, weight: u8) -> Updated {
+    pub fn new(account: AccountId, public_key: String, weight: u8) -> Updated {
         Updated {
             account,
             public_key,
@@ -18,7 +19,7 @@ impl Updated {
     }
 
     /// The public address of the account with an updated signer
-    pub fn account(&self) -> &String {
+    pub fn account(&self) -> &AccountId {
         &self.account
     }
 
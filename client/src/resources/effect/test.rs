@@ -1,3 +1,4 @@
+use chrono::prelude::*;
 use resources::{
     asset::Flags,
     effect::{
@@ -62,13 +63,39 @@ fn it_parses_account_credited_from_json() {
             effect_details.account(),
             "GDLGTRIBFH24364GPWPUS45GUFC2GU4ARPGWTXVCPLGTUHX3IOS3ON47"
         );
-        assert_eq!(effect_details.asset().code(), "XLM");
+        assert_eq!(effect_details.asset().code(), None);
         assert_eq!(effect_details.amount(), Amount::new(10_000_000_000));
     } else {
         panic!("Did not generate account credited kind");
     }
 }
 
+#[test]
+fn it_parses_the_metadata_common_to_every_effect() {
+    let effect: Effect = serde_json::from_str(&account_credited_json()).unwrap();
+    assert_eq!(effect.id(), "0000013563506724865-0000000001");
+    assert_eq!(effect.paging_token(), "13563506724865-1");
+    assert_eq!(effect.operation_id(), 13563506724865);
+    assert_eq!(
+        effect.created_at(),
+        Utc.ymd(2018, 8, 20).and_hms(20, 55, 58)
+    );
+    assert_eq!(
+        effect.account(),
+        "GDLGTRIBFH24364GPWPUS45GUFC2GU4ARPGWTXVCPLGTUHX3IOS3ON47"
+    );
+
+    let effect: Effect = serde_json::from_str(&trade_json()).unwrap();
+    assert_eq!(effect.operation_id(), 33788507721730);
+}
+
+#[test]
+fn it_errs_on_a_malformed_effect_id() {
+    let json = account_credited_json().replace("0000013563506724865-", "abc-");
+    let error = serde_json::from_str::<Effect>(&json).unwrap_err();
+    assert_eq!(error.to_string(), "Malformed effect id.");
+}
+
 fn account_debited_json() -> &'static str {
     include_str!("../../../fixtures/effects/account_debited.json")
 }
@@ -82,7 +109,7 @@ fn it_parses_account_debited_from_json() {
             effect_details.account(),
             "GBRPYHIL2CI3FNQ4BXLFMNDLFJUNPU2HY3ZMFSHONUCEOASW7QC7OX2H"
         );
-        assert_eq!(effect_details.asset().code(), "XLM");
+        assert_eq!(effect_details.asset().code(), None);
         assert_eq!(effect_details.amount(), Amount::new(300_000_000));
     } else {
         panic!("Did not generate account debited kind");
@@ -123,7 +150,7 @@ fn it_parses_account_home_domain_updated_from_json() {
     {
         assert_eq!(
             effect_details.account(),
-            "GA6U5X6WOPNKKDKQULBR7IDHDBAQKOWPHYEC7WSXHZBFEYFD3XVZAKO5"
+            "GA6U5X6WOPNKKDKQULBR7IDHDBAQKOWPHYEC7WSXHZBFEYFD3XVZAKOO"
         );
         assert_eq!(effect_details.home_domain(), "stellar.org");
     } else {
@@ -290,7 +317,7 @@ fn it_parses_trustline_created_from_json() {
             effect_details.account(),
             "GA6U5X6WOPNKKDKQULBR7IDHDBAQKOWPHYEC7WSXHZBFEYFD3XVZAKOO"
         );
-        assert_eq!(effect_details.asset().code(), "EUR");
+        assert_eq!(effect_details.asset().code(), Some("EUR"));
         assert_eq!(effect_details.limit(), Amount::new(10_000_000_000));
     } else {
         panic!("Did not generate trustline created kind");
@@ -308,9 +335,9 @@ fn it_parses_trustline_removed_from_json() {
     if let &EffectKind::Trustline(TrustlineKind::Removed(ref effect_details)) = effect.kind() {
         assert_eq!(
             effect_details.account(),
-            "QA6U5X6WOPNKKDKQULBR7IDHDBAQKOWPHYEC7WSXHZBFEYFD3XVZAKOO"
+            "GA6U5X6WOPNKKDKQULBR7IDHDBAQKOWPHYEC7WSXHZBFEYFD3XVZAKOO"
         );
-        assert_eq!(effect_details.asset().code(), "EUR");
+        assert_eq!(effect_details.asset().code(), Some("EUR"));
         assert_eq!(effect_details.limit(), Amount::new(0));
     } else {
         panic!("Did not generate trustline removed kind");
@@ -330,7 +357,7 @@ fn it_parses_trustline_updated_from_json() {
             effect_details.account(),
             "GA6U5X6WOPNKKDKQULBR7IDHDBAQKOWPHYEC7WSXHZBFEYFD3XVZAKOO"
         );
-        assert_eq!(effect_details.asset().code(), "TESTTEST");
+        assert_eq!(effect_details.asset().code(), Some("TESTTEST"));
         assert_eq!(effect_details.limit(), Amount::new(1_000_000_000));
     } else {
         panic!("Did not generate trustline updated kind");
@@ -350,7 +377,7 @@ fn it_parses_trustline_authorized_from_json() {
             effect_details.account(),
             "GA6U5X6WOPNKKDKQULBR7IDHDBAQKOWPHYEC7WSXHZBFEYFD3XVZAKOO"
         );
-        assert_eq!(effect_details.asset().code(), "TESTTEST");
+        assert_eq!(effect_details.asset().code(), Some("TESTTEST"));
     } else {
         panic!("Did not generate trustline authorized kind");
     }
@@ -369,7 +396,7 @@ fn it_parses_trustline_deauthorized_from_json() {
             effect_details.account(),
             "GA6U5X6WOPNKKDKQULBR7IDHDBAQKOWPHYEC7WSXHZBFEYFD3XVZAKOO"
         );
-        assert_eq!(effect_details.asset().code(), "EUR");
+        assert_eq!(effect_details.asset().code(), Some("EUR"));
     } else {
         panic!("Did not generate trustline authorized kind");
     }
@@ -393,9 +420,9 @@ fn it_parses_trade_from_json() {
             "GCVHDLN6EHZBYW2M3BQIY32C23E4GPIRZZDBNF2Q73DAZ5VJDRGSMYRB"
         );
         assert_eq!(effect_details.offer_id(), 1);
-        assert_eq!(effect_details.sold_asset().code(), "EUR");
+        assert_eq!(effect_details.sold_asset().code(), Some("EUR"));
         assert_eq!(effect_details.sold_amount(), Amount::new(10_000_000_000));
-        assert_eq!(effect_details.bought_asset().code(), "TESTTEST");
+        assert_eq!(effect_details.bought_asset().code(), Some("TESTTEST"));
         assert_eq!(effect_details.bought_amount(), Amount::new(600_000_000));
     } else {
         panic!("Did not generate trustline authorized kind");
@@ -410,7 +437,13 @@ mod errors_on_missing_fields_for_effect_types {
             #[test]
             fn $type_to_check() {
                 let json = format!(
-                    r#"{{"id":"1","paging_token":"7","type_i":{},"type":"{}"}}"#,
+                    r#"{{
+                        "id":"1",
+                        "paging_token":"7",
+                        "created_at":"2018-08-20T20:55:58Z",
+                        "type_i":{},
+                        "type":"{}"
+                    }}"#,
                     $type_i,
                     stringify!($type_to_check),
                 );
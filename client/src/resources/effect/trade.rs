@@ -1,5 +1,5 @@
 //! Contains effects that pertain to trades being executed.
-use resources::{Amount, AssetIdentifier};
+use resources::{AccountId, Amount, AssetIdentifier};
 
 /// Enum representing all the different kinds of effects that represent
 /// changes made to an account.
@@ -13,9 +13,9 @@ pub enum Kind {
 /// partially fulfilled, a trade happens.
 #[derive(Debug, Deserialize, Clone)]
 pub struct Trade {
-    account: String,
+    account: AccountId,
     offer_id: i64,
-    seller: String,
+    seller: AccountId,
     sold_amount: Amount,
     sold_asset: AssetIdentifier,
     bought_amount: Amount,
@@ -25,9 +25,9 @@ pub struct Trade {
 impl Trade {
     /// Creates a new Trade
     pub fn new(
-        account: String,
+        account: AccountId,
         offer_id: i64,
-        seller: String,
+        seller: AccountId,
         sold_amount: Amount,
         sold_asset: AssetIdentifier,
         bought_amount: Amount,
@@ -45,7 +45,7 @@ impl Trade {
     }
 
     /// The public address of the account that bought a trade
-    pub fn account(&self) -> &String {
+    pub fn account(&self) -> &AccountId {
         &self.account
     }
 
@@ -55,7 +55,7 @@ impl Trade {
     }
 
     /// The public address of the other party in the trade
-    pub fn seller(&self) -> &String {
+    pub fn seller(&self) -> &AccountId {
         &self.seller
     }
 
@@ -1,16 +1,16 @@
-use resources::{Amount, AssetIdentifier};
+use resources::{AccountId, Amount, AssetIdentifier};
 /// This effect can be the result of a change trust operation and represents
 /// the fact that a new trustline has been created between an asset and account
 #[derive(Debug, Deserialize, Clone)]
 pub struct Created {
-    account: String,
+    account: AccountId,
     limit: Amount,
     asset: AssetIdentifier,
 }
 
 impl Created {
     /// Creates a new Trustline Created effect
-    pub fn new(account: String, limit: Amount, asset: AssetIdentifier) -> Created {
+    pub fn new(account: AccountId, limit: Amount, asset: AssetIdentifier) -> Created {
         Created {
             account,
             limit,
@@ -19,7 +19,7 @@ impl Created {
     }
 
     /// The public address of the account that is creating a new trustline
-    pub fn account(&self) -> &String {
+    pub fn account(&self) -> &AccountId {
         &self.account
     }
 
@@ -1,20 +1,20 @@
-use resources::AssetIdentifier;
+use resources::{AccountId, AssetIdentifier};
 /// This effect can be the result of a allow trust operation and represents
 /// the fact that an asset issuer will no longer allow an account to hold its assets.
 #[derive(Debug, Deserialize, Clone)]
 pub struct Deauthorized {
-    account: String,
+    account: AccountId,
     asset: AssetIdentifier,
 }
 
 impl Deauthorized {
     /// Creates a new Trustline Deauthorized effect
-    pub fn new(account: String, asset: AssetIdentifier) -> Deauthorized {
+    pub fn new(account: AccountId, asset: AssetIdentifier) -> Deauthorized {
         Deauthorized { account, asset }
     }
 
     /// The public address of the account that can no longer hold the asset
-    pub fn account(&self) -> &String {
+    pub fn account(&self) -> &AccountId {
         &self.account
     }
 
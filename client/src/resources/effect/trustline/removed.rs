@@ -1,16 +1,16 @@
-use resources::{Amount, AssetIdentifier};
+use resources::{AccountId, Amount, AssetIdentifier};
 /// This effect can be the result of a change trust operation and represents
 /// the fact that a trustline has been removed between an asset and account
 #[derive(Debug, Deserialize, Clone)]
 pub struct Removed {
-    account: String,
+    account: AccountId,
     limit: Amount,
     asset: AssetIdentifier,
 }
 
 impl Removed {
     /// Creates a new Trustline Removed effect
-    pub fn new(account: String, limit: Amount, asset: AssetIdentifier) -> Removed {
+    pub fn new(account: AccountId, limit: Amount, asset: AssetIdentifier) -> Removed {
         Removed {
             account,
             limit,
@@ -19,7 +19,7 @@ impl Removed {
     }
 
     /// The public address of the account that had its trustline removed
-    pub fn account(&self) -> &String {
+    pub fn account(&self) -> &AccountId {
         &self.account
     }
 
@@ -1,16 +1,16 @@
-use resources::{Amount, AssetIdentifier};
+use resources::{AccountId, Amount, AssetIdentifier};
 /// This effect can be the result of a change trust operation and represents
 /// the fact that a trustline has been updated between an asset and account
 #[derive(Debug, Deserialize, Clone)]
 pub struct Updated {
-    account: String,
+    account: AccountId,
     limit: Amount,
     asset: AssetIdentifier,
 }
 
 impl Updated {
     /// Creates a new Trustline Updated effect
-    pub fn new(account: String, limit: Amount, asset: AssetIdentifier) -> Updated {
+    pub fn new(account: AccountId, limit: Amount, asset: AssetIdentifier) -> Updated {
         Updated {
             account,
             limit,
@@ -19,7 +19,7 @@ impl Updated {
     }
 
     /// The public address of the account that updated its trustline
-    pub fn account(&self) -> &String {
+    pub fn account(&self) -> &AccountId {
         &self.account
     }
 
@@ -0,0 +1,1674 @@
+use super::{AccountId, Amount, AssetIdentifier, Memo, PriceRatio};
+use base64;
+#[cfg(feature = "keys")]
+use error;
+#[cfg(feature = "keys")]
+use futures::Future;
+#[cfg(feature = "keys")]
+use keys::Signer;
+#[cfg(any(feature = "soroban", feature = "keys"))]
+use network::Network;
+#[cfg(any(feature = "soroban", feature = "keys"))]
+use openssl::sha::sha256;
+use std::fmt;
+use std::str::FromStr;
+use strkey;
+use xdr::{self, hex, Reader, Result, Writer, XdrError};
+
+const ENVELOPE_TYPE_TX_V0: i32 = 0;
+const ENVELOPE_TYPE_TX: i32 = 2;
+const ENVELOPE_TYPE_TX_FEE_BUMP: i32 = 5;
+const KEY_TYPE_ED25519: i32 = 0;
+const KEY_TYPE_MUXED_ED25519: i32 = 0x100;
+
+/// The fee, in stroops, a built transaction pays for each operation unless
+/// told otherwise. It is the least the network accepts.
+const BASE_FEE: u32 = 100;
+
+/// A signed transaction decoded from its XDR, such as the `envelope_xdr` of
+/// a transaction from horizon or one handed over to be signed. It doesn't
+/// need a client, so it can be used to check what a transaction does before
+/// signing or submitting it.
+///
+/// Fee bump envelopes are decoded too. The accessors describe the inner
+/// transaction and `fee_bump` describes the wrapper.
+///
+/// ## Example
+///
+/// ```
+/// use stellar_client::resources::{envelope::OperationBody, TransactionEnvelope};
+///
+/// let envelope = TransactionEnvelope::from_base64(
+///     "AAAAAH2Hmt1JWMfqAdUlDeyUtO9V8zPqJ0aLG8KrZyXv78QGAAAAZAAIgb4AAtRiAAAAAAAAAAEAAAAAAAAAAQAAAAA\
+///      AAAABAAAAAJZgy/0KAk+3JQwG8hPGBNTZVGew2Joi1TwkVBdwPn9QAAAAAAAAAAA7mUNgAAAAAAAAAAHv78QGAAAAQI\
+///      TCXzWfgHgAjF3djx1VK9JK08UypfpftzFoyNXv7A0Agau/ur/3/+ZZtQb8xSsao8yVAsTiV4ttiT/HqfvvlAk=",
+/// ).unwrap();
+/// assert_eq!(envelope.fee(), 100);
+/// assert_eq!(envelope.operations().len(), 1);
+/// match *envelope.operations()[0].body() {
+///     OperationBody::Payment { ref amount, .. } => assert_eq!(amount.to_string(), "99.9900000"),
+///     ref other => panic!("expected a payment, got {:?}", other),
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TransactionEnvelope {
+    source_account: MuxedAccount,
+    fee: u32,
+    sequence: i64,
+    preconditions: Preconditions,
+    memo: Memo,
+    operations: Vec<Operation>,
+    signatures: Vec<Signature>,
+    fee_bump: Option<FeeBump>,
+    transaction: Vec<u8>,
+}
+
+impl TransactionEnvelope {
+    /// Decodes a base64 encoded `TransactionEnvelope`.
+    pub fn from_base64(xdr: &str) -> Result<TransactionEnvelope> {
+        TransactionEnvelope::read(&mut Reader::from_base64(xdr)?)
+    }
+
+    /// Reads an envelope that is part of a larger structure, such as a
+    /// transaction set.
+    pub(crate) fn read(r: &mut Reader) -> Result<TransactionEnvelope> {
+        match r.i32()? {
+            ENVELOPE_TYPE_TX_V0 => {
+                let start = r.position();
+                let mut envelope = transaction_v0(r)?;
+                // A v0 transaction is signed as the v1 transaction it
+                // becomes once its source key is read as a muxed account.
+                envelope.transaction = vec![0, 0, 0, 0];
+                envelope.transaction.extend_from_slice(r.since(start));
+                envelope.signatures = r.vec(signature)?;
+                Ok(envelope)
+            }
+            ENVELOPE_TYPE_TX => transaction_v1_envelope(r),
+            ENVELOPE_TYPE_TX_FEE_BUMP => {
+                let start = r.position();
+                let fee_source = muxed_account(r)?;
+                let fee = r.i64()?;
+                let mut envelope = match r.i32()? {
+                    ENVELOPE_TYPE_TX => transaction_v1_envelope(r)?,
+                    other => return Err(XdrError::UnknownDiscriminant("InnerTx", other)),
+                };
+                r.extension_point()?;
+                let transaction = r.since(start).to_vec();
+                envelope.fee_bump = Some(FeeBump {
+                    fee_source,
+                    fee,
+                    signatures: r.vec(signature)?,
+                    transaction,
+                });
+                Ok(envelope)
+            }
+            other => Err(XdrError::UnknownDiscriminant("TransactionEnvelope", other)),
+        }
+    }
+
+    /// The account that the transaction's sequence number and, unless it is
+    /// fee bumped, fee come from.
+    pub fn source_account(&self) -> &MuxedAccount {
+        &self.source_account
+    }
+
+    /// The most the transaction will pay in fees, in stroops.
+    pub fn fee(&self) -> u32 {
+        self.fee
+    }
+
+    /// The sequence number the transaction uses up.
+    pub fn sequence(&self) -> i64 {
+        self.sequence
+    }
+
+    /// The conditions that must hold for the transaction to be valid.
+    pub fn preconditions(&self) -> &Preconditions {
+        &self.preconditions
+    }
+
+    /// The earliest and latest close times, in unix seconds, that the
+    /// transaction is valid between. A latest time of 0 means no limit.
+    pub fn time_bounds(&self) -> Option<(u64, u64)> {
+        self.preconditions.time_bounds
+    }
+
+    /// The memo attached to the transaction. Hash memos are base64 encoded,
+    /// as horizon has them.
+    pub fn memo(&self) -> &Memo {
+        &self.memo
+    }
+
+    /// The operations in the transaction, in the order they are applied.
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    /// The signatures on the transaction. For a fee bump these are the
+    /// signatures of the inner transaction.
+    pub fn signatures(&self) -> &[Signature] {
+        &self.signatures
+    }
+
+    /// The fee bump wrapped around the transaction, if it has one.
+    pub fn fee_bump(&self) -> Option<&FeeBump> {
+        self.fee_bump.as_ref()
+    }
+
+    /// The hash of the transaction on `network`, which is what its signers
+    /// sign. For a fee bump this is the hash of the inner transaction.
+    #[cfg(any(feature = "soroban", feature = "keys"))]
+    pub fn hash(&self, network: &Network) -> [u8; 32] {
+        signature_payload(network, ENVELOPE_TYPE_TX, &self.transaction)
+    }
+
+    /// The hex encoded hash that horizon lists the transaction under on
+    /// `network`. For a fee bump this is the hash of the fee bump rather
+    /// than of the inner transaction.
+    #[cfg(any(feature = "soroban", feature = "keys"))]
+    pub fn horizon_hash(&self, network: &Network) -> String {
+        match self.fee_bump {
+            Some(ref fee_bump) => hex(&fee_bump.hash(network)),
+            None => hex(&self.hash(network)),
+        }
+    }
+
+    /// Has `signer` sign the envelope for `network` and adds the signature
+    /// to it. A fee bump is signed as its fee source would sign it, so the
+    /// signature goes on the fee bump rather than the transaction inside it.
+    ///
+    /// Only available with the `keys` feature enabled.
+    #[cfg(feature = "keys")]
+    pub fn sign<S>(&mut self, network: &Network, signer: &S) -> error::Result<()>
+    where
+        S: Signer + ?Sized,
+    {
+        let hash = match self.fee_bump {
+            Some(ref fee_bump) => fee_bump.hash(network),
+            None => self.hash(network),
+        };
+        let signature = signer.sign_hash(&hash).wait()?;
+        let signature = Signature {
+            hint: signature.hint(),
+            signature: signature.signature().to_vec(),
+        };
+        match self.fee_bump {
+            Some(ref mut fee_bump) => fee_bump.signatures.push(signature),
+            None => self.signatures.push(signature),
+        }
+        Ok(())
+    }
+
+    /// Encodes the envelope as base64, ready to be submitted. An envelope
+    /// decoded from the old v0 format is encoded as the v1 envelope it
+    /// stands for, which has the same hash, so its signatures still hold.
+    pub fn to_base64(&self) -> String {
+        let mut w = Writer::new();
+        match self.fee_bump {
+            Some(ref fee_bump) => {
+                w.i32(ENVELOPE_TYPE_TX_FEE_BUMP);
+                w.raw(&fee_bump.transaction);
+                write_signatures(&mut w, &fee_bump.signatures);
+            }
+            None => {
+                w.i32(ENVELOPE_TYPE_TX);
+                w.raw(&self.transaction);
+                write_signatures(&mut w, &self.signatures);
+            }
+        }
+        base64::encode(&w.into_bytes())
+    }
+}
+
+/// Builds an unsigned transaction out of operations, ready to be signed
+/// with `TransactionEnvelope::sign` and submitted.
+///
+/// ## Example
+///
+/// ```
+/// use stellar_client::resources::envelope::{EnvelopeBuilder, Operation, OperationBody};
+/// use stellar_client::resources::{Amount, AssetIdentifier, Memo, TransactionEnvelope};
+///
+/// let source = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+/// let envelope = EnvelopeBuilder::new(source.parse().unwrap(), 42)
+///     .with_memo(Memo::Text("rent".to_string()))
+///     .with_operation(Operation::new(OperationBody::Payment {
+///         destination: "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4"
+///             .parse()
+///             .unwrap(),
+///         asset: AssetIdentifier::native(),
+///         amount: Amount::new(100_000_000),
+///     }))
+///     .build()
+///     .unwrap();
+/// assert_eq!(envelope.fee(), 100);
+///
+/// let decoded = TransactionEnvelope::from_base64(&envelope.to_base64()).unwrap();
+/// assert_eq!(decoded.sequence(), 42);
+/// assert_eq!(*decoded.memo(), Memo::Text("rent".to_string()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct EnvelopeBuilder {
+    source_account: MuxedAccount,
+    fee: Option<u32>,
+    sequence: i64,
+    time_bounds: Option<(u64, u64)>,
+    memo: Memo,
+    operations: Vec<Operation>,
+}
+
+impl EnvelopeBuilder {
+    /// A transaction from `source_account` that uses up `sequence`, which
+    /// has to be the one after the account's current sequence number. By
+    /// default it pays 100 stroops for each of its operations and has no
+    /// memo or time bounds.
+    pub fn new(source_account: MuxedAccount, sequence: i64) -> EnvelopeBuilder {
+        EnvelopeBuilder {
+            source_account,
+            fee: None,
+            sequence,
+            time_bounds: None,
+            memo: Memo::None,
+            operations: Vec::new(),
+        }
+    }
+
+    /// The most the whole transaction will pay in fees, in stroops.
+    pub fn with_fee(mut self, fee: u32) -> EnvelopeBuilder {
+        self.fee = Some(fee);
+        self
+    }
+
+    /// Attaches a memo. Hash memos are given base64 encoded, as horizon has
+    /// them.
+    pub fn with_memo(mut self, memo: Memo) -> EnvelopeBuilder {
+        self.memo = memo;
+        self
+    }
+
+    /// Only lets the transaction be applied between two close times, in unix
+    /// seconds. A latest time of 0 means no limit.
+    pub fn with_time_bounds(mut self, min_time: u64, max_time: u64) -> EnvelopeBuilder {
+        self.time_bounds = Some((min_time, max_time));
+        self
+    }
+
+    /// Adds an operation after the ones already in the transaction.
+    pub fn with_operation(mut self, operation: Operation) -> EnvelopeBuilder {
+        self.operations.push(operation);
+        self
+    }
+
+    /// Encodes the transaction. Fails when one of its operations holds
+    /// something that can't be encoded, such as an account id that isn't
+    /// valid.
+    pub fn build(self) -> Result<TransactionEnvelope> {
+        let fee = self
+            .fee
+            .unwrap_or_else(|| BASE_FEE.saturating_mul(self.operations.len() as u32));
+        let mut envelope = TransactionEnvelope {
+            source_account: self.source_account,
+            fee,
+            sequence: self.sequence,
+            preconditions: Preconditions {
+                time_bounds: self.time_bounds,
+                ..Preconditions::default()
+            },
+            memo: self.memo,
+            operations: self.operations,
+            signatures: Vec::new(),
+            fee_bump: None,
+            transaction: Vec::new(),
+        };
+        let mut w = Writer::new();
+        write_transaction(&mut w, &envelope)?;
+        envelope.transaction = w.into_bytes();
+        Ok(envelope)
+    }
+}
+
+/// The part of a fee bump envelope that pays the fee of the transaction
+/// inside it.
+#[derive(Debug, Clone)]
+pub struct FeeBump {
+    fee_source: MuxedAccount,
+    fee: i64,
+    signatures: Vec<Signature>,
+    transaction: Vec<u8>,
+}
+
+impl FeeBump {
+    /// The account paying the fee.
+    pub fn fee_source(&self) -> &MuxedAccount {
+        &self.fee_source
+    }
+
+    /// The most the fee source will pay for the whole transaction, in
+    /// stroops.
+    pub fn fee(&self) -> i64 {
+        self.fee
+    }
+
+    /// The signatures of the fee source.
+    pub fn signatures(&self) -> &[Signature] {
+        &self.signatures
+    }
+
+    /// The hash of the fee bump on `network`, which is the hash horizon
+    /// lists the transaction under.
+    #[cfg(any(feature = "soroban", feature = "keys"))]
+    pub fn hash(&self, network: &Network) -> [u8; 32] {
+        signature_payload(network, ENVELOPE_TYPE_TX_FEE_BUMP, &self.transaction)
+    }
+}
+
+/// An account, possibly along with an id that tells apart the many users
+/// sharing it. Displays as its `M...` address when it has an id and as the
+/// account id otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MuxedAccount {
+    key: [u8; 32],
+    id: Option<u64>,
+}
+
+impl MuxedAccount {
+    /// Creates a muxed account from an account and, optionally, the id of
+    /// one of its users.
+    pub fn new(account_id: &AccountId, id: Option<u64>) -> MuxedAccount {
+        MuxedAccount {
+            key: account_id.public_key(),
+            id,
+        }
+    }
+
+    /// The account itself.
+    pub fn account_id(&self) -> AccountId {
+        account_id(&self.key)
+    }
+
+    /// The multiplexing id, if there is one.
+    pub fn id(&self) -> Option<u64> {
+        self.id
+    }
+}
+
+impl fmt::Display for MuxedAccount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.id {
+            Some(id) => {
+                let mut data = self.key.to_vec();
+                data.extend_from_slice(&id.to_be_bytes());
+                f.write_str(&strkey::encode(strkey::MUXED_ACCOUNT, &data))
+            }
+            None => f.write_str(&strkey::encode(strkey::ACCOUNT, &self.key)),
+        }
+    }
+}
+
+/// Parses an `M...` address, or a `G...` account id as an account without
+/// an id.
+impl FromStr for MuxedAccount {
+    type Err = ParseMuxedAccountError;
+
+    fn from_str(s: &str) -> ::std::result::Result<MuxedAccount, ParseMuxedAccountError> {
+        if let Some((key, id)) = strkey::decode_muxed(s) {
+            return Ok(MuxedAccount { key, id: Some(id) });
+        }
+        match strkey::decode(strkey::ACCOUNT, s) {
+            Some(key) => Ok(MuxedAccount { key, id: None }),
+            None => Err(ParseMuxedAccountError(s.to_string())),
+        }
+    }
+}
+
+/// The error returned when a string is neither an `M...` address nor a
+/// `G...` account id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseMuxedAccountError(String);
+
+impl fmt::Display for ParseMuxedAccountError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} is not a valid muxed account", self.0)
+    }
+}
+
+impl ::std::error::Error for ParseMuxedAccountError {
+    fn description(&self) -> &str {
+        "invalid muxed account"
+    }
+}
+
+/// A signature on a transaction. It only names its signer by the last four
+/// bytes of their key, so it can be matched against a key but not resolved
+/// to one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    hint: [u8; 4],
+    signature: Vec<u8>,
+}
+
+impl Signature {
+    /// The last four bytes of the signer's key.
+    pub fn hint(&self) -> [u8; 4] {
+        self.hint
+    }
+
+    /// The signature itself.
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// Whether the signature's hint fits `account`. It could still be from
+    /// another key that happens to end the same way.
+    pub fn matches(&self, account: &AccountId) -> bool {
+        match strkey::decode(strkey::ACCOUNT, account.as_ref()) {
+            Some(key) => key[28..] == self.hint,
+            None => false,
+        }
+    }
+}
+
+/// The conditions a transaction sets on when it can be applied.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Preconditions {
+    time_bounds: Option<(u64, u64)>,
+    ledger_bounds: Option<(u32, u32)>,
+    min_sequence: Option<i64>,
+    min_sequence_age: u64,
+    min_sequence_ledger_gap: u32,
+    extra_signers: Vec<String>,
+}
+
+impl Preconditions {
+    /// The earliest and latest close times, in unix seconds.
+    pub fn time_bounds(&self) -> Option<(u64, u64)> {
+        self.time_bounds
+    }
+
+    /// The first and last ledgers the transaction can be applied in. A last
+    /// ledger of 0 means no limit.
+    pub fn ledger_bounds(&self) -> Option<(u32, u32)> {
+        self.ledger_bounds
+    }
+
+    /// The lowest sequence number the source account may have, where
+    /// otherwise it must be one below the transaction's.
+    pub fn min_sequence(&self) -> Option<i64> {
+        self.min_sequence
+    }
+
+    /// How many seconds the source account's sequence number must have gone
+    /// unchanged for.
+    pub fn min_sequence_age(&self) -> u64 {
+        self.min_sequence_age
+    }
+
+    /// How many ledgers the source account's sequence number must have gone
+    /// unchanged for.
+    pub fn min_sequence_ledger_gap(&self) -> u32 {
+        self.min_sequence_ledger_gap
+    }
+
+    /// Signers, as strkeys, whose signatures the transaction needs on top of
+    /// the usual ones.
+    pub fn extra_signers(&self) -> &[String] {
+        &self.extra_signers
+    }
+}
+
+/// An operation in a transaction envelope.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operation {
+    source_account: Option<MuxedAccount>,
+    body: OperationBody,
+}
+
+impl Operation {
+    /// An operation that acts for the transaction's source account.
+    pub fn new(body: OperationBody) -> Operation {
+        Operation {
+            source_account: None,
+            body,
+        }
+    }
+
+    /// Has the operation act for `source_account` instead of the
+    /// transaction's source account. That account has to sign the
+    /// transaction too.
+    pub fn with_source_account(mut self, source_account: MuxedAccount) -> Operation {
+        self.source_account = Some(source_account);
+        self
+    }
+
+    /// The account the operation acts for, when it isn't the transaction's
+    /// source account.
+    pub fn source_account(&self) -> Option<&MuxedAccount> {
+        self.source_account.as_ref()
+    }
+
+    /// What the operation does.
+    pub fn body(&self) -> &OperationBody {
+        &self.body
+    }
+}
+
+/// What an operation in a transaction envelope does, with its parameters.
+#[derive(Debug, Clone, PartialEq)]
+#[allow(missing_docs)]
+pub enum OperationBody {
+    CreateAccount {
+        destination: AccountId,
+        starting_balance: Amount,
+    },
+    Payment {
+        destination: MuxedAccount,
+        asset: AssetIdentifier,
+        amount: Amount,
+    },
+    PathPaymentStrictReceive {
+        send_asset: AssetIdentifier,
+        send_max: Amount,
+        destination: MuxedAccount,
+        dest_asset: AssetIdentifier,
+        dest_amount: Amount,
+        path: Vec<AssetIdentifier>,
+    },
+    ManageSellOffer {
+        selling: AssetIdentifier,
+        buying: AssetIdentifier,
+        amount: Amount,
+        price: PriceRatio,
+        offer_id: i64,
+    },
+    CreatePassiveSellOffer {
+        selling: AssetIdentifier,
+        buying: AssetIdentifier,
+        amount: Amount,
+        price: PriceRatio,
+    },
+    SetOptions {
+        inflation_dest: Option<AccountId>,
+        clear_flags: Option<u32>,
+        set_flags: Option<u32>,
+        master_weight: Option<u32>,
+        low_threshold: Option<u32>,
+        med_threshold: Option<u32>,
+        high_threshold: Option<u32>,
+        home_domain: Option<String>,
+        /// The signer as a strkey, with its weight.
+        signer: Option<(String, u32)>,
+    },
+    /// A change to a trust line. The line is `None` for liquidity pool
+    /// shares.
+    ChangeTrust {
+        line: Option<AssetIdentifier>,
+        limit: Amount,
+    },
+    AllowTrust {
+        trustor: AccountId,
+        asset_code: String,
+        authorize: u32,
+    },
+    AccountMerge {
+        destination: MuxedAccount,
+    },
+    Inflation,
+    ManageData {
+        name: String,
+        /// The new value, or `None` to delete the entry.
+        value: Option<Vec<u8>>,
+    },
+    BumpSequence {
+        bump_to: i64,
+    },
+    ManageBuyOffer {
+        selling: AssetIdentifier,
+        buying: AssetIdentifier,
+        buy_amount: Amount,
+        price: PriceRatio,
+        offer_id: i64,
+    },
+    PathPaymentStrictSend {
+        send_asset: AssetIdentifier,
+        send_amount: Amount,
+        destination: MuxedAccount,
+        dest_asset: AssetIdentifier,
+        dest_min: Amount,
+        path: Vec<AssetIdentifier>,
+    },
+    ClaimClaimableBalance {
+        /// The balance id in hex, as horizon has it.
+        balance_id: String,
+    },
+    BeginSponsoringFutureReserves {
+        sponsored_id: AccountId,
+    },
+    EndSponsoringFutureReserves,
+    Clawback {
+        asset: AssetIdentifier,
+        from: MuxedAccount,
+        amount: Amount,
+    },
+    ClawbackClaimableBalance {
+        /// The balance id in hex, as horizon has it.
+        balance_id: String,
+    },
+    SetTrustLineFlags {
+        trustor: AccountId,
+        asset: AssetIdentifier,
+        clear_flags: u32,
+        set_flags: u32,
+    },
+    LiquidityPoolDeposit {
+        /// The pool id in hex.
+        liquidity_pool_id: String,
+        max_amount_a: Amount,
+        max_amount_b: Amount,
+        min_price: PriceRatio,
+        max_price: PriceRatio,
+    },
+    LiquidityPoolWithdraw {
+        /// The pool id in hex.
+        liquidity_pool_id: String,
+        amount: Amount,
+        min_amount_a: Amount,
+        min_amount_b: Amount,
+    },
+}
+
+#[cfg(any(feature = "soroban", feature = "keys"))]
+fn signature_payload(network: &Network, envelope_type: i32, transaction: &[u8]) -> [u8; 32] {
+    let mut payload = network.id().to_vec();
+    payload.extend_from_slice(&(envelope_type as u32).to_be_bytes());
+    payload.extend_from_slice(transaction);
+    sha256(&payload)
+}
+
+fn transaction_v0(r: &mut Reader) -> Result<TransactionEnvelope> {
+    let mut key = [0; 32];
+    key.copy_from_slice(r.take(32)?);
+    let fee = r.u32()?;
+    let sequence = r.i64()?;
+    let time_bounds = r.optional(time_bounds)?;
+    let memo = memo(r)?;
+    let operations = r.vec(operation)?;
+    r.extension_point()?;
+    Ok(TransactionEnvelope {
+        source_account: MuxedAccount { key, id: None },
+        fee,
+        sequence,
+        preconditions: Preconditions {
+            time_bounds,
+            ..Preconditions::default()
+        },
+        memo,
+        operations,
+        signatures: Vec::new(),
+        fee_bump: None,
+        transaction: Vec::new(),
+    })
+}
+
+fn transaction_v1_envelope(r: &mut Reader) -> Result<TransactionEnvelope> {
+    let start = r.position();
+    let source_account = muxed_account(r)?;
+    let fee = r.u32()?;
+    let sequence = r.i64()?;
+    let preconditions = preconditions(r)?;
+    let memo = memo(r)?;
+    let operations = r.vec(operation)?;
+    match r.i32()? {
+        0 => {}
+        1 => return Err(XdrError::Unsupported("SorobanTransactionData")),
+        other => return Err(XdrError::UnknownDiscriminant("Transaction", other)),
+    }
+    let transaction = r.since(start).to_vec();
+    Ok(TransactionEnvelope {
+        source_account,
+        fee,
+        sequence,
+        preconditions,
+        memo,
+        operations,
+        signatures: r.vec(signature)?,
+        fee_bump: None,
+        transaction,
+    })
+}
+
+fn signature(r: &mut Reader) -> Result<Signature> {
+    let mut hint = [0; 4];
+    hint.copy_from_slice(r.take(4)?);
+    Ok(Signature {
+        hint,
+        signature: r.opaque()?,
+    })
+}
+
+fn muxed_account(r: &mut Reader) -> Result<MuxedAccount> {
+    let id = match r.i32()? {
+        KEY_TYPE_ED25519 => None,
+        KEY_TYPE_MUXED_ED25519 => Some(r.u64()?),
+        other => return Err(XdrError::UnknownDiscriminant("MuxedAccount", other)),
+    };
+    let mut key = [0; 32];
+    key.copy_from_slice(r.take(32)?);
+    Ok(MuxedAccount { key, id })
+}
+
+fn account_id(key: &[u8; 32]) -> AccountId {
+    AccountId::from(strkey::encode(strkey::ACCOUNT, key).as_str())
+}
+
+fn time_bounds(r: &mut Reader) -> Result<(u64, u64)> {
+    Ok((r.u64()?, r.u64()?))
+}
+
+fn preconditions(r: &mut Reader) -> Result<Preconditions> {
+    match r.i32()? {
+        0 => Ok(Preconditions::default()),
+        1 => Ok(Preconditions {
+            time_bounds: Some(time_bounds(r)?),
+            ..Preconditions::default()
+        }),
+        2 => Ok(Preconditions {
+            time_bounds: r.optional(time_bounds)?,
+            ledger_bounds: r.optional(|r| Ok((r.u32()?, r.u32()?)))?,
+            min_sequence: r.optional(Reader::i64)?,
+            min_sequence_age: r.u64()?,
+            min_sequence_ledger_gap: r.u32()?,
+            extra_signers: r.vec(signer_key)?,
+        }),
+        other => Err(XdrError::UnknownDiscriminant("Preconditions", other)),
+    }
+}
+
+fn signer_key(r: &mut Reader) -> Result<String> {
+    let version = match r.i32()? {
+        0 => strkey::ACCOUNT,
+        1 => strkey::PRE_AUTH_TX,
+        2 => strkey::SHA256_HASH,
+        3 => {
+            let mut data = r.take(32)?.to_vec();
+            let payload = r.opaque()?;
+            data.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+            data.extend_from_slice(&payload);
+            data.resize(data.len() + (4 - payload.len() % 4) % 4, 0);
+            return Ok(strkey::encode(strkey::SIGNED_PAYLOAD, &data));
+        }
+        other => return Err(XdrError::UnknownDiscriminant("SignerKey", other)),
+    };
+    Ok(strkey::encode(version, r.take(32)?))
+}
+
+fn memo(r: &mut Reader) -> Result<Memo> {
+    match r.i32()? {
+        0 => Ok(Memo::None),
+        1 => Ok(Memo::Text(r.string_lossy()?)),
+        2 => Ok(Memo::Id(r.i64()?)),
+        3 => Ok(Memo::Hash(base64::encode(r.take(32)?))),
+        4 => Ok(Memo::Return(base64::encode(r.take(32)?))),
+        other => Err(XdrError::UnknownDiscriminant("Memo", other)),
+    }
+}
+
+fn amount(r: &mut Reader) -> Result<Amount> {
+    Ok(Amount::new(r.i64()?))
+}
+
+fn price(r: &mut Reader) -> Result<PriceRatio> {
+    Ok(PriceRatio::new(r.i32()? as u64, r.i32()? as u64))
+}
+
+// Claimable balance ids are shown with their type in front, as horizon
+// does.
+fn claimable_balance_id(r: &mut Reader) -> Result<String> {
+    let start = r.position();
+    match r.i32()? {
+        0 => {
+            r.take(32)?;
+            Ok(hex(r.since(start)))
+        }
+        other => Err(XdrError::UnknownDiscriminant("ClaimableBalanceID", other)),
+    }
+}
+
+fn operation(r: &mut Reader) -> Result<Operation> {
+    let source_account = r.optional(muxed_account)?;
+    let body = match r.i32()? {
+        0 => OperationBody::CreateAccount {
+            destination: account_id(&r.account_id()?),
+            starting_balance: amount(r)?,
+        },
+        1 => OperationBody::Payment {
+            destination: muxed_account(r)?,
+            asset: r.asset()?,
+            amount: amount(r)?,
+        },
+        2 => OperationBody::PathPaymentStrictReceive {
+            send_asset: r.asset()?,
+            send_max: amount(r)?,
+            destination: muxed_account(r)?,
+            dest_asset: r.asset()?,
+            dest_amount: amount(r)?,
+            path: r.vec(|r| r.asset())?,
+        },
+        3 => OperationBody::ManageSellOffer {
+            selling: r.asset()?,
+            buying: r.asset()?,
+            amount: amount(r)?,
+            price: price(r)?,
+            offer_id: r.i64()?,
+        },
+        4 => OperationBody::CreatePassiveSellOffer {
+            selling: r.asset()?,
+            buying: r.asset()?,
+            amount: amount(r)?,
+            price: price(r)?,
+        },
+        5 => OperationBody::SetOptions {
+            inflation_dest: r.optional(|r| Ok(account_id(&r.account_id()?)))?,
+            clear_flags: r.optional(Reader::u32)?,
+            set_flags: r.optional(Reader::u32)?,
+            master_weight: r.optional(Reader::u32)?,
+            low_threshold: r.optional(Reader::u32)?,
+            med_threshold: r.optional(Reader::u32)?,
+            high_threshold: r.optional(Reader::u32)?,
+            home_domain: r.optional(Reader::string)?,
+            signer: r.optional(|r| Ok((signer_key(r)?, r.u32()?)))?,
+        },
+        6 => OperationBody::ChangeTrust {
+            line: change_trust_asset(r)?,
+            limit: amount(r)?,
+        },
+        7 => OperationBody::AllowTrust {
+            trustor: account_id(&r.account_id()?),
+            asset_code: asset_code(r)?,
+            authorize: r.u32()?,
+        },
+        8 => OperationBody::AccountMerge {
+            destination: muxed_account(r)?,
+        },
+        9 => OperationBody::Inflation,
+        10 => OperationBody::ManageData {
+            name: r.string_lossy()?,
+            value: r.optional(Reader::opaque)?,
+        },
+        11 => OperationBody::BumpSequence { bump_to: r.i64()? },
+        12 => OperationBody::ManageBuyOffer {
+            selling: r.asset()?,
+            buying: r.asset()?,
+            buy_amount: amount(r)?,
+            price: price(r)?,
+            offer_id: r.i64()?,
+        },
+        13 => OperationBody::PathPaymentStrictSend {
+            send_asset: r.asset()?,
+            send_amount: amount(r)?,
+            destination: muxed_account(r)?,
+            dest_asset: r.asset()?,
+            dest_min: amount(r)?,
+            path: r.vec(|r| r.asset())?,
+        },
+        14 => return Err(XdrError::Unsupported("CreateClaimableBalanceOp")),
+        15 => OperationBody::ClaimClaimableBalance {
+            balance_id: claimable_balance_id(r)?,
+        },
+        16 => OperationBody::BeginSponsoringFutureReserves {
+            sponsored_id: account_id(&r.account_id()?),
+        },
+        17 => OperationBody::EndSponsoringFutureReserves,
+        18 => return Err(XdrError::Unsupported("RevokeSponsorshipOp")),
+        19 => OperationBody::Clawback {
+            asset: r.asset()?,
+            from: muxed_account(r)?,
+            amount: amount(r)?,
+        },
+        20 => OperationBody::ClawbackClaimableBalance {
+            balance_id: claimable_balance_id(r)?,
+        },
+        21 => OperationBody::SetTrustLineFlags {
+            trustor: account_id(&r.account_id()?),
+            asset: r.asset()?,
+            clear_flags: r.u32()?,
+            set_flags: r.u32()?,
+        },
+        22 => OperationBody::LiquidityPoolDeposit {
+            liquidity_pool_id: hex(r.take(32)?),
+            max_amount_a: amount(r)?,
+            max_amount_b: amount(r)?,
+            min_price: price(r)?,
+            max_price: price(r)?,
+        },
+        23 => OperationBody::LiquidityPoolWithdraw {
+            liquidity_pool_id: hex(r.take(32)?),
+            amount: amount(r)?,
+            min_amount_a: amount(r)?,
+            min_amount_b: amount(r)?,
+        },
+        24..=26 => return Err(XdrError::Unsupported("soroban operations")),
+        other => return Err(XdrError::UnknownDiscriminant("OperationType", other)),
+    };
+    Ok(Operation {
+        source_account,
+        body,
+    })
+}
+
+/// Reads a `ChangeTrustAsset`. Liquidity pool shares are not an asset of
+/// their own, so they come back as `None`.
+fn change_trust_asset(r: &mut Reader) -> Result<Option<AssetIdentifier>> {
+    match r.i32()? {
+        0 => Ok(Some(AssetIdentifier::native())),
+        1 => r.issued_asset(4).map(Some),
+        2 => r.issued_asset(12).map(Some),
+        // The constant product parameters: both assets and the fee
+        3 => match r.i32()? {
+            0 => {
+                r.asset()?;
+                r.asset()?;
+                r.i32()?;
+                Ok(None)
+            }
+            other => Err(XdrError::UnknownDiscriminant(
+                "LiquidityPoolParameters",
+                other,
+            )),
+        },
+        other => Err(XdrError::UnknownDiscriminant("ChangeTrustAsset", other)),
+    }
+}
+
+fn asset_code(r: &mut Reader) -> Result<String> {
+    let len = match r.i32()? {
+        1 => 4,
+        2 => 12,
+        other => return Err(XdrError::UnknownDiscriminant("AssetCode", other)),
+    };
+    xdr::asset_code(r.take(len)?)
+}
+
+fn write_transaction(w: &mut Writer, envelope: &TransactionEnvelope) -> Result<()> {
+    write_muxed_account(w, &envelope.source_account);
+    w.u32(envelope.fee);
+    w.i64(envelope.sequence);
+    write_preconditions(w, &envelope.preconditions)?;
+    write_memo(w, &envelope.memo)?;
+    w.vec(&envelope.operations, write_operation)?;
+    // No soroban data
+    w.i32(0);
+    Ok(())
+}
+
+fn write_signatures(w: &mut Writer, signatures: &[Signature]) {
+    w.u32(signatures.len() as u32);
+    for signature in signatures {
+        w.raw(&signature.hint);
+        w.opaque(&signature.signature);
+    }
+}
+
+fn write_muxed_account(w: &mut Writer, account: &MuxedAccount) {
+    match account.id {
+        Some(id) => {
+            w.i32(KEY_TYPE_MUXED_ED25519);
+            w.u64(id);
+        }
+        None => w.i32(KEY_TYPE_ED25519),
+    }
+    w.raw(&account.key);
+}
+
+fn write_time_bounds(&(min_time, max_time): &(u64, u64), w: &mut Writer) -> Result<()> {
+    w.u64(min_time);
+    w.u64(max_time);
+    Ok(())
+}
+
+fn write_preconditions(w: &mut Writer, preconditions: &Preconditions) -> Result<()> {
+    let only_time_bounds = Preconditions {
+        time_bounds: preconditions.time_bounds,
+        ..Preconditions::default()
+    };
+    if *preconditions == only_time_bounds {
+        return match preconditions.time_bounds {
+            Some(ref time_bounds) => {
+                w.i32(1);
+                write_time_bounds(time_bounds, w)
+            }
+            None => {
+                w.i32(0);
+                Ok(())
+            }
+        };
+    }
+    w.i32(2);
+    w.optional(preconditions.time_bounds.as_ref(), write_time_bounds)?;
+    w.optional(preconditions.ledger_bounds.as_ref(), |&(min, max), w| {
+        w.u32(min);
+        w.u32(max);
+        Ok(())
+    })?;
+    w.optional(preconditions.min_sequence.as_ref(), |sequence, w| {
+        w.i64(*sequence);
+        Ok(())
+    })?;
+    w.u64(preconditions.min_sequence_age);
+    w.u32(preconditions.min_sequence_ledger_gap);
+    w.vec(&preconditions.extra_signers, |signer, w| {
+        write_signer_key(w, signer)
+    })
+}
+
+fn write_signer_key(w: &mut Writer, signer: &str) -> Result<()> {
+    let key_types = [
+        (0, strkey::ACCOUNT),
+        (1, strkey::PRE_AUTH_TX),
+        (2, strkey::SHA256_HASH),
+    ];
+    for &(key_type, version) in &key_types {
+        if let Some(key) = strkey::decode(version, signer) {
+            w.i32(key_type);
+            w.raw(&key);
+            return Ok(());
+        }
+    }
+    if signer.starts_with('P') {
+        return Err(XdrError::Unsupported("signed payload signers"));
+    }
+    Err(XdrError::InvalidAddress)
+}
+
+fn write_memo(w: &mut Writer, memo: &Memo) -> Result<()> {
+    match *memo {
+        Memo::None => w.i32(0),
+        Memo::Text(ref text) => {
+            w.i32(1);
+            w.opaque(text.as_bytes());
+        }
+        Memo::Id(id) => {
+            w.i32(2);
+            w.i64(id);
+        }
+        Memo::Hash(ref hash) => {
+            w.i32(3);
+            write_memo_hash(w, hash)?;
+        }
+        Memo::Return(ref hash) => {
+            w.i32(4);
+            write_memo_hash(w, hash)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_memo_hash(w: &mut Writer, hash: &str) -> Result<()> {
+    match base64::decode(hash) {
+        Ok(ref bytes) if bytes.len() == 32 => {
+            w.raw(bytes);
+            Ok(())
+        }
+        _ => Err(XdrError::InvalidHash),
+    }
+}
+
+fn write_amount(w: &mut Writer, amount: &Amount) {
+    w.i64(amount.to_stroops());
+}
+
+/// Writes a price as the exact ratio it is, reduced to its lowest terms so
+/// that as many prices as possible fit in 32 bits.
+fn write_price(w: &mut Writer, price: &PriceRatio) -> Result<()> {
+    let (mut a, mut b) = (price.numerator(), price.denominator());
+    while b != 0 {
+        let rest = a % b;
+        a = b;
+        b = rest;
+    }
+    let divisor = a.max(1);
+    let numerator = price.numerator() / divisor;
+    let denominator = price.denominator() / divisor;
+    if numerator > i32::MAX as u64 || denominator > i32::MAX as u64 {
+        return Err(XdrError::InvalidPrice);
+    }
+    w.i32(numerator as i32);
+    w.i32(denominator as i32);
+    Ok(())
+}
+
+fn write_account_id(w: &mut Writer, account: &AccountId) -> Result<()> {
+    w.account_id(account.as_str())
+}
+
+fn write_claimable_balance_id(w: &mut Writer, balance_id: &str) -> Result<()> {
+    if !balance_id.starts_with("00000000") {
+        return Err(XdrError::InvalidHash);
+    }
+    w.i32(0);
+    w.hash(&balance_id[8..])
+}
+
+fn write_operation(operation: &Operation, w: &mut Writer) -> Result<()> {
+    w.optional(operation.source_account.as_ref(), |account, w| {
+        write_muxed_account(w, account);
+        Ok(())
+    })?;
+    match operation.body {
+        OperationBody::CreateAccount {
+            ref destination,
+            ref starting_balance,
+        } => {
+            w.i32(0);
+            write_account_id(w, destination)?;
+            write_amount(w, starting_balance);
+        }
+        OperationBody::Payment {
+            ref destination,
+            ref asset,
+            ref amount,
+        } => {
+            w.i32(1);
+            write_muxed_account(w, destination);
+            w.asset(asset)?;
+            write_amount(w, amount);
+        }
+        OperationBody::PathPaymentStrictReceive {
+            ref send_asset,
+            ref send_max,
+            ref destination,
+            ref dest_asset,
+            ref dest_amount,
+            ref path,
+        } => {
+            w.i32(2);
+            w.asset(send_asset)?;
+            write_amount(w, send_max);
+            write_muxed_account(w, destination);
+            w.asset(dest_asset)?;
+            write_amount(w, dest_amount);
+            w.vec(path, |asset, w| w.asset(asset))?;
+        }
+        OperationBody::ManageSellOffer {
+            ref selling,
+            ref buying,
+            ref amount,
+            ref price,
+            offer_id,
+        } => {
+            w.i32(3);
+            w.asset(selling)?;
+            w.asset(buying)?;
+            write_amount(w, amount);
+            write_price(w, price)?;
+            w.i64(offer_id);
+        }
+        OperationBody::CreatePassiveSellOffer {
+            ref selling,
+            ref buying,
+            ref amount,
+            ref price,
+        } => {
+            w.i32(4);
+            w.asset(selling)?;
+            w.asset(buying)?;
+            write_amount(w, amount);
+            write_price(w, price)?;
+        }
+        OperationBody::SetOptions {
+            ref inflation_dest,
+            ref clear_flags,
+            ref set_flags,
+            ref master_weight,
+            ref low_threshold,
+            ref med_threshold,
+            ref high_threshold,
+            ref home_domain,
+            ref signer,
+        } => {
+            w.i32(5);
+            w.optional(inflation_dest.as_ref(), |account, w| {
+                write_account_id(w, account)
+            })?;
+            for value in &[
+                clear_flags,
+                set_flags,
+                master_weight,
+                low_threshold,
+                med_threshold,
+                high_threshold,
+            ] {
+                w.optional(value.as_ref(), |value, w| {
+                    w.u32(*value);
+                    Ok(())
+                })?;
+            }
+            w.optional(home_domain.as_ref(), |domain, w| {
+                w.opaque(domain.as_bytes());
+                Ok(())
+            })?;
+            w.optional(signer.as_ref(), |&(ref key, weight), w| {
+                write_signer_key(w, key)?;
+                w.u32(weight);
+                Ok(())
+            })?;
+        }
+        OperationBody::ChangeTrust {
+            ref line,
+            ref limit,
+        } => {
+            w.i32(6);
+            match *line {
+                Some(ref asset) => w.asset(asset)?,
+                None => return Err(XdrError::Unsupported("liquidity pool trust lines")),
+            }
+            write_amount(w, limit);
+        }
+        OperationBody::AllowTrust {
+            ref trustor,
+            ref asset_code,
+            authorize,
+        } => {
+            w.i32(7);
+            write_account_id(w, trustor)?;
+            if asset_code.len() <= 4 {
+                w.i32(1);
+                w.asset_code(asset_code, 4)?;
+            } else {
+                w.i32(2);
+                w.asset_code(asset_code, 12)?;
+            }
+            w.u32(authorize);
+        }
+        OperationBody::AccountMerge { ref destination } => {
+            w.i32(8);
+            write_muxed_account(w, destination);
+        }
+        OperationBody::Inflation => w.i32(9),
+        OperationBody::ManageData {
+            ref name,
+            ref value,
+        } => {
+            w.i32(10);
+            w.opaque(name.as_bytes());
+            w.optional(value.as_ref(), |value, w| {
+                w.opaque(value);
+                Ok(())
+            })?;
+        }
+        OperationBody::BumpSequence { bump_to } => {
+            w.i32(11);
+            w.i64(bump_to);
+        }
+        OperationBody::ManageBuyOffer {
+            ref selling,
+            ref buying,
+            ref buy_amount,
+            ref price,
+            offer_id,
+        } => {
+            w.i32(12);
+            w.asset(selling)?;
+            w.asset(buying)?;
+            write_amount(w, buy_amount);
+            write_price(w, price)?;
+            w.i64(offer_id);
+        }
+        OperationBody::PathPaymentStrictSend {
+            ref send_asset,
+            ref send_amount,
+            ref destination,
+            ref dest_asset,
+            ref dest_min,
+            ref path,
+        } => {
+            w.i32(13);
+            w.asset(send_asset)?;
+            write_amount(w, send_amount);
+            write_muxed_account(w, destination);
+            w.asset(dest_asset)?;
+            write_amount(w, dest_min);
+            w.vec(path, |asset, w| w.asset(asset))?;
+        }
+        OperationBody::ClaimClaimableBalance { ref balance_id } => {
+            w.i32(15);
+            write_claimable_balance_id(w, balance_id)?;
+        }
+        OperationBody::BeginSponsoringFutureReserves { ref sponsored_id } => {
+            w.i32(16);
+            write_account_id(w, sponsored_id)?;
+        }
+        OperationBody::EndSponsoringFutureReserves => w.i32(17),
+        OperationBody::Clawback {
+            ref asset,
+            ref from,
+            ref amount,
+        } => {
+            w.i32(19);
+            w.asset(asset)?;
+            write_muxed_account(w, from);
+            write_amount(w, amount);
+        }
+        OperationBody::ClawbackClaimableBalance { ref balance_id } => {
+            w.i32(20);
+            write_claimable_balance_id(w, balance_id)?;
+        }
+        OperationBody::SetTrustLineFlags {
+            ref trustor,
+            ref asset,
+            clear_flags,
+            set_flags,
+        } => {
+            w.i32(21);
+            write_account_id(w, trustor)?;
+            w.asset(asset)?;
+            w.u32(clear_flags);
+            w.u32(set_flags);
+        }
+        OperationBody::LiquidityPoolDeposit {
+            ref liquidity_pool_id,
+            ref max_amount_a,
+            ref max_amount_b,
+            ref min_price,
+            ref max_price,
+        } => {
+            w.i32(22);
+            w.hash(liquidity_pool_id)?;
+            write_amount(w, max_amount_a);
+            write_amount(w, max_amount_b);
+            write_price(w, min_price)?;
+            write_price(w, max_price)?;
+        }
+        OperationBody::LiquidityPoolWithdraw {
+            ref liquidity_pool_id,
+            ref amount,
+            ref min_amount_a,
+            ref min_amount_b,
+        } => {
+            w.i32(23);
+            w.hash(liquidity_pool_id)?;
+            write_amount(w, amount);
+            write_amount(w, min_amount_a);
+            write_amount(w, min_amount_b);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod envelope_tests {
+    use super::*;
+
+    // A payment of 99.99 lumens from the public network, with an empty text memo.
+    const PAYMENT: &str = "AAAAAH2Hmt1JWMfqAdUlDeyUtO9V8zPqJ0aLG8KrZyXv78QGAAAAZAAIgb4AAtRiAAAAAAAAAAEAAAAAAAAAAQAAAAAAAAABAAAAAJZgy/0KAk+3JQwG8hPGBNTZVGew2Joi1TwkVBdwPn9QAAAAAAAAAAA7mUNgAAAAAAAAAAHv78QGAAAAQITCXzWfgHgAjF3djx1VK9JK08UypfpftzFoyNXv7A0Agau/ur/3/+ZZtQb8xSsao8yVAsTiV4ttiT/HqfvvlAk=";
+    const SOURCE: &str = "GB6YPGW5JFMMP2QB2USQ33EUWTXVL4ZT5ITUNCY3YKVWOJPP57CANOF3";
+
+    fn int(bytes: &mut Vec<u8>, value: i32) {
+        bytes.extend_from_slice(&(value as u32).to_be_bytes());
+    }
+
+    // The payment as a v1 envelope with a muxed source and text memo,
+    // optionally wrapped in a fee bump.
+    fn v1_payment(fee_bump: bool) -> String {
+        let v0 = base64::decode(PAYMENT).unwrap();
+        let key = &v0[4..36];
+        let mut tx = Vec::new();
+        int(&mut tx, KEY_TYPE_MUXED_ED25519);
+        tx.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 7]);
+        tx.extend_from_slice(key);
+        // The fee and sequence, then time bounds
+        tx.extend_from_slice(&v0[36..48]);
+        int(&mut tx, 1);
+        tx.extend_from_slice(&[0; 8]);
+        tx.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 60]);
+        int(&mut tx, 1);
+        int(&mut tx, 2);
+        tx.extend_from_slice(b"hi\0\0");
+        // The operations, ext and signatures
+        tx.extend_from_slice(&v0[60..]);
+
+        let mut envelope = Vec::new();
+        if fee_bump {
+            int(&mut envelope, ENVELOPE_TYPE_TX_FEE_BUMP);
+            int(&mut envelope, KEY_TYPE_ED25519);
+            envelope.extend_from_slice(key);
+            envelope.extend_from_slice(&[0, 0, 0, 0, 0, 0, 1, 0]);
+        }
+        int(&mut envelope, ENVELOPE_TYPE_TX);
+        envelope.extend_from_slice(&tx);
+        if fee_bump {
+            int(&mut envelope, 0);
+            int(&mut envelope, 0);
+        }
+        base64::encode(&envelope)
+    }
+
+    #[test]
+    fn it_decodes_a_v0_envelope() {
+        let envelope = TransactionEnvelope::from_base64(PAYMENT).unwrap();
+        assert_eq!(envelope.source_account().to_string(), SOURCE);
+        assert_eq!(envelope.source_account().id(), None);
+        assert_eq!(envelope.fee(), 100);
+        assert_eq!(envelope.sequence(), 2394452857640034);
+        assert_eq!(envelope.time_bounds(), None);
+        assert_eq!(*envelope.memo(), Memo::Text(String::new()));
+        assert!(envelope.fee_bump().is_none());
+
+        let operation = &envelope.operations()[0];
+        assert!(operation.source_account().is_none());
+        match *operation.body() {
+            OperationBody::Payment {
+                ref destination,
+                ref asset,
+                ref amount,
+            } => {
+                assert_eq!(
+                    destination.to_string(),
+                    "GCLGBS75BIBE7NZFBQDPEE6GATKNSVDHWDMJUIWVHQSFIF3QHZ7VBBYH"
+                );
+                assert_eq!(*asset, AssetIdentifier::native());
+                assert_eq!(*amount, Amount::new(999_900_000));
+            }
+            ref other => panic!("expected a payment, got {:?}", other),
+        }
+
+        assert_eq!(envelope.signatures().len(), 1);
+        assert!(envelope.signatures()[0].matches(&AccountId::from(SOURCE)));
+        assert_eq!(envelope.signatures()[0].signature().len(), 64);
+    }
+
+    #[test]
+    fn it_decodes_a_v1_envelope_with_a_muxed_source() {
+        let envelope = TransactionEnvelope::from_base64(&v1_payment(false)).unwrap();
+        let source = envelope.source_account();
+        assert_eq!(source.id(), Some(7));
+        assert_eq!(source.account_id(), AccountId::from(SOURCE));
+        assert!(source.to_string().starts_with('M'));
+        assert_eq!(
+            source.to_string().parse::<MuxedAccount>().as_ref(),
+            Ok(source)
+        );
+        assert_eq!(
+            SOURCE.parse::<MuxedAccount>(),
+            Ok(MuxedAccount::new(&SOURCE.into(), None))
+        );
+        assert!("MABC".parse::<MuxedAccount>().is_err());
+        assert_eq!(envelope.time_bounds(), Some((0, 60)));
+        assert_eq!(*envelope.memo(), Memo::Text("hi".to_string()));
+        assert_eq!(envelope.operations().len(), 1);
+        assert_eq!(envelope.signatures().len(), 1);
+    }
+
+    #[test]
+    fn it_decodes_a_fee_bump() {
+        let envelope = TransactionEnvelope::from_base64(&v1_payment(true)).unwrap();
+        let fee_bump = envelope.fee_bump().unwrap();
+        assert_eq!(fee_bump.fee_source().to_string(), SOURCE);
+        assert_eq!(fee_bump.fee(), 256);
+        assert!(fee_bump.signatures().is_empty());
+        assert_eq!(envelope.fee(), 100);
+        assert_eq!(envelope.signatures().len(), 1);
+    }
+
+    #[test]
+    fn it_refuses_operations_it_cant_decode() {
+        let mut bytes = base64::decode(PAYMENT).unwrap();
+        // The type of the first operation
+        bytes[71] = 24;
+        let err = TransactionEnvelope::from_base64(&base64::encode(&bytes)).unwrap_err();
+        assert_eq!(err, XdrError::Unsupported("soroban operations"));
+    }
+
+    #[test]
+    #[cfg(any(feature = "soroban", feature = "keys"))]
+    fn it_hashes_the_transaction() {
+        let envelope = TransactionEnvelope::from_base64(PAYMENT).unwrap();
+        assert_eq!(
+            hex(&envelope.hash(&Network::public())),
+            "648da0d47aa3b3b20afd4499a68f89b6d10ead8b1f38858e99b1d94b6fef6e69"
+        );
+        assert_eq!(
+            envelope.horizon_hash(&Network::public()),
+            "648da0d47aa3b3b20afd4499a68f89b6d10ead8b1f38858e99b1d94b6fef6e69"
+        );
+    }
+
+    #[test]
+    #[cfg(any(feature = "soroban", feature = "keys"))]
+    fn it_lists_a_fee_bump_under_the_hash_of_the_fee_bump() {
+        let envelope = TransactionEnvelope::from_base64(&v1_payment(true)).unwrap();
+        let network = Network::test();
+        assert_eq!(
+            envelope.horizon_hash(&network),
+            hex(&envelope.fee_bump().unwrap().hash(&network))
+        );
+        assert_ne!(
+            envelope.horizon_hash(&network),
+            hex(&envelope.hash(&network))
+        );
+    }
+
+    fn built(operations: Vec<OperationBody>) -> TransactionEnvelope {
+        let mut builder = EnvelopeBuilder::new(MuxedAccount::new(&SOURCE.into(), None), 9);
+        for body in operations {
+            builder = builder.with_operation(Operation::new(body));
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn it_builds_envelopes_that_decode_to_what_was_built() {
+        let issuer: AccountId = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4".into();
+        let usd = AssetIdentifier::alphanum4("USD", issuer.as_str()).unwrap();
+        let muxed = MuxedAccount::new(&issuer, Some(3));
+        let envelope = EnvelopeBuilder::new(muxed.clone(), 9)
+            .with_fee(1_000)
+            .with_memo(Memo::Hash(base64::encode(&[7; 32])))
+            .with_time_bounds(10, 20)
+            .with_operation(Operation::new(OperationBody::ManageSellOffer {
+                selling: usd.clone(),
+                buying: AssetIdentifier::native(),
+                amount: Amount::new(5),
+                price: PriceRatio::new(3, 7),
+                offer_id: 12,
+            }))
+            .with_operation(
+                Operation::new(OperationBody::SetOptions {
+                    inflation_dest: None,
+                    clear_flags: None,
+                    set_flags: Some(2),
+                    master_weight: Some(0),
+                    low_threshold: Some(1),
+                    med_threshold: Some(1),
+                    high_threshold: Some(1),
+                    home_domain: Some("example.com".to_string()),
+                    signer: Some((SOURCE.to_string(), 1)),
+                })
+                .with_source_account(MuxedAccount::new(&SOURCE.into(), None)),
+            )
+            .with_operation(Operation::new(OperationBody::ClaimClaimableBalance {
+                balance_id: format!("00000000{}", hex(&[9; 32])),
+            }))
+            .with_operation(Operation::new(OperationBody::PathPaymentStrictReceive {
+                send_asset: AssetIdentifier::native(),
+                send_max: Amount::new(100),
+                destination: muxed.clone(),
+                dest_asset: usd.clone(),
+                dest_amount: Amount::new(10),
+                path: vec![usd],
+            }))
+            .build()
+            .unwrap();
+
+        let decoded = TransactionEnvelope::from_base64(&envelope.to_base64()).unwrap();
+        assert_eq!(decoded.source_account(), &muxed);
+        assert_eq!(decoded.fee(), 1_000);
+        assert_eq!(decoded.sequence(), 9);
+        assert_eq!(decoded.time_bounds(), Some((10, 20)));
+        assert_eq!(decoded.memo(), envelope.memo());
+        assert_eq!(decoded.operations(), envelope.operations());
+        assert!(decoded.signatures().is_empty());
+    }
+
+    #[test]
+    fn it_charges_the_base_fee_for_each_operation() {
+        let envelope = built(vec![OperationBody::Inflation, OperationBody::Inflation]);
+        assert_eq!(envelope.fee(), 200);
+    }
+
+    #[test]
+    fn it_writes_prices_in_their_lowest_terms() {
+        let offer = |price| OperationBody::ManageSellOffer {
+            selling: AssetIdentifier::native(),
+            buying: AssetIdentifier::native(),
+            amount: Amount::new(1),
+            price,
+            offer_id: 0,
+        };
+        let envelope = built(vec![offer(PriceRatio::new(4_000_000_000, 6_000_000_000))]);
+        let decoded = TransactionEnvelope::from_base64(&envelope.to_base64()).unwrap();
+        assert_eq!(
+            decoded.operations()[0].body(),
+            &offer(PriceRatio::new(2, 3))
+        );
+
+        let err = EnvelopeBuilder::new(MuxedAccount::new(&SOURCE.into(), None), 9)
+            .with_operation(Operation::new(offer(PriceRatio::new(1 << 31, 1))))
+            .build()
+            .unwrap_err();
+        assert_eq!(err, XdrError::InvalidPrice);
+    }
+
+    #[test]
+    fn it_reencodes_decoded_envelopes() {
+        let fee_bump = v1_payment(true);
+        let envelope = TransactionEnvelope::from_base64(&fee_bump).unwrap();
+        assert_eq!(envelope.to_base64(), fee_bump);
+
+        let v0 = TransactionEnvelope::from_base64(PAYMENT).unwrap();
+        let v1 = TransactionEnvelope::from_base64(&v0.to_base64()).unwrap();
+        assert_eq!(v1.signatures(), v0.signatures());
+        assert_eq!(v1.operations(), v0.operations());
+    }
+
+    #[test]
+    #[cfg(feature = "keys")]
+    fn it_signs_the_envelope() {
+        use keys::Keypair;
+
+        let keypair = Keypair::random().unwrap();
+        let network = Network::test();
+        let mut envelope = built(vec![OperationBody::Inflation]);
+        envelope.sign(&network, &keypair).unwrap();
+        assert_eq!(envelope.signatures().len(), 1);
+        assert!(envelope.signatures()[0].matches(&keypair.account_id().into()));
+
+        let decoded = TransactionEnvelope::from_base64(&envelope.to_base64()).unwrap();
+        assert_eq!(decoded.hash(&network), envelope.hash(&network));
+        assert_eq!(decoded.signatures(), envelope.signatures());
+
+        let mut envelope = TransactionEnvelope::from_base64(&v1_payment(true)).unwrap();
+        envelope.sign(&network, &keypair).unwrap();
+        assert_eq!(envelope.signatures().len(), 1);
+        assert_eq!(envelope.fee_bump().unwrap().signatures().len(), 1);
+    }
+}
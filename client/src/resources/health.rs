@@ -0,0 +1,63 @@
+/// The health of a horizon server, as reported by its health check. A server
+/// is only ready to serve requests when it can reach its database and its
+/// stellar core node is up and in sync with the network.
+///
+/// <https://developers.stellar.org/api/aggregations/health/>
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Health {
+    database_connected: bool,
+    core_up: bool,
+    core_synced: bool,
+}
+
+impl Health {
+    /// Creates a new health report.
+    pub fn new(database_connected: bool, core_up: bool, core_synced: bool) -> Health {
+        Health {
+            database_connected,
+            core_up,
+            core_synced,
+        }
+    }
+
+    /// Whether horizon can reach its database.
+    pub fn database_connected(&self) -> bool {
+        self.database_connected
+    }
+
+    /// Whether the stellar core node behind horizon is running.
+    pub fn core_up(&self) -> bool {
+        self.core_up
+    }
+
+    /// Whether the stellar core node behind horizon is in sync with the network.
+    pub fn core_synced(&self) -> bool {
+        self.core_synced
+    }
+
+    /// Whether every part of the server is up and in sync.
+    pub fn is_healthy(&self) -> bool {
+        self.database_connected && self.core_up && self.core_synced
+    }
+}
+
+#[cfg(test)]
+mod health_tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn it_parses_a_health_check() {
+        let health: Health =
+            serde_json::from_str(include_str!("../../fixtures/health.json")).unwrap();
+        assert!(health.database_connected());
+        assert!(health.core_up());
+        assert!(health.core_synced());
+        assert!(health.is_healthy());
+    }
+
+    #[test]
+    fn it_is_unhealthy_when_core_is_out_of_sync() {
+        assert!(!Health::new(true, true, false).is_healthy());
+    }
+}
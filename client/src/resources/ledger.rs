@@ -1,10 +1,12 @@
 use chrono::prelude::*;
-use resources::Amount;
+use resources::{Amount, Links};
 
 /// A ledger represents the state of the Stellar universe at a given point in time. It contains the list of all the accounts and balances, all the orders in the distributed exchange, and any other data that persists.
 /// The first ledger in the history of the network is called the genesis ledger.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Ledger {
+    #[serde(rename = "_links", default)]
+    links: Links,
     id: String,
     paging_token: String,
     hash: String,
@@ -25,6 +27,10 @@ impl Ledger {
     pub fn id(&self) -> &String {
         &self.id
     }
+    /// The links to the resources related to this one.
+    pub fn links(&self) -> &Links {
+        &self.links
+    }
     /// A paging token suitable for use as the cursor parameter to ledger collection
     /// resources.
     pub fn paging_token(&self) -> &String {
@@ -130,3 +136,111 @@ mod ledger_tests {
         assert_eq!(ledger.protocol_version(), 4);
     }
 }
+
+/// Builds a `Ledger` from its sequence and hash, setting the counts, amounts
+/// and network settings the caller cares about.
+///
+/// ```
+/// use stellar_client::resources::LedgerBuilder;
+///
+/// let ledger = LedgerBuilder::new(69859, "eee9e6e0")
+///     .with_transaction_count(3)
+///     .build();
+/// assert_eq!(ledger.sequence(), 69859);
+/// assert_eq!(ledger.transaction_count(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LedgerBuilder {
+    ledger: Ledger,
+}
+
+impl LedgerBuilder {
+    /// Starts building a ledger with the given sequence and hash. Counts and
+    /// amounts start at zero and the close time at the unix epoch. The fees and
+    /// limits match the network defaults.
+    pub fn new(sequence: u32, hash: &str) -> LedgerBuilder {
+        LedgerBuilder {
+            ledger: Ledger {
+                links: Links::default(),
+                id: hash.to_string(),
+                paging_token: String::new(),
+                hash: hash.to_string(),
+                sequence,
+                transaction_count: 0,
+                operation_count: 0,
+                closed_at: Utc.timestamp(0, 0),
+                total_coins: Amount::new(0),
+                fee_pool: Amount::new(0),
+                base_fee_in_stroops: 100,
+                base_reserve_in_stroops: 5_000_000,
+                max_tx_set_size: 50,
+                protocol_version: 0,
+            },
+        }
+    }
+
+    /// Sets the paging token of the ledger.
+    pub fn with_paging_token(mut self, paging_token: &str) -> LedgerBuilder {
+        self.ledger.paging_token = paging_token.to_string();
+        self
+    }
+
+    /// Sets the number of transactions in the ledger.
+    pub fn with_transaction_count(mut self, transaction_count: i64) -> LedgerBuilder {
+        self.ledger.transaction_count = transaction_count;
+        self
+    }
+
+    /// Sets the number of operations in the ledger.
+    pub fn with_operation_count(mut self, operation_count: i64) -> LedgerBuilder {
+        self.ledger.operation_count = operation_count;
+        self
+    }
+
+    /// Sets the time the ledger closed.
+    pub fn with_closed_at(mut self, closed_at: DateTime<Utc>) -> LedgerBuilder {
+        self.ledger.closed_at = closed_at;
+        self
+    }
+
+    /// Sets the total number of lumens in existence.
+    pub fn with_total_coins(mut self, total_coins: Amount) -> LedgerBuilder {
+        self.ledger.total_coins = total_coins;
+        self
+    }
+
+    /// Sets the sum of all transaction fees not yet distributed.
+    pub fn with_fee_pool(mut self, fee_pool: Amount) -> LedgerBuilder {
+        self.ledger.fee_pool = fee_pool;
+        self
+    }
+
+    /// Sets the fee charged per operation, in stroops.
+    pub fn with_base_fee_in_stroops(mut self, base_fee: i64) -> LedgerBuilder {
+        self.ledger.base_fee_in_stroops = base_fee;
+        self
+    }
+
+    /// Sets the reserve required per account entry, in stroops.
+    pub fn with_base_reserve_in_stroops(mut self, base_reserve: i64) -> LedgerBuilder {
+        self.ledger.base_reserve_in_stroops = base_reserve;
+        self
+    }
+
+    /// Sets the maximum number of transactions the ledger can hold.
+    pub fn with_max_tx_set_size(mut self, max_tx_set_size: u32) -> LedgerBuilder {
+        self.ledger.max_tx_set_size = max_tx_set_size;
+        self
+    }
+
+    /// Sets the protocol version the ledger was closed under.
+    pub fn with_protocol_version(mut self, protocol_version: u32) -> LedgerBuilder {
+        self.ledger.protocol_version = protocol_version;
+        self
+    }
+
+    /// Creates the ledger.
+    pub fn build(self) -> Ledger {
+        self.ledger
+    }
+}
@@ -0,0 +1,167 @@
+use http;
+use std::collections::BTreeMap;
+
+/// The `_links` section of a horizon resource, which points at related
+/// resources by name, such as an account's `transactions` or an operation's
+/// `effects`. Pass a link to `sync::Client::follow` to fetch what it points
+/// at.
+///
+/// <https://developers.stellar.org/api/introduction/response-format/>
+///
+/// ## Example
+///
+/// ```
+/// use stellar_client::resources::{Operation, Resource};
+///
+/// let json = r#"{
+///     "_links": {
+///         "self": { "href": "/operations/58402965295104" },
+///         "effects": {
+///             "href": "/operations/58402965295104/effects{?cursor,limit,order}",
+///             "templated": true
+///         }
+///     },
+///     "id": "58402965295104",
+///     "paging_token": "58402965295104",
+///     "source_account": "GAKLBGHNHFQ3BMUYG5KU4BEWO6EYQHZHAXEWC33W34PH2RBHZDSQBD75",
+///     "type": "restore_footprint",
+///     "type_i": 26,
+///     "transaction_hash": "f0222a5421ccfc4e612f11d9ff95755fbb6300df7c61442d990d498a4cd01c92",
+///     "transaction_successful": true,
+///     "created_at": "2018-01-01T00:00:00Z"
+/// }"#;
+/// let operation = Operation::from_json_str(json).unwrap();
+/// let effects = operation.links().get("effects").unwrap();
+/// assert!(effects.templated());
+/// assert_eq!(
+///     effects.uri().unwrap().path(),
+///     "/operations/58402965295104/effects"
+/// );
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Links(BTreeMap<String, Link>);
+
+impl Links {
+    /// The link with the given relation, such as `self` or `transactions`.
+    pub fn get(&self, rel: &str) -> Option<&Link> {
+        self.0.get(rel)
+    }
+
+    /// The link to the resource itself.
+    pub fn self_link(&self) -> Option<&Link> {
+        self.get("self")
+    }
+
+    /// Iterates over the relations and their links in name order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Link)> {
+        self.0.iter().map(|(rel, link)| (rel.as_str(), link))
+    }
+
+    /// Returns true when the resource came without any links.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The number of links.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// A single link to a related resource.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    href: String,
+    #[serde(default)]
+    templated: bool,
+}
+
+impl Link {
+    /// Creates a link to the given href.
+    pub fn new<S: Into<String>>(href: S) -> Link {
+        Link {
+            href: href.into(),
+            templated: false,
+        }
+    }
+
+    /// The href as horizon sent it. A templated href still has its
+    /// `{?cursor,limit,order}` style template in it.
+    pub fn href(&self) -> &str {
+        &self.href
+    }
+
+    /// Whether the href is a uri template that takes query params.
+    pub fn templated(&self) -> bool {
+        self.templated
+    }
+
+    /// The uri the link points at. The template of a templated link is left
+    /// off, so the uri asks for the first page with horizon's defaults.
+    pub fn uri(&self) -> Result<http::Uri, http::uri::InvalidUri> {
+        if !self.templated {
+            return self.href.parse();
+        }
+        let mut href = String::with_capacity(self.href.len());
+        let mut in_template = false;
+        for c in self.href.chars() {
+            match c {
+                '{' => in_template = true,
+                '}' => in_template = false,
+                c if !in_template => href.push(c),
+                _ => {}
+            }
+        }
+        href.parse()
+    }
+}
+
+#[cfg(test)]
+mod links_tests {
+    use super::*;
+    use resources::Account;
+    use serde_json;
+
+    #[test]
+    fn it_parses_the_links_of_a_resource() {
+        let account: Account =
+            serde_json::from_str(include_str!("../../fixtures/account.json")).unwrap();
+        let links = account.links();
+        assert_eq!(links.len(), 2);
+        assert_eq!(
+            links.self_link().unwrap().href(),
+            "https://horizon-testnet.stellar.org/accounts/GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG"
+        );
+        assert!(!links.self_link().unwrap().templated());
+
+        let transactions = links.get("transactions").unwrap();
+        assert!(transactions.templated());
+        let uri = transactions.uri().unwrap();
+        assert_eq!(
+            uri.path(),
+            "/accounts/GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG/transactions"
+        );
+        assert_eq!(uri.query(), None);
+    }
+
+    #[test]
+    fn it_keeps_the_query_of_a_link() {
+        let link = Link::new("/operations?cursor=58402965295104&order=asc");
+        let uri = link.uri().unwrap();
+        assert_eq!(uri.path(), "/operations");
+        assert_eq!(uri.query(), Some("cursor=58402965295104&order=asc"));
+    }
+
+    #[test]
+    fn it_writes_links_in_horizons_format() {
+        let json = r#"{
+            "self": { "href": "/accounts/GA", "templated": false },
+            "effects": { "href": "/accounts/GA/effects{?cursor,limit,order}", "templated": true }
+        }"#;
+        let links: Links = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            serde_json::to_value(&links).unwrap(),
+            serde_json::from_str::<serde_json::Value>(json).unwrap()
+        );
+    }
+}
@@ -1,40 +1,90 @@
 //! Defines the basic resources of stellar's horizon end points and
-//! implements their deserialization from JSON into rust types.
+//! implements their deserialization from JSON into rust types, along with
+//! their serialization back into horizon's JSON.
+//!
+//! The resources can also be stored in self describing binary formats such
+//! as CBOR or MessagePack and read back. Every field is always written, with
+//! `null` or an empty value for those horizon left out, so structs can be
+//! written compactly as arrays, as `rmp_serde::to_vec` does. Formats that
+//! aren't self describing, such as bincode, aren't supported.
+//!
+//! Resources don't have to come from horizon. Simulators, caches and adapters
+//! can synthesize them with the `new` constructors of the simpler resources
+//! and the builders of the larger ones, such as `AccountBuilder` and
+//! `TransactionBuilder`, which fill in every field they aren't given.
 
 mod account;
+mod account_id;
 mod amount;
 mod asset;
 mod base64string;
+mod claimable_balance;
 mod datum;
 mod deserialize;
 
 /// An effect represents specific changes that occur in the ledger resulting from operations.
 pub mod effect;
+/// A transaction envelope is a transaction along with its signatures, as it
+/// is submitted to the network.
+pub mod envelope;
+mod health;
 mod ledger;
+mod links;
 mod offer;
 /// An operation is an individual command that mutates the ledger.
 pub mod operation;
 mod orderbook;
+mod paging_token;
 /// A payment path is a payment route from a source asset to a destination asset.
 pub mod payment_path;
+#[cfg(feature = "raw-json")]
+mod raw;
+mod resource;
+mod root;
+mod serialize;
+mod submitted_transaction;
+mod summary;
+mod toid;
 mod trade;
 mod transaction;
+mod transaction_meta;
+mod transaction_result;
 
 /// # Stellar Resources
 ///
 /// A collection of data types and resources used within the stellar api.
 /// All the derives for XDR and JSON are implemented for the resources so that
 /// they can be used with a client. Either for reading or for writing.
-pub use self::account::Account;
-pub use self::amount::{Amount, ParseAmountError};
+pub use self::account::{Account, AccountBuilder, Balance, PoolShareBalance, Signer, Thresholds};
+pub use self::account_id::{AccountId, ParseAccountIdError};
+pub use self::amount::{Amount, AmountFormat, ParseAmountError};
 pub use self::asset::{Asset, AssetIdentifier, Flags, ParseAssetIdentifierError};
+pub use self::base64string::Base64String;
+pub(crate) use self::claimable_balance::canonical_asset;
+pub use self::claimable_balance::{ClaimPredicate, ClaimableBalance, Claimant};
 pub use self::datum::Datum;
 pub use self::effect::Effect;
-pub use self::ledger::Ledger;
-pub use self::offer::Offer;
-pub use self::operation::{Operation, OperationKind};
+pub use self::envelope::TransactionEnvelope;
+pub use self::health::Health;
+pub use self::ledger::{Ledger, LedgerBuilder};
+pub use self::links::{Link, Links};
+pub use self::offer::{Offer, OfferSummary, PriceRatio};
+pub use self::operation::{Operation, OperationCategory, OperationKind};
 pub use self::orderbook::Orderbook;
+pub use self::paging_token::PagingToken;
 pub use self::payment_path::PaymentPath;
-pub use self::trade::{Seller as TradeSeller, Trade, TradeAggregation};
+#[cfg(feature = "raw-json")]
+pub use self::raw::Raw;
+pub(crate) use self::resource::from_json_reader_with;
+pub use self::resource::{ParseResourceError, Resource, Strictness};
+pub use self::root::{HorizonVersion, Root};
+pub use self::submitted_transaction::SubmittedTransaction;
+pub use self::toid::{ParseToidError, Toid};
+pub use self::trade::{Seller as TradeSeller, Trade, TradeAggregation, TradeBuilder, TradeParty};
 pub use self::transaction::Memo;
-pub use self::transaction::Transaction;
+pub use self::transaction::{Transaction, TransactionBuilder, TransactionHeader};
+pub use self::transaction_meta::BalanceChange;
+#[cfg(feature = "history")]
+pub(crate) use self::transaction_result::skip_transaction_result;
+pub use self::transaction_result::FailureReasons;
+pub use xdr::XdrError;
@@ -1,4 +1,4 @@
-use resources::{Amount, AssetIdentifier};
+use resources::{AccountId, Amount, AssetIdentifier, Links};
 use std::fmt;
 
 /// The ratio between the asking and selling price
@@ -37,7 +37,7 @@ impl PriceRatio {
 }
 
 /// Summary of an offer to be shown in an orderbook
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct OfferSummary {
     amount: Amount,
     #[serde(rename = "price_r")]
@@ -46,6 +46,15 @@ pub struct OfferSummary {
 }
 
 impl OfferSummary {
+    /// Creates a new offer summary.
+    pub fn new(amount: Amount, price_ratio: PriceRatio, price: Amount) -> OfferSummary {
+        OfferSummary {
+            amount,
+            price_ratio,
+            price,
+        }
+    }
+
     /// Returns the amount of an asset the offer is willing to buy or sell
     pub fn amount(&self) -> Amount {
         self.amount
@@ -81,11 +90,13 @@ mod offer_summary_tests {
 }
 
 /// An offer being made for particular assets at a particular exchange rate.
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Offer {
+    #[serde(rename = "_links", default)]
+    links: Links,
     id: i64,
     paging_token: String,
-    seller: String,
+    seller: AccountId,
     selling: AssetIdentifier,
     buying: AssetIdentifier,
     amount: Amount,
@@ -95,6 +106,36 @@ pub struct Offer {
 }
 
 impl Offer {
+    /// Creates a new offer.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        id: i64,
+        paging_token: String,
+        seller: AccountId,
+        selling: AssetIdentifier,
+        buying: AssetIdentifier,
+        amount: Amount,
+        price_ratio: PriceRatio,
+        price: Amount,
+    ) -> Offer {
+        Offer {
+            id,
+            paging_token,
+            seller,
+            selling,
+            buying,
+            amount,
+            price_ratio,
+            price,
+            links: Links::default(),
+        }
+    }
+
+    /// The links to the resources related to this one.
+    pub fn links(&self) -> &Links {
+        &self.links
+    }
+
     /// The id of the offer
     pub fn id(&self) -> i64 {
         self.id
@@ -106,7 +147,7 @@ impl Offer {
     }
 
     /// The account id fo the account making this offer.
-    pub fn seller(&self) -> &str {
+    pub fn seller(&self) -> &AccountId {
         &self.seller
     }
 
@@ -152,10 +193,27 @@ mod offer_tests {
         let offer: Offer = serde_json::from_str(&offer_json()).unwrap();
         assert_eq!(offer.id(), 121);
         assert_eq!(offer.paging_token(), "121");
-        assert_eq!(offer.selling().code(), "BAR");
-        assert_eq!(offer.buying().code(), "FOO");
+        assert_eq!(offer.selling().code(), Some("BAR"));
+        assert_eq!(offer.buying().code(), Some("FOO"));
         assert_eq!(offer.price_ratio(), (387, 50));
         assert_eq!(offer.amount(), Amount::new(236_692_509));
         assert_eq!(offer.price(), Amount::new(77_400_000));
     }
+
+    #[test]
+    fn it_constructs_an_offer() {
+        let offer = Offer::new(
+            121,
+            "121".to_string(),
+            "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4".into(),
+            AssetIdentifier::native(),
+            AssetIdentifier::native(),
+            Amount::new(236_692_509),
+            PriceRatio::new(387, 50),
+            Amount::new(77_400_000),
+        );
+        assert_eq!(offer.id(), 121);
+        assert_eq!(offer.price_ratio(), (387, 50));
+        assert_eq!(offer.amount(), Amount::new(236_692_509));
+    }
 }
@@ -1,24 +1,25 @@
+use resources::AccountId;
 /// Removes the account and transfers all remaining XLM to the destination account.
 #[derive(Debug, Clone)]
 pub struct AccountMerge {
-    account: String,
-    into: String,
+    account: AccountId,
+    into: AccountId,
 }
 
 /// Removes the account and transfers all remaining XLM to the destination account.
 impl AccountMerge {
     /// Creates a new AccountMerge
-    pub fn new(account: String, into: String) -> AccountMerge {
+    pub fn new(account: AccountId, into: AccountId) -> AccountMerge {
         AccountMerge { account, into }
     }
 
     /// The account being deleted from the ledger
-    pub fn account(&self) -> &str {
+    pub fn account(&self) -> &AccountId {
         &self.account
     }
 
     /// Account ID where funds of deleted account were transferred.
-    pub fn into(&self) -> &str {
+    pub fn into(&self) -> &AccountId {
         &self.into
     }
 }
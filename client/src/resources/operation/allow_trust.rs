@@ -1,4 +1,4 @@
-use resources::AssetIdentifier;
+use resources::{AccountId, AssetIdentifier};
 
 ///Updates the “authorized” flag of an existing trust line this is called by the issuer of the
 ///asset.
@@ -7,8 +7,8 @@ use resources::AssetIdentifier;
 ///only be set and never cleared.
 #[derive(Debug, Clone)]
 pub struct AllowTrust {
-    trustee: String,
-    trustor: String,
+    trustee: AccountId,
+    trustor: AccountId,
     asset: AssetIdentifier,
     authorize: bool,
 }
@@ -16,8 +16,8 @@ pub struct AllowTrust {
 impl AllowTrust {
     /// Creates a new AllowTrust
     pub fn new(
-        trustee: String,
-        trustor: String,
+        trustee: AccountId,
+        trustor: AccountId,
         asset: AssetIdentifier,
         authorize: bool,
     ) -> AllowTrust {
@@ -30,12 +30,12 @@ impl AllowTrust {
     }
 
     /// Trustee account.
-    pub fn trustee(&self) -> &str {
+    pub fn trustee(&self) -> &AccountId {
         &self.trustee
     }
 
     /// Trustor account.
-    pub fn trustor(&self) -> &str {
+    pub fn trustor(&self) -> &AccountId {
         &self.trustor
     }
 
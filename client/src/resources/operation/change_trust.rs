@@ -1,11 +1,11 @@
-use resources::{Amount, AssetIdentifier};
+use resources::{AccountId, Amount, AssetIdentifier};
 
 /// Use “Change Trust” operation to create/update/delete a trust line from the source account to
 /// another. The issuer being trusted and the asset code are in the given Asset object.
 #[derive(Debug, Clone)]
 pub struct ChangeTrust {
-    trustee: String,
-    trustor: String,
+    trustee: AccountId,
+    trustor: AccountId,
     asset: AssetIdentifier,
     limit: Amount,
 }
@@ -13,8 +13,8 @@ pub struct ChangeTrust {
 impl ChangeTrust {
     /// Creates a new ChangeTrust
     pub fn new(
-        trustee: String,
-        trustor: String,
+        trustee: AccountId,
+        trustor: AccountId,
         asset: AssetIdentifier,
         limit: Amount,
     ) -> ChangeTrust {
@@ -27,12 +27,12 @@ impl ChangeTrust {
     }
 
     /// Trustee account.
-    pub fn trustee(&self) -> &str {
+    pub fn trustee(&self) -> &AccountId {
         &self.trustee
     }
 
     /// Trustor account.
-    pub fn trustor(&self) -> &str {
+    pub fn trustor(&self) -> &AccountId {
         &self.trustor
     }
 
@@ -1,16 +1,16 @@
-use resources::Amount;
+use resources::{AccountId, Amount};
 
 /// A create account operation represents a new account creation.
 #[derive(Debug, Clone)]
 pub struct CreateAccount {
-    account: String,
-    funder: String,
+    account: AccountId,
+    funder: AccountId,
     starting_balance: Amount,
 }
 
 impl CreateAccount {
     /// Creates a new CreateAccount
-    pub fn new(account: String, funder: String, starting_balance: Amount) -> CreateAccount {
+    pub fn new(account: AccountId, funder: AccountId, starting_balance: Amount) -> CreateAccount {
         CreateAccount {
             account,
             funder,
@@ -18,12 +18,12 @@ impl CreateAccount {
         }
     }
     /// The public address of a new account that was funded.
-    pub fn account(&self) -> &str {
+    pub fn account(&self) -> &AccountId {
         &self.account
     }
 
     /// The public address of the account that funded a new account.
-    pub fn funder(&self) -> &str {
+    pub fn funder(&self) -> &AccountId {
         &self.funder
     }
 
@@ -0,0 +1,18 @@
+/// Extends the time to live of the contract data entries in the
+/// transaction's footprint, so they are not archived.
+#[derive(Debug, Clone)]
+pub struct ExtendFootprintTtl {
+    extend_to: u32,
+}
+
+impl ExtendFootprintTtl {
+    /// Creates a new ExtendFootprintTtl
+    pub fn new(extend_to: u32) -> ExtendFootprintTtl {
+        ExtendFootprintTtl { extend_to }
+    }
+
+    /// The number of ledgers from now that the entries will live until.
+    pub fn extend_to(&self) -> u32 {
+        self.extend_to
+    }
+}
@@ -0,0 +1,193 @@
+use resources::{Amount, AssetIdentifier};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Invokes a soroban host function: calling a contract, uploading contract
+/// code or creating a contract instance.
+#[derive(Debug, Clone)]
+pub struct InvokeHostFunction {
+    function: String,
+    parameters: Vec<HostFunctionParameter>,
+    address: String,
+    salt: String,
+    asset_balance_changes: Vec<AssetBalanceChange>,
+}
+
+impl InvokeHostFunction {
+    /// Creates a new InvokeHostFunction
+    pub fn new(
+        function: String,
+        parameters: Vec<HostFunctionParameter>,
+        address: String,
+        salt: String,
+        asset_balance_changes: Vec<AssetBalanceChange>,
+    ) -> InvokeHostFunction {
+        InvokeHostFunction {
+            function,
+            parameters,
+            address,
+            salt,
+            asset_balance_changes,
+        }
+    }
+
+    /// The type of host function invoked, such as
+    /// `HostFunctionTypeHostFunctionTypeInvokeContract`.
+    pub fn function(&self) -> &str {
+        &self.function
+    }
+
+    /// The arguments passed to the host function.
+    pub fn parameters(&self) -> &[HostFunctionParameter] {
+        &self.parameters
+    }
+
+    /// The address used to derive a newly created contract's id. Empty for
+    /// other kinds of host function.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// The salt used to derive a newly created contract's id. Empty for
+    /// other kinds of host function.
+    pub fn salt(&self) -> &str {
+        &self.salt
+    }
+
+    /// Movements of classic assets caused by the invocation, through the
+    /// stellar asset contract.
+    pub fn asset_balance_changes(&self) -> &[AssetBalanceChange] {
+        &self.asset_balance_changes
+    }
+}
+
+/// An argument passed to a host function.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct HostFunctionParameter {
+    #[serde(rename = "type")]
+    value_type: String,
+    value: String,
+}
+
+impl HostFunctionParameter {
+    /// Creates a new HostFunctionParameter
+    pub fn new(value_type: String, value: String) -> HostFunctionParameter {
+        HostFunctionParameter { value_type, value }
+    }
+
+    /// The type of the value, such as `Address` or `Sym`.
+    pub fn value_type(&self) -> &str {
+        &self.value_type
+    }
+
+    /// The base64 encoded XDR of the value.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+/// A change to an asset balance made by a contract invocation.
+#[derive(Debug, Clone)]
+pub struct AssetBalanceChange {
+    asset: AssetIdentifier,
+    kind: String,
+    from: String,
+    to: String,
+    amount: Amount,
+}
+
+// Horizon writes the asset's fields alongside the others rather than
+// nested.
+#[derive(Deserialize, Serialize)]
+struct IntermediateBalanceChange {
+    asset_type: String,
+    asset_code: Option<String>,
+    asset_issuer: Option<String>,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    from: String,
+    #[serde(default)]
+    to: String,
+    amount: Amount,
+}
+
+impl<'de> Deserialize<'de> for AssetBalanceChange {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let rep = IntermediateBalanceChange::deserialize(d)?;
+        let asset = AssetIdentifier::new(&rep.asset_type, rep.asset_code, rep.asset_issuer)
+            .map_err(de::Error::custom)?;
+        Ok(AssetBalanceChange {
+            asset,
+            kind: rep.kind,
+            from: rep.from,
+            to: rep.to,
+            amount: rep.amount,
+        })
+    }
+}
+
+impl Serialize for AssetBalanceChange {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        IntermediateBalanceChange {
+            asset_type: self.asset.asset_type().to_string(),
+            asset_code: self.asset.asset_code(),
+            asset_issuer: self.asset.asset_issuer(),
+            kind: self.kind.clone(),
+            from: self.from.clone(),
+            to: self.to.clone(),
+            amount: self.amount,
+        }
+        .serialize(s)
+    }
+}
+
+impl AssetBalanceChange {
+    /// Creates a new AssetBalanceChange
+    pub fn new(
+        asset: AssetIdentifier,
+        kind: String,
+        from: String,
+        to: String,
+        amount: Amount,
+    ) -> AssetBalanceChange {
+        AssetBalanceChange {
+            asset,
+            kind,
+            from,
+            to,
+            amount,
+        }
+    }
+
+    /// The asset whose balance changed.
+    pub fn asset(&self) -> &AssetIdentifier {
+        &self.asset
+    }
+
+    /// How the balance changed: `transfer`, `mint`, `burn` or `clawback`.
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// The account or contract the amount left. Empty for a mint.
+    pub fn from(&self) -> &str {
+        &self.from
+    }
+
+    /// The account or contract the amount arrived at. Empty for a burn or
+    /// clawback.
+    pub fn to(&self) -> &str {
+        &self.to
+    }
+
+    /// The amount that moved.
+    pub fn amount(&self) -> Amount {
+        self.amount
+    }
+}
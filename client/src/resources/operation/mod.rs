@@ -1,11 +1,15 @@
-use super::deserialize;
-use resources::{asset::Flags, offer::PriceRatio, Amount, AssetIdentifier};
-use serde::{de, Deserialize, Deserializer};
+use super::{deserialize, serialize};
+use chrono::prelude::*;
+use resources::envelope::MuxedAccount;
+use resources::{asset::Flags, offer::PriceRatio, AccountId, Amount, AssetIdentifier, Links};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 mod account_merge;
 mod allow_trust;
 mod change_trust;
 mod create_account;
 mod create_passive_offer;
+mod extend_footprint_ttl;
+mod invoke_host_function;
 mod manage_data;
 mod manage_offer;
 mod path_payment;
@@ -17,6 +21,10 @@ pub use self::allow_trust::AllowTrust;
 pub use self::change_trust::ChangeTrust;
 pub use self::create_account::CreateAccount;
 pub use self::create_passive_offer::CreatePassiveOffer;
+pub use self::extend_footprint_ttl::ExtendFootprintTtl;
+pub use self::invoke_host_function::{
+    AssetBalanceChange, HostFunctionParameter, InvokeHostFunction,
+};
 pub use self::manage_data::ManageData;
 pub use self::manage_offer::ManageOffer;
 pub use self::path_payment::PathPayment;
@@ -29,8 +37,9 @@ pub mod prelude {
     pub use super::{
         account_merge::AccountMerge, allow_trust::AllowTrust, change_trust::ChangeTrust,
         create_account::CreateAccount, create_passive_offer::CreatePassiveOffer,
+        extend_footprint_ttl::ExtendFootprintTtl, invoke_host_function::InvokeHostFunction,
         manage_data::ManageData, manage_offer::ManageOffer, path_payment::PathPayment,
-        payment::Payment, set_options::SetOptions, Operation, OperationKind,
+        payment::Payment, set_options::SetOptions, Operation, OperationCategory, OperationKind,
     };
 }
 
@@ -42,9 +51,13 @@ mod test;
 /// Stellar network grouped in a Transaction.
 #[derive(Debug, Clone)]
 pub struct Operation {
+    links: Links,
     id: i64,
     paging_token: String,
     transaction_hash: String,
+    source_account: AccountId,
+    created_at: DateTime<Utc>,
+    transaction_successful: bool,
     kind: Kind,
 }
 
@@ -90,17 +103,73 @@ pub enum OperationKind {
     Inflation,
     /// Set, modify or delete a Data Entry (name/value pair) for an account.
     ManageData(ManageData),
+    /// Invokes a soroban host function, such as calling a smart contract.
+    InvokeHostFunction(InvokeHostFunction),
+    /// Extends how long the contract data in the transaction's footprint lives.
+    ExtendFootprintTtl(ExtendFootprintTtl),
+    /// Restores archived contract data in the transaction's footprint.
+    RestoreFootprint,
 }
 // Use inside file to be brief
 use self::OperationKind as Kind;
 
+/// A coarse grouping of the operation kinds, for tallying activity without
+/// matching on every kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperationCategory {
+    /// Moves lumens or another asset from one account to another: account
+    /// creations, payments, path payments and account merges.
+    Payment,
+    /// Places, changes or removes an offer on the decentralized exchange.
+    Trade,
+    /// Changes the settings of an account, such as its signers, flags and
+    /// data entries.
+    AccountConfig,
+    /// Opens, changes or authorizes a trust line.
+    Trust,
+    /// Begins, ends or revokes the sponsorship of reserves. None of the
+    /// operation kinds this library decodes fall in this category yet.
+    Sponsorship,
+    /// Invokes a smart contract or manages the lifetime of contract data.
+    Soroban,
+    /// Anything else, such as running inflation.
+    Other,
+}
+
 impl Operation {
+    /// Creates a new operation of the given kind.
+    pub fn new(
+        id: i64,
+        paging_token: String,
+        transaction_hash: String,
+        source_account: AccountId,
+        created_at: DateTime<Utc>,
+        transaction_successful: bool,
+        kind: Kind,
+    ) -> Operation {
+        Operation {
+            links: Links::default(),
+            id,
+            paging_token,
+            transaction_hash,
+            source_account,
+            created_at,
+            transaction_successful,
+            kind,
+        }
+    }
+
     /// The canonical id of this operation, suitable for use as the :id parameter for url templates
     /// that require an operation’s ID.
     pub fn id(&self) -> i64 {
         self.id
     }
 
+    /// The links to the resources related to this one.
+    pub fn links(&self) -> &Links {
+        &self.links
+    }
+
     /// A paging token suitable for use as a cursor parameter.
     pub fn paging_token(&self) -> &str {
         &self.paging_token
@@ -111,6 +180,30 @@ impl Operation {
         &self.transaction_hash
     }
 
+    /// The hash for the transaction that the operation was part of. The same
+    /// as `transaction`, named after the field horizon returns.
+    pub fn transaction_hash(&self) -> &str {
+        &self.transaction_hash
+    }
+
+    /// The account that the operation acts on behalf of. This is the
+    /// transaction's source account unless the operation set its own.
+    pub fn source_account(&self) -> &AccountId {
+        &self.source_account
+    }
+
+    /// The time the transaction containing the operation was applied.
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    /// Whether the transaction containing the operation succeeded. Failed
+    /// transactions still appear in the ledger, but none of their
+    /// operations took effect.
+    pub fn transaction_successful(&self) -> bool {
+        self.transaction_successful
+    }
+
     /// Specifies the type of operation, See “Types” section below for reference.
     pub fn type_i(&self) -> u32 {
         match self.kind {
@@ -125,6 +218,9 @@ impl Operation {
             Kind::AccountMerge(_) => 8,
             Kind::Inflation => 9,
             Kind::ManageData(_) => 10,
+            Kind::InvokeHostFunction(_) => 24,
+            Kind::ExtendFootprintTtl(_) => 25,
+            Kind::RestoreFootprint => 26,
         }
     }
 
@@ -147,6 +243,52 @@ impl Operation {
             Kind::AccountMerge(_) => "Account Merge",
             Kind::Inflation => "Inflation",
             Kind::ManageData(_) => "Manage Data",
+            Kind::InvokeHostFunction(_) => "Invoke Host Function",
+            Kind::ExtendFootprintTtl(_) => "Extend Footprint TTL",
+            Kind::RestoreFootprint => "Restore Footprint",
+        }
+    }
+
+    /// The category the operation's kind falls in.
+    pub fn category(&self) -> OperationCategory {
+        match self.kind {
+            Kind::CreateAccount(_)
+            | Kind::Payment(_)
+            | Kind::PathPayment(_)
+            | Kind::AccountMerge(_) => OperationCategory::Payment,
+            Kind::ManageOffer(_) | Kind::CreatePassiveOffer(_) => OperationCategory::Trade,
+            Kind::SetOptions(_) | Kind::ManageData(_) => OperationCategory::AccountConfig,
+            Kind::ChangeTrust(_) | Kind::AllowTrust(_) => OperationCategory::Trust,
+            Kind::InvokeHostFunction(_) | Kind::ExtendFootprintTtl(_) | Kind::RestoreFootprint => {
+                OperationCategory::Soroban
+            }
+            Kind::Inflation => OperationCategory::Other,
+        }
+    }
+
+    /// Returns true if the operation can change the balance of an account,
+    /// leaving aside the fee every transaction pays. Offers count, since
+    /// they can trade as soon as they are placed, as does inflation, which
+    /// paid out to the accounts voted for. A contract invocation counts
+    /// only when horizon reports that it moved assets.
+    pub fn is_balance_affecting(&self) -> bool {
+        match self.kind {
+            Kind::CreateAccount(_)
+            | Kind::Payment(_)
+            | Kind::PathPayment(_)
+            | Kind::AccountMerge(_)
+            | Kind::ManageOffer(_)
+            | Kind::CreatePassiveOffer(_)
+            | Kind::Inflation => true,
+            Kind::InvokeHostFunction(ref invocation) => {
+                !invocation.asset_balance_changes().is_empty()
+            }
+            Kind::SetOptions(_)
+            | Kind::ManageData(_)
+            | Kind::ChangeTrust(_)
+            | Kind::AllowTrust(_)
+            | Kind::ExtendFootprintTtl(_)
+            | Kind::RestoreFootprint => false,
         }
     }
 
@@ -237,23 +379,157 @@ impl Operation {
             _ => false,
         }
     }
+
+    /// Returns true if the operation is an invoke host function operation
+    pub fn is_invoke_host_function(&self) -> bool {
+        match self.kind {
+            Kind::InvokeHostFunction(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns true if the operation is an extend footprint ttl operation
+    pub fn is_extend_footprint_ttl(&self) -> bool {
+        match self.kind {
+            Kind::ExtendFootprintTtl(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns true if the operation is a restore footprint operation
+    pub fn is_restore_footprint(&self) -> bool {
+        match self.kind {
+            Kind::RestoreFootprint => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the details of the operation if it is a create account operation
+    pub fn as_create_account(&self) -> Option<&CreateAccount> {
+        match self.kind {
+            Kind::CreateAccount(ref details) => Some(details),
+            _ => None,
+        }
+    }
+
+    /// Returns the details of the operation if it is a payment operation
+    pub fn as_payment(&self) -> Option<&Payment> {
+        match self.kind {
+            Kind::Payment(ref details) => Some(details),
+            _ => None,
+        }
+    }
+
+    /// Returns the details of the operation if it is a path payment operation
+    pub fn as_path_payment(&self) -> Option<&PathPayment> {
+        match self.kind {
+            Kind::PathPayment(ref details) => Some(details),
+            _ => None,
+        }
+    }
+
+    /// Returns the details of the operation if it is a manage offer operation
+    pub fn as_manage_offer(&self) -> Option<&ManageOffer> {
+        match self.kind {
+            Kind::ManageOffer(ref details) => Some(details),
+            _ => None,
+        }
+    }
+
+    /// Returns the details of the operation if it is a create passive offer operation
+    pub fn as_create_passive_offer(&self) -> Option<&CreatePassiveOffer> {
+        match self.kind {
+            Kind::CreatePassiveOffer(ref details) => Some(details),
+            _ => None,
+        }
+    }
+
+    /// Returns the details of the operation if it is a set options operation
+    pub fn as_set_options(&self) -> Option<&SetOptions> {
+        match self.kind {
+            Kind::SetOptions(ref details) => Some(details),
+            _ => None,
+        }
+    }
+
+    /// Returns the details of the operation if it is a change trust operation
+    pub fn as_change_trust(&self) -> Option<&ChangeTrust> {
+        match self.kind {
+            Kind::ChangeTrust(ref details) => Some(details),
+            _ => None,
+        }
+    }
+
+    /// Returns the details of the operation if it is an allow trust operation
+    pub fn as_allow_trust(&self) -> Option<&AllowTrust> {
+        match self.kind {
+            Kind::AllowTrust(ref details) => Some(details),
+            _ => None,
+        }
+    }
+
+    /// Returns the details of the operation if it is an account merge operation
+    pub fn as_account_merge(&self) -> Option<&AccountMerge> {
+        match self.kind {
+            Kind::AccountMerge(ref details) => Some(details),
+            _ => None,
+        }
+    }
+
+    /// Returns the details of the operation if it is a manage data operation
+    pub fn as_manage_data(&self) -> Option<&ManageData> {
+        match self.kind {
+            Kind::ManageData(ref details) => Some(details),
+            _ => None,
+        }
+    }
+
+    /// Returns the details of the operation if it is an invoke host function operation
+    pub fn as_invoke_host_function(&self) -> Option<&InvokeHostFunction> {
+        match self.kind {
+            Kind::InvokeHostFunction(ref details) => Some(details),
+            _ => None,
+        }
+    }
+
+    /// Returns the details of the operation if it is an extend footprint ttl operation
+    pub fn as_extend_footprint_ttl(&self) -> Option<&ExtendFootprintTtl> {
+        match self.kind {
+            Kind::ExtendFootprintTtl(ref details) => Some(details),
+            _ => None,
+        }
+    }
 }
 
 /// Represents the actual structure of the json api. This allows us to parse
 /// directly from the captured json into our own types.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 struct Intermediate {
-    #[serde(deserialize_with = "deserialize::from_str")]
+    #[serde(rename = "_links", default)]
+    links: Links,
+    #[serde(
+        deserialize_with = "deserialize::from_str",
+        serialize_with = "serialize::to_string"
+    )]
     id: i64,
     paging_token: String,
     transaction_hash: String,
+    source_account: AccountId,
+    created_at: DateTime<Utc>,
+    transaction_successful: bool,
     #[serde(rename = "type")]
     kind: String,
-    account: Option<String>,
-    funder: Option<String>,
+    #[serde(default)]
+    type_i: Option<u32>,
+    account: Option<AccountId>,
+    funder: Option<AccountId>,
     starting_balance: Option<Amount>,
-    from: Option<String>,
-    to: Option<String>,
+    from: Option<AccountId>,
+    to: Option<AccountId>,
+    from_muxed: Option<String>,
+    from_muxed_id: Option<String>,
+    to_muxed: Option<String>,
+    to_muxed_id: Option<String>,
     asset_type: Option<String>,
     asset_code: Option<String>,
     asset_issuer: Option<String>,
@@ -284,13 +560,39 @@ struct Intermediate {
     set_flags_s: Option<Vec<String>>,
     clear_flags: Option<Vec<u32>>,
     clear_flags_s: Option<Vec<String>>,
-    trustor: Option<String>,
-    trustee: Option<String>,
+    trustor: Option<AccountId>,
+    trustee: Option<AccountId>,
     authorize: Option<bool>,
     limit: Option<Amount>,
-    into: Option<String>,
+    into: Option<AccountId>,
     name: Option<String>,
     value: Option<String>,
+    function: Option<String>,
+    parameters: Option<Vec<HostFunctionParameter>>,
+    address: Option<String>,
+    salt: Option<String>,
+    asset_balance_changes: Option<Vec<AssetBalanceChange>>,
+    extend_to: Option<u32>,
+}
+
+/// Maps the names horizon gave operations before protocol 11 to the names it
+/// gives them now, so that operations from old ledgers are read into the
+/// same kinds.
+#[cfg(feature = "legacy-ops")]
+fn modern_type(kind: &str) -> &str {
+    match kind {
+        "path_payment" => "path_payment_strict_receive",
+        "manage_offer" => "manage_sell_offer",
+        "create_passive_offer" => "create_passive_sell_offer",
+        other => other,
+    }
+}
+
+/// Leaves the names of operations alone. The names from before protocol 11
+/// are only read with the `legacy-ops` feature.
+#[cfg(not(feature = "legacy-ops"))]
+fn modern_type(kind: &str) -> &str {
+    kind
 }
 
 impl<'de> Deserialize<'de> for Operation {
@@ -300,7 +602,7 @@ impl<'de> Deserialize<'de> for Operation {
     {
         let rep = Intermediate::deserialize(d)?;
 
-        let kind: Kind = match rep.kind.as_str() {
+        let kind: Kind = match modern_type(&rep.kind) {
             "create_account" => match rep {
                 Intermediate {
                     account: Some(account),
@@ -314,7 +616,7 @@ impl<'de> Deserialize<'de> for Operation {
                     ))
                 }
             },
-            "path_payment" => match rep {
+            "path_payment_strict_receive" => match rep {
                 Intermediate {
                     from: Some(from),
                     to: Some(to),
@@ -326,6 +628,8 @@ impl<'de> Deserialize<'de> for Operation {
                     source_asset_issuer,
                     source_asset_type: Some(source_asset_type),
                     source_max: Some(source_max),
+                    from_muxed,
+                    to_muxed,
                     ..
                 } => {
                     let destination_asset_identifier =
@@ -336,18 +640,25 @@ impl<'de> Deserialize<'de> for Operation {
                         source_asset_code,
                         source_asset_issuer,
                     ).map_err(de::Error::custom)?;
-                    Kind::PathPayment(PathPayment::new(
+                    let mut op = PathPayment::new(
                         from,
                         to,
                         destination_asset_identifier,
                         amount,
                         source_asset_identifier,
                         source_max,
-                    ))
+                    );
+                    if let Some(from_muxed) = from_muxed {
+                        op = op.with_from_muxed(from_muxed.parse().map_err(de::Error::custom)?);
+                    }
+                    if let Some(to_muxed) = to_muxed {
+                        op = op.with_to_muxed(to_muxed.parse().map_err(de::Error::custom)?);
+                    }
+                    Kind::PathPayment(op)
                 }
                 _ => {
                     return Err(de::Error::custom(
-                        "Missing fields for path_payment operation.",
+                        "Missing fields for path_payment_strict_receive operation.",
                     ))
                 }
             },
@@ -359,16 +670,25 @@ impl<'de> Deserialize<'de> for Operation {
                     asset_issuer,
                     asset_type: Some(asset_type),
                     amount: Some(amount),
+                    from_muxed,
+                    to_muxed,
                     ..
                 } => {
                     let asset_identifier =
                         AssetIdentifier::new(&asset_type, asset_code, asset_issuer)
                             .map_err(de::Error::custom)?;
-                    Kind::Payment(Payment::new(from, to, asset_identifier, amount))
+                    let mut op = Payment::new(from, to, asset_identifier, amount);
+                    if let Some(from_muxed) = from_muxed {
+                        op = op.with_from_muxed(from_muxed.parse().map_err(de::Error::custom)?);
+                    }
+                    if let Some(to_muxed) = to_muxed {
+                        op = op.with_to_muxed(to_muxed.parse().map_err(de::Error::custom)?);
+                    }
+                    Kind::Payment(op)
                 }
                 _ => return Err(de::Error::custom("Missing fields for payment operation.")),
             },
-            "create_passive_offer" => match rep {
+            "create_passive_sell_offer" => match rep {
                 Intermediate {
                     offer_id: Some(offer_id),
                     buying_asset_code,
@@ -404,11 +724,11 @@ impl<'de> Deserialize<'de> for Operation {
                 }
                 _ => {
                     return Err(de::Error::custom(
-                        "Missing fields for create_passive_offer operation.",
+                        "Missing fields for create_passive_sell_offer operation.",
                     ))
                 }
             },
-            "manage_offer" => match rep {
+            "manage_sell_offer" => match rep {
                 Intermediate {
                     offer_id: Some(offer_id),
                     buying_asset_code,
@@ -444,7 +764,7 @@ impl<'de> Deserialize<'de> for Operation {
                 }
                 _ => {
                     return Err(de::Error::custom(
-                        "Missing fields for manage_offer operation.",
+                        "Missing fields for manage_sell_offer operation.",
                     ))
                 }
             },
@@ -517,6 +837,7 @@ impl<'de> Deserialize<'de> for Operation {
                     ))
                 }
             },
+            #[cfg(feature = "legacy-ops")]
             "allow_trust" => match rep {
                 Intermediate {
                     authorize: Some(authorize),
@@ -561,14 +882,304 @@ impl<'de> Deserialize<'de> for Operation {
                     ))
                 }
             },
+            "invoke_host_function" => match rep {
+                Intermediate {
+                    function: Some(function),
+                    parameters,
+                    address,
+                    salt,
+                    asset_balance_changes,
+                    ..
+                } => Kind::InvokeHostFunction(InvokeHostFunction::new(
+                    function,
+                    parameters.unwrap_or_default(),
+                    address.unwrap_or_default(),
+                    salt.unwrap_or_default(),
+                    asset_balance_changes.unwrap_or_default(),
+                )),
+                _ => {
+                    return Err(de::Error::custom(
+                        "Missing fields for invoke_host_function operation.",
+                    ))
+                }
+            },
+            "extend_footprint_ttl" => match rep {
+                Intermediate {
+                    extend_to: Some(extend_to),
+                    ..
+                } => Kind::ExtendFootprintTtl(ExtendFootprintTtl::new(extend_to)),
+                _ => {
+                    return Err(de::Error::custom(
+                        "Missing fields for extend_footprint_ttl operation.",
+                    ))
+                }
+            },
+            "restore_footprint" => Kind::RestoreFootprint,
+            #[cfg(feature = "legacy-ops")]
             "inflation" => Kind::Inflation,
             _ => return Err(de::Error::custom("Unknown operation type.")),
         };
         Ok(Operation {
+            links: rep.links,
             id: rep.id,
             paging_token: rep.paging_token,
             transaction_hash: rep.transaction_hash,
+            source_account: rep.source_account,
+            created_at: rep.created_at,
+            transaction_successful: rep.transaction_successful,
             kind,
         })
     }
 }
+
+impl Serialize for Operation {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut rep = Intermediate {
+            links: self.links.clone(),
+            id: self.id,
+            paging_token: self.paging_token.clone(),
+            transaction_hash: self.transaction_hash.clone(),
+            source_account: self.source_account.clone(),
+            created_at: self.created_at,
+            transaction_successful: self.transaction_successful,
+            kind: String::new(),
+            type_i: Some(self.type_i()),
+            account: None,
+            funder: None,
+            starting_balance: None,
+            from: None,
+            to: None,
+            from_muxed: None,
+            from_muxed_id: None,
+            to_muxed: None,
+            to_muxed_id: None,
+            asset_type: None,
+            asset_code: None,
+            asset_issuer: None,
+            source_asset_type: None,
+            source_asset_code: None,
+            source_asset_issuer: None,
+            amount: None,
+            source_amount: None,
+            source_max: None,
+            buying_asset_type: None,
+            buying_asset_code: None,
+            buying_asset_issuer: None,
+            selling_asset_type: None,
+            selling_asset_code: None,
+            selling_asset_issuer: None,
+            offer_id: None,
+            price_ratio: None,
+            price: None,
+            signer_key: None,
+            signer_weight: None,
+            master_key_weight: None,
+            low_threshold: None,
+            med_threshold: None,
+            high_threshold: None,
+            home_domain: None,
+            set_flags: None,
+            set_flags_s: None,
+            clear_flags: None,
+            clear_flags_s: None,
+            trustor: None,
+            trustee: None,
+            authorize: None,
+            limit: None,
+            into: None,
+            name: None,
+            value: None,
+            function: None,
+            parameters: None,
+            address: None,
+            salt: None,
+            asset_balance_changes: None,
+            extend_to: None,
+        };
+        let kind = match self.kind {
+            Kind::CreateAccount(ref op) => {
+                rep.account = Some(op.account().clone());
+                rep.funder = Some(op.funder().clone());
+                rep.starting_balance = Some(op.starting_balance());
+                "create_account"
+            }
+            Kind::Payment(ref op) => {
+                rep.from = Some(op.from().clone());
+                rep.to = Some(op.to().clone());
+                rep.set_muxed(op.from_muxed(), op.to_muxed());
+                let (kind, code, issuer) = asset_fields(op.asset());
+                rep.asset_type = kind;
+                rep.asset_code = code;
+                rep.asset_issuer = issuer;
+                rep.amount = Some(op.amount());
+                "payment"
+            }
+            Kind::PathPayment(ref op) => {
+                rep.from = Some(op.from().clone());
+                rep.to = Some(op.to().clone());
+                rep.set_muxed(op.from_muxed(), op.to_muxed());
+                let (kind, code, issuer) = asset_fields(op.destination_asset());
+                rep.asset_type = kind;
+                rep.asset_code = code;
+                rep.asset_issuer = issuer;
+                rep.amount = Some(op.destination_amount());
+                let (kind, code, issuer) = asset_fields(op.source_asset());
+                rep.source_asset_type = kind;
+                rep.source_asset_code = code;
+                rep.source_asset_issuer = issuer;
+                rep.source_max = Some(op.source_max());
+                "path_payment_strict_receive"
+            }
+            Kind::ManageOffer(ref op) => {
+                rep.offer_id = Some(op.offer_id());
+                rep.set_offer(
+                    op.selling(),
+                    op.buying(),
+                    op.amount(),
+                    op.price_ratio(),
+                    op.price(),
+                );
+                "manage_sell_offer"
+            }
+            Kind::CreatePassiveOffer(ref op) => {
+                rep.offer_id = Some(op.offer_id());
+                rep.set_offer(
+                    op.selling(),
+                    op.buying(),
+                    op.amount(),
+                    op.price_ratio(),
+                    op.price(),
+                );
+                "create_passive_sell_offer"
+            }
+            Kind::SetOptions(ref op) => {
+                rep.signer_key = Some(op.signer_key().to_string());
+                rep.signer_weight = Some(op.signer_weight());
+                rep.master_key_weight = Some(op.master_key_weight());
+                rep.low_threshold = Some(op.low_threshold());
+                rep.med_threshold = Some(op.med_threshold());
+                rep.high_threshold = Some(op.high_threshold());
+                rep.home_domain = Some(op.home_domain().to_string());
+                let (flags, names) = flag_fields(op.set_flags());
+                rep.set_flags = flags;
+                rep.set_flags_s = names;
+                let (flags, names) = flag_fields(op.clear_flags());
+                rep.clear_flags = flags;
+                rep.clear_flags_s = names;
+                "set_options"
+            }
+            Kind::ChangeTrust(ref op) => {
+                rep.trustee = Some(op.trustee().clone());
+                rep.trustor = Some(op.trustor().clone());
+                let (kind, code, issuer) = asset_fields(op.asset());
+                rep.asset_type = kind;
+                rep.asset_code = code;
+                rep.asset_issuer = issuer;
+                rep.limit = Some(op.limit());
+                "change_trust"
+            }
+            Kind::AllowTrust(ref op) => {
+                rep.trustee = Some(op.trustee().clone());
+                rep.trustor = Some(op.trustor().clone());
+                let (kind, code, issuer) = asset_fields(op.asset());
+                rep.asset_type = kind;
+                rep.asset_code = code;
+                rep.asset_issuer = issuer;
+                rep.authorize = Some(op.authorize());
+                "allow_trust"
+            }
+            Kind::AccountMerge(ref op) => {
+                rep.account = Some(op.account().clone());
+                rep.into = Some(op.into().clone());
+                "account_merge"
+            }
+            Kind::Inflation => "inflation",
+            Kind::ManageData(ref op) => {
+                rep.name = Some(op.name().to_string());
+                rep.value = Some(op.value().to_string());
+                "manage_data"
+            }
+            Kind::InvokeHostFunction(ref op) => {
+                rep.function = Some(op.function().to_string());
+                rep.parameters = Some(op.parameters().to_vec());
+                rep.address = Some(op.address().to_string());
+                rep.salt = Some(op.salt().to_string());
+                rep.asset_balance_changes = Some(op.asset_balance_changes().to_vec());
+                "invoke_host_function"
+            }
+            Kind::ExtendFootprintTtl(ref op) => {
+                rep.extend_to = Some(op.extend_to());
+                "extend_footprint_ttl"
+            }
+            Kind::RestoreFootprint => "restore_footprint",
+        };
+        rep.kind = kind.to_string();
+        rep.serialize(s)
+    }
+}
+
+impl Intermediate {
+    fn set_offer(
+        &mut self,
+        selling: &AssetIdentifier,
+        buying: &AssetIdentifier,
+        amount: Amount,
+        price_ratio: &PriceRatio,
+        price: Amount,
+    ) {
+        let (kind, code, issuer) = asset_fields(selling);
+        self.selling_asset_type = kind;
+        self.selling_asset_code = code;
+        self.selling_asset_issuer = issuer;
+        let (kind, code, issuer) = asset_fields(buying);
+        self.buying_asset_type = kind;
+        self.buying_asset_code = code;
+        self.buying_asset_issuer = issuer;
+        self.amount = Some(amount);
+        self.price_ratio = Some(*price_ratio);
+        self.price = Some(price);
+    }
+
+    fn set_muxed(&mut self, from: Option<&MuxedAccount>, to: Option<&MuxedAccount>) {
+        if let Some(from) = from {
+            self.from_muxed = Some(from.to_string());
+            self.from_muxed_id = from.id().map(|id| id.to_string());
+        }
+        if let Some(to) = to {
+            self.to_muxed = Some(to.to_string());
+            self.to_muxed_id = to.id().map(|id| id.to_string());
+        }
+    }
+}
+
+/// Splits an asset into horizon's type, code and issuer fields.
+fn asset_fields(asset: &AssetIdentifier) -> (Option<String>, Option<String>, Option<String>) {
+    (
+        Some(asset.asset_type().to_string()),
+        asset.asset_code(),
+        asset.asset_issuer(),
+    )
+}
+
+/// Lists the flags that are set both as numbers and by name, the way
+/// horizon shows the flags a set options operation sets or clears.
+fn flag_fields(flags: Option<Flags>) -> (Option<Vec<u32>>, Option<Vec<String>>) {
+    let flags = match flags {
+        Some(flags) => flags,
+        None => return (None, None),
+    };
+    let mut numbers = Vec::new();
+    let mut names = Vec::new();
+    if flags.is_auth_required() {
+        numbers.push(1);
+        names.push("auth_required_flag".to_string());
+    }
+    if flags.is_auth_revocable() {
+        numbers.push(2);
+        names.push("auth_revocable_flag".to_string());
+    }
+    (Some(numbers), Some(names))
+}
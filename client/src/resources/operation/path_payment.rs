@@ -1,23 +1,26 @@
-use resources::{Amount, AssetIdentifier};
+use resources::envelope::MuxedAccount;
+use resources::{AccountId, Amount, AssetIdentifier};
 
 /// A path payment operation represents a payment from one account to another through a path. This
 /// type of payment starts as one type of asset and ends as another type of asset. There can be
 /// other assets that are traded into and out of along the path.
 #[derive(Debug, Clone)]
 pub struct PathPayment {
-    from: String,
-    to: String,
+    from: AccountId,
+    to: AccountId,
     destination_asset: AssetIdentifier,
     destination_amount: Amount,
     source_asset: AssetIdentifier,
     source_max: Amount,
+    from_muxed: Option<MuxedAccount>,
+    to_muxed: Option<MuxedAccount>,
 }
 
 impl PathPayment {
     /// Creates a new PathPayment
     pub fn new(
-        from: String,
-        to: String,
+        from: AccountId,
+        to: AccountId,
         destination_asset: AssetIdentifier,
         destination_amount: Amount,
         source_asset: AssetIdentifier,
@@ -30,15 +33,17 @@ impl PathPayment {
             destination_amount,
             source_asset,
             source_max,
+            from_muxed: None,
+            to_muxed: None,
         }
     }
     /// Sender of a payment.
-    pub fn from(&self) -> &str {
+    pub fn from(&self) -> &AccountId {
         &self.from
     }
 
     /// Destination of a payment.
-    pub fn to(&self) -> &str {
+    pub fn to(&self) -> &AccountId {
         &self.to
     }
 
@@ -61,4 +66,41 @@ impl PathPayment {
     pub fn source_max(&self) -> Amount {
         self.source_max
     }
+
+    /// Records the `M...` address the payment was sent from, when the sender
+    /// is one of the many users sharing the `from` account.
+    pub fn with_from_muxed(mut self, from_muxed: MuxedAccount) -> PathPayment {
+        self.from_muxed = Some(from_muxed);
+        self
+    }
+
+    /// Records the `M...` address the payment was sent to, when the
+    /// recipient is one of the many users sharing the `to` account.
+    pub fn with_to_muxed(mut self, to_muxed: MuxedAccount) -> PathPayment {
+        self.to_muxed = Some(to_muxed);
+        self
+    }
+
+    /// The `M...` address the payment was sent from, if the sender used one.
+    /// Its account is the same as `from`.
+    pub fn from_muxed(&self) -> Option<&MuxedAccount> {
+        self.from_muxed.as_ref()
+    }
+
+    /// The `M...` address the payment was sent to, if the sender used one.
+    /// Its account is the same as `to`.
+    pub fn to_muxed(&self) -> Option<&MuxedAccount> {
+        self.to_muxed.as_ref()
+    }
+
+    /// The multiplexing id of the sender within the `from` account, if any.
+    pub fn from_muxed_id(&self) -> Option<u64> {
+        self.from_muxed.as_ref().and_then(MuxedAccount::id)
+    }
+
+    /// The multiplexing id of the recipient within the `to` account, if any.
+    /// Exchanges use it to tell which of their users to credit.
+    pub fn to_muxed_id(&self) -> Option<u64> {
+        self.to_muxed.as_ref().and_then(MuxedAccount::id)
+    }
 }
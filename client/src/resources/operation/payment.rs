@@ -1,33 +1,38 @@
-use resources::{Amount, AssetIdentifier};
+use resources::envelope::MuxedAccount;
+use resources::{AccountId, Amount, AssetIdentifier};
 
 /// A payment operation represents a payment from one account to another. This payment can be
 /// either a simple native asset payment or a fiat asset payment.
 #[derive(Debug, Clone)]
 pub struct Payment {
-    from: String,
-    to: String,
+    from: AccountId,
+    to: AccountId,
     asset: AssetIdentifier,
     amount: Amount,
+    from_muxed: Option<MuxedAccount>,
+    to_muxed: Option<MuxedAccount>,
 }
 
 impl Payment {
     /// Creates a new Payment
-    pub fn new(from: String, to: String, asset: AssetIdentifier, amount: Amount) -> Payment {
+    pub fn new(from: AccountId, to: AccountId, asset: AssetIdentifier, amount: Amount) -> Payment {
         Payment {
             from,
             to,
             asset,
             amount,
+            from_muxed: None,
+            to_muxed: None,
         }
     }
 
     /// The public address of the account making a payment.
-    pub fn from(&self) -> &str {
+    pub fn from(&self) -> &AccountId {
         &self.from
     }
 
     /// The public address of the account receiving a payment.
-    pub fn to(&self) -> &str {
+    pub fn to(&self) -> &AccountId {
         &self.to
     }
 
@@ -40,4 +45,41 @@ impl Payment {
     pub fn amount(&self) -> Amount {
         self.amount
     }
+
+    /// Records the `M...` address the payment was sent from, when the sender
+    /// is one of the many users sharing the `from` account.
+    pub fn with_from_muxed(mut self, from_muxed: MuxedAccount) -> Payment {
+        self.from_muxed = Some(from_muxed);
+        self
+    }
+
+    /// Records the `M...` address the payment was sent to, when the
+    /// recipient is one of the many users sharing the `to` account.
+    pub fn with_to_muxed(mut self, to_muxed: MuxedAccount) -> Payment {
+        self.to_muxed = Some(to_muxed);
+        self
+    }
+
+    /// The `M...` address the payment was sent from, if the sender used one.
+    /// Its account is the same as `from`.
+    pub fn from_muxed(&self) -> Option<&MuxedAccount> {
+        self.from_muxed.as_ref()
+    }
+
+    /// The `M...` address the payment was sent to, if the sender used one.
+    /// Its account is the same as `to`.
+    pub fn to_muxed(&self) -> Option<&MuxedAccount> {
+        self.to_muxed.as_ref()
+    }
+
+    /// The multiplexing id of the sender within the `from` account, if any.
+    pub fn from_muxed_id(&self) -> Option<u64> {
+        self.from_muxed.as_ref().and_then(MuxedAccount::id)
+    }
+
+    /// The multiplexing id of the recipient within the `to` account, if any.
+    /// Exchanges use it to tell which of their users to credit.
+    pub fn to_muxed_id(&self) -> Option<u64> {
+        self.to_muxed.as_ref().and_then(MuxedAccount::id)
+    }
 }
@@ -1,4 +1,6 @@
-use resources::{asset::Flags, Amount, Operation, OperationKind};
+use chrono::prelude::*;
+use resources::envelope::MuxedAccount;
+use resources::{asset::Flags, AccountId, Amount, Operation, OperationCategory, OperationKind};
 use serde_json;
 
 fn account_merge_json() -> &'static str {
@@ -10,12 +12,18 @@ mod errors_on_missing_fields_for_type {
 
     macro_rules! assert_err_on_missing_fields {
         ($type_to_check:ident, $type_i:expr) => {
+            assert_err_on_missing_fields!($type_to_check, $type_i, $type_to_check);
+        };
+        ($type_to_check:ident, $type_i:expr, $reported_as:ident) => {
             #[test]
             fn $type_to_check() {
                 let json = format!(
                     r#"{{
                         "id":"1",
                         "paging_token":"7",
+                        "transaction_successful":true,
+                        "source_account":"GAKLBGHNHFQ3BMUYG5KU4BEWO6EYQHZHAXEWC33W34PH2RBHZDSQBD75",
+                        "created_at":"2018-08-20T20:55:58Z",
                         "type_i":{},
                         "type":"{}",
                         "transaction_hash":"123"
@@ -30,7 +38,7 @@ mod errors_on_missing_fields_for_type {
                     format!("{}", result.unwrap_err()),
                     format!(
                         "Missing fields for {} operation.",
-                        stringify!($type_to_check)
+                        stringify!($reported_as)
                     )
                 );
             }
@@ -39,15 +47,27 @@ mod errors_on_missing_fields_for_type {
 
     assert_err_on_missing_fields!(create_account, 0);
     assert_err_on_missing_fields!(payment, 1);
-    assert_err_on_missing_fields!(path_payment, 2);
-    assert_err_on_missing_fields!(manage_offer, 3);
-    assert_err_on_missing_fields!(create_passive_offer, 4);
+    assert_err_on_missing_fields!(path_payment_strict_receive, 2);
+    assert_err_on_missing_fields!(manage_sell_offer, 3);
+    assert_err_on_missing_fields!(create_passive_sell_offer, 4);
     assert_err_on_missing_fields!(set_options, 5);
     assert_err_on_missing_fields!(change_trust, 6);
+    #[cfg(feature = "legacy-ops")]
     assert_err_on_missing_fields!(allow_trust, 7);
     assert_err_on_missing_fields!(account_merge, 8);
     // Inflation (id 9) is infallible as it has no fields.
     assert_err_on_missing_fields!(manage_data, 10);
+    assert_err_on_missing_fields!(invoke_host_function, 24);
+    assert_err_on_missing_fields!(extend_footprint_ttl, 25);
+    // Restore footprint (id 26) is infallible as it has no fields.
+
+    // The names from before protocol 11 are read as the modern kinds.
+    #[cfg(feature = "legacy-ops")]
+    assert_err_on_missing_fields!(path_payment, 2, path_payment_strict_receive);
+    #[cfg(feature = "legacy-ops")]
+    assert_err_on_missing_fields!(manage_offer, 3, manage_sell_offer);
+    #[cfg(feature = "legacy-ops")]
+    assert_err_on_missing_fields!(create_passive_offer, 4, create_passive_sell_offer);
 }
 
 #[test]
@@ -69,11 +89,13 @@ fn it_parses_account_merge_from_json() {
     }
 }
 
+#[cfg(feature = "legacy-ops")]
 fn allow_trust_json() -> &'static str {
     include_str!("../../../fixtures/operations/allow_trust.json")
 }
 
 #[test]
+#[cfg(feature = "legacy-ops")]
 fn it_parses_allow_trust_from_json() {
     let operation: Operation = serde_json::from_str(&allow_trust_json()).unwrap();
     assert!(operation.is_allow_trust());
@@ -87,7 +109,7 @@ fn it_parses_allow_trust_from_json() {
             account_details.trustor(),
             "GBXGQJWVLWOYHFLVTKWV5FGHA3LNYY2JQKM7OAJAUEQFU6LPCSEFVXON"
         );
-        assert_eq!(account_details.asset().code(), "USD");
+        assert_eq!(account_details.asset().code(), Some("USD"));
         assert_eq!(account_details.authorize(), true);
     } else {
         panic!("Did not generate allow trust kind");
@@ -112,7 +134,7 @@ fn it_parses_change_trust_from_json() {
             account_details.trustor(),
             "GDVXG2FMFFSUMMMBIUEMWPZAIU2FNCH7QNGJMWRXRD6K5FZK5KJS4DDR"
         );
-        assert_eq!(account_details.asset().code(), "CHP");
+        assert_eq!(account_details.asset().code(), Some("CHP"));
         assert_eq!(account_details.limit(), Amount::new(50_000_000));
     } else {
         panic!("Did not generate change trust kind");
@@ -147,7 +169,7 @@ fn it_parses_create_account_from_json() {
 }
 
 fn create_passive_offer_json() -> &'static str {
-    include_str!("../../../fixtures/operations/create_passive_offer.json")
+    include_str!("../../../fixtures/operations/create_passive_sell_offer.json")
 }
 
 #[test]
@@ -157,8 +179,8 @@ fn it_parses_a_create_passive_offer_from_json() {
     assert_eq!(operation.type_i(), 4);
     if let &OperationKind::CreatePassiveOffer(ref account_details) = operation.kind() {
         assert_eq!(account_details.offer_id(), 9);
-        assert_eq!(account_details.selling().code(), "XLM");
-        assert_eq!(account_details.buying().code(), "USD");
+        assert_eq!(account_details.selling().code(), None);
+        assert_eq!(account_details.buying().code(), Some("USD"));
         assert_eq!(account_details.amount(), Amount::new(112_782_700));
         assert_eq!(account_details.price_ratio().numerator(), 1);
         assert_eq!(account_details.price(), Amount::new(10_000_000));
@@ -167,17 +189,128 @@ fn it_parses_a_create_passive_offer_from_json() {
     }
 }
 
+#[cfg(feature = "legacy-ops")]
 fn inflation_json() -> &'static str {
     include_str!("../../../fixtures/operations/inflation.json")
 }
 
 #[test]
+#[cfg(feature = "legacy-ops")]
 fn it_parses_inflation_from_json() {
     let operation: Operation = serde_json::from_str(&inflation_json()).unwrap();
     assert!(operation.is_inflation());
     assert_eq!(operation.type_i(), 9);
 }
 
+#[test]
+#[cfg(not(feature = "legacy-ops"))]
+fn it_rejects_retired_operations_without_legacy_ops() {
+    for fixture in &[
+        include_str!("../../../fixtures/operations/allow_trust.json"),
+        include_str!("../../../fixtures/operations/inflation.json"),
+    ] {
+        let err = serde_json::from_str::<Operation>(fixture).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown operation type.");
+    }
+}
+
+#[test]
+#[cfg(feature = "legacy-ops")]
+fn it_parses_operations_by_their_names_before_protocol_11() {
+    let renamed = [
+        (manage_offer_json(), "manage_sell_offer", "manage_offer"),
+        (
+            create_passive_offer_json(),
+            "create_passive_sell_offer",
+            "create_passive_offer",
+        ),
+        (
+            path_payment_json(),
+            "path_payment_strict_receive",
+            "path_payment",
+        ),
+    ];
+    for &(json, modern, legacy) in &renamed {
+        let legacy_json = json.replace(
+            &format!(r#""type": "{}""#, modern),
+            &format!(r#""type": "{}""#, legacy),
+        );
+        let operation: Operation = serde_json::from_str(&legacy_json).unwrap();
+        let written = serde_json::to_value(&operation).unwrap();
+        assert_eq!(written["type"], modern);
+    }
+}
+
+fn extend_footprint_ttl_json() -> &'static str {
+    include_str!("../../../fixtures/operations/extend_footprint_ttl.json")
+}
+
+#[test]
+fn it_parses_extend_footprint_ttl_from_json() {
+    let operation: Operation = serde_json::from_str(&extend_footprint_ttl_json()).unwrap();
+    assert!(operation.is_extend_footprint_ttl());
+    assert_eq!(operation.type_i(), 25);
+    if let &OperationKind::ExtendFootprintTtl(ref details) = operation.kind() {
+        assert_eq!(details.extend_to(), 535_680);
+    } else {
+        panic!("Did not generate extend footprint ttl kind");
+    }
+}
+
+fn invoke_host_function_json() -> &'static str {
+    include_str!("../../../fixtures/operations/invoke_host_function.json")
+}
+
+#[test]
+fn it_parses_invoke_host_function_from_json() {
+    let operation: Operation = serde_json::from_str(&invoke_host_function_json()).unwrap();
+    assert!(operation.is_invoke_host_function());
+    assert_eq!(operation.type_i(), 24);
+    if let &OperationKind::InvokeHostFunction(ref details) = operation.kind() {
+        assert_eq!(
+            details.function(),
+            "HostFunctionTypeHostFunctionTypeInvokeContract"
+        );
+        assert_eq!(details.parameters().len(), 2);
+        assert_eq!(details.parameters()[1].value_type(), "Sym");
+        assert_eq!(details.parameters()[1].value(), "AAAADwAAAAh0cmFuc2Zlcg==");
+        assert_eq!(details.address(), "");
+        assert_eq!(details.salt(), "");
+
+        let change = &details.asset_balance_changes()[0];
+        assert_eq!(change.asset().code(), Some("USDC"));
+        assert_eq!(change.kind(), "transfer");
+        assert_eq!(
+            change.to(),
+            "CBQHNAXSI55GX2GN6D67GK7BHVPSLJUGZQEU7WJ5LKR5PNUCGLIMAO4K"
+        );
+        assert_eq!(change.amount(), Amount::new(100_000_000));
+    } else {
+        panic!("Did not generate invoke host function kind");
+    }
+}
+
+#[test]
+fn it_parses_invoke_host_function_without_optional_fields() {
+    let json = r#"{
+        "id":"1",
+        "paging_token":"1",
+        "transaction_successful":true,
+        "source_account":"GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM",
+        "created_at":"2023-11-15T22:41:31Z",
+        "type":"invoke_host_function",
+        "transaction_hash":"123",
+        "function":"HostFunctionTypeHostFunctionTypeUploadContractWasm"
+    }"#;
+    let operation: Operation = serde_json::from_str(json).unwrap();
+    if let &OperationKind::InvokeHostFunction(ref details) = operation.kind() {
+        assert!(details.parameters().is_empty());
+        assert!(details.asset_balance_changes().is_empty());
+    } else {
+        panic!("Did not generate invoke host function kind");
+    }
+}
+
 fn manage_data_json() -> &'static str {
     include_str!("../../../fixtures/operations/manage_data.json")
 }
@@ -196,7 +329,7 @@ fn it_parses_manage_data_from_json() {
 }
 
 fn manage_offer_json() -> &'static str {
-    include_str!("../../../fixtures/operations/manage_offer.json")
+    include_str!("../../../fixtures/operations/manage_sell_offer.json")
 }
 
 #[test]
@@ -206,8 +339,8 @@ fn it_parses_a_manage_offer_from_json() {
     assert_eq!(operation.type_i(), 3);
     if let &OperationKind::ManageOffer(ref account_details) = operation.kind() {
         assert_eq!(account_details.offer_id(), 8);
-        assert_eq!(account_details.selling().code(), "YEN");
-        assert_eq!(account_details.buying().code(), "CHP");
+        assert_eq!(account_details.selling().code(), Some("YEN"));
+        assert_eq!(account_details.buying().code(), Some("CHP"));
         assert_eq!(account_details.amount(), Amount::new(1_000_000_000));
         assert_eq!(account_details.price_ratio().numerator(), 2);
         assert_eq!(account_details.price(), Amount::new(20_000_000));
@@ -217,7 +350,7 @@ fn it_parses_a_manage_offer_from_json() {
 }
 
 fn path_payment_json() -> &'static str {
-    include_str!("../../../fixtures/operations/path_payment.json")
+    include_str!("../../../fixtures/operations/path_payment_strict_receive.json")
 }
 
 #[test]
@@ -234,12 +367,12 @@ fn it_parses_a_path_payment_from_json() {
             account_details.to(),
             "GA5WBPYA5Y4WAEHXWR2UKO2UO4BUGHUQ74EUPKON2QHV4WRHOIRNKKH2"
         );
-        assert_eq!(account_details.destination_asset().code(), "EUR");
+        assert_eq!(account_details.destination_asset().code(), Some("EUR"));
         assert_eq!(
             account_details.destination_amount(),
             Amount::new(100_000_000)
         );
-        assert_eq!(account_details.source_asset().code(), "USD");
+        assert_eq!(account_details.source_asset().code(), Some("USD"));
         assert_eq!(account_details.source_max(), Amount::new(100_000_000));
     } else {
         panic!("Did not generate path payment kind");
@@ -264,13 +397,72 @@ fn it_parses_a_payment_from_json() {
             account_details.to(),
             "GCEZWKCA5VLDNRLN3RPRJMRZOX3Z6G5CHCGSNFHEYVXM3XOJMDS674JZ"
         );
-        assert_eq!(account_details.asset().code(), "XLM");
+        assert_eq!(account_details.asset().code(), None);
         assert_eq!(account_details.amount(), Amount::new(2_000_000_000));
     } else {
         panic!("Did not generate payment kind");
     }
 }
 
+#[test]
+fn it_parses_a_payment_to_a_muxed_account() {
+    let to: AccountId = "GCEZWKCA5VLDNRLN3RPRJMRZOX3Z6G5CHCGSNFHEYVXM3XOJMDS674JZ".into();
+    let to_muxed = MuxedAccount::new(&to, Some(42)).to_string();
+    let mut json: serde_json::Value = serde_json::from_str(&payment_json()).unwrap();
+    json["to_muxed"] = serde_json::Value::from(to_muxed.clone());
+    json["to_muxed_id"] = serde_json::Value::from("42");
+
+    let operation: Operation = serde_json::from_value(json).unwrap();
+    if let &OperationKind::Payment(ref payment) = operation.kind() {
+        assert_eq!(payment.to(), &to);
+        assert_eq!(payment.to_muxed().unwrap().account_id(), to);
+        assert_eq!(payment.to_muxed_id(), Some(42));
+        assert_eq!(payment.from_muxed(), None);
+        assert_eq!(payment.from_muxed_id(), None);
+    } else {
+        panic!("Did not generate payment kind");
+    }
+
+    let json = serde_json::to_value(&operation).unwrap();
+    assert_eq!(json["to_muxed"], serde_json::Value::from(to_muxed));
+    assert_eq!(json["to_muxed_id"], serde_json::Value::from("42"));
+    assert!(json["from_muxed"].is_null());
+}
+
+#[test]
+fn it_fails_to_parse_a_payment_to_a_malformed_muxed_account() {
+    let mut json: serde_json::Value = serde_json::from_str(&payment_json()).unwrap();
+    json["to_muxed"] = serde_json::Value::from("MABC");
+    assert!(serde_json::from_value::<Operation>(json).is_err());
+}
+
+#[test]
+fn it_parses_the_fields_common_to_every_operation() {
+    let operation: Operation = serde_json::from_str(&payment_json()).unwrap();
+    assert_eq!(operation.id(), 58402965295104);
+    assert_eq!(operation.paging_token(), "58402965295104");
+    assert_eq!(
+        operation.transaction_hash(),
+        "f0222a5421ccfc4e612f11d9ff95755fbb6300df7c61442d990d498a4cd01c92"
+    );
+    assert_eq!(
+        operation.source_account(),
+        "GAKLBGHNHFQ3BMUYG5KU4BEWO6EYQHZHAXEWC33W34PH2RBHZDSQBD75"
+    );
+    assert_eq!(
+        operation.created_at(),
+        Utc.ymd(2018, 8, 20).and_hms(20, 55, 58)
+    );
+    assert!(operation.transaction_successful());
+
+    let json = payment_json().replace(
+        "\"transaction_successful\": true",
+        "\"transaction_successful\": false",
+    );
+    let operation: Operation = serde_json::from_str(&json).unwrap();
+    assert!(!operation.transaction_successful());
+}
+
 fn set_options_json() -> &'static str {
     include_str!("../../../fixtures/operations/set_options.json")
 }
@@ -300,3 +492,65 @@ fn it_parses_a_set_options_from_json() {
         panic!("Did not generate set options kind");
     }
 }
+
+fn restore_footprint_json() -> &'static str {
+    include_str!("../../../fixtures/operations/restore_footprint.json")
+}
+
+#[test]
+fn it_parses_restore_footprint_from_json() {
+    let operation: Operation = serde_json::from_str(&restore_footprint_json()).unwrap();
+    assert!(operation.is_restore_footprint());
+    assert_eq!(operation.type_i(), 26);
+    assert_eq!(operation.kind_name(), "Restore Footprint");
+}
+
+#[test]
+fn it_categorizes_operations() {
+    let category = |json: &str| {
+        let operation: Operation = serde_json::from_str(json).unwrap();
+        (operation.category(), operation.is_balance_affecting())
+    };
+    assert_eq!(category(payment_json()), (OperationCategory::Payment, true));
+    assert_eq!(
+        category(account_merge_json()),
+        (OperationCategory::Payment, true)
+    );
+    assert_eq!(
+        category(manage_offer_json()),
+        (OperationCategory::Trade, true)
+    );
+    assert_eq!(
+        category(set_options_json()),
+        (OperationCategory::AccountConfig, false)
+    );
+    assert_eq!(
+        category(manage_data_json()),
+        (OperationCategory::AccountConfig, false)
+    );
+    assert_eq!(
+        category(change_trust_json()),
+        (OperationCategory::Trust, false)
+    );
+    assert_eq!(
+        category(invoke_host_function_json()),
+        (OperationCategory::Soroban, true)
+    );
+    assert_eq!(
+        category(restore_footprint_json()),
+        (OperationCategory::Soroban, false)
+    );
+}
+
+#[test]
+fn it_gives_the_details_of_one_kind() {
+    let operation: Operation = serde_json::from_str(&payment_json()).unwrap();
+    let payment = operation.as_payment().unwrap();
+    assert_eq!(payment.amount(), Amount::new(2_000_000_000));
+    assert!(operation.as_path_payment().is_none());
+    assert!(operation.as_manage_offer().is_none());
+
+    let operation: Operation = serde_json::from_str(&manage_data_json()).unwrap();
+    assert!(operation.as_manage_data().is_some());
+    assert!(operation.as_payment().is_none());
+}
@@ -5,7 +5,7 @@ use resources::{offer::OfferSummary, AssetIdentifier};
 /// The asset pairs are refered to as a base and counter.
 ///
 /// <https://www.stellar.org/developers/horizon/reference/resources/orderbook.html>
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct Orderbook {
     bids: Vec<OfferSummary>,
     asks: Vec<OfferSummary>,
@@ -14,6 +14,21 @@ pub struct Orderbook {
 }
 
 impl Orderbook {
+    /// Creates a new orderbook for the base and counter assets.
+    pub fn new(
+        bids: Vec<OfferSummary>,
+        asks: Vec<OfferSummary>,
+        base: AssetIdentifier,
+        counter: AssetIdentifier,
+    ) -> Orderbook {
+        Orderbook {
+            bids,
+            asks,
+            base,
+            counter,
+        }
+    }
+
     /// Returns an array of offers to purchase the base asset
     /// in exchange for the counter asset
     pub fn bids(&self) -> &Vec<OfferSummary> {
@@ -61,7 +76,7 @@ mod orderbook_tests {
             orderbook.asks().first().unwrap().amount(),
             Amount::new(2_384_804_125)
         );
-        assert_eq!(orderbook.base().code(), "XLM".to_string());
-        assert_eq!(orderbook.counter().code(), "FOO".to_string());
+        assert_eq!(orderbook.base().code(), None);
+        assert_eq!(orderbook.counter().code(), Some("FOO"));
     }
 }
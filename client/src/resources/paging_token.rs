@@ -0,0 +1,108 @@
+use resources::{Effect, Ledger, Offer, Operation, Trade, Transaction, TransactionHeader};
+use std::cmp::Ordering;
+
+/// A resource that horizon pages through by a paging token, so that records
+/// from different requests can be told apart and put in order.
+///
+/// # Examples
+///
+/// ```
+/// use stellar_client::resources::{Operation, PagingToken, Resource};
+///
+/// let operation = Operation::from_json_str(
+///     include_str!("../../fixtures/operations/payment.json"),
+/// ).unwrap();
+/// assert_eq!(PagingToken::paging_token(&operation), "58402965295104");
+/// ```
+pub trait PagingToken {
+    /// The paging token of the record, which is unique among the records of
+    /// its endpoint.
+    fn paging_token(&self) -> &str;
+
+    /// Compares the paging tokens of two records.
+    ///
+    /// The paging tokens of the history endpoints are the id of an
+    /// operation, optionally followed by a dash and an index, and those ids
+    /// grow with the ledger. Comparing them as numbers therefore puts records
+    /// in the order they happened in, which comparing them as strings does
+    /// not. Tokens that aren't numbers, such as those of accounts, come after
+    /// the numeric ones and are compared as strings.
+    fn cmp_paging_token(&self, other: &Self) -> Ordering
+    where
+        Self: Sized,
+    {
+        let (a, b) = (self.paging_token(), other.paging_token());
+        position(a).cmp(&position(b)).then_with(|| a.cmp(b))
+    }
+}
+
+/// The id and index a paging token is made of, behind a flag that is set
+/// when it isn't one of the numeric tokens of the history endpoints.
+fn position(paging_token: &str) -> (bool, u64, u64) {
+    let mut parts = paging_token.splitn(2, '-');
+    let id = parts.next().and_then(|id| id.parse().ok());
+    let index = match parts.next() {
+        Some(index) => index.parse().ok(),
+        None => Some(0),
+    };
+    match (id, index) {
+        (Some(id), Some(index)) => (false, id, index),
+        _ => (true, 0, 0),
+    }
+}
+
+impl PagingToken for Effect {
+    fn paging_token(&self) -> &str {
+        Effect::paging_token(self)
+    }
+}
+
+impl PagingToken for Ledger {
+    fn paging_token(&self) -> &str {
+        Ledger::paging_token(self)
+    }
+}
+
+impl PagingToken for Offer {
+    fn paging_token(&self) -> &str {
+        Offer::paging_token(self)
+    }
+}
+
+impl PagingToken for Operation {
+    fn paging_token(&self) -> &str {
+        Operation::paging_token(self)
+    }
+}
+
+impl PagingToken for Trade {
+    fn paging_token(&self) -> &str {
+        Trade::paging_token(self)
+    }
+}
+
+impl PagingToken for Transaction {
+    fn paging_token(&self) -> &str {
+        Transaction::paging_token(self)
+    }
+}
+
+impl PagingToken for TransactionHeader {
+    fn paging_token(&self) -> &str {
+        TransactionHeader::paging_token(self)
+    }
+}
+
+#[cfg(test)]
+mod paging_token_tests {
+    use super::*;
+
+    #[test]
+    fn it_orders_numeric_tokens_by_value() {
+        assert!(position("9") < position("10"));
+        assert!(position("12884905984") < position("12884905984-1"));
+        assert!(position("12884905984-2") < position("12884905984-10"));
+        assert!(position("12884905985") < position("GAKLBGHN"));
+        assert_eq!(position("now"), position("GAKLBGHN"));
+    }
+}
@@ -1,5 +1,5 @@
 use super::{amount::Amount, asset::AssetIdentifier};
-use serde::{de, Deserialize, Deserializer};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 /// A path resource contains information about a payment path. A path can be used by code to
 /// populate necessary fields on path payment operation, such as path and sendMax.  The
@@ -57,7 +57,7 @@ impl PaymentPath {
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 struct IntermediatePaymentPath {
     path: Vec<AssetIdentifier>,
     destination_amount: Amount,
@@ -96,6 +96,26 @@ impl<'de> Deserialize<'de> for PaymentPath {
     }
 }
 
+impl Serialize for PaymentPath {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let rep = IntermediatePaymentPath {
+            path: self.path.clone(),
+            destination_amount: self.destination_amount,
+            destination_asset_type: self.destination_asset.asset_type().to_string(),
+            destination_asset_code: self.destination_asset.asset_code(),
+            destination_asset_issuer: self.destination_asset.asset_issuer(),
+            source_amount: self.source_amount,
+            source_asset_type: self.source_asset.asset_type().to_string(),
+            source_asset_code: self.source_asset.asset_code(),
+            source_asset_issuer: self.source_asset.asset_issuer(),
+        };
+        rep.serialize(s)
+    }
+}
+
 #[cfg(test)]
 mod payment_path_tests {
     use super::*;
@@ -108,10 +128,10 @@ mod payment_path_tests {
     #[test]
     fn it_deserializes_payment_paths_from_json() {
         let payment_path: PaymentPath = serde_json::from_str(&payment_path_json()).unwrap();
-        assert_eq!(payment_path.path().first().unwrap().code(), "1");
+        assert_eq!(payment_path.path().first().unwrap().code(), Some("1"));
         assert_eq!(payment_path.source_amount(), &Amount::new(200_000_000));
         assert_eq!(payment_path.destination_amount(), &Amount::new(200_000_000));
-        assert_eq!(payment_path.destination_asset().code(), "EUR");
-        assert_eq!(payment_path.source_asset().code(), "USD");
+        assert_eq!(payment_path.destination_asset().code(), Some("EUR"));
+        assert_eq!(payment_path.source_asset().code(), Some("USD"));
     }
 }
@@ -0,0 +1,135 @@
+use resources::Resource;
+use serde::de::{self, Deserialize, DeserializeOwned, Deserializer};
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use serde_path_to_error;
+use std::ops::Deref;
+
+/// A resource along with the json that it was parsed from.
+///
+/// Horizon sometimes adds fields before this crate models them. Parsing into
+/// a `Raw` keeps the whole document around so those fields can be read from
+/// the json in the meantime, while the parsed resource is still available
+/// through `Deref`. Use `sync::Client::request_raw` to fetch one, or parse a
+/// page of `Records<Raw<T>>` to keep the json of every record.
+///
+/// This is only available with the `raw-json` feature enabled.
+///
+/// # Examples
+///
+/// ```
+/// use stellar_client::resources::{Asset, Raw, Resource};
+///
+/// let json = r#"{
+///     "asset_type": "credit_alphanum4",
+///     "asset_code": "USD",
+///     "asset_issuer": "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG",
+///     "amount": "100.0",
+///     "num_accounts": 3,
+///     "flags": { "auth_required": false, "auth_revocable": false },
+///     "liquidity_pools_amount": "5.0"
+/// }"#;
+/// let asset = Raw::<Asset>::from_json_str(json).unwrap();
+/// assert_eq!(asset.code(), "USD");
+/// assert_eq!(asset.raw()["liquidity_pools_amount"], "5.0");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Raw<T> {
+    resource: T,
+    raw: Value,
+}
+
+impl<T> Raw<T> {
+    /// The json document the resource was parsed from.
+    pub fn raw(&self) -> &Value {
+        &self.raw
+    }
+
+    /// The parsed resource.
+    pub fn resource(&self) -> &T {
+        &self.resource
+    }
+
+    /// Splits into the parsed resource and its json.
+    pub fn into_parts(self) -> (T, Value) {
+        (self.resource, self.raw)
+    }
+}
+
+impl<T> Deref for Raw<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.resource
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Raw<T>
+where
+    T: DeserializeOwned,
+{
+    fn deserialize<D>(d: D) -> Result<Raw<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = Value::deserialize(d)?;
+        // The resource is parsed from the buffered json rather than the
+        // original document, so the path to a failure is added by hand.
+        let resource = serde_path_to_error::deserialize(&raw)
+            .map_err(|err| de::Error::custom(format!("{} at `{}`", err.inner(), err.path())))?;
+        Ok(Raw { resource, raw })
+    }
+}
+
+/// Serializes as the json the resource was parsed from, including the
+/// fields this crate doesn't model.
+impl<T> Serialize for Raw<T> {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.raw.serialize(s)
+    }
+}
+
+impl<T> Resource for Raw<T> where T: Resource {}
+
+#[cfg(test)]
+mod raw_tests {
+    use super::*;
+    use resources::{Ledger, Operation};
+    use serde_json;
+
+    #[test]
+    fn it_keeps_the_json_alongside_the_resource() {
+        let json = include_str!("../../fixtures/ledger.json");
+        let ledger = Raw::<Ledger>::from_json_str(json).unwrap();
+        assert_eq!(ledger.raw()["sequence"], ledger.sequence());
+        assert_eq!(
+            ledger.resource().hash(),
+            ledger.raw()["hash"].as_str().unwrap()
+        );
+
+        let (ledger, raw) = ledger.into_parts();
+        assert_eq!(raw, serde_json::from_str::<Value>(json).unwrap());
+        assert_eq!(raw["id"].as_str().unwrap(), ledger.id());
+    }
+
+    #[test]
+    fn it_writes_the_json_it_was_parsed_from() {
+        let json = include_str!("../../fixtures/ledger.json");
+        let ledger = Raw::<Ledger>::from_json_str(json).unwrap();
+        assert_eq!(
+            serde_json::to_value(&ledger).unwrap(),
+            serde_json::from_str::<Value>(json).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_reports_where_the_resource_failed_to_parse() {
+        let json = include_str!("../../fixtures/operations/payment.json")
+            .replace("\"amount\": \"200.0\"", "\"amount\": 200");
+        let error = serde_json::from_str::<Raw<Operation>>(&json).unwrap_err();
+        assert!(error.to_string().contains("at `amount`"));
+    }
+}
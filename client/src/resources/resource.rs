@@ -0,0 +1,536 @@
+use resources::{
+    Account, Asset, ClaimableBalance, Datum, Effect, Health, Ledger, Offer, OfferSummary,
+    Operation, Orderbook, PaymentPath, Root, SubmittedTransaction, Trade, TradeAggregation,
+    Transaction, TransactionHeader,
+};
+#[cfg(feature = "validate")]
+use serde::de;
+use serde::de::DeserializeOwned;
+use serde_json;
+use serde_path_to_error;
+use std::any::type_name;
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+
+/// A resource that horizon returns as a json document.
+///
+/// Parsing through this trait rather than with `serde_json` directly keeps
+/// track of where in the document a failure happened, so that a bad response
+/// reports the offending field instead of just "missing field".
+///
+/// # Examples
+///
+/// ```
+/// use stellar_client::resources::{Asset, Resource};
+///
+/// let error = Asset::from_json_str(r#"{ "asset_type": "native", "amount": 5 }"#).unwrap_err();
+/// assert_eq!(error.path(), "amount");
+/// ```
+pub trait Resource: DeserializeOwned {
+    /// Parses the resource from a json string.
+    fn from_json_str(json: &str) -> Result<Self, ParseResourceError> {
+        from_json_str(json)
+    }
+
+    /// Parses the resource and returns the paths of every field in the json
+    /// that the resource does not model. Comparing recorded horizon responses
+    /// against this list shows when horizon has started sending something new.
+    #[cfg(feature = "validate")]
+    fn unknown_fields(json: &str) -> Result<Vec<String>, ParseResourceError> {
+        let (_, unknown): (Self, _) =
+            from_deserializer_tracking_unknown(serde_json::Deserializer::from_str(json))?;
+        Ok(unknown)
+    }
+}
+
+/// How closely a horizon response has to match the resource it is parsed
+/// into. Set it on a client with `ClientBuilder::with_strictness`.
+///
+/// Which variants exist depends on the enabled features, so matches on it
+/// need a wildcard arm.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Strictness {
+    /// Fields that the resource does not model are ignored, so that horizon
+    /// adding a field doesn't break anything. This is the default.
+    #[default]
+    Lenient,
+    /// A field that the resource does not model fails the parse. This
+    /// catches horizon's schema drifting away from the resources while
+    /// developing the library or checking fixtures. Only available with the
+    /// `validate` feature enabled.
+    #[cfg(feature = "validate")]
+    Strict,
+}
+
+impl Resource for Account {}
+impl Resource for Asset {}
+impl Resource for ClaimableBalance {}
+impl Resource for Datum {}
+impl Resource for Effect {}
+impl Resource for Health {}
+impl Resource for Root {}
+impl Resource for Ledger {}
+impl Resource for Offer {}
+impl Resource for OfferSummary {}
+impl Resource for Operation {}
+impl Resource for Orderbook {}
+impl Resource for PaymentPath {}
+impl Resource for SubmittedTransaction {}
+impl Resource for Trade {}
+impl Resource for TradeAggregation {}
+impl Resource for Transaction {}
+impl Resource for TransactionHeader {}
+
+/// Parses any json document while tracking the path to a failure.
+pub(crate) fn from_json_str<T>(json: &str) -> Result<T, ParseResourceError>
+where
+    T: DeserializeOwned,
+{
+    from_deserializer(serde_json::Deserializer::from_str(json)).map_err(|err| {
+        let excerpt = Excerpt::of(json.as_bytes());
+        err.with_body(excerpt.text())
+    })
+}
+
+/// Parses a json document as it is read, without buffering the whole of it
+/// in memory first.
+pub(crate) fn from_json_reader<R, T>(reader: R) -> Result<T, ParseResourceError>
+where
+    R: io::Read,
+    T: DeserializeOwned,
+{
+    let mut excerpt = Excerpt::new(reader);
+    from_deserializer(serde_json::Deserializer::from_reader(&mut excerpt))
+        .map_err(|err| err.with_body(excerpt.finish()))
+}
+
+/// Parses a json document as it is read, like `from_json_reader`, but with
+/// the given strictness.
+pub(crate) fn from_json_reader_with<R, T>(
+    reader: R,
+    strictness: Strictness,
+) -> Result<T, ParseResourceError>
+where
+    R: io::Read,
+    T: DeserializeOwned,
+{
+    match strictness {
+        Strictness::Lenient => from_json_reader(reader),
+        #[cfg(feature = "validate")]
+        Strictness::Strict => {
+            let mut excerpt = Excerpt::new(reader);
+            let de = serde_json::Deserializer::from_reader(&mut excerpt);
+            let result = from_deserializer_tracking_unknown(de).and_then(|(value, unknown)| {
+                match unknown.into_iter().next() {
+                    Some(path) => Err(ParseResourceError::new::<T>(
+                        path,
+                        de::Error::custom("unknown field"),
+                    )),
+                    None => Ok(value),
+                }
+            });
+            result.map_err(|err| err.with_body(excerpt.finish()))
+        }
+    }
+}
+
+fn from_deserializer<'de, R, T>(
+    mut de: serde_json::Deserializer<R>,
+) -> Result<T, ParseResourceError>
+where
+    R: serde_json::de::Read<'de>,
+    T: DeserializeOwned,
+{
+    let value = serde_path_to_error::deserialize(&mut de)
+        .map_err(|err| ParseResourceError::new::<T>(err.path().to_string(), err.into_inner()))?;
+    de.end()
+        .map_err(|inner| ParseResourceError::new::<T>(".".to_string(), inner))?;
+    Ok(value)
+}
+
+/// Parses a json document along with the paths of every field that the
+/// resource does not model.
+#[cfg(feature = "validate")]
+fn from_deserializer_tracking_unknown<'de, R, T>(
+    mut de: serde_json::Deserializer<R>,
+) -> Result<(T, Vec<String>), ParseResourceError>
+where
+    R: serde_json::de::Read<'de>,
+    T: DeserializeOwned,
+{
+    use serde_ignored;
+
+    let mut unknown = Vec::new();
+    let mut track = serde_path_to_error::Track::new();
+    let result = {
+        let de = serde_path_to_error::Deserializer::new(&mut de, &mut track);
+        serde_ignored::deserialize(de, |path| unknown.push(path.to_string()))
+    };
+    let value =
+        result.map_err(|inner| ParseResourceError::new::<T>(track.path().to_string(), inner))?;
+    de.end()
+        .map_err(|inner| ParseResourceError::new::<T>(".".to_string(), inner))?;
+    Ok((value, unknown))
+}
+
+/// The most of a document that a parse error keeps.
+const EXCERPT_LEN: usize = 512;
+
+/// Reads a document while keeping its start, so that a parse error can show
+/// what was being parsed.
+struct Excerpt<R> {
+    inner: R,
+    kept: Vec<u8>,
+    truncated: bool,
+}
+
+impl<R> Excerpt<R>
+where
+    R: io::Read,
+{
+    fn new(inner: R) -> Excerpt<R> {
+        Excerpt {
+            inner,
+            kept: Vec::new(),
+            truncated: false,
+        }
+    }
+
+    /// Reads on from where parsing stopped until the excerpt is full, so
+    /// that it shows what came after the failure as well.
+    fn finish(&mut self) -> String {
+        let mut rest = [0; 64];
+        while !self.truncated {
+            match io::Read::read(self, &mut rest) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => (),
+            }
+        }
+        self.text()
+    }
+
+    fn text(&self) -> String {
+        let text = String::from_utf8_lossy(&self.kept);
+        if self.truncated {
+            format!("{}...", text)
+        } else {
+            text.into_owned()
+        }
+    }
+}
+
+impl<'a> Excerpt<&'a [u8]> {
+    /// Keeps the start of a document that is already in memory.
+    fn of(document: &'a [u8]) -> Excerpt<&'a [u8]> {
+        let len = document.len().min(EXCERPT_LEN);
+        Excerpt {
+            inner: document,
+            kept: document[..len].to_vec(),
+            truncated: document.len() > len,
+        }
+    }
+}
+
+impl<R> io::Read for Excerpt<R>
+where
+    R: io::Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        let room = EXCERPT_LEN - self.kept.len();
+        self.kept.extend_from_slice(&buf[..read.min(room)]);
+        self.truncated |= read > room;
+        Ok(read)
+    }
+}
+
+/// An error raised when a json document could not be parsed into a resource.
+#[derive(Debug)]
+pub struct ParseResourceError {
+    path: String,
+    inner: serde_json::Error,
+    type_name: &'static str,
+    body: Option<String>,
+    request_id: Option<String>,
+}
+
+impl ParseResourceError {
+    fn new<T>(path: String, inner: serde_json::Error) -> ParseResourceError {
+        ParseResourceError {
+            path,
+            inner,
+            type_name: type_name::<T>(),
+            body: None,
+            request_id: None,
+        }
+    }
+
+    fn with_body(mut self, body: String) -> ParseResourceError {
+        self.body = Some(body);
+        self
+    }
+
+    /// The path to the field that failed to parse, such as `balances[0].limit`.
+    /// A path of `.` means the failure was in the document itself.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The underlying json error.
+    pub fn inner(&self) -> &serde_json::Error {
+        &self.inner
+    }
+
+    /// The name of the type the document was being parsed into, such as
+    /// `stellar_client::resources::account::Account`.
+    pub fn type_name(&self) -> &str {
+        self.type_name
+    }
+
+    /// The start of the document, up to the first 512 bytes, with `...`
+    /// on the end when there was more of it read.
+    pub fn body(&self) -> Option<&str> {
+        self.body.as_ref().map(String::as_str)
+    }
+
+    /// The `X-Request-Id` of the request the document was the response to,
+    /// when it came from the sync client.
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    pub(crate) fn with_request_id(mut self, request_id: &str) -> ParseResourceError {
+        self.request_id = Some(request_id.to_string());
+        self
+    }
+}
+
+impl fmt::Display for ParseResourceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at `{}` parsing {}",
+            self.inner, self.path, self.type_name
+        )?;
+        if let Some(ref body) = self.body {
+            write!(f, " from {}", body)?;
+        }
+        Ok(())
+    }
+}
+
+impl StdError for ParseResourceError {
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+}
+
+#[cfg(test)]
+mod resource_tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_a_resource() {
+        let asset = Asset::from_json_str(include_str!("../../fixtures/asset.json")).unwrap();
+        assert_eq!(asset.code(), "USD");
+    }
+
+    #[test]
+    fn it_reports_the_path_to_a_missing_field() {
+        let json = include_str!("../../fixtures/account.json").replace("\"balance\":", "\"nope\":");
+        let error = Account::from_json_str(&json).unwrap_err();
+        assert_eq!(error.path(), "balances[0]");
+        assert!(error.to_string().contains("missing field `balance`"));
+    }
+
+    #[test]
+    fn it_reports_the_path_to_a_badly_typed_field() {
+        let json = include_str!("../../fixtures/ledger.json")
+            .replace("\"operation_count\": 0", "\"operation_count\": \"zero\"");
+        let error = Ledger::from_json_str(&json).unwrap_err();
+        assert_eq!(error.path(), "operation_count");
+    }
+
+    #[test]
+    fn it_keeps_the_start_of_the_document() {
+        let json = include_str!("../../fixtures/account.json").replace("\"balance\":", "\"nope\":");
+        let error = Account::from_json_str(&json).unwrap_err();
+        assert!(error.type_name().ends_with("::Account"));
+        let body = error.body().unwrap();
+        assert!(json.starts_with(body.trim_end_matches("...")));
+        assert_eq!(body.len(), EXCERPT_LEN + 3);
+
+        let error = from_json_reader::<_, Account>(json.as_bytes()).unwrap_err();
+        assert_eq!(error.body(), Some(body));
+        assert!(error
+            .to_string()
+            .contains(&format!("parsing {} from {{", error.type_name())));
+    }
+
+    #[test]
+    fn it_parses_from_a_reader() {
+        let json = include_str!("../../fixtures/asset.json");
+        let asset: Asset = from_json_reader(json.as_bytes()).unwrap();
+        assert_eq!(asset.code(), "USD");
+
+        let json = json.replace("\"num_accounts\": 91547871", "\"num_accounts\": -1");
+        let error = from_json_reader::<_, Asset>(json.as_bytes()).unwrap_err();
+        assert_eq!(error.path(), "num_accounts");
+    }
+
+    #[test]
+    fn it_rejects_trailing_characters() {
+        let json = format!("{} }}", include_str!("../../fixtures/asset.json"));
+        let error = Asset::from_json_str(&json).unwrap_err();
+        assert_eq!(error.path(), ".");
+    }
+
+    #[cfg(feature = "validate")]
+    #[test]
+    fn it_lists_fields_the_resource_does_not_model() {
+        let unknown = Asset::unknown_fields(include_str!("../../fixtures/asset.json")).unwrap();
+        assert_eq!(unknown, vec!["paging_token".to_string()]);
+    }
+
+    #[test]
+    fn it_ignores_unknown_fields_when_lenient() {
+        let json = include_str!("../../fixtures/asset.json");
+        let asset: Asset = from_json_reader_with(json.as_bytes(), Strictness::Lenient).unwrap();
+        assert_eq!(asset.code(), "USD");
+    }
+
+    #[cfg(feature = "validate")]
+    #[test]
+    fn it_rejects_unknown_fields_when_strict() {
+        let json = include_str!("../../fixtures/asset.json");
+        let error =
+            from_json_reader_with::<_, Asset>(json.as_bytes(), Strictness::Strict).unwrap_err();
+        assert_eq!(error.path(), "paging_token");
+        assert!(error.to_string().starts_with("unknown field"));
+
+        let json: Vec<_> = json
+            .lines()
+            .filter(|line| !line.contains("paging_token"))
+            .collect();
+        let json = json.join("\n");
+        assert!(from_json_reader_with::<_, Asset>(json.as_bytes(), Strictness::Strict).is_ok());
+    }
+}
+
+/// Every recorded horizon sample should parse cleanly into its resource, and
+/// what the resource writes back out should parse into the same resource.
+#[cfg(test)]
+mod schema_sample_tests {
+    use super::*;
+
+    macro_rules! samples {
+        ($resource:ty, $($path:expr),*) => {
+            $(
+                let resource = <$resource>::from_json_str(include_str!($path))
+                    .unwrap_or_else(|err| panic!("{} failed to parse: {}", $path, err));
+                let written = serde_json::to_value(&resource).unwrap();
+                let reread = <$resource>::from_json_str(&written.to_string())
+                    .unwrap_or_else(|err| panic!("{} failed to read back: {}", $path, err));
+                assert_eq!(
+                    serde_json::to_value(&reread).unwrap(),
+                    written,
+                    "{} changed on the way back",
+                    $path
+                );
+            )*
+        };
+    }
+
+    #[test]
+    fn it_parses_every_resource_sample() {
+        samples!(
+            Account,
+            "../../fixtures/account.json",
+            "../../fixtures/account_with_pool_shares.json"
+        );
+        samples!(
+            Asset,
+            "../../fixtures/asset.json",
+            "../../fixtures/native_asset.json"
+        );
+        samples!(ClaimableBalance, "../../fixtures/claimable_balance.json");
+        samples!(Health, "../../fixtures/health.json");
+        samples!(Ledger, "../../fixtures/ledger.json");
+        samples!(Offer, "../../fixtures/offer.json");
+        samples!(OfferSummary, "../../fixtures/offer_summary.json");
+        samples!(Orderbook, "../../fixtures/orderbook.json");
+        samples!(PaymentPath, "../../fixtures/payment_path.json");
+        samples!(Root, "../../fixtures/root.json");
+        samples!(
+            SubmittedTransaction,
+            "../../fixtures/submitted_transaction.json"
+        );
+        samples!(Trade, "../../fixtures/trade.json");
+        samples!(TradeAggregation, "../../fixtures/trade_aggregation.json");
+        samples!(
+            Transaction,
+            "../../fixtures/transactions/transaction_memo_none.json",
+            "../../fixtures/transactions/transaction_memo_text.json",
+            "../../fixtures/transactions/transaction_memo_id.json",
+            "../../fixtures/transactions/transaction_memo_hash.json",
+            "../../fixtures/transactions/transaction_memo_return.json"
+        );
+    }
+
+    #[test]
+    fn it_parses_every_operation_sample() {
+        samples!(
+            Operation,
+            "../../fixtures/operations/create_account.json",
+            "../../fixtures/operations/payment.json",
+            "../../fixtures/operations/path_payment_strict_receive.json",
+            "../../fixtures/operations/manage_sell_offer.json",
+            "../../fixtures/operations/create_passive_sell_offer.json",
+            "../../fixtures/operations/set_options.json",
+            "../../fixtures/operations/change_trust.json",
+            "../../fixtures/operations/account_merge.json",
+            "../../fixtures/operations/manage_data.json",
+            "../../fixtures/operations/invoke_host_function.json",
+            "../../fixtures/operations/extend_footprint_ttl.json",
+            "../../fixtures/operations/restore_footprint.json"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "legacy-ops")]
+    fn it_parses_every_legacy_operation_sample() {
+        samples!(
+            Operation,
+            "../../fixtures/operations/allow_trust.json",
+            "../../fixtures/operations/inflation.json"
+        );
+    }
+
+    #[test]
+    fn it_parses_every_effect_sample() {
+        samples!(
+            Effect,
+            "../../fixtures/effects/account_created.json",
+            "../../fixtures/effects/account_credited.json",
+            "../../fixtures/effects/account_debited.json",
+            "../../fixtures/effects/account_removed.json",
+            "../../fixtures/effects/account_threshold_updated.json",
+            "../../fixtures/effects/account_home_domain_updated.json",
+            "../../fixtures/effects/account_flags_updated.json",
+            "../../fixtures/effects/signer_created.json",
+            "../../fixtures/effects/signer_removed.json",
+            "../../fixtures/effects/signer_updated.json",
+            "../../fixtures/effects/trustline_created.json",
+            "../../fixtures/effects/trustline_removed.json",
+            "../../fixtures/effects/trustline_updated.json",
+            "../../fixtures/effects/trustline_authorized.json",
+            "../../fixtures/effects/trustline_deauthorized.json",
+            "../../fixtures/effects/trade.json",
+            "../../fixtures/effects/data_created.json",
+            "../../fixtures/effects/data_removed.json",
+            "../../fixtures/effects/data_updated.json"
+        );
+    }
+}
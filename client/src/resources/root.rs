@@ -0,0 +1,202 @@
+use chrono::prelude::*;
+use resources::Links;
+use std::fmt;
+
+/// The root of a horizon server, which describes the server and how far it
+/// has got through the ledgers of the network.
+///
+/// Horizon ingests ledgers from its stellar core node into its history
+/// database, so `history_latest_ledger` trails `core_latest_ledger` a little
+/// even on a healthy server. A gap that keeps growing means horizon is
+/// serving stale data.
+///
+/// <https://developers.stellar.org/api/introduction/response-format/>
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Root {
+    #[serde(rename = "_links", default)]
+    links: Links,
+    horizon_version: String,
+    core_version: String,
+    ingest_latest_ledger: u32,
+    history_latest_ledger: u32,
+    history_latest_ledger_closed_at: DateTime<Utc>,
+    history_elder_ledger: u32,
+    core_latest_ledger: u32,
+    network_passphrase: String,
+    current_protocol_version: u32,
+    supported_protocol_version: u32,
+    core_supported_protocol_version: u32,
+}
+
+impl Root {
+    /// The version of horizon the server is running.
+    pub fn horizon_version(&self) -> &str {
+        &self.horizon_version
+    }
+
+    /// The links to the resources related to this one.
+    pub fn links(&self) -> &Links {
+        &self.links
+    }
+
+    /// The release of horizon the server is running, when its version is one
+    /// that can be read. Builds from source may report something else.
+    pub fn version(&self) -> Option<HorizonVersion> {
+        HorizonVersion::parse(&self.horizon_version)
+    }
+
+    /// The version of stellar core behind horizon.
+    pub fn core_version(&self) -> &str {
+        &self.core_version
+    }
+
+    /// The latest ledger horizon has ingested.
+    pub fn ingest_latest_ledger(&self) -> u32 {
+        self.ingest_latest_ledger
+    }
+
+    /// The latest ledger available in horizon's history, and so the latest
+    /// one its endpoints can answer for.
+    pub fn history_latest_ledger(&self) -> u32 {
+        self.history_latest_ledger
+    }
+
+    /// When the latest ledger in horizon's history closed.
+    pub fn history_latest_ledger_closed_at(&self) -> DateTime<Utc> {
+        self.history_latest_ledger_closed_at
+    }
+
+    /// The oldest ledger available in horizon's history.
+    pub fn history_elder_ledger(&self) -> u32 {
+        self.history_elder_ledger
+    }
+
+    /// The latest ledger stellar core has closed.
+    pub fn core_latest_ledger(&self) -> u32 {
+        self.core_latest_ledger
+    }
+
+    /// The passphrase of the network the server belongs to.
+    pub fn network_passphrase(&self) -> &str {
+        &self.network_passphrase
+    }
+
+    /// The protocol version the network is currently on.
+    pub fn current_protocol_version(&self) -> u32 {
+        self.current_protocol_version
+    }
+
+    /// The newest protocol version horizon supports.
+    pub fn supported_protocol_version(&self) -> u32 {
+        self.supported_protocol_version
+    }
+
+    /// The newest protocol version stellar core supports.
+    pub fn core_supported_protocol_version(&self) -> u32 {
+        self.core_supported_protocol_version
+    }
+
+    /// How many ledgers horizon's history is behind stellar core.
+    pub fn ingest_lag(&self) -> u32 {
+        self.core_latest_ledger
+            .saturating_sub(self.history_latest_ledger)
+    }
+}
+
+/// A release of horizon, such as the `2.27.0` in the version string
+/// `2.27.0-0a0d0c8ea4b1a1d5eef4a0ba1aeea0b4c0c9a2b6`. Versions compare in
+/// release order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HorizonVersion {
+    major: u32,
+    minor: u32,
+    patch: u32,
+}
+
+impl HorizonVersion {
+    /// Creates a version from its parts.
+    pub fn new(major: u32, minor: u32, patch: u32) -> HorizonVersion {
+        HorizonVersion {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Reads the release out of a version string as horizon reports it.
+    /// Older servers prefix it, as in `horizon-v0.15.0`, and anything after
+    /// the release, such as a commit hash, is ignored.
+    pub fn parse(version: &str) -> Option<HorizonVersion> {
+        let start = version.find(|c: char| c.is_ascii_digit())?;
+        let release = &version[start..];
+        let end = release
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(release.len());
+        let mut parts = release[..end].split('.').map(str::parse::<u32>);
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+        let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+        Some(HorizonVersion::new(major, minor, patch))
+    }
+
+    /// The major version.
+    pub fn major(&self) -> u32 {
+        self.major
+    }
+
+    /// The minor version.
+    pub fn minor(&self) -> u32 {
+        self.minor
+    }
+
+    /// The patch version.
+    pub fn patch(&self) -> u32 {
+        self.patch
+    }
+}
+
+impl fmt::Display for HorizonVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[cfg(test)]
+mod root_tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn it_parses_the_root_of_a_server() {
+        let root: Root = serde_json::from_str(include_str!("../../fixtures/root.json")).unwrap();
+        assert_eq!(root.history_latest_ledger(), 1_524_298);
+        assert_eq!(root.core_latest_ledger(), 1_524_302);
+        assert_eq!(root.history_elder_ledger(), 2);
+        assert_eq!(root.ingest_lag(), 4);
+        assert_eq!(
+            root.network_passphrase(),
+            "Test SDF Network ; September 2015"
+        );
+        assert_eq!(root.current_protocol_version(), 20);
+        assert_eq!(
+            root.history_latest_ledger_closed_at(),
+            Utc.ymd(2023, 11, 15).and_hms(22, 20, 30)
+        );
+        assert_eq!(root.version(), Some(HorizonVersion::new(2, 27, 0)));
+    }
+
+    #[test]
+    fn it_reads_horizon_versions() {
+        assert_eq!(
+            HorizonVersion::parse("horizon-v0.15.0"),
+            Some(HorizonVersion::new(0, 15, 0))
+        );
+        assert_eq!(
+            HorizonVersion::parse("1.2"),
+            Some(HorizonVersion::new(1, 2, 0))
+        );
+        assert_eq!(HorizonVersion::parse("snapshot"), None);
+        assert!(HorizonVersion::new(0, 24, 1) < HorizonVersion::new(2, 0, 0));
+        assert_eq!(HorizonVersion::new(2, 27, 0).to_string(), "2.27.0");
+    }
+}
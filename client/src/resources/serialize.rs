@@ -0,0 +1,315 @@
+use serde::Serializer;
+use std::fmt::Display;
+
+/// The counterpart of `deserialize::from_str`. Writes a field that horizon
+/// represents as a string, such as a sequence number, using its `Display`
+/// implementation.
+pub(crate) fn to_string<T, S>(value: &T, s: S) -> Result<S::Ok, S::Error>
+where
+    T: Display,
+    S: Serializer,
+{
+    s.collect_str(value)
+}
+
+#[cfg(test)]
+mod to_string_tests {
+    use serde_json::value::Serializer;
+
+    #[test]
+    fn it_writes_a_u64_as_a_string() {
+        let value = super::to_string(&123u64, Serializer).unwrap();
+        assert_eq!(value, "123");
+    }
+}
+
+#[cfg(test)]
+mod horizon_format_tests {
+    use resources::{
+        Account, Asset, ClaimableBalance, Effect, Health, Ledger, Offer, OfferSummary, Operation,
+        Orderbook, PaymentPath, Root, SubmittedTransaction, Trade, TradeAggregation, Transaction,
+    };
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use serde_json::{self, Value};
+
+    // Every field written has to be one horizon sends, apart from empty ones
+    // standing in for fields it left out, and writing what was read back in
+    // has to give the same document.
+    fn assert_horizon_format<T>(json: &str)
+    where
+        T: DeserializeOwned + Serialize,
+    {
+        let original: Value = serde_json::from_str(json).unwrap();
+        let resource: T = serde_json::from_str(json).unwrap();
+        let written = serde_json::to_value(&resource).unwrap();
+        assert_fields_within(&written, &original, "");
+        let reread: T = serde_json::from_value(written.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&reread).unwrap(), written);
+    }
+
+    fn assert_fields_within(written: &Value, original: &Value, path: &str) {
+        match (written, original) {
+            (&Value::Object(ref written), &Value::Object(ref original)) => {
+                for (key, value) in written {
+                    let path = format!("{}.{}", path, key);
+                    match original.get(key) {
+                        Some(original) => assert_fields_within(value, original, &path),
+                        None if is_empty(value) => {}
+                        None => panic!("{} is not a field horizon sends", path),
+                    }
+                }
+            }
+            (&Value::Array(ref written), &Value::Array(ref original)) => {
+                for (value, original) in written.iter().zip(original) {
+                    assert_fields_within(value, original, path);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn is_empty(value: &Value) -> bool {
+        match *value {
+            Value::Null | Value::Bool(false) => true,
+            Value::String(ref value) => value.is_empty(),
+            Value::Array(ref values) => values.is_empty(),
+            Value::Object(ref fields) => fields.is_empty(),
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn it_writes_resources_in_horizons_format() {
+        assert_horizon_format::<Account>(include_str!("../../fixtures/account.json"));
+        assert_horizon_format::<Account>(include_str!(
+            "../../fixtures/account_with_pool_shares.json"
+        ));
+        assert_horizon_format::<Asset>(include_str!("../../fixtures/asset.json"));
+        assert_horizon_format::<Asset>(include_str!("../../fixtures/native_asset.json"));
+        assert_horizon_format::<ClaimableBalance>(include_str!(
+            "../../fixtures/claimable_balance.json"
+        ));
+        assert_horizon_format::<Health>(include_str!("../../fixtures/health.json"));
+        assert_horizon_format::<Ledger>(include_str!("../../fixtures/ledger.json"));
+        assert_horizon_format::<Offer>(include_str!("../../fixtures/offer.json"));
+        assert_horizon_format::<OfferSummary>(include_str!("../../fixtures/offer_summary.json"));
+        assert_horizon_format::<Orderbook>(include_str!("../../fixtures/orderbook.json"));
+        assert_horizon_format::<PaymentPath>(include_str!("../../fixtures/payment_path.json"));
+        assert_horizon_format::<Root>(include_str!("../../fixtures/root.json"));
+        assert_horizon_format::<SubmittedTransaction>(include_str!(
+            "../../fixtures/submitted_transaction.json"
+        ));
+        assert_horizon_format::<Trade>(include_str!("../../fixtures/trade.json"));
+        assert_horizon_format::<TradeAggregation>(include_str!(
+            "../../fixtures/trade_aggregation.json"
+        ));
+    }
+
+    #[test]
+    fn it_writes_transactions_with_each_kind_of_memo() {
+        assert_horizon_format::<Transaction>(include_str!(
+            "../../fixtures/transactions/transaction_memo_hash.json"
+        ));
+        assert_horizon_format::<Transaction>(include_str!(
+            "../../fixtures/transactions/transaction_memo_id.json"
+        ));
+        assert_horizon_format::<Transaction>(include_str!(
+            "../../fixtures/transactions/transaction_memo_none.json"
+        ));
+        assert_horizon_format::<Transaction>(include_str!(
+            "../../fixtures/transactions/transaction_memo_return.json"
+        ));
+        assert_horizon_format::<Transaction>(include_str!(
+            "../../fixtures/transactions/transaction_memo_text.json"
+        ));
+    }
+
+    #[test]
+    fn it_writes_every_kind_of_operation() {
+        let mut fixtures = vec![
+            include_str!("../../fixtures/operations/account_merge.json"),
+            include_str!("../../fixtures/operations/change_trust.json"),
+            include_str!("../../fixtures/operations/create_account.json"),
+            include_str!("../../fixtures/operations/create_passive_sell_offer.json"),
+            include_str!("../../fixtures/operations/extend_footprint_ttl.json"),
+            include_str!("../../fixtures/operations/invoke_host_function.json"),
+            include_str!("../../fixtures/operations/manage_data.json"),
+            include_str!("../../fixtures/operations/manage_sell_offer.json"),
+            include_str!("../../fixtures/operations/path_payment_strict_receive.json"),
+            include_str!("../../fixtures/operations/payment.json"),
+            include_str!("../../fixtures/operations/restore_footprint.json"),
+            include_str!("../../fixtures/operations/set_options.json"),
+        ];
+        if cfg!(feature = "legacy-ops") {
+            fixtures.push(include_str!("../../fixtures/operations/allow_trust.json"));
+            fixtures.push(include_str!("../../fixtures/operations/inflation.json"));
+        }
+        for fixture in &fixtures {
+            assert_horizon_format::<Operation>(fixture);
+            let original: Value = serde_json::from_str(fixture).unwrap();
+            let written =
+                serde_json::to_value(&serde_json::from_str::<Operation>(fixture).unwrap()).unwrap();
+            assert_eq!(written["type"], original["type"]);
+            assert_eq!(written["type_i"], original["type_i"]);
+            assert_eq!(written["id"], original["id"]);
+        }
+    }
+
+    #[test]
+    fn it_writes_every_kind_of_effect() {
+        let fixtures = [
+            include_str!("../../fixtures/effects/account_created.json"),
+            include_str!("../../fixtures/effects/account_credited.json"),
+            include_str!("../../fixtures/effects/account_debited.json"),
+            include_str!("../../fixtures/effects/account_flags_updated.json"),
+            include_str!("../../fixtures/effects/account_home_domain_updated.json"),
+            include_str!("../../fixtures/effects/account_removed.json"),
+            include_str!("../../fixtures/effects/account_threshold_updated.json"),
+            include_str!("../../fixtures/effects/data_created.json"),
+            include_str!("../../fixtures/effects/data_removed.json"),
+            include_str!("../../fixtures/effects/data_updated.json"),
+            include_str!("../../fixtures/effects/signer_created.json"),
+            include_str!("../../fixtures/effects/signer_removed.json"),
+            include_str!("../../fixtures/effects/signer_updated.json"),
+            include_str!("../../fixtures/effects/trade.json"),
+            include_str!("../../fixtures/effects/trustline_authorized.json"),
+            include_str!("../../fixtures/effects/trustline_created.json"),
+            include_str!("../../fixtures/effects/trustline_deauthorized.json"),
+            include_str!("../../fixtures/effects/trustline_removed.json"),
+            include_str!("../../fixtures/effects/trustline_updated.json"),
+        ];
+        for fixture in &fixtures {
+            assert_horizon_format::<Effect>(fixture);
+            let original: Value = serde_json::from_str(fixture).unwrap();
+            let written =
+                serde_json::to_value(&serde_json::from_str::<Effect>(fixture).unwrap()).unwrap();
+            assert_eq!(written["type"], original["type"]);
+            assert_eq!(written["type_i"], original["type_i"]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod binary_format_tests {
+    use resources::{
+        Account, Asset, ClaimableBalance, Effect, Health, Ledger, Offer, OfferSummary, Operation,
+        Orderbook, PaymentPath, Root, SubmittedTransaction, Trade, TradeAggregation, Transaction,
+    };
+    use rmp_serde;
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use serde_cbor;
+    use serde_json;
+
+    // Reading back what was written has to give the same resource. The
+    // resources are read through readers, as they would be from a file, so
+    // nothing can borrow from the input.
+    fn assert_round_trips<T>(json: &str)
+    where
+        T: DeserializeOwned + Serialize,
+    {
+        let resource: T = serde_json::from_str(json).unwrap();
+        let expected = serde_json::to_value(&resource).unwrap();
+
+        let cbor = serde_cbor::to_vec(&resource).unwrap();
+        let reread: T = serde_cbor::from_reader(&cbor[..]).unwrap();
+        assert_eq!(serde_json::to_value(&reread).unwrap(), expected);
+
+        let msgpack = rmp_serde::to_vec(&resource).unwrap();
+        let reread: T = rmp_serde::from_read(&msgpack[..]).unwrap();
+        assert_eq!(serde_json::to_value(&reread).unwrap(), expected);
+    }
+
+    #[test]
+    fn it_round_trips_resources_through_cbor_and_msgpack() {
+        assert_round_trips::<Account>(include_str!("../../fixtures/account.json"));
+        assert_round_trips::<Account>(include_str!("../../fixtures/account_with_pool_shares.json"));
+        assert_round_trips::<Asset>(include_str!("../../fixtures/asset.json"));
+        assert_round_trips::<Asset>(include_str!("../../fixtures/native_asset.json"));
+        assert_round_trips::<ClaimableBalance>(include_str!(
+            "../../fixtures/claimable_balance.json"
+        ));
+        assert_round_trips::<Health>(include_str!("../../fixtures/health.json"));
+        assert_round_trips::<Ledger>(include_str!("../../fixtures/ledger.json"));
+        assert_round_trips::<Offer>(include_str!("../../fixtures/offer.json"));
+        assert_round_trips::<OfferSummary>(include_str!("../../fixtures/offer_summary.json"));
+        assert_round_trips::<Orderbook>(include_str!("../../fixtures/orderbook.json"));
+        assert_round_trips::<PaymentPath>(include_str!("../../fixtures/payment_path.json"));
+        assert_round_trips::<Root>(include_str!("../../fixtures/root.json"));
+        assert_round_trips::<SubmittedTransaction>(include_str!(
+            "../../fixtures/submitted_transaction.json"
+        ));
+        assert_round_trips::<Trade>(include_str!("../../fixtures/trade.json"));
+        assert_round_trips::<TradeAggregation>(include_str!(
+            "../../fixtures/trade_aggregation.json"
+        ));
+    }
+
+    #[test]
+    fn it_round_trips_transactions_through_cbor_and_msgpack() {
+        let fixtures = [
+            include_str!("../../fixtures/transactions/transaction_memo_hash.json"),
+            include_str!("../../fixtures/transactions/transaction_memo_id.json"),
+            include_str!("../../fixtures/transactions/transaction_memo_none.json"),
+            include_str!("../../fixtures/transactions/transaction_memo_return.json"),
+            include_str!("../../fixtures/transactions/transaction_memo_text.json"),
+        ];
+        for fixture in &fixtures {
+            assert_round_trips::<Transaction>(fixture);
+        }
+    }
+
+    #[test]
+    fn it_round_trips_operations_through_cbor_and_msgpack() {
+        let mut fixtures = vec![
+            include_str!("../../fixtures/operations/account_merge.json"),
+            include_str!("../../fixtures/operations/change_trust.json"),
+            include_str!("../../fixtures/operations/create_account.json"),
+            include_str!("../../fixtures/operations/create_passive_sell_offer.json"),
+            include_str!("../../fixtures/operations/extend_footprint_ttl.json"),
+            include_str!("../../fixtures/operations/invoke_host_function.json"),
+            include_str!("../../fixtures/operations/manage_data.json"),
+            include_str!("../../fixtures/operations/manage_sell_offer.json"),
+            include_str!("../../fixtures/operations/path_payment_strict_receive.json"),
+            include_str!("../../fixtures/operations/payment.json"),
+            include_str!("../../fixtures/operations/restore_footprint.json"),
+            include_str!("../../fixtures/operations/set_options.json"),
+        ];
+        if cfg!(feature = "legacy-ops") {
+            fixtures.push(include_str!("../../fixtures/operations/allow_trust.json"));
+            fixtures.push(include_str!("../../fixtures/operations/inflation.json"));
+        }
+        for fixture in &fixtures {
+            assert_round_trips::<Operation>(fixture);
+        }
+    }
+
+    #[test]
+    fn it_round_trips_effects_through_cbor_and_msgpack() {
+        let fixtures = [
+            include_str!("../../fixtures/effects/account_created.json"),
+            include_str!("../../fixtures/effects/account_credited.json"),
+            include_str!("../../fixtures/effects/account_debited.json"),
+            include_str!("../../fixtures/effects/account_flags_updated.json"),
+            include_str!("../../fixtures/effects/account_home_domain_updated.json"),
+            include_str!("../../fixtures/effects/account_removed.json"),
+            include_str!("../../fixtures/effects/account_threshold_updated.json"),
+            include_str!("../../fixtures/effects/data_created.json"),
+            include_str!("../../fixtures/effects/data_removed.json"),
+            include_str!("../../fixtures/effects/data_updated.json"),
+            include_str!("../../fixtures/effects/signer_created.json"),
+            include_str!("../../fixtures/effects/signer_removed.json"),
+            include_str!("../../fixtures/effects/signer_updated.json"),
+            include_str!("../../fixtures/effects/trade.json"),
+            include_str!("../../fixtures/effects/trustline_authorized.json"),
+            include_str!("../../fixtures/effects/trustline_created.json"),
+            include_str!("../../fixtures/effects/trustline_deauthorized.json"),
+            include_str!("../../fixtures/effects/trustline_removed.json"),
+            include_str!("../../fixtures/effects/trustline_updated.json"),
+        ];
+        for fixture in &fixtures {
+            assert_round_trips::<Effect>(fixture);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use resources::{Links, Transaction};
+
+/// A transaction that horizon has accepted into a ledger, as returned when it
+/// is submitted. Friendbot answers with one of these for the transaction that
+/// funded the account.
+///
+/// <https://developers.stellar.org/api/resources/transactions/post/>
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SubmittedTransaction {
+    #[serde(rename = "_links", default)]
+    links: Links,
+    hash: String,
+    ledger: u32,
+    envelope_xdr: String,
+    result_xdr: String,
+    result_meta_xdr: String,
+}
+
+impl SubmittedTransaction {
+    /// The hash of the transaction.
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    /// The links to the resources related to this one.
+    pub fn links(&self) -> &Links {
+        &self.links
+    }
+
+    /// The sequence of the ledger the transaction was included in.
+    pub fn ledger(&self) -> u32 {
+        self.ledger
+    }
+
+    /// The base64 encoded XDR of the transaction envelope.
+    pub fn envelope_xdr(&self) -> &str {
+        &self.envelope_xdr
+    }
+
+    /// The base64 encoded XDR of the transaction result.
+    pub fn result_xdr(&self) -> &str {
+        &self.result_xdr
+    }
+
+    /// The base64 encoded XDR of the changes the transaction made.
+    pub fn result_meta_xdr(&self) -> &str {
+        &self.result_meta_xdr
+    }
+}
+
+impl From<Transaction> for SubmittedTransaction {
+    /// The submission of a transaction that was found in horizon's history,
+    /// such as one whose submission timed out but made it into a ledger.
+    fn from(transaction: Transaction) -> Self {
+        SubmittedTransaction {
+            links: transaction.links().clone(),
+            hash: transaction.hash().clone(),
+            ledger: transaction.ledger(),
+            envelope_xdr: transaction.envelope_xdr().clone(),
+            result_xdr: transaction.result_xdr().clone(),
+            result_meta_xdr: transaction.result_meta_xdr().clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod submitted_transaction_tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn it_parses_a_submitted_transaction() {
+        let transaction: SubmittedTransaction =
+            serde_json::from_str(include_str!("../../fixtures/submitted_transaction.json"))
+                .unwrap();
+        assert_eq!(
+            transaction.hash(),
+            "1ff1b8a8b5d8bdc0c3c4b7c9e7b8a3be4fa3a1de2fcbef3bd7c4be6f7bd0da3a"
+        );
+        assert_eq!(transaction.ledger(), 1_524_302);
+        assert_eq!(
+            transaction.result_xdr(),
+            "AAAAAAAAAGQAAAAAAAAAAQAAAAAAAAAAAAAAAAAAAAA="
+        );
+    }
+
+    #[test]
+    fn it_is_made_from_a_transaction_in_history() {
+        let transaction: Transaction = serde_json::from_str(include_str!(
+            "../../fixtures/transactions/transaction_memo_none.json"
+        ))
+        .unwrap();
+        let submitted = SubmittedTransaction::from(transaction.clone());
+        assert_eq!(submitted.hash(), transaction.hash());
+        assert_eq!(submitted.ledger(), transaction.ledger());
+        assert_eq!(submitted.envelope_xdr(), transaction.envelope_xdr());
+        assert_eq!(submitted.result_xdr(), transaction.result_xdr());
+        assert_eq!(submitted.result_meta_xdr(), transaction.result_meta_xdr());
+    }
+}
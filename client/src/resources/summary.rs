@@ -0,0 +1,216 @@
+//! One line, human readable summaries of operations and transactions for log
+//! lines, alerts and command line output.
+use resources::{AccountId, Amount, AssetIdentifier, Memo, Operation, OperationKind, Transaction};
+
+impl Operation {
+    /// Describes what the operation did in a sentence, such as
+    /// `GAKL…BD75 paid 25 USD to GCEZ…74JZ`. Accounts are shortened to
+    /// their first and last four characters and amounts lose their trailing
+    /// zeros.
+    ///
+    /// ```
+    /// use stellar_client::resources::{Operation, Resource};
+    ///
+    /// let operation = Operation::from_json_str(
+    ///     include_str!("../../fixtures/operations/payment.json"),
+    /// ).unwrap();
+    /// assert_eq!(operation.summarize(), "GAKL…BD75 paid 200 XLM to GCEZ…74JZ");
+    /// ```
+    pub fn summarize(&self) -> String {
+        let source = short(self.source_account());
+        match *self.kind() {
+            OperationKind::CreateAccount(ref create) => format!(
+                "{} created {} with {}",
+                short(create.funder()),
+                short(create.account()),
+                amount(create.starting_balance(), &AssetIdentifier::native())
+            ),
+            OperationKind::Payment(ref payment) => format!(
+                "{} paid {} to {}",
+                short(payment.from()),
+                amount(payment.amount(), payment.asset()),
+                short(payment.to())
+            ),
+            OperationKind::PathPayment(ref payment) => format!(
+                "{} paid {} to {} with up to {}",
+                short(payment.from()),
+                amount(payment.destination_amount(), payment.destination_asset()),
+                short(payment.to()),
+                amount(payment.source_max(), payment.source_asset())
+            ),
+            OperationKind::ManageOffer(ref offer) if offer.amount() == Amount::new(0) => {
+                format!("{} cancelled offer {}", source, offer.offer_id())
+            }
+            OperationKind::ManageOffer(ref offer) => format!(
+                "{} offered {} for {} at {}",
+                source,
+                amount(offer.amount(), offer.selling()),
+                code(offer.buying()),
+                offer.price().format().trim_trailing_zeros()
+            ),
+            OperationKind::CreatePassiveOffer(ref offer) => format!(
+                "{} passively offered {} for {} at {}",
+                source,
+                amount(offer.amount(), offer.selling()),
+                code(offer.buying()),
+                offer.price().format().trim_trailing_zeros()
+            ),
+            OperationKind::SetOptions(_) => format!("{} set its account options", source),
+            OperationKind::ChangeTrust(ref trust) if trust.limit() == Amount::new(0) => format!(
+                "{} removed its trustline to {}",
+                short(trust.trustor()),
+                code(trust.asset())
+            ),
+            OperationKind::ChangeTrust(ref trust) => format!(
+                "{} trusted {} up to {}",
+                short(trust.trustor()),
+                code(trust.asset()),
+                trust.limit().format().trim_trailing_zeros()
+            ),
+            OperationKind::AllowTrust(ref trust) => format!(
+                "{} {} {} to hold {}",
+                short(trust.trustee()),
+                if trust.authorize() {
+                    "authorized"
+                } else {
+                    "deauthorized"
+                },
+                short(trust.trustor()),
+                code(trust.asset())
+            ),
+            OperationKind::AccountMerge(ref merge) => format!(
+                "{} merged into {}",
+                short(merge.account()),
+                short(merge.into())
+            ),
+            OperationKind::Inflation => format!("{} ran inflation", source),
+            OperationKind::ManageData(ref data) if data.value().is_empty() => {
+                format!("{} removed data entry {}", source, data.name())
+            }
+            OperationKind::ManageData(ref data) => {
+                format!("{} set data entry {}", source, data.name())
+            }
+            OperationKind::InvokeHostFunction(_) => format!("{} invoked a contract", source),
+            OperationKind::ExtendFootprintTtl(ref extend) => format!(
+                "{} extended contract data to ledger {}",
+                source,
+                extend.extend_to()
+            ),
+            OperationKind::RestoreFootprint => {
+                format!("{} restored archived contract data", source)
+            }
+        }
+    }
+}
+
+impl Transaction {
+    /// Describes the transaction in a sentence, such as
+    /// `GAKL…BD75 submitted 3 operations for a fee of 0.00003 XLM (memo: invoice 42)`.
+    /// The memo is left off when there isn't one.
+    pub fn summarize(&self) -> String {
+        let operations = match self.operation_count() {
+            1 => "1 operation".to_string(),
+            count => format!("{} operations", count),
+        };
+        let summary = format!(
+            "{} submitted {} for a fee of {}",
+            short(self.source_account()),
+            operations,
+            amount(self.fee_as_amount(), &AssetIdentifier::native())
+        );
+        self.with_memo(summary)
+    }
+
+    /// Describes the transaction by its operations, which have to be
+    /// fetched separately, such as
+    /// `GAKL…BD75 paid 25 USD to GCEZ…74JZ (memo: invoice 42)`. Falls back to
+    /// `summarize` when `operations` is empty.
+    pub fn summarize_with(&self, operations: &[Operation]) -> String {
+        if operations.is_empty() {
+            return self.summarize();
+        }
+        let summaries: Vec<String> = operations.iter().map(Operation::summarize).collect();
+        self.with_memo(summaries.join("; "))
+    }
+
+    fn with_memo(&self, summary: String) -> String {
+        match *self.memo() {
+            Memo::None => summary,
+            Memo::Text(ref text) => format!("{} (memo: {})", summary, text),
+            Memo::Id(id) => format!("{} (memo: {})", summary, id),
+            Memo::Hash(ref hash) => format!("{} (memo hash: {})", summary, hash),
+            Memo::Return(ref hash) => format!("{} (memo return: {})", summary, hash),
+        }
+    }
+}
+
+/// An account id cut down to its first and last four characters.
+fn short(account_id: &AccountId) -> String {
+    let id = account_id.as_str();
+    if id.len() <= 8 {
+        return id.to_string();
+    }
+    format!("{}…{}", &id[..4], &id[id.len() - 4..])
+}
+
+/// The code of the asset, or `XLM` for lumens.
+fn code(asset: &AssetIdentifier) -> &str {
+    asset.code().unwrap_or("XLM")
+}
+
+fn amount(amount: Amount, asset: &AssetIdentifier) -> String {
+    format!("{} {}", amount.format().trim_trailing_zeros(), code(asset))
+}
+
+#[cfg(test)]
+mod summary_tests {
+    use super::*;
+    use resources::{Resource, TransactionBuilder};
+
+    fn operation(json: &str) -> Operation {
+        Operation::from_json_str(json).unwrap()
+    }
+
+    fn transaction(memo: Memo) -> Transaction {
+        TransactionBuilder::new(
+            "abc",
+            "GAKLBGHNHFQ3BMUYG5KU4BEWO6EYQHZHAXEWC33W34PH2RBHZDSQBD75".into(),
+        )
+        .with_fee_paid(300)
+        .with_operation_count(3)
+        .with_memo(memo)
+        .build()
+    }
+
+    #[test]
+    fn it_summarizes_operations() {
+        assert_eq!(
+            operation(include_str!(
+                "../../fixtures/operations/create_account.json"
+            ))
+            .summarize(),
+            "GBIA…QHRO created GCEZ…74JZ with 10000 XLM"
+        );
+        assert_eq!(
+            operation(include_str!("../../fixtures/operations/account_merge.json")).summarize(),
+            "GBCR…FD42 merged into GBS4…6S3K"
+        );
+    }
+
+    #[test]
+    fn it_summarizes_transactions() {
+        assert_eq!(
+            transaction(Memo::None).summarize(),
+            "GAKL…BD75 submitted 3 operations for a fee of 0.00003 XLM"
+        );
+        let payment = operation(include_str!("../../fixtures/operations/payment.json"));
+        assert_eq!(
+            transaction(Memo::Text("invoice 42".to_string())).summarize_with(&[payment]),
+            "GAKL…BD75 paid 200 XLM to GCEZ…74JZ (memo: invoice 42)"
+        );
+        assert_eq!(
+            transaction(Memo::Id(42)).summarize_with(&[]),
+            "GAKL…BD75 submitted 3 operations for a fee of 0.00003 XLM (memo: 42)"
+        );
+    }
+}
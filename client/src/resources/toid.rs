@@ -0,0 +1,256 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The number of bits of an id that hold the transaction order.
+const TRANSACTION_BITS: u32 = 20;
+/// The number of bits of an id that hold the operation index.
+const OPERATION_BITS: u32 = 12;
+
+const MAX_LEDGER: u32 = i32::MAX as u32;
+const MAX_TRANSACTION_ORDER: u32 = (1 << TRANSACTION_BITS) - 1;
+const MAX_OPERATION_INDEX: u32 = (1 << OPERATION_BITS) - 1;
+
+/// The id horizon gives transactions and operations, which is also the
+/// paging token of the history endpoints, taken apart into the ledger, the
+/// order of the transaction within it and the index of the operation within
+/// the transaction.
+///
+/// The ledger sequence takes the top 32 bits of the id, the transaction order
+/// the next 20 and the operation index the last 12. Transactions are ordered
+/// from 1 within their ledger and operations from 1 within their transaction.
+/// The id of a transaction has an operation index of 0.
+///
+/// Since the ids grow with the ledger, a `Toid` for the start of a ledger
+/// makes a cursor that starts listing at that ledger, without searching for
+/// the paging token of its first record.
+///
+/// # Examples
+///
+/// ```
+/// use stellar_client::endpoint::{transaction, Cursor};
+/// use stellar_client::resources::Toid;
+///
+/// // Everything from ledger 16751283 on.
+/// let start = Toid::ledger_start(16751283);
+/// let endpoint = transaction::All::default().with_cursor(&start.to_string());
+/// assert_eq!(endpoint.cursor(), Some("71946212651040768"));
+///
+/// let toid: Toid = "71946212651044864".parse().unwrap();
+/// assert_eq!(toid.ledger(), 16751283);
+/// assert_eq!(toid.transaction_order(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Toid {
+    ledger: u32,
+    transaction_order: u32,
+    operation_index: u32,
+}
+
+impl Toid {
+    /// The id of the operation at `operation_index` in the transaction at
+    /// `transaction_order` in `ledger`. Fails when a part is too large to
+    /// fit in its bits: the ledger in 31, since ids are signed, the
+    /// transaction order in 20 and the operation index in 12.
+    pub fn new(
+        ledger: u32,
+        transaction_order: u32,
+        operation_index: u32,
+    ) -> Result<Toid, ParseToidError> {
+        if ledger > MAX_LEDGER
+            || transaction_order > MAX_TRANSACTION_ORDER
+            || operation_index > MAX_OPERATION_INDEX
+        {
+            return Err(ParseToidError {
+                kind: ErrorKind::OutOfRange,
+            });
+        }
+        Ok(Toid {
+            ledger,
+            transaction_order,
+            operation_index,
+        })
+    }
+
+    /// Takes an id apart. Fails when it is negative.
+    pub fn from_id(id: i64) -> Result<Toid, ParseToidError> {
+        if id < 0 {
+            return Err(ParseToidError {
+                kind: ErrorKind::OutOfRange,
+            });
+        }
+        let id = id as u64;
+        Ok(Toid {
+            ledger: (id >> (TRANSACTION_BITS + OPERATION_BITS)) as u32,
+            transaction_order: (id >> OPERATION_BITS) as u32 & MAX_TRANSACTION_ORDER,
+            operation_index: id as u32 & MAX_OPERATION_INDEX,
+        })
+    }
+
+    /// The id that comes before every transaction and operation in
+    /// `ledger`. As a cursor in ascending order, it starts at the first
+    /// record of the ledger. In descending order, it starts at the last
+    /// record of the ledger before. Ledgers past `i32::MAX`, which don't fit
+    /// in an id, are taken as `i32::MAX`.
+    pub fn ledger_start(ledger: u32) -> Toid {
+        Toid {
+            ledger: ledger.min(MAX_LEDGER),
+            transaction_order: 0,
+            operation_index: 0,
+        }
+    }
+
+    /// The id that comes after every transaction and operation in
+    /// `ledger`. As a cursor in descending order, it starts at the last
+    /// record of the ledger. Ledgers past `i32::MAX` are taken as
+    /// `i32::MAX`.
+    pub fn ledger_end(ledger: u32) -> Toid {
+        Toid {
+            ledger: ledger.min(MAX_LEDGER),
+            transaction_order: MAX_TRANSACTION_ORDER,
+            operation_index: MAX_OPERATION_INDEX,
+        }
+    }
+
+    /// The id as horizon gives it.
+    pub fn id(&self) -> i64 {
+        (i64::from(self.ledger) << (TRANSACTION_BITS + OPERATION_BITS))
+            | (i64::from(self.transaction_order) << OPERATION_BITS)
+            | i64::from(self.operation_index)
+    }
+
+    /// The sequence of the ledger.
+    pub fn ledger(&self) -> u32 {
+        self.ledger
+    }
+
+    /// The order of the transaction within its ledger, starting at 1.
+    pub fn transaction_order(&self) -> u32 {
+        self.transaction_order
+    }
+
+    /// The index of the operation within its transaction, starting at 1, or
+    /// 0 for the id of a transaction.
+    pub fn operation_index(&self) -> u32 {
+        self.operation_index
+    }
+
+    /// The id of the transaction an operation belongs to.
+    pub fn transaction(&self) -> Toid {
+        Toid {
+            operation_index: 0,
+            ..*self
+        }
+    }
+}
+
+/// Writes the id, ready to be used as a cursor.
+impl fmt::Display for Toid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.id())
+    }
+}
+
+/// Reads an id or a paging token. The paging tokens of effects and trades
+/// follow the id of their operation with a dash and an index of their own,
+/// which is left off.
+impl FromStr for Toid {
+    type Err = ParseToidError;
+
+    fn from_str(s: &str) -> Result<Toid, ParseToidError> {
+        let id = s.split('-').next().unwrap_or(s);
+        match id.parse() {
+            Ok(id) => Toid::from_id(id),
+            Err(_) => Err(ParseToidError {
+                kind: ErrorKind::InvalidToken,
+            }),
+        }
+    }
+}
+
+/// Why an id could not be made or read.
+#[derive(Debug)]
+pub struct ParseToidError {
+    kind: ErrorKind,
+}
+
+#[derive(Debug)]
+enum ErrorKind {
+    InvalidToken,
+    OutOfRange,
+}
+
+impl Error for ParseToidError {
+    fn description(&self) -> &str {
+        match self.kind {
+            ErrorKind::InvalidToken => "The paging token isn't an id",
+            ErrorKind::OutOfRange => "The id is out of range",
+        }
+    }
+}
+
+impl fmt::Display for ParseToidError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+#[cfg(test)]
+mod toid_tests {
+    use super::*;
+    use resources::{Operation, Resource, Transaction};
+
+    #[test]
+    fn it_takes_apart_the_ids_horizon_gives() {
+        let transaction = Transaction::from_json_str(include_str!(
+            "../../fixtures/transactions/transaction_memo_text.json"
+        ))
+        .unwrap();
+        let toid: Toid = transaction.paging_token().parse().unwrap();
+        assert_eq!(toid.ledger(), transaction.ledger());
+        assert_eq!(toid.transaction_order(), 1);
+        assert_eq!(toid.operation_index(), 0);
+        assert_eq!(toid.to_string(), *transaction.paging_token());
+
+        let operation =
+            Operation::from_json_str(include_str!("../../fixtures/operations/payment.json"))
+                .unwrap();
+        let toid: Toid = operation.paging_token().parse().unwrap();
+        assert_eq!(toid, Toid::new(13598, 1, 0).unwrap());
+    }
+
+    #[test]
+    fn it_round_trips_every_part() {
+        let toid = Toid::new(16751283, 7, 3).unwrap();
+        assert_eq!(Toid::from_id(toid.id()).unwrap(), toid);
+        assert_eq!(toid.transaction(), Toid::new(16751283, 7, 0).unwrap());
+
+        let largest = Toid::new(MAX_LEDGER, MAX_TRANSACTION_ORDER, MAX_OPERATION_INDEX).unwrap();
+        assert_eq!(largest.id(), i64::MAX);
+        assert_eq!(Toid::from_id(i64::MAX).unwrap(), largest);
+        assert_eq!(Toid::ledger_end(u32::MAX), largest);
+    }
+
+    #[test]
+    fn it_bounds_a_ledger() {
+        let start = Toid::ledger_start(3);
+        let end = Toid::ledger_end(3);
+        let first = Toid::new(3, 1, 1).unwrap();
+        assert!(Toid::ledger_end(2) < start);
+        assert!(start < first && first < end);
+        assert!(end < Toid::ledger_start(4));
+        assert_eq!(start.id(), 12884901888);
+    }
+
+    #[test]
+    fn it_reads_paging_tokens() {
+        let toid: Toid = "12884905984-2".parse().unwrap();
+        assert_eq!(toid, Toid::new(3, 1, 0).unwrap());
+        assert!("now".parse::<Toid>().is_err());
+        assert!("-1".parse::<Toid>().is_err());
+        assert!(Toid::from_id(-1).is_err());
+        assert!(Toid::new(1, 1 << 20, 0).is_err());
+        assert!(Toid::new(1, 1, 1 << 12).is_err());
+        assert!(Toid::new(1 << 31, 0, 0).is_err());
+    }
+}
@@ -1,21 +1,24 @@
-use super::{amount::Amount, asset::AssetIdentifier, offer::PriceRatio};
+use super::{
+    account_id::AccountId, amount::Amount, asset::AssetIdentifier, offer::PriceRatio, Links,
+};
 use chrono::prelude::*;
-use serde::{de, Deserialize, Deserializer};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 /// A trade represents an offer that was fulfilled between two assets and accounts.
 ///
 /// <https://www.stellar.org/developers/horizon/reference/resources/trade.html>
 #[derive(Debug, Clone)]
 pub struct Trade {
+    links: Links,
     id: String,
     offer_id: String,
     paging_token: String,
     ledger_close_time: DateTime<Utc>,
-    base_account: String,
+    base_account: AccountId,
     base_amount: Amount,
     base_asset: AssetIdentifier,
     counter_amount: Amount,
-    counter_account: String,
+    counter_account: AccountId,
     counter_asset: AssetIdentifier,
     price: PriceRatio,
     seller: Seller,
@@ -76,6 +79,7 @@ impl<'de> Deserialize<'de> for Trade {
             rep.counter_asset_issuer,
         ).map_err(|err| de::Error::custom(&err))?;
         Ok(Trade {
+            links: rep.links,
             id: rep.id,
             paging_token: rep.paging_token,
             ledger_close_time: rep.ledger_close_time,
@@ -92,7 +96,38 @@ impl<'de> Deserialize<'de> for Trade {
     }
 }
 
-#[derive(Deserialize, Debug)]
+impl Serialize for Trade {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let rep = TradeIntermediate {
+            links: self.links.clone(),
+            id: self.id.clone(),
+            paging_token: self.paging_token.clone(),
+            ledger_close_time: self.ledger_close_time,
+            offer_id: self.offer_id.clone(),
+            base_account: self.base_account.clone(),
+            base_amount: self.base_amount,
+            base_asset_type: self.base_asset.asset_type().to_string(),
+            base_asset_code: self.base_asset.asset_code(),
+            base_asset_issuer: self.base_asset.asset_issuer(),
+            counter_account: self.counter_account.clone(),
+            counter_amount: self.counter_amount,
+            counter_asset_type: self.counter_asset.asset_type().to_string(),
+            counter_asset_code: self.counter_asset.asset_code(),
+            counter_asset_issuer: self.counter_asset.asset_issuer(),
+            base_is_seller: self.seller.is_base(),
+            price: Price {
+                n: self.price.numerator(),
+                d: self.price.denominator(),
+            },
+        };
+        rep.serialize(s)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
 struct Price {
     n: u64,
     d: u64,
@@ -104,25 +139,23 @@ impl From<Price> for PriceRatio {
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 struct TradeIntermediate {
+    #[serde(rename = "_links", default)]
+    links: Links,
     id: String,
     paging_token: String,
     ledger_close_time: DateTime<Utc>,
     offer_id: String,
-    base_account: String,
+    base_account: AccountId,
     base_amount: Amount,
     base_asset_type: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
     base_asset_code: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     base_asset_issuer: Option<String>,
-    counter_account: String,
+    counter_account: AccountId,
     counter_amount: Amount,
     counter_asset_type: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
     counter_asset_code: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     counter_asset_issuer: Option<String>,
     base_is_seller: bool,
     price: Price,
@@ -134,6 +167,11 @@ impl Trade {
         &self.id
     }
 
+    /// The links to the resources related to this one.
+    pub fn links(&self) -> &Links {
+        &self.links
+    }
+
     /// A paging_token suitable for use as a cursor parameter.
     pub fn paging_token(&self) -> &str {
         &self.paging_token
@@ -150,7 +188,7 @@ impl Trade {
     }
 
     /// The base account of the trade that received the counter asset.
-    pub fn base_account(&self) -> &str {
+    pub fn base_account(&self) -> &AccountId {
         &self.base_account
     }
 
@@ -165,7 +203,7 @@ impl Trade {
     }
 
     /// The counter account of the trade that received the base asset.
-    pub fn counter_account(&self) -> &str {
+    pub fn counter_account(&self) -> &AccountId {
         &self.counter_account
     }
 
@@ -190,13 +228,94 @@ impl Trade {
     }
 
     /// Account of the selling party
-    pub fn selling_account(&self) -> &str {
+    pub fn selling_account(&self) -> &AccountId {
         if self.seller().is_base() {
             self.base_account()
         } else {
             self.counter_account()
         }
     }
+
+    /// The base party of the trade, which gave up the base amount of the
+    /// base asset.
+    ///
+    /// ```
+    /// use stellar_client::resources::{Amount, AssetIdentifier, TradeBuilder, TradeSeller};
+    ///
+    /// let trade = TradeBuilder::new("68836918321750017-0")
+    ///     .with_base(
+    ///         "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into(),
+    ///         AssetIdentifier::native(),
+    ///         Amount::new(100),
+    ///     )
+    ///     .with_seller(TradeSeller::Counter)
+    ///     .build();
+    /// assert_eq!(trade.base().amount(), Amount::new(100));
+    /// assert!(trade.base().asset().is_native());
+    /// assert!(!trade.base().is_seller());
+    /// assert!(trade.counter().is_seller());
+    /// ```
+    pub fn base(&self) -> TradeParty {
+        TradeParty {
+            account: &self.base_account,
+            asset: &self.base_asset,
+            amount: self.base_amount,
+            is_seller: self.seller.is_base(),
+        }
+    }
+
+    /// The counter party of the trade, which gave up the counter amount of
+    /// the counter asset.
+    pub fn counter(&self) -> TradeParty {
+        TradeParty {
+            account: &self.counter_account,
+            asset: &self.counter_asset,
+            amount: self.counter_amount,
+            is_seller: self.seller.is_counter(),
+        }
+    }
+
+    /// The price of the trade as units of the counter asset per unit of the
+    /// base asset, to seven decimal places. This is the same whichever party
+    /// is the seller.
+    pub fn price_as_decimal(&self) -> Amount {
+        let price = u128::from(self.price.numerator()) * 10_000_000
+            / u128::from(self.price.denominator().max(1));
+        Amount::new(price.min(i64::max_value() as u128) as i64)
+    }
+}
+
+/// One party to a trade, made with `Trade::base` or `Trade::counter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeParty<'a> {
+    account: &'a AccountId,
+    asset: &'a AssetIdentifier,
+    amount: Amount,
+    is_seller: bool,
+}
+
+impl<'a> TradeParty<'a> {
+    /// The account of the party.
+    pub fn account(&self) -> &'a AccountId {
+        self.account
+    }
+
+    /// The asset the party gave up, which the other party received.
+    pub fn asset(&self) -> &'a AssetIdentifier {
+        self.asset
+    }
+
+    /// The amount of the asset the party gave up.
+    pub fn amount(&self) -> Amount {
+        self.amount
+    }
+
+    /// Whether the party is the seller, which is what horizon's
+    /// `base_is_seller` refers to. The seller's offer was already on the
+    /// books, and the other party's offer or payment crossed it.
+    pub fn is_seller(&self) -> bool {
+        self.is_seller
+    }
 }
 
 #[cfg(test)]
@@ -220,13 +339,13 @@ mod trade_tests {
             "GBZXCJIUEPDXGHMS64UBJHUVKV6ETWYOVHADLTBXJNJFUC7A7RU5B3GN"
         );
         assert_eq!(trade.base_amount(), Amount::new(1217566));
-        assert_eq!(trade.base_asset().code(), "XLM");
+        assert_eq!(trade.base_asset().code(), None);
         assert_eq!(
             trade.counter_account(),
             "GBHKUQDYXGK5IEYORI7DZMMXANOIEHHOF364LNT4Q7EWPUL7FOO2SP6D"
         );
         assert_eq!(trade.counter_amount(), Amount::new(199601));
-        assert_eq!(trade.counter_asset().code(), "SLT");
+        assert_eq!(trade.counter_asset().code(), Some("SLT"));
         assert_eq!(trade.price(), PriceRatio::new(10, 61));
         assert!(trade.seller().is_base());
         assert_eq!(
@@ -234,11 +353,149 @@ mod trade_tests {
             "GBZXCJIUEPDXGHMS64UBJHUVKV6ETWYOVHADLTBXJNJFUC7A7RU5B3GN"
         )
     }
+
+    #[test]
+    fn it_names_the_parties_to_a_trade() {
+        let trade: Trade = serde_json::from_str(&trade_json()).unwrap();
+        let base = trade.base();
+        assert_eq!(base.account(), trade.base_account());
+        assert_eq!(base.asset(), &AssetIdentifier::native());
+        assert_eq!(base.amount(), Amount::new(1217566));
+        assert!(base.is_seller());
+        let counter = trade.counter();
+        assert_eq!(counter.account(), trade.counter_account());
+        assert_eq!(counter.asset().code(), Some("SLT"));
+        assert_eq!(counter.amount(), Amount::new(199601));
+        assert!(!counter.is_seller());
+    }
+
+    #[test]
+    fn it_gives_the_price_as_a_decimal() {
+        let trade: Trade = serde_json::from_str(&trade_json()).unwrap();
+        // 10/61 SLT per XLM, which is close to 0.0199601 / 0.1217566.
+        assert_eq!(trade.price_as_decimal(), Amount::new(1639344));
+        let trade = TradeBuilder::new("1")
+            .with_price(PriceRatio::new(5, 2))
+            .with_seller(Seller::Counter)
+            .build();
+        assert_eq!(trade.price_as_decimal(), "2.5".parse().unwrap());
+    }
+}
+
+// The strkey of an all zero public key.
+const ZERO_ACCOUNT: &str = "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF";
+
+/// Builds a `Trade` from its id, setting the account, asset and amount on
+/// each side and which side sold.
+///
+/// ```
+/// use stellar_client::resources::{Amount, AssetIdentifier, TradeBuilder, TradeSeller};
+///
+/// let trade = TradeBuilder::new("68836918321750017-0")
+///     .with_base(
+///         "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG".into(),
+///         AssetIdentifier::native(),
+///         Amount::new(100),
+///     )
+///     .with_seller(TradeSeller::Counter)
+///     .build();
+/// assert_eq!(trade.base_amount(), Amount::new(100));
+/// assert!(trade.seller().is_counter());
+/// ```
+#[derive(Debug, Clone)]
+pub struct TradeBuilder {
+    trade: Trade,
+}
+
+impl TradeBuilder {
+    /// Starts building a trade with the given id. Both sides of the trade
+    /// start out as zero lumens from the account with an all zero public
+    /// key, priced one to one, with the base as the seller.
+    pub fn new(id: &str) -> TradeBuilder {
+        TradeBuilder {
+            trade: Trade {
+                links: Links::default(),
+                id: id.to_string(),
+                offer_id: String::new(),
+                paging_token: id.to_string(),
+                ledger_close_time: Utc.timestamp(0, 0),
+                base_account: AccountId::from(ZERO_ACCOUNT),
+                base_amount: Amount::new(0),
+                base_asset: AssetIdentifier::native(),
+                counter_amount: Amount::new(0),
+                counter_account: AccountId::from(ZERO_ACCOUNT),
+                counter_asset: AssetIdentifier::native(),
+                price: PriceRatio::new(1, 1),
+                seller: Seller::Base,
+            },
+        }
+    }
+
+    /// Sets the id of the offer that was matched.
+    pub fn with_offer_id(mut self, offer_id: &str) -> TradeBuilder {
+        self.trade.offer_id = offer_id.to_string();
+        self
+    }
+
+    /// Sets the paging token, which otherwise matches the id.
+    pub fn with_paging_token(mut self, paging_token: &str) -> TradeBuilder {
+        self.trade.paging_token = paging_token.to_string();
+        self
+    }
+
+    /// Sets the time the ledger containing the trade closed.
+    pub fn with_closed_at(mut self, closed_at: DateTime<Utc>) -> TradeBuilder {
+        self.trade.ledger_close_time = closed_at;
+        self
+    }
+
+    /// Sets the account, asset and amount on the base side of the trade.
+    pub fn with_base(
+        mut self,
+        account: AccountId,
+        asset: AssetIdentifier,
+        amount: Amount,
+    ) -> TradeBuilder {
+        self.trade.base_account = account;
+        self.trade.base_asset = asset;
+        self.trade.base_amount = amount;
+        self
+    }
+
+    /// Sets the account, asset and amount on the counter side of the trade.
+    pub fn with_counter(
+        mut self,
+        account: AccountId,
+        asset: AssetIdentifier,
+        amount: Amount,
+    ) -> TradeBuilder {
+        self.trade.counter_account = account;
+        self.trade.counter_asset = asset;
+        self.trade.counter_amount = amount;
+        self
+    }
+
+    /// Sets the price of the trade.
+    pub fn with_price(mut self, price: PriceRatio) -> TradeBuilder {
+        self.trade.price = price;
+        self
+    }
+
+    /// Sets which side of the trade created the sell offer.
+    pub fn with_seller(mut self, seller: Seller) -> TradeBuilder {
+        self.trade.seller = seller;
+        self
+    }
+
+    /// Creates the trade.
+    pub fn build(self) -> Trade {
+        self.trade
+    }
 }
 
 /// The aggregation of trades for a specifc base/counter pair of assets over a given
 /// time period.
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct TradeAggregation {
     // Several fields are omitted since they don't seem to be in the actual response from horizon.
     // Or they don't seem to make sense.
@@ -258,6 +515,33 @@ pub struct TradeAggregation {
 }
 
 impl TradeAggregation {
+    /// Creates a new trade aggregation for the segment starting at `timestamp`,
+    /// given in milliseconds since epoch.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        timestamp: u64,
+        trade_count: u64,
+        base_volume: Amount,
+        counter_volume: Amount,
+        avg: Amount,
+        high: Amount,
+        low: Amount,
+        open: Amount,
+        close: Amount,
+    ) -> TradeAggregation {
+        TradeAggregation {
+            timestamp,
+            trade_count,
+            base_volume,
+            counter_volume,
+            avg,
+            high,
+            low,
+            open,
+            close,
+        }
+    }
+
     /// The beginning of this time segment
     pub fn started_at(&self) -> DateTime<Utc> {
         let secs = self.timestamp / 1000;
@@ -1,17 +1,16 @@
-use super::{amount::Amount, deserialize};
+use super::transaction_meta::{self, BalanceChange};
+use super::transaction_result::FailureReasons;
+use super::{account_id::AccountId, amount::Amount, deserialize, serialize, Links};
 use chrono::prelude::*;
+use xdr::XdrError;
 
 /// Memos are a useful source for adding meta data to a transaction.
 /// A consists of a type and content (unless memo type is none).
 ///
 /// To learn more about the concept of memos in the Stellar network, take a look at the Stellar memo description here:
 /// <https://www.stellar.org/developers/guides/concepts/transactions.html#memo>
-#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
-#[serde(
-    rename_all = "lowercase",
-    tag = "memo_type",
-    content = "memo"
-)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase", tag = "memo_type", content = "memo")]
 pub enum Memo {
     ///  A string encoded using either ASCII or UTF-8, up to 28-bytes long.
     Text(String),
@@ -29,15 +28,20 @@ pub enum Memo {
 /// A transaction is a grouping of operations.
 ///
 /// To learn more about the concept of transactions in the Stellar network, take a look at the Stellar transactions concept guide.
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Transaction {
+    #[serde(rename = "_links", default)]
+    links: Links,
     id: String,
     paging_token: String,
     hash: String,
     ledger: u32,
     created_at: DateTime<Utc>,
-    source_account: String,
-    #[serde(deserialize_with = "deserialize::from_str")]
+    source_account: AccountId,
+    #[serde(
+        deserialize_with = "deserialize::from_str",
+        serialize_with = "serialize::to_string"
+    )]
     source_account_sequence: u64,
     fee_paid: i64,
     operation_count: u32,
@@ -56,6 +60,11 @@ impl Transaction {
         &self.id
     }
 
+    /// The links to the resources related to this one.
+    pub fn links(&self) -> &Links {
+        &self.links
+    }
+
     /// A paging token suitable for use as the cursor parameter to transaction collection
     /// resources.
     pub fn paging_token(&self) -> &String {
@@ -78,7 +87,7 @@ impl Transaction {
     }
 
     /// The account that signed and initiated the transaction
-    pub fn source_account(&self) -> &String {
+    pub fn source_account(&self) -> &AccountId {
         &self.source_account
     }
 
@@ -110,16 +119,34 @@ impl Transaction {
         &self.envelope_xdr
     }
 
-    /// A base64 encoded string of the raw TransactionResultPair xdr struct for this transaction
+    /// A base64 encoded string of the raw TransactionResult xdr struct for this transaction
     pub fn result_xdr(&self) -> &String {
         &self.result_xdr
     }
 
+    /// Why the transaction failed, decoded from its result xdr, or `None`
+    /// if it succeeded. Horizon only lists failed transactions when asked to
+    /// with `with_include_failed`.
+    pub fn failure_reasons(&self) -> Result<Option<FailureReasons>, XdrError> {
+        FailureReasons::from_result_xdr(&self.result_xdr)
+    }
+
     /// A base64 encoded string of the raw TransactionMeta xdr struct for this transaction
     pub fn result_meta_xdr(&self) -> &String {
         &self.result_meta_xdr
     }
 
+    /// Every balance the transaction changed, including the fee it was
+    /// charged, worked out from its fee and result meta xdr. This catches
+    /// deposits that arrive in ways other than a plain payment, such as
+    /// path payments, account merges and claimed balances.
+    ///
+    /// Meta that touches contract data can't be read yet, and results in an
+    /// error.
+    pub fn balance_changes(&self) -> Result<Vec<BalanceChange>, XdrError> {
+        transaction_meta::balance_changes(&self.fee_meta_xdr, &self.result_meta_xdr)
+    }
+
     /// A base64 encoded string of the raw LedgerEntryChanges xdr struct produced by taking fees
     /// for this transaction.
     pub fn fee_meta_xdr(&self) -> &String {
@@ -135,6 +162,7 @@ impl Transaction {
 #[cfg(test)]
 mod transaction_tests {
     use super::*;
+    use resources::AssetIdentifier;
     use serde_json;
 
     fn transaction_json() -> &'static str {
@@ -221,6 +249,40 @@ mod transaction_tests {
         }
     }
 
+    #[test]
+    fn it_has_no_failure_reasons_when_successful() {
+        let transaction: Transaction = serde_json::from_str(&transaction_json()).unwrap();
+        assert_eq!(transaction.failure_reasons(), Ok(None));
+    }
+
+    #[test]
+    fn it_lists_balance_changes() {
+        let transaction: Transaction = serde_json::from_str(&transaction_json()).unwrap();
+        let changes: Vec<_> = transaction
+            .balance_changes()
+            .unwrap()
+            .iter()
+            .map(|change| {
+                (
+                    change.account_id().to_string(),
+                    change.asset().clone(),
+                    change.delta(),
+                    change.is_fee(),
+                )
+            })
+            .collect();
+        let source = "GB6YPGW5JFMMP2QB2USQ33EUWTXVL4ZT5ITUNCY3YKVWOJPP57CANOF3".to_string();
+        let destination = "GCLGBS75BIBE7NZFBQDPEE6GATKNSVDHWDMJUIWVHQSFIF3QHZ7VBBYH".to_string();
+        assert_eq!(
+            changes,
+            vec![
+                (source.clone(), AssetIdentifier::native(), -100, true),
+                (source, AssetIdentifier::native(), -999_900_000, false),
+                (destination, AssetIdentifier::native(), 999_900_000, false),
+            ]
+        );
+    }
+
     #[test]
     fn it_parses_memo_text() {
         let transaction: Transaction = serde_json::from_str(&transaction_json_memo_text()).unwrap();
@@ -275,3 +337,314 @@ mod transaction_tests {
         assert_eq!(transaction.memo(), &Memo::None);
     }
 }
+
+/// Builds a `Transaction` from its hash and source account, setting the
+/// ledger it made it into, what it paid, its memo and its xdr.
+///
+/// ```
+/// use stellar_client::resources::{Memo, TransactionBuilder};
+///
+/// let transaction = TransactionBuilder::new(
+///     "5fef21d5",
+///     "GB6YPGW5JFMMP2QB2USQ33EUWTXVL4ZT5ITUNCY3YKVWOJPP57CANOF3".into(),
+/// )
+///     .with_fee_paid(100)
+///     .with_memo(Memo::Id(1))
+///     .build();
+/// assert_eq!(transaction.fee_paid(), 100);
+/// assert_eq!(transaction.memo(), &Memo::Id(1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TransactionBuilder {
+    transaction: Transaction,
+}
+
+impl TransactionBuilder {
+    /// Starts building a transaction with the given hash submitted by the
+    /// source account. Numbers start at zero, the xdr is empty, the creation
+    /// time is the unix epoch and there is no memo.
+    pub fn new(hash: &str, source_account: AccountId) -> TransactionBuilder {
+        TransactionBuilder {
+            transaction: Transaction {
+                links: Links::default(),
+                id: hash.to_string(),
+                paging_token: String::new(),
+                hash: hash.to_string(),
+                ledger: 0,
+                created_at: Utc.timestamp(0, 0),
+                source_account,
+                source_account_sequence: 0,
+                fee_paid: 0,
+                operation_count: 0,
+                envelope_xdr: String::new(),
+                result_xdr: String::new(),
+                result_meta_xdr: String::new(),
+                fee_meta_xdr: String::new(),
+                memo: Memo::None,
+            },
+        }
+    }
+
+    /// Sets the paging token of the transaction.
+    pub fn with_paging_token(mut self, paging_token: &str) -> TransactionBuilder {
+        self.transaction.paging_token = paging_token.to_string();
+        self
+    }
+
+    /// Sets the sequence of the ledger the transaction was included in.
+    pub fn with_ledger(mut self, ledger: u32) -> TransactionBuilder {
+        self.transaction.ledger = ledger;
+        self
+    }
+
+    /// Sets the time the transaction was created.
+    pub fn with_created_at(mut self, created_at: DateTime<Utc>) -> TransactionBuilder {
+        self.transaction.created_at = created_at;
+        self
+    }
+
+    /// Sets the sequence number of the source account used by the transaction.
+    pub fn with_source_account_sequence(mut self, sequence: u64) -> TransactionBuilder {
+        self.transaction.source_account_sequence = sequence;
+        self
+    }
+
+    /// Sets the fee paid, in stroops.
+    pub fn with_fee_paid(mut self, fee_paid: i64) -> TransactionBuilder {
+        self.transaction.fee_paid = fee_paid;
+        self
+    }
+
+    /// Sets the number of operations in the transaction.
+    pub fn with_operation_count(mut self, operation_count: u32) -> TransactionBuilder {
+        self.transaction.operation_count = operation_count;
+        self
+    }
+
+    /// Sets the base64 encoded envelope xdr.
+    pub fn with_envelope_xdr(mut self, xdr: &str) -> TransactionBuilder {
+        self.transaction.envelope_xdr = xdr.to_string();
+        self
+    }
+
+    /// Sets the base64 encoded result xdr.
+    pub fn with_result_xdr(mut self, xdr: &str) -> TransactionBuilder {
+        self.transaction.result_xdr = xdr.to_string();
+        self
+    }
+
+    /// Sets the base64 encoded result meta xdr.
+    pub fn with_result_meta_xdr(mut self, xdr: &str) -> TransactionBuilder {
+        self.transaction.result_meta_xdr = xdr.to_string();
+        self
+    }
+
+    /// Sets the base64 encoded fee meta xdr.
+    pub fn with_fee_meta_xdr(mut self, xdr: &str) -> TransactionBuilder {
+        self.transaction.fee_meta_xdr = xdr.to_string();
+        self
+    }
+
+    /// Sets the memo attached to the transaction.
+    pub fn with_memo(mut self, memo: Memo) -> TransactionBuilder {
+        self.transaction.memo = memo;
+        self
+    }
+
+    /// Creates the transaction.
+    pub fn build(self) -> Transaction {
+        self.transaction
+    }
+}
+
+/// A transaction without its xdr, for consumers that only need to know who
+/// sent what and when.
+///
+/// The four xdr blobs make up most of a transaction as horizon sends it.
+/// Parsing into a `TransactionHeader` passes over them without keeping them,
+/// which saves the memory and time of copying them for every record of a
+/// large page. Fetch them with the `without_xdr` option of the transaction
+/// list endpoints.
+///
+/// ```
+/// use stellar_client::resources::{Resource, TransactionHeader};
+///
+/// let transaction = TransactionHeader::from_json_str(
+///     include_str!("../../fixtures/transactions/transaction_memo_none.json"),
+/// ).unwrap();
+/// assert_eq!(transaction.operation_count(), 1);
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TransactionHeader {
+    #[serde(rename = "_links", default)]
+    links: Links,
+    id: String,
+    paging_token: String,
+    hash: String,
+    ledger: u32,
+    created_at: DateTime<Utc>,
+    source_account: AccountId,
+    #[serde(
+        deserialize_with = "deserialize::from_str",
+        serialize_with = "serialize::to_string"
+    )]
+    source_account_sequence: u64,
+    fee_paid: i64,
+    operation_count: u32,
+    // The xdr is declared so that it is skipped rather than buffered for
+    // the flattened memo.
+    #[serde(
+        rename = "envelope_xdr",
+        default,
+        skip_serializing,
+        deserialize_with = "deserialize::skip"
+    )]
+    _envelope_xdr: (),
+    #[serde(
+        rename = "result_xdr",
+        default,
+        skip_serializing,
+        deserialize_with = "deserialize::skip"
+    )]
+    _result_xdr: (),
+    #[serde(
+        rename = "result_meta_xdr",
+        default,
+        skip_serializing,
+        deserialize_with = "deserialize::skip"
+    )]
+    _result_meta_xdr: (),
+    #[serde(
+        rename = "fee_meta_xdr",
+        default,
+        skip_serializing,
+        deserialize_with = "deserialize::skip"
+    )]
+    _fee_meta_xdr: (),
+    #[serde(flatten)]
+    memo: Memo,
+}
+
+impl TransactionHeader {
+    /// The canonical id of this transaction.
+    pub fn id(&self) -> &String {
+        &self.id
+    }
+
+    /// The links to the resources related to this one.
+    pub fn links(&self) -> &Links {
+        &self.links
+    }
+
+    /// A paging token suitable for use as the cursor parameter to transaction collection
+    /// resources.
+    pub fn paging_token(&self) -> &String {
+        &self.paging_token
+    }
+
+    /// A hex-encoded SHA-256 hash of the transaction’s XDR-encoded form.
+    pub fn hash(&self) -> &String {
+        &self.hash
+    }
+
+    /// Sequence number of the ledger in which this transaction was applied.
+    pub fn ledger(&self) -> u32 {
+        self.ledger
+    }
+
+    /// The time the transaction was processed.
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    /// The account that signed and initiated the transaction
+    pub fn source_account(&self) -> &AccountId {
+        &self.source_account
+    }
+
+    /// The sequence number the source account used for the transaction.
+    pub fn source_account_sequence(&self) -> u64 {
+        self.source_account_sequence
+    }
+
+    /// The fee paid by the source account of this transaction, in stroops.
+    pub fn fee_paid(&self) -> i64 {
+        self.fee_paid
+    }
+
+    /// The fee represented as an amount.
+    pub fn fee_as_amount(&self) -> Amount {
+        Amount::new(self.fee_paid)
+    }
+
+    /// The number of operations that are contained within this transaction.
+    pub fn operation_count(&self) -> u32 {
+        self.operation_count
+    }
+
+    /// The value/contents of the memo for this transaction
+    pub fn memo(&self) -> &Memo {
+        &self.memo
+    }
+}
+
+impl From<Transaction> for TransactionHeader {
+    fn from(transaction: Transaction) -> TransactionHeader {
+        TransactionHeader {
+            links: transaction.links,
+            id: transaction.id,
+            paging_token: transaction.paging_token,
+            hash: transaction.hash,
+            ledger: transaction.ledger,
+            created_at: transaction.created_at,
+            source_account: transaction.source_account,
+            source_account_sequence: transaction.source_account_sequence,
+            fee_paid: transaction.fee_paid,
+            operation_count: transaction.operation_count,
+            _envelope_xdr: (),
+            _result_xdr: (),
+            _result_meta_xdr: (),
+            _fee_meta_xdr: (),
+            memo: transaction.memo,
+        }
+    }
+}
+
+#[cfg(test)]
+mod transaction_header_tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn it_parses_a_transaction_without_its_xdr() {
+        let json = include_str!("../../fixtures/transactions/transaction_memo_text.json");
+        let header: TransactionHeader = serde_json::from_str(json).unwrap();
+        let transaction: Transaction = serde_json::from_str(json).unwrap();
+        assert_eq!(header.hash(), transaction.hash());
+        assert_eq!(header.paging_token(), transaction.paging_token());
+        assert_eq!(
+            header.source_account_sequence(),
+            transaction.source_account_sequence()
+        );
+        assert_eq!(header.memo(), &Memo::Text("hello".to_string()));
+
+        let value = serde_json::to_value(&header).unwrap();
+        assert!(value.get("envelope_xdr").is_none());
+        assert_eq!(value["memo"], "hello");
+
+        let converted = TransactionHeader::from(transaction);
+        assert_eq!(converted.hash(), header.hash());
+    }
+
+    #[cfg(feature = "validate")]
+    #[test]
+    fn it_knows_the_fields_it_skips() {
+        use resources::Resource;
+
+        let json = include_str!("../../fixtures/transactions/transaction_memo_text.json");
+        assert_eq!(
+            TransactionHeader::unknown_fields(json).unwrap(),
+            Transaction::unknown_fields(json).unwrap()
+        );
+    }
+}
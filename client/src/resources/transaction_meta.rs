@@ -0,0 +1,562 @@
+use super::transaction_result::offer_entry;
+use super::{AccountId, AssetIdentifier};
+use strkey;
+use xdr::{Reader, Result, XdrError};
+
+/// A change in the balance of one asset held by one account, worked out by
+/// comparing the account's ledger entries from before and after a
+/// transaction.
+///
+/// Unlike payment operations and effects, ledger entries catch every way a
+/// balance can move, including path payments, trades, merges, claimable
+/// balances and fees. Use `Transaction::balance_changes` to list them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceChange {
+    account_id: AccountId,
+    asset: AssetIdentifier,
+    delta: i64,
+    fee: bool,
+}
+
+impl BalanceChange {
+    /// The account whose balance changed.
+    pub fn account_id(&self) -> &AccountId {
+        &self.account_id
+    }
+
+    /// The asset whose balance changed.
+    pub fn asset(&self) -> &AssetIdentifier {
+        &self.asset
+    }
+
+    /// How much the balance changed by, in stroops. Debits are negative.
+    pub fn delta(&self) -> i64 {
+        self.delta
+    }
+
+    /// Whether the change was made for the transaction as a whole rather
+    /// than by one of its operations. These are the fee, and any part of it
+    /// that is refunded.
+    pub fn is_fee(&self) -> bool {
+        self.fee
+    }
+}
+
+type Key = (AccountId, AssetIdentifier);
+
+/// Lists the balance changes in a transaction's fee meta, which is a bare
+/// `LedgerEntryChanges`, and its result meta, a `TransactionMeta`. Changes
+/// to the same balance are added together.
+pub(super) fn balance_changes(
+    fee_meta_xdr: &str,
+    result_meta_xdr: &str,
+) -> Result<Vec<BalanceChange>> {
+    let mut changes = Vec::new();
+    ledger_entry_changes(&mut Reader::from_base64(fee_meta_xdr)?, true, &mut changes)?;
+
+    let mut r = Reader::from_base64(result_meta_xdr)?;
+    let version = r.i32()?;
+    if version > 3 {
+        return Err(XdrError::UnknownDiscriminant("TransactionMeta", version));
+    }
+    if version == 3 {
+        r.extension_point()?;
+    }
+    if version >= 1 {
+        ledger_entry_changes(&mut r, true, &mut changes)?;
+    }
+    let operations = r.u32()?;
+    for _ in 0..operations {
+        ledger_entry_changes(&mut r, false, &mut changes)?;
+    }
+    if version >= 2 {
+        ledger_entry_changes(&mut r, true, &mut changes)?;
+    }
+    // Whatever soroban adds after the changes doesn't move balances.
+
+    changes.retain(|change| change.delta != 0);
+    Ok(changes)
+}
+
+/// Reads a `LedgerEntryChanges`, where every updated or removed entry is
+/// preceded by its state beforehand.
+fn ledger_entry_changes(r: &mut Reader, fee: bool, changes: &mut Vec<BalanceChange>) -> Result<()> {
+    let mut states: Vec<(Key, i64)> = Vec::new();
+    let count = r.u32()?;
+    for _ in 0..count {
+        let (key, balance) = match r.i32()? {
+            // Created or updated
+            0 | 1 => match ledger_entry(r)? {
+                Some((key, after)) => {
+                    let before = take_state(&mut states, &key).unwrap_or(0);
+                    (key, after - before)
+                }
+                None => continue,
+            },
+            // Removed
+            2 => match ledger_key(r)? {
+                Some(key) => {
+                    let before = take_state(&mut states, &key).unwrap_or(0);
+                    (key, -before)
+                }
+                None => continue,
+            },
+            // The state before the change, or an archived entry restored
+            3 | 4 => {
+                if let Some(state) = ledger_entry(r)? {
+                    states.push(state);
+                }
+                continue;
+            }
+            other => return Err(XdrError::UnknownDiscriminant("LedgerEntryChange", other)),
+        };
+        add(changes, key, balance, fee);
+    }
+    Ok(())
+}
+
+fn take_state(states: &mut Vec<(Key, i64)>, key: &Key) -> Option<i64> {
+    let index = states.iter().position(|state| state.0 == *key)?;
+    Some(states.remove(index).1)
+}
+
+fn add(changes: &mut Vec<BalanceChange>, key: Key, delta: i64, fee: bool) {
+    let (account_id, asset) = key;
+    let existing = changes.iter_mut().find(|change| {
+        change.fee == fee && change.account_id == account_id && change.asset == asset
+    });
+    match existing {
+        Some(change) => change.delta += delta,
+        None => changes.push(BalanceChange {
+            account_id,
+            asset,
+            delta,
+            fee,
+        }),
+    }
+}
+
+fn account_id(key: &[u8; 32]) -> AccountId {
+    AccountId::from(strkey::encode(strkey::ACCOUNT, key).as_str())
+}
+
+/// Reads a `TrustLineAsset`. Liquidity pool shares are not an asset of
+/// their own, so they come back as `None`.
+fn trust_line_asset(r: &mut Reader) -> Result<Option<AssetIdentifier>> {
+    match r.i32()? {
+        0 => Ok(Some(AssetIdentifier::native())),
+        1 => r.issued_asset(4).map(Some),
+        2 => r.issued_asset(12).map(Some),
+        3 => {
+            r.take(32)?;
+            Ok(None)
+        }
+        other => Err(XdrError::UnknownDiscriminant("TrustLineAsset", other)),
+    }
+}
+
+/// Reads a `LedgerEntry`, returning the balance it holds if it is an account
+/// or a trust line.
+fn ledger_entry(r: &mut Reader) -> Result<Option<(Key, i64)>> {
+    // The ledger it was last modified in
+    r.u32()?;
+    let balance = match r.i32()? {
+        0 => Some(account_entry(r)?),
+        1 => trust_line_entry(r)?,
+        2 => {
+            offer_entry(r)?;
+            None
+        }
+        3 => {
+            r.account_id()?;
+            r.opaque()?;
+            r.opaque()?;
+            r.extension_point()?;
+            None
+        }
+        4 => {
+            claimable_balance_entry(r)?;
+            None
+        }
+        5 => {
+            liquidity_pool_entry(r)?;
+            None
+        }
+        7 => {
+            contract_code_entry(r)?;
+            None
+        }
+        // A time to live: the hash of the entry it belongs to and when it
+        // runs out
+        9 => {
+            r.take(32)?;
+            r.u32()?;
+            None
+        }
+        other => return Err(XdrError::UnknownDiscriminant("LedgerEntryType", other)),
+    };
+    match r.i32()? {
+        0 => {}
+        // Who sponsors the entry
+        1 => {
+            if r.bool()? {
+                r.account_id()?;
+            }
+            r.extension_point()?;
+        }
+        other => return Err(XdrError::UnknownDiscriminant("LedgerEntryExt", other)),
+    }
+    Ok(balance)
+}
+
+/// Reads a `LedgerKey`, returning the balance it names if it is an account
+/// or a trust line.
+fn ledger_key(r: &mut Reader) -> Result<Option<Key>> {
+    Ok(match r.i32()? {
+        0 => Some((account_id(&r.account_id()?), AssetIdentifier::native())),
+        1 => {
+            let account = account_id(&r.account_id()?);
+            trust_line_asset(r)?.map(|asset| (account, asset))
+        }
+        2 => {
+            r.account_id()?;
+            r.i64()?;
+            None
+        }
+        3 => {
+            r.account_id()?;
+            r.opaque()?;
+            None
+        }
+        4 => {
+            claimable_balance_id(r)?;
+            None
+        }
+        5 | 7 | 9 => {
+            r.take(32)?;
+            None
+        }
+        other => return Err(XdrError::UnknownDiscriminant("LedgerKey", other)),
+    })
+}
+
+fn account_entry(r: &mut Reader) -> Result<(Key, i64)> {
+    let account = account_id(&r.account_id()?);
+    let balance = r.i64()?;
+    // The sequence number and number of subentries
+    r.i64()?;
+    r.u32()?;
+    // The inflation destination
+    if r.bool()? {
+        r.account_id()?;
+    }
+    // The flags, home domain and thresholds
+    r.u32()?;
+    r.opaque()?;
+    r.take(4)?;
+    r.vec(|r| {
+        signer_key(r)?;
+        r.u32()
+    })?;
+    match r.i32()? {
+        0 => {}
+        1 => {
+            // The buying and selling liabilities
+            r.i64()?;
+            r.i64()?;
+            match r.i32()? {
+                0 => {}
+                2 => {
+                    // The number of entries sponsored and sponsoring, then
+                    // the sponsor of each signer
+                    r.u32()?;
+                    r.u32()?;
+                    r.vec(|r| {
+                        if r.bool()? {
+                            r.account_id()?;
+                        }
+                        Ok(())
+                    })?;
+                    match r.i32()? {
+                        0 => {}
+                        // When the sequence number was last bumped
+                        3 => {
+                            r.extension_point()?;
+                            r.u32()?;
+                            r.i64()?;
+                        }
+                        other => {
+                            return Err(XdrError::UnknownDiscriminant(
+                                "AccountEntryExtensionV2",
+                                other,
+                            ))
+                        }
+                    }
+                }
+                other => {
+                    return Err(XdrError::UnknownDiscriminant(
+                        "AccountEntryExtensionV1",
+                        other,
+                    ))
+                }
+            }
+        }
+        other => return Err(XdrError::UnknownDiscriminant("AccountEntry", other)),
+    }
+    Ok(((account, AssetIdentifier::native()), balance))
+}
+
+fn signer_key(r: &mut Reader) -> Result<()> {
+    match r.i32()? {
+        0 | 1 | 2 => {
+            r.take(32)?;
+        }
+        // A key along with the payload it signs
+        3 => {
+            r.take(32)?;
+            r.opaque()?;
+        }
+        other => return Err(XdrError::UnknownDiscriminant("SignerKey", other)),
+    }
+    Ok(())
+}
+
+fn trust_line_entry(r: &mut Reader) -> Result<Option<(Key, i64)>> {
+    let account = account_id(&r.account_id()?);
+    let asset = trust_line_asset(r)?;
+    let balance = r.i64()?;
+    // The limit and flags
+    r.i64()?;
+    r.u32()?;
+    match r.i32()? {
+        0 => {}
+        1 => {
+            // The buying and selling liabilities
+            r.i64()?;
+            r.i64()?;
+            match r.i32()? {
+                0 => {}
+                // How many liquidity pools use the trust line
+                2 => {
+                    r.i32()?;
+                    r.extension_point()?;
+                }
+                other => {
+                    return Err(XdrError::UnknownDiscriminant(
+                        "TrustLineEntryExtensionV2",
+                        other,
+                    ))
+                }
+            }
+        }
+        other => return Err(XdrError::UnknownDiscriminant("TrustLineEntry", other)),
+    }
+    Ok(asset.map(|asset| ((account, asset), balance)))
+}
+
+fn claimable_balance_id(r: &mut Reader) -> Result<()> {
+    match r.i32()? {
+        0 => {
+            r.take(32)?;
+            Ok(())
+        }
+        other => Err(XdrError::UnknownDiscriminant("ClaimableBalanceID", other)),
+    }
+}
+
+fn claimable_balance_entry(r: &mut Reader) -> Result<()> {
+    claimable_balance_id(r)?;
+    r.vec(|r| match r.i32()? {
+        0 => {
+            r.account_id()?;
+            claim_predicate(r)
+        }
+        other => Err(XdrError::UnknownDiscriminant("Claimant", other)),
+    })?;
+    r.asset()?;
+    r.i64()?;
+    match r.i32()? {
+        0 => Ok(()),
+        // The flags
+        1 => {
+            r.extension_point()?;
+            r.u32()?;
+            Ok(())
+        }
+        other => Err(XdrError::UnknownDiscriminant(
+            "ClaimableBalanceEntry",
+            other,
+        )),
+    }
+}
+
+fn claim_predicate(r: &mut Reader) -> Result<()> {
+    r.nested(|r| {
+        match r.i32()? {
+            0 => {}
+            // And, or
+            1 | 2 => {
+                r.vec(claim_predicate)?;
+            }
+            // Not
+            3 => {
+                if r.bool()? {
+                    claim_predicate(r)?;
+                }
+            }
+            // Before an absolute or relative time
+            4 | 5 => {
+                r.i64()?;
+            }
+            other => return Err(XdrError::UnknownDiscriminant("ClaimPredicate", other)),
+        }
+        Ok(())
+    })
+}
+
+fn liquidity_pool_entry(r: &mut Reader) -> Result<()> {
+    r.take(32)?;
+    match r.i32()? {
+        // The two assets and fee, then the reserves, total shares and
+        // trust line count
+        0 => {
+            r.asset()?;
+            r.asset()?;
+            r.i32()?;
+            for _ in 0..4 {
+                r.i64()?;
+            }
+            Ok(())
+        }
+        other => Err(XdrError::UnknownDiscriminant("LiquidityPoolType", other)),
+    }
+}
+
+fn contract_code_entry(r: &mut Reader) -> Result<()> {
+    match r.i32()? {
+        0 => {}
+        // The cost inputs of the code
+        1 => {
+            r.extension_point()?;
+            for _ in 0..10 {
+                r.u32()?;
+            }
+        }
+        other => return Err(XdrError::UnknownDiscriminant("ContractCodeEntry", other)),
+    }
+    // The hash of the code, then the code itself
+    r.take(32)?;
+    r.opaque()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod balance_changes_tests {
+    use super::*;
+    use base64;
+
+    const SOURCE: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+    const ISSUER: &str = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4";
+
+    fn encode(words: &[u32]) -> String {
+        let bytes: Vec<u8> = words
+            .iter()
+            .flat_map(|word| {
+                vec![
+                    (word >> 24) as u8,
+                    (word >> 16) as u8,
+                    (word >> 8) as u8,
+                    *word as u8,
+                ]
+            })
+            .collect();
+        base64::encode(&bytes)
+    }
+
+    fn key(account: &str) -> Vec<u32> {
+        let key = strkey::decode(strkey::ACCOUNT, account).unwrap();
+        let mut words = vec![0];
+        words.extend(key.chunks(4).map(|b| {
+            (u32::from(b[0]) << 24)
+                | (u32::from(b[1]) << 16)
+                | (u32::from(b[2]) << 8)
+                | u32::from(b[3])
+        }));
+        words
+    }
+
+    // A ledger entry change holding an account with a lumen balance.
+    fn account(change: u32, account: &str, balance: u32) -> Vec<u32> {
+        let mut words = vec![change, 1, 0];
+        words.extend(key(account));
+        // The balance, sequence number, subentries, inflation destination,
+        // flags, home domain, thresholds, signers and both extensions.
+        words.extend_from_slice(&[0, balance, 0, 1, 0, 0, 0, 0, 0x0100_0000, 0, 0, 0]);
+        words
+    }
+
+    // A ledger entry change holding a trust line for USD.
+    fn trust_line(change: u32, account: &str, balance: u32) -> Vec<u32> {
+        let mut words = vec![change, 1, 1];
+        words.extend(key(account));
+        words.extend_from_slice(&[1, 0x5553_4400]);
+        words.extend(key(ISSUER));
+        // The balance, limit, flags and both extensions.
+        words.extend_from_slice(&[0, balance, 0, 1_000, 1, 0, 0]);
+        words
+    }
+
+    #[test]
+    fn it_compares_balances_before_and_after() {
+        let mut fee = vec![2];
+        fee.extend(account(3, SOURCE, 1_000));
+        fee.extend(account(1, SOURCE, 900));
+
+        // Version 1 meta, with no transaction level changes and one
+        // operation that moved USD and created an account.
+        let mut meta = vec![1, 0, 1, 3];
+        meta.extend(trust_line(3, SOURCE, 50));
+        meta.extend(trust_line(1, SOURCE, 20));
+        meta.extend(account(0, ISSUER, 300));
+
+        let changes = balance_changes(&encode(&fee), &encode(&meta)).unwrap();
+        let usd = AssetIdentifier::alphanum4("USD", ISSUER).unwrap();
+        assert_eq!(changes.len(), 3);
+        assert_eq!(*changes[0].account_id(), SOURCE);
+        assert_eq!(changes[0].asset(), &AssetIdentifier::native());
+        assert_eq!(changes[0].delta(), -100);
+        assert!(changes[0].is_fee());
+        assert_eq!(changes[1].asset(), &usd);
+        assert_eq!(changes[1].delta(), -30);
+        assert!(!changes[1].is_fee());
+        assert_eq!(*changes[2].account_id(), ISSUER);
+        assert_eq!(changes[2].delta(), 300);
+    }
+
+    #[test]
+    fn it_debits_the_balance_of_a_removed_account() {
+        // An account merged away, then credited to another account.
+        let mut meta = vec![0, 1, 4];
+        meta.extend(account(3, SOURCE, 500));
+        meta.extend_from_slice(&[2, 0]);
+        meta.extend(key(SOURCE));
+        meta.extend(account(3, ISSUER, 100));
+        meta.extend(account(1, ISSUER, 600));
+        let changes = balance_changes(&encode(&[0]), &encode(&meta)).unwrap();
+        let deltas: Vec<_> = changes
+            .iter()
+            .map(|change| (change.account_id().as_str(), change.delta()))
+            .collect();
+        assert_eq!(deltas, vec![(SOURCE, -500), (ISSUER, 500)]);
+    }
+
+    #[test]
+    fn it_errs_on_entries_it_cannot_read() {
+        // A state change holding contract data.
+        let meta = encode(&[0, 1, 1, 3, 1, 6]);
+        assert_eq!(
+            balance_changes(&encode(&[0]), &meta),
+            Err(XdrError::UnknownDiscriminant("LedgerEntryType", 6))
+        );
+    }
+}
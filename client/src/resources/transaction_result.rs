@@ -0,0 +1,546 @@
+use xdr::{Reader, Result, XdrError};
+
+/// Why a transaction failed, decoded from its result XDR.
+///
+/// The codes are the ones horizon uses in the `result_codes` of a failed
+/// submission, such as `tx_failed` and `op_underfunded`. There is one code
+/// for each operation, and operations that went through have `op_success`.
+/// A transaction that failed before any operation ran, such as one with a
+/// bad sequence number, has no operation codes.
+///
+/// ## Example
+///
+/// ```
+/// use stellar_client::resources::FailureReasons;
+///
+/// // A single payment that failed for lack of funds.
+/// let reasons = FailureReasons::from_result_xdr("AAAAAAAAAGT/////AAAAAQAAAAAAAAAB/////gAAAAA=")
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(reasons.transaction(), "tx_failed");
+/// assert_eq!(reasons.operations(), &vec!["op_underfunded".to_string()]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailureReasons {
+    transaction: String,
+    operations: Vec<String>,
+}
+
+impl FailureReasons {
+    pub(crate) fn new(transaction: String, operations: Vec<String>) -> FailureReasons {
+        FailureReasons {
+            transaction,
+            operations,
+        }
+    }
+
+    /// Decodes a base64 `TransactionResult`, as found in a transaction's
+    /// `result_xdr`. Returns `None` if the transaction succeeded.
+    pub fn from_result_xdr(xdr: &str) -> ::std::result::Result<Option<FailureReasons>, XdrError> {
+        let mut r = Reader::from_base64(xdr)?;
+        // The fee charged
+        r.i64()?;
+        let code = r.i32()?;
+        let operations = match code {
+            0 => return Ok(None),
+            -1 => operation_results(&mut r)?,
+            // A fee bump wrapping a transaction, which comes with its own
+            // hash and result.
+            1 | -13 => {
+                r.take(32)?;
+                r.i64()?;
+                match r.i32()? {
+                    0 if code == 1 => return Ok(None),
+                    0 | -1 => operation_results(&mut r)?,
+                    _ => Vec::new(),
+                }
+            }
+            _ => Vec::new(),
+        };
+        Ok(Some(FailureReasons {
+            transaction: transaction_code(code),
+            operations,
+        }))
+    }
+
+    /// The code for the transaction as a whole.
+    pub fn transaction(&self) -> &str {
+        &self.transaction
+    }
+
+    /// The code for each operation, in order.
+    pub fn operations(&self) -> &Vec<String> {
+        &self.operations
+    }
+
+    /// The index of the first operation that didn't go through, which is
+    /// the one that failed the transaction. `None` when the transaction
+    /// failed before its operations ran.
+    pub fn failed_operation(&self) -> Option<usize> {
+        self.operations.iter().position(|code| code != "op_success")
+    }
+}
+
+/// Skips over a `TransactionResult`, such as one in the results of a
+/// history archive.
+#[cfg(feature = "history")]
+pub(crate) fn skip_transaction_result(r: &mut Reader) -> Result<()> {
+    r.i64()?;
+    match r.i32()? {
+        0 | -1 => {
+            operation_results(r)?;
+        }
+        1 | -13 => {
+            r.take(32)?;
+            r.i64()?;
+            if let 0 | -1 = r.i32()? {
+                operation_results(r)?;
+            }
+            r.extension_point()?;
+        }
+        _ => {}
+    }
+    r.extension_point()
+}
+
+fn transaction_code(code: i32) -> String {
+    let name = match code {
+        1 => "tx_fee_bump_inner_success",
+        0 => "tx_success",
+        -1 => "tx_failed",
+        -2 => "tx_too_early",
+        -3 => "tx_too_late",
+        -4 => "tx_missing_operation",
+        -5 => "tx_bad_seq",
+        -6 => "tx_bad_auth",
+        -7 => "tx_insufficient_balance",
+        -8 => "tx_no_source_account",
+        -9 => "tx_insufficient_fee",
+        -10 => "tx_bad_auth_extra",
+        -11 => "tx_internal_error",
+        -12 => "tx_not_supported",
+        -13 => "tx_fee_bump_inner_failed",
+        -14 => "tx_bad_sponsorship",
+        -15 => "tx_bad_minseq_age_or_gap",
+        -16 => "tx_malformed",
+        -17 => "tx_soroban_invalid",
+        _ => return format!("tx_unknown_{}", -code),
+    };
+    name.to_string()
+}
+
+fn operation_results(r: &mut Reader) -> Result<Vec<String>> {
+    r.vec(operation_result)
+}
+
+/// Reads an `OperationResult`, skipping over whatever a successful operation
+/// returns so that the next result can be read.
+fn operation_result(r: &mut Reader) -> Result<String> {
+    let name = match r.i32()? {
+        0 => return inner_operation_result(r),
+        -1 => "op_bad_auth",
+        -2 => "op_no_source_account",
+        -3 => "op_not_supported",
+        -4 => "op_too_many_subentries",
+        -5 => "op_exceeded_work_limit",
+        -6 => "op_too_many_sponsoring",
+        other => return Ok(format!("op_unknown_{}", -other)),
+    };
+    Ok(name.to_string())
+}
+
+fn inner_operation_result(r: &mut Reader) -> Result<String> {
+    let kind = r.i32()?;
+    let code = r.i32()?;
+    if code == 0 {
+        skip_success(r, kind)?;
+        return Ok("op_success".to_string());
+    }
+    // Path payments name the asset that has no issuer.
+    if (kind == 2 || kind == 13) && code == -9 {
+        r.asset()?;
+    }
+    let codes = failure_codes(kind)?;
+    Ok(match codes.get((-code - 1) as usize) {
+        Some(name) => format!("op_{}", name),
+        None => format!("op_unknown_{}", -code),
+    })
+}
+
+/// The failure codes of each operation type, starting from -1.
+fn failure_codes(kind: i32) -> Result<&'static [&'static str]> {
+    const PAYMENT: &[&str] = &[
+        "malformed",
+        "underfunded",
+        "src_no_trust",
+        "src_not_authorized",
+        "no_destination",
+        "no_trust",
+        "not_authorized",
+        "line_full",
+        "no_issuer",
+    ];
+    const PATH_PAYMENT_STRICT_RECEIVE: &[&str] = &[
+        "malformed",
+        "underfunded",
+        "src_no_trust",
+        "src_not_authorized",
+        "no_destination",
+        "no_trust",
+        "not_authorized",
+        "line_full",
+        "no_issuer",
+        "too_few_offers",
+        "cross_self",
+        "over_source_max",
+    ];
+    const PATH_PAYMENT_STRICT_SEND: &[&str] = &[
+        "malformed",
+        "underfunded",
+        "src_no_trust",
+        "src_not_authorized",
+        "no_destination",
+        "no_trust",
+        "not_authorized",
+        "line_full",
+        "no_issuer",
+        "too_few_offers",
+        "cross_self",
+        "under_dest_min",
+    ];
+    const MANAGE_OFFER: &[&str] = &[
+        "malformed",
+        "sell_no_trust",
+        "buy_no_trust",
+        "sell_not_authorized",
+        "buy_not_authorized",
+        "line_full",
+        "underfunded",
+        "cross_self",
+        "sell_no_issuer",
+        "buy_no_issuer",
+        "offer_not_found",
+        "low_reserve",
+    ];
+    const FOOTPRINT: &[&str] = &[
+        "malformed",
+        "resource_limit_exceeded",
+        "insufficient_refundable_fee",
+    ];
+    Ok(match kind {
+        0 => &["malformed", "underfunded", "low_reserve", "already_exists"],
+        1 => PAYMENT,
+        2 => PATH_PAYMENT_STRICT_RECEIVE,
+        3 | 4 | 12 => MANAGE_OFFER,
+        5 => &[
+            "low_reserve",
+            "too_many_signers",
+            "bad_flags",
+            "invalid_inflation",
+            "cant_change",
+            "unknown_flag",
+            "threshold_out_of_range",
+            "bad_signer",
+            "invalid_home_domain",
+            "auth_revocable_required",
+        ],
+        6 => &[
+            "malformed",
+            "no_issuer",
+            "invalid_limit",
+            "low_reserve",
+            "self_not_allowed",
+            "trust_line_missing",
+            "cannot_delete",
+            "not_auth_maintain_liabilities",
+        ],
+        7 => &[
+            "malformed",
+            "no_trustline",
+            "not_required",
+            "cant_revoke",
+            "self_not_allowed",
+            "low_reserve",
+        ],
+        8 => &[
+            "malformed",
+            "no_account",
+            "immutable_set",
+            "has_sub_entries",
+            "seq_num_too_far",
+            "dest_full",
+            "is_sponsor",
+        ],
+        9 => &["not_time"],
+        10 => &[
+            "not_supported_yet",
+            "data_name_not_found",
+            "low_reserve",
+            "data_invalid_name",
+        ],
+        11 => &["bad_seq"],
+        13 => PATH_PAYMENT_STRICT_SEND,
+        14 => &[
+            "malformed",
+            "low_reserve",
+            "no_trust",
+            "not_authorized",
+            "underfunded",
+        ],
+        15 => &[
+            "does_not_exist",
+            "cannot_claim",
+            "line_full",
+            "no_trust",
+            "not_authorized",
+        ],
+        16 => &["malformed", "already_sponsored", "recursive"],
+        17 => &["not_sponsored"],
+        18 => &[
+            "does_not_exist",
+            "not_sponsor",
+            "low_reserve",
+            "only_transferable",
+            "malformed",
+        ],
+        19 => &[
+            "malformed",
+            "not_clawback_enabled",
+            "no_trust",
+            "underfunded",
+        ],
+        20 => &["does_not_exist", "not_issuer", "not_clawback_enabled"],
+        21 => &[
+            "malformed",
+            "no_trust_line",
+            "cant_revoke",
+            "invalid_state",
+            "low_reserve",
+        ],
+        22 => &[
+            "malformed",
+            "no_trust",
+            "not_authorized",
+            "underfunded",
+            "line_full",
+            "bad_price",
+            "pool_full",
+        ],
+        23 => &[
+            "malformed",
+            "no_trust",
+            "underfunded",
+            "line_full",
+            "under_minimum",
+        ],
+        24 => &[
+            "malformed",
+            "trapped",
+            "resource_limit_exceeded",
+            "entry_archived",
+            "insufficient_refundable_fee",
+        ],
+        25 | 26 => FOOTPRINT,
+        other => return Err(XdrError::UnknownDiscriminant("OperationType", other)),
+    })
+}
+
+fn skip_success(r: &mut Reader, kind: i32) -> Result<()> {
+    match kind {
+        // Path payments: the offers crossed, then where the payment ended up.
+        2 | 13 => {
+            r.vec(claim_atom)?;
+            r.account_id()?;
+            r.asset()?;
+            r.i64()?;
+        }
+        // Offers: the offers crossed, then the offer left on the book, if any.
+        3 | 4 | 12 => {
+            r.vec(claim_atom)?;
+            match r.i32()? {
+                0 | 1 => offer_entry(r)?,
+                2 => {}
+                other => return Err(XdrError::UnknownDiscriminant("ManageOfferEffect", other)),
+            }
+        }
+        // Account merge: the balance moved over.
+        8 => {
+            r.i64()?;
+        }
+        // Inflation: the payouts.
+        9 => {
+            r.vec(|r| {
+                r.account_id()?;
+                r.i64()
+            })?;
+        }
+        // Claimable balance creation: the id of the new balance.
+        14 => match r.i32()? {
+            0 => {
+                r.take(32)?;
+            }
+            other => return Err(XdrError::UnknownDiscriminant("ClaimableBalanceID", other)),
+        },
+        // Contract invocation: the hash of the return value and events.
+        24 => {
+            r.take(32)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn claim_atom(r: &mut Reader) -> Result<()> {
+    match r.i32()? {
+        // The seller as a bare key, or as an account id
+        0 => {
+            r.take(32)?;
+        }
+        1 => {
+            r.account_id()?;
+        }
+        // The liquidity pool id
+        2 => {
+            r.take(32)?;
+            r.asset()?;
+            r.i64()?;
+            r.asset()?;
+            r.i64()?;
+            return Ok(());
+        }
+        other => return Err(XdrError::UnknownDiscriminant("ClaimAtom", other)),
+    }
+    // The offer id, then what was sold and bought.
+    r.i64()?;
+    r.asset()?;
+    r.i64()?;
+    r.asset()?;
+    r.i64()?;
+    Ok(())
+}
+
+/// Skips over an `OfferEntry`.
+pub(super) fn offer_entry(r: &mut Reader) -> Result<()> {
+    r.account_id()?;
+    r.i64()?;
+    r.asset()?;
+    r.asset()?;
+    r.i64()?;
+    // The price, as a fraction, and the flags
+    r.i32()?;
+    r.i32()?;
+    r.u32()?;
+    r.extension_point()
+}
+
+#[cfg(test)]
+mod failure_reasons_tests {
+    use super::*;
+    use base64;
+
+    fn encode(words: &[i32]) -> String {
+        let bytes: Vec<u8> = words
+            .iter()
+            .flat_map(|word| {
+                let word = *word as u32;
+                vec![
+                    (word >> 24) as u8,
+                    (word >> 16) as u8,
+                    (word >> 8) as u8,
+                    word as u8,
+                ]
+            })
+            .collect();
+        base64::encode(&bytes)
+    }
+
+    #[test]
+    fn it_has_no_reasons_for_a_successful_transaction() {
+        let xdr = "AAAAAAAAAGQAAAAAAAAAAQAAAAAAAAABAAAAAAAAAAA=";
+        assert_eq!(FailureReasons::from_result_xdr(xdr).unwrap(), None);
+    }
+
+    #[test]
+    fn it_decodes_a_transaction_level_failure() {
+        // The fee, then tx_bad_seq and the extension.
+        let xdr = encode(&[0, 100, -5, 0]);
+        let reasons = FailureReasons::from_result_xdr(&xdr).unwrap().unwrap();
+        assert_eq!(reasons.transaction(), "tx_bad_seq");
+        assert!(reasons.operations().is_empty());
+        assert_eq!(reasons.failed_operation(), None);
+    }
+
+    #[test]
+    fn it_decodes_each_operation_result() {
+        let mut words = vec![0, 200, -1, 4];
+        // A payment that went through.
+        words.extend_from_slice(&[0, 1, 0]);
+        // An account merge that went through, with the balance it moved.
+        words.extend_from_slice(&[0, 8, 0, 0, 5_000]);
+        // An operation whose source account doesn't exist.
+        words.push(-2);
+        // A trust line change to an issuer that doesn't exist.
+        words.extend_from_slice(&[0, 6, -2]);
+        words.push(0);
+        let reasons = FailureReasons::from_result_xdr(&encode(&words))
+            .unwrap()
+            .unwrap();
+        assert_eq!(reasons.transaction(), "tx_failed");
+        assert_eq!(
+            reasons.operations(),
+            &vec![
+                "op_success".to_string(),
+                "op_success".to_string(),
+                "op_no_source_account".to_string(),
+                "op_no_issuer".to_string(),
+            ]
+        );
+        assert_eq!(reasons.failed_operation(), Some(2));
+    }
+
+    #[test]
+    fn it_skips_over_crossed_offers() {
+        let mut words = vec![0, 100, -1, 2];
+        // A sell offer that crossed one offer and was used up: the seller,
+        // offer id, lumens sold and an alphanum4 asset bought.
+        words.extend_from_slice(&[0, 3, 0, 1, 1, 0]);
+        words.extend_from_slice(&[7; 8]);
+        words.extend_from_slice(&[0, 12, 0, 0, 10, 1, 0x5553_4400, 0]);
+        words.extend_from_slice(&[9; 8]);
+        words.extend_from_slice(&[0, 20, 2]);
+        // A payment that was underfunded.
+        words.extend_from_slice(&[0, 1, -2]);
+        words.push(0);
+        let reasons = FailureReasons::from_result_xdr(&encode(&words))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            reasons.operations(),
+            &vec!["op_success".to_string(), "op_underfunded".to_string()]
+        );
+    }
+
+    #[test]
+    fn it_decodes_a_failed_fee_bump() {
+        let mut words = vec![0, 200, -13];
+        words.extend_from_slice(&[1; 8]);
+        words.extend_from_slice(&[0, 100, -1, 1, 0, 1, -5, 0, 0]);
+        let reasons = FailureReasons::from_result_xdr(&encode(&words))
+            .unwrap()
+            .unwrap();
+        assert_eq!(reasons.transaction(), "tx_fee_bump_inner_failed");
+        assert_eq!(reasons.operations(), &vec!["op_no_destination".to_string()]);
+    }
+
+    #[test]
+    fn it_errs_on_bad_xdr() {
+        assert_eq!(
+            FailureReasons::from_result_xdr("not base64!"),
+            Err(XdrError::Base64)
+        );
+        assert_eq!(
+            FailureReasons::from_result_xdr(&encode(&[0, 100, -1, 1])),
+            Err(XdrError::UnexpectedEnd)
+        );
+    }
+}
@@ -0,0 +1,279 @@
+use base64;
+use serde_json;
+use std::error::Error as StdError;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+use strkey;
+
+/// A JWT issued by a SEP-10 server, with the claims that matter to a client
+/// decoded.
+///
+/// The signature isn't checked: the token was issued by the server the
+/// client is talking to, and only that server, which has the key, can tell
+/// whether it is genuine. Decoding lets a client see who a token is for and
+/// when it expires, so that it can get a new one before being turned away.
+///
+/// The token is left out of the `Debug` output so that it doesn't end up in
+/// logs.
+///
+/// ## Example
+///
+/// ```
+/// use stellar_client::sep10::Jwt;
+///
+/// let jwt = Jwt::decode(
+///     "eyJhbGciOiJIUzI1NiJ9.eyJpc3MiOiJodHRwczovL2V4YW1wbGUuY29tL2F1dGgiLCJzdWIiOiJHQUJDIiwiZXhwIjoxMDB9.c2ln",
+/// ).unwrap();
+/// assert_eq!(jwt.issuer(), Some("https://example.com/auth"));
+/// assert_eq!(jwt.subject(), Some("GABC"));
+/// assert!(jwt.is_expired());
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct Jwt {
+    token: String,
+    claims: Claims,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+struct Claims {
+    #[serde(default)]
+    iss: Option<String>,
+    #[serde(default)]
+    sub: Option<String>,
+    #[serde(default)]
+    iat: Option<u64>,
+    #[serde(default)]
+    exp: Option<u64>,
+    #[serde(default)]
+    jti: Option<String>,
+}
+
+impl Jwt {
+    /// Decodes the claims of a compact JWT: three base64url parts joined by
+    /// dots, the second of which is a json object.
+    pub fn decode(token: &str) -> Result<Jwt, JwtError> {
+        let parts: Vec<&str> = token.split('.').collect();
+        if parts.len() != 3 {
+            return Err(JwtError::Malformed);
+        }
+        let payload = base64::decode_config(parts[1], base64::URL_SAFE_NO_PAD)
+            .map_err(|_| JwtError::Malformed)?;
+        let claims = serde_json::from_slice(&payload).map_err(|_| JwtError::Malformed)?;
+        Ok(Jwt {
+            token: token.to_string(),
+            claims,
+        })
+    }
+
+    /// The token as it was issued, to send in an `Authorization` header.
+    pub fn as_str(&self) -> &str {
+        &self.token
+    }
+
+    /// Who issued the token, the `iss` claim. For SEP-10 this is the url of
+    /// the web auth endpoint.
+    pub fn issuer(&self) -> Option<&str> {
+        self.claims.iss.as_deref()
+    }
+
+    /// Who the token is for, the `sub` claim. For SEP-10 this is the account
+    /// that signed the challenge, followed by a memo when there is one.
+    pub fn subject(&self) -> Option<&str> {
+        self.claims.sub.as_deref()
+    }
+
+    /// The unix time the token was issued, the `iat` claim.
+    pub fn issued_at(&self) -> Option<u64> {
+        self.claims.iat
+    }
+
+    /// The unix time the token expires, the `exp` claim.
+    pub fn expires_at(&self) -> Option<u64> {
+        self.claims.exp
+    }
+
+    /// The id of the token, the `jti` claim.
+    pub fn id(&self) -> Option<&str> {
+        self.claims.jti.as_deref()
+    }
+
+    /// Whether the token has expired. A token without an expiry never does.
+    pub fn is_expired(&self) -> bool {
+        self.expires_within(0)
+    }
+
+    /// Whether the token expires within `seconds` from now.
+    pub fn expires_within(&self, seconds: u64) -> bool {
+        self.expires_within_at(seconds, unix_now())
+    }
+
+    fn expires_within_at(&self, seconds: u64, now: u64) -> bool {
+        self.claims
+            .exp
+            .is_some_and(|exp| exp <= now.saturating_add(seconds))
+    }
+
+    /// Checks that the token hasn't expired, was issued by `issuer` when one
+    /// is given, and is for `subject`.
+    ///
+    /// A token for an account used with a memo, `G...:memo`, or muxed,
+    /// `M...`, is for the account too.
+    pub fn validate(&self, issuer: Option<&str>, subject: &str) -> Result<(), JwtError> {
+        self.validate_at(issuer, subject, unix_now())
+    }
+
+    /// `validate` as of the unix time `now`.
+    pub fn validate_at(
+        &self,
+        issuer: Option<&str>,
+        subject: &str,
+        now: u64,
+    ) -> Result<(), JwtError> {
+        if self.expires_within_at(0, now) {
+            return Err(JwtError::Expired);
+        }
+        if issuer.is_some() && self.issuer() != issuer {
+            return Err(JwtError::WrongIssuer);
+        }
+        if !self.subject().is_some_and(|sub| is_for(sub, subject)) {
+            return Err(JwtError::WrongSubject);
+        }
+        Ok(())
+    }
+}
+
+// Whether the subject of a token, `sub`, is `account` itself or that account
+// with a memo or muxed id.
+fn is_for(sub: &str, account: &str) -> bool {
+    if sub == account {
+        return true;
+    }
+    if let Some((base, _)) = sub.split_once(':') {
+        return base == account;
+    }
+    strkey::decode_muxed(sub)
+        .is_some_and(|(key, _)| strkey::encode(strkey::ACCOUNT, &key) == account)
+}
+
+// The token is left out so that it doesn't end up in logs.
+impl fmt::Debug for Jwt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Jwt").field("claims", &self.claims).finish()
+    }
+}
+
+/// Why a JWT was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JwtError {
+    /// The token isn't a compact JWT with a json payload.
+    Malformed,
+    /// The token has expired.
+    Expired,
+    /// The token was issued by someone else.
+    WrongIssuer,
+    /// The token is for someone else.
+    WrongSubject,
+}
+
+impl fmt::Display for JwtError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+impl StdError for JwtError {
+    fn description(&self) -> &str {
+        match *self {
+            JwtError::Malformed => "The token isn't a JWT",
+            JwtError::Expired => "The token has expired",
+            JwtError::WrongIssuer => "The token was issued by someone else",
+            JwtError::WrongSubject => "The token is for another account",
+        }
+    }
+}
+
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod jwt_tests {
+    use super::*;
+
+    // A token with the claims, signed with nonsense.
+    fn token(claims: &str) -> String {
+        format!(
+            "eyJhbGciOiJIUzI1NiJ9.{}.c2ln",
+            base64::encode_config(claims, base64::URL_SAFE_NO_PAD)
+        )
+    }
+
+    #[test]
+    fn it_decodes_the_claims() {
+        let jwt = Jwt::decode(&token(
+            r#"{"iss":"https://example.com/auth","sub":"GABC","iat":10,"exp":20,"jti":"abc"}"#,
+        ))
+        .unwrap();
+        assert_eq!(jwt.issuer(), Some("https://example.com/auth"));
+        assert_eq!(jwt.subject(), Some("GABC"));
+        assert_eq!((jwt.issued_at(), jwt.expires_at()), (Some(10), Some(20)));
+        assert_eq!(jwt.id(), Some("abc"));
+        assert!(!format!("{:?}", jwt).contains(jwt.as_str()));
+
+        assert_eq!(Jwt::decode("a.b"), Err(JwtError::Malformed));
+        assert_eq!(Jwt::decode("a.!!.c"), Err(JwtError::Malformed));
+        assert_eq!(Jwt::decode(&token("1")), Err(JwtError::Malformed));
+    }
+
+    #[test]
+    fn it_validates_the_claims() {
+        let jwt = Jwt::decode(&token(r#"{"iss":"me","sub":"GABC","exp":100}"#)).unwrap();
+        assert_eq!(jwt.validate_at(Some("me"), "GABC", 99), Ok(()));
+        assert_eq!(jwt.validate_at(None, "GABC", 99), Ok(()));
+        assert_eq!(
+            jwt.validate_at(Some("me"), "GABC", 100),
+            Err(JwtError::Expired)
+        );
+        assert_eq!(
+            jwt.validate_at(Some("you"), "GABC", 99),
+            Err(JwtError::WrongIssuer)
+        );
+        assert_eq!(
+            jwt.validate_at(None, "GXYZ", 99),
+            Err(JwtError::WrongSubject)
+        );
+        assert!(jwt.expires_within_at(10, 95));
+        assert!(!Jwt::decode(&token("{}")).unwrap().is_expired());
+    }
+
+    #[test]
+    fn it_accepts_the_account_with_a_memo_or_muxed() {
+        let account = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+        let key = strkey::decode(strkey::ACCOUNT, account).unwrap();
+        let mut payload = key.to_vec();
+        payload.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 42]);
+        let muxed = strkey::encode(strkey::MUXED_ACCOUNT, &payload);
+
+        let sub = |sub: &str| Jwt::decode(&token(&format!(r#"{{"sub":"{}"}}"#, sub))).unwrap();
+        let memo = format!("{}:1234", account);
+        assert_eq!(sub(&memo).validate_at(None, account, 0), Ok(()));
+        assert_eq!(sub(&memo).validate_at(None, &memo, 0), Ok(()));
+        assert_eq!(sub(&muxed).validate_at(None, account, 0), Ok(()));
+        assert_eq!(sub(&muxed).validate_at(None, &muxed, 0), Ok(()));
+        assert_eq!(
+            sub(&memo).validate_at(None, "GABC", 0),
+            Err(JwtError::WrongSubject)
+        );
+        assert_eq!(
+            sub(account).validate_at(None, &memo, 0),
+            Err(JwtError::WrongSubject)
+        );
+        assert_eq!(
+            sub(account).validate_at(None, &muxed, 0),
+            Err(JwtError::WrongSubject)
+        );
+    }
+}
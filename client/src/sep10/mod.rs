@@ -0,0 +1,479 @@
+//! Web authentication, as described in SEP-10: replay protection for servers
+//! and token handling for clients.
+//!
+//! A SEP-10 server hands out a challenge transaction with a random nonce in
+//! a `manage_data` operation and a short time window. The client signs it and
+//! sends it back in exchange for a JWT. Checking the signatures alone doesn't
+//! stop a challenge that was signed once from being exchanged again, such as
+//! by someone who saw it in a log. A `ReplayGuard` refuses challenges that
+//! are outside their time window, live longer than the server allows, or
+//! whose nonce has been exchanged before, and tracks the ids of the JWTs the
+//! server issues in the same way.
+//!
+//! Which nonces have been used is kept by a `NonceStore`. The
+//! `MemoryNonceStore` does for a single server process. Servers behind a load
+//! balancer implement the trait over a shared store such as a database, so
+//! that a challenge exchanged with one of them is refused by the others.
+//!
+//! Building challenges and checking their signatures is left to the server.
+//!
+//! On the client side, a `Jwt` decodes the token the server issues so that
+//! its expiry and subject can be checked, and an `AuthSession` keeps an
+//! account authenticated with an anchor, taking tokens from a `TokenCache`
+//! and getting a new one when the cached one expires or is turned away.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use stellar_client::sep10::{Challenge, ChallengeError, MemoryNonceStore, ReplayGuard};
+//!
+//! let guard = ReplayGuard::new(MemoryNonceStore::new());
+//! # let signed = "";
+//! let challenge = Challenge::from_base64(signed).unwrap();
+//! match guard.check(&challenge) {
+//!     Ok(()) => println!("issue a token to {}", challenge.account()),
+//!     Err(ChallengeError::Replayed) => println!("the challenge was used before"),
+//!     Err(err) => println!("refused: {}", err),
+//! }
+//! ```
+use error::Error;
+use resources::envelope::OperationBody;
+use resources::{AccountId, TransactionEnvelope};
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+
+mod jwt;
+mod token;
+
+use self::jwt::unix_now;
+pub use self::jwt::{Jwt, JwtError};
+pub use self::token::{AuthSession, MemoryTokenCache, TokenCache};
+
+/// The length of a nonce: 48 random bytes, base64 encoded.
+const NONCE_LEN: usize = 64;
+
+/// The longest a challenge may be valid for unless a guard is told
+/// otherwise, which is what SEP-10 recommends.
+const DEFAULT_MAX_VALIDITY: Duration = Duration::from_secs(15 * 60);
+
+/// The parts of a signed challenge transaction that replay protection looks
+/// at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Challenge {
+    server: AccountId,
+    account: AccountId,
+    home_domain: String,
+    nonce: String,
+    valid_from: u64,
+    valid_until: u64,
+}
+
+impl Challenge {
+    /// Decodes a base64 challenge transaction envelope. It has to have a
+    /// sequence number of zero, time bounds with an upper bound, and a first
+    /// operation that is a `manage_data` with a source account, a name ending
+    /// in ` auth` and a 64 byte nonce as its value.
+    pub fn from_base64(xdr: &str) -> Result<Challenge, ChallengeError> {
+        let envelope = TransactionEnvelope::from_base64(xdr)
+            .map_err(|_| ChallengeError::Malformed("the envelope could not be decoded"))?;
+        if envelope.sequence() != 0 {
+            return Err(ChallengeError::Malformed("the sequence number isn't zero"));
+        }
+        let (valid_from, valid_until) = match envelope.time_bounds() {
+            Some((_, 0)) | None => {
+                return Err(ChallengeError::Malformed("the time bounds are missing"))
+            }
+            Some(bounds) => bounds,
+        };
+        let operation = envelope
+            .operations()
+            .first()
+            .ok_or(ChallengeError::Malformed("there are no operations"))?;
+        let account = operation
+            .source_account()
+            .ok_or(ChallengeError::Malformed(
+                "the first operation has no source account",
+            ))?
+            .account_id();
+        let (home_domain, nonce) = match *operation.body() {
+            OperationBody::ManageData {
+                ref name,
+                value: Some(ref value),
+            } if name.ends_with(" auth") => (&name[..name.len() - 5], value),
+            _ => {
+                return Err(ChallengeError::Malformed(
+                    "the first operation isn't a manage_data auth entry",
+                ))
+            }
+        };
+        let nonce = match String::from_utf8(nonce.clone()) {
+            Ok(ref nonce) if nonce.len() == NONCE_LEN => nonce.clone(),
+            _ => {
+                return Err(ChallengeError::Malformed(
+                    "the nonce isn't 64 bytes of base64",
+                ))
+            }
+        };
+        Ok(Challenge {
+            server: envelope.source_account().account_id(),
+            account,
+            home_domain: home_domain.to_string(),
+            nonce,
+            valid_from,
+            valid_until,
+        })
+    }
+
+    /// The server account the challenge came from.
+    pub fn server(&self) -> &AccountId {
+        &self.server
+    }
+
+    /// The account the client is proving it controls.
+    pub fn account(&self) -> &AccountId {
+        &self.account
+    }
+
+    /// The home domain the challenge was issued for.
+    pub fn home_domain(&self) -> &str {
+        &self.home_domain
+    }
+
+    /// The random nonce that tells this challenge apart from any other.
+    pub fn nonce(&self) -> &str {
+        &self.nonce
+    }
+
+    /// The unix time the challenge is valid from.
+    pub fn valid_from(&self) -> u64 {
+        self.valid_from
+    }
+
+    /// The unix time the challenge is valid until.
+    pub fn valid_until(&self) -> u64 {
+        self.valid_until
+    }
+}
+
+/// Somewhere to keep the nonces and token ids that have been used, until
+/// they expire.
+///
+/// Claiming has to be atomic: when two servers claim the same nonce at
+/// once, only one of them may succeed.
+pub trait NonceStore {
+    /// Records `nonce` as used until the unix time `expires_at`. Returns
+    /// `false` when it was already recorded and hasn't expired by `now`, the
+    /// current unix time.
+    fn claim(&self, nonce: &str, expires_at: u64, now: u64) -> Result<bool, Error>;
+}
+
+impl<'a, S> NonceStore for &'a S
+where
+    S: NonceStore + ?Sized,
+{
+    fn claim(&self, nonce: &str, expires_at: u64, now: u64) -> Result<bool, Error> {
+        (**self).claim(nonce, expires_at, now)
+    }
+}
+
+/// A `NonceStore` in memory, which forgets nonces once they expire.
+#[derive(Debug, Default)]
+pub struct MemoryNonceStore {
+    used: Mutex<HashMap<String, u64>>,
+}
+
+impl MemoryNonceStore {
+    /// An empty store.
+    pub fn new() -> MemoryNonceStore {
+        MemoryNonceStore::default()
+    }
+
+    /// The number of nonces that were recorded and haven't been forgotten.
+    pub fn len(&self) -> usize {
+        self.used
+            .lock()
+            .expect("Nonce store lock was poisoned")
+            .len()
+    }
+
+    /// Whether no nonces are recorded.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl NonceStore for MemoryNonceStore {
+    fn claim(&self, nonce: &str, expires_at: u64, now: u64) -> Result<bool, Error> {
+        let mut used = self.used.lock().expect("Nonce store lock was poisoned");
+        used.retain(|_, expires_at| *expires_at >= now);
+        if used.contains_key(nonce) {
+            return Ok(false);
+        }
+        used.insert(nonce.to_string(), expires_at);
+        Ok(true)
+    }
+}
+
+/// Refuses challenges and tokens that have expired or been used before.
+#[derive(Debug)]
+pub struct ReplayGuard<S> {
+    store: S,
+    leeway: u64,
+    max_validity: u64,
+}
+
+impl<S> ReplayGuard<S>
+where
+    S: NonceStore,
+{
+    /// A guard that records used nonces in `store`. It allows no leeway
+    /// for clocks that disagree, and challenges valid for up to 15 minutes.
+    pub fn new(store: S) -> ReplayGuard<S> {
+        ReplayGuard {
+            store,
+            leeway: 0,
+            max_validity: DEFAULT_MAX_VALIDITY.as_secs(),
+        }
+    }
+
+    /// Accepts challenges up to `leeway` before they are valid or after
+    /// they expire, for clients whose clocks are off.
+    pub fn with_leeway(mut self, leeway: Duration) -> ReplayGuard<S> {
+        self.leeway = leeway.as_secs();
+        self
+    }
+
+    /// Refuses challenges whose time bounds span more than `max_validity`,
+    /// so that one issued by a misconfigured server can't be held on to.
+    pub fn with_max_validity(mut self, max_validity: Duration) -> ReplayGuard<S> {
+        self.max_validity = max_validity.as_secs();
+        self
+    }
+
+    /// The store the used nonces are recorded in.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Checks the challenge against the current time, then records its
+    /// nonce as used. Call it after the signatures have been checked, so
+    /// that a challenge with a bad signature doesn't use up the nonce.
+    pub fn check(&self, challenge: &Challenge) -> Result<(), ChallengeError> {
+        self.check_at(challenge, unix_now())
+    }
+
+    /// `check` as of the unix time `now`.
+    pub fn check_at(&self, challenge: &Challenge, now: u64) -> Result<(), ChallengeError> {
+        if challenge.valid_until < challenge.valid_from
+            || challenge.valid_until - challenge.valid_from > self.max_validity
+        {
+            return Err(ChallengeError::TooLong);
+        }
+        if now.saturating_add(self.leeway) < challenge.valid_from {
+            return Err(ChallengeError::NotYetValid);
+        }
+        if now > challenge.valid_until.saturating_add(self.leeway) {
+            return Err(ChallengeError::Expired);
+        }
+        self.claim(
+            &challenge.nonce,
+            challenge.valid_until.saturating_add(self.leeway),
+            now,
+        )
+    }
+
+    /// Records the id, the `jti` claim, of a JWT the server is about to
+    /// issue, which expires at the unix time `expires_at`. Fails with
+    /// `ChallengeError::Replayed` when the id has been issued before.
+    pub fn issue_token(&self, jti: &str, expires_at: u64) -> Result<(), ChallengeError> {
+        let now = unix_now();
+        if now > expires_at {
+            return Err(ChallengeError::Expired);
+        }
+        self.claim(&format!("jti:{}", jti), expires_at, now)
+    }
+
+    fn claim(&self, nonce: &str, expires_at: u64, now: u64) -> Result<(), ChallengeError> {
+        match self.store.claim(nonce, expires_at, now) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(ChallengeError::Replayed),
+            Err(err) => Err(ChallengeError::Store(err)),
+        }
+    }
+}
+
+/// Why a challenge was refused.
+#[derive(Debug)]
+pub enum ChallengeError {
+    /// The transaction isn't a challenge. The inner string says what is
+    /// wrong with it.
+    Malformed(&'static str),
+    /// The challenge's time bounds haven't started yet.
+    NotYetValid,
+    /// The challenge, or a token, has expired.
+    Expired,
+    /// The challenge's time bounds span longer than the guard allows.
+    TooLong,
+    /// The nonce of the challenge, or the id of a token, has been used
+    /// before.
+    Replayed,
+    /// The nonce store failed, so it isn't known whether the nonce was used.
+    Store(Error),
+}
+
+impl fmt::Display for ChallengeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ChallengeError::Malformed(reason) => write!(f, "{}: {}", self.description(), reason),
+            ChallengeError::Store(ref err) => write!(f, "{}: {}", self.description(), err),
+            _ => f.write_str(self.description()),
+        }
+    }
+}
+
+impl StdError for ChallengeError {
+    fn description(&self) -> &str {
+        match *self {
+            ChallengeError::Malformed(_) => "The transaction isn't a SEP-10 challenge",
+            ChallengeError::NotYetValid => "The challenge isn't valid yet",
+            ChallengeError::Expired => "The challenge has expired",
+            ChallengeError::TooLong => "The challenge is valid for longer than allowed",
+            ChallengeError::Replayed => "The challenge has been used before",
+            ChallengeError::Store(_) => "The nonce store failed",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64;
+    use strkey;
+
+    const SERVER: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+    const CLIENT: &str = "GCJ34JYMXNI7N55YREWAACMMZECOMTPIYDTFCQBWPUP7BLJQDDTVGUW4";
+    const NONCE: &str = "qBW5WgDk1HHEd4aJvqkSxH6pDtH8l5OUFHWMYgDJpPlIzmKmoDvBwZq9XGuZfrG2";
+
+    fn put_u32(xdr: &mut Vec<u8>, value: u32) {
+        xdr.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn put_account(xdr: &mut Vec<u8>, account: &str) {
+        put_u32(xdr, 0);
+        xdr.extend_from_slice(&strkey::decode(strkey::ACCOUNT, account).unwrap());
+    }
+
+    fn put_opaque(xdr: &mut Vec<u8>, bytes: &[u8]) {
+        put_u32(xdr, bytes.len() as u32);
+        xdr.extend_from_slice(bytes);
+        xdr.extend_from_slice(&[0; 3][..(4 - bytes.len() % 4) % 4]);
+    }
+
+    // A challenge from the server for the client, valid between the two
+    // times.
+    fn challenge(sequence: u64, valid_from: u64, valid_until: u64, nonce: &str) -> String {
+        let mut xdr = Vec::new();
+        put_u32(&mut xdr, 2);
+        put_account(&mut xdr, SERVER);
+        put_u32(&mut xdr, 100);
+        xdr.extend_from_slice(&sequence.to_be_bytes());
+        // Time bounds, no memo and a single manage_data from the client.
+        put_u32(&mut xdr, 1);
+        xdr.extend_from_slice(&valid_from.to_be_bytes());
+        xdr.extend_from_slice(&valid_until.to_be_bytes());
+        put_u32(&mut xdr, 0);
+        put_u32(&mut xdr, 1);
+        put_u32(&mut xdr, 1);
+        put_account(&mut xdr, CLIENT);
+        put_u32(&mut xdr, 10);
+        put_opaque(&mut xdr, b"example.com auth");
+        put_u32(&mut xdr, 1);
+        put_opaque(&mut xdr, nonce.as_bytes());
+        // No extension or signatures.
+        put_u32(&mut xdr, 0);
+        put_u32(&mut xdr, 0);
+        base64::encode(&xdr)
+    }
+
+    #[test]
+    fn it_decodes_a_challenge() {
+        let challenge = Challenge::from_base64(&challenge(0, 1_000, 1_300, NONCE)).unwrap();
+        assert_eq!(challenge.server().as_str(), SERVER);
+        assert_eq!(challenge.account().as_str(), CLIENT);
+        assert_eq!(challenge.home_domain(), "example.com");
+        assert_eq!(challenge.nonce(), NONCE);
+        assert_eq!(
+            (challenge.valid_from(), challenge.valid_until()),
+            (1_000, 1_300)
+        );
+    }
+
+    #[test]
+    fn it_refuses_transactions_that_are_not_challenges() {
+        for xdr in &[
+            "not base64!".to_string(),
+            challenge(1, 1_000, 1_300, NONCE),
+            challenge(0, 1_000, 0, NONCE),
+            challenge(0, 1_000, 1_300, "short"),
+        ] {
+            match Challenge::from_base64(xdr) {
+                Err(ChallengeError::Malformed(_)) => (),
+                other => panic!("expected a malformed challenge, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn it_refuses_challenges_outside_their_time_bounds() {
+        let challenge = Challenge::from_base64(&challenge(0, 1_000, 1_300, NONCE)).unwrap();
+        let guard = ReplayGuard::new(MemoryNonceStore::new());
+        match guard.check_at(&challenge, 999) {
+            Err(ChallengeError::NotYetValid) => (),
+            other => panic!("expected a challenge that isn't valid yet, got {:?}", other),
+        }
+        match guard.check_at(&challenge, 1_301) {
+            Err(ChallengeError::Expired) => (),
+            other => panic!("expected an expired challenge, got {:?}", other),
+        }
+        let lenient = ReplayGuard::new(MemoryNonceStore::new()).with_leeway(Duration::from_secs(5));
+        assert!(lenient.check_at(&challenge, 1_305).is_ok());
+
+        let strict =
+            ReplayGuard::new(MemoryNonceStore::new()).with_max_validity(Duration::from_secs(60));
+        match strict.check_at(&challenge, 1_100) {
+            Err(ChallengeError::TooLong) => (),
+            other => panic!("expected a challenge that is too long, got {:?}", other),
+        }
+        assert!(guard.store().is_empty());
+    }
+
+    #[test]
+    fn it_refuses_a_challenge_used_before() {
+        let challenge = Challenge::from_base64(&challenge(0, 1_000, 1_300, NONCE)).unwrap();
+        let store = MemoryNonceStore::new();
+        let guard = ReplayGuard::new(&store);
+        assert!(guard.check_at(&challenge, 1_100).is_ok());
+        match ReplayGuard::new(&store).check_at(&challenge, 1_200) {
+            Err(ChallengeError::Replayed) => (),
+            other => panic!("expected a replayed challenge, got {:?}", other),
+        }
+
+        assert!(guard.issue_token("token-1", u64::MAX).is_ok());
+        match guard.issue_token("token-1", u64::MAX) {
+            Err(ChallengeError::Replayed) => (),
+            other => panic!("expected a reused token id, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn it_forgets_nonces_once_they_expire() {
+        let store = MemoryNonceStore::new();
+        assert!(store.claim("a", 10, 0).unwrap());
+        assert!(!store.claim("a", 10, 5).unwrap());
+        assert!(store.claim("b", 30, 20).unwrap());
+        assert_eq!(store.len(), 1);
+    }
+}
@@ -0,0 +1,315 @@
+use super::jwt::Jwt;
+use error::Result;
+use reqwest;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How long before it expires a cached token is replaced, unless a session
+/// is told otherwise.
+const DEFAULT_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// Somewhere to keep the tokens a client has been issued, keyed by the home
+/// domain of the anchor that issued them and the account they are for, so
+/// that they can be shared between sessions and outlive them.
+pub trait TokenCache {
+    /// The token for `account` at `domain`, if there is one.
+    fn get(&self, domain: &str, account: &str) -> Option<Jwt>;
+
+    /// Keeps `token` for `account` at `domain`, replacing any before it.
+    fn put(&self, domain: &str, account: &str, token: Jwt);
+
+    /// Forgets the token for `account` at `domain`.
+    fn remove(&self, domain: &str, account: &str);
+}
+
+impl<'a, C> TokenCache for &'a C
+where
+    C: TokenCache + ?Sized,
+{
+    fn get(&self, domain: &str, account: &str) -> Option<Jwt> {
+        (**self).get(domain, account)
+    }
+
+    fn put(&self, domain: &str, account: &str, token: Jwt) {
+        (**self).put(domain, account, token)
+    }
+
+    fn remove(&self, domain: &str, account: &str) {
+        (**self).remove(domain, account)
+    }
+}
+
+/// A `TokenCache` in memory.
+#[derive(Debug, Default)]
+pub struct MemoryTokenCache {
+    tokens: Mutex<HashMap<(String, String), Jwt>>,
+}
+
+impl MemoryTokenCache {
+    /// An empty cache.
+    pub fn new() -> MemoryTokenCache {
+        MemoryTokenCache::default()
+    }
+}
+
+impl TokenCache for MemoryTokenCache {
+    fn get(&self, domain: &str, account: &str) -> Option<Jwt> {
+        let key = (domain.to_string(), account.to_string());
+        self.tokens
+            .lock()
+            .expect("Token cache lock was poisoned")
+            .get(&key)
+            .cloned()
+    }
+
+    fn put(&self, domain: &str, account: &str, token: Jwt) {
+        let key = (domain.to_string(), account.to_string());
+        self.tokens
+            .lock()
+            .expect("Token cache lock was poisoned")
+            .insert(key, token);
+    }
+
+    fn remove(&self, domain: &str, account: &str) {
+        let key = (domain.to_string(), account.to_string());
+        self.tokens
+            .lock()
+            .expect("Token cache lock was poisoned")
+            .remove(&key);
+    }
+}
+
+/// Keeps an account authenticated with an anchor's SEP-10 server, for
+/// requests to the anchor's other services such as SEP-6, SEP-12, SEP-24 and
+/// SEP-38. This crate has no clients for those services, so `send` takes a
+/// callback that makes the request with the token, using any http client.
+///
+/// Tokens come from a callback that runs the SEP-10 flow: fetching a
+/// challenge, having the account sign it and exchanging it for a token. The
+/// session calls it when the cache has no token, when the cached one is
+/// about to expire, and when the anchor turns a request away with a 401
+/// before its token expired, such as after the anchor rotated its keys.
+///
+/// ## Example
+///
+/// ```no_run
+/// extern crate reqwest;
+/// extern crate stellar_client;
+///
+/// use stellar_client::sep10::{AuthSession, MemoryTokenCache};
+///
+/// # fn main() {
+/// # fn sep10_flow() -> std::io::Result<String> { unimplemented!() }
+/// let cache = MemoryTokenCache::new();
+/// let account = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+/// let session = AuthSession::new("example.com", account, &cache, sep10_flow);
+/// let http = reqwest::Client::new();
+/// let response = session
+///     .send(|token| {
+///         http.get("https://example.com/sep24/info")
+///             .bearer_auth(token)
+///             .send()
+///     })
+///     .unwrap();
+/// println!("{}", response.status());
+/// # }
+/// ```
+pub struct AuthSession<C, F> {
+    domain: String,
+    account: String,
+    issuer: Option<String>,
+    refresh_margin: u64,
+    cache: C,
+    authenticate: F,
+}
+
+impl<C, F> AuthSession<C, F>
+where
+    C: TokenCache,
+    F: Fn() -> io::Result<String>,
+{
+    /// A session for `account` with the anchor at `domain`, keeping its
+    /// tokens in `cache` and getting new ones from `authenticate`. Cached
+    /// tokens are replaced a minute before they expire.
+    pub fn new(domain: &str, account: &str, cache: C, authenticate: F) -> AuthSession<C, F> {
+        AuthSession {
+            domain: domain.to_string(),
+            account: account.to_string(),
+            issuer: None,
+            refresh_margin: DEFAULT_REFRESH_MARGIN.as_secs(),
+            cache,
+            authenticate,
+        }
+    }
+
+    /// Refuses tokens that weren't issued by `issuer`, usually the url of
+    /// the anchor's web auth endpoint.
+    pub fn with_issuer(mut self, issuer: &str) -> AuthSession<C, F> {
+        self.issuer = Some(issuer.to_string());
+        self
+    }
+
+    /// Replaces cached tokens that expire within `margin`.
+    pub fn with_refresh_margin(mut self, margin: Duration) -> AuthSession<C, F> {
+        self.refresh_margin = margin.as_secs();
+        self
+    }
+
+    /// A token for the account, from the cache when it has one that isn't
+    /// about to expire and from `authenticate` otherwise. A new token that
+    /// isn't for the account or from the issuer fails with an
+    /// `io::ErrorKind::InvalidData` error.
+    pub fn token(&self) -> io::Result<Jwt> {
+        if let Some(token) = self.cache.get(&self.domain, &self.account) {
+            if !token.expires_within(self.refresh_margin) {
+                return Ok(token);
+            }
+        }
+        let token = Jwt::decode(&(self.authenticate)()?)
+            .and_then(|token| {
+                token.validate(self.issuer.as_deref(), &self.account)?;
+                Ok(token)
+            })
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        self.cache.put(&self.domain, &self.account, token.clone());
+        Ok(token)
+    }
+
+    /// Forgets the cached token, so that the next one comes from
+    /// `authenticate`.
+    pub fn invalidate(&self) {
+        self.cache.remove(&self.domain, &self.account);
+    }
+
+    /// Sends a request built by `request` with a token. When the anchor
+    /// answers `401 Unauthorized`, the token is replaced and the request
+    /// sent once more.
+    pub fn send<R>(&self, request: R) -> Result<reqwest::Response>
+    where
+        R: Fn(&str) -> reqwest::Result<reqwest::Response>,
+    {
+        let response = request(self.token()?.as_str())?;
+        if response.status() != reqwest::StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+        self.invalidate();
+        Ok(request(self.token()?.as_str())?)
+    }
+}
+
+// The cache and callback are left out, since they may hold tokens.
+impl<C, F> fmt::Debug for AuthSession<C, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AuthSession")
+            .field("domain", &self.domain)
+            .field("account", &self.account)
+            .field("issuer", &self.issuer)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod auth_session_tests {
+    use super::*;
+    use base64;
+    use sep10::jwt::unix_now;
+    use std::cell::Cell;
+    use test::{TestResponse, TestServer};
+
+    const ACCOUNT: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+
+    // A token for `sub` that expires `exp` seconds from now, with `jti` to
+    // tell tokens apart.
+    fn token(sub: &str, exp: u64, jti: u32) -> String {
+        let claims = format!(
+            r#"{{"sub":"{}","exp":{},"jti":"{}"}}"#,
+            sub,
+            unix_now() + exp,
+            jti
+        );
+        format!(
+            "eyJhbGciOiJIUzI1NiJ9.{}.c2ln",
+            base64::encode_config(&claims, base64::URL_SAFE_NO_PAD)
+        )
+    }
+
+    #[test]
+    fn it_caches_tokens_until_they_are_about_to_expire() {
+        let cache = MemoryTokenCache::new();
+        let issued = Cell::new(0);
+        let lifetime = Cell::new(3_600);
+        let session = AuthSession::new("example.com", ACCOUNT, &cache, || {
+            issued.set(issued.get() + 1);
+            Ok(token(ACCOUNT, lifetime.get(), issued.get()))
+        });
+        assert_eq!(session.token().unwrap().id(), Some("1"));
+        assert_eq!(session.token().unwrap().id(), Some("1"));
+        assert!(cache.get("example.com", ACCOUNT).is_some());
+        assert!(cache.get("other.com", ACCOUNT).is_none());
+
+        session.invalidate();
+        lifetime.set(30);
+        assert_eq!(session.token().unwrap().id(), Some("2"));
+        assert_eq!(session.token().unwrap().id(), Some("3"));
+    }
+
+    #[test]
+    fn it_refuses_a_token_for_another_account() {
+        let session = AuthSession::new("example.com", ACCOUNT, MemoryTokenCache::new(), || {
+            Ok(token("GABC", 3_600, 1))
+        });
+        let error = session.token().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn it_authenticates_again_when_turned_away() {
+        let server = TestServer::new(vec![
+            TestResponse::new("401 Unauthorized"),
+            TestResponse::new("200 OK"),
+        ]);
+        let uri = format!("{}/info", server.uri());
+
+        let issued = Cell::new(0);
+        let session = AuthSession::new("example.com", ACCOUNT, MemoryTokenCache::new(), || {
+            issued.set(issued.get() + 1);
+            Ok(token(ACCOUNT, 3_600, issued.get()))
+        });
+        let http = reqwest::Client::new();
+        let response = session
+            .send(|token| http.get(&uri).bearer_auth(token).send())
+            .unwrap();
+        assert!(response.status().is_success());
+        assert_eq!(issued.get(), 2);
+        let requests = server.requests();
+        assert_ne!(
+            requests[0].header("authorization"),
+            requests[1].header("authorization")
+        );
+    }
+
+    #[test]
+    fn it_authenticates_again_only_once() {
+        let server = TestServer::new(vec![
+            TestResponse::new("401 Unauthorized"),
+            TestResponse::new("401 Unauthorized"),
+        ]);
+        let uri = format!("{}/info", server.uri());
+
+        let issued = Cell::new(0);
+        let session = AuthSession::new("example.com", ACCOUNT, MemoryTokenCache::new(), || {
+            issued.set(issued.get() + 1);
+            Ok(token(ACCOUNT, 3_600, issued.get()))
+        });
+        let http = reqwest::Client::new();
+        let response = session
+            .send(|token| http.get(&uri).bearer_auth(token).send())
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+        assert_eq!(issued.get(), 2);
+        assert_eq!(server.received().len(), 2);
+    }
+}
@@ -0,0 +1,429 @@
+//! A client for the approval servers of regulated assets, as described in
+//! SEP-8.
+//!
+//! The issuer of a regulated asset has to approve every transaction that
+//! moves it. Its stellar.toml marks the asset as `regulated` and names an
+//! approval server. A wallet sends its candidate transaction to the server,
+//! which signs it, revises it to comply, asks for more time or information,
+//! or rejects it. Only a transaction that comes back signed can be
+//! submitted.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use stellar_client::sep8::{Approval, Client};
+//!
+//! let client = Client::new().unwrap();
+//! let assets = client.regulated_assets("example.com").unwrap();
+//! let transaction = "AAAAAgAAAAA..."; // a base64 encoded, signed envelope
+//! match client.approve(&assets[0], transaction).unwrap() {
+//!     Approval::Success { tx, .. } => println!("submit {}", tx),
+//!     Approval::Revised { tx, message } => println!("check then submit {}: {}", tx, message),
+//!     Approval::Pending { timeout, .. } => println!("try again in {}ms", timeout),
+//!     Approval::ActionRequired(action) => println!("visit {}", action.url()),
+//!     Approval::Rejected { error } => println!("rejected: {}", error),
+//! }
+//! ```
+use error::{Error, Result};
+use reqwest;
+use resources::{AccountId, AssetIdentifier};
+use serde_json;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use stellar_toml::{self, StellarToml};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// An asset whose transactions need the approval of its issuer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegulatedAsset {
+    identifier: AssetIdentifier,
+    approval_server: String,
+    approval_criteria: Option<String>,
+}
+
+impl RegulatedAsset {
+    /// Finds the regulated assets in the text of a stellar.toml. Currencies
+    /// without an approval server or with an invalid code or issuer are
+    /// skipped.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use stellar_client::sep8::RegulatedAsset;
+    ///
+    /// let assets = RegulatedAsset::from_toml(r#"
+    /// [[CURRENCIES]]
+    /// code = "FOO"
+    /// issuer = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG"
+    /// regulated = true
+    /// approval_server = "https://example.com/tx-approve"
+    /// "#);
+    /// assert_eq!(assets[0].approval_server(), "https://example.com/tx-approve");
+    /// ```
+    pub fn from_toml(toml: &str) -> Vec<RegulatedAsset> {
+        StellarToml::parse(toml)
+            .tables("CURRENCIES")
+            .filter(|currency| currency.bool("regulated") == Some(true))
+            .filter_map(|currency| {
+                let code = currency.string("code")?;
+                let issuer = currency.string("issuer")?;
+                let identifier = if code.len() <= 4 {
+                    AssetIdentifier::alphanum4(code, issuer)
+                } else {
+                    AssetIdentifier::alphanum12(code, issuer)
+                };
+                Some(RegulatedAsset {
+                    identifier: identifier.ok()?,
+                    approval_server: currency.string("approval_server")?.to_string(),
+                    approval_criteria: currency.string("approval_criteria").map(String::from),
+                })
+            })
+            .collect()
+    }
+
+    /// The asset.
+    pub fn identifier(&self) -> &AssetIdentifier {
+        &self.identifier
+    }
+
+    /// The issuer of the asset.
+    pub fn issuer(&self) -> Option<&AccountId> {
+        self.identifier.issuer()
+    }
+
+    /// The uri that transactions are sent to for approval.
+    pub fn approval_server(&self) -> &str {
+        &self.approval_server
+    }
+
+    /// A human readable description of what the issuer approves.
+    pub fn approval_criteria(&self) -> Option<&str> {
+        self.approval_criteria.as_deref()
+    }
+}
+
+/// The answer of an approval server to a candidate transaction.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Approval {
+    /// The transaction was approved and signed as it was sent.
+    Success {
+        /// The signed transaction, ready to submit.
+        tx: String,
+        /// Something to show the user, if anything.
+        #[serde(default)]
+        message: Option<String>,
+    },
+    /// The transaction was changed to comply and then signed. It should be
+    /// checked, such as with `TransactionEnvelope::from_base64`, and signed
+    /// again by the user before it is submitted.
+    Revised {
+        /// The revised and signed transaction.
+        tx: String,
+        /// What was changed and why.
+        #[serde(default)]
+        message: String,
+    },
+    /// The issuer needs more time. The transaction should be sent again
+    /// later.
+    Pending {
+        /// How long to wait before trying again, in milliseconds. Zero if
+        /// the server doesn't know.
+        #[serde(default)]
+        timeout: u64,
+        /// Something to show the user, if anything.
+        #[serde(default)]
+        message: Option<String>,
+    },
+    /// The user has to provide more information before the transaction can
+    /// be approved.
+    ActionRequired(ActionRequired),
+    /// The transaction was not approved and can't be.
+    Rejected {
+        /// Why the transaction was rejected.
+        error: String,
+    },
+}
+
+impl Approval {
+    /// The signed transaction, if the approval server signed one.
+    pub fn transaction(&self) -> Option<&str> {
+        match *self {
+            Approval::Success { ref tx, .. } | Approval::Revised { ref tx, .. } => Some(tx),
+            _ => None,
+        }
+    }
+}
+
+/// What the user has to do before a transaction can be approved.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ActionRequired {
+    message: String,
+    action_url: String,
+    #[serde(default = "default_action_method")]
+    action_method: String,
+    #[serde(default)]
+    action_fields: Vec<String>,
+}
+
+fn default_action_method() -> String {
+    "GET".to_string()
+}
+
+impl ActionRequired {
+    /// Why the action is needed.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Where the action is taken.
+    pub fn url(&self) -> &str {
+        &self.action_url
+    }
+
+    /// `GET` if the user should open the url in a browser, or `POST` if the
+    /// fields can be sent to it with `Client::take_action`.
+    pub fn method(&self) -> &str {
+        &self.action_method
+    }
+
+    /// The SEP-9 fields, such as `email_address`, that the server asks for.
+    pub fn fields(&self) -> &Vec<String> {
+        &self.action_fields
+    }
+}
+
+/// The answer to fields sent for an action.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum ActionResult {
+    /// Nothing more is needed. The transaction can be sent for approval
+    /// again.
+    NoFurtherActionRequired,
+    /// The user has to carry on at another url in a browser.
+    FollowNextUrl {
+        /// Where to send the user.
+        next_url: String,
+        /// Something to show the user, if anything.
+        #[serde(default)]
+        message: Option<String>,
+    },
+}
+
+/// A synchronous client for approval servers.
+#[derive(Debug)]
+pub struct Client {
+    inner: reqwest::Client,
+}
+
+impl Client {
+    /// Constructs a client.
+    pub fn new() -> Result<Self> {
+        let inner = reqwest::ClientBuilder::new()
+            .timeout(DEFAULT_TIMEOUT)
+            .build()?;
+        Ok(Client { inner })
+    }
+
+    /// Fetches the stellar.toml of `home_domain` and returns the regulated
+    /// assets it lists.
+    pub fn regulated_assets(&self, home_domain: &str) -> Result<Vec<RegulatedAsset>> {
+        let toml = stellar_toml::fetch(&self.inner, home_domain)?;
+        Ok(RegulatedAsset::from_toml(&toml))
+    }
+
+    /// Sends a base64 encoded transaction envelope that moves `asset` to
+    /// its approval server. A rejection is an `Approval` rather than an
+    /// error, since servers answer it with a client error status.
+    pub fn approve(&self, asset: &RegulatedAsset, transaction: &str) -> Result<Approval> {
+        let mut body = BTreeMap::new();
+        body.insert("tx", transaction);
+        self.post(&asset.approval_server, &body)
+    }
+
+    /// Sends the values of the fields an action asks for, keyed by field
+    /// name, to the action's url.
+    pub fn take_action(
+        &self,
+        action: &ActionRequired,
+        values: &BTreeMap<String, String>,
+    ) -> Result<ActionResult> {
+        self.post(&action.action_url, values)
+    }
+
+    fn post<B, R>(&self, uri: &str, body: &B) -> Result<R>
+    where
+        B: ::serde::Serialize,
+        R: ::serde::de::DeserializeOwned,
+    {
+        let response = self.inner.post(uri).json(body).send()?;
+        if response.status().is_server_error() {
+            return Err(Error::ServerError(None));
+        }
+        Ok(serde_json::from_reader(response)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test::{TestResponse, TestServer};
+
+    const ISSUER: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+
+    // Answers a single request on a local port with `status` and `body`.
+    // Returns the approval server's uri along with the server.
+    fn serve_once(status: &str, body: &str) -> (String, TestServer) {
+        let server = TestServer::new(vec![TestResponse::json(body).with_status(status)]);
+        (format!("{}/tx-approve", server.uri()), server)
+    }
+
+    fn asset(approval_server: &str) -> RegulatedAsset {
+        RegulatedAsset {
+            identifier: AssetIdentifier::alphanum4("FOO", ISSUER).unwrap(),
+            approval_server: approval_server.to_string(),
+            approval_criteria: None,
+        }
+    }
+
+    #[test]
+    fn it_finds_regulated_assets_in_a_toml() {
+        let assets = RegulatedAsset::from_toml(&format!(
+            r#"
+[[CURRENCIES]]
+code = "FOO"
+issuer = "{issuer}"
+regulated = true
+approval_server = "https://example.com/tx-approve"
+approval_criteria = "Only KYC'd accounts"
+
+[[CURRENCIES]]
+code = "LONGERCODE"
+issuer = "{issuer}"
+regulated = true
+approval_server = "https://example.com/tx-approve"
+
+[[CURRENCIES]]
+code = "BAR"
+issuer = "{issuer}"
+
+[[CURRENCIES]]
+code = "BAZ"
+issuer = "{issuer}"
+regulated = true
+"#,
+            issuer = ISSUER
+        ));
+        assert_eq!(assets.len(), 2);
+        assert_eq!(assets[0].identifier().code(), Some("FOO"));
+        assert_eq!(assets[0].issuer().unwrap(), ISSUER);
+        assert_eq!(assets[0].approval_criteria(), Some("Only KYC'd accounts"));
+        assert_eq!(assets[1].identifier().asset_type(), "credit_alphanum12");
+    }
+
+    #[test]
+    fn it_sends_the_transaction_for_approval() {
+        let (uri, server) = serve_once(
+            "200 OK",
+            r#"{"status":"success","tx":"AAAAAgAAAAB","message":"Approved"}"#,
+        );
+        let approval = Client::new()
+            .unwrap()
+            .approve(&asset(&uri), "AAAAAgAAAAA")
+            .unwrap();
+        assert_eq!(
+            approval,
+            Approval::Success {
+                tx: "AAAAAgAAAAB".to_string(),
+                message: Some("Approved".to_string()),
+            }
+        );
+        assert_eq!(approval.transaction(), Some("AAAAAgAAAAB"));
+        let request = server.next_request();
+        assert_eq!(request.request_line(), "POST /tx-approve HTTP/1.1");
+        assert_eq!(request.body(), r#"{"tx":"AAAAAgAAAAA"}"#);
+    }
+
+    #[test]
+    fn it_reads_revised_and_pending_answers() {
+        let (uri, _server) = serve_once(
+            "200 OK",
+            r#"{"status":"revised","tx":"AAAAAgAAAAC","message":"Added the issuer's fee"}"#,
+        );
+        let approval = Client::new()
+            .unwrap()
+            .approve(&asset(&uri), "AAAAAgAAAAA")
+            .unwrap();
+        assert_eq!(approval.transaction(), Some("AAAAAgAAAAC"));
+
+        let (uri, _server) = serve_once("200 OK", r#"{"status":"pending","timeout":3600000}"#);
+        let approval = Client::new()
+            .unwrap()
+            .approve(&asset(&uri), "AAAAAgAAAAA")
+            .unwrap();
+        assert_eq!(
+            approval,
+            Approval::Pending {
+                timeout: 3_600_000,
+                message: None,
+            }
+        );
+        assert_eq!(approval.transaction(), None);
+    }
+
+    #[test]
+    fn it_reads_a_rejection_from_a_client_error() {
+        let (uri, _server) = serve_once(
+            "400 Bad Request",
+            r#"{"status":"rejected","error":"The destination is not KYC'd"}"#,
+        );
+        let approval = Client::new()
+            .unwrap()
+            .approve(&asset(&uri), "AAAAAgAAAAA")
+            .unwrap();
+        assert_eq!(
+            approval,
+            Approval::Rejected {
+                error: "The destination is not KYC'd".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn it_takes_the_action_that_is_required() {
+        let (uri, _server) = serve_once(
+            "200 OK",
+            r#"{"status":"action_required","message":"We need your email","action_url":"http://127.0.0.1:1/action","action_method":"POST","action_fields":["email_address"]}"#,
+        );
+        let client = Client::new().unwrap();
+        let action = match client.approve(&asset(&uri), "AAAAAgAAAAA").unwrap() {
+            Approval::ActionRequired(action) => action,
+            other => panic!("expected an action, got {:?}", other),
+        };
+        assert_eq!(action.method(), "POST");
+        assert_eq!(action.fields(), &vec!["email_address".to_string()]);
+
+        let (uri, server) = serve_once(
+            "200 OK",
+            r#"{"result":"follow_next_url","next_url":"https://example.com/kyc","message":"Confirm your email"}"#,
+        );
+        let action = ActionRequired {
+            action_url: uri,
+            ..action
+        };
+        let mut values = BTreeMap::new();
+        values.insert("email_address".to_string(), "user@example.com".to_string());
+        assert_eq!(
+            client.take_action(&action, &values).unwrap(),
+            ActionResult::FollowNextUrl {
+                next_url: "https://example.com/kyc".to_string(),
+                message: Some("Confirm your email".to_string()),
+            }
+        );
+        assert_eq!(
+            server.next_request().body(),
+            r#"{"email_address":"user@example.com"}"#
+        );
+    }
+}
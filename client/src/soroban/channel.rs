@@ -0,0 +1,365 @@
+use super::invoke::InvokeContractBuilder;
+use super::{Client, SendTransaction};
+use error::Result;
+use keys::{Keypair, Signer};
+use network::Network;
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex};
+
+/// Sends transactions concurrently through a pool of channel accounts.
+///
+/// An account can only have one transaction in flight at a time, since each
+/// one has to use the next sequence number. To send more than that, the
+/// usual trick is to keep a few funded channel accounts around that do
+/// nothing but pay fees and provide sequence numbers. Each transaction is
+/// moved onto a free channel, which becomes its source, while the account it
+/// was built for stays the source of its operation.
+///
+/// The submitter hands every channel to one transaction at a time, and keeps
+/// track of each channel's sequence number as transactions use them up. When
+/// every channel is busy, `submit` waits for one to come free, so the pool
+/// can be shared between threads. Free channels are handed out in turn, so
+/// transactions sent one after another go through different channels.
+///
+/// Both the channel and the operation's source sign, so the channel keypairs
+/// are held by the submitter and the source's signer is passed to `submit`.
+///
+/// This is only available with the `keys` feature enabled.
+///
+/// ## Example
+///
+/// ```no_run
+/// use stellar_client::keys::Keypair;
+/// use stellar_client::soroban::{ChannelSubmitter, Client, InvokeContractBuilder};
+/// use stellar_client::Network;
+///
+/// let client = Client::new("https://soroban-testnet.stellar.org").unwrap();
+/// let source = Keypair::from_secret_seed(
+///     "SAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6NKI",
+/// ).unwrap();
+/// // The channels and their current sequence numbers, as horizon reports them.
+/// let submitter = ChannelSubmitter::new(vec![
+///     (Keypair::random().unwrap(), 30232549674450945),
+///     (Keypair::random().unwrap(), 30232549674450950),
+/// ]);
+///
+/// let builder = InvokeContractBuilder::new(
+///     source.account_id(),
+///     0,
+///     "CBQHNAXSI55GX2GN6D67GK7BHVPSLJUGZQEU7WJ5LKR5PNUCGLIMAO4K",
+///     "increment",
+/// );
+/// let sent = submitter.submit(&client, &Network::test(), builder, &source).unwrap();
+/// println!("sent {}", sent.hash());
+/// ```
+#[derive(Debug)]
+pub struct ChannelSubmitter {
+    free: Mutex<VecDeque<Channel>>,
+    released: Condvar,
+    size: usize,
+}
+
+#[derive(Debug)]
+struct Channel {
+    keypair: Keypair,
+    sequence: u64,
+}
+
+impl ChannelSubmitter {
+    /// A submitter for the channel accounts with the given keypairs and
+    /// their current sequence numbers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no channels, since nothing could ever be sent.
+    pub fn new(channels: Vec<(Keypair, u64)>) -> ChannelSubmitter {
+        assert!(!channels.is_empty(), "A channel submitter needs channels");
+        let size = channels.len();
+        let free = channels
+            .into_iter()
+            .map(|(keypair, sequence)| Channel { keypair, sequence })
+            .collect();
+        ChannelSubmitter {
+            free: Mutex::new(free),
+            released: Condvar::new(),
+            size,
+        }
+    }
+
+    /// How many channels the submitter sends through.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// How many channels are not sending a transaction right now.
+    pub fn available(&self) -> usize {
+        self.free.lock().expect("Channel lock was poisoned").len()
+    }
+
+    /// Moves the transaction onto a free channel, assembles and signs it,
+    /// and sends it. Waits for a channel if they are all busy.
+    ///
+    /// The sequence number the builder was given is ignored in favour of the
+    /// channel's. `source` signs for the account the transaction was built
+    /// for, which stays the source of the operation.
+    ///
+    /// A channel's sequence number only moves on once the rpc server has
+    /// taken a transaction from it. If sending fails without an answer, the
+    /// transaction may still have been taken, and the channel's next
+    /// transaction will be rejected with a bad sequence. `resync` puts the
+    /// channel right again.
+    pub fn submit<S>(
+        &self,
+        client: &Client,
+        network: &Network,
+        builder: InvokeContractBuilder,
+        source: &S,
+    ) -> Result<SendTransaction>
+    where
+        S: Signer + ?Sized,
+    {
+        let mut lease = self.acquire();
+        let sent = {
+            let channel = lease.channel();
+            let transaction = builder
+                .through_channel(channel.keypair.account_id(), channel.sequence)
+                .assemble(client)?
+                .sign(network, &channel.keypair)?
+                .sign(network, source)?;
+            client.send_transaction(transaction.envelope_xdr())?
+        };
+        // Rejected transactions and ones the server was too busy to take
+        // leave the sequence number as it was.
+        match sent.status() {
+            "PENDING" | "DUPLICATE" => lease.channel_mut().sequence += 1,
+            _ => {}
+        }
+        Ok(sent)
+    }
+
+    /// Sets the sequence number of a channel, as horizon reports it, once it
+    /// is free. Returns false if the submitter has no such channel.
+    pub fn resync(&self, account_id: &str, sequence: u64) -> bool {
+        let mut free = self.free.lock().expect("Channel lock was poisoned");
+        loop {
+            if let Some(channel) = free
+                .iter_mut()
+                .find(|channel| channel.keypair.account_id() == account_id)
+            {
+                channel.sequence = sequence;
+                return true;
+            }
+            // The channel is either busy or doesn't exist. Only wait for it
+            // while some channel is busy.
+            if free.len() == self.size {
+                return false;
+            }
+            free = self.released.wait(free).expect("Channel lock was poisoned");
+        }
+    }
+
+    fn acquire(&self) -> Lease<'_> {
+        let mut free = self.free.lock().expect("Channel lock was poisoned");
+        loop {
+            if let Some(channel) = free.pop_front() {
+                return Lease {
+                    submitter: self,
+                    channel: Some(channel),
+                };
+            }
+            free = self.released.wait(free).expect("Channel lock was poisoned");
+        }
+    }
+}
+
+/// A channel on loan to one transaction. It goes back in the pool when the
+/// lease is dropped, even if sending panics.
+struct Lease<'a> {
+    submitter: &'a ChannelSubmitter,
+    channel: Option<Channel>,
+}
+
+impl<'a> Lease<'a> {
+    fn channel(&self) -> &Channel {
+        self.channel.as_ref().expect("Leased channel was returned")
+    }
+
+    fn channel_mut(&mut self) -> &mut Channel {
+        self.channel.as_mut().expect("Leased channel was returned")
+    }
+}
+
+impl<'a> Drop for Lease<'a> {
+    fn drop(&mut self) {
+        if let Some(channel) = self.channel.take() {
+            if let Ok(mut free) = self.submitter.free.lock() {
+                free.push_back(channel);
+            }
+            self.submitter.released.notify_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod channel_submitter_tests {
+    use super::*;
+    use base64;
+    use std::sync::Arc;
+    use std::thread;
+    use strkey;
+    use test::{TestResponse, TestServer};
+
+    const SOURCE: &str = "SAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6NKI";
+    const CONTRACT: &str = "CBQHNAXSI55GX2GN6D67GK7BHVPSLJUGZQEU7WJ5LKR5PNUCGLIMAO4K";
+    const SIMULATION: &str = r#"{"jsonrpc":"2.0","id":1,"result":{"latestLedger":1000,"minResourceFee":"0","transactionData":"AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=","results":[]}}"#;
+
+    // Answers each rpc call in turn on a local port.
+    fn serve(bodies: Vec<String>) -> TestServer {
+        TestServer::new(bodies.iter().map(|body| TestResponse::json(body)).collect())
+    }
+
+    fn sent(status: &str) -> String {
+        format!(
+            r#"{{"jsonrpc":"2.0","id":2,"result":{{"hash":"abc","status":"{}","latestLedger":1000,"latestLedgerCloseTime":"1700086333"}}}}"#,
+            status
+        )
+    }
+
+    // The envelope the rpc server was asked to send.
+    fn envelope(request: &str) -> Vec<u8> {
+        let start = request.find(r#""transaction":""#).unwrap() + 15;
+        let end = start + request[start..].find('"').unwrap();
+        base64::decode(&request[start..end]).unwrap()
+    }
+
+    #[test]
+    fn it_sends_through_a_channel_and_moves_its_sequence_on() {
+        let source = Keypair::from_secret_seed(SOURCE).unwrap();
+        let channel = Keypair::random().unwrap();
+        let submitter = ChannelSubmitter::new(vec![(channel.clone(), 41)]);
+        let server = serve(vec![
+            SIMULATION.to_string(),
+            sent("PENDING"),
+            SIMULATION.to_string(),
+            sent("ERROR"),
+            SIMULATION.to_string(),
+            sent("PENDING"),
+        ]);
+        let client = Client::new(server.uri()).unwrap();
+        let builder = InvokeContractBuilder::new(source.account_id(), 7, CONTRACT, "hello");
+        for status in &["PENDING", "ERROR", "PENDING"] {
+            let sent = submitter
+                .submit(&client, &Network::test(), builder.clone(), &source)
+                .unwrap();
+            assert_eq!(sent.status(), *status);
+        }
+        assert_eq!(submitter.available(), 1);
+
+        let requests = server.requests();
+        let sequences: Vec<_> = [&requests[1], &requests[3], &requests[5]]
+            .iter()
+            .map(|request| envelope(request.body())[44..52].to_vec())
+            .collect();
+        // The rejected transaction didn't use its sequence number up.
+        assert_eq!(sequences[0], &[0, 0, 0, 0, 0, 0, 0, 42]);
+        assert_eq!(sequences[1], &[0, 0, 0, 0, 0, 0, 0, 43]);
+        assert_eq!(sequences[2], &[0, 0, 0, 0, 0, 0, 0, 43]);
+
+        let sent = envelope(requests[1].body());
+        assert_eq!(&sent[8..40], &channel.public_key()[..]);
+        assert_eq!(
+            &sent[72..104],
+            &strkey::decode(strkey::ACCOUNT, source.account_id()).unwrap()[..]
+        );
+        // Signed by the channel, then by the source.
+        let signatures = &sent[sent.len() - 2 * 72 - 4..];
+        assert_eq!(&signatures[..4], &[0, 0, 0, 2]);
+        assert_eq!(&signatures[4..8], &channel.signature_hint());
+        assert_eq!(&signatures[76..80], &source.signature_hint());
+    }
+
+    #[test]
+    fn it_returns_the_channel_when_sending_fails() {
+        let source = Keypair::from_secret_seed(SOURCE).unwrap();
+        let submitter = ChannelSubmitter::new(vec![(Keypair::random().unwrap(), 1)]);
+        let server = serve(vec![
+            r#"{"jsonrpc":"2.0","id":1,"result":{"latestLedger":1000,"error":"HostError"}}"#
+                .to_string(),
+        ]);
+        let client = Client::new(server.uri()).unwrap();
+        let builder = InvokeContractBuilder::new(source.account_id(), 7, CONTRACT, "hello");
+        assert!(submitter
+            .submit(&client, &Network::test(), builder, &source)
+            .is_err());
+        assert_eq!(submitter.available(), 1);
+    }
+
+    #[test]
+    fn it_lends_each_channel_to_one_transaction_at_a_time() {
+        let channels = vec![
+            (Keypair::random().unwrap(), 1),
+            (Keypair::random().unwrap(), 1),
+        ];
+        let submitter = Arc::new(ChannelSubmitter::new(channels));
+        let first = submitter.acquire();
+        let second = submitter.acquire();
+        assert_ne!(
+            first.channel().keypair.account_id(),
+            second.channel().keypair.account_id()
+        );
+        assert_eq!(submitter.available(), 0);
+
+        // A third transaction waits until a channel comes back.
+        let waiting = {
+            let submitter = submitter.clone();
+            thread::spawn(move || {
+                submitter
+                    .acquire()
+                    .channel()
+                    .keypair
+                    .account_id()
+                    .to_string()
+            })
+        };
+        let returned = first.channel().keypair.account_id().to_string();
+        drop(first);
+        assert_eq!(waiting.join().unwrap(), returned);
+        drop(second);
+        assert_eq!(submitter.available(), 2);
+    }
+
+    #[test]
+    fn it_hands_out_channels_in_turn() {
+        let channels = vec![
+            (Keypair::random().unwrap(), 1),
+            (Keypair::random().unwrap(), 1),
+        ];
+        let submitter = ChannelSubmitter::new(channels);
+        let next = || {
+            submitter
+                .acquire()
+                .channel()
+                .keypair
+                .account_id()
+                .to_string()
+        };
+        let (first, second, third) = (next(), next(), next());
+        assert_ne!(first, second);
+        assert_eq!(first, third);
+    }
+
+    #[test]
+    fn it_resyncs_a_channel() {
+        let channel = Keypair::random().unwrap();
+        let submitter = ChannelSubmitter::new(vec![(channel.clone(), 1)]);
+        assert!(submitter.resync(channel.account_id(), 90));
+        assert_eq!(submitter.acquire().channel().sequence, 90);
+        assert!(!submitter.resync(SOURCE, 90));
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_panics_without_channels() {
+        let _ = ChannelSubmitter::new(Vec::new());
+    }
+}
@@ -0,0 +1,635 @@
+use super::xdr::{Encode, ScAddress, ScVal, SorobanTransactionData, Writer};
+use super::Client;
+use base64;
+use error::{Error, Result};
+#[cfg(feature = "keys")]
+use futures::Future;
+#[cfg(feature = "keys")]
+use keys::{DecoratedSignature, Signer};
+#[cfg(feature = "keys")]
+use network::Network;
+#[cfg(feature = "keys")]
+use openssl::sha::sha256;
+use std::convert::TryFrom;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+use strkey;
+
+const BASE_FEE: u32 = 100;
+
+/// Assembles a transaction that invokes a single contract function.
+///
+/// A soroban transaction has to declare up front which ledger entries it
+/// touches and what resources it uses, and pay for them. `assemble` learns
+/// all of that by simulating the call and writes it into the transaction,
+/// the way the javascript sdk's `assembleTransaction` does.
+///
+/// The assembled envelope is unsigned. With the `keys` feature enabled it can
+/// be signed with `AssembledTransaction::sign`, otherwise it needs to be
+/// signed elsewhere before it is sent with `Client::send_transaction`.
+///
+/// ## Example
+///
+/// ```no_run
+/// use stellar_client::soroban::{Client, InvokeContractBuilder, ScAddress, ScVal};
+///
+/// let client = Client::new("https://soroban-testnet.stellar.org").unwrap();
+/// let transaction = InvokeContractBuilder::new(
+///     "GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM",
+///     30232549674450945,
+///     "CBQHNAXSI55GX2GN6D67GK7BHVPSLJUGZQEU7WJ5LKR5PNUCGLIMAO4K",
+///     "balance",
+/// ).with_arg(ScVal::Address(ScAddress::Account(
+///     "GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM".to_string(),
+/// ))).assemble(&client)
+///     .unwrap();
+/// println!("needs a fee of {}", transaction.fee());
+/// ```
+#[derive(Debug, Clone)]
+pub struct InvokeContractBuilder {
+    source_account: String,
+    sequence: u64,
+    contract_id: String,
+    function: String,
+    args: Vec<ScVal>,
+    fee: u32,
+    time_bounds: Option<(u64, u64)>,
+    operation_source: Option<String>,
+}
+
+impl InvokeContractBuilder {
+    /// Starts a call to `function` on the contract `contract_id`, sent from
+    /// `source_account`. The `sequence` is the account's current sequence
+    /// number as horizon reports it; the transaction uses the one after it.
+    pub fn new(
+        source_account: &str,
+        sequence: u64,
+        contract_id: &str,
+        function: &str,
+    ) -> InvokeContractBuilder {
+        InvokeContractBuilder {
+            source_account: source_account.to_string(),
+            sequence,
+            contract_id: contract_id.to_string(),
+            function: function.to_string(),
+            args: Vec::new(),
+            fee: BASE_FEE,
+            time_bounds: None,
+            operation_source: None,
+        }
+    }
+
+    /// Adds the next argument to pass to the function.
+    pub fn with_arg(mut self, arg: ScVal) -> InvokeContractBuilder {
+        self.args.push(arg);
+        self
+    }
+
+    /// Sets the inclusion fee in stroops, paid on top of the resource fee
+    /// the simulation asks for. Defaults to 100.
+    pub fn with_fee(mut self, fee: u32) -> InvokeContractBuilder {
+        self.fee = fee;
+        self
+    }
+
+    /// Only allows the transaction into a ledger closing between the two
+    /// unix timestamps. A `max_time` of 0 means no upper bound.
+    pub fn with_time_bounds(mut self, min_time: u64, max_time: u64) -> InvokeContractBuilder {
+        self.time_bounds = Some((min_time, max_time));
+        self
+    }
+
+    /// Makes the call on behalf of `account` while `source_account` pays
+    /// the fee and provides the sequence number. Both accounts have to sign.
+    pub fn with_operation_source(mut self, account: &str) -> InvokeContractBuilder {
+        self.operation_source = Some(account.to_string());
+        self
+    }
+
+    /// Moves the transaction onto a channel account, which pays the fee and
+    /// provides the sequence number, leaving the call made on behalf of the
+    /// original source.
+    pub(crate) fn through_channel(mut self, channel: &str, sequence: u64) -> InvokeContractBuilder {
+        if self.operation_source.is_none() {
+            self.operation_source = Some(self.source_account.clone());
+        }
+        self.source_account = channel.to_string();
+        self.sequence = sequence;
+        self
+    }
+
+    /// Checks the transaction against the rules the network applies before
+    /// it is accepted, returning every rule it breaks. Horizon and the rpc
+    /// server report these failures with terse result codes, and only one at
+    /// a time, so checking first saves a round trip.
+    ///
+    /// The transaction holds a single operation and no memo, so it cannot
+    /// break the operation count or memo rules.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use stellar_client::soroban::{InvokeContractBuilder, Violation};
+    ///
+    /// let builder = InvokeContractBuilder::new(
+    ///     "GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM",
+    ///     30232549674450945,
+    ///     "CBQHNAXSI55GX2GN6D67GK7BHVPSLJUGZQEU7WJ5LKR5PNUCGLIMAO4K",
+    ///     "balance",
+    /// ).with_fee(10);
+    /// assert_eq!(
+    ///     builder.validate(),
+    ///     vec![Violation::FeeTooLow { fee: 10, minimum: 100 }]
+    /// );
+    /// ```
+    pub fn validate(&self) -> Vec<Violation> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        self.validate_at(now)
+    }
+
+    fn validate_at(&self, now: u64) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        if strkey::decode(strkey::ACCOUNT, &self.source_account).is_none() {
+            violations.push(Violation::InvalidSourceAccount(self.source_account.clone()));
+        }
+        if let Some(ref account) = self.operation_source {
+            if strkey::decode(strkey::ACCOUNT, account).is_none() {
+                violations.push(Violation::InvalidOperationSource(account.clone()));
+            }
+        }
+        if strkey::decode(strkey::CONTRACT, &self.contract_id).is_none() {
+            violations.push(Violation::InvalidContractId(self.contract_id.clone()));
+        }
+        // The base fee is charged for every operation.
+        if self.fee < BASE_FEE {
+            violations.push(Violation::FeeTooLow {
+                fee: self.fee,
+                minimum: BASE_FEE,
+            });
+        }
+        if self.next_sequence().is_none() {
+            violations.push(Violation::SequenceExhausted(self.sequence));
+        }
+        if let Some((min_time, max_time)) = self.time_bounds {
+            if max_time != 0 && min_time > max_time {
+                violations.push(Violation::InvalidTimeBounds { min_time, max_time });
+            } else if max_time != 0 && max_time < now {
+                violations.push(Violation::Expired { max_time });
+            }
+        }
+        violations
+    }
+
+    /// The sequence number the transaction uses, the one after the
+    /// account's, or `None` when the account's is the largest there is.
+    fn next_sequence(&self) -> Option<i64> {
+        i64::try_from(self.sequence).ok()?.checked_add(1)
+    }
+
+    /// Builds the unsigned transaction envelope as base64 XDR, without any
+    /// footprint or resource fee. This is what gets simulated.
+    pub fn build(&self) -> Result<String> {
+        Ok(envelope_xdr(&self.encode(self.fee, &[], None)?, &[]))
+    }
+
+    /// Simulates the call and returns the transaction with the footprint,
+    /// authorizations and fees from the simulation applied.
+    pub fn assemble(&self, client: &Client) -> Result<AssembledTransaction> {
+        let simulation = client.simulate_transaction(&self.build()?)?;
+        if let Some(error) = simulation.error() {
+            return Err(Error::Simulation(error.to_string()));
+        }
+        let raw_data = match simulation.transaction_data() {
+            Some(data) => decode_base64(data)?,
+            None => return Err(Error::ServerError(None)),
+        };
+        let transaction_data = simulation
+            .decode_transaction_data()?
+            .ok_or(Error::ServerError(None))?;
+        let resource_fee: u32 = match simulation.min_resource_fee() {
+            Some(fee) => fee.parse().map_err(|_| Error::ServerError(None))?,
+            None => 0,
+        };
+        let fee = self.fee.saturating_add(resource_fee);
+
+        let (auth, result) = match simulation.results().first() {
+            Some(result) => {
+                let auth = result
+                    .auth()
+                    .iter()
+                    .map(|entry| decode_base64(entry))
+                    .collect::<Result<Vec<_>>>()?;
+                (auth, Some(result.decode_xdr()?))
+            }
+            None => (Vec::new(), None),
+        };
+
+        let transaction = self.encode(fee, &auth, Some(&raw_data))?;
+        Ok(AssembledTransaction {
+            envelope_xdr: envelope_xdr(&transaction, &[]),
+            transaction,
+            signatures: Vec::new(),
+            transaction_data,
+            fee,
+            result,
+        })
+    }
+
+    /// Writes a `TransactionEnvelope` holding a single invoke host function
+    /// operation, up to but not including its signatures. Authorization
+    /// entries and soroban data come from the simulation already encoded, so
+    /// they are copied in as they are.
+    fn encode(&self, fee: u32, auth: &[Vec<u8>], soroban_data: Option<&[u8]>) -> Result<Vec<u8>> {
+        let mut w = Writer::new();
+        // ENVELOPE_TYPE_TX
+        w.i32(2);
+        w.account_id(&self.source_account)?;
+        w.u32(fee);
+        let exhausted = Error::InvalidTransaction(Violation::SequenceExhausted(self.sequence));
+        let sequence = self.next_sequence().ok_or(exhausted)?;
+        w.i64(sequence);
+        match self.time_bounds {
+            Some((min_time, max_time)) => {
+                // PRECOND_TIME
+                w.i32(1);
+                w.u64(min_time);
+                w.u64(max_time);
+            }
+            // PRECOND_NONE
+            None => w.i32(0),
+        }
+        // MEMO_NONE
+        w.i32(0);
+
+        // A single operation, with its own source account if it has one.
+        w.u32(1);
+        match self.operation_source {
+            Some(ref account) => {
+                w.bool(true);
+                w.account_id(account)?;
+            }
+            None => w.bool(false),
+        }
+        // INVOKE_HOST_FUNCTION, HOST_FUNCTION_TYPE_INVOKE_CONTRACT
+        w.i32(24);
+        w.i32(0);
+        ScAddress::Contract(self.contract_id.clone()).encode(&mut w)?;
+        w.opaque(self.function.as_bytes());
+        w.vec(&self.args, Encode::encode)?;
+        w.u32(auth.len() as u32);
+        for entry in auth {
+            w.raw(entry);
+        }
+
+        match soroban_data {
+            Some(data) => {
+                w.i32(1);
+                w.raw(data);
+            }
+            None => w.i32(0),
+        }
+        Ok(w.into_bytes())
+    }
+}
+
+/// A rule that a transaction breaks, found by `InvokeContractBuilder::validate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// The source account is not a valid `G...` account id.
+    InvalidSourceAccount(String),
+    /// The operation's source is not a valid `G...` account id.
+    InvalidOperationSource(String),
+    /// The contract id is not a valid `C...` contract id.
+    InvalidContractId(String),
+    /// The inclusion fee is below the base fee for the transaction's
+    /// operations.
+    FeeTooLow {
+        /// The fee that was set, in stroops.
+        fee: u32,
+        /// The lowest fee the network accepts, in stroops.
+        minimum: u32,
+    },
+    /// The source account's sequence number, given as the inner value, is
+    /// the largest there is, so no transaction can follow it.
+    SequenceExhausted(u64),
+    /// The time bounds end before they start.
+    InvalidTimeBounds {
+        /// The earliest time the transaction is valid.
+        min_time: u64,
+        /// The latest time the transaction is valid.
+        max_time: u64,
+    },
+    /// The time bounds have already passed.
+    Expired {
+        /// The latest time the transaction was valid.
+        max_time: u64,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Violation::InvalidSourceAccount(ref id) => {
+                write!(f, "{} is not a valid source account", id)
+            }
+            Violation::InvalidOperationSource(ref id) => {
+                write!(f, "{} is not a valid operation source", id)
+            }
+            Violation::InvalidContractId(ref id) => write!(f, "{} is not a valid contract id", id),
+            Violation::FeeTooLow { fee, minimum } => {
+                write!(f, "the fee of {} is below the minimum of {}", fee, minimum)
+            }
+            Violation::SequenceExhausted(sequence) => {
+                write!(f, "no sequence number follows {}", sequence)
+            }
+            Violation::InvalidTimeBounds { min_time, max_time } => write!(
+                f,
+                "the time bounds end at {} before they start at {}",
+                max_time, min_time
+            ),
+            Violation::Expired { max_time } => write!(f, "the time bounds ended at {}", max_time),
+        }
+    }
+}
+
+/// Finishes an encoded transaction off with its signatures, each of which
+/// is an already encoded `DecoratedSignature`.
+fn envelope_xdr(transaction: &[u8], signatures: &[Vec<u8>]) -> String {
+    let mut w = Writer::new();
+    w.raw(transaction);
+    w.u32(signatures.len() as u32);
+    for signature in signatures {
+        w.raw(signature);
+    }
+    base64::encode(&w.into_bytes())
+}
+
+fn decode_base64(xdr: &str) -> Result<Vec<u8>> {
+    base64::decode(xdr).map_err(|_| Error::ServerError(None))
+}
+
+/// A contract invocation ready to be signed and sent.
+#[derive(Debug, Clone)]
+pub struct AssembledTransaction {
+    transaction: Vec<u8>,
+    signatures: Vec<Vec<u8>>,
+    envelope_xdr: String,
+    transaction_data: SorobanTransactionData,
+    fee: u32,
+    result: Option<ScVal>,
+}
+
+impl AssembledTransaction {
+    /// The transaction envelope as base64 XDR, along with any signatures
+    /// added so far.
+    pub fn envelope_xdr(&self) -> &str {
+        &self.envelope_xdr
+    }
+
+    /// The hash of the transaction on `network`, which is what signers sign.
+    #[cfg(feature = "keys")]
+    pub fn hash(&self, network: &Network) -> [u8; 32] {
+        // The envelope starts with the ENVELOPE_TYPE_TX that the signature
+        // payload also puts in front of the transaction.
+        let mut payload = network.id().to_vec();
+        payload.extend_from_slice(&self.transaction);
+        sha256(&payload)
+    }
+
+    /// Adds a signature made elsewhere to the envelope.
+    #[cfg(feature = "keys")]
+    pub fn with_signature(mut self, signature: &DecoratedSignature) -> AssembledTransaction {
+        let mut w = Writer::new();
+        w.raw(&signature.hint());
+        w.opaque(signature.signature());
+        self.signatures.push(w.into_bytes());
+        self.envelope_xdr = envelope_xdr(&self.transaction, &self.signatures);
+        self
+    }
+
+    /// Signs the transaction for `network` and adds the signature to the
+    /// envelope, waiting for the signer to finish.
+    #[cfg(feature = "keys")]
+    pub fn sign<S>(self, network: &Network, signer: &S) -> Result<AssembledTransaction>
+    where
+        S: Signer + ?Sized,
+    {
+        let signature = signer.sign_hash(&self.hash(network)).wait()?;
+        Ok(self.with_signature(&signature))
+    }
+
+    /// The footprint and resources the transaction declares.
+    pub fn transaction_data(&self) -> &SorobanTransactionData {
+        &self.transaction_data
+    }
+
+    /// The total fee in stroops: the inclusion fee plus the resource fee.
+    pub fn fee(&self) -> u32 {
+        self.fee
+    }
+
+    /// The value the function returned in the simulation. For read only
+    /// calls this is the answer, and there is no need to send the
+    /// transaction at all.
+    pub fn result(&self) -> Option<&ScVal> {
+        self.result.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod invoke_contract_tests {
+    use super::*;
+    use test::{TestResponse, TestServer};
+
+    const ACCOUNT: &str = "GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM";
+    const CONTRACT: &str = "CBQHNAXSI55GX2GN6D67GK7BHVPSLJUGZQEU7WJ5LKR5PNUCGLIMAO4K";
+
+    fn builder() -> InvokeContractBuilder {
+        InvokeContractBuilder::new(ACCOUNT, 100, CONTRACT, "hello")
+            .with_arg(ScVal::Symbol("world".to_string()))
+    }
+
+    #[test]
+    fn it_builds_an_unsigned_envelope() {
+        assert_eq!(
+            builder().build().unwrap(),
+            "AAAAAgAAAADtkTiQi4ZRH8iWIPtj317otdpdRK+ML6Y/N2/grJrD7gAAAGQAAAAAAAAAZQAAAAAAAAAAAAAAAQAAAAAAAAAYAAAAAAAAAAFgdoLyR3pr6M3w/fMr4T1fJaaGzAlP2T1ao9e2gjLQwAAAAAVoZWxsbwAAAAAAAAEAAAAPAAAABXdvcmxkAAAAAAAAAAAAAAAAAAAA"
+        );
+    }
+
+    #[test]
+    fn it_errs_on_a_bad_source_account() {
+        let builder = InvokeContractBuilder::new("GABC", 1, CONTRACT, "hello");
+        assert!(builder.build().is_err());
+    }
+
+    #[test]
+    fn it_errs_when_no_sequence_number_follows() {
+        for &sequence in &[i64::MAX as u64, u64::MAX] {
+            let builder = InvokeContractBuilder::new(ACCOUNT, sequence, CONTRACT, "hello");
+            match builder.build().unwrap_err() {
+                Error::InvalidTransaction(Violation::SequenceExhausted(s)) => {
+                    assert_eq!(s, sequence)
+                }
+                err => panic!("expected an exhausted sequence, got {:?}", err),
+            }
+            assert_eq!(
+                builder.validate(),
+                vec![Violation::SequenceExhausted(sequence)]
+            );
+        }
+    }
+
+    #[test]
+    fn it_moves_a_transaction_onto_a_channel() {
+        let channel = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+        let moved = builder().through_channel(channel, 41);
+        let expected = InvokeContractBuilder::new(channel, 41, CONTRACT, "hello")
+            .with_arg(ScVal::Symbol("world".to_string()))
+            .with_operation_source(ACCOUNT);
+        assert_eq!(moved.build().unwrap(), expected.build().unwrap());
+
+        let envelope = base64::decode(&moved.build().unwrap()).unwrap();
+        // The channel is the transaction source, with its next sequence.
+        assert_eq!(
+            &envelope[8..40],
+            &strkey::decode(strkey::ACCOUNT, channel).unwrap()[..]
+        );
+        assert_eq!(&envelope[44..52], &[0, 0, 0, 0, 0, 0, 0, 42]);
+        // The operation count, then the original source as the operation's.
+        assert_eq!(&envelope[60..68], &[0, 0, 0, 1, 0, 0, 0, 1]);
+        assert_eq!(
+            &envelope[72..104],
+            &strkey::decode(strkey::ACCOUNT, ACCOUNT).unwrap()[..]
+        );
+    }
+
+    #[test]
+    fn it_validates_a_well_formed_transaction() {
+        assert!(builder().validate().is_empty());
+        let bounded = builder().with_time_bounds(0, 2_000);
+        assert!(bounded.validate_at(1_000).is_empty());
+        let open_ended = builder().with_time_bounds(500, 0);
+        assert!(open_ended.validate_at(1_000).is_empty());
+    }
+
+    #[test]
+    fn it_lists_every_violation() {
+        let builder = InvokeContractBuilder::new("GABC", 1, ACCOUNT, "hello")
+            .with_operation_source("GDEF")
+            .with_fee(99)
+            .with_time_bounds(0, 500);
+        assert_eq!(
+            builder.validate_at(1_000),
+            vec![
+                Violation::InvalidSourceAccount("GABC".to_string()),
+                Violation::InvalidOperationSource("GDEF".to_string()),
+                Violation::InvalidContractId(ACCOUNT.to_string()),
+                Violation::FeeTooLow {
+                    fee: 99,
+                    minimum: 100,
+                },
+                Violation::Expired { max_time: 500 },
+            ]
+        );
+        assert_eq!(
+            builder.with_time_bounds(2_000, 1_500).validate_at(1_000),
+            vec![
+                Violation::InvalidSourceAccount("GABC".to_string()),
+                Violation::InvalidOperationSource("GDEF".to_string()),
+                Violation::InvalidContractId(ACCOUNT.to_string()),
+                Violation::FeeTooLow {
+                    fee: 99,
+                    minimum: 100,
+                },
+                Violation::InvalidTimeBounds {
+                    min_time: 2_000,
+                    max_time: 1_500,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_applies_the_simulation() {
+        let data = "AAAAAAAAAAEAAAAHAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABAAAABgAAAAFgdoLyR3pr6M3w/fMr4T1fJaaGzAlP2T1ao9e2gjLQwAAAABQAAAABAAGGoAAAAGQAAAAyAAAAAAAAJxA=";
+        let server = TestServer::new(vec![TestResponse::json(&format!(
+            r#"{{"jsonrpc":"2.0","id":1,"result":{{"latestLedger":1000,"minResourceFee":"10000","transactionData":"{}","results":[{{"auth":[],"xdr":"AAAADwAAAAV3b3JsZAAAAA=="}}]}}}}"#,
+            data
+        ))]);
+        let client = Client::new(server.uri()).unwrap();
+        let transaction = builder().assemble(&client).unwrap();
+        assert_eq!(transaction.fee(), 10_100);
+        assert_eq!(transaction.transaction_data().instructions(), 100_000);
+        assert_eq!(
+            transaction.result(),
+            Some(&ScVal::Symbol("world".to_string()))
+        );
+
+        let envelope = base64::decode(transaction.envelope_xdr()).unwrap();
+        let data = base64::decode(data).unwrap();
+        // The soroban data replaces the empty extension, followed by the
+        // empty signature list.
+        assert!(envelope[..envelope.len() - 4].ends_with(&data));
+        // The fee follows the envelope type and source account.
+        assert_eq!(&envelope[40..44], &[0, 0, 0x27, 0x74]);
+    }
+
+    #[cfg(feature = "keys")]
+    #[test]
+    fn it_signs_an_assembled_transaction() {
+        use keys::Keypair;
+
+        let transaction = builder().encode(100, &[], None).unwrap();
+        let assembled = AssembledTransaction {
+            envelope_xdr: envelope_xdr(&transaction, &[]),
+            transaction,
+            signatures: Vec::new(),
+            transaction_data: SorobanTransactionData::from_xdr_base64(
+                "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=",
+            )
+            .unwrap(),
+            fee: 100,
+            result: None,
+        };
+        assert_eq!(assembled.envelope_xdr(), builder().build().unwrap());
+
+        let hash: String = assembled
+            .hash(&Network::test())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        assert_eq!(
+            hash,
+            "5d2cd70812179062a849dd3374fbb0746276ff6f76a074f186fc5b8766134a96"
+        );
+
+        let keypair =
+            Keypair::from_secret_seed("SAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6NKI")
+                .unwrap();
+        let signed = assembled.sign(&Network::test(), &keypair).unwrap();
+        let envelope = base64::decode(signed.envelope_xdr()).unwrap();
+        let signature = keypair.sign(&signed.hash(&Network::test())).unwrap();
+        // One signature: its hint, then the 64 byte signature as opaque data.
+        let tail = &envelope[envelope.len() - 76..];
+        assert_eq!(&tail[..4], &[0, 0, 0, 1]);
+        assert_eq!(&tail[4..8], &keypair.signature_hint());
+        assert_eq!(&tail[8..12], &[0, 0, 0, 64]);
+        assert_eq!(&tail[12..], &signature[..]);
+    }
+
+    #[test]
+    fn it_returns_simulation_errors() {
+        let server = TestServer::new(vec![TestResponse::json(
+            r#"{"jsonrpc":"2.0","id":1,"result":{"latestLedger":1000,"error":"HostError: Error(Contract, #1)"}}"#,
+        )]);
+        let client = Client::new(server.uri()).unwrap();
+        match builder().assemble(&client).unwrap_err() {
+            Error::Simulation(message) => assert_eq!(message, "HostError: Error(Contract, #1)"),
+            error => panic!("Assemble did not return a simulation error {:?}", error),
+        }
+    }
+}
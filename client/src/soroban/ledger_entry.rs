@@ -0,0 +1,1440 @@
+//! The ledger entries behind accounts, trust lines, offers, data, claimable
+//! balances, liquidity pools and contracts, as soroban rpc's
+//! `getLedgerEntries` and captive core hand them out. Entries decode to the
+//! same string forms as the rest of the soroban module and encode back to
+//! the exact XDR they came from.
+use super::xdr::{
+    account_id, asset_code, from_base64, to_base64, ContractDataKey, Decode, Encode, Reader,
+    Result, ScVal, TrustLineAsset, Writer, XdrError,
+};
+use strkey;
+
+/// The contents of a ledger entry, the `LedgerEntryData` XDR union.
+///
+/// ## Example
+///
+/// ```
+/// use stellar_client::soroban::LedgerEntryData;
+///
+/// let xdr = "AAAACasAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAD6A==";
+/// match LedgerEntryData::from_xdr_base64(xdr).unwrap() {
+///     LedgerEntryData::Ttl(ttl) => assert_eq!(ttl.live_until_ledger_seq(), 1000),
+///     other => panic!("expected a ttl entry, got {:?}", other),
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LedgerEntryData {
+    /// An account.
+    Account(AccountEntry),
+    /// A trust line from an account to an asset.
+    Trustline(TrustLineEntry),
+    /// An offer on the decentralized exchange.
+    Offer(OfferEntry),
+    /// A data entry attached to an account.
+    Data(DataEntry),
+    /// A claimable balance.
+    ClaimableBalance(ClaimableBalanceEntry),
+    /// A liquidity pool.
+    LiquidityPool(LiquidityPoolEntry),
+    /// Data stored by a contract.
+    ContractData(ContractDataEntry),
+    /// Uploaded contract code.
+    ContractCode(ContractCodeEntry),
+    /// The time to live of a contract data or code entry.
+    Ttl(TtlEntry),
+}
+
+impl LedgerEntryData {
+    /// Decodes entry data from base64 encoded XDR, such as the `xdr` of an
+    /// entry returned by `getLedgerEntries`.
+    pub fn from_xdr_base64(xdr: &str) -> Result<LedgerEntryData> {
+        from_base64(xdr)
+    }
+
+    /// Encodes the entry data as base64 XDR.
+    pub fn to_xdr_base64(&self) -> Result<String> {
+        to_base64(self)
+    }
+}
+
+/// A complete ledger entry, the `LedgerEntry` XDR struct: the entry's data
+/// along with when it last changed and who sponsors its reserve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LedgerEntryRecord {
+    last_modified_ledger_seq: u32,
+    data: LedgerEntryData,
+    sponsoring_id: Option<Option<String>>,
+}
+
+impl LedgerEntryRecord {
+    /// Decodes an entry from base64 encoded XDR.
+    pub fn from_xdr_base64(xdr: &str) -> Result<LedgerEntryRecord> {
+        from_base64(xdr)
+    }
+
+    /// Encodes the entry as base64 XDR.
+    pub fn to_xdr_base64(&self) -> Result<String> {
+        to_base64(self)
+    }
+
+    /// The ledger the entry last changed in.
+    pub fn last_modified_ledger_seq(&self) -> u32 {
+        self.last_modified_ledger_seq
+    }
+
+    /// The contents of the entry.
+    pub fn data(&self) -> &LedgerEntryData {
+        &self.data
+    }
+
+    /// The `G...` id of the account paying the entry's reserve, if another
+    /// account sponsors it.
+    pub fn sponsoring_id(&self) -> Option<&str> {
+        match self.sponsoring_id {
+            Some(Some(ref id)) => Some(id),
+            _ => None,
+        }
+    }
+}
+
+/// An asset that is not a liquidity pool share, the `Asset` XDR union.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Asset {
+    /// Lumens.
+    Native,
+    /// An asset with a code of up to 4 characters, and its issuer.
+    CreditAlphanum4(String, String),
+    /// An asset with a code of up to 12 characters, and its issuer.
+    CreditAlphanum12(String, String),
+}
+
+/// The key of a signer on an account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignerKey {
+    /// An ed25519 key, as its `G...` public key.
+    Ed25519(String),
+    /// The hex encoded hash of a transaction that is allowed to sign.
+    PreAuthTx(String),
+    /// The hex encoded hash of a preimage that signs.
+    HashX(String),
+    /// An ed25519 key, as its `G...` public key, that signs a payload.
+    Ed25519SignedPayload(String, Vec<u8>),
+}
+
+/// A signer on an account, with the weight of its signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signer {
+    key: SignerKey,
+    weight: u32,
+}
+
+impl Signer {
+    /// The signer's key.
+    pub fn key(&self) -> &SignerKey {
+        &self.key
+    }
+
+    /// The weight of the signer's signature.
+    pub fn weight(&self) -> u32 {
+        self.weight
+    }
+}
+
+/// Amounts an account or trust line has promised to offers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Liabilities {
+    buying: i64,
+    selling: i64,
+}
+
+impl Liabilities {
+    /// The amount that open offers would buy, in stroops.
+    pub fn buying(&self) -> i64 {
+        self.buying
+    }
+
+    /// The amount that open offers would sell, in stroops.
+    pub fn selling(&self) -> i64 {
+        self.selling
+    }
+}
+
+/// An account ledger entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountEntry {
+    account_id: String,
+    balance: i64,
+    seq_num: i64,
+    num_sub_entries: u32,
+    inflation_dest: Option<String>,
+    flags: u32,
+    home_domain: String,
+    thresholds: [u8; 4],
+    signers: Vec<Signer>,
+    liabilities: Option<Liabilities>,
+    sponsorship: Option<AccountSponsorship>,
+    seq_info: Option<(u32, u64)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct AccountSponsorship {
+    num_sponsored: u32,
+    num_sponsoring: u32,
+    signer_sponsoring_ids: Vec<Option<String>>,
+}
+
+impl AccountEntry {
+    /// The `G...` id of the account.
+    pub fn account_id(&self) -> &str {
+        &self.account_id
+    }
+
+    /// The account's balance of lumens, in stroops.
+    pub fn balance(&self) -> i64 {
+        self.balance
+    }
+
+    /// The account's sequence number.
+    pub fn seq_num(&self) -> i64 {
+        self.seq_num
+    }
+
+    /// How many trust lines, offers, signers and data entries the account
+    /// has.
+    pub fn num_sub_entries(&self) -> u32 {
+        self.num_sub_entries
+    }
+
+    /// The account that the account's inflation votes go to.
+    pub fn inflation_dest(&self) -> Option<&str> {
+        self.inflation_dest.as_deref()
+    }
+
+    /// The account's authorization flags.
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// The domain that hosts the account's stellar.toml.
+    pub fn home_domain(&self) -> &str {
+        &self.home_domain
+    }
+
+    /// The master key weight followed by the low, medium and high
+    /// thresholds.
+    pub fn thresholds(&self) -> [u8; 4] {
+        self.thresholds
+    }
+
+    /// The account's signers other than its master key.
+    pub fn signers(&self) -> &[Signer] {
+        &self.signers
+    }
+
+    /// The amounts the account's offers have promised in lumens.
+    pub fn liabilities(&self) -> Liabilities {
+        self.liabilities.unwrap_or_default()
+    }
+
+    /// How many of the account's reserves other accounts pay for.
+    pub fn num_sponsored(&self) -> u32 {
+        self.sponsorship.as_ref().map_or(0, |s| s.num_sponsored)
+    }
+
+    /// How many reserves of other accounts the account pays for.
+    pub fn num_sponsoring(&self) -> u32 {
+        self.sponsorship.as_ref().map_or(0, |s| s.num_sponsoring)
+    }
+
+    /// The sponsor of each signer, in the same order as `signers`.
+    pub fn signer_sponsoring_ids(&self) -> Vec<Option<&str>> {
+        match self.sponsorship {
+            Some(ref sponsorship) => sponsorship
+                .signer_sponsoring_ids
+                .iter()
+                .map(|id| id.as_deref())
+                .collect(),
+            None => vec![None; self.signers.len()],
+        }
+    }
+
+    /// The ledger the sequence number last changed in, once the network
+    /// tracks it.
+    pub fn seq_ledger(&self) -> Option<u32> {
+        self.seq_info.map(|(ledger, _)| ledger)
+    }
+
+    /// The close time, in unix seconds, of the ledger the sequence number
+    /// last changed in, once the network tracks it.
+    pub fn seq_time(&self) -> Option<u64> {
+        self.seq_info.map(|(_, time)| time)
+    }
+}
+
+/// A trust line ledger entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustLineEntry {
+    account_id: String,
+    asset: TrustLineAsset,
+    balance: i64,
+    limit: i64,
+    flags: u32,
+    liabilities: Option<Liabilities>,
+    liquidity_pool_use_count: Option<i32>,
+}
+
+impl TrustLineEntry {
+    /// The `G...` id of the account holding the trust line.
+    pub fn account_id(&self) -> &str {
+        &self.account_id
+    }
+
+    /// The asset trusted.
+    pub fn asset(&self) -> &TrustLineAsset {
+        &self.asset
+    }
+
+    /// How much of the asset the account holds, in stroops.
+    pub fn balance(&self) -> i64 {
+        self.balance
+    }
+
+    /// The most of the asset the account will hold, in stroops.
+    pub fn limit(&self) -> i64 {
+        self.limit
+    }
+
+    /// The trust line's authorization flags.
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// The amounts of the asset the account's offers have promised.
+    pub fn liabilities(&self) -> Liabilities {
+        self.liabilities.unwrap_or_default()
+    }
+
+    /// How many liquidity pools use the trust line.
+    pub fn liquidity_pool_use_count(&self) -> i32 {
+        self.liquidity_pool_use_count.unwrap_or(0)
+    }
+}
+
+/// An offer ledger entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OfferEntry {
+    seller_id: String,
+    offer_id: i64,
+    selling: Asset,
+    buying: Asset,
+    amount: i64,
+    price: (i32, i32),
+    flags: u32,
+}
+
+impl OfferEntry {
+    /// The `G...` id of the account making the offer.
+    pub fn seller_id(&self) -> &str {
+        &self.seller_id
+    }
+
+    /// The offer's id.
+    pub fn offer_id(&self) -> i64 {
+        self.offer_id
+    }
+
+    /// The asset being sold.
+    pub fn selling(&self) -> &Asset {
+        &self.selling
+    }
+
+    /// The asset being bought.
+    pub fn buying(&self) -> &Asset {
+        &self.buying
+    }
+
+    /// How much is being sold, in stroops.
+    pub fn amount(&self) -> i64 {
+        self.amount
+    }
+
+    /// The price of the selling asset in the buying asset, as a numerator
+    /// and denominator.
+    pub fn price(&self) -> (i32, i32) {
+        self.price
+    }
+
+    /// The offer's flags, such as whether it is passive.
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+}
+
+/// A data ledger entry, a name and value attached to an account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataEntry {
+    account_id: String,
+    name: String,
+    value: Vec<u8>,
+}
+
+impl DataEntry {
+    /// The `G...` id of the account the data is attached to.
+    pub fn account_id(&self) -> &str {
+        &self.account_id
+    }
+
+    /// The data's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The data's value.
+    pub fn value(&self) -> &[u8] {
+        &self.value
+    }
+}
+
+/// A claimable balance ledger entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimableBalanceEntry {
+    balance_id: String,
+    claimants: Vec<Claimant>,
+    asset: Asset,
+    amount: i64,
+    flags: Option<u32>,
+}
+
+impl ClaimableBalanceEntry {
+    /// The hex encoded id of the balance.
+    pub fn balance_id(&self) -> &str {
+        &self.balance_id
+    }
+
+    /// The accounts that can claim the balance.
+    pub fn claimants(&self) -> &[Claimant] {
+        &self.claimants
+    }
+
+    /// The asset held.
+    pub fn asset(&self) -> &Asset {
+        &self.asset
+    }
+
+    /// How much is held, in stroops.
+    pub fn amount(&self) -> i64 {
+        self.amount
+    }
+
+    /// The balance's flags, such as whether it can be clawed back.
+    pub fn flags(&self) -> u32 {
+        self.flags.unwrap_or(0)
+    }
+}
+
+/// An account that can claim a claimable balance, and when.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Claimant {
+    destination: String,
+    predicate: ClaimPredicate,
+}
+
+impl Claimant {
+    /// The `G...` id of the account that can claim.
+    pub fn destination(&self) -> &str {
+        &self.destination
+    }
+
+    /// The condition under which the account can claim.
+    pub fn predicate(&self) -> &ClaimPredicate {
+        &self.predicate
+    }
+}
+
+/// A condition on claiming a claimable balance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClaimPredicate {
+    /// The balance can always be claimed.
+    Unconditional,
+    /// Every predicate must hold.
+    And(Vec<ClaimPredicate>),
+    /// Any predicate must hold.
+    Or(Vec<ClaimPredicate>),
+    /// The predicate must not hold.
+    Not(Option<Box<ClaimPredicate>>),
+    /// Before a close time, in unix seconds.
+    BeforeAbsoluteTime(i64),
+    /// Within a number of seconds of the balance being created.
+    BeforeRelativeTime(i64),
+}
+
+/// A constant product liquidity pool ledger entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiquidityPoolEntry {
+    liquidity_pool_id: String,
+    asset_a: Asset,
+    asset_b: Asset,
+    fee: i32,
+    reserve_a: i64,
+    reserve_b: i64,
+    total_pool_shares: i64,
+    pool_shares_trust_line_count: i64,
+}
+
+impl LiquidityPoolEntry {
+    /// The hex encoded id of the pool.
+    pub fn liquidity_pool_id(&self) -> &str {
+        &self.liquidity_pool_id
+    }
+
+    /// The first asset in the pool.
+    pub fn asset_a(&self) -> &Asset {
+        &self.asset_a
+    }
+
+    /// The second asset in the pool.
+    pub fn asset_b(&self) -> &Asset {
+        &self.asset_b
+    }
+
+    /// The fee charged on trades, in basis points.
+    pub fn fee(&self) -> i32 {
+        self.fee
+    }
+
+    /// How much of the first asset the pool holds, in stroops.
+    pub fn reserve_a(&self) -> i64 {
+        self.reserve_a
+    }
+
+    /// How much of the second asset the pool holds, in stroops.
+    pub fn reserve_b(&self) -> i64 {
+        self.reserve_b
+    }
+
+    /// The number of pool shares in existence.
+    pub fn total_pool_shares(&self) -> i64 {
+        self.total_pool_shares
+    }
+
+    /// How many trust lines hold shares of the pool.
+    pub fn pool_shares_trust_line_count(&self) -> i64 {
+        self.pool_shares_trust_line_count
+    }
+}
+
+/// A contract data ledger entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractDataEntry {
+    key: ContractDataKey,
+    val: ScVal,
+}
+
+impl ContractDataEntry {
+    /// The contract, key and durability the data is stored under.
+    pub fn key(&self) -> &ContractDataKey {
+        &self.key
+    }
+
+    /// The stored value.
+    pub fn val(&self) -> &ScVal {
+        &self.val
+    }
+}
+
+/// A contract code ledger entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractCodeEntry {
+    hash: String,
+    code: Vec<u8>,
+    cost_inputs: Option<[u32; 10]>,
+}
+
+impl ContractCodeEntry {
+    /// The hex encoded hash of the code.
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    /// The wasm itself.
+    pub fn code(&self) -> &[u8] {
+        &self.code
+    }
+
+    /// The counts the network charges for loading the code, in the order
+    /// the XDR lists them: instructions, functions, globals, table entries,
+    /// types, data segments, element segments, imports, exports and data
+    /// segment bytes. Code uploaded before protocol 21 has none.
+    pub fn cost_inputs(&self) -> Option<&[u32; 10]> {
+        self.cost_inputs.as_ref()
+    }
+}
+
+/// The time to live of a contract data or code entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TtlEntry {
+    key_hash: String,
+    live_until_ledger_seq: u32,
+}
+
+impl TtlEntry {
+    /// The hex encoded hash of the `LedgerKey` of the entry.
+    pub fn key_hash(&self) -> &str {
+        &self.key_hash
+    }
+
+    /// The last ledger the entry is live for.
+    pub fn live_until_ledger_seq(&self) -> u32 {
+        self.live_until_ledger_seq
+    }
+}
+
+impl Decode for LedgerEntryRecord {
+    fn decode(r: &mut Reader) -> Result<LedgerEntryRecord> {
+        let last_modified_ledger_seq = r.u32()?;
+        let data = LedgerEntryData::decode(r)?;
+        let sponsoring_id = match r.i32()? {
+            0 => None,
+            1 => {
+                let id = optional_account_id(r)?;
+                r.extension_point()?;
+                Some(id)
+            }
+            other => return Err(XdrError::UnknownDiscriminant("LedgerEntry ext", other)),
+        };
+        Ok(LedgerEntryRecord {
+            last_modified_ledger_seq,
+            data,
+            sponsoring_id,
+        })
+    }
+}
+
+impl Decode for LedgerEntryData {
+    fn decode(r: &mut Reader) -> Result<LedgerEntryData> {
+        Ok(match r.i32()? {
+            0 => LedgerEntryData::Account(AccountEntry::decode(r)?),
+            1 => LedgerEntryData::Trustline(TrustLineEntry::decode(r)?),
+            2 => LedgerEntryData::Offer(OfferEntry::decode(r)?),
+            3 => {
+                let entry = DataEntry {
+                    account_id: account_id(r)?,
+                    name: r.string()?,
+                    value: r.opaque()?,
+                };
+                r.extension_point()?;
+                LedgerEntryData::Data(entry)
+            }
+            4 => LedgerEntryData::ClaimableBalance(ClaimableBalanceEntry::decode(r)?),
+            5 => LedgerEntryData::LiquidityPool(LiquidityPoolEntry::decode(r)?),
+            6 => {
+                r.extension_point()?;
+                LedgerEntryData::ContractData(ContractDataEntry {
+                    key: ContractDataKey::decode(r)?,
+                    val: ScVal::decode(r)?,
+                })
+            }
+            7 => LedgerEntryData::ContractCode(ContractCodeEntry::decode(r)?),
+            8 => return Err(XdrError::Unsupported("ConfigSettingEntry")),
+            9 => LedgerEntryData::Ttl(TtlEntry {
+                key_hash: r.hash()?,
+                live_until_ledger_seq: r.u32()?,
+            }),
+            other => return Err(XdrError::UnknownDiscriminant("LedgerEntryType", other)),
+        })
+    }
+}
+
+impl Decode for AccountEntry {
+    fn decode(r: &mut Reader) -> Result<AccountEntry> {
+        let mut entry = AccountEntry {
+            account_id: account_id(r)?,
+            balance: r.i64()?,
+            seq_num: r.i64()?,
+            num_sub_entries: r.u32()?,
+            inflation_dest: optional_account_id(r)?,
+            flags: r.u32()?,
+            home_domain: r.string()?,
+            thresholds: r.fixed()?,
+            signers: r.vec(Signer::decode)?,
+            liabilities: None,
+            sponsorship: None,
+            seq_info: None,
+        };
+        match r.i32()? {
+            0 => return Ok(entry),
+            1 => entry.liabilities = Some(Liabilities::decode(r)?),
+            other => return Err(XdrError::UnknownDiscriminant("AccountEntry ext", other)),
+        }
+        match r.i32()? {
+            0 => return Ok(entry),
+            2 => {
+                let num_sponsored = r.u32()?;
+                let num_sponsoring = r.u32()?;
+                let count = r.u32()?;
+                let mut signer_sponsoring_ids = Vec::new();
+                for _ in 0..count {
+                    signer_sponsoring_ids.push(optional_account_id(r)?);
+                }
+                entry.sponsorship = Some(AccountSponsorship {
+                    num_sponsored,
+                    num_sponsoring,
+                    signer_sponsoring_ids,
+                });
+            }
+            other => {
+                return Err(XdrError::UnknownDiscriminant(
+                    "AccountEntryExtensionV1 ext",
+                    other,
+                ))
+            }
+        }
+        match r.i32()? {
+            0 => {}
+            3 => {
+                r.extension_point()?;
+                entry.seq_info = Some((r.u32()?, r.u64()?));
+            }
+            other => {
+                return Err(XdrError::UnknownDiscriminant(
+                    "AccountEntryExtensionV2 ext",
+                    other,
+                ))
+            }
+        }
+        Ok(entry)
+    }
+}
+
+impl Decode for Signer {
+    fn decode(r: &mut Reader) -> Result<Signer> {
+        let key = match r.i32()? {
+            0 => SignerKey::Ed25519(public_key(r)?),
+            1 => SignerKey::PreAuthTx(r.hash()?),
+            2 => SignerKey::HashX(r.hash()?),
+            3 => SignerKey::Ed25519SignedPayload(public_key(r)?, r.opaque()?),
+            other => return Err(XdrError::UnknownDiscriminant("SignerKey", other)),
+        };
+        Ok(Signer {
+            key,
+            weight: r.u32()?,
+        })
+    }
+}
+
+impl Decode for Liabilities {
+    fn decode(r: &mut Reader) -> Result<Liabilities> {
+        Ok(Liabilities {
+            buying: r.i64()?,
+            selling: r.i64()?,
+        })
+    }
+}
+
+impl Decode for TrustLineEntry {
+    fn decode(r: &mut Reader) -> Result<TrustLineEntry> {
+        let mut entry = TrustLineEntry {
+            account_id: account_id(r)?,
+            asset: TrustLineAsset::decode(r)?,
+            balance: r.i64()?,
+            limit: r.i64()?,
+            flags: r.u32()?,
+            liabilities: None,
+            liquidity_pool_use_count: None,
+        };
+        match r.i32()? {
+            0 => return Ok(entry),
+            1 => entry.liabilities = Some(Liabilities::decode(r)?),
+            other => return Err(XdrError::UnknownDiscriminant("TrustLineEntry ext", other)),
+        }
+        match r.i32()? {
+            0 => {}
+            2 => {
+                entry.liquidity_pool_use_count = Some(r.i32()?);
+                r.extension_point()?;
+            }
+            other => return Err(XdrError::UnknownDiscriminant("TrustLineEntryV1 ext", other)),
+        }
+        Ok(entry)
+    }
+}
+
+impl Decode for OfferEntry {
+    fn decode(r: &mut Reader) -> Result<OfferEntry> {
+        let entry = OfferEntry {
+            seller_id: account_id(r)?,
+            offer_id: r.i64()?,
+            selling: Asset::decode(r)?,
+            buying: Asset::decode(r)?,
+            amount: r.i64()?,
+            price: (r.i32()?, r.i32()?),
+            flags: r.u32()?,
+        };
+        r.extension_point()?;
+        Ok(entry)
+    }
+}
+
+impl Decode for ClaimableBalanceEntry {
+    fn decode(r: &mut Reader) -> Result<ClaimableBalanceEntry> {
+        let balance_id = match r.i32()? {
+            0 => r.hash()?,
+            other => return Err(XdrError::UnknownDiscriminant("ClaimableBalanceID", other)),
+        };
+        let claimants = r.vec(Claimant::decode)?;
+        let asset = Asset::decode(r)?;
+        let amount = r.i64()?;
+        let flags = match r.i32()? {
+            0 => None,
+            1 => {
+                r.extension_point()?;
+                Some(r.u32()?)
+            }
+            other => {
+                return Err(XdrError::UnknownDiscriminant(
+                    "ClaimableBalanceEntry ext",
+                    other,
+                ))
+            }
+        };
+        Ok(ClaimableBalanceEntry {
+            balance_id,
+            claimants,
+            asset,
+            amount,
+            flags,
+        })
+    }
+}
+
+impl Decode for Claimant {
+    fn decode(r: &mut Reader) -> Result<Claimant> {
+        match r.i32()? {
+            0 => Ok(Claimant {
+                destination: account_id(r)?,
+                predicate: ClaimPredicate::decode(r)?,
+            }),
+            other => Err(XdrError::UnknownDiscriminant("Claimant", other)),
+        }
+    }
+}
+
+impl Decode for ClaimPredicate {
+    fn decode(r: &mut Reader) -> Result<ClaimPredicate> {
+        r.nested(|r| {
+            Ok(match r.i32()? {
+                0 => ClaimPredicate::Unconditional,
+                1 => ClaimPredicate::And(r.vec(ClaimPredicate::decode)?),
+                2 => ClaimPredicate::Or(r.vec(ClaimPredicate::decode)?),
+                3 => ClaimPredicate::Not(r.optional(ClaimPredicate::decode)?.map(Box::new)),
+                4 => ClaimPredicate::BeforeAbsoluteTime(r.i64()?),
+                5 => ClaimPredicate::BeforeRelativeTime(r.i64()?),
+                other => return Err(XdrError::UnknownDiscriminant("ClaimPredicate", other)),
+            })
+        })
+    }
+}
+
+impl Decode for LiquidityPoolEntry {
+    fn decode(r: &mut Reader) -> Result<LiquidityPoolEntry> {
+        let liquidity_pool_id = r.hash()?;
+        match r.i32()? {
+            0 => Ok(LiquidityPoolEntry {
+                liquidity_pool_id,
+                asset_a: Asset::decode(r)?,
+                asset_b: Asset::decode(r)?,
+                fee: r.i32()?,
+                reserve_a: r.i64()?,
+                reserve_b: r.i64()?,
+                total_pool_shares: r.i64()?,
+                pool_shares_trust_line_count: r.i64()?,
+            }),
+            other => Err(XdrError::UnknownDiscriminant("LiquidityPoolType", other)),
+        }
+    }
+}
+
+impl Decode for ContractCodeEntry {
+    fn decode(r: &mut Reader) -> Result<ContractCodeEntry> {
+        let cost_inputs = match r.i32()? {
+            0 => None,
+            1 => {
+                r.extension_point()?;
+                r.extension_point()?;
+                let mut inputs = [0; 10];
+                for input in inputs.iter_mut() {
+                    *input = r.u32()?;
+                }
+                Some(inputs)
+            }
+            other => {
+                return Err(XdrError::UnknownDiscriminant(
+                    "ContractCodeEntry ext",
+                    other,
+                ))
+            }
+        };
+        Ok(ContractCodeEntry {
+            hash: r.hash()?,
+            code: r.opaque()?,
+            cost_inputs,
+        })
+    }
+}
+
+impl Decode for Asset {
+    fn decode(r: &mut Reader) -> Result<Asset> {
+        Ok(match r.i32()? {
+            0 => Asset::Native,
+            1 => {
+                let code = asset_code(&r.fixed::<[u8; 4]>()?)?;
+                Asset::CreditAlphanum4(code, account_id(r)?)
+            }
+            2 => {
+                let code = asset_code(&r.fixed::<[u8; 12]>()?)?;
+                Asset::CreditAlphanum12(code, account_id(r)?)
+            }
+            other => return Err(XdrError::UnknownDiscriminant("Asset", other)),
+        })
+    }
+}
+
+/// An ed25519 key without the `PublicKey` discriminant in front.
+fn public_key(r: &mut Reader) -> Result<String> {
+    Ok(strkey::encode(strkey::ACCOUNT, &r.fixed::<[u8; 32]>()?))
+}
+
+/// An optional account id, which is also how sponsors are recorded.
+fn optional_account_id(r: &mut Reader) -> Result<Option<String>> {
+    if r.bool()? {
+        account_id(r).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+impl Encode for LedgerEntryRecord {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        w.u32(self.last_modified_ledger_seq);
+        self.data.encode(w)?;
+        match self.sponsoring_id {
+            None => w.i32(0),
+            Some(ref id) => {
+                w.i32(1);
+                write_optional_account_id(w, id)?;
+                w.i32(0);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Encode for LedgerEntryData {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        match *self {
+            LedgerEntryData::Account(ref entry) => {
+                w.i32(0);
+                entry.encode(w)
+            }
+            LedgerEntryData::Trustline(ref entry) => {
+                w.i32(1);
+                entry.encode(w)
+            }
+            LedgerEntryData::Offer(ref entry) => {
+                w.i32(2);
+                entry.encode(w)
+            }
+            LedgerEntryData::Data(ref entry) => {
+                w.i32(3);
+                w.account_id(&entry.account_id)?;
+                w.opaque(entry.name.as_bytes());
+                w.opaque(&entry.value);
+                w.i32(0);
+                Ok(())
+            }
+            LedgerEntryData::ClaimableBalance(ref entry) => {
+                w.i32(4);
+                entry.encode(w)
+            }
+            LedgerEntryData::LiquidityPool(ref entry) => {
+                w.i32(5);
+                entry.encode(w)
+            }
+            LedgerEntryData::ContractData(ref entry) => {
+                w.i32(6);
+                w.i32(0);
+                entry.key.encode(w)?;
+                entry.val.encode(w)
+            }
+            LedgerEntryData::ContractCode(ref entry) => {
+                w.i32(7);
+                entry.encode(w)
+            }
+            LedgerEntryData::Ttl(ref entry) => {
+                w.i32(9);
+                w.hash(&entry.key_hash)?;
+                w.u32(entry.live_until_ledger_seq);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Encode for AccountEntry {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        w.account_id(&self.account_id)?;
+        w.i64(self.balance);
+        w.i64(self.seq_num);
+        w.u32(self.num_sub_entries);
+        write_optional_account_id(w, &self.inflation_dest)?;
+        w.u32(self.flags);
+        w.opaque(self.home_domain.as_bytes());
+        w.raw(&self.thresholds);
+        w.vec(&self.signers, Encode::encode)?;
+        // Each extension sits inside the one before it, so a later one
+        // brings the earlier ones along.
+        if self.liabilities.is_none() && self.sponsorship.is_none() && self.seq_info.is_none() {
+            w.i32(0);
+            return Ok(());
+        }
+        w.i32(1);
+        self.liabilities.unwrap_or_default().encode(w)?;
+        if self.sponsorship.is_none() && self.seq_info.is_none() {
+            w.i32(0);
+            return Ok(());
+        }
+        w.i32(2);
+        let sponsorship = self.sponsorship.clone().unwrap_or_default();
+        w.u32(sponsorship.num_sponsored);
+        w.u32(sponsorship.num_sponsoring);
+        w.u32(sponsorship.signer_sponsoring_ids.len() as u32);
+        for id in &sponsorship.signer_sponsoring_ids {
+            write_optional_account_id(w, id)?;
+        }
+        match self.seq_info {
+            None => w.i32(0),
+            Some((ledger, time)) => {
+                w.i32(3);
+                w.i32(0);
+                w.u32(ledger);
+                w.u64(time);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Encode for Signer {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        match self.key {
+            SignerKey::Ed25519(ref key) => {
+                w.i32(0);
+                write_public_key(w, key)?;
+            }
+            SignerKey::PreAuthTx(ref hash) => {
+                w.i32(1);
+                w.hash(hash)?;
+            }
+            SignerKey::HashX(ref hash) => {
+                w.i32(2);
+                w.hash(hash)?;
+            }
+            SignerKey::Ed25519SignedPayload(ref key, ref payload) => {
+                w.i32(3);
+                write_public_key(w, key)?;
+                w.opaque(payload);
+            }
+        }
+        w.u32(self.weight);
+        Ok(())
+    }
+}
+
+impl Encode for Liabilities {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        w.i64(self.buying);
+        w.i64(self.selling);
+        Ok(())
+    }
+}
+
+impl Encode for TrustLineEntry {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        w.account_id(&self.account_id)?;
+        self.asset.encode(w)?;
+        w.i64(self.balance);
+        w.i64(self.limit);
+        w.u32(self.flags);
+        if self.liabilities.is_none() && self.liquidity_pool_use_count.is_none() {
+            w.i32(0);
+            return Ok(());
+        }
+        w.i32(1);
+        self.liabilities.unwrap_or_default().encode(w)?;
+        match self.liquidity_pool_use_count {
+            None => w.i32(0),
+            Some(count) => {
+                w.i32(2);
+                w.i32(count);
+                w.i32(0);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Encode for OfferEntry {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        w.account_id(&self.seller_id)?;
+        w.i64(self.offer_id);
+        self.selling.encode(w)?;
+        self.buying.encode(w)?;
+        w.i64(self.amount);
+        w.i32(self.price.0);
+        w.i32(self.price.1);
+        w.u32(self.flags);
+        w.i32(0);
+        Ok(())
+    }
+}
+
+impl Encode for ClaimableBalanceEntry {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        w.i32(0);
+        w.hash(&self.balance_id)?;
+        w.vec(&self.claimants, Encode::encode)?;
+        self.asset.encode(w)?;
+        w.i64(self.amount);
+        match self.flags {
+            None => w.i32(0),
+            Some(flags) => {
+                w.i32(1);
+                w.i32(0);
+                w.u32(flags);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Encode for Claimant {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        w.i32(0);
+        w.account_id(&self.destination)?;
+        self.predicate.encode(w)
+    }
+}
+
+impl Encode for ClaimPredicate {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        match *self {
+            ClaimPredicate::Unconditional => w.i32(0),
+            ClaimPredicate::And(ref predicates) => {
+                w.i32(1);
+                w.vec(predicates, Encode::encode)?;
+            }
+            ClaimPredicate::Or(ref predicates) => {
+                w.i32(2);
+                w.vec(predicates, Encode::encode)?;
+            }
+            ClaimPredicate::Not(ref predicate) => {
+                w.i32(3);
+                w.bool(predicate.is_some());
+                if let Some(ref predicate) = *predicate {
+                    predicate.encode(w)?;
+                }
+            }
+            ClaimPredicate::BeforeAbsoluteTime(time) => {
+                w.i32(4);
+                w.i64(time);
+            }
+            ClaimPredicate::BeforeRelativeTime(seconds) => {
+                w.i32(5);
+                w.i64(seconds);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Encode for LiquidityPoolEntry {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        w.hash(&self.liquidity_pool_id)?;
+        w.i32(0);
+        self.asset_a.encode(w)?;
+        self.asset_b.encode(w)?;
+        w.i32(self.fee);
+        w.i64(self.reserve_a);
+        w.i64(self.reserve_b);
+        w.i64(self.total_pool_shares);
+        w.i64(self.pool_shares_trust_line_count);
+        Ok(())
+    }
+}
+
+impl Encode for ContractCodeEntry {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        match self.cost_inputs {
+            None => w.i32(0),
+            Some(ref inputs) => {
+                w.i32(1);
+                w.i32(0);
+                w.i32(0);
+                for input in inputs {
+                    w.u32(*input);
+                }
+            }
+        }
+        w.hash(&self.hash)?;
+        w.opaque(&self.code);
+        Ok(())
+    }
+}
+
+impl Encode for Asset {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        match *self {
+            Asset::Native => w.i32(0),
+            Asset::CreditAlphanum4(ref code, ref issuer) => {
+                w.i32(1);
+                w.asset_code(code, 4)?;
+                w.account_id(issuer)?;
+            }
+            Asset::CreditAlphanum12(ref code, ref issuer) => {
+                w.i32(2);
+                w.asset_code(code, 12)?;
+                w.account_id(issuer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_public_key(w: &mut Writer, key: &str) -> Result<()> {
+    let key = strkey::decode(strkey::ACCOUNT, key).ok_or(XdrError::InvalidAddress)?;
+    w.raw(&key);
+    Ok(())
+}
+
+fn write_optional_account_id(w: &mut Writer, id: &Option<String>) -> Result<()> {
+    w.bool(id.is_some());
+    match *id {
+        Some(ref id) => w.account_id(id),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod ledger_entry_tests {
+    use super::*;
+    use soroban::xdr::{ContractDataDurability, LedgerKey, ScAddress};
+
+    const ACCOUNT: &str = "GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM";
+    const HASH: &str = "ab00000000000000000000000000000000000000000000000000000000000000";
+
+    fn usd() -> Asset {
+        Asset::CreditAlphanum4("USD".to_string(), ACCOUNT.to_string())
+    }
+
+    fn round_trip(data: LedgerEntryData) {
+        let xdr = data.to_xdr_base64().unwrap();
+        assert_eq!(LedgerEntryData::from_xdr_base64(&xdr).unwrap(), data);
+    }
+
+    fn account(
+        liabilities: Option<Liabilities>,
+        sponsorship: Option<AccountSponsorship>,
+        seq_info: Option<(u32, u64)>,
+    ) -> AccountEntry {
+        AccountEntry {
+            account_id: ACCOUNT.to_string(),
+            balance: 100_0000000,
+            seq_num: 12345,
+            num_sub_entries: 2,
+            inflation_dest: None,
+            flags: 1,
+            home_domain: "example.com".to_string(),
+            thresholds: [1, 0, 2, 3],
+            signers: vec![
+                Signer {
+                    key: SignerKey::HashX(HASH.to_string()),
+                    weight: 1,
+                },
+                Signer {
+                    key: SignerKey::Ed25519SignedPayload(ACCOUNT.to_string(), vec![1, 2, 3]),
+                    weight: 2,
+                },
+            ],
+            liabilities,
+            sponsorship,
+            seq_info,
+        }
+    }
+
+    #[test]
+    fn it_round_trips_accounts_at_every_extension() {
+        let liabilities = Liabilities {
+            buying: 5,
+            selling: 6,
+        };
+        let sponsorship = AccountSponsorship {
+            num_sponsored: 1,
+            num_sponsoring: 0,
+            signer_sponsoring_ids: vec![Some(ACCOUNT.to_string()), None],
+        };
+        round_trip(LedgerEntryData::Account(account(None, None, None)));
+        round_trip(LedgerEntryData::Account(account(
+            Some(liabilities),
+            None,
+            None,
+        )));
+        round_trip(LedgerEntryData::Account(account(
+            Some(liabilities),
+            Some(sponsorship.clone()),
+            None,
+        )));
+        let entry = account(
+            Some(liabilities),
+            Some(sponsorship),
+            Some((77, 1_700_000_000)),
+        );
+        round_trip(LedgerEntryData::Account(entry.clone()));
+        assert_eq!(entry.liabilities().selling(), 6);
+        assert_eq!(entry.signer_sponsoring_ids(), vec![Some(ACCOUNT), None]);
+        assert_eq!(entry.seq_ledger(), Some(77));
+    }
+
+    #[test]
+    fn it_round_trips_classic_entries() {
+        round_trip(LedgerEntryData::Trustline(TrustLineEntry {
+            account_id: ACCOUNT.to_string(),
+            asset: TrustLineAsset::PoolShare(HASH.to_string()),
+            balance: 10,
+            limit: i64::max_value(),
+            flags: 1,
+            liabilities: Some(Liabilities::default()),
+            liquidity_pool_use_count: Some(3),
+        }));
+        round_trip(LedgerEntryData::Offer(OfferEntry {
+            seller_id: ACCOUNT.to_string(),
+            offer_id: 99,
+            selling: Asset::Native,
+            buying: usd(),
+            amount: 500,
+            price: (3, 2),
+            flags: 0,
+        }));
+        round_trip(LedgerEntryData::Data(DataEntry {
+            account_id: ACCOUNT.to_string(),
+            name: "config".to_string(),
+            value: vec![0xff; 5],
+        }));
+        round_trip(LedgerEntryData::ClaimableBalance(ClaimableBalanceEntry {
+            balance_id: HASH.to_string(),
+            claimants: vec![Claimant {
+                destination: ACCOUNT.to_string(),
+                predicate: ClaimPredicate::And(vec![
+                    ClaimPredicate::Not(Some(Box::new(ClaimPredicate::BeforeRelativeTime(60)))),
+                    ClaimPredicate::Or(vec![
+                        ClaimPredicate::Unconditional,
+                        ClaimPredicate::BeforeAbsoluteTime(1_700_000_000),
+                    ]),
+                ]),
+            }],
+            asset: Asset::CreditAlphanum12("LONGERCODE".to_string(), ACCOUNT.to_string()),
+            amount: 1,
+            flags: Some(1),
+        }));
+        round_trip(LedgerEntryData::LiquidityPool(LiquidityPoolEntry {
+            liquidity_pool_id: HASH.to_string(),
+            asset_a: Asset::Native,
+            asset_b: usd(),
+            fee: 30,
+            reserve_a: 1000,
+            reserve_b: 2000,
+            total_pool_shares: 1414,
+            pool_shares_trust_line_count: 4,
+        }));
+    }
+
+    #[test]
+    fn it_round_trips_contract_entries() {
+        let key = match LedgerKey::from_xdr_base64(
+            "AAAABgAAAAFgdoLyR3pr6M3w/fMr4T1fJaaGzAlP2T1ao9e2gjLQwAAAABQAAAAB",
+        )
+        .unwrap()
+        {
+            LedgerKey::ContractData(key) => key,
+            other => panic!("expected a contract data key, got {:?}", other),
+        };
+        assert_eq!(
+            *key.contract(),
+            ScAddress::Contract(
+                "CBQHNAXSI55GX2GN6D67GK7BHVPSLJUGZQEU7WJ5LKR5PNUCGLIMAO4K".to_string()
+            )
+        );
+        assert_eq!(key.durability(), ContractDataDurability::Persistent);
+        round_trip(LedgerEntryData::ContractData(ContractDataEntry {
+            key,
+            val: ScVal::I128(-5),
+        }));
+        round_trip(LedgerEntryData::ContractCode(ContractCodeEntry {
+            hash: HASH.to_string(),
+            code: b"\0asm".to_vec(),
+            cost_inputs: Some([1, 2, 3, 4, 5, 6, 7, 8, 9, 10]),
+        }));
+        round_trip(LedgerEntryData::Ttl(TtlEntry {
+            key_hash: HASH.to_string(),
+            live_until_ledger_seq: 1000,
+        }));
+    }
+
+    #[test]
+    fn it_round_trips_sponsored_entries() {
+        let record = LedgerEntryRecord {
+            last_modified_ledger_seq: 500,
+            data: LedgerEntryData::Account(account(None, None, None)),
+            sponsoring_id: Some(Some(ACCOUNT.to_string())),
+        };
+        let xdr = record.to_xdr_base64().unwrap();
+        let decoded = LedgerEntryRecord::from_xdr_base64(&xdr).unwrap();
+        assert_eq!(decoded, record);
+        assert_eq!(decoded.sponsoring_id(), Some(ACCOUNT));
+        assert_eq!(decoded.last_modified_ledger_seq(), 500);
+    }
+
+    #[test]
+    fn it_rejects_predicates_nested_too_deep() {
+        let entry = |depth: usize| {
+            let mut predicate = ClaimPredicate::Unconditional;
+            for _ in 1..depth {
+                predicate = ClaimPredicate::Not(Some(Box::new(predicate)));
+            }
+            LedgerEntryData::ClaimableBalance(ClaimableBalanceEntry {
+                balance_id: HASH.to_string(),
+                claimants: vec![Claimant {
+                    destination: ACCOUNT.to_string(),
+                    predicate,
+                }],
+                asset: Asset::Native,
+                amount: 1,
+                flags: None,
+            })
+            .to_xdr_base64()
+            .unwrap()
+        };
+        assert!(LedgerEntryData::from_xdr_base64(&entry(500)).is_ok());
+        assert_eq!(
+            LedgerEntryData::from_xdr_base64(&entry(501)).unwrap_err(),
+            XdrError::TooDeep
+        );
+    }
+
+    #[test]
+    fn it_refuses_config_settings() {
+        assert_eq!(
+            LedgerEntryData::from_xdr_base64("AAAACAAAAAA=").unwrap_err(),
+            XdrError::Unsupported("ConfigSettingEntry")
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! A client for the soroban rpc server, which serves smart contract data and
+//! accepts contract transactions in the same way horizon does for the rest of
+//! the network.
+//!
+//! Soroban rpc speaks JSON-RPC 2.0 over http. Transactions, ledger keys and
+//! other XDR structures are passed in and returned as base64 encoded strings.
+//! Contract values, events, footprints and ledger entries in the responses
+//! can be decoded with the types in this module, and contract calls are
+//! built, simulated and signed with `InvokeContractBuilder`.
+//!
+//! This module is only available with the `soroban` feature enabled.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use stellar_client::soroban::Client;
+//!
+//! let client = Client::new("https://soroban-testnet.stellar.org").unwrap();
+//! let ledger = client.get_latest_ledger().unwrap();
+//! println!("latest ledger {}", ledger.sequence());
+//! ```
+use error::{Error, Result};
+use http::Uri;
+use reqwest;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+#[cfg(feature = "keys")]
+mod channel;
+mod invoke;
+mod ledger_entry;
+mod sac;
+mod types;
+#[cfg(feature = "keys")]
+mod withdrawal;
+mod xdr;
+
+#[cfg(feature = "keys")]
+pub use self::channel::ChannelSubmitter;
+pub use self::invoke::{AssembledTransaction, InvokeContractBuilder, Violation};
+pub use self::ledger_entry::{
+    AccountEntry, Asset, ClaimPredicate, ClaimableBalanceEntry, Claimant, ContractCodeEntry,
+    ContractDataEntry, DataEntry, LedgerEntryData, LedgerEntryRecord, Liabilities,
+    LiquidityPoolEntry, OfferEntry, Signer, SignerKey, TrustLineEntry, TtlEntry,
+};
+pub use self::sac::StellarAssetContract;
+pub use self::types::{
+    Event, EventFilter, Events, EventsRequest, LatestLedger, LedgerEntries, LedgerEntry,
+    SendTransaction, SimulateResult, SimulateTransaction, Transaction,
+};
+#[cfg(feature = "keys")]
+pub use self::withdrawal::{
+    CheckWithdrawal, MemoRequired, Withdrawal, WithdrawalOutcome, WithdrawalQueue, WithdrawalStatus,
+};
+pub use self::xdr::{
+    ContractDataDurability, ContractDataKey, ContractEvent, ContractEventType, ContractExecutable,
+    ContractInstance, DiagnosticEvent, LedgerFootprint, LedgerKey, ScAddress, ScError, ScVal,
+    SorobanTransactionData, TrustLineAsset, XdrError,
+};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A synchronous client for a soroban rpc server.
+#[derive(Debug)]
+pub struct Client {
+    inner: reqwest::Client,
+    uri: String,
+    next_id: AtomicUsize,
+}
+
+impl Client {
+    /// Constructs a client for the rpc server at `uri`.
+    pub fn new(uri: &str) -> Result<Self> {
+        // Ensure that the uri passed in can parse.
+        let _: Uri = uri.parse()?;
+        let inner = reqwest::ClientBuilder::new()
+            .timeout(DEFAULT_TIMEOUT)
+            .build()?;
+        Ok(Client {
+            inner,
+            uri: uri.to_string(),
+            next_id: AtomicUsize::new(1),
+        })
+    }
+
+    /// The uri of the rpc server.
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Fetches the most recent ledger known to the server.
+    pub fn get_latest_ledger(&self) -> Result<LatestLedger> {
+        self.call("getLatestLedger", None::<()>)
+    }
+
+    /// Simulates a base64 encoded transaction envelope, returning the
+    /// footprint, authorization and resource fee it would need.
+    pub fn simulate_transaction(&self, transaction: &str) -> Result<SimulateTransaction> {
+        self.call(
+            "simulateTransaction",
+            Some(TransactionParams { transaction }),
+        )
+    }
+
+    /// Submits a base64 encoded, signed transaction envelope. The
+    /// transaction is not yet applied when this returns. Use
+    /// `get_transaction` to find out how it went.
+    pub fn send_transaction(&self, transaction: &str) -> Result<SendTransaction> {
+        self.call("sendTransaction", Some(TransactionParams { transaction }))
+    }
+
+    /// Fetches the status and result of a submitted transaction by its hash.
+    pub fn get_transaction(&self, hash: &str) -> Result<Transaction> {
+        self.call("getTransaction", Some(HashParams { hash }))
+    }
+
+    /// Fetches contract events matching the request.
+    pub fn get_events(&self, request: &EventsRequest) -> Result<Events> {
+        self.call("getEvents", Some(request))
+    }
+
+    /// Fetches the current value of ledger entries by their base64 encoded
+    /// ledger keys.
+    pub fn get_ledger_entries(&self, keys: &[&str]) -> Result<LedgerEntries> {
+        self.call("getLedgerEntries", Some(KeysParams { keys }))
+    }
+
+    fn call<P, R>(&self, method: &str, params: Option<P>) -> Result<R>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        let request = RpcRequest {
+            jsonrpc: "2.0",
+            id: self.next_id.fetch_add(1, Ordering::SeqCst),
+            method,
+            params,
+        };
+        let response = self.inner.post(&self.uri).json(&request).send()?;
+        if response.status().is_server_error() {
+            return Err(Error::ServerError(None));
+        }
+        let response: RpcResponse<R> = serde_json::from_reader(response)?;
+        match (response.result, response.error) {
+            (_, Some(error)) => Err(Error::Rpc(error)),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err(Error::ServerError(None)),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RpcRequest<'a, P> {
+    jsonrpc: &'static str,
+    id: usize,
+    method: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    params: Option<P>,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<R> {
+    result: Option<R>,
+    error: Option<RpcError>,
+}
+
+#[derive(Serialize)]
+struct TransactionParams<'a> {
+    transaction: &'a str,
+}
+
+#[derive(Serialize)]
+struct HashParams<'a> {
+    hash: &'a str,
+}
+
+#[derive(Serialize)]
+struct KeysParams<'a> {
+    keys: &'a [&'a str],
+}
+
+/// An error returned by the rpc server in place of a result.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    /// The JSON-RPC error code.
+    pub fn code(&self) -> i64 {
+        self.code
+    }
+
+    /// A description of what went wrong.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+
+#[cfg(test)]
+mod soroban_client_tests {
+    use super::*;
+    use test::{TestResponse, TestServer};
+
+    // Answers a single rpc call on a local port.
+    fn serve_once(body: &str) -> TestServer {
+        TestServer::new(vec![TestResponse::json(body)])
+    }
+
+    #[test]
+    fn it_gets_the_latest_ledger() {
+        let server = serve_once(
+            r#"{"jsonrpc":"2.0","id":1,"result":{"id":"c73c5eac58a441d4eb733c35253ae85f783e018f7be5ef974258fed067aabb36","protocolVersion":20,"sequence":2539605}}"#,
+        );
+        let ledger = Client::new(server.uri()).unwrap().get_latest_ledger().unwrap();
+        assert_eq!(ledger.sequence(), 2_539_605);
+        assert_eq!(ledger.protocol_version(), 20);
+        let request = server.next_request();
+        assert_eq!(request.request_line(), "POST / HTTP/1.1");
+        assert!(request.body().contains(r#""method":"getLatestLedger""#));
+        assert!(!request.body().contains("params"));
+    }
+
+    #[test]
+    fn it_sends_params_for_a_transaction_lookup() {
+        let server = serve_once(
+            r#"{"jsonrpc":"2.0","id":1,"result":{"status":"NOT_FOUND","latestLedger":2540076,"latestLedgerCloseTime":"1700086333","oldestLedger":2538637,"oldestLedgerCloseTime":"1700078796"}}"#,
+        );
+        let transaction = Client::new(server.uri()).unwrap().get_transaction("abc").unwrap();
+        assert_eq!(transaction.status(), "NOT_FOUND");
+        assert_eq!(transaction.ledger(), None);
+        assert!(server
+            .next_request()
+            .body()
+            .contains(r#""params":{"hash":"abc"}"#));
+    }
+
+    #[test]
+    fn it_returns_rpc_errors() {
+        let server = serve_once(
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"invalid parameters"}}"#,
+        );
+        match Client::new(server.uri())
+            .unwrap()
+            .get_ledger_entries(&[])
+            .unwrap_err()
+        {
+            Error::Rpc(error) => {
+                assert_eq!(error.code(), -32602);
+                assert_eq!(error.message(), "invalid parameters");
+            }
+            error => panic!("Client did not return an rpc error {:?}", error),
+        }
+    }
+}
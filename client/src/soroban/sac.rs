@@ -0,0 +1,177 @@
+use super::invoke::InvokeContractBuilder;
+use super::xdr::{ScAddress, ScVal, Writer, XdrError};
+use network::Network;
+use openssl::sha::sha256;
+use resources::{Amount, AssetIdentifier};
+use strkey;
+
+/// The stellar asset contract (SAC) for a classic asset, which lets
+/// contracts hold and move the asset.
+///
+/// Every classic asset has exactly one such contract per network, at an id
+/// derived from the asset and the network. Balances held by accounts are the
+/// same ones horizon reports, so the SAC is the bridge between the two worlds.
+/// The builders it returns still need to be assembled and signed.
+///
+/// ## Example
+///
+/// ```
+/// use stellar_client::Network;
+/// use stellar_client::resources::AssetIdentifier;
+/// use stellar_client::soroban::StellarAssetContract;
+///
+/// let lumens = StellarAssetContract::new(AssetIdentifier::native(), &Network::test()).unwrap();
+/// assert_eq!(
+///     lumens.contract_id(),
+///     "CDLZFC3SYJYDZT7K67VZ75HPJVIEUVNIXF47ZG2FB2RMQQVU2HHGCYSC"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StellarAssetContract {
+    asset: AssetIdentifier,
+    contract_id: String,
+}
+
+impl StellarAssetContract {
+    /// The contract for `asset` on `network`. Fails if the asset can't be
+    /// encoded as XDR.
+    pub fn new(
+        asset: AssetIdentifier,
+        network: &Network,
+    ) -> Result<StellarAssetContract, XdrError> {
+        let mut w = Writer::new();
+        // ENVELOPE_TYPE_CONTRACT_ID, then CONTRACT_ID_PREIMAGE_FROM_ASSET
+        w.i32(8);
+        w.raw(&network.id());
+        w.i32(1);
+        w.asset(&asset)?;
+        let contract_id = strkey::encode(strkey::CONTRACT, &sha256(&w.into_bytes()));
+        Ok(StellarAssetContract { asset, contract_id })
+    }
+
+    /// The asset the contract wraps.
+    pub fn asset(&self) -> &AssetIdentifier {
+        &self.asset
+    }
+
+    /// The `C...` id of the contract.
+    pub fn contract_id(&self) -> &str {
+        &self.contract_id
+    }
+
+    /// Moves `amount` from `from` to `to`. Either side can be an account or
+    /// a contract, and `from` has to authorize the transfer.
+    pub fn transfer(
+        &self,
+        source_account: &str,
+        sequence: u64,
+        from: &str,
+        to: &str,
+        amount: Amount,
+    ) -> InvokeContractBuilder {
+        self.invoke(source_account, sequence, "transfer")
+            .with_arg(address(from))
+            .with_arg(address(to))
+            .with_arg(ScVal::I128(i128::from(amount.to_stroops())))
+    }
+
+    /// Issues `amount` of new asset to `to`. Only the asset's admin, the
+    /// issuer unless it has been changed, can mint.
+    pub fn mint(
+        &self,
+        source_account: &str,
+        sequence: u64,
+        to: &str,
+        amount: Amount,
+    ) -> InvokeContractBuilder {
+        self.invoke(source_account, sequence, "mint")
+            .with_arg(address(to))
+            .with_arg(ScVal::I128(i128::from(amount.to_stroops())))
+    }
+
+    /// Destroys `amount` of the asset held by `from`, who has to authorize it.
+    pub fn burn(
+        &self,
+        source_account: &str,
+        sequence: u64,
+        from: &str,
+        amount: Amount,
+    ) -> InvokeContractBuilder {
+        self.invoke(source_account, sequence, "burn")
+            .with_arg(address(from))
+            .with_arg(ScVal::I128(i128::from(amount.to_stroops())))
+    }
+
+    /// Reads the balance held by `id`. The simulated result of the assembled
+    /// call is the balance, so it doesn't need to be sent.
+    pub fn balance(&self, source_account: &str, sequence: u64, id: &str) -> InvokeContractBuilder {
+        self.invoke(source_account, sequence, "balance")
+            .with_arg(address(id))
+    }
+
+    fn invoke(&self, source_account: &str, sequence: u64, function: &str) -> InvokeContractBuilder {
+        InvokeContractBuilder::new(source_account, sequence, &self.contract_id, function)
+    }
+}
+
+/// Contracts have `C...` ids and everything else is an account.
+fn address(id: &str) -> ScVal {
+    if id.starts_with('C') {
+        ScVal::Address(ScAddress::Contract(id.to_string()))
+    } else {
+        ScVal::Address(ScAddress::Account(id.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod stellar_asset_contract_tests {
+    use super::*;
+
+    const ISSUER: &str = "GBBD47IF6LWK7P7MDEVSCWR7DPUWV3NY3DTQEVFL4NAT4AQH3ZLLFLA5";
+
+    #[test]
+    fn it_derives_the_contract_id_for_lumens() {
+        let test = StellarAssetContract::new(AssetIdentifier::native(), &Network::test()).unwrap();
+        assert_eq!(
+            test.contract_id(),
+            "CDLZFC3SYJYDZT7K67VZ75HPJVIEUVNIXF47ZG2FB2RMQQVU2HHGCYSC"
+        );
+        let public =
+            StellarAssetContract::new(AssetIdentifier::native(), &Network::public()).unwrap();
+        assert_eq!(
+            public.contract_id(),
+            "CAS3J7GYLGXMF6TDJBBYYSE3HQ6BBSMLNUQ34T6TZMYMW2EVH34XOWMA"
+        );
+    }
+
+    #[test]
+    fn it_derives_the_contract_id_for_an_issued_asset() {
+        let usdc = AssetIdentifier::alphanum4("USDC", ISSUER).unwrap();
+        let contract = StellarAssetContract::new(usdc.clone(), &Network::test()).unwrap();
+        assert_eq!(contract.asset(), &usdc);
+        assert_eq!(
+            contract.contract_id(),
+            "CBIELTK6YBZJU5UP2WWQEUCYKLPU6AUNZ2BQ4WWFEIE3USCIHMXQDAMA"
+        );
+    }
+
+    #[test]
+    fn it_builds_a_transfer() {
+        let contract =
+            StellarAssetContract::new(AssetIdentifier::native(), &Network::test()).unwrap();
+        let expected = InvokeContractBuilder::new(ISSUER, 7, contract.contract_id(), "transfer")
+            .with_arg(ScVal::Address(ScAddress::Account(ISSUER.to_string())))
+            .with_arg(ScVal::Address(ScAddress::Contract(
+                contract.contract_id().to_string(),
+            )))
+            .with_arg(ScVal::I128(10_000_000));
+        let transfer = contract.transfer(
+            ISSUER,
+            7,
+            ISSUER,
+            contract.contract_id(),
+            Amount::new(10_000_000),
+        );
+        assert_eq!(transfer.build().unwrap(), expected.build().unwrap());
+    }
+}
@@ -0,0 +1,545 @@
+use super::ledger_entry::LedgerEntryData;
+use super::xdr::{DiagnosticEvent, ScVal, SorobanTransactionData, XdrError};
+
+/// The most recent ledger known to the rpc server.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LatestLedger {
+    id: String,
+    protocol_version: u32,
+    sequence: u32,
+}
+
+impl LatestLedger {
+    /// The hash of the ledger.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The stellar protocol version the ledger was closed under.
+    pub fn protocol_version(&self) -> u32 {
+        self.protocol_version
+    }
+
+    /// The sequence number of the ledger.
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+}
+
+/// The outcome of simulating a transaction.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateTransaction {
+    latest_ledger: u32,
+    min_resource_fee: Option<String>,
+    transaction_data: Option<String>,
+    #[serde(default)]
+    results: Vec<SimulateResult>,
+    #[serde(default)]
+    events: Vec<String>,
+    error: Option<String>,
+}
+
+impl SimulateTransaction {
+    /// The latest ledger when the simulation ran.
+    pub fn latest_ledger(&self) -> u32 {
+        self.latest_ledger
+    }
+
+    /// The minimum resource fee in stroops that the transaction needs, as a
+    /// decimal string.
+    pub fn min_resource_fee(&self) -> Option<&str> {
+        self.min_resource_fee.as_ref().map(|s| &**s)
+    }
+
+    /// The base64 encoded `SorobanTransactionData`, holding the footprint
+    /// and resources, to attach to the transaction before submitting it.
+    pub fn transaction_data(&self) -> Option<&str> {
+        self.transaction_data.as_ref().map(|s| &**s)
+    }
+
+    /// The result of each host function invocation.
+    pub fn results(&self) -> &Vec<SimulateResult> {
+        &self.results
+    }
+
+    /// The base64 encoded diagnostic events emitted during the simulation.
+    pub fn events(&self) -> &Vec<String> {
+        &self.events
+    }
+
+    /// Why the simulation failed, if it did.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_ref().map(|s| &**s)
+    }
+
+    /// Decodes the transaction data, if the simulation returned any.
+    pub fn decode_transaction_data(&self) -> Result<Option<SorobanTransactionData>, XdrError> {
+        match self.transaction_data {
+            Some(ref xdr) => SorobanTransactionData::from_xdr_base64(xdr).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Decodes the diagnostic events emitted during the simulation.
+    pub fn decode_events(&self) -> Result<Vec<DiagnosticEvent>, XdrError> {
+        self.events
+            .iter()
+            .map(|xdr| DiagnosticEvent::from_xdr_base64(xdr))
+            .collect()
+    }
+}
+
+/// The result of a single host function invocation during a simulation.
+#[derive(Deserialize, Debug, Clone)]
+pub struct SimulateResult {
+    xdr: String,
+    #[serde(default)]
+    auth: Vec<String>,
+}
+
+impl SimulateResult {
+    /// The base64 encoded `SCVal` the invocation returned.
+    pub fn xdr(&self) -> &str {
+        &self.xdr
+    }
+
+    /// The base64 encoded `SorobanAuthorizationEntry` values that the
+    /// invocation requires.
+    pub fn auth(&self) -> &Vec<String> {
+        &self.auth
+    }
+
+    /// Decodes the value the invocation returned.
+    pub fn decode_xdr(&self) -> Result<ScVal, XdrError> {
+        ScVal::from_xdr_base64(&self.xdr)
+    }
+}
+
+/// The response to submitting a transaction.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SendTransaction {
+    hash: String,
+    status: String,
+    latest_ledger: u32,
+    error_result_xdr: Option<String>,
+}
+
+impl SendTransaction {
+    /// The hash of the transaction.
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    /// Whether the transaction was accepted, one of `PENDING`, `DUPLICATE`,
+    /// `TRY_AGAIN_LATER` or `ERROR`.
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    /// The latest ledger when the transaction was received.
+    pub fn latest_ledger(&self) -> u32 {
+        self.latest_ledger
+    }
+
+    /// The base64 encoded `TransactionResult` when the status is `ERROR`.
+    pub fn error_result_xdr(&self) -> Option<&str> {
+        self.error_result_xdr.as_ref().map(|s| &**s)
+    }
+}
+
+/// The status of a submitted transaction.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+    status: String,
+    latest_ledger: u32,
+    ledger: Option<u32>,
+    envelope_xdr: Option<String>,
+    result_xdr: Option<String>,
+    result_meta_xdr: Option<String>,
+}
+
+impl Transaction {
+    /// One of `SUCCESS`, `FAILED` or `NOT_FOUND`.
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    /// The latest ledger when the status was looked up.
+    pub fn latest_ledger(&self) -> u32 {
+        self.latest_ledger
+    }
+
+    /// The ledger the transaction was included in, once it has been.
+    pub fn ledger(&self) -> Option<u32> {
+        self.ledger
+    }
+
+    /// The base64 encoded `TransactionEnvelope`.
+    pub fn envelope_xdr(&self) -> Option<&str> {
+        self.envelope_xdr.as_ref().map(|s| &**s)
+    }
+
+    /// The base64 encoded `TransactionResult`.
+    pub fn result_xdr(&self) -> Option<&str> {
+        self.result_xdr.as_ref().map(|s| &**s)
+    }
+
+    /// The base64 encoded `TransactionMeta`.
+    pub fn result_meta_xdr(&self) -> Option<&str> {
+        self.result_meta_xdr.as_ref().map(|s| &**s)
+    }
+}
+
+/// Which contract events to fetch.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EventsRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_ledger: Option<u32>,
+    filters: Vec<EventFilter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pagination: Option<Pagination>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+struct Pagination {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<u32>,
+}
+
+impl EventsRequest {
+    /// Fetches events starting at the given ledger.
+    pub fn new(start_ledger: u32) -> EventsRequest {
+        EventsRequest {
+            start_ledger: Some(start_ledger),
+            ..EventsRequest::default()
+        }
+    }
+
+    /// Fetches events following the cursor of an earlier request.
+    pub fn after(cursor: &str) -> EventsRequest {
+        EventsRequest {
+            pagination: Some(Pagination {
+                cursor: Some(cursor.to_string()),
+                limit: None,
+            }),
+            ..EventsRequest::default()
+        }
+    }
+
+    /// Only returns events matching at least one of the filters.
+    pub fn with_filter(mut self, filter: EventFilter) -> EventsRequest {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Limits the number of events returned.
+    pub fn with_limit(mut self, limit: u32) -> EventsRequest {
+        self.pagination
+            .get_or_insert_with(Pagination::default)
+            .limit = Some(limit);
+        self
+    }
+}
+
+/// Matches contract events by type, contract and topics.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EventFilter {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    kind: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    contract_ids: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    topics: Vec<Vec<String>>,
+}
+
+impl EventFilter {
+    /// Creates a filter that matches every event.
+    pub fn new() -> EventFilter {
+        EventFilter::default()
+    }
+
+    /// Only matches events of a type: `contract`, `system` or `diagnostic`.
+    pub fn with_type(mut self, kind: &str) -> EventFilter {
+        self.kind = Some(kind.to_string());
+        self
+    }
+
+    /// Only matches events emitted by the contract.
+    pub fn with_contract_id(mut self, contract_id: &str) -> EventFilter {
+        self.contract_ids.push(contract_id.to_string());
+        self
+    }
+
+    /// Only matches events whose topics match the segments. Each segment is a
+    /// base64 encoded `SCVal`, `*` to match any single topic or `**` to match
+    /// any number of trailing topics.
+    pub fn with_topic(mut self, segments: &[&str]) -> EventFilter {
+        self.topics
+            .push(segments.iter().map(|s| s.to_string()).collect());
+        self
+    }
+}
+
+/// A page of contract events.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Events {
+    latest_ledger: u32,
+    events: Vec<Event>,
+    cursor: Option<String>,
+}
+
+impl Events {
+    /// The latest ledger when the events were fetched.
+    pub fn latest_ledger(&self) -> u32 {
+        self.latest_ledger
+    }
+
+    /// The events in this page.
+    pub fn events(&self) -> &Vec<Event> {
+        &self.events
+    }
+
+    /// The cursor to fetch the following page with.
+    pub fn cursor(&self) -> Option<&str> {
+        self.cursor.as_ref().map(|s| &**s)
+    }
+}
+
+/// An event emitted by a contract.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+    #[serde(rename = "type")]
+    kind: String,
+    ledger: u32,
+    ledger_closed_at: String,
+    contract_id: String,
+    id: String,
+    topic: Vec<String>,
+    value: String,
+    tx_hash: Option<String>,
+}
+
+impl Event {
+    /// The type of the event: `contract`, `system` or `diagnostic`.
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// The ledger the event was emitted in.
+    pub fn ledger(&self) -> u32 {
+        self.ledger
+    }
+
+    /// When the ledger closed, as an ISO 8601 string.
+    pub fn ledger_closed_at(&self) -> &str {
+        &self.ledger_closed_at
+    }
+
+    /// The strkey of the contract that emitted the event.
+    pub fn contract_id(&self) -> &str {
+        &self.contract_id
+    }
+
+    /// A unique identifier for the event.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The base64 encoded `SCVal` topics of the event.
+    pub fn topic(&self) -> &Vec<String> {
+        &self.topic
+    }
+
+    /// The base64 encoded `SCVal` body of the event.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// The hash of the transaction that emitted the event.
+    pub fn tx_hash(&self) -> Option<&str> {
+        self.tx_hash.as_ref().map(|s| &**s)
+    }
+
+    /// Decodes the topics of the event.
+    pub fn decode_topic(&self) -> Result<Vec<ScVal>, XdrError> {
+        self.topic
+            .iter()
+            .map(|xdr| ScVal::from_xdr_base64(xdr))
+            .collect()
+    }
+
+    /// Decodes the body of the event.
+    pub fn decode_value(&self) -> Result<ScVal, XdrError> {
+        ScVal::from_xdr_base64(&self.value)
+    }
+}
+
+/// The current values of the requested ledger entries.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntries {
+    latest_ledger: u32,
+    #[serde(default)]
+    entries: Vec<LedgerEntry>,
+}
+
+impl LedgerEntries {
+    /// The latest ledger when the entries were read.
+    pub fn latest_ledger(&self) -> u32 {
+        self.latest_ledger
+    }
+
+    /// The entries that exist. Keys without an entry are left out.
+    pub fn entries(&self) -> &Vec<LedgerEntry> {
+        &self.entries
+    }
+}
+
+/// A single ledger entry.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntry {
+    key: String,
+    xdr: String,
+    last_modified_ledger_seq: u32,
+    live_until_ledger_seq: Option<u32>,
+}
+
+impl LedgerEntry {
+    /// The base64 encoded `LedgerKey` of the entry.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The base64 encoded `LedgerEntryData` of the entry.
+    pub fn xdr(&self) -> &str {
+        &self.xdr
+    }
+
+    /// Decodes the entry's data.
+    pub fn decode_xdr(&self) -> Result<LedgerEntryData, XdrError> {
+        LedgerEntryData::from_xdr_base64(&self.xdr)
+    }
+
+    /// The ledger the entry was last changed in.
+    pub fn last_modified_ledger_seq(&self) -> u32 {
+        self.last_modified_ledger_seq
+    }
+
+    /// The last ledger the entry is live for, for contract data and code.
+    pub fn live_until_ledger_seq(&self) -> Option<u32> {
+        self.live_until_ledger_seq
+    }
+}
+
+#[cfg(test)]
+mod soroban_types_tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn it_serializes_an_events_request() {
+        let request = EventsRequest::new(100)
+            .with_filter(
+                EventFilter::new()
+                    .with_type("contract")
+                    .with_contract_id("CABC")
+                    .with_topic(&["AAAADwAAAAh0cmFuc2Zlcg==", "*"]),
+            )
+            .with_limit(10);
+        assert_eq!(
+            serde_json::to_string(&request).unwrap(),
+            r#"{"startLedger":100,"filters":[{"type":"contract","contractIds":["CABC"],"topics":[["AAAADwAAAAh0cmFuc2Zlcg==","*"]]}],"pagination":{"limit":10}}"#
+        );
+    }
+
+    #[test]
+    fn it_serializes_a_follow_up_events_request() {
+        let request = EventsRequest::after("0000000001-0000000000");
+        assert_eq!(
+            serde_json::to_string(&request).unwrap(),
+            r#"{"filters":[],"pagination":{"cursor":"0000000001-0000000000"}}"#
+        );
+    }
+
+    #[test]
+    fn it_parses_events() {
+        let json = r#"{
+            "latestLedger": 200,
+            "cursor": "0000000150-0000000001",
+            "events": [{
+                "type": "contract",
+                "ledger": 150,
+                "ledgerClosedAt": "2024-01-01T00:00:00Z",
+                "contractId": "CABC",
+                "id": "0000000150-0000000001",
+                "pagingToken": "0000000150-0000000001",
+                "topic": ["AAAADwAAAAh0cmFuc2Zlcg=="],
+                "value": "AAAAAQ==",
+                "inSuccessfulContractCall": true,
+                "txHash": "abc"
+            }]
+        }"#;
+        let events: Events = serde_json::from_str(json).unwrap();
+        assert_eq!(events.latest_ledger(), 200);
+        assert_eq!(events.cursor(), Some("0000000150-0000000001"));
+        let event = &events.events()[0];
+        assert_eq!(event.kind(), "contract");
+        assert_eq!(event.contract_id(), "CABC");
+        assert_eq!(event.topic().len(), 1);
+        assert_eq!(event.tx_hash(), Some("abc"));
+        assert_eq!(
+            event.decode_topic().unwrap(),
+            vec![ScVal::Symbol("transfer".to_string())]
+        );
+        assert_eq!(event.decode_value().unwrap(), ScVal::Void);
+    }
+
+    #[test]
+    fn it_parses_a_simulation() {
+        let json = r#"{
+            "latestLedger": 1000,
+            "minResourceFee": "58181",
+            "transactionData": "AAAAAA==",
+            "results": [{ "auth": [], "xdr": "AAAAAQ==" }],
+            "events": []
+        }"#;
+        let simulation: SimulateTransaction = serde_json::from_str(json).unwrap();
+        assert_eq!(simulation.min_resource_fee(), Some("58181"));
+        assert_eq!(simulation.results()[0].xdr(), "AAAAAQ==");
+        assert_eq!(simulation.error(), None);
+        assert_eq!(simulation.results()[0].decode_xdr().unwrap(), ScVal::Void);
+        assert!(simulation.decode_events().unwrap().is_empty());
+        assert!(simulation.decode_transaction_data().is_err());
+    }
+
+    #[test]
+    fn it_parses_ledger_entries() {
+        let json = r#"{
+            "latestLedger": 1000,
+            "entries": [{
+                "key": "AAAAAA==",
+                "xdr": "AAAACasAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAD6A==",
+                "lastModifiedLedgerSeq": 990,
+                "liveUntilLedgerSeq": 2000
+            }]
+        }"#;
+        let entries: LedgerEntries = serde_json::from_str(json).unwrap();
+        assert_eq!(entries.entries()[0].last_modified_ledger_seq(), 990);
+        assert_eq!(entries.entries()[0].live_until_ledger_seq(), Some(2000));
+        match entries.entries()[0].decode_xdr().unwrap() {
+            LedgerEntryData::Ttl(ttl) => assert_eq!(ttl.live_until_ledger_seq(), 1000),
+            other => panic!("expected a ttl entry, got {:?}", other),
+        }
+    }
+}
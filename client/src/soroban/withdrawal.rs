@@ -0,0 +1,587 @@
+use super::channel::ChannelSubmitter;
+use super::sac::StellarAssetContract;
+use super::Client;
+use endpoint::account;
+use keys::Signer;
+use network::Network;
+use resources::Amount;
+use std::collections::VecDeque;
+use std::fmt;
+use std::thread;
+use std::time::Duration;
+use sync::Requester;
+
+/// The data entry that SEP-29 accounts set to say payments to them need a
+/// memo, and the value that turns it on.
+const MEMO_REQUIRED_KEY: &str = "config.memo_required";
+const MEMO_REQUIRED_VALUE: &str = "1";
+
+/// A payout waiting in a `WithdrawalQueue`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Withdrawal {
+    id: String,
+    destination: String,
+    amount: Amount,
+}
+
+impl Withdrawal {
+    /// A payout of `amount` to `destination`, an account or a contract.
+    /// The `id` is yours, and comes back with the payout's status.
+    pub fn new(id: &str, destination: &str, amount: Amount) -> Withdrawal {
+        Withdrawal {
+            id: id.to_string(),
+            destination: destination.to_string(),
+            amount,
+        }
+    }
+
+    /// The id the payout was queued with.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The `G...` or `C...` id that is paid.
+    pub fn destination(&self) -> &str {
+        &self.destination
+    }
+
+    /// The amount that is paid.
+    pub fn amount(&self) -> Amount {
+        self.amount
+    }
+}
+
+/// A check that every withdrawal has to pass before it is sent, such as a
+/// sanctions screening or a limit on how much a user can withdraw. Add
+/// checks with `WithdrawalQueue::with_check`.
+pub trait CheckWithdrawal: fmt::Debug {
+    /// Returns the reason to turn the withdrawal down, or `Ok` to let it
+    /// through.
+    fn check(&self, withdrawal: &Withdrawal) -> Result<(), String>;
+}
+
+/// Turns down withdrawals to accounts that require a memo, as set out in
+/// SEP-29. Payouts go out as contract transfers, which carry no memo, so
+/// such an account couldn't tell whose funds arrived. Withdrawals to
+/// contracts are let through, and so are those to accounts that can't be
+/// loaded from horizon only when `allow_unknown` is set.
+#[derive(Debug)]
+pub struct MemoRequired<C> {
+    client: C,
+    allow_unknown: bool,
+}
+
+impl<C> MemoRequired<C>
+where
+    C: Requester + fmt::Debug,
+{
+    /// Looks destination accounts up with `client`. Withdrawals to accounts
+    /// that can't be loaded are turned down.
+    pub fn new(client: C) -> MemoRequired<C> {
+        MemoRequired {
+            client,
+            allow_unknown: false,
+        }
+    }
+
+    /// Lets withdrawals through when their destination can't be loaded,
+    /// instead of turning them down.
+    pub fn allow_unknown(mut self) -> MemoRequired<C> {
+        self.allow_unknown = true;
+        self
+    }
+}
+
+impl<C> CheckWithdrawal for MemoRequired<C>
+where
+    C: Requester + fmt::Debug,
+{
+    fn check(&self, withdrawal: &Withdrawal) -> Result<(), String> {
+        if !withdrawal.destination.starts_with('G') {
+            return Ok(());
+        }
+        match self.client.request(account::Details::new(
+            withdrawal.destination.as_str().into(),
+        )) {
+            Ok(account) => match account.data().get(MEMO_REQUIRED_KEY) {
+                Some(value) if value.0 == MEMO_REQUIRED_VALUE => {
+                    Err("The destination requires a memo".to_string())
+                }
+                _ => Ok(()),
+            },
+            Err(_) if self.allow_unknown => Ok(()),
+            Err(err) => Err(format!("The destination could not be loaded: {}", err)),
+        }
+    }
+}
+
+/// Where a withdrawal ended up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WithdrawalStatus {
+    /// A check turned the withdrawal down, for the given reason. Nothing was
+    /// sent.
+    Rejected(String),
+    /// The payout was included in the ledger and succeeded.
+    Completed {
+        /// The hash of the transaction that paid out.
+        hash: String,
+        /// The ledger the transaction was included in.
+        ledger: Option<u32>,
+    },
+    /// The payout could not be sent, or was included in the ledger and
+    /// failed. Nothing was paid.
+    Failed(String),
+    /// The payout was sent but hadn't been included in a ledger by the time
+    /// the queue stopped waiting. It may still be, so look the hash up
+    /// before paying it again.
+    Unconfirmed(String),
+}
+
+/// A withdrawal along with where it ended up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WithdrawalOutcome {
+    withdrawal: Withdrawal,
+    status: WithdrawalStatus,
+}
+
+impl WithdrawalOutcome {
+    /// The withdrawal.
+    pub fn withdrawal(&self) -> &Withdrawal {
+        &self.withdrawal
+    }
+
+    /// Where it ended up.
+    pub fn status(&self) -> &WithdrawalStatus {
+        &self.status
+    }
+}
+
+/// Pays out queued withdrawals of one asset through a `ChannelSubmitter`.
+///
+/// Each withdrawal is a transfer on the asset's stellar asset contract from
+/// the paying account. Contract transactions hold a single operation, so
+/// the queue batches withdrawals by sending as many at once as there are
+/// channels, one through each, and then waits for all of them to be
+/// included in the ledger before sending the next batch. Every check given
+/// to `with_check` is run before a withdrawal is sent.
+///
+/// This is only available with the `keys` feature enabled.
+///
+/// ## Example
+///
+/// ```no_run
+/// use stellar_client::keys::Keypair;
+/// use stellar_client::resources::{Amount, AssetIdentifier};
+/// use stellar_client::soroban::{
+///     ChannelSubmitter, Client, MemoRequired, StellarAssetContract, Withdrawal, WithdrawalQueue,
+/// };
+/// use stellar_client::sync;
+/// use stellar_client::Network;
+///
+/// let rpc = Client::new("https://soroban-testnet.stellar.org").unwrap();
+/// let horizon = sync::Client::horizon_test().unwrap();
+/// let network = Network::test();
+/// let hot_wallet = Keypair::from_secret_seed(
+///     "SAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6NKI",
+/// ).unwrap();
+/// let submitter = ChannelSubmitter::new(vec![
+///     (Keypair::random().unwrap(), 30232549674450945),
+///     (Keypair::random().unwrap(), 30232549674450950),
+/// ]);
+/// let lumens = StellarAssetContract::new(AssetIdentifier::native(), &network).unwrap();
+///
+/// let mut queue = WithdrawalQueue::new(lumens, network).with_check(MemoRequired::new(horizon));
+/// queue.push(Withdrawal::new(
+///     "payout-1",
+///     "GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM",
+///     Amount::new(10_000_000),
+/// ));
+/// for outcome in queue.drain(&rpc, &submitter, &hot_wallet) {
+///     println!("{}: {:?}", outcome.withdrawal().id(), outcome.status());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct WithdrawalQueue {
+    contract: StellarAssetContract,
+    network: Network,
+    checks: Vec<Box<dyn CheckWithdrawal>>,
+    queue: VecDeque<Withdrawal>,
+    max_attempts: u32,
+    poll_interval: Duration,
+    max_polls: u32,
+}
+
+impl WithdrawalQueue {
+    /// An empty queue paying out through `contract` on `network`.
+    pub fn new(contract: StellarAssetContract, network: Network) -> WithdrawalQueue {
+        WithdrawalQueue {
+            contract,
+            network,
+            checks: Vec::new(),
+            queue: VecDeque::new(),
+            max_attempts: 3,
+            poll_interval: Duration::from_secs(1),
+            max_polls: 30,
+        }
+    }
+
+    /// Runs `check` on every withdrawal before it is sent. Checks run in the
+    /// order they were added, and the first to turn a withdrawal down stops
+    /// it.
+    pub fn with_check<K>(mut self, check: K) -> WithdrawalQueue
+    where
+        K: CheckWithdrawal + 'static,
+    {
+        self.checks.push(Box::new(check));
+        self
+    }
+
+    /// Sends a withdrawal up to `max_attempts` times while the rpc server is
+    /// too busy to take it. Defaults to 3.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> WithdrawalQueue {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Looks up the transactions of a batch every `interval`, up to
+    /// `max_polls` times, while waiting for them to be included in the
+    /// ledger. Defaults to every second, 30 times.
+    pub fn with_polling(mut self, interval: Duration, max_polls: u32) -> WithdrawalQueue {
+        self.poll_interval = interval;
+        self.max_polls = max_polls;
+        self
+    }
+
+    /// Adds a withdrawal to the back of the queue.
+    pub fn push(&mut self, withdrawal: Withdrawal) {
+        self.queue.push_back(withdrawal);
+    }
+
+    /// The number of withdrawals waiting in the queue.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Whether no withdrawals are waiting.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Pays out every queued withdrawal from the account `source` signs
+    /// for, and reports where each one ended up, in the order they were
+    /// queued.
+    pub fn drain<S>(
+        &mut self,
+        client: &Client,
+        submitter: &ChannelSubmitter,
+        source: &S,
+    ) -> Vec<WithdrawalOutcome>
+    where
+        S: Signer + ?Sized,
+    {
+        let mut outcomes = Vec::new();
+        while !self.queue.is_empty() {
+            let size = submitter.size().min(self.queue.len());
+            let batch: Vec<_> = self.queue.drain(..size).collect();
+            outcomes.extend(self.send_batch(client, submitter, source, batch));
+        }
+        outcomes
+    }
+
+    fn send_batch<S>(
+        &self,
+        client: &Client,
+        submitter: &ChannelSubmitter,
+        source: &S,
+        batch: Vec<Withdrawal>,
+    ) -> Vec<WithdrawalOutcome>
+    where
+        S: Signer + ?Sized,
+    {
+        let statuses: Vec<_> = batch
+            .iter()
+            .map(|withdrawal| match self.check(withdrawal) {
+                Err(reason) => WithdrawalStatus::Rejected(reason),
+                Ok(()) => self.send(client, submitter, source, withdrawal),
+            })
+            .collect();
+        let statuses = self.wait(client, statuses);
+        batch
+            .into_iter()
+            .zip(statuses)
+            .map(|(withdrawal, status)| WithdrawalOutcome { withdrawal, status })
+            .collect()
+    }
+
+    fn check(&self, withdrawal: &Withdrawal) -> Result<(), String> {
+        self.checks
+            .iter()
+            .try_for_each(|check| check.check(withdrawal))
+    }
+
+    // Sends the withdrawal, coming back with it `Unconfirmed` once the rpc
+    // server has taken it.
+    fn send<S>(
+        &self,
+        client: &Client,
+        submitter: &ChannelSubmitter,
+        source: &S,
+        withdrawal: &Withdrawal,
+    ) -> WithdrawalStatus
+    where
+        S: Signer + ?Sized,
+    {
+        let builder = self.contract.transfer(
+            source.account_id(),
+            0,
+            source.account_id(),
+            &withdrawal.destination,
+            withdrawal.amount,
+        );
+        for _ in 0..self.max_attempts {
+            let sent = match submitter.submit(client, &self.network, builder.clone(), source) {
+                Ok(sent) => sent,
+                Err(err) => return WithdrawalStatus::Failed(err.to_string()),
+            };
+            match sent.status() {
+                "PENDING" | "DUPLICATE" => {
+                    return WithdrawalStatus::Unconfirmed(sent.hash().to_string())
+                }
+                "TRY_AGAIN_LATER" => continue,
+                status => {
+                    return WithdrawalStatus::Failed(format!("The transaction was {}", status))
+                }
+            }
+        }
+        WithdrawalStatus::Failed("The rpc server was too busy to take the transaction".to_string())
+    }
+
+    // Looks up the sent transactions until each is in the ledger or the
+    // queue gives up on it.
+    fn wait(&self, client: &Client, mut statuses: Vec<WithdrawalStatus>) -> Vec<WithdrawalStatus> {
+        for poll in 0..self.max_polls {
+            if poll > 0 {
+                thread::sleep(self.poll_interval);
+            }
+            let mut waiting = false;
+            for status in &mut statuses {
+                let hash = match *status {
+                    WithdrawalStatus::Unconfirmed(ref hash) => hash.clone(),
+                    _ => continue,
+                };
+                match client.get_transaction(&hash) {
+                    Ok(ref transaction) if transaction.status() == "SUCCESS" => {
+                        *status = WithdrawalStatus::Completed {
+                            hash,
+                            ledger: transaction.ledger(),
+                        }
+                    }
+                    Ok(ref transaction) if transaction.status() == "FAILED" => {
+                        *status = WithdrawalStatus::Failed(format!(
+                            "The transaction {} failed in the ledger",
+                            hash
+                        ))
+                    }
+                    // Not in a ledger yet, or the lookup failed and can be
+                    // tried again.
+                    _ => waiting = true,
+                }
+            }
+            if !waiting {
+                break;
+            }
+        }
+        statuses
+    }
+}
+
+#[cfg(test)]
+mod withdrawal_queue_tests {
+    use super::*;
+    use keys::Keypair;
+    use resources::AssetIdentifier;
+    use test::{MockClient, ReceivedRequest, TestResponse, TestServer};
+
+    const SOURCE: &str = "SAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6NKI";
+    const DESTINATION: &str = "GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM";
+    const SIMULATION: &str = r#"{"jsonrpc":"2.0","id":1,"result":{"latestLedger":1000,"minResourceFee":"0","transactionData":"AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=","results":[]}}"#;
+
+    // Answers each rpc call in turn on a local port.
+    fn serve(bodies: Vec<String>) -> TestServer {
+        TestServer::new(bodies.iter().map(|body| TestResponse::json(body)).collect())
+    }
+
+    // The rpc method a request called.
+    fn method(request: &ReceivedRequest) -> String {
+        let body = request.body();
+        let start = body.find(r#""method":""#).unwrap() + 10;
+        let end = start + body[start..].find('"').unwrap();
+        body[start..end].to_string()
+    }
+
+    fn sent(status: &str, hash: &str) -> String {
+        format!(
+            r#"{{"jsonrpc":"2.0","id":2,"result":{{"hash":"{}","status":"{}","latestLedger":1000,"latestLedgerCloseTime":"1700086333"}}}}"#,
+            hash, status
+        )
+    }
+
+    fn transaction(status: &str) -> String {
+        format!(
+            r#"{{"jsonrpc":"2.0","id":3,"result":{{"status":"{}","latestLedger":1001,"ledger":1001}}}}"#,
+            status
+        )
+    }
+
+    fn queue() -> WithdrawalQueue {
+        let network = Network::test();
+        let lumens = StellarAssetContract::new(AssetIdentifier::native(), &network).unwrap();
+        WithdrawalQueue::new(lumens, network).with_polling(Duration::from_millis(1), 3)
+    }
+
+    fn withdrawal(id: &str) -> Withdrawal {
+        Withdrawal::new(id, DESTINATION, Amount::new(10_000_000))
+    }
+
+    #[derive(Debug)]
+    struct Limit(Amount);
+
+    impl CheckWithdrawal for Limit {
+        fn check(&self, withdrawal: &Withdrawal) -> Result<(), String> {
+            if withdrawal.amount().to_stroops() > self.0.to_stroops() {
+                Err("Over the limit".to_string())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[test]
+    fn it_pays_out_a_batch_through_the_channels() {
+        let source = Keypair::from_secret_seed(SOURCE).unwrap();
+        let submitter = ChannelSubmitter::new(vec![
+            (Keypair::random().unwrap(), 1),
+            (Keypair::random().unwrap(), 1),
+        ]);
+        let server = serve(vec![
+            SIMULATION.to_string(),
+            sent("PENDING", "aaa"),
+            SIMULATION.to_string(),
+            sent("TRY_AGAIN_LATER", "bbb"),
+            SIMULATION.to_string(),
+            sent("PENDING", "bbb"),
+            transaction("SUCCESS"),
+            transaction("NOT_FOUND"),
+            transaction("FAILED"),
+        ]);
+        let client = Client::new(server.uri()).unwrap();
+        let mut queue = queue();
+        queue.push(withdrawal("one"));
+        queue.push(withdrawal("two"));
+        let outcomes = queue.drain(&client, &submitter, &source);
+        assert!(queue.is_empty());
+
+        assert_eq!(outcomes[0].withdrawal().id(), "one");
+        assert_eq!(
+            outcomes[0].status(),
+            &WithdrawalStatus::Completed {
+                hash: "aaa".to_string(),
+                ledger: Some(1001),
+            }
+        );
+        assert_eq!(outcomes[1].withdrawal().id(), "two");
+        match *outcomes[1].status() {
+            WithdrawalStatus::Failed(ref reason) => assert!(reason.contains("bbb")),
+            ref status => panic!("expected the payout to fail, got {:?}", status),
+        }
+        let methods: Vec<_> = server.requests().iter().map(method).collect();
+        assert_eq!(
+            &methods[5..],
+            &[
+                "sendTransaction",
+                "getTransaction",
+                "getTransaction",
+                "getTransaction"
+            ]
+        );
+    }
+
+    #[test]
+    fn it_rejects_withdrawals_a_check_turns_down() {
+        let source = Keypair::from_secret_seed(SOURCE).unwrap();
+        let submitter = ChannelSubmitter::new(vec![(Keypair::random().unwrap(), 1)]);
+        // Nothing is listening, so sending anything would fail.
+        let client = Client::new(&TestServer::unreachable()).unwrap();
+        let mut queue = queue().with_check(Limit(Amount::new(1)));
+        queue.push(withdrawal("one"));
+        assert_eq!(queue.len(), 1);
+        let outcomes = queue.drain(&client, &submitter, &source);
+        assert_eq!(
+            outcomes[0].status(),
+            &WithdrawalStatus::Rejected("Over the limit".to_string())
+        );
+    }
+
+    #[test]
+    fn it_reports_payouts_that_never_reach_a_ledger() {
+        let source = Keypair::from_secret_seed(SOURCE).unwrap();
+        let submitter = ChannelSubmitter::new(vec![(Keypair::random().unwrap(), 1)]);
+        let server = serve(vec![
+            SIMULATION.to_string(),
+            sent("PENDING", "aaa"),
+            transaction("NOT_FOUND"),
+            transaction("NOT_FOUND"),
+            transaction("NOT_FOUND"),
+        ]);
+        let client = Client::new(server.uri()).unwrap();
+        let mut queue = queue();
+        queue.push(withdrawal("one"));
+        let outcomes = queue.drain(&client, &submitter, &source);
+        assert_eq!(
+            outcomes[0].status(),
+            &WithdrawalStatus::Unconfirmed("aaa".to_string())
+        );
+    }
+
+    #[test]
+    fn it_turns_down_accounts_that_require_a_memo() {
+        let account = include_str!("../../fixtures/account.json");
+        let required = MockClient::new().with_json(
+            "/accounts/*",
+            &account.replace(
+                r#""welcome": "aGVsbG8gd29ybGQ=""#,
+                r#""config.memo_required": "MQ==""#,
+            ),
+        );
+        assert!(MemoRequired::new(&required)
+            .check(&withdrawal("one"))
+            .is_err());
+
+        let plain = MockClient::new().with_json("/accounts/*", account);
+        assert!(MemoRequired::new(&plain).check(&withdrawal("one")).is_ok());
+
+        let contract = Withdrawal::new(
+            "two",
+            "CBQHNAXSI55GX2GN6D67GK7BHVPSLJUGZQEU7WJ5LKR5PNUCGLIMAO4K",
+            Amount::new(1),
+        );
+        assert!(MemoRequired::new(&plain).check(&contract).is_ok());
+        assert_eq!(plain.requests().len(), 1);
+    }
+
+    #[test]
+    fn it_turns_down_accounts_that_cannot_be_loaded_unless_allowed() {
+        let missing = MockClient::new().with_response(
+            "/accounts/*",
+            404,
+            include_str!("../../fixtures/before_history_error.json"),
+        );
+        assert!(MemoRequired::new(&missing)
+            .check(&withdrawal("one"))
+            .is_err());
+        assert!(MemoRequired::new(&missing)
+            .allow_unknown()
+            .check(&withdrawal("one"))
+            .is_ok());
+    }
+}
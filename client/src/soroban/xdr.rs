@@ -0,0 +1,1175 @@
+//! Decoding for the XDR structures that soroban rpc hands back as base64:
+//! contract values, contract events, transaction footprints and ledger keys.
+//! Contract values and ledger keys can be encoded too, to pass them as
+//! arguments.
+//!
+//! Account and contract addresses are converted to their `G...` and `C...`
+//! string forms and hashes to lowercase hex, the same way horizon presents
+//! them, so decoded values sit comfortably next to the horizon resources.
+//!
+//! ## Example
+//!
+//! ```
+//! use stellar_client::soroban::ScVal;
+//!
+//! let value = ScVal::from_xdr_base64("AAAADwAAAAh0cmFuc2Zlcg==").unwrap();
+//! assert_eq!(value.as_str(), Some("transfer"));
+//! ```
+use base64;
+use strkey;
+pub use xdr::XdrError;
+pub(super) use xdr::{asset_code, Reader, Result, Writer};
+
+/// A soroban contract value, the `SCVal` XDR union.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScVal {
+    /// A boolean.
+    Bool(bool),
+    /// The absence of a value.
+    Void,
+    /// An error raised by a contract or the host.
+    Error(ScError),
+    /// An unsigned 32 bit integer.
+    U32(u32),
+    /// A signed 32 bit integer.
+    I32(i32),
+    /// An unsigned 64 bit integer.
+    U64(u64),
+    /// A signed 64 bit integer.
+    I64(i64),
+    /// Seconds since the unix epoch.
+    Timepoint(u64),
+    /// A span of seconds.
+    Duration(u64),
+    /// An unsigned 128 bit integer.
+    U128(u128),
+    /// A signed 128 bit integer. Token amounts are usually this type.
+    I128(i128),
+    /// An unsigned 256 bit integer as big endian bytes.
+    U256([u8; 32]),
+    /// A signed 256 bit integer as big endian bytes.
+    I256([u8; 32]),
+    /// Arbitrary bytes.
+    Bytes(Vec<u8>),
+    /// A string.
+    String(String),
+    /// A symbol, a short identifier such as a function or event name.
+    Symbol(String),
+    /// A vector of values. `None` when the host left it out.
+    Vec(Option<Vec<ScVal>>),
+    /// A map of keys to values. `None` when the host left it out.
+    Map(Option<Vec<(ScVal, ScVal)>>),
+    /// An account or contract address.
+    Address(ScAddress),
+    /// The instance of a contract along with its instance storage.
+    ContractInstance(ContractInstance),
+    /// The key of a contract's instance ledger entry.
+    LedgerKeyContractInstance,
+    /// The key of a nonce ledger entry.
+    LedgerKeyNonce(i64),
+}
+
+impl ScVal {
+    /// Decodes a value from base64 encoded XDR.
+    pub fn from_xdr_base64(xdr: &str) -> Result<ScVal> {
+        from_base64(xdr)
+    }
+
+    /// Encodes the value as base64 XDR. Fails if an address or hash in the
+    /// value is malformed.
+    pub fn to_xdr_base64(&self) -> Result<String> {
+        to_base64(self)
+    }
+
+    /// The value as a bool, if it is one.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            ScVal::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value as a u32, if it is one.
+    pub fn as_u32(&self) -> Option<u32> {
+        match *self {
+            ScVal::U32(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value as an i32, if it is one.
+    pub fn as_i32(&self) -> Option<i32> {
+        match *self {
+            ScVal::I32(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value as a u64. Timepoints and durations are included.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            ScVal::U64(value) | ScVal::Timepoint(value) | ScVal::Duration(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value as an i64, if it is one.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            ScVal::I64(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value as a u128, if it is one.
+    pub fn as_u128(&self) -> Option<u128> {
+        match *self {
+            ScVal::U128(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value as an i128, if it is one.
+    pub fn as_i128(&self) -> Option<i128> {
+        match *self {
+            ScVal::I128(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value as bytes, if it is a byte array.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match *self {
+            ScVal::Bytes(ref value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value as a str, if it is a string or a symbol.
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            ScVal::String(ref value) | ScVal::Symbol(ref value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The elements of a vector value.
+    pub fn as_vec(&self) -> Option<&[ScVal]> {
+        match *self {
+            ScVal::Vec(Some(ref value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The entries of a map value.
+    pub fn as_map(&self) -> Option<&[(ScVal, ScVal)]> {
+        match *self {
+            ScVal::Map(Some(ref value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value as an address, if it is one.
+    pub fn as_address(&self) -> Option<&ScAddress> {
+        match *self {
+            ScVal::Address(ref value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// An error value raised by a contract or the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScError {
+    /// An error code defined by the contract itself.
+    Contract(u32),
+    /// An error raised by the host. The first value is the error type, such
+    /// as wasm vm or storage, and the second is the error code.
+    Host(i32, i32),
+}
+
+/// The address of an account or a contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScAddress {
+    /// An account, as its `G...` public key.
+    Account(String),
+    /// A contract, as its `C...` contract id.
+    Contract(String),
+}
+
+impl ScAddress {
+    /// The address as a `G...` or `C...` string.
+    pub fn as_str(&self) -> &str {
+        match *self {
+            ScAddress::Account(ref address) | ScAddress::Contract(ref address) => address,
+        }
+    }
+}
+
+/// A contract instance: the code it runs and its instance storage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractInstance {
+    executable: ContractExecutable,
+    storage: Option<Vec<(ScVal, ScVal)>>,
+}
+
+impl ContractInstance {
+    /// The code the contract runs.
+    pub fn executable(&self) -> &ContractExecutable {
+        &self.executable
+    }
+
+    /// The contract's instance storage, if it has any.
+    pub fn storage(&self) -> Option<&[(ScVal, ScVal)]> {
+        self.storage.as_ref().map(|storage| &storage[..])
+    }
+}
+
+/// The code behind a contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContractExecutable {
+    /// Uploaded wasm, identified by the hex encoded hash of the code.
+    Wasm(String),
+    /// The built in stellar asset contract.
+    StellarAsset,
+}
+
+/// Where an event came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractEventType {
+    /// Emitted by the host itself.
+    System,
+    /// Emitted by a contract.
+    Contract,
+    /// Emitted for debugging. These are only returned from simulations and
+    /// are not part of consensus.
+    Diagnostic,
+}
+
+/// An event emitted during a contract invocation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractEvent {
+    contract_id: Option<String>,
+    event_type: ContractEventType,
+    topics: Vec<ScVal>,
+    data: ScVal,
+}
+
+impl ContractEvent {
+    /// Decodes an event from base64 encoded XDR.
+    pub fn from_xdr_base64(xdr: &str) -> Result<ContractEvent> {
+        from_base64(xdr)
+    }
+
+    /// The `C...` id of the contract that emitted the event, if any.
+    pub fn contract_id(&self) -> Option<&str> {
+        self.contract_id.as_ref().map(|s| &**s)
+    }
+
+    /// Where the event came from.
+    pub fn event_type(&self) -> ContractEventType {
+        self.event_type
+    }
+
+    /// The topics the event was published under.
+    pub fn topics(&self) -> &[ScVal] {
+        &self.topics
+    }
+
+    /// The event's payload.
+    pub fn data(&self) -> &ScVal {
+        &self.data
+    }
+}
+
+/// An event recorded while simulating or running a transaction, along with
+/// whether the call that emitted it succeeded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagnosticEvent {
+    in_successful_contract_call: bool,
+    event: ContractEvent,
+}
+
+impl DiagnosticEvent {
+    /// Decodes a diagnostic event from base64 encoded XDR.
+    pub fn from_xdr_base64(xdr: &str) -> Result<DiagnosticEvent> {
+        from_base64(xdr)
+    }
+
+    /// Whether the contract call that emitted the event succeeded.
+    pub fn in_successful_contract_call(&self) -> bool {
+        self.in_successful_contract_call
+    }
+
+    /// The event itself.
+    pub fn event(&self) -> &ContractEvent {
+        &self.event
+    }
+}
+
+/// The resources and fee a soroban transaction needs, as returned by a
+/// simulation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SorobanTransactionData {
+    footprint: LedgerFootprint,
+    instructions: u32,
+    read_bytes: u32,
+    write_bytes: u32,
+    resource_fee: i64,
+}
+
+impl SorobanTransactionData {
+    /// Decodes transaction data from base64 encoded XDR.
+    pub fn from_xdr_base64(xdr: &str) -> Result<SorobanTransactionData> {
+        from_base64(xdr)
+    }
+
+    /// The ledger entries the transaction reads and writes.
+    pub fn footprint(&self) -> &LedgerFootprint {
+        &self.footprint
+    }
+
+    /// The number of cpu instructions the transaction may use.
+    pub fn instructions(&self) -> u32 {
+        self.instructions
+    }
+
+    /// The number of bytes the transaction may read from the ledger.
+    pub fn read_bytes(&self) -> u32 {
+        self.read_bytes
+    }
+
+    /// The number of bytes the transaction may write to the ledger.
+    pub fn write_bytes(&self) -> u32 {
+        self.write_bytes
+    }
+
+    /// The fee in stroops charged for the resources, on top of the
+    /// inclusion fee.
+    pub fn resource_fee(&self) -> i64 {
+        self.resource_fee
+    }
+}
+
+/// The ledger entries a soroban transaction is allowed to touch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LedgerFootprint {
+    read_only: Vec<LedgerKey>,
+    read_write: Vec<LedgerKey>,
+}
+
+impl LedgerFootprint {
+    /// Entries the transaction only reads.
+    pub fn read_only(&self) -> &[LedgerKey] {
+        &self.read_only
+    }
+
+    /// Entries the transaction may change.
+    pub fn read_write(&self) -> &[LedgerKey] {
+        &self.read_write
+    }
+}
+
+/// Identifies a single ledger entry.
+///
+/// Keys can be encoded to ask soroban rpc for entries directly:
+///
+/// ```
+/// use stellar_client::soroban::LedgerKey;
+///
+/// let key = LedgerKey::Account(
+///     "GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM".to_string(),
+/// );
+/// let xdr = key.to_xdr_base64().unwrap();
+/// assert_eq!(LedgerKey::from_xdr_base64(&xdr).unwrap(), key);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LedgerKey {
+    /// An account, by its `G...` id.
+    Account(String),
+    /// A trust line, by account and asset.
+    Trustline(String, TrustLineAsset),
+    /// An offer, by seller and offer id.
+    Offer(String, i64),
+    /// A data entry, by account and name.
+    Data(String, String),
+    /// A claimable balance, by its hex encoded id.
+    ClaimableBalance(String),
+    /// A liquidity pool, by its hex encoded id.
+    LiquidityPool(String),
+    /// A contract data entry.
+    ContractData(ContractDataKey),
+    /// Uploaded contract code, by the hex encoded hash of the code.
+    ContractCode(String),
+    /// A network configuration setting, by its id.
+    ConfigSetting(i32),
+    /// The time to live of another entry, by the hex encoded hash of its key.
+    Ttl(String),
+}
+
+impl LedgerKey {
+    /// Decodes a key from base64 encoded XDR.
+    pub fn from_xdr_base64(xdr: &str) -> Result<LedgerKey> {
+        from_base64(xdr)
+    }
+
+    /// Encodes the key as base64 XDR. Fails if an address, hash or asset
+    /// code in the key is malformed.
+    pub fn to_xdr_base64(&self) -> Result<String> {
+        to_base64(self)
+    }
+}
+
+/// The asset of a trust line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrustLineAsset {
+    /// Lumens.
+    Native,
+    /// An asset with a code of up to 4 characters, and its issuer.
+    CreditAlphanum4(String, String),
+    /// An asset with a code of up to 12 characters, and its issuer.
+    CreditAlphanum12(String, String),
+    /// Shares of a liquidity pool, by the pool's hex encoded id.
+    PoolShare(String),
+}
+
+/// How long contract data lives for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractDataDurability {
+    /// Data that is deleted once its time to live runs out.
+    Temporary,
+    /// Data that is archived once its time to live runs out, and can be
+    /// restored.
+    Persistent,
+}
+
+/// The key of a contract data ledger entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractDataKey {
+    contract: ScAddress,
+    key: ScVal,
+    durability: ContractDataDurability,
+}
+
+impl ContractDataKey {
+    /// The contract that owns the data.
+    pub fn contract(&self) -> &ScAddress {
+        &self.contract
+    }
+
+    /// The key the contract stored the data under.
+    pub fn key(&self) -> &ScVal {
+        &self.key
+    }
+
+    /// How long the data lives for.
+    pub fn durability(&self) -> ContractDataDurability {
+        self.durability
+    }
+}
+
+/// Decodes a complete structure from base64, rejecting leftover bytes.
+pub(super) fn from_base64<T: Decode>(xdr: &str) -> Result<T> {
+    let mut reader = Reader::from_base64(xdr)?;
+    let value = T::decode(&mut reader)?;
+    if reader.remaining() == 0 {
+        Ok(value)
+    } else {
+        Err(XdrError::TrailingBytes)
+    }
+}
+
+/// Encodes a structure as base64.
+pub(super) fn to_base64<T: Encode>(value: &T) -> Result<String> {
+    let mut writer = Writer::new();
+    value.encode(&mut writer)?;
+    Ok(base64::encode(&writer.into_bytes()))
+}
+
+pub(super) trait Decode: Sized {
+    fn decode(r: &mut Reader) -> Result<Self>;
+}
+
+impl Decode for ScVal {
+    fn decode(r: &mut Reader) -> Result<ScVal> {
+        Ok(match r.i32()? {
+            0 => ScVal::Bool(r.bool()?),
+            1 => ScVal::Void,
+            2 => ScVal::Error(ScError::decode(r)?),
+            3 => ScVal::U32(r.u32()?),
+            4 => ScVal::I32(r.i32()?),
+            5 => ScVal::U64(r.u64()?),
+            6 => ScVal::I64(r.i64()?),
+            7 => ScVal::Timepoint(r.u64()?),
+            8 => ScVal::Duration(r.u64()?),
+            9 => ScVal::U128((u128::from(r.u64()?) << 64) | u128::from(r.u64()?)),
+            10 => ScVal::I128(((i128::from(r.i64()?)) << 64) | i128::from(r.u64()?)),
+            11 => ScVal::U256(r.fixed()?),
+            12 => ScVal::I256(r.fixed()?),
+            13 => ScVal::Bytes(r.opaque()?),
+            14 => ScVal::String(r.string()?),
+            15 => ScVal::Symbol(r.string()?),
+            16 => ScVal::Vec(r.nested(|r| r.optional(|r| r.vec(ScVal::decode)))?),
+            17 => ScVal::Map(r.nested(|r| r.optional(map))?),
+            18 => ScVal::Address(ScAddress::decode(r)?),
+            19 => ScVal::ContractInstance(ContractInstance::decode(r)?),
+            20 => ScVal::LedgerKeyContractInstance,
+            21 => ScVal::LedgerKeyNonce(r.i64()?),
+            other => return Err(XdrError::UnknownDiscriminant("SCVal", other)),
+        })
+    }
+}
+
+/// The entries of an `SCMap`.
+fn map(r: &mut Reader) -> Result<Vec<(ScVal, ScVal)>> {
+    r.vec(|r| Ok((ScVal::decode(r)?, ScVal::decode(r)?)))
+}
+
+impl Decode for ScError {
+    fn decode(r: &mut Reader) -> Result<ScError> {
+        Ok(match r.i32()? {
+            0 => ScError::Contract(r.u32()?),
+            kind @ 1..=9 => ScError::Host(kind, r.i32()?),
+            other => return Err(XdrError::UnknownDiscriminant("SCError", other)),
+        })
+    }
+}
+
+impl Decode for ScAddress {
+    fn decode(r: &mut Reader) -> Result<ScAddress> {
+        Ok(match r.i32()? {
+            0 => ScAddress::Account(account_id(r)?),
+            1 => ScAddress::Contract(strkey::encode(strkey::CONTRACT, &r.fixed::<[u8; 32]>()?)),
+            other => return Err(XdrError::UnknownDiscriminant("SCAddress", other)),
+        })
+    }
+}
+
+/// A `PublicKey` as its `G...` account id.
+pub(super) fn account_id(r: &mut Reader) -> Result<String> {
+    Ok(strkey::encode(strkey::ACCOUNT, &r.account_id()?))
+}
+
+impl Decode for ContractInstance {
+    fn decode(r: &mut Reader) -> Result<ContractInstance> {
+        let executable = match r.i32()? {
+            0 => ContractExecutable::Wasm(r.hash()?),
+            1 => ContractExecutable::StellarAsset,
+            other => return Err(XdrError::UnknownDiscriminant("ContractExecutable", other)),
+        };
+        let storage = r.nested(|r| r.optional(map))?;
+        Ok(ContractInstance {
+            executable,
+            storage,
+        })
+    }
+}
+
+impl Decode for ContractEvent {
+    fn decode(r: &mut Reader) -> Result<ContractEvent> {
+        r.extension_point()?;
+        let contract_id = if r.bool()? {
+            Some(strkey::encode(strkey::CONTRACT, &r.fixed::<[u8; 32]>()?))
+        } else {
+            None
+        };
+        let event_type = match r.i32()? {
+            0 => ContractEventType::System,
+            1 => ContractEventType::Contract,
+            2 => ContractEventType::Diagnostic,
+            other => return Err(XdrError::UnknownDiscriminant("ContractEventType", other)),
+        };
+        match r.i32()? {
+            0 => (),
+            other => return Err(XdrError::UnknownDiscriminant("ContractEvent body", other)),
+        }
+        Ok(ContractEvent {
+            contract_id,
+            event_type,
+            topics: r.vec(ScVal::decode)?,
+            data: ScVal::decode(r)?,
+        })
+    }
+}
+
+impl Decode for DiagnosticEvent {
+    fn decode(r: &mut Reader) -> Result<DiagnosticEvent> {
+        Ok(DiagnosticEvent {
+            in_successful_contract_call: r.bool()?,
+            event: ContractEvent::decode(r)?,
+        })
+    }
+}
+
+impl Decode for SorobanTransactionData {
+    fn decode(r: &mut Reader) -> Result<SorobanTransactionData> {
+        match r.i32()? {
+            0 => (),
+            // Protocol 23 lists archived entries that need restoring. They
+            // are part of the footprint already, so they are skipped.
+            1 => {
+                r.vec(|r| r.u32())?;
+            }
+            other => {
+                return Err(XdrError::UnknownDiscriminant(
+                    "SorobanTransactionData ext",
+                    other,
+                ))
+            }
+        }
+        Ok(SorobanTransactionData {
+            footprint: LedgerFootprint {
+                read_only: r.vec(LedgerKey::decode)?,
+                read_write: r.vec(LedgerKey::decode)?,
+            },
+            instructions: r.u32()?,
+            read_bytes: r.u32()?,
+            write_bytes: r.u32()?,
+            resource_fee: r.i64()?,
+        })
+    }
+}
+
+impl Decode for LedgerKey {
+    fn decode(r: &mut Reader) -> Result<LedgerKey> {
+        Ok(match r.i32()? {
+            0 => LedgerKey::Account(account_id(r)?),
+            1 => LedgerKey::Trustline(account_id(r)?, TrustLineAsset::decode(r)?),
+            2 => LedgerKey::Offer(account_id(r)?, r.i64()?),
+            3 => LedgerKey::Data(account_id(r)?, r.string()?),
+            4 => match r.i32()? {
+                0 => LedgerKey::ClaimableBalance(r.hash()?),
+                other => return Err(XdrError::UnknownDiscriminant("ClaimableBalanceID", other)),
+            },
+            5 => LedgerKey::LiquidityPool(r.hash()?),
+            6 => LedgerKey::ContractData(ContractDataKey::decode(r)?),
+            7 => LedgerKey::ContractCode(r.hash()?),
+            8 => LedgerKey::ConfigSetting(r.i32()?),
+            9 => LedgerKey::Ttl(r.hash()?),
+            other => return Err(XdrError::UnknownDiscriminant("LedgerKey", other)),
+        })
+    }
+}
+
+impl Decode for ContractDataKey {
+    fn decode(r: &mut Reader) -> Result<ContractDataKey> {
+        Ok(ContractDataKey {
+            contract: ScAddress::decode(r)?,
+            key: ScVal::decode(r)?,
+            durability: match r.i32()? {
+                0 => ContractDataDurability::Temporary,
+                1 => ContractDataDurability::Persistent,
+                other => {
+                    return Err(XdrError::UnknownDiscriminant(
+                        "ContractDataDurability",
+                        other,
+                    ))
+                }
+            },
+        })
+    }
+}
+
+impl Decode for TrustLineAsset {
+    fn decode(r: &mut Reader) -> Result<TrustLineAsset> {
+        Ok(match r.i32()? {
+            0 => TrustLineAsset::Native,
+            1 => {
+                let code = asset_code(&r.fixed::<[u8; 4]>()?)?;
+                TrustLineAsset::CreditAlphanum4(code, account_id(r)?)
+            }
+            2 => {
+                let code = asset_code(&r.fixed::<[u8; 12]>()?)?;
+                TrustLineAsset::CreditAlphanum12(code, account_id(r)?)
+            }
+            3 => TrustLineAsset::PoolShare(r.hash()?),
+            other => return Err(XdrError::UnknownDiscriminant("TrustLineAsset", other)),
+        })
+    }
+}
+
+pub(super) trait Encode {
+    fn encode(&self, w: &mut Writer) -> Result<()>;
+}
+
+impl<A: Encode, B: Encode> Encode for (A, B) {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        self.0.encode(w)?;
+        self.1.encode(w)
+    }
+}
+
+impl Encode for ScVal {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        match *self {
+            ScVal::Bool(value) => {
+                w.i32(0);
+                w.bool(value);
+            }
+            ScVal::Void => w.i32(1),
+            ScVal::Error(ref error) => {
+                w.i32(2);
+                error.encode(w)?;
+            }
+            ScVal::U32(value) => {
+                w.i32(3);
+                w.u32(value);
+            }
+            ScVal::I32(value) => {
+                w.i32(4);
+                w.i32(value);
+            }
+            ScVal::U64(value) => {
+                w.i32(5);
+                w.u64(value);
+            }
+            ScVal::I64(value) => {
+                w.i32(6);
+                w.i64(value);
+            }
+            ScVal::Timepoint(value) => {
+                w.i32(7);
+                w.u64(value);
+            }
+            ScVal::Duration(value) => {
+                w.i32(8);
+                w.u64(value);
+            }
+            ScVal::U128(value) => {
+                w.i32(9);
+                w.u64((value >> 64) as u64);
+                w.u64(value as u64);
+            }
+            ScVal::I128(value) => {
+                w.i32(10);
+                w.i64((value >> 64) as i64);
+                w.u64(value as u64);
+            }
+            ScVal::U256(ref value) => {
+                w.i32(11);
+                w.raw(value);
+            }
+            ScVal::I256(ref value) => {
+                w.i32(12);
+                w.raw(value);
+            }
+            ScVal::Bytes(ref value) => {
+                w.i32(13);
+                w.opaque(value);
+            }
+            ScVal::String(ref value) => {
+                w.i32(14);
+                w.opaque(value.as_bytes());
+            }
+            ScVal::Symbol(ref value) => {
+                w.i32(15);
+                w.opaque(value.as_bytes());
+            }
+            ScVal::Vec(ref values) => {
+                w.i32(16);
+                w.bool(values.is_some());
+                if let Some(ref values) = *values {
+                    w.vec(values, Encode::encode)?;
+                }
+            }
+            ScVal::Map(ref entries) => {
+                w.i32(17);
+                w.bool(entries.is_some());
+                if let Some(ref entries) = *entries {
+                    w.vec(entries, Encode::encode)?;
+                }
+            }
+            ScVal::Address(ref address) => {
+                w.i32(18);
+                address.encode(w)?;
+            }
+            ScVal::ContractInstance(ref instance) => {
+                w.i32(19);
+                instance.encode(w)?;
+            }
+            ScVal::LedgerKeyContractInstance => w.i32(20),
+            ScVal::LedgerKeyNonce(nonce) => {
+                w.i32(21);
+                w.i64(nonce);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Encode for ScError {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        match *self {
+            ScError::Contract(code) => {
+                w.i32(0);
+                w.u32(code);
+            }
+            ScError::Host(kind, code) => {
+                w.i32(kind);
+                w.i32(code);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Encode for ScAddress {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        match *self {
+            ScAddress::Account(ref account) => {
+                w.i32(0);
+                w.account_id(account)
+            }
+            ScAddress::Contract(ref contract) => {
+                w.i32(1);
+                let key =
+                    strkey::decode(strkey::CONTRACT, contract).ok_or(XdrError::InvalidAddress)?;
+                w.raw(&key);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Encode for ContractInstance {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        match self.executable {
+            ContractExecutable::Wasm(ref hash) => {
+                w.i32(0);
+                w.hash(hash)?;
+            }
+            ContractExecutable::StellarAsset => w.i32(1),
+        }
+        w.bool(self.storage.is_some());
+        if let Some(ref storage) = self.storage {
+            w.vec(storage, Encode::encode)?;
+        }
+        Ok(())
+    }
+}
+
+impl Encode for LedgerKey {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        match *self {
+            LedgerKey::Account(ref account) => {
+                w.i32(0);
+                w.account_id(account)?;
+            }
+            LedgerKey::Trustline(ref account, ref asset) => {
+                w.i32(1);
+                w.account_id(account)?;
+                asset.encode(w)?;
+            }
+            LedgerKey::Offer(ref seller, offer_id) => {
+                w.i32(2);
+                w.account_id(seller)?;
+                w.i64(offer_id);
+            }
+            LedgerKey::Data(ref account, ref name) => {
+                w.i32(3);
+                w.account_id(account)?;
+                w.opaque(name.as_bytes());
+            }
+            LedgerKey::ClaimableBalance(ref id) => {
+                w.i32(4);
+                w.i32(0);
+                w.hash(id)?;
+            }
+            LedgerKey::LiquidityPool(ref id) => {
+                w.i32(5);
+                w.hash(id)?;
+            }
+            LedgerKey::ContractData(ref key) => {
+                w.i32(6);
+                key.encode(w)?;
+            }
+            LedgerKey::ContractCode(ref hash) => {
+                w.i32(7);
+                w.hash(hash)?;
+            }
+            LedgerKey::ConfigSetting(id) => {
+                w.i32(8);
+                w.i32(id);
+            }
+            LedgerKey::Ttl(ref hash) => {
+                w.i32(9);
+                w.hash(hash)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Encode for ContractDataKey {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        self.contract.encode(w)?;
+        self.key.encode(w)?;
+        w.i32(match self.durability {
+            ContractDataDurability::Temporary => 0,
+            ContractDataDurability::Persistent => 1,
+        });
+        Ok(())
+    }
+}
+
+impl Encode for TrustLineAsset {
+    fn encode(&self, w: &mut Writer) -> Result<()> {
+        match *self {
+            TrustLineAsset::Native => w.i32(0),
+            TrustLineAsset::CreditAlphanum4(ref code, ref issuer) => {
+                w.i32(1);
+                w.asset_code(code, 4)?;
+                w.account_id(issuer)?;
+            }
+            TrustLineAsset::CreditAlphanum12(ref code, ref issuer) => {
+                w.i32(2);
+                w.asset_code(code, 12)?;
+                w.account_id(issuer)?;
+            }
+            TrustLineAsset::PoolShare(ref id) => {
+                w.i32(3);
+                w.hash(id)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod xdr_tests {
+    use super::*;
+
+    #[test]
+    fn it_decodes_scalar_values() {
+        assert_eq!(
+            ScVal::from_xdr_base64("AAAAAAAAAAE=").unwrap(),
+            ScVal::Bool(true)
+        );
+        assert_eq!(ScVal::from_xdr_base64("AAAAAQ==").unwrap(), ScVal::Void);
+        assert_eq!(
+            ScVal::from_xdr_base64("AAAAAwAAAAc=").unwrap().as_u32(),
+            Some(7)
+        );
+        assert_eq!(
+            ScVal::from_xdr_base64("AAAABP////8=").unwrap().as_i32(),
+            Some(-1)
+        );
+        assert_eq!(
+            ScVal::from_xdr_base64("AAAACgAAAAAAAAAAAAAAAAX14QA=")
+                .unwrap()
+                .as_i128(),
+            Some(100_000_000)
+        );
+        assert_eq!(
+            ScVal::from_xdr_base64("AAAACv////////////////////8=")
+                .unwrap()
+                .as_i128(),
+            Some(-1)
+        );
+    }
+
+    #[test]
+    fn it_decodes_strings_and_symbols() {
+        let value = ScVal::from_xdr_base64("AAAADwAAAAh0cmFuc2Zlcg==").unwrap();
+        assert_eq!(value, ScVal::Symbol("transfer".to_string()));
+        let value = ScVal::from_xdr_base64("AAAADgAAAAVoZWxsbwAAAA==").unwrap();
+        assert_eq!(value, ScVal::String("hello".to_string()));
+    }
+
+    #[test]
+    fn it_decodes_vectors_and_maps() {
+        // vec![sym("a"), u32(1)]
+        let value = ScVal::from_xdr_base64("AAAAEAAAAAEAAAACAAAADwAAAAFhAAAAAAAAAwAAAAE=").unwrap();
+        let elements = value.as_vec().unwrap();
+        assert_eq!(elements[0].as_str(), Some("a"));
+        assert_eq!(elements[1].as_u32(), Some(1));
+
+        // map { sym("a"): true }
+        let value = ScVal::from_xdr_base64("AAAAEQAAAAEAAAABAAAADwAAAAFhAAAAAAAAAAAAAAE=").unwrap();
+        let entries = value.as_map().unwrap();
+        assert_eq!(entries[0].0.as_str(), Some("a"));
+        assert_eq!(entries[0].1.as_bool(), Some(true));
+    }
+
+    #[test]
+    fn it_decodes_addresses_to_strkeys() {
+        let value =
+            ScVal::from_xdr_base64("AAAAEgAAAAAAAAAA7ZE4kIuGUR/IliD7Y99e6LXaXUSvjC+mPzdv4Kyaw+4=")
+                .unwrap();
+        assert_eq!(
+            value.as_address(),
+            Some(&ScAddress::Account(
+                "GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM".to_string()
+            ))
+        );
+
+        let value =
+            ScVal::from_xdr_base64("AAAAEgAAAAFgdoLyR3pr6M3w/fMr4T1fJaaGzAlP2T1ao9e2gjLQwA==")
+                .unwrap();
+        assert_eq!(
+            value.as_address().unwrap().as_str(),
+            "CBQHNAXSI55GX2GN6D67GK7BHVPSLJUGZQEU7WJ5LKR5PNUCGLIMAO4K"
+        );
+    }
+
+    #[test]
+    fn it_decodes_a_contract_event() {
+        let event = ContractEvent::from_xdr_base64(
+            "AAAAAAAAAAFgdoLyR3pr6M3w/fMr4T1fJaaGzAlP2T1ao9e2gjLQwAAAAAEAAAAAAAAAAQAAAA8AAAAIdHJhbnNmZXIAAAAKAAAAAAAAAAAAAAAABfXhAA==",
+        ).unwrap();
+        assert_eq!(
+            event.contract_id(),
+            Some("CBQHNAXSI55GX2GN6D67GK7BHVPSLJUGZQEU7WJ5LKR5PNUCGLIMAO4K")
+        );
+        assert_eq!(event.event_type(), ContractEventType::Contract);
+        assert_eq!(event.topics()[0].as_str(), Some("transfer"));
+        assert_eq!(event.data().as_i128(), Some(100_000_000));
+    }
+
+    #[test]
+    fn it_decodes_a_footprint() {
+        let data = SorobanTransactionData::from_xdr_base64(
+            "AAAAAAAAAAEAAAAHAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABAAAABgAAAAFgdoLyR3pr6M3w/fMr4T1fJaaGzAlP2T1ao9e2gjLQwAAAABQAAAABAAGGoAAAAGQAAAAyAAAAAAAAJxA=",
+        ).unwrap();
+        assert_eq!(
+            data.footprint().read_only(),
+            &[LedgerKey::ContractCode(
+                "0000000000000000000000000000000000000000000000000000000000000000".to_string()
+            )][..]
+        );
+        match data.footprint().read_write()[0] {
+            LedgerKey::ContractData(ref key) => {
+                assert_eq!(key.key(), &ScVal::LedgerKeyContractInstance);
+                assert_eq!(key.durability(), ContractDataDurability::Persistent);
+            }
+            ref key => panic!("Unexpected ledger key {:?}", key),
+        }
+        assert_eq!(data.instructions(), 100_000);
+        assert_eq!(data.read_bytes(), 100);
+        assert_eq!(data.write_bytes(), 50);
+        assert_eq!(data.resource_fee(), 10_000);
+    }
+
+    #[test]
+    fn it_round_trips_values_through_xdr() {
+        let value = ScVal::Vec(Some(vec![
+            ScVal::Address(ScAddress::Account(
+                "GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM".to_string(),
+            )),
+            ScVal::Address(ScAddress::Contract(
+                "CBQHNAXSI55GX2GN6D67GK7BHVPSLJUGZQEU7WJ5LKR5PNUCGLIMAO4K".to_string(),
+            )),
+            ScVal::I128(-100_000_000),
+            ScVal::U128(u128::max_value()),
+            ScVal::Bytes(vec![1, 2, 3]),
+            ScVal::Map(Some(vec![(
+                ScVal::Symbol("a".to_string()),
+                ScVal::Error(ScError::Host(3, 2)),
+            )])),
+            ScVal::ContractInstance(ContractInstance {
+                executable: ContractExecutable::Wasm(
+                    "ab00000000000000000000000000000000000000000000000000000000000000".to_string(),
+                ),
+                storage: None,
+            }),
+            ScVal::Vec(None),
+        ]));
+        let xdr = value.to_xdr_base64().unwrap();
+        assert_eq!(ScVal::from_xdr_base64(&xdr).unwrap(), value);
+        assert_eq!(
+            ScVal::Symbol("transfer".to_string())
+                .to_xdr_base64()
+                .unwrap(),
+            "AAAADwAAAAh0cmFuc2Zlcg=="
+        );
+    }
+
+    #[test]
+    fn it_round_trips_ledger_keys_through_xdr() {
+        let account = "GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM".to_string();
+        let hash = "ab00000000000000000000000000000000000000000000000000000000000000".to_string();
+        let keys = vec![
+            LedgerKey::Account(account.clone()),
+            LedgerKey::Trustline(
+                account.clone(),
+                TrustLineAsset::CreditAlphanum12("LONGCODE".to_string(), account.clone()),
+            ),
+            LedgerKey::Offer(account.clone(), 42),
+            LedgerKey::Data(account.clone(), "config".to_string()),
+            LedgerKey::ClaimableBalance(hash.clone()),
+            LedgerKey::LiquidityPool(hash.clone()),
+            LedgerKey::ContractData(ContractDataKey {
+                contract: ScAddress::Contract(
+                    "CBQHNAXSI55GX2GN6D67GK7BHVPSLJUGZQEU7WJ5LKR5PNUCGLIMAO4K".to_string(),
+                ),
+                key: ScVal::LedgerKeyContractInstance,
+                durability: ContractDataDurability::Persistent,
+            }),
+            LedgerKey::ContractCode(hash.clone()),
+            LedgerKey::ConfigSetting(3),
+            LedgerKey::Ttl(hash),
+        ];
+        for key in keys {
+            let xdr = key.to_xdr_base64().unwrap();
+            assert_eq!(LedgerKey::from_xdr_base64(&xdr).unwrap(), key);
+        }
+        assert_eq!(
+            LedgerKey::Trustline(
+                account.clone(),
+                TrustLineAsset::CreditAlphanum4("TOOLONG".to_string(), account),
+            )
+            .to_xdr_base64()
+            .unwrap_err(),
+            XdrError::InvalidAssetCode
+        );
+    }
+
+    #[test]
+    fn it_rejects_malformed_addresses() {
+        let address = |s: &str| ScVal::Address(ScAddress::Account(s.to_string())).to_xdr_base64();
+        // A contract id where an account id belongs.
+        assert_eq!(
+            address("CBQHNAXSI55GX2GN6D67GK7BHVPSLJUGZQEU7WJ5LKR5PNUCGLIMAO4K").unwrap_err(),
+            XdrError::InvalidAddress
+        );
+        // A bad checksum.
+        assert_eq!(
+            address("GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYA").unwrap_err(),
+            XdrError::InvalidAddress
+        );
+        assert_eq!(address("GDWZ").unwrap_err(), XdrError::InvalidAddress);
+    }
+
+    #[test]
+    fn it_rejects_values_nested_too_deep() {
+        let nested = |depth: usize| {
+            let mut value = ScVal::Void;
+            for _ in 0..depth {
+                value = ScVal::Vec(Some(vec![value]));
+            }
+            value.to_xdr_base64().unwrap()
+        };
+        assert!(ScVal::from_xdr_base64(&nested(500)).is_ok());
+        assert_eq!(
+            ScVal::from_xdr_base64(&nested(501)).unwrap_err(),
+            XdrError::TooDeep
+        );
+    }
+
+    #[test]
+    fn it_rejects_bad_xdr() {
+        assert_eq!(ScVal::from_xdr_base64("!!").unwrap_err(), XdrError::Base64);
+        assert_eq!(
+            ScVal::from_xdr_base64("AAAAAw==").unwrap_err(),
+            XdrError::UnexpectedEnd
+        );
+        assert_eq!(
+            ScVal::from_xdr_base64("AAAAAQAAAAA=").unwrap_err(),
+            XdrError::TrailingBytes
+        );
+        assert_eq!(
+            ScVal::from_xdr_base64("AAAAYw==").unwrap_err(),
+            XdrError::UnknownDiscriminant("SCVal", 99)
+        );
+    }
+}
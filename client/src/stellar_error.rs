@@ -1,5 +1,7 @@
+use resources::FailureReasons;
 use serde::{de, Deserialize, Deserializer};
 use std::error::Error;
+use std::time::Duration;
 use std::{fmt, str::FromStr};
 
 /// A resource for the stellar horizon API specific error codes.
@@ -13,6 +15,9 @@ pub struct StellarError {
     status: u16,
     detail: String,
     instance: Option<String>,
+    request_id: Option<String>,
+    retry_after: Option<Duration>,
+    result_codes: Option<FailureReasons>,
 }
 
 #[derive(Deserialize)]
@@ -24,6 +29,19 @@ struct Intermediate {
     status: u16,
     detail: String,
     instance: Option<String>,
+    extras: Option<Extras>,
+}
+
+#[derive(Deserialize)]
+struct Extras {
+    result_codes: Option<ResultCodes>,
+}
+
+#[derive(Deserialize)]
+struct ResultCodes {
+    transaction: String,
+    #[serde(default)]
+    operations: Vec<String>,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
@@ -37,6 +55,7 @@ pub enum Kind {
     RateLimitExceeded,
     InternalServerError,
     StaleHistory,
+    Timeout,
     TransactionFailed,
     TransactionMalformed,
     UnknownError,
@@ -69,6 +88,12 @@ impl<'de> Deserialize<'de> for StellarError {
             status: int.status,
             detail: int.detail,
             instance: int.instance,
+            request_id: None,
+            retry_after: None,
+            result_codes: int
+                .extras
+                .and_then(|extras| extras.result_codes)
+                .map(|codes| FailureReasons::new(codes.transaction, codes.operations)),
         })
     }
 }
@@ -87,6 +112,7 @@ impl FromStr for Kind {
             "https://stellar.org/horizon-errors/rate_limit_exceeded" => Kind::RateLimitExceeded,
             "https://stellar.org/horizon-errors/internal_server_error" => Kind::InternalServerError,
             "https://stellar.org/horizon-errors/stale_history" => Kind::StaleHistory,
+            "https://stellar.org/horizon-errors/timeout" => Kind::Timeout,
             "https://stellar.org/horizon-errors/transaction_failed" => Kind::TransactionFailed,
             "https://stellar.org/horizon-errors/transaction_malformed" => {
                 Kind::TransactionMalformed
@@ -107,6 +133,53 @@ impl StellarError {
         &self.url
     }
 
+    /// The `X-Request-Id` the failed request was sent with, which horizon
+    /// logs alongside its own record of the request.
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    pub(crate) fn with_request_id(mut self, request_id: &str) -> Self {
+        self.request_id = Some(request_id.to_string());
+        self
+    }
+
+    /// How long horizon asked the client to wait before trying again, from
+    /// the `Retry-After` header of the response. Horizon sends it when the
+    /// client is rate limited.
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.retry_after
+    }
+
+    pub(crate) fn with_retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = Some(retry_after);
+        self
+    }
+
+    /// The transaction_failed error for a transaction that is known to have
+    /// failed other than by submitting it, such as one looked up after its
+    /// submission timed out, so that it can be handled like any other.
+    #[cfg(any(feature = "soroban", feature = "keys"))]
+    pub(crate) fn transaction_failed(result_codes: FailureReasons) -> Self {
+        StellarError {
+            kind: Kind::TransactionFailed,
+            url: "https://stellar.org/horizon-errors/transaction_failed".to_string(),
+            title: "Transaction Failed".to_string(),
+            status: 400,
+            detail: "The transaction failed when it was applied to a ledger.".to_string(),
+            instance: None,
+            request_id: None,
+            retry_after: None,
+            result_codes: Some(result_codes),
+        }
+    }
+
+    /// The result codes of a transaction that failed when it was submitted,
+    /// from the `extras` horizon sends with a transaction_failed error.
+    pub fn result_codes(&self) -> Option<&FailureReasons> {
+        self.result_codes.as_ref()
+    }
+
     /// If Horizon cannot understand a request due to invalid parameters, it will return a
     /// bad_request error. This is analogous to the HTTP 400 Error.
     ///
@@ -187,6 +260,16 @@ impl StellarError {
         self.kind == Kind::StaleHistory
     }
 
+    /// When Horizon submits a transaction to stellar core and it doesn't make it into a ledger
+    /// before Horizon stops waiting, Horizon returns a timeout error. This is analogous to the
+    /// HTTP 504 Error.
+    ///
+    /// The transaction may still be included in a later ledger, so look it up by its hash before
+    /// submitting it again.
+    pub fn is_timeout(&self) -> bool {
+        self.kind == Kind::Timeout
+    }
+
     /// This error occurs when a client submits a transaction that was well-formed but was not
     /// included into the ledger due to some other failure. For example, a transaction may fail if:
     ///
@@ -229,7 +312,11 @@ impl Error for StellarError {
 
 impl fmt::Display for StellarError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}\n\nTo learn more: {}", self.detail, self.url)
+        write!(f, "{}\n\nTo learn more: {}", self.detail, self.url)?;
+        if let Some(ref request_id) = self.request_id {
+            write!(f, "\nRequest id: {}", request_id)?;
+        }
+        Ok(())
     }
 }
 
@@ -260,6 +347,42 @@ mod asset_identifier_tests {
         );
     }
 
+    #[test]
+    fn it_shows_the_request_id() {
+        let error: StellarError = serde_json::from_str(&before_history_json()).unwrap();
+        assert_eq!(error.request_id(), None);
+        let error = error.with_request_id("1f-2a");
+        assert_eq!(error.request_id(), Some("1f-2a"));
+        assert!(error.to_string().ends_with("\nRequest id: 1f-2a"));
+    }
+
+    #[test]
+    fn it_keeps_the_result_codes_of_a_failed_transaction() {
+        let error: StellarError = serde_json::from_str(
+            r#"{
+                "type": "https://stellar.org/horizon-errors/transaction_failed",
+                "title": "Transaction Failed",
+                "status": 400,
+                "detail": "The transaction failed when submitted to the stellar network.",
+                "extras": {
+                    "envelope_xdr": "AAAA",
+                    "result_codes": {
+                        "transaction": "tx_failed",
+                        "operations": ["op_over_source_max"]
+                    },
+                    "result_xdr": "AAAA"
+                }
+            }"#,
+        )
+        .unwrap();
+        let codes = error.result_codes().unwrap();
+        assert_eq!(codes.transaction(), "tx_failed");
+        assert_eq!(codes.operations(), &vec!["op_over_source_max".to_string()]);
+
+        let error: StellarError = serde_json::from_str(&before_history_json()).unwrap();
+        assert!(error.result_codes().is_none());
+    }
+
     #[test]
     fn it_will_deserialize_unknown_errors() {
         let kind: Kind = serde_json::from_str("\"bad type\"").unwrap();
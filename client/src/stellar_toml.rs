@@ -0,0 +1,257 @@
+//! Just enough TOML parsing to read the stellar.toml that a home domain
+//! publishes at `/.well-known/stellar.toml`. Tables, arrays of tables and
+//! string and boolean values are understood. Other values, such as numbers
+//! and arrays, are skipped over.
+
+use error::{Error, Result};
+use reqwest;
+
+/// Fetches the text of the stellar.toml that `home_domain` publishes.
+pub(crate) fn fetch(client: &reqwest::Client, home_domain: &str) -> Result<String> {
+    let uri = format!("https://{}/.well-known/stellar.toml", home_domain);
+    let mut response = client.get(&uri).send()?;
+    if !response.status().is_success() {
+        return Err(Error::ServerError(None));
+    }
+    Ok(response.text()?)
+}
+
+/// A parsed stellar.toml, as a list of tables. Keys that come before any
+/// table header are in a table with an empty name.
+#[derive(Debug, Clone)]
+pub(crate) struct StellarToml {
+    tables: Vec<Table>,
+}
+
+/// One table or one element of an array of tables, such as a single
+/// `[[CURRENCIES]]` entry.
+#[derive(Debug, Clone)]
+pub(crate) struct Table {
+    name: String,
+    entries: Vec<(String, Value)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    String(String),
+    Bool(bool),
+    Other,
+}
+
+impl StellarToml {
+    /// Parses the file, skipping any line that can't be read rather than
+    /// failing, since domains often publish slightly malformed files.
+    pub fn parse(text: &str) -> StellarToml {
+        let mut tables = vec![Table {
+            name: String::new(),
+            entries: Vec::new(),
+        }];
+        let mut lines = text.lines();
+        while let Some(line) = lines.next() {
+            let line = strip_comment(line);
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') {
+                let name = line.trim_matches(|c| c == '[' || c == ']').trim();
+                tables.push(Table {
+                    name: name.to_string(),
+                    entries: Vec::new(),
+                });
+                continue;
+            }
+            let (key, value) = match line.find('=') {
+                Some(index) => (line[..index].trim(), line[index + 1..].trim()),
+                None => continue,
+            };
+            let mut value = value.to_string();
+            // Arrays and multi-line strings carry on over the next lines.
+            while !is_complete(&value) {
+                match lines.next() {
+                    Some(next) => {
+                        value.push('\n');
+                        value.push_str(&strip_comment(next));
+                    }
+                    None => break,
+                }
+            }
+            let key = key.trim_matches('"').to_string();
+            let value = parse_value(value.trim());
+            if let Some(table) = tables.last_mut() {
+                table.entries.push((key, value));
+            }
+        }
+        StellarToml { tables }
+    }
+
+    /// Every table with `name`, in the order they appear. For an array of
+    /// tables this is each of its elements.
+    pub fn tables<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Table> + 'a {
+        self.tables.iter().filter(move |table| table.name == name)
+    }
+}
+
+impl Table {
+    /// The string value of `key`.
+    pub fn string(&self, key: &str) -> Option<&str> {
+        match self.value(key) {
+            Some(Value::String(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The boolean value of `key`.
+    pub fn bool(&self, key: &str) -> Option<bool> {
+        match self.value(key) {
+            Some(Value::Bool(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn value(&self, key: &str) -> Option<&Value> {
+        self.entries
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Drops a `#` comment, leaving any `#` inside a string alone.
+fn strip_comment(line: &str) -> String {
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        match quote {
+            Some(q) => {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' && q == '"' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+            }
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' => return line[..index].to_string(),
+            None => {}
+        }
+    }
+    line.to_string()
+}
+
+/// Whether a value is finished, or is an array or multi-line string that
+/// goes on to the next line.
+fn is_complete(value: &str) -> bool {
+    if value.starts_with("\"\"\"") || value.starts_with("'''") {
+        let delimiter = &value[..3];
+        return value.len() >= 6 && value[3..].contains(delimiter);
+    }
+    if value.starts_with('[') {
+        let mut depth = 0;
+        let mut quote = None;
+        for c in value.chars() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if c == '"' || c == '\'' => quote = Some(c),
+                None if c == '[' => depth += 1,
+                None if c == ']' => depth -= 1,
+                None => {}
+            }
+        }
+        return depth <= 0;
+    }
+    true
+}
+
+fn parse_value(value: &str) -> Value {
+    if value.starts_with("\"\"\"") || value.starts_with("'''") {
+        let inner = &value[3..];
+        let inner = match inner.find(&value[..3]) {
+            Some(end) => &inner[..end],
+            None => inner,
+        };
+        return Value::String(inner.trim_start_matches('\n').to_string());
+    }
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        return Value::String(unescape(&value[1..value.len() - 1]));
+    }
+    if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        return Value::String(value[1..value.len() - 1].to_string());
+    }
+    match value {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => Value::Other,
+    }
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some(other) => unescaped.push(other),
+            None => {}
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod stellar_toml_tests {
+    use super::*;
+
+    const TOML: &str = r#"
+# A stellar.toml with a regulated asset.
+NETWORK_PASSPHRASE = "Test SDF Network ; September 2015"
+ACCOUNTS = [
+    "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG", # the issuer
+]
+VERSION = 2
+
+[DOCUMENTATION]
+ORG_NAME = "Example # Org"
+
+[[CURRENCIES]]
+code = "FOO"
+issuer = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG"
+regulated = true
+approval_server = "https://example.com/tx-approve"
+desc = """
+A regulated asset."""
+
+[[CURRENCIES]]
+code='BAR'
+regulated=false
+"#;
+
+    #[test]
+    fn it_reads_tables_and_values() {
+        let toml = StellarToml::parse(TOML);
+        let top = toml.tables("").next().unwrap();
+        assert_eq!(
+            top.string("NETWORK_PASSPHRASE"),
+            Some("Test SDF Network ; September 2015")
+        );
+        assert_eq!(top.string("ACCOUNTS"), None);
+
+        let documentation = toml.tables("DOCUMENTATION").next().unwrap();
+        assert_eq!(documentation.string("ORG_NAME"), Some("Example # Org"));
+
+        let currencies: Vec<&Table> = toml.tables("CURRENCIES").collect();
+        assert_eq!(currencies.len(), 2);
+        assert_eq!(currencies[0].string("code"), Some("FOO"));
+        assert_eq!(currencies[0].bool("regulated"), Some(true));
+        assert_eq!(currencies[0].string("desc"), Some("A regulated asset."));
+        assert_eq!(currencies[1].string("code"), Some("BAR"));
+        assert_eq!(currencies[1].bool("regulated"), Some(false));
+    }
+}
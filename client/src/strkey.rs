@@ -0,0 +1,173 @@
+//! Encodes keys into the base32 strings stellar shows to people, and back.
+//!
+//! A strkey is a version byte saying what kind of key it holds, the key
+//! itself and a CRC-16/XMODEM checksum, all base32 encoded. The version byte
+//! is what makes account ids start with `G`, contracts with `C` and secret
+//! seeds with `S`.
+#![cfg_attr(not(all(feature = "soroban", feature = "keys")), allow(dead_code))]
+
+/// An ed25519 public key, the `G...` account id.
+pub const ACCOUNT: u8 = 6 << 3;
+/// An ed25519 secret seed, the `S...` secret key.
+pub const SEED: u8 = 18 << 3;
+/// A contract id, the `C...` contract address.
+pub const CONTRACT: u8 = 2 << 3;
+/// An account id along with a multiplexing id, the `M...` address.
+pub const MUXED_ACCOUNT: u8 = 12 << 3;
+/// The hash of a transaction that is allowed to sign, a `T...` signer.
+pub const PRE_AUTH_TX: u8 = 19 << 3;
+/// A hash whose preimage signs, an `X...` signer.
+pub const SHA256_HASH: u8 = 23 << 3;
+/// An ed25519 key that signs a given payload, a `P...` signer.
+pub const SIGNED_PAYLOAD: u8 = 15 << 3;
+
+const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Encodes a key with the given version byte.
+pub fn encode(version: u8, key: &[u8]) -> String {
+    let mut data = Vec::with_capacity(key.len() + 3);
+    data.push(version);
+    data.extend_from_slice(key);
+    let checksum = crc16(&data);
+    data.push(checksum as u8);
+    data.push((checksum >> 8) as u8);
+
+    let mut encoded = String::new();
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for byte in data {
+        buffer = (buffer << 8) | u16::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    encoded
+}
+
+/// Decodes a strkey of the given version into its 32 byte key, checking
+/// the checksum along the way. Returns `None` for anything that isn't a
+/// well formed strkey of that version.
+pub fn decode(version: u8, encoded: &str) -> Option<[u8; 32]> {
+    let data = decode_payload(version, encoded, 32)?;
+    let mut key = [0; 32];
+    key.copy_from_slice(&data);
+    Some(key)
+}
+
+/// Decodes an `M...` address into the key of its account and its
+/// multiplexing id.
+pub fn decode_muxed(encoded: &str) -> Option<([u8; 32], u64)> {
+    let data = decode_payload(MUXED_ACCOUNT, encoded, 40)?;
+    let mut key = [0; 32];
+    key.copy_from_slice(&data[..32]);
+    let mut id = [0; 8];
+    id.copy_from_slice(&data[32..]);
+    Some((key, u64::from_be_bytes(id)))
+}
+
+/// Decodes a strkey whose payload is `len` bytes long, returning the
+/// payload without the version byte and checksum.
+fn decode_payload(version: u8, encoded: &str, len: usize) -> Option<Vec<u8>> {
+    if encoded.len() != ((len + 3) * 8 + 4) / 5 {
+        return None;
+    }
+    let mut data = Vec::with_capacity(len + 3);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for c in encoded.bytes() {
+        let value = ALPHABET.iter().position(|a| *a == c)?;
+        buffer = (buffer << 5) | value as u16;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            data.push((buffer >> bits) as u8);
+        }
+    }
+    let checksum = crc16(&data[..=len]);
+    if data[0] != version
+        || data[len + 1] != checksum as u8
+        || data[len + 2] != (checksum >> 8) as u8
+    {
+        return None;
+    }
+    Some(data[1..=len].to_vec())
+}
+
+/// CRC-16/XMODEM
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for byte in data {
+        crc ^= u16::from(*byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod strkey_tests {
+    use super::*;
+
+    const SEED_BYTES: [u8; 32] = [
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+        25, 26, 27, 28, 29, 30, 31,
+    ];
+
+    #[test]
+    fn it_round_trips_a_seed() {
+        let encoded = encode(SEED, &SEED_BYTES);
+        assert_eq!(
+            encoded,
+            "SAAACAQDAQCQMBYIBEFAWDANBYHRAEISCMKBKFQXDAMRUGY4DUPB6NKI"
+        );
+        assert_eq!(decode(SEED, &encoded), Some(SEED_BYTES));
+    }
+
+    #[test]
+    fn it_checks_the_version() {
+        let encoded = encode(SEED, &SEED_BYTES);
+        assert_eq!(decode(ACCOUNT, &encoded), None);
+    }
+
+    #[test]
+    fn it_checks_the_checksum() {
+        assert_eq!(
+            decode(
+                ACCOUNT,
+                "GAB2CB576PHBBPQ5ODORRZ2LYCMWPZGWGCN2KDK7DXOIMZASKUY3QZ6Q"
+            )
+            .map(|_| ()),
+            Some(())
+        );
+        assert_eq!(
+            decode(
+                ACCOUNT,
+                "GAB2CB576PHBBPQ5ODORRZ2LYCMWPZGWGCN2KDK7DXOIMZASKUY3QZ6A"
+            ),
+            None
+        );
+        assert_eq!(decode(ACCOUNT, "GABC"), None);
+    }
+
+    #[test]
+    fn it_round_trips_a_muxed_account() {
+        let mut data = SEED_BYTES.to_vec();
+        data.extend_from_slice(&1234u64.to_be_bytes());
+        let encoded = encode(MUXED_ACCOUNT, &data);
+        assert!(encoded.starts_with('M'));
+        assert_eq!(encoded.len(), 69);
+        assert_eq!(decode_muxed(&encoded), Some((SEED_BYTES, 1234)));
+        assert_eq!(decode(MUXED_ACCOUNT, &encoded), None);
+        assert_eq!(decode_muxed(&encode(ACCOUNT, &SEED_BYTES)), None);
+    }
+}
@@ -0,0 +1,212 @@
+use client::sync::parse_response;
+use endpoint::IntoRequest;
+use error::Result;
+use serde_json;
+use std::cell::RefCell;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use sync::{Client, Requester};
+
+/// A requester that records real horizon responses to disk and replays them
+/// on later runs.
+///
+/// When a cassette is created with `Cassette::new` it looks for an existing
+/// recording at the given path. If one is found every request is answered from
+/// it without touching the network. Otherwise requests go through the wrapped
+/// client and each response is appended to the recording so the next run is
+/// fast and deterministic. Deleting the file re-records it.
+///
+/// Requests are matched by path and query, and repeated requests to the same
+/// uri are replayed in the order they were recorded. Asking a replaying
+/// cassette for something that was never recorded panics.
+///
+/// # Examples
+///
+/// ```no_run
+/// use stellar_client::endpoint::asset;
+/// use stellar_client::sync::{Client, Requester};
+/// use stellar_client::test::Cassette;
+///
+/// let client = Client::horizon_test().unwrap();
+/// let cassette = Cassette::new(client, "tests/cassettes/assets.json").unwrap();
+/// let assets = cassette.request(asset::All::default()).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Cassette {
+    client: Client,
+    path: PathBuf,
+    mode: Mode,
+    interactions: RefCell<Vec<Interaction>>,
+}
+
+/// Whether a cassette is capturing new responses or serving recorded ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Requests are sent to horizon and the responses are saved.
+    Record,
+    /// Requests are answered from the saved responses.
+    Replay,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Interaction {
+    uri: String,
+    status: u16,
+    body: String,
+    #[serde(skip)]
+    played: bool,
+}
+
+impl Cassette {
+    /// Replays the recording at `path` if it exists, otherwise records to it.
+    pub fn new<P: AsRef<Path>>(client: Client, path: P) -> Result<Cassette> {
+        if path.as_ref().exists() {
+            Cassette::replay(client, path)
+        } else {
+            Ok(Cassette::record(client, path))
+        }
+    }
+
+    /// Creates a cassette that records every response to `path`, replacing
+    /// any recording already there.
+    pub fn record<P: AsRef<Path>>(client: Client, path: P) -> Cassette {
+        Cassette {
+            client,
+            path: path.as_ref().to_path_buf(),
+            mode: Mode::Record,
+            interactions: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Creates a cassette that serves the responses recorded at `path`.
+    pub fn replay<P: AsRef<Path>>(client: Client, path: P) -> Result<Cassette> {
+        let file = File::open(path.as_ref())?;
+        let interactions: Vec<Interaction> = serde_json::from_reader(file)?;
+        Ok(Cassette {
+            client,
+            path: path.as_ref().to_path_buf(),
+            mode: Mode::Replay,
+            interactions: RefCell::new(interactions),
+        })
+    }
+
+    /// Whether this cassette is recording or replaying.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// The file the cassette reads from or writes to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn save(&self) -> Result<()> {
+        let file = File::create(&self.path)?;
+        serde_json::to_writer_pretty(file, &*self.interactions.borrow())?;
+        Ok(())
+    }
+}
+
+impl Requester for Cassette {
+    fn request<E>(&self, endpoint: E) -> Result<E::Response>
+    where
+        E: IntoRequest,
+    {
+        let request = endpoint.into_request(self.client.uri())?;
+        let uri = match request.uri().path_and_query() {
+            Some(path_and_query) => path_and_query.as_str().to_string(),
+            None => request.uri().path().to_string(),
+        };
+
+        match self.mode {
+            Mode::Record => {
+                let (status, body) = self.client.execute(&request)?;
+                self.interactions.borrow_mut().push(Interaction {
+                    uri,
+                    status,
+                    body: body.clone(),
+                    played: true,
+                });
+                self.save()?;
+                parse_response(status, body.as_bytes(), self.client.strictness())
+            }
+            Mode::Replay => {
+                let mut interactions = self.interactions.borrow_mut();
+                let interaction = match interactions
+                    .iter_mut()
+                    .find(|interaction| !interaction.played && interaction.uri == uri)
+                {
+                    Some(interaction) => interaction,
+                    None => panic!(
+                        "No recorded response for {} in {}",
+                        uri,
+                        self.path.display()
+                    ),
+                };
+                interaction.played = true;
+                parse_response(
+                    interaction.status,
+                    interaction.body.as_bytes(),
+                    self.client.strictness(),
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod cassette_tests {
+    use super::*;
+    use endpoint::account;
+    use std::env;
+    use std::fs;
+    use std::io::Write;
+
+    const ACCOUNT_ID: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+
+    fn write_recording(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(name);
+        let interactions = vec![Interaction {
+            uri: format!("/accounts/{}", ACCOUNT_ID),
+            status: 200,
+            body: include_str!("../../fixtures/account.json").to_string(),
+            played: false,
+        }];
+        let mut file = File::create(&path).unwrap();
+        file.write_all(serde_json::to_string(&interactions).unwrap().as_bytes())
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn it_replays_existing_recordings() {
+        let path = write_recording("stellar_client_cassette_replay.json");
+        let cassette = Cassette::new(Client::horizon_test().unwrap(), &path).unwrap();
+        assert_eq!(cassette.mode(), Mode::Replay);
+        let account = cassette
+            .request(account::Details::new(ACCOUNT_ID.into()))
+            .unwrap();
+        assert_eq!(account.id(), ACCOUNT_ID);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_only_replays_each_interaction_once() {
+        let path = write_recording("stellar_client_cassette_replay_once.json");
+        let cassette = Cassette::replay(Client::horizon_test().unwrap(), &path).unwrap();
+        fs::remove_file(&path).unwrap();
+        cassette
+            .request(account::Details::new(ACCOUNT_ID.into()))
+            .unwrap();
+        let _ = cassette.request(account::Details::new(ACCOUNT_ID.into()));
+    }
+
+    #[test]
+    fn it_records_when_there_is_no_recording() {
+        let path = env::temp_dir().join("stellar_client_cassette_missing.json");
+        let cassette = Cassette::new(Client::horizon_test().unwrap(), &path).unwrap();
+        assert_eq!(cassette.mode(), Mode::Record);
+        assert_eq!(cassette.path(), path.as_path());
+    }
+}
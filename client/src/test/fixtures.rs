@@ -0,0 +1,195 @@
+//! Fixture constructors for the resources. Each returns a resource parsed from
+//! a sample horizon response, which gives downstream crates realistic values
+//! to use in their own tests.
+use resources::{
+    Account, Asset, Effect, Health, Ledger, Offer, Operation, Orderbook, PaymentPath, Root, Trade,
+    TradeAggregation, Transaction,
+};
+use serde_json;
+
+macro_rules! fixture {
+    ($resource:ty { $($(#[$attr:meta])* fn $name:ident => $path:expr;)* }) => {
+        impl $resource {
+            $(
+                $(#[$attr])*
+                pub fn $name() -> $resource {
+                    serde_json::from_str(include_str!($path))
+                        .expect(concat!("Fixture ", $path, " failed to parse"))
+                }
+            )*
+        }
+    };
+}
+
+fixture!(Account {
+    /// An account holding lumens and a single credit asset.
+    fn fixture => "../../fixtures/account.json";
+});
+
+fixture!(Asset {
+    /// A credit_alphanum4 asset.
+    fn fixture => "../../fixtures/asset.json";
+});
+
+fixture!(Health {
+    /// A health check from a server that is up and in sync.
+    fn fixture => "../../fixtures/health.json";
+});
+
+fixture!(Ledger {
+    /// A closed ledger.
+    fn fixture => "../../fixtures/ledger.json";
+});
+
+fixture!(Offer {
+    /// An offer selling one credit asset for another.
+    fn fixture => "../../fixtures/offer.json";
+});
+
+fixture!(Orderbook {
+    /// An orderbook with a single bid and ask between lumens and a credit asset.
+    fn fixture => "../../fixtures/orderbook.json";
+});
+
+fixture!(PaymentPath {
+    /// A payment path between two credit assets.
+    fn fixture => "../../fixtures/payment_path.json";
+});
+
+fixture!(Root {
+    /// The root of a server whose history is a few ledgers behind core.
+    fn fixture => "../../fixtures/root.json";
+});
+
+fixture!(Trade {
+    /// A trade between two assets.
+    fn fixture => "../../fixtures/trade.json";
+});
+
+fixture!(TradeAggregation {
+    /// A single segment of aggregated trades.
+    fn fixture => "../../fixtures/trade_aggregation.json";
+});
+
+fixture!(Transaction {
+    /// A transaction without a memo.
+    fn fixture => "../../fixtures/transactions/transaction_memo_none.json";
+    /// A transaction with a text memo.
+    fn text_memo_fixture => "../../fixtures/transactions/transaction_memo_text.json";
+    /// A transaction with an id memo.
+    fn id_memo_fixture => "../../fixtures/transactions/transaction_memo_id.json";
+    /// A transaction with a hash memo.
+    fn hash_memo_fixture => "../../fixtures/transactions/transaction_memo_hash.json";
+    /// A transaction with a return memo.
+    fn return_memo_fixture => "../../fixtures/transactions/transaction_memo_return.json";
+});
+
+fixture!(Operation {
+    /// A create account operation.
+    fn create_account_fixture => "../../fixtures/operations/create_account.json";
+    /// A payment operation.
+    fn payment_fixture => "../../fixtures/operations/payment.json";
+    /// A path payment operation.
+    fn path_payment_fixture => "../../fixtures/operations/path_payment_strict_receive.json";
+    /// A manage offer operation.
+    fn manage_offer_fixture => "../../fixtures/operations/manage_sell_offer.json";
+    /// A create passive offer operation.
+    fn create_passive_offer_fixture => "../../fixtures/operations/create_passive_sell_offer.json";
+    /// A set options operation.
+    fn set_options_fixture => "../../fixtures/operations/set_options.json";
+    /// A change trust operation.
+    fn change_trust_fixture => "../../fixtures/operations/change_trust.json";
+    /// An allow trust operation.
+    #[cfg(feature = "legacy-ops")]
+    fn allow_trust_fixture => "../../fixtures/operations/allow_trust.json";
+    /// An account merge operation.
+    fn account_merge_fixture => "../../fixtures/operations/account_merge.json";
+    /// An inflation operation.
+    #[cfg(feature = "legacy-ops")]
+    fn inflation_fixture => "../../fixtures/operations/inflation.json";
+    /// A manage data operation.
+    fn manage_data_fixture => "../../fixtures/operations/manage_data.json";
+    /// An invoke host function operation.
+    fn invoke_host_function_fixture => "../../fixtures/operations/invoke_host_function.json";
+    /// An extend footprint ttl operation.
+    fn extend_footprint_ttl_fixture => "../../fixtures/operations/extend_footprint_ttl.json";
+    /// A restore footprint operation.
+    fn restore_footprint_fixture => "../../fixtures/operations/restore_footprint.json";
+});
+
+fixture!(Effect {
+    /// An account created effect.
+    fn account_created_fixture => "../../fixtures/effects/account_created.json";
+    /// An account credited effect.
+    fn account_credited_fixture => "../../fixtures/effects/account_credited.json";
+    /// An account debited effect.
+    fn account_debited_fixture => "../../fixtures/effects/account_debited.json";
+    /// An account removed effect.
+    fn account_removed_fixture => "../../fixtures/effects/account_removed.json";
+    /// An account thresholds updated effect.
+    fn account_thresholds_updated_fixture =>
+        "../../fixtures/effects/account_threshold_updated.json";
+    /// An account home domain updated effect.
+    fn account_home_domain_updated_fixture =>
+        "../../fixtures/effects/account_home_domain_updated.json";
+    /// An account flags updated effect.
+    fn account_flags_updated_fixture => "../../fixtures/effects/account_flags_updated.json";
+    /// A signer created effect.
+    fn signer_created_fixture => "../../fixtures/effects/signer_created.json";
+    /// A signer removed effect.
+    fn signer_removed_fixture => "../../fixtures/effects/signer_removed.json";
+    /// A signer updated effect.
+    fn signer_updated_fixture => "../../fixtures/effects/signer_updated.json";
+    /// A trustline created effect.
+    fn trustline_created_fixture => "../../fixtures/effects/trustline_created.json";
+    /// A trustline removed effect.
+    fn trustline_removed_fixture => "../../fixtures/effects/trustline_removed.json";
+    /// A trustline updated effect.
+    fn trustline_updated_fixture => "../../fixtures/effects/trustline_updated.json";
+    /// A trustline authorized effect.
+    fn trustline_authorized_fixture => "../../fixtures/effects/trustline_authorized.json";
+    /// A trustline deauthorized effect.
+    fn trustline_deauthorized_fixture => "../../fixtures/effects/trustline_deauthorized.json";
+    /// A trade effect.
+    fn trade_fixture => "../../fixtures/effects/trade.json";
+    /// A data created effect.
+    fn data_created_fixture => "../../fixtures/effects/data_created.json";
+    /// A data removed effect.
+    fn data_removed_fixture => "../../fixtures/effects/data_removed.json";
+    /// A data updated effect.
+    fn data_updated_fixture => "../../fixtures/effects/data_updated.json";
+});
+
+#[cfg(test)]
+mod fixtures_tests {
+    use super::*;
+
+    #[test]
+    fn it_builds_resource_fixtures() {
+        assert_eq!(Account::fixture().balances().len(), 2);
+        assert_eq!(Asset::fixture().code(), "USD");
+        assert_eq!(Offer::fixture().id(), 121);
+        assert!(Transaction::fixture().fee_paid() > 0);
+        assert!(Health::fixture().is_healthy());
+        let _ = Ledger::fixture();
+        let _ = Orderbook::fixture();
+        let _ = PaymentPath::fixture();
+        assert_eq!(Root::fixture().ingest_lag(), 4);
+        let _ = Trade::fixture();
+        let _ = TradeAggregation::fixture();
+    }
+
+    #[test]
+    fn it_builds_operation_fixtures() {
+        assert!(Operation::payment_fixture().is_payment());
+        assert!(Operation::create_account_fixture().is_create_account());
+        assert!(Operation::manage_data_fixture().is_manage_data());
+    }
+
+    #[test]
+    fn it_builds_effect_fixtures() {
+        assert_eq!(Effect::account_credited_fixture().type_i(), 2);
+        assert_eq!(Effect::trade_fixture().type_i(), 33);
+        assert_eq!(Effect::data_updated_fixture().type_i(), 42);
+    }
+}
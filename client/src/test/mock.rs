@@ -0,0 +1,331 @@
+use client::sync::parse_response;
+use endpoint::IntoRequest;
+use error::Result;
+use http::HeaderMap;
+use resources::Strictness;
+use std::cell::RefCell;
+use sync::{Requester, ResponseEnvelope};
+
+const MOCK_HOST: &str = "https://horizon.mock";
+
+/// A client that serves canned responses instead of talking to horizon.
+///
+/// Responses are registered against a uri pattern which is matched against the
+/// path and query of each request. A `*` in the pattern matches any run of
+/// characters. Patterns are checked in the order they were registered and the
+/// first match wins. Issuing a request that matches no pattern panics so that
+/// unexpected requests fail the test loudly.
+///
+/// Every request made is recorded so that tests can assert on what was asked for.
+///
+/// Responses fetched with `request_with_headers` carry a `Latest-Ledger`
+/// header when ledgers are given with `with_latest_ledgers`.
+///
+/// # Examples
+///
+/// ```
+/// use stellar_client::endpoint::asset;
+/// use stellar_client::sync::Requester;
+/// use stellar_client::test::MockClient;
+///
+/// let client = MockClient::new().with_json(
+///     "/assets*",
+///     r#"{ "_embedded": { "records": [] } }"#,
+/// );
+///
+/// let assets = client.request(asset::All::default()).unwrap();
+/// assert!(assets.records().is_empty());
+/// client.assert_requested("/assets");
+/// ```
+#[derive(Debug, Default)]
+pub struct MockClient {
+    mocks: Vec<Mock>,
+    latest_ledgers: Vec<u32>,
+    requests: RefCell<Vec<String>>,
+}
+
+#[derive(Debug, Clone)]
+struct Mock {
+    pattern: String,
+    status: u16,
+    body: String,
+}
+
+impl MockClient {
+    /// Creates a mock client with no registered responses.
+    pub fn new() -> MockClient {
+        MockClient::default()
+    }
+
+    /// Registers a successful response whose body is the given json.
+    pub fn with_json(self, pattern: &str, body: &str) -> MockClient {
+        self.with_response(pattern, 200, body)
+    }
+
+    /// Registers a response with an explicit status code. Client errors are
+    /// expected to carry a horizon problem document so that they can be
+    /// surfaced as a `StellarError`.
+    pub fn with_response(mut self, pattern: &str, status: u16, body: &str) -> MockClient {
+        self.mocks.push(Mock {
+            pattern: pattern.to_string(),
+            status,
+            body: body.to_string(),
+        });
+        self
+    }
+
+    /// Sends the ledgers in turn as the `Latest-Ledger` header of each
+    /// request's response, repeating the last one once they run out. This
+    /// lets tests have the ledger advance between requests.
+    pub fn with_latest_ledgers(mut self, ledgers: &[u32]) -> MockClient {
+        self.latest_ledgers = ledgers.to_vec();
+        self
+    }
+
+    /// The path and query of every request that has been made, in order.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.borrow().clone()
+    }
+
+    /// The number of requests made that match the pattern.
+    pub fn hits(&self, pattern: &str) -> usize {
+        self.requests
+            .borrow()
+            .iter()
+            .filter(|uri| matches(pattern, uri))
+            .count()
+    }
+
+    /// Panics unless at least one request has been made that matches the pattern.
+    pub fn assert_requested(&self, pattern: &str) {
+        if self.hits(pattern) == 0 {
+            panic!(
+                "Expected a request matching {} but only saw {:?}",
+                pattern,
+                self.requests()
+            );
+        }
+    }
+}
+
+impl Requester for MockClient {
+    fn request<E>(&self, endpoint: E) -> Result<E::Response>
+    where
+        E: IntoRequest,
+    {
+        let request = endpoint.into_request(MOCK_HOST)?;
+        let uri = match request.uri().path_and_query() {
+            Some(path_and_query) => path_and_query.as_str().to_string(),
+            None => request.uri().path().to_string(),
+        };
+        self.requests.borrow_mut().push(uri.clone());
+
+        let mock = match self.mocks.iter().find(|mock| matches(&mock.pattern, &uri)) {
+            Some(mock) => mock,
+            None => panic!("No mocked response matches the request for {}", uri),
+        };
+
+        parse_response(mock.status, mock.body.as_bytes(), Strictness::default())
+    }
+
+    fn request_with_headers<E>(&self, endpoint: E) -> Result<ResponseEnvelope<E::Response>>
+    where
+        E: IntoRequest,
+    {
+        let resource = self.request(endpoint)?;
+        let served = self.requests.borrow().len();
+        let mut headers = HeaderMap::new();
+        if let Some(ledger) = self
+            .latest_ledgers
+            .get(served - 1)
+            .or_else(|| self.latest_ledgers.last())
+        {
+            headers.insert("Latest-Ledger", ledger.to_string().parse().unwrap());
+        }
+        Ok(ResponseEnvelope::new(resource, headers, ""))
+    }
+}
+
+/// Matches a uri against a pattern where `*` stands in for any run of characters.
+fn matches(pattern: &str, uri: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    if !uri.starts_with(first) {
+        return false;
+    }
+    let mut rest = &uri[first.len()..];
+    let parts: Vec<&str> = parts.collect();
+    match parts.split_last() {
+        None => rest.is_empty(),
+        Some((last, middle)) => {
+            for part in middle {
+                match rest.find(part) {
+                    Some(index) => rest = &rest[index + part.len()..],
+                    None => return false,
+                }
+            }
+            rest.ends_with(last)
+        }
+    }
+}
+
+#[cfg(test)]
+mod mock_client_tests {
+    use super::*;
+    use endpoint::{account, asset, Cursor};
+    use error::Error;
+    use std::time::Duration;
+    use stellar_error;
+    use sync::Iter;
+
+    const ACCOUNT_ID: &str = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+
+    fn account_json() -> &'static str {
+        include_str!("../../fixtures/account.json")
+    }
+
+    fn not_found_json() -> &'static str {
+        r#"{
+            "type": "https://stellar.org/horizon-errors/not_found",
+            "title": "Resource Missing",
+            "status": 404,
+            "detail": "The resource at the url requested was not found."
+        }"#
+    }
+
+    #[test]
+    fn it_matches_patterns() {
+        assert!(matches("/accounts/abc", "/accounts/abc"));
+        assert!(!matches("/accounts/abc", "/accounts/abc/offers"));
+        assert!(matches("/accounts/*", "/accounts/abc/offers"));
+        assert!(matches("/accounts/*/offers", "/accounts/abc/offers"));
+        assert!(matches("*cursor=1*", "/assets?cursor=1&limit=2"));
+        assert!(!matches("/accounts/*/offers", "/accounts/abc/trades"));
+    }
+
+    #[test]
+    fn it_serves_canned_responses() {
+        let client = MockClient::new().with_json("/accounts/*", account_json());
+        let account = client
+            .request(account::Details::new(ACCOUNT_ID.into()))
+            .unwrap();
+        assert_eq!(account.id(), ACCOUNT_ID);
+        assert_eq!(client.requests(), vec![format!("/accounts/{}", ACCOUNT_ID)]);
+        assert_eq!(client.hits("/accounts/*"), 1);
+    }
+
+    #[test]
+    fn it_returns_horizon_errors() {
+        let client = MockClient::new().with_response("/accounts/*", 404, not_found_json());
+        match client
+            .request(account::Details::new(ACCOUNT_ID.into()))
+            .unwrap_err()
+        {
+            Error::BadResponse(error) => assert_eq!(error.kind(), stellar_error::Kind::NotFound),
+            error => panic!("Mock did not return a bad response {:?}", error),
+        }
+    }
+
+    #[test]
+    fn it_returns_server_errors() {
+        let client = MockClient::new().with_response("*", 500, "");
+        match client
+            .request(account::Details::new(ACCOUNT_ID.into()))
+            .unwrap_err()
+        {
+            Error::ServerError(_) => {}
+            error => panic!("Mock did not return a server error {:?}", error),
+        }
+    }
+
+    #[test]
+    fn it_sends_the_latest_ledgers_in_turn() {
+        let client = MockClient::new()
+            .with_json("/accounts/*", account_json())
+            .with_latest_ledgers(&[7, 8]);
+        let ledgers: Vec<_> = (0..3)
+            .map(|_| {
+                client
+                    .request_with_headers(account::Details::new(ACCOUNT_ID.into()))
+                    .unwrap()
+                    .latest_ledger()
+            })
+            .collect();
+        assert_eq!(ledgers, vec![Some(7), Some(8), Some(8)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_panics_on_unexpected_requests() {
+        let client = MockClient::new().with_json("/assets", "{}");
+        let _ = client.request(account::Details::new(ACCOUNT_ID.into()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_panics_when_an_expected_request_was_not_made() {
+        MockClient::new().assert_requested("/assets");
+    }
+
+    #[test]
+    fn it_drives_iterators_across_pages() {
+        let client = MockClient::new()
+            .with_json(
+                "/assets?cursor=NEXT*",
+                r#"{ "_embedded": { "records": [] } }"#,
+            )
+            .with_json(
+                "/assets*",
+                &format!(
+                    r#"{{
+                        "_links": {{
+                            "next": {{ "href": "https://horizon.mock/assets?cursor=NEXT" }}
+                        }},
+                        "_embedded": {{ "records": [{}] }}
+                    }}"#,
+                    include_str!("../../fixtures/asset.json")
+                ),
+            );
+        let endpoint = asset::All::default().with_cursor("FIRST");
+        let assets: Vec<_> = Iter::new(&client, endpoint).collect();
+        assert_eq!(assets.len(), 1);
+        assert!(assets[0].is_ok());
+        assert_eq!(client.hits("/assets*"), 2);
+        client.assert_requested("/assets?cursor=NEXT*");
+    }
+
+    #[test]
+    fn it_keeps_following_past_an_empty_page() {
+        let client = MockClient::new()
+            .with_response("/assets?cursor=LATER*", 500, "")
+            .with_json(
+                "/assets?cursor=NEXT*",
+                r#"{
+                    "_links": {
+                        "next": { "href": "https://horizon.mock/assets?cursor=LATER" }
+                    },
+                    "_embedded": { "records": [] }
+                }"#,
+            )
+            .with_json(
+                "/assets*",
+                &format!(
+                    r#"{{
+                        "_links": {{
+                            "next": {{ "href": "https://horizon.mock/assets?cursor=NEXT" }}
+                        }},
+                        "_embedded": {{ "records": [{}] }}
+                    }}"#,
+                    include_str!("../../fixtures/asset.json")
+                ),
+            );
+        let endpoint = asset::All::default().with_cursor("FIRST");
+        let assets: Vec<_> = Iter::new(&client, endpoint)
+            .follow(Duration::from_millis(0))
+            .collect();
+        assert_eq!(assets.len(), 2);
+        assert!(assets[0].is_ok());
+        assert!(assets[1].is_err());
+        client.assert_requested("/assets?cursor=LATER*");
+    }
+}
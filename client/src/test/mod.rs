@@ -0,0 +1,25 @@
+//! Utilities for testing code that talks to horizon without needing the
+//! network.
+//!
+//! The `MockClient` implements the same `Requester` interface as the
+//! synchronous client, so any code written against that trait can be handed
+//! a mock in its tests instead. When real responses are preferred, a
+//! `Cassette` records them from horizon once and replays them afterwards.
+//! Code that makes its own http requests can be pointed at a `TestServer`,
+//! which answers on a local port with canned responses.
+//!
+//! Enabling the `test-util` feature adds fixture constructors to the
+//! resources, such as `Transaction::fixture()` or `Operation::payment_fixture()`,
+//! so that resource values can be fabricated in downstream tests.
+
+mod cassette;
+#[cfg(feature = "test-util")]
+mod fixtures;
+mod mock;
+#[cfg(any(test, feature = "test-util"))]
+mod server;
+
+pub use self::cassette::{Cassette, Mode as CassetteMode};
+pub use self::mock::MockClient;
+#[cfg(any(test, feature = "test-util"))]
+pub use self::server::{ReceivedRequest, TestResponse, TestServer};
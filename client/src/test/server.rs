@@ -0,0 +1,293 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// How long to wait for a request before deciding none is coming.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// An http server on a local port that answers each connection with the next
+/// of a list of canned responses, for tests of code that makes its own http
+/// requests, such as the sync client itself or the soroban and SEP clients,
+/// where a `MockClient` can't stand in.
+///
+/// The server stops accepting connections once it has used up its
+/// responses. Every request it receives is kept, in order, for the test to
+/// look at.
+///
+/// # Examples
+///
+/// ```
+/// use stellar_client::endpoint::account;
+/// use stellar_client::sync::Client;
+/// use stellar_client::test::{TestResponse, TestServer};
+///
+/// let server = TestServer::new(vec![TestResponse::json(
+///     r#"{
+///         "type": "https://stellar.org/horizon-errors/not_found",
+///         "title": "Resource Missing",
+///         "status": 404,
+///         "detail": "The resource at the url requested was not found."
+///     }"#,
+/// )
+/// .with_status("404 Not Found")]);
+///
+/// let client = Client::new(server.uri()).unwrap();
+/// let account_id = "GBAUUA74H4XOQYRSOW2RZUA4QL5PB37U3JS5NE3RTB2ELJVMIF5RLMAG";
+/// assert!(client.request(account::Details::new(account_id.into())).is_err());
+/// assert_eq!(server.next_request().path(), format!("/accounts/{}", account_id));
+/// ```
+#[derive(Debug)]
+pub struct TestServer {
+    uri: String,
+    requests: mpsc::Receiver<ReceivedRequest>,
+}
+
+impl TestServer {
+    /// Starts a server that answers the connections it gets with the
+    /// responses in turn.
+    pub fn new(responses: Vec<TestResponse>) -> TestServer {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let uri = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, requests) = mpsc::channel();
+        thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = match listener.accept() {
+                    Ok(connection) => connection,
+                    Err(_) => return,
+                };
+                let _ = sender.send(read_request(&mut stream));
+                response.write(&mut stream);
+            }
+        });
+        TestServer { uri, requests }
+    }
+
+    /// The uri of the server, such as `http://127.0.0.1:4321`.
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// The next request the server received, waiting a few seconds for one
+    /// to come in. Panics when none does.
+    pub fn next_request(&self) -> ReceivedRequest {
+        self.requests
+            .recv_timeout(REQUEST_TIMEOUT)
+            .expect("the test server didn't receive a request")
+    }
+
+    /// Every request the server receives from now until it has used up its
+    /// responses, or until no request has come in for a few seconds.
+    pub fn requests(&self) -> Vec<ReceivedRequest> {
+        let mut requests = Vec::new();
+        while let Ok(request) = self.requests.recv_timeout(REQUEST_TIMEOUT) {
+            requests.push(request);
+        }
+        requests
+    }
+
+    /// The requests the server has received so far, without waiting for
+    /// more. Once a client has its response, its request is among them.
+    pub fn received(&self) -> Vec<ReceivedRequest> {
+        self.requests.try_iter().collect()
+    }
+
+    /// A uri that nothing is listening on, for testing connection failures.
+    pub fn unreachable() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+}
+
+/// A canned response for a `TestServer` to send.
+#[derive(Debug, Clone)]
+pub struct TestResponse {
+    status: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl TestResponse {
+    /// A response with the given status line, such as `404 Not Found`, and
+    /// an empty body.
+    pub fn new(status: &str) -> TestResponse {
+        TestResponse {
+            status: status.to_string(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// A successful response whose body is the given json.
+    pub fn json(body: &str) -> TestResponse {
+        TestResponse::new("200 OK").with_body("application/json", body)
+    }
+
+    /// A successful response that streams the given server sent events.
+    pub fn events(body: &str) -> TestResponse {
+        TestResponse::new("200 OK").with_body("text/event-stream", body)
+    }
+
+    /// Sets the status line of the response.
+    pub fn with_status(mut self, status: &str) -> TestResponse {
+        self.status = status.to_string();
+        self
+    }
+
+    /// Adds a header to the response.
+    pub fn with_header(mut self, name: &str, value: &str) -> TestResponse {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Sets the body of the response along with its content type.
+    pub fn with_body<B>(self, content_type: &str, body: B) -> TestResponse
+    where
+        B: Into<Vec<u8>>,
+    {
+        let mut response = self.with_header("Content-Type", content_type);
+        response.body = body.into();
+        response
+    }
+
+    // The client may have hung up already, which some tests count on.
+    fn write(&self, stream: &mut TcpStream) {
+        let mut head = format!("HTTP/1.1 {}\r\n", self.status);
+        for &(ref name, ref value) in &self.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str(&format!(
+            "Content-Length: {}\r\nConnection: close\r\n\r\n",
+            self.body.len()
+        ));
+        let _ = stream
+            .write_all(head.as_bytes())
+            .and_then(|_| stream.write_all(&self.body));
+    }
+}
+
+/// A request received by a `TestServer`.
+#[derive(Debug, Clone)]
+pub struct ReceivedRequest {
+    text: String,
+    received_at: Instant,
+}
+
+impl ReceivedRequest {
+    /// The whole request as it was received, headers and body included.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// The request line, such as `GET /accounts HTTP/1.1`.
+    pub fn request_line(&self) -> &str {
+        self.text.lines().next().unwrap_or("")
+    }
+
+    /// The method of the request.
+    pub fn method(&self) -> &str {
+        self.request_line().split(' ').next().unwrap_or("")
+    }
+
+    /// The path and query the request was sent to.
+    pub fn path(&self) -> &str {
+        self.request_line().split(' ').nth(1).unwrap_or("")
+    }
+
+    /// The value of the named header, whatever its case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.head().lines().skip(1).find_map(|line| {
+            let mut parts = line.splitn(2, ':');
+            match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) if key.eq_ignore_ascii_case(name) => Some(value.trim()),
+                _ => None,
+            }
+        })
+    }
+
+    /// The body of the request.
+    pub fn body(&self) -> &str {
+        match self.text.find("\r\n\r\n") {
+            Some(end) => &self.text[end + 4..],
+            None => "",
+        }
+    }
+
+    /// When the request came in.
+    pub fn received_at(&self) -> Instant {
+        self.received_at
+    }
+
+    fn head(&self) -> &str {
+        match self.text.find("\r\n\r\n") {
+            Some(end) => &self.text[..end],
+            None => &self.text,
+        }
+    }
+}
+
+// Reads a whole request, since a body can arrive after the headers.
+fn read_request<R: Read>(stream: &mut R) -> ReceivedRequest {
+    let mut request = Vec::new();
+    let mut buf = [0; 4096];
+    let mut received_at = None;
+    loop {
+        let read = stream.read(&mut buf).unwrap_or(0);
+        received_at = received_at.or_else(|| Some(Instant::now()));
+        request.extend_from_slice(&buf[..read]);
+        let received = ReceivedRequest {
+            text: String::from_utf8_lossy(&request).into_owned(),
+            received_at: received_at.unwrap(),
+        };
+        let complete = received.text.contains("\r\n\r\n")
+            && received.body().len()
+                >= received
+                    .header("content-length")
+                    .and_then(|length| length.parse().ok())
+                    .unwrap_or(0);
+        if read == 0 || complete {
+            return received;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_server_tests {
+    use super::*;
+    use reqwest;
+
+    #[test]
+    fn it_answers_with_each_response_in_turn() {
+        let server = TestServer::new(vec![
+            TestResponse::new("404 Not Found"),
+            TestResponse::json("{}").with_header("Latest-Ledger", "7"),
+        ]);
+        let http = reqwest::Client::new();
+        let missing = http.get(&format!("{}/a", server.uri())).send().unwrap();
+        assert_eq!(missing.status().as_u16(), 404);
+        let mut found = http
+            .post(&format!("{}/b?c=d", server.uri()))
+            .header("X-Test", "yes")
+            .body("hello")
+            .send()
+            .unwrap();
+        assert_eq!(found.headers()["latest-ledger"], "7");
+        assert_eq!(found.text().unwrap(), "{}");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].request_line(), "GET /a HTTP/1.1");
+        assert_eq!(requests[1].method(), "POST");
+        assert_eq!(requests[1].path(), "/b?c=d");
+        assert_eq!(requests[1].header("x-test"), Some("yes"));
+        assert_eq!(requests[1].body(), "hello");
+        assert!(requests[1].received_at() >= requests[0].received_at());
+    }
+
+    #[test]
+    fn it_gives_a_uri_nothing_listens_on() {
+        let uri = TestServer::unreachable();
+        assert!(reqwest::Client::new().get(&uri).send().is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use endpoint::ParseDirectionError;
 use http;
-use resources::{ParseAmountError, ParseAssetIdentifierError};
+use resources::{ParseAccountIdError, ParseAmountError, ParseAssetIdentifierError};
 use std::str::FromStr;
 use std::{self, fmt};
 
@@ -148,6 +148,7 @@ pub enum ErrorKind {
     ParseIntError(std::num::ParseIntError),
     ParseDirectionError(ParseDirectionError),
     ParseAmountError(ParseAmountError),
+    ParseAccountIdError(ParseAccountIdError),
     ParseAssetIdentifierError(ParseAssetIdentifierError),
     InvalidPath,
 }
@@ -168,6 +169,14 @@ impl From<ParseAmountError> for Error {
     }
 }
 
+impl From<ParseAccountIdError> for Error {
+    fn from(inner: ParseAccountIdError) -> Error {
+        Error {
+            kind: ErrorKind::ParseAccountIdError(inner),
+        }
+    }
+}
+
 impl From<ParseAssetIdentifierError> for Error {
     fn from(inner: ParseAssetIdentifierError) -> Error {
         Error {
@@ -215,6 +224,7 @@ impl ::std::error::Error for Error {
             ErrorKind::ParseIntError(ref inner) => inner.description(),
             ErrorKind::ParseDirectionError(ref inner) => inner.description(),
             ErrorKind::ParseAmountError(_) => "An error occured while parsing amount",
+            ErrorKind::ParseAccountIdError(_) => "An error occured while parsing account id",
             ErrorKind::ParseAssetIdentifierError(_) => "An error occured while parsing asset",
             ErrorKind::InvalidPath => "The path of the uri is invalid in some way",
         }
@@ -234,6 +244,7 @@ impl fmt::Display for Error {
             ErrorKind::ParseError(ref inner) => format!("{}", inner),
             ErrorKind::ParseIntError(ref inner) => format!("{}", inner),
             ErrorKind::ParseAmountError(ref inner) => format!("{:?}", inner),
+            ErrorKind::ParseAccountIdError(ref inner) => format!("{}", inner),
             ErrorKind::ParseAssetIdentifierError(ref inner) => format!("{}", inner),
             ErrorKind::ParseDirectionError(ref inner) => format!("{}", inner),
         };
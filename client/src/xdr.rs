@@ -0,0 +1,509 @@
+//! Reads and writes the XDR encoding that stellar uses for everything that
+//! goes on the ledger: transactions, their results and meta, ledger entries
+//! and soroban contract values.
+//!
+//! This module only knows the primitives and the handful of structures that
+//! are everywhere, such as account ids and assets. The resources, history
+//! and soroban modules build the structures they need on top of it.
+use base64;
+use resources::AssetIdentifier;
+use std::borrow::Cow;
+use std::error::Error as StdError;
+use std::fmt;
+use strkey;
+
+/// An error raised when XDR could not be decoded or encoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XdrError {
+    /// The string was not valid base64.
+    Base64,
+    /// The XDR ended before the structure was complete.
+    UnexpectedEnd,
+    /// There were bytes left over after the structure was complete.
+    TrailingBytes,
+    /// A union discriminant or enum value that this library does not know.
+    UnknownDiscriminant(&'static str, i32),
+    /// A string that was not valid utf-8.
+    InvalidUtf8,
+    /// An address that was not a valid `G...` or `C...` strkey.
+    InvalidAddress,
+    /// A hash that was not 64 hex characters.
+    InvalidHash,
+    /// An asset whose code or issuer is not valid.
+    InvalidAsset,
+    /// An asset code that was empty or too long for its asset type.
+    InvalidAssetCode,
+    /// A structure that this library can't decode or encode yet.
+    Unsupported(&'static str),
+    /// A price whose numerator or denominator doesn't fit in 32 bits, even
+    /// once the ratio is reduced.
+    InvalidPrice,
+    /// Values, such as contract vectors or claim predicates, nested inside
+    /// each other more than 500 levels deep.
+    TooDeep,
+}
+
+impl fmt::Display for XdrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            XdrError::UnknownDiscriminant(name, value) => {
+                write!(f, "Unknown {} discriminant {}", name, value)
+            }
+            XdrError::Unsupported(name) => write!(f, "{} is not supported", name),
+            _ => f.write_str(self.description()),
+        }
+    }
+}
+
+impl StdError for XdrError {
+    fn description(&self) -> &str {
+        match *self {
+            XdrError::Base64 => "The xdr was not valid base64",
+            XdrError::UnexpectedEnd => "The xdr ended unexpectedly",
+            XdrError::TrailingBytes => "The xdr had unexpected trailing bytes",
+            XdrError::UnknownDiscriminant(_, _) => "The xdr had an unknown discriminant",
+            XdrError::InvalidUtf8 => "The xdr had a string that was not valid utf-8",
+            XdrError::InvalidAddress => "The address was not a valid strkey",
+            XdrError::InvalidHash => "The hash was not 64 hex characters",
+            XdrError::InvalidAsset => "The xdr had an invalid asset",
+            XdrError::InvalidAssetCode => "The asset code was empty or too long",
+            XdrError::Unsupported(_) => "The xdr had a structure that isn't supported",
+            XdrError::InvalidPrice => "The price doesn't fit in 32 bits",
+            XdrError::TooDeep => "The xdr nested values too deeply",
+        }
+    }
+}
+
+pub(crate) type Result<T> = ::std::result::Result<T, XdrError>;
+
+/// How deep values that hold values of their own, such as contract vectors
+/// and claim predicates, may nest before decoding them fails. Each level is
+/// a call, so without a limit a few kilobytes of XDR could overflow the
+/// stack.
+pub(crate) const MAX_DEPTH: usize = 500;
+
+/// Reads XDR from the front of a buffer, which it either borrows or, when
+/// decoded from base64, owns.
+pub(crate) struct Reader<'a> {
+    bytes: Cow<'a, [u8]>,
+    position: usize,
+    depth: usize,
+}
+
+impl Reader<'static> {
+    pub fn from_base64(xdr: &str) -> Result<Reader<'static>> {
+        let bytes = base64::decode(xdr).map_err(|_| XdrError::Base64)?;
+        Ok(Reader {
+            bytes: Cow::Owned(bytes),
+            position: 0,
+            depth: 0,
+        })
+    }
+}
+
+impl<'a> Reader<'a> {
+    #[cfg(any(feature = "history", test))]
+    pub fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader {
+            bytes: Cow::Borrowed(bytes),
+            position: 0,
+            depth: 0,
+        }
+    }
+
+    pub fn take(&mut self, len: usize) -> Result<&[u8]> {
+        if self.remaining() < len {
+            return Err(XdrError::UnexpectedEnd);
+        }
+        let start = self.position;
+        self.position += len;
+        Ok(&self.bytes[start..self.position])
+    }
+
+    /// How many bytes have been read so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The bytes read since `start`, a position from earlier.
+    pub fn since(&self, start: usize) -> &[u8] {
+        &self.bytes[start..self.position]
+    }
+
+    /// How many bytes are left to read.
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    pub fn u32(&mut self) -> Result<u32> {
+        let b = self.take(4)?;
+        Ok((u32::from(b[0]) << 24)
+            | (u32::from(b[1]) << 16)
+            | (u32::from(b[2]) << 8)
+            | u32::from(b[3]))
+    }
+
+    pub fn i32(&mut self) -> Result<i32> {
+        Ok(self.u32()? as i32)
+    }
+
+    pub fn u64(&mut self) -> Result<u64> {
+        Ok((u64::from(self.u32()?) << 32) | u64::from(self.u32()?))
+    }
+
+    pub fn i64(&mut self) -> Result<i64> {
+        Ok(self.u64()? as i64)
+    }
+
+    pub fn bool(&mut self) -> Result<bool> {
+        match self.i32()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(XdrError::UnknownDiscriminant("bool", other)),
+        }
+    }
+
+    /// Fixed length opaque data, such as a key or a hash.
+    pub fn fixed<T: Default + AsMut<[u8]>>(&mut self) -> Result<T> {
+        let mut value = T::default();
+        let len = value.as_mut().len();
+        value.as_mut().copy_from_slice(self.take(len)?);
+        Ok(value)
+    }
+
+    /// Variable length opaque data, padded to a multiple of four bytes.
+    pub fn opaque(&mut self) -> Result<Vec<u8>> {
+        let len = self.u32()? as usize;
+        let value = self.take(len)?.to_vec();
+        self.take((4 - len % 4) % 4)?;
+        Ok(value)
+    }
+
+    /// A `string`, which has to be valid utf-8.
+    pub fn string(&mut self) -> Result<String> {
+        String::from_utf8(self.opaque()?).map_err(|_| XdrError::InvalidUtf8)
+    }
+
+    /// A `string` where invalid utf-8 is replaced rather than rejected, for
+    /// text such as memos that the network never checked.
+    pub fn string_lossy(&mut self) -> Result<String> {
+        Ok(String::from_utf8_lossy(&self.opaque()?).into_owned())
+    }
+
+    /// A 32 byte hash, as lowercase hex.
+    #[cfg(any(feature = "soroban", feature = "history"))]
+    pub fn hash(&mut self) -> Result<String> {
+        Ok(hex(self.take(32)?))
+    }
+
+    /// An optional value, which is a bool then the value if it is there.
+    pub fn optional<T, F>(&mut self, read: F) -> Result<Option<T>>
+    where
+        F: FnOnce(&mut Reader<'a>) -> Result<T>,
+    {
+        if self.bool()? {
+            read(self).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Reads `count` then that many of something.
+    pub fn vec<T, F>(&mut self, mut read: F) -> Result<Vec<T>>
+    where
+        F: FnMut(&mut Reader<'a>) -> Result<T>,
+    {
+        let len = self.u32()? as usize;
+        // Cap the allocation by what is left so a bad length can't exhaust
+        // memory before the read fails.
+        let mut values = Vec::with_capacity(len.min(self.remaining()));
+        for _ in 0..len {
+            values.push(read(self)?);
+        }
+        Ok(values)
+    }
+
+    /// Reads a value that can hold values of its own kind, one level deeper
+    /// than the value being read. Fails with `XdrError::TooDeep` past
+    /// `MAX_DEPTH` levels.
+    pub fn nested<T, F>(&mut self, read: F) -> Result<T>
+    where
+        F: FnOnce(&mut Reader<'a>) -> Result<T>,
+    {
+        if self.depth >= MAX_DEPTH {
+            return Err(XdrError::TooDeep);
+        }
+        self.depth += 1;
+        let value = read(self);
+        self.depth -= 1;
+        value
+    }
+
+    /// A `PublicKey`, which is always an ed25519 key.
+    pub fn account_id(&mut self) -> Result<[u8; 32]> {
+        match self.i32()? {
+            0 => self.fixed(),
+            other => Err(XdrError::UnknownDiscriminant("PublicKey", other)),
+        }
+    }
+
+    /// An `Asset`.
+    pub fn asset(&mut self) -> Result<AssetIdentifier> {
+        match self.i32()? {
+            0 => Ok(AssetIdentifier::native()),
+            1 => self.issued_asset(4),
+            2 => self.issued_asset(12),
+            other => Err(XdrError::UnknownDiscriminant("Asset", other)),
+        }
+    }
+
+    /// The code, padded out to `len`, and issuer of an issued asset, which
+    /// is an alphanum 4 asset when `len` is 4 and an alphanum 12 one
+    /// otherwise.
+    pub fn issued_asset(&mut self, len: usize) -> Result<AssetIdentifier> {
+        let code = asset_code(self.take(len)?)?;
+        let issuer = strkey::encode(strkey::ACCOUNT, &self.account_id()?);
+        let asset = if len == 4 {
+            AssetIdentifier::alphanum4(&code, &issuer)
+        } else {
+            AssetIdentifier::alphanum12(&code, &issuer)
+        };
+        asset.map_err(|_| XdrError::InvalidAsset)
+    }
+
+    /// An empty extension point, which only has a version 0.
+    pub fn extension_point(&mut self) -> Result<()> {
+        match self.i32()? {
+            0 => Ok(()),
+            other => Err(XdrError::UnknownDiscriminant("ExtensionPoint", other)),
+        }
+    }
+}
+
+/// Writes XDR to the end of a buffer.
+pub(crate) struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    pub fn new() -> Writer {
+        Writer { bytes: Vec::new() }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Appends bytes that are already XDR encoded.
+    pub fn raw(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        self.raw(&[
+            (value >> 24) as u8,
+            (value >> 16) as u8,
+            (value >> 8) as u8,
+            value as u8,
+        ]);
+    }
+
+    pub fn i32(&mut self, value: i32) {
+        self.u32(value as u32);
+    }
+
+    pub fn u64(&mut self, value: u64) {
+        self.u32((value >> 32) as u32);
+        self.u32(value as u32);
+    }
+
+    pub fn i64(&mut self, value: i64) {
+        self.u64(value as u64);
+    }
+
+    pub fn bool(&mut self, value: bool) {
+        self.i32(value as i32);
+    }
+
+    /// Variable length opaque data, padded to a multiple of four bytes.
+    pub fn opaque(&mut self, value: &[u8]) {
+        self.u32(value.len() as u32);
+        self.raw(value);
+        let padding = (4 - value.len() % 4) % 4;
+        self.raw(&[0; 3][..padding]);
+    }
+
+    /// A 32 byte hash given as hex.
+    pub fn hash(&mut self, hex: &str) -> Result<()> {
+        if hex.len() != 64 || !hex.is_ascii() {
+            return Err(XdrError::InvalidHash);
+        }
+        for i in 0..32 {
+            let byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+                .map_err(|_| XdrError::InvalidHash)?;
+            self.raw(&[byte]);
+        }
+        Ok(())
+    }
+
+    /// A `G...` account id as a `PublicKey` or `MuxedAccount`, which share
+    /// their encoding for plain ed25519 keys.
+    pub fn account_id(&mut self, account: &str) -> Result<()> {
+        let key = strkey::decode(strkey::ACCOUNT, account).ok_or(XdrError::InvalidAddress)?;
+        self.i32(0);
+        self.raw(&key);
+        Ok(())
+    }
+
+    /// An asset code, padded out to `len` with zeros.
+    pub fn asset_code(&mut self, code: &str, len: usize) -> Result<()> {
+        if code.is_empty() || code.len() > len {
+            return Err(XdrError::InvalidAssetCode);
+        }
+        self.raw(code.as_bytes());
+        self.raw(&[0; 12][..len - code.len()]);
+        Ok(())
+    }
+
+    /// A flag for whether `value` is there, then the value itself.
+    pub fn optional<T, F>(&mut self, value: Option<&T>, write: F) -> Result<()>
+    where
+        F: FnOnce(&T, &mut Writer) -> Result<()>,
+    {
+        match value {
+            Some(value) => {
+                self.bool(true);
+                write(value, self)
+            }
+            None => {
+                self.bool(false);
+                Ok(())
+            }
+        }
+    }
+
+    /// Writes `count` then each of `values`.
+    pub fn vec<T, F>(&mut self, values: &[T], mut write: F) -> Result<()>
+    where
+        F: FnMut(&T, &mut Writer) -> Result<()>,
+    {
+        self.u32(values.len() as u32);
+        for value in values {
+            write(value, self)?;
+        }
+        Ok(())
+    }
+
+    /// An `Asset`.
+    pub fn asset(&mut self, asset: &AssetIdentifier) -> Result<()> {
+        let (asset_type, code, len) = match *asset {
+            AssetIdentifier::Native => {
+                self.i32(0);
+                return Ok(());
+            }
+            AssetIdentifier::CreditAlphanum4(ref id) => (1, id.code(), 4),
+            AssetIdentifier::CreditAlphanum12(ref id) => (2, id.code(), 12),
+        };
+        self.i32(asset_type);
+        self.asset_code(code, len)?;
+        match asset.issuer() {
+            Some(issuer) => self.account_id(issuer.as_str()),
+            None => Err(XdrError::InvalidAsset),
+        }
+    }
+}
+
+/// Asset codes are padded out to their fixed length with zeros.
+pub(crate) fn asset_code(bytes: &[u8]) -> Result<String> {
+    let len = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8(bytes[..len].to_vec()).map_err(|_| XdrError::InvalidUtf8)
+}
+
+/// Bytes as lowercase hex, the way horizon shows hashes.
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod xdr_tests {
+    use super::*;
+
+    #[test]
+    fn it_round_trips_primitives() {
+        let mut w = Writer::new();
+        w.u32(7);
+        w.i64(-2);
+        w.bool(true);
+        w.opaque(b"hello");
+        let bytes = w.into_bytes();
+        assert_eq!(bytes.len(), 4 + 8 + 4 + 4 + 8);
+
+        let mut r = Reader::new(&bytes);
+        assert_eq!(r.u32().unwrap(), 7);
+        assert_eq!(r.i64().unwrap(), -2);
+        assert!(r.bool().unwrap());
+        assert_eq!(r.string().unwrap(), "hello");
+        assert_eq!(r.remaining(), 0);
+        assert_eq!(r.u32().unwrap_err(), XdrError::UnexpectedEnd);
+    }
+
+    #[test]
+    fn it_round_trips_assets() {
+        const ISSUER: &str = "GDWZCOEQRODFCH6ISYQPWY67L3ULLWS5ISXYYL5GH43W7YFMTLB65PYM";
+        let assets = vec![
+            AssetIdentifier::native(),
+            AssetIdentifier::alphanum4("USD", ISSUER).unwrap(),
+            AssetIdentifier::alphanum12("LONGCODE", ISSUER).unwrap(),
+        ];
+        let mut w = Writer::new();
+        for asset in &assets {
+            w.asset(asset).unwrap();
+        }
+        let bytes = w.into_bytes();
+        let mut r = Reader::new(&bytes);
+        for asset in assets {
+            assert_eq!(r.asset().unwrap(), asset);
+        }
+    }
+
+    #[test]
+    fn it_fails_at_the_end_of_the_buffer() {
+        let mut r = Reader::new(&[0, 0, 0, 1, 0]);
+        assert_eq!(r.u32().unwrap(), 1);
+        assert_eq!(r.remaining(), 1);
+        assert_eq!(r.u32().unwrap_err(), XdrError::UnexpectedEnd);
+    }
+
+    #[test]
+    fn it_limits_how_deep_values_nest() {
+        fn nest(r: &mut Reader) -> Result<usize> {
+            r.nested(|r| {
+                if r.bool()? {
+                    nest(r).map(|d| d + 1)
+                } else {
+                    Ok(1)
+                }
+            })
+        }
+        let xdr = |depth: usize| {
+            let mut w = Writer::new();
+            for _ in 1..depth {
+                w.bool(true);
+            }
+            w.bool(false);
+            w.into_bytes()
+        };
+        assert_eq!(nest(&mut Reader::new(&xdr(MAX_DEPTH))), Ok(MAX_DEPTH));
+        assert_eq!(
+            nest(&mut Reader::new(&xdr(MAX_DEPTH + 1))),
+            Err(XdrError::TooDeep)
+        );
+    }
+
+    #[test]
+    fn it_reads_base64() {
+        let mut r = Reader::from_base64("AAAAAQ==").unwrap();
+        assert_eq!(r.u32().unwrap(), 1);
+        assert!(Reader::from_base64("!!").is_err());
+    }
+}